@@ -206,7 +206,7 @@ fn compute_genesis(
     genesis_path: &Path,
     db_path: &Path,
 ) -> Result<(DbReaderWriter, Waypoint), Error> {
-    let diemdb = DiemDB::open(db_path, false, None, RocksdbConfig::default())
+    let diemdb = DiemDB::open(db_path, false, None, None, RocksdbConfig::default())
         .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(diemdb);
 
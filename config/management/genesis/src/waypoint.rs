@@ -35,7 +35,7 @@ impl CreateWaypoint {
         let genesis = genesis_helper.execute()?;
 
         let path = TempPath::new();
-        let diemdb = DiemDB::open(&path, false, None, RocksdbConfig::default())
+        let diemdb = DiemDB::open(&path, false, None, None, RocksdbConfig::default())
             .map_err(|e| Error::UnexpectedError(e.to_string()))?;
         let db_rw = DbReaderWriter::new(diemdb);
 
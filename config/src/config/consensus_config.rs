@@ -35,10 +35,9 @@ impl Default for ConsensusConfig {
             mempool_txn_pull_timeout_ms: 1000,
             mempool_executed_txn_timeout_ms: 1000,
             round_initial_timeout_ms: 1000,
-            proposer_type: ConsensusProposerType::LeaderReputation(LeaderReputationConfig {
-                active_weights: 99,
-                inactive_weights: 1,
-            }),
+            proposer_type: ConsensusProposerType::LeaderReputation(
+                LeaderReputationConfig::default(),
+            ),
             safety_rules: SafetyRulesConfig::default(),
             sync_only: false,
             mempool_poll_count: 1,
@@ -68,8 +67,45 @@ pub enum ConsensusProposerType {
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
-#[serde(deny_unknown_fields)]
+#[serde(default, deny_unknown_fields)]
 pub struct LeaderReputationConfig {
+    pub heuristic: ReputationHeuristicType,
+    // Weight of the candidates that proposed or voted in the window
     pub active_weights: u64,
+    // Weight of the candidates that neither proposed nor voted in the window
     pub inactive_weights: u64,
+    // Weight of the candidates that failed to propose in too many of their rounds
+    pub failed_weights: u64,
+    // Percentage of failed rounds among the rounds of a candidate above which it is demoted to
+    // failed_weights
+    pub failure_threshold_percent: u32,
+    // Size of the window used to count proposals and failed rounds, as a multiple of the number
+    // of validators
+    pub proposer_window_num_validators_multiplier: usize,
+    // Size of the window used to count votes, as a multiple of the number of validators
+    pub voter_window_num_validators_multiplier: usize,
+}
+
+impl Default for LeaderReputationConfig {
+    fn default() -> LeaderReputationConfig {
+        LeaderReputationConfig {
+            heuristic: ReputationHeuristicType::ActiveInactive,
+            active_weights: 99,
+            inactive_weights: 1,
+            failed_weights: 1,
+            failure_threshold_percent: 10,
+            proposer_window_num_validators_multiplier: 10,
+            voter_window_num_validators_multiplier: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReputationHeuristicType {
+    // Candidates that proposed or voted in the voter window are active, others are inactive
+    ActiveInactive,
+    // Like ActiveInactive, but candidates failing to propose in too many of their rounds within
+    // the proposer window are demoted to failed
+    ProposerAndVoter,
 }
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{
+    config::{invariant, Error},
+    utils,
+};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -14,6 +17,7 @@ pub struct JsonRpcConfig {
     pub content_length_limit: usize,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    pub stream_rpc: StreamConfig,
 }
 
 pub const DEFAULT_JSON_RPC_ADDRESS: &str = "127.0.0.1";
@@ -21,6 +25,9 @@ pub const DEFAULT_JSON_RPC_PORT: u16 = 8080;
 pub const DEFAULT_BATCH_SIZE_LIMIT: u16 = 20;
 pub const DEFAULT_PAGE_SIZE_LIMIT: u16 = 1000;
 pub const DEFAULT_CONTENT_LENGTH_LIMIT: usize = 32 * 1024; // 32kb
+pub const DEFAULT_STREAM_FETCH_SIZE: u16 = 100;
+pub const DEFAULT_STREAM_SEND_QUEUE_SIZE: usize = 100;
+pub const DEFAULT_STREAM_SUBSCRIPTIONS_PER_CONNECTION_LIMIT: usize = 10;
 
 impl Default for JsonRpcConfig {
     fn default() -> JsonRpcConfig {
@@ -33,6 +40,7 @@ impl Default for JsonRpcConfig {
             content_length_limit: DEFAULT_CONTENT_LENGTH_LIMIT,
             tls_cert_path: None,
             tls_key_path: None,
+            stream_rpc: StreamConfig::default(),
         }
     }
 }
@@ -42,3 +50,37 @@ impl JsonRpcConfig {
         self.address.set_port(utils::get_available_port());
     }
 }
+
+/// Configuration of the WebSocket endpoint used to subscribe to new transactions and events.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StreamConfig {
+    pub enabled: bool,
+    /// Maximum number of transactions or events read from the DB at once for a subscription
+    pub fetch_size: u16,
+    /// Number of messages buffered for a connection before subscriptions wait for the client
+    pub send_queue_size: usize,
+    pub subscriptions_per_connection_limit: usize,
+}
+
+impl Default for StreamConfig {
+    fn default() -> StreamConfig {
+        StreamConfig {
+            enabled: false,
+            fetch_size: DEFAULT_STREAM_FETCH_SIZE,
+            send_queue_size: DEFAULT_STREAM_SEND_QUEUE_SIZE,
+            subscriptions_per_connection_limit: DEFAULT_STREAM_SUBSCRIPTIONS_PER_CONNECTION_LIMIT,
+        }
+    }
+}
+
+impl StreamConfig {
+    /// Checks that subscriptions can make progress: with a `fetch_size` of 0 they would poll the
+    /// DB forever without ever reading anything.
+    pub fn verify(&self) -> Result<(), Error> {
+        invariant(
+            self.fetch_size > 0,
+            "The fetch_size of stream_rpc must be at least 1".into(),
+        )
+    }
+}
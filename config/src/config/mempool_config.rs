@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -10,8 +11,20 @@ pub struct MempoolConfig {
     pub capacity_per_user: usize,
     // number of failovers to broadcast to when the primary network is alive
     pub default_failovers: usize,
+    // if enabled, a full mempool evicts its lowest ranked ready transactions to make room for
+    // higher ranked ready ones, instead of only evicting from the parking lot
+    pub evict_ready_txns_when_full: bool,
+    // interval at which the journal is rewritten from the current mempool contents
+    pub journal_compaction_interval_secs: u64,
+    pub journal_dir: PathBuf,
+    // if enabled, accepted transactions are journaled to disk and reloaded on restart
+    pub journal_enabled: bool,
     pub max_broadcasts_per_peer: usize,
     pub mempool_snapshot_interval_secs: u64,
+    // if enabled, a pending transaction can be replaced by any transaction with the same sender
+    // and sequence number whose gas price is at least `replace_by_fee_min_bump_pct` percent higher
+    pub replace_by_fee_enabled: bool,
+    pub replace_by_fee_min_bump_pct: u64,
     pub shared_mempool_ack_timeout_ms: u64,
     pub shared_mempool_backoff_interval_ms: u64,
     pub shared_mempool_batch_size: usize,
@@ -19,6 +32,8 @@ pub struct MempoolConfig {
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    #[serde(skip)]
+    data_dir: PathBuf,
 }
 
 impl Default for MempoolConfig {
@@ -34,8 +49,29 @@ impl Default for MempoolConfig {
             capacity: 1_000_000,
             capacity_per_user: 100,
             default_failovers: 3,
+            replace_by_fee_enabled: false,
+            replace_by_fee_min_bump_pct: 10,
+            evict_ready_txns_when_full: false,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            journal_enabled: false,
+            journal_dir: PathBuf::from("mempool"),
+            journal_compaction_interval_secs: 600,
+            data_dir: PathBuf::from("/opt/diem/data"),
         }
     }
 }
+
+impl MempoolConfig {
+    pub fn journal_dir(&self) -> PathBuf {
+        if self.journal_dir.is_relative() {
+            self.data_dir.join(&self.journal_dir)
+        } else {
+            self.journal_dir.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
@@ -217,6 +217,7 @@ impl NodeConfig {
         self.base.data_dir = data_dir.clone();
         self.consensus.set_data_dir(data_dir.clone());
         self.execution.set_data_dir(data_dir.clone());
+        self.mempool.set_data_dir(data_dir.clone());
         self.metrics.set_data_dir(data_dir.clone());
         self.storage.set_data_dir(data_dir);
     }
@@ -230,6 +231,7 @@ impl NodeConfig {
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
 
+        config.json_rpc.stream_rpc.verify()?;
         let mut config = config.validate_network_configs()?;
         config.set_data_dir(config.data_dir().to_path_buf());
         Ok(config)
@@ -461,4 +463,37 @@ mod test {
         SafetyRulesConfig::parse(&contents)
             .unwrap_or_else(|e| panic!("Error in safety_rules.yaml: {}", e));
     }
+
+    #[test]
+    fn verify_discovery_methods() {
+        let file = |path: &str| {
+            DiscoveryMethod::File(FileDiscovery {
+                path: path.into(),
+                interval_secs: 10,
+            })
+        };
+        let mut network = NetworkConfig::network_with_id(NetworkId::Public);
+        network.discovery_method = file("peers_a.yaml");
+        network.discovery_methods = vec![DiscoveryMethod::Onchain];
+        network.verify_discovery_methods().unwrap();
+
+        // Two sources of the same kind would discard each other's peers
+        network.discovery_methods.push(file("peers_b.yaml"));
+        assert!(matches!(
+            network.load_fullnode_network(),
+            Err(Error::InvariantViolation(_))
+        ));
+    }
+
+    #[test]
+    fn verify_stream_config() {
+        let mut stream_config = StreamConfig::default();
+        stream_config.verify().unwrap();
+
+        stream_config.fetch_size = 0;
+        assert!(matches!(
+            stream_config.verify(),
+            Err(Error::InvariantViolation(_))
+        ));
+    }
 }
@@ -20,8 +20,10 @@ use rand::{
 use serde::{Deserialize, Serialize};
 use short_hex_str::AsShortHexStr;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
+    mem,
+    path::PathBuf,
     string::ToString,
 };
 
@@ -40,9 +42,12 @@ pub const MAX_CONNECTION_DELAY_MS: u64 = 60_000; /* 1 minute */
 pub const MAX_FULLNODE_OUTBOUND_CONNECTIONS: usize = 3;
 pub const MAX_INBOUND_CONNECTIONS: usize = 100;
 pub const MAX_FRAME_SIZE: usize = 8 * 1024 * 1024; /* 8 MiB */
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const DISCOVERY_INTERVAL_SECS: u64 = 60;
+pub const MIN_COMPRESSION_SIZE: usize = 1024; /* 1 KiB */
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -61,6 +66,9 @@ pub struct NetworkConfig {
     // `DiscoveryMethod::None` disables discovery and dialing out (unless you have
     // seed peers configured).
     pub discovery_method: DiscoveryMethod,
+    // Additional protocols to discover peers on this network, used alongside `discovery_method`.
+    // Each kind of protocol can only be configured once.
+    pub discovery_methods: Vec<DiscoveryMethod>,
     pub identity: Identity,
     // TODO: Add support for multiple listen/advertised addresses in config.
     // The address that this node is listening on for new connections.
@@ -79,6 +87,9 @@ pub struct NetworkConfig {
     pub seeds: PeerSet,
     // The maximum size of an inbound or outbound request frame
     pub max_frame_size: usize,
+    // The maximum size of an inbound or outbound message, messages larger than a frame being
+    // streamed as fragments
+    pub max_message_size: usize,
     // Enables proxy protocol on incoming connections to get original source addresses
     pub enable_proxy_protocol: bool,
     // Interval to send healthcheck pings to peers
@@ -95,6 +106,10 @@ pub struct NetworkConfig {
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Compression of the application messages, negotiated with each peer during the handshake
+    pub compression: CompressionConfig,
+    // Priorities of the outbound application messages on each connection
+    pub priority: PriorityConfig,
 }
 
 impl Default for NetworkConfig {
@@ -107,6 +122,7 @@ impl NetworkConfig {
     pub fn network_with_id(network_id: NetworkId) -> NetworkConfig {
         let mut config = Self {
             discovery_method: DiscoveryMethod::None,
+            discovery_methods: Vec::new(),
             identity: Identity::None,
             listen_address: "/ip4/0.0.0.0/tcp/6180".parse().unwrap(),
             mutual_authentication: false,
@@ -115,6 +131,7 @@ impl NetworkConfig {
             seed_addrs: HashMap::new(),
             seeds: PeerSet::default(),
             max_frame_size: MAX_FRAME_SIZE,
+            max_message_size: MAX_MESSAGE_SIZE,
             enable_proxy_protocol: false,
             max_connection_delay_ms: MAX_CONNECTION_DELAY_MS,
             connectivity_check_interval_ms: CONNECTIVITY_CHECK_INTERVAL_MS,
@@ -128,6 +145,8 @@ impl NetworkConfig {
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            compression: CompressionConfig::default(),
+            priority: PriorityConfig::default(),
         };
         config.prepare_identity();
         config
@@ -135,6 +154,14 @@ impl NetworkConfig {
 }
 
 impl NetworkConfig {
+    /// All the discovery methods configured for this network, in order of configuration
+    pub fn discovery_methods(&self) -> Vec<&DiscoveryMethod> {
+        std::iter::once(&self.discovery_method)
+            .chain(self.discovery_methods.iter())
+            .filter(|method| **method != DiscoveryMethod::None)
+            .collect()
+    }
+
     pub fn identity_key(&self) -> x25519::PrivateKey {
         let key = match &self.identity {
             Identity::FromConfig(config) => Some(config.key.clone().key),
@@ -191,6 +218,7 @@ impl NetworkConfig {
                 .ok_or_else(|| Error::InvariantViolation("No local IP".to_string()))?;
         }
 
+        self.verify_discovery_methods()?;
         self.prepare_identity();
         Ok(())
     }
@@ -258,6 +286,22 @@ impl NetworkConfig {
         )
     }
 
+    // Verifies that no kind of discovery method is configured twice: the peers discovered by a
+    // method replace the ones previously discovered under the same `DiscoverySource`, which is
+    // shared by all the methods of a kind.
+    pub fn verify_discovery_methods(&self) -> Result<(), Error> {
+        let methods = self.discovery_methods();
+        for (idx, method) in methods.iter().enumerate() {
+            crate::config::invariant(
+                methods[..idx]
+                    .iter()
+                    .all(|other| mem::discriminant(*other) != mem::discriminant(*method)),
+                format!("Discovery method configured more than once: {:?}", method),
+            )?;
+        }
+        Ok(())
+    }
+
     // Verifies both the `seed_addrs` and `seeds` before they're merged
     pub fn verify_seeds(&self) -> Result<(), Error> {
         for (peer_id, addrs) in self.seed_addrs.iter() {
@@ -285,9 +329,53 @@ impl NetworkConfig {
 #[serde(rename_all = "snake_case")]
 pub enum DiscoveryMethod {
     Onchain,
+    File(FileDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
+/// Discovers peers from a local file in the `PeerSet` format of `NetworkConfig::seeds`, which is
+/// reloaded whenever it changes.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileDiscovery {
+    pub path: PathBuf,
+    // How often to check the file for changes
+    #[serde(default = "FileDiscovery::default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl FileDiscovery {
+    fn default_interval_secs() -> u64 {
+        DISCOVERY_INTERVAL_SECS
+    }
+}
+
+/// Discovers peers by periodically resolving the DNS records of a domain. TXT records of the
+/// domain are full `NetworkAddress`es including their `ln-noise-ik` key, SRV records of the domain
+/// point to hosts whose TXT record is their x25519 network public key.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DnsDiscovery {
+    pub domain: String,
+    // How often to resolve the records
+    #[serde(default = "DnsDiscovery::default_interval_secs")]
+    pub interval_secs: u64,
+    // The role given to the discovered peers
+    #[serde(default = "DnsDiscovery::default_role")]
+    pub role: PeerRole,
+}
+
+impl DnsDiscovery {
+    fn default_interval_secs() -> u64 {
+        DISCOVERY_INTERVAL_SECS
+    }
+
+    fn default_role() -> PeerRole {
+        PeerRole::Upstream
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Identity {
@@ -352,6 +440,105 @@ impl Default for RateLimitConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionConfig {
+    /// Names of the application protocols to compress, e.g. "StateSyncDirectSend". A protocol is
+    /// only compressed with peers which also enable compression for it.
+    pub protocols: Vec<String>,
+    /// Messages smaller than this many bytes are sent uncompressed
+    pub min_compression_size: usize,
+    /// Maximum size of a decompressed message, larger messages are dropped
+    pub max_decompressed_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            protocols: Vec::new(),
+            min_compression_size: MIN_COMPRESSION_SIZE,
+            max_decompressed_size: MAX_MESSAGE_SIZE,
+        }
+    }
+}
+
+/// Priority class of outbound messages, from the highest to the lowest priority.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum PriorityClass {
+    High = 0,
+    Normal,
+    Low,
+}
+
+impl PriorityClass {
+    pub fn all() -> &'static [PriorityClass] {
+        &[
+            PriorityClass::High,
+            PriorityClass::Normal,
+            PriorityClass::Low,
+        ]
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PriorityClass::High => "high",
+            PriorityClass::Normal => "normal",
+            PriorityClass::Low => "low",
+        }
+    }
+}
+
+impl Default for PriorityClass {
+    fn default() -> Self {
+        PriorityClass::Normal
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityConfig {
+    /// Priority class of the messages of each application protocol, by protocol name, e.g.
+    /// "ConsensusDirectSend". Protocols which aren't listed get the normal priority.
+    pub protocols: BTreeMap<String, PriorityClass>,
+    /// Number of messages of each class sent in turn while several classes have messages
+    /// pending, i.e. the share of the connection each class gets when it is saturated
+    pub high_weight: u32,
+    pub normal_weight: u32,
+    pub low_weight: u32,
+}
+
+impl PriorityConfig {
+    pub fn weight(&self, class: PriorityClass) -> u32 {
+        match class {
+            PriorityClass::High => self.high_weight,
+            PriorityClass::Normal => self.normal_weight,
+            PriorityClass::Low => self.low_weight,
+        }
+    }
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        let protocols = [
+            ("ConsensusRpc", PriorityClass::High),
+            ("ConsensusDirectSend", PriorityClass::High),
+            ("HealthCheckerRpc", PriorityClass::High),
+            ("MempoolDirectSend", PriorityClass::Normal),
+            ("DiscoveryDirectSend", PriorityClass::Normal),
+            ("StateSyncDirectSend", PriorityClass::Low),
+        ];
+        Self {
+            protocols: protocols
+                .iter()
+                .map(|(name, class)| (name.to_string(), *class))
+                .collect(),
+            high_weight: 16,
+            normal_weight: 4,
+            low_weight: 1,
+        }
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
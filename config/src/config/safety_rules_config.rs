@@ -26,6 +26,11 @@ pub struct SafetyRulesConfig {
     // Read/Write/Connect networking operation timeout in milliseconds.
     pub network_timeout_ms: u64,
     pub enable_cached_safety_data: bool,
+    // Vote and form quorum certificates on the order of blocks only, then execute the ordered
+    // blocks and collect a second round of signatures on the result before committing. This is
+    // read by both consensus and SafetyRules, so it must be the same in the config of a
+    // SafetyRules process and in the consensus config of its node.
+    pub decoupled_execution: bool,
 }
 
 impl Default for SafetyRulesConfig {
@@ -40,6 +45,7 @@ impl Default for SafetyRulesConfig {
             // Default value of 30 seconds for a timeout
             network_timeout_ms: 30_000,
             enable_cached_safety_data: true,
+            decoupled_execution: false,
         }
     }
 }
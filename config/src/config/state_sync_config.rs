@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncConfig {
+    // The way a node that is behind its waypoint catches up to it
+    pub bootstrapping_mode: BootstrappingMode,
     // Size of chunk to request for state synchronization
     pub chunk_limit: u64,
     // The timeout of the state sync client to process a commit notification (in milliseconds)
@@ -32,6 +34,7 @@ pub struct StateSyncConfig {
 impl Default for StateSyncConfig {
     fn default() -> Self {
         Self {
+            bootstrapping_mode: BootstrappingMode::ExecuteTransactions,
             chunk_limit: 1000,
             client_commit_timeout_ms: 5_000,
             long_poll_timeout_ms: 10_000,
@@ -44,3 +47,13 @@ impl Default for StateSyncConfig {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BootstrappingMode {
+    // Fetch and execute all the transactions up to the waypoint
+    ExecuteTransactions,
+    // Fetch a verified account state snapshot at the waypoint version, then sync transactions
+    // from there
+    ApplyStateSnapshot,
+}
@@ -42,6 +42,9 @@ pub struct StorageConfig {
     /// None disables pruning. The windows is in number of versions, consider system tps
     /// (transaction per second) when calculating proper window.
     pub prune_window: Option<u64>,
+    /// None disables pruning of the ledger history, i.e. transactions, transaction infos and
+    /// events. The window is in number of versions, like `prune_window`.
+    pub ledger_prune_window: Option<u64>,
     #[serde(skip)]
     data_dir: PathBuf,
     /// Read, Write, Connect timeout for network operations in milliseconds
@@ -64,6 +67,9 @@ impl Default for StorageConfig {
             // conservatively safe minimal prune window. It'll take a few Gigabytes of disk space
             // depending on the size of an average account blob.
             prune_window: Some(1_000_000),
+            // Keep the whole ledger history by default, nodes serving historical queries rely on
+            // it.
+            ledger_prune_window: None,
             data_dir: PathBuf::from("/opt/diem/data"),
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
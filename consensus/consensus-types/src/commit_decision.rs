@@ -0,0 +1,57 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::Round;
+use anyhow::Context;
+use diem_types::{ledger_info::LedgerInfoWithSignatures, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display, Formatter};
+
+/// CommitDecision is broadcast by a validator with decoupled execution once it has aggregated a
+/// quorum of commit votes on an executed block, so that the validators that are lagging behind can
+/// commit the block without waiting for the votes themselves.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct CommitDecision {
+    ledger_info: LedgerInfoWithSignatures,
+}
+
+// this is required by structured log
+impl Debug for CommitDecision {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Display for CommitDecision {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "CommitDecision: [{}]", self.ledger_info.ledger_info())
+    }
+}
+
+impl CommitDecision {
+    pub fn new(ledger_info: LedgerInfoWithSignatures) -> Self {
+        Self { ledger_info }
+    }
+
+    /// Return the LedgerInfoWithSignatures of the committed block
+    pub fn ledger_info(&self) -> &LedgerInfoWithSignatures {
+        &self.ledger_info
+    }
+
+    /// Return the epoch of the commit decision
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.ledger_info().epoch()
+    }
+
+    /// Return the round of the committed block
+    pub fn round(&self) -> Round {
+        self.ledger_info.ledger_info().round()
+    }
+
+    /// Verifies that the LedgerInfo carries a quorum of signatures.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.ledger_info
+            .verify_signatures(validator)
+            .context("Failed to verify Commit Decision")
+    }
+}
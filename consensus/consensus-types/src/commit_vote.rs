@@ -0,0 +1,89 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Round};
+use anyhow::Context;
+use diem_crypto::ed25519::Ed25519Signature;
+use diem_types::{ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::fmt::{Debug, Display, Formatter};
+
+/// CommitVote is sent by a validator with decoupled execution once it has executed an ordered
+/// block: it carries the signature on the `LedgerInfo` with the execution result, and a quorum of
+/// them is needed before the block can be committed.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct CommitVote {
+    /// The identity of the voter.
+    author: Author,
+    /// LedgerInfo of the executed block, which is going to be committed in case this vote
+    /// gathers a quorum.
+    ledger_info: LedgerInfo,
+    /// Signature of the LedgerInfo
+    signature: Ed25519Signature,
+}
+
+// this is required by structured log
+impl Debug for CommitVote {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Display for CommitVote {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "CommitVote: [author: {}, {}]",
+            self.author.short_str(),
+            self.ledger_info
+        )
+    }
+}
+
+impl CommitVote {
+    /// Generates a new CommitVote using a signature over the specified ledger_info
+    pub fn new_with_signature(
+        author: Author,
+        ledger_info: LedgerInfo,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            author,
+            ledger_info,
+            signature,
+        }
+    }
+
+    /// Return the author of the commit vote
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    /// Return the LedgerInfo associated with this commit vote
+    pub fn ledger_info(&self) -> &LedgerInfo {
+        &self.ledger_info
+    }
+
+    /// Return the signature of the commit vote
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    /// Return the epoch of the commit vote
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.epoch()
+    }
+
+    /// Return the round of the block the commit vote is for
+    pub fn round(&self) -> Round {
+        self.ledger_info.round()
+    }
+
+    /// Verifies that the signature on the LedgerInfo comes from the author.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(self.author(), &self.ledger_info, &self.signature)
+            .context("Failed to verify Commit Vote")
+    }
+}
@@ -6,6 +6,8 @@
 pub mod block;
 pub mod block_data;
 pub mod block_retrieval;
+pub mod commit_decision;
+pub mod commit_vote;
 pub mod common;
 pub mod epoch_retrieval;
 pub mod executed_block;
@@ -14,6 +16,7 @@ pub mod quorum_cert;
 pub mod safety_data;
 pub mod sync_info;
 pub mod timeout;
+pub mod timeout_2chain;
 pub mod timeout_certificate;
 pub mod vote;
 pub mod vote_data;
@@ -63,9 +63,7 @@ impl ProposalMsg {
 
         let highest_certified_round = std::cmp::max(
             self.proposal.quorum_cert().certified_block().round(),
-            self.sync_info
-                .highest_timeout_certificate()
-                .map_or(0, |tc| tc.round()),
+            self.sync_info.highest_timeout_round(),
         );
         ensure!(
             previous_round == highest_certified_round,
@@ -89,6 +87,9 @@ impl ProposalMsg {
         if let Some(tc) = self.sync_info.highest_timeout_certificate() {
             tc.verify(validator).map_err(|e| format_err!("{:?}", e))?;
         }
+        if let Some(tc) = self.sync_info.highest_2chain_timeout_cert() {
+            tc.verify(validator).map_err(|e| format_err!("{:?}", e))?;
+        }
         // Note that we postpone the verification of SyncInfo until it's being used.
         self.verify_well_formed()
     }
@@ -12,6 +12,12 @@ pub struct SafetyData {
     pub last_voted_round: u64,
     pub preferred_round: u64,
     pub last_vote: Option<Vote>,
+    /// The highest certified block round seen, used by the two-chain timeout rule.
+    #[serde(default)]
+    pub one_chain_round: u64,
+    /// The highest round a timeout was signed for with the two-chain commit rule.
+    #[serde(default)]
+    pub highest_timeout_round: u64,
 }
 
 impl SafetyData {
@@ -26,6 +32,8 @@ impl SafetyData {
             last_voted_round,
             preferred_round,
             last_vote,
+            one_chain_round: 0,
+            highest_timeout_round: 0,
         }
     }
 }
@@ -34,8 +42,12 @@ impl fmt::Display for SafetyData {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "SafetyData: [epoch: {}, last_voted_round: {}, preferred_round: {}]",
-            self.epoch, self.last_voted_round, self.preferred_round
+            "SafetyData: [epoch: {}, last_voted_round: {}, preferred_round: {}, one_chain_round: {}, highest_timeout_round: {}]",
+            self.epoch,
+            self.last_voted_round,
+            self.preferred_round,
+            self.one_chain_round,
+            self.highest_timeout_round
         )
     }
 }
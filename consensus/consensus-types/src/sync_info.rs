@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{common::Round, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate};
+use crate::{
+    common::Round, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate,
+    timeout_certificate::TimeoutCertificate,
+};
 use anyhow::{ensure, Context};
 use diem_types::{block_info::BlockInfo, validator_verifier::ValidatorVerifier};
 use serde::{Deserialize, Serialize};
@@ -16,6 +19,8 @@ pub struct SyncInfo {
     highest_commit_cert: Option<QuorumCert>,
     /// Optional highest timeout certificate if available.
     highest_timeout_cert: Option<TimeoutCertificate>,
+    /// Optional highest timeout certificate with the two-chain commit rule if available.
+    highest_2chain_timeout_cert: Option<TwoChainTimeoutCertificate>,
 }
 
 // this is required by structured log
@@ -31,12 +36,17 @@ impl Display for SyncInfo {
             Some(tc) => format!("{}", tc.round()),
             None => "None".to_string(),
         };
+        let h2tc_repr = match self.highest_2chain_timeout_cert() {
+            Some(tc) => format!("{}", tc.round()),
+            None => "None".to_string(),
+        };
         write!(
             f,
-            "SyncInfo[HQC: {}, HCC: {}, HTC: {}]",
+            "SyncInfo[HQC: {}, HCC: {}, HTC: {}, H2TC: {}]",
             self.highest_certified_round(),
             self.highest_commit_round(),
             htc_repr,
+            h2tc_repr,
         )
     }
 }
@@ -46,6 +56,7 @@ impl SyncInfo {
         highest_quorum_cert: QuorumCert,
         highest_commit_cert: QuorumCert,
         highest_timeout_cert: Option<TimeoutCertificate>,
+        highest_2chain_timeout_cert: Option<TwoChainTimeoutCertificate>,
     ) -> Self {
         let commit_cert = if highest_quorum_cert == highest_commit_cert {
             None
@@ -55,10 +66,13 @@ impl SyncInfo {
         // No need to include HTC if it's lower than HQC
         let highest_timeout_cert = highest_timeout_cert
             .filter(|tc| tc.round() > highest_quorum_cert.certified_block().round());
+        let highest_2chain_timeout_cert = highest_2chain_timeout_cert
+            .filter(|tc| tc.round() > highest_quorum_cert.certified_block().round());
         Self {
             highest_quorum_cert,
             highest_commit_cert: commit_cert,
             highest_timeout_cert,
+            highest_2chain_timeout_cert,
         }
     }
 
@@ -79,13 +93,22 @@ impl SyncInfo {
         self.highest_timeout_cert.as_ref()
     }
 
+    /// Highest timeout certificate with the two-chain commit rule if available
+    pub fn highest_2chain_timeout_cert(&self) -> Option<&TwoChainTimeoutCertificate> {
+        self.highest_2chain_timeout_cert.as_ref()
+    }
+
     pub fn highest_certified_round(&self) -> Round {
         self.highest_quorum_cert.certified_block().round()
     }
 
     pub fn highest_timeout_round(&self) -> Round {
-        self.highest_timeout_certificate()
-            .map_or(0, |tc| tc.round())
+        std::cmp::max(
+            self.highest_timeout_certificate()
+                .map_or(0, |tc| tc.round()),
+            self.highest_2chain_timeout_cert()
+                .map_or(0, |tc| tc.round()),
+        )
     }
 
     pub fn highest_commit_round(&self) -> Round {
@@ -106,12 +129,24 @@ impl SyncInfo {
         if let Some(tc) = &self.highest_timeout_cert {
             ensure!(epoch == tc.epoch(), "Multi epoch in SyncInfo - TC and HQC");
         }
+        if let Some(tc) = &self.highest_2chain_timeout_cert {
+            ensure!(
+                epoch == tc.epoch(),
+                "Multi epoch in SyncInfo - 2-chain TC and HQC"
+            );
+        }
 
         ensure!(
             self.highest_quorum_cert.certified_block().round()
                 >= self.highest_commit_cert().certified_block().round(),
             "HQC has lower round than HCC"
         );
+        if let Some(tc) = &self.highest_2chain_timeout_cert {
+            ensure!(
+                self.highest_certified_round() >= tc.highest_hqc_round(),
+                "HQC has lower round than the highest QC of 2-chain TC"
+            );
+        }
         ensure!(
             *self.highest_commit_cert().commit_info() != BlockInfo::empty(),
             "HCC has no committed block"
@@ -127,6 +162,9 @@ impl SyncInfo {
                 if let Some(tc) = &self.highest_timeout_cert {
                     tc.verify(validator)?;
                 }
+                if let Some(tc) = &self.highest_2chain_timeout_cert {
+                    tc.verify(validator)?;
+                }
                 Ok(())
             })
             .context("Fail to verify SyncInfo")?;
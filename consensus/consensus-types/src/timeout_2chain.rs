@@ -0,0 +1,256 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{Author, Round},
+    quorum_cert::QuorumCert,
+};
+use anyhow::{ensure, Context};
+use diem_crypto::ed25519::Ed25519Signature;
+use diem_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use diem_types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// This structure contains all the information necessary to construct a signature
+/// on the equivalent of a timeout message with the two-chain commit rule, it carries the highest
+/// quorum cert known to the signer.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TwoChainTimeout {
+    /// Epoch number corresponds to the set of validators that are active for this round.
+    epoch: u64,
+    /// The consensus protocol executes proposals (blocks) in rounds, which monotically increase per epoch.
+    round: Round,
+    /// The highest quorum cert the signer has seen.
+    quorum_cert: QuorumCert,
+}
+
+impl TwoChainTimeout {
+    pub fn new(epoch: u64, round: Round, quorum_cert: QuorumCert) -> Self {
+        Self {
+            epoch,
+            round,
+            quorum_cert,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    /// Returns the round of the highest quorum cert known to the signer.
+    pub fn hqc_round(&self) -> Round {
+        self.quorum_cert.certified_block().round()
+    }
+
+    pub fn quorum_cert(&self) -> &QuorumCert {
+        &self.quorum_cert
+    }
+
+    pub fn sign(&self, signer: &ValidatorSigner) -> Ed25519Signature {
+        signer.sign(&self.signing_format())
+    }
+
+    /// The signature only covers the round of the quorum cert, so that a timeout certificate
+    /// doesn't need to carry the quorum cert of every signer.
+    pub fn signing_format(&self) -> TimeoutSigningRepr {
+        TimeoutSigningRepr {
+            epoch: self.epoch(),
+            round: self.round(),
+            hqc_round: self.hqc_round(),
+        }
+    }
+
+    pub fn verify(&self, validators: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.hqc_round() < self.round(),
+            "Timeout round {} should be larger than the QC round {}",
+            self.round(),
+            self.hqc_round()
+        );
+        self.quorum_cert
+            .verify(validators)
+            .context("Failed to verify QC of TwoChainTimeout")?;
+        Ok(())
+    }
+}
+
+impl Display for TwoChainTimeout {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "TwoChainTimeout: [epoch: {}, round: {}, hqc_round: {}]",
+            self.epoch,
+            self.round,
+            self.hqc_round(),
+        )
+    }
+}
+
+/// The message validators sign for a TwoChainTimeout.
+#[derive(Debug, Deserialize, Serialize, CryptoHasher, BCSCryptoHash)]
+pub struct TimeoutSigningRepr {
+    pub epoch: u64,
+    pub round: Round,
+    pub hqc_round: Round,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+/// TwoChainTimeoutCertificate is a proof that 2f+1 participants in epoch i have timed out in
+/// round r. Along with each signature it carries the round of the highest quorum cert known to
+/// the signer, and the timeout of the signer with the highest quorum cert.
+pub struct TwoChainTimeoutCertificate {
+    timeout: TwoChainTimeout,
+    signatures: BTreeMap<Author, (Round, Ed25519Signature)>,
+}
+
+impl Display for TwoChainTimeoutCertificate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "TwoChainTimeoutCertificate[epoch: {}, round: {}, hqc_round: {}]",
+            self.timeout.epoch(),
+            self.timeout.round(),
+            self.timeout.hqc_round(),
+        )
+    }
+}
+
+impl TwoChainTimeoutCertificate {
+    /// Creates new TwoChainTimeoutCertificate
+    pub fn new(timeout: TwoChainTimeout) -> Self {
+        Self {
+            timeout,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Verifies the signatures of the round along with the signed quorum cert rounds, and that the
+    /// quorum cert carried is the highest one among the signers.
+    pub fn verify(&self, validators: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.timeout.verify(validators)?;
+        validators
+            .check_voting_power(self.signatures.keys())
+            .context("Failed to verify TwoChainTimeoutCertificate")?;
+        let mut signed_hqc_round = 0;
+        for (author, (hqc_round, signature)) in &self.signatures {
+            let signed_timeout = TimeoutSigningRepr {
+                epoch: self.epoch(),
+                round: self.round(),
+                hqc_round: *hqc_round,
+            };
+            validators
+                .verify(*author, &signed_timeout, signature)
+                .context("Failed to verify TwoChainTimeoutCertificate")?;
+            signed_hqc_round = std::cmp::max(signed_hqc_round, *hqc_round);
+        }
+        ensure!(
+            self.highest_hqc_round() == signed_hqc_round,
+            "QC round {} of TwoChainTimeoutCertificate isn't the highest signed QC round {}",
+            self.highest_hqc_round(),
+            signed_hqc_round
+        );
+        Ok(())
+    }
+
+    /// Returns the epoch of the timeout certificate
+    pub fn epoch(&self) -> u64 {
+        self.timeout.epoch()
+    }
+
+    /// Returns the round of the timeout certificate
+    pub fn round(&self) -> Round {
+        self.timeout.round()
+    }
+
+    /// Returns the highest quorum cert round among the signers
+    pub fn highest_hqc_round(&self) -> Round {
+        self.timeout.hqc_round()
+    }
+
+    /// Returns the timeout carrying the highest quorum cert among the signers
+    pub fn timeout(&self) -> &TwoChainTimeout {
+        &self.timeout
+    }
+
+    /// Returns the signatures certifying the round along with the signed quorum cert rounds
+    pub fn signatures(&self) -> &BTreeMap<Author, (Round, Ed25519Signature)> {
+        &self.signatures
+    }
+
+    /// Adds the signature of an already verified timeout of the same epoch and round, keeping
+    /// the timeout with the highest quorum cert.
+    pub fn add(&mut self, author: Author, timeout: TwoChainTimeout, signature: Ed25519Signature) {
+        debug_assert_eq!(
+            (self.epoch(), self.round()),
+            (timeout.epoch(), timeout.round()),
+            "Timeout should have the same epoch and round as the certificate"
+        );
+        if self.signatures.contains_key(&author) {
+            return;
+        }
+        let hqc_round = timeout.hqc_round();
+        if hqc_round > self.highest_hqc_round() {
+            self.timeout = timeout;
+        }
+        self.signatures.insert(author, (hqc_round, signature));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::block_test_utils::{certificate_for_genesis, gen_test_certificate};
+    use diem_types::{block_info::BlockInfo, validator_verifier::random_validator_verifier};
+
+    #[test]
+    fn test_2chain_timeout_certificate() {
+        let (signers, validators) = random_validator_verifier(4, None, false);
+        let quorum_size = validators.quorum_voting_power() as usize;
+        let generate_quorum = |round, parent_round| {
+            gen_test_certificate(
+                signers.iter().collect(),
+                BlockInfo::random(round),
+                BlockInfo::random(parent_round),
+                None,
+            )
+        };
+        let timeouts: Vec<_> = (1..=3)
+            .map(|qc_round| TwoChainTimeout::new(1, 4, generate_quorum(qc_round, qc_round - 1)))
+            .collect();
+
+        // timeout with the highest quorum cert is kept
+        let mut tc = TwoChainTimeoutCertificate::new(timeouts[0].clone());
+        for (signer, timeout) in signers.iter().zip(timeouts.iter()) {
+            tc.add(signer.author(), timeout.clone(), timeout.sign(signer));
+        }
+        assert_eq!(tc.highest_hqc_round(), 3);
+        assert_eq!(tc.signatures().len(), quorum_size);
+        tc.verify(&validators).unwrap();
+
+        // not enough signatures
+        let mut partial_tc = TwoChainTimeoutCertificate::new(timeouts[2].clone());
+        partial_tc.add(
+            signers[0].author(),
+            timeouts[2].clone(),
+            timeouts[2].sign(&signers[0]),
+        );
+        assert!(partial_tc.verify(&validators).is_err());
+
+        // the quorum cert carried isn't the highest one signed
+        let mut lower_tc = tc.clone();
+        lower_tc.timeout = timeouts[0].clone();
+        assert!(lower_tc.verify(&validators).is_err());
+
+        // the timeout round has to be higher than the quorum cert round
+        let invalid_timeout = TwoChainTimeout::new(1, 0, certificate_for_genesis());
+        assert!(invalid_timeout.verify(&validators).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{common::Author, timeout::Timeout, vote_data::VoteData};
+use crate::{
+    common::Author, quorum_cert::QuorumCert, timeout::Timeout, timeout_2chain::TwoChainTimeout,
+    vote_data::VoteData,
+};
 use anyhow::{ensure, Context};
 use diem_crypto::{ed25519::Ed25519Signature, hash::CryptoHash};
 use diem_types::{
@@ -28,6 +31,9 @@ pub struct Vote {
     signature: Ed25519Signature,
     /// The round signatures can be aggregated into a timeout certificate if present.
     timeout_signature: Option<Ed25519Signature>,
+    /// The timeout and its signature with the two-chain commit rule, they can be aggregated into
+    /// a two-chain timeout certificate if present.
+    two_chain_timeout: Option<(TwoChainTimeout, Ed25519Signature)>,
 }
 
 // this is required by structured log
@@ -77,6 +83,7 @@ impl Vote {
             ledger_info,
             signature,
             timeout_signature: None,
+            two_chain_timeout: None,
         }
     }
 
@@ -90,6 +97,16 @@ impl Vote {
         self.timeout_signature.replace(signature);
     }
 
+    /// Adds the timeout and its signature with the two-chain commit rule, which can then be used
+    /// for aggregating a two-chain timeout certificate.
+    pub fn add_2chain_timeout(&mut self, timeout: TwoChainTimeout, signature: Ed25519Signature) {
+        if self.two_chain_timeout.is_some() {
+            return; // timeout is already set
+        }
+
+        self.two_chain_timeout.replace((timeout, signature));
+    }
+
     pub fn vote_data(&self) -> &VoteData {
         &self.vote_data
     }
@@ -117,6 +134,16 @@ impl Vote {
         )
     }
 
+    /// Returns the timeout of the vote's round with the two-chain commit rule, carrying the
+    /// given highest quorum cert
+    pub fn generate_2chain_timeout(&self, qc: QuorumCert) -> TwoChainTimeout {
+        TwoChainTimeout::new(
+            self.vote_data().proposed().epoch(),
+            self.vote_data().proposed().round(),
+            qc,
+        )
+    }
+
     /// Return the epoch of the vote
     pub fn epoch(&self) -> u64 {
         self.vote_data.proposed().epoch()
@@ -128,10 +155,17 @@ impl Vote {
         self.timeout_signature.as_ref()
     }
 
+    /// Returns the timeout and its signature with the two-chain commit rule, which can be
+    /// aggregated for TwoChainTimeoutCertificate.
+    pub fn two_chain_timeout(&self) -> Option<&(TwoChainTimeout, Ed25519Signature)> {
+        self.two_chain_timeout.as_ref()
+    }
+
     /// The vote message is considered a timeout vote message if it carries a signature on the
-    /// round, which can then be used for aggregating it to the TimeoutCertificate.
+    /// round, which can then be used for aggregating it to the TimeoutCertificate or the
+    /// TwoChainTimeoutCertificate.
     pub fn is_timeout(&self) -> bool {
-        self.timeout_signature.is_some()
+        self.timeout_signature.is_some() || self.two_chain_timeout.is_some()
     }
 
     /// Verifies that the consensus data hash of LedgerInfo corresponds to the vote info,
@@ -149,6 +183,17 @@ impl Vote {
                 .verify(self.author(), &self.timeout(), timeout_signature)
                 .context("Failed to verify Timeout Vote")?;
         }
+        if let Some((timeout, signature)) = &self.two_chain_timeout {
+            ensure!(
+                (timeout.epoch(), timeout.round())
+                    == (self.epoch(), self.vote_data.proposed().round()),
+                "2-chain timeout has different (epoch, round) than Vote"
+            );
+            timeout.verify(validator)?;
+            validator
+                .verify(self.author(), &timeout.signing_format(), signature)
+                .context("Failed to verify 2-chain timeout signature")?;
+        }
         // Let us verify the vote data as well
         self.vote_data().verify()?;
         Ok(())
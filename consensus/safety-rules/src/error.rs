@@ -7,6 +7,8 @@ use thiserror::Error;
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 /// Different reasons for proposal rejection
 pub enum Error {
+    #[error("Ordered block {0} doesn't match executed block {1}")]
+    InconsistentExecutionResult(String, String),
     #[error("Provided epoch, {0}, does not match expected epoch, {1}")]
     IncorrectEpoch(u64, u64),
     #[error("block has next round that wraps around: {0}")]
@@ -17,6 +19,8 @@ pub enum Error {
     IncorrectPreferredRound(u64, u64),
     #[error("Unable to verify that the new tree extends the parent: {0}")]
     InvalidAccumulatorExtension(String),
+    #[error("Invalid ordered LedgerInfo: {0}")]
+    InvalidOrderedLedgerInfo(String),
     #[error("Invalid EpochChangeProof: {0}")]
     InvalidEpochChangeProof(String),
     #[error("Internal error: {0}")]
@@ -27,8 +31,16 @@ pub enum Error {
     InvalidProposal(String),
     #[error("Invalid QC: {0}")]
     InvalidQuorumCertificate(String),
+    #[error("Invalid timeout: {0}")]
+    InvalidTimeout(String),
+    #[error("Invalid TC: {0}")]
+    InvalidTimeoutCertificate(String),
     #[error("{0} is not set, SafetyRules is not initialized")]
     NotInitialized(String),
+    #[error("Does not satisfy 2-chain timeout rule, round {0}, qc round {1}, tc round {2}, one-chain round {3}")]
+    NotSafeToTimeout(u64, u64, u64, u64),
+    #[error("Does not satisfy 2-chain voting rule, round {0}, qc round {1}, tc round {2}, tc hqc round {3}")]
+    NotSafeToVote(u64, u64, u64, u64),
     #[error("Data not found in secure storage: {0}")]
     SecureStorageMissingDataError(String),
     #[error("Unexpected error returned by secure storage: {0}")]
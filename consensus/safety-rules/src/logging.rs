@@ -14,6 +14,8 @@ pub struct SafetyLogSchema<'a> {
     round: Option<Round>,
     preferred_round: Option<u64>,
     last_voted_round: Option<u64>,
+    one_chain_round: Option<u64>,
+    highest_timeout_round: Option<u64>,
     epoch: Option<u64>,
     #[schema(display)]
     error: Option<&'a Error>,
@@ -29,6 +31,8 @@ impl<'a> SafetyLogSchema<'a> {
             round: None,
             preferred_round: None,
             last_voted_round: None,
+            one_chain_round: None,
+            highest_timeout_round: None,
             epoch: None,
             error: None,
             waypoint: None,
@@ -42,13 +46,18 @@ impl<'a> SafetyLogSchema<'a> {
 pub enum LogEntry {
     ConsensusState,
     ConstructAndSignVote,
+    ConstructAndSignVoteTwoChain,
     Epoch,
+    HighestTimeoutRound,
     Initialize,
     KeyReconciliation,
     LastVotedRound,
+    OneChainRound,
     PreferredRound,
+    SignCommitVote,
     SignProposal,
     SignTimeout,
+    SignTimeoutWithQC,
     State,
     Waypoint,
 }
@@ -58,13 +67,18 @@ impl LogEntry {
         match self {
             LogEntry::ConsensusState => "consensus_state",
             LogEntry::ConstructAndSignVote => "construct_and_sign_vote",
+            LogEntry::ConstructAndSignVoteTwoChain => "construct_and_sign_vote_2chain",
             LogEntry::Epoch => "epoch",
+            LogEntry::HighestTimeoutRound => "highest_timeout_round",
             LogEntry::Initialize => "initialize",
             LogEntry::LastVotedRound => "last_voted_round",
             LogEntry::KeyReconciliation => "key_reconciliation",
+            LogEntry::OneChainRound => "one_chain_round",
             LogEntry::PreferredRound => "preferred_round",
+            LogEntry::SignCommitVote => "sign_commit_vote",
             LogEntry::SignProposal => "sign_proposal",
             LogEntry::SignTimeout => "sign_timeout",
+            LogEntry::SignTimeoutWithQC => "sign_timeout_with_qc",
             LogEntry::State => "state",
             LogEntry::Waypoint => "waypoint",
         }
@@ -20,6 +20,7 @@ impl Process {
 
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
         let export_consensus_key = config.export_consensus_key;
+        let decoupled_execution = config.decoupled_execution;
         let service = match &config.service {
             SafetyRulesService::Process(service) => service,
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
@@ -32,6 +33,7 @@ impl Process {
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                decoupled_execution,
                 network_timeout: config.network_timeout_ms,
             }),
         }
@@ -44,6 +46,7 @@ impl Process {
             data.server_addr,
             data.verify_vote_proposal_signature,
             data.export_consensus_key,
+            data.decoupled_execution,
             data.network_timeout,
         );
     }
@@ -54,6 +57,7 @@ struct ProcessData {
     storage: PersistentSafetyStorage,
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
+    decoupled_execution: bool,
     // Timeout in Seconds for network operations
     network_timeout: u64,
 }
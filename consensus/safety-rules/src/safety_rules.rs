@@ -17,19 +17,23 @@ use consensus_types::{
     quorum_cert::QuorumCert,
     safety_data::SafetyData,
     timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
     vote::Vote,
     vote_data::VoteData,
     vote_proposal::{MaybeSignedVoteProposal, VoteProposal},
 };
 use diem_crypto::{
     ed25519::{Ed25519PublicKey, Ed25519Signature},
-    hash::{CryptoHash, HashValue},
+    hash::{CryptoHash, HashValue, ACCUMULATOR_PLACEHOLDER_HASH},
     traits::Signature,
 };
 use diem_logger::prelude::*;
 use diem_types::{
-    block_info::BlockInfo, epoch_change::EpochChangeProof, epoch_state::EpochState,
-    ledger_info::LedgerInfo, waypoint::Waypoint,
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    waypoint::Waypoint,
 };
 use serde::Serialize;
 use std::cmp::Ordering;
@@ -39,6 +43,8 @@ pub struct SafetyRules {
     persistent_storage: PersistentSafetyStorage,
     execution_public_key: Option<Ed25519PublicKey>,
     export_consensus_key: bool,
+    /// Whether blocks are voted on for their ordering only, before they are executed.
+    decoupled_execution: bool,
     validator_signer: Option<ConfigurableValidatorSigner>,
     epoch_state: Option<EpochState>,
 }
@@ -50,6 +56,7 @@ impl SafetyRules {
         persistent_storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
         export_consensus_key: bool,
+        decoupled_execution: bool,
     ) -> Self {
         let execution_public_key = if verify_vote_proposal_signature {
             Some(
@@ -64,6 +71,7 @@ impl SafetyRules {
             persistent_storage,
             execution_public_key,
             export_consensus_key,
+            decoupled_execution,
             validator_signer: None,
             epoch_state: None,
         }
@@ -89,6 +97,14 @@ impl SafetyRules {
     /// Check if the executed result extends the parent result.
     fn extension_check(&self, vote_proposal: &VoteProposal) -> Result<VoteData, Error> {
         let proposed_block = vote_proposal.block();
+        // With decoupled execution the block is voted on before it's executed, there is no
+        // execution result to check yet.
+        if self.decoupled_execution {
+            return Ok(VoteData::new(
+                proposed_block.gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None),
+                proposed_block.quorum_cert().certified_block().clone(),
+            ));
+        }
         let new_tree = vote_proposal
             .accumulator_extension_proof()
             .verify(
@@ -124,8 +140,6 @@ impl SafetyRules {
         let block0 = proposed_block.quorum_cert().parent_block().round();
 
         // verify 3-chain rule
-        let next_round =
-            |round: u64| u64::checked_add(round, 1).ok_or(Error::IncorrectRound(round));
         let commit = next_round(block0)? == block1 && next_round(block1)? == block2;
 
         // create a ledger info
@@ -138,6 +152,32 @@ impl SafetyRules {
         Ok(LedgerInfo::new(commit_info, consensus_data_hash))
     }
 
+    /// Produces a LedgerInfo that either commits a block based upon the 2-chain
+    /// commit rule or an empty LedgerInfo for no commit. The 2-chain commit rule is: B0 and its
+    /// prefixes can be committed if there exist certified block B1 that satisfy:
+    /// 1) B0 <- B1 <--
+    /// 2) round(B0) + 1 = round(B1)
+    pub fn construct_ledger_info_2chain(
+        &self,
+        proposed_block: &Block,
+        consensus_data_hash: HashValue,
+    ) -> Result<LedgerInfo, Error> {
+        let block1 = proposed_block.round();
+        let block0 = proposed_block.quorum_cert().certified_block().round();
+
+        // verify 2-chain rule
+        let commit = next_round(block0)? == block1;
+
+        // create a ledger info
+        let commit_info = if commit {
+            proposed_block.quorum_cert().certified_block().clone()
+        } else {
+            BlockInfo::empty()
+        };
+
+        Ok(LedgerInfo::new(commit_info, consensus_data_hash))
+    }
+
     /// Second voting rule
     fn verify_and_update_preferred_round(
         &mut self,
@@ -176,6 +216,92 @@ impl SafetyRules {
         Ok(updated)
     }
 
+    /// Records the 1-chain and 2-chain rounds of the quorum cert with the two-chain commit rule,
+    /// returns whether the safety data got updated.
+    fn observe_qc(&self, quorum_cert: &QuorumCert, safety_data: &mut SafetyData) -> bool {
+        let mut updated = false;
+        let one_chain_round = quorum_cert.certified_block().round();
+        let two_chain_round = quorum_cert.parent_block().round();
+        if one_chain_round > safety_data.one_chain_round {
+            safety_data.one_chain_round = one_chain_round;
+            info!(
+                SafetyLogSchema::new(LogEntry::OneChainRound, LogEvent::Update)
+                    .one_chain_round(safety_data.one_chain_round)
+            );
+            updated = true;
+        }
+        if two_chain_round > safety_data.preferred_round {
+            safety_data.preferred_round = two_chain_round;
+            info!(
+                SafetyLogSchema::new(LogEntry::PreferredRound, LogEvent::Update)
+                    .preferred_round(safety_data.preferred_round)
+            );
+            updated = true;
+        }
+        updated
+    }
+
+    /// 2-chain voting rule: a block can be voted if it directly extends the quorum cert it carries,
+    /// or if it directly extends the timeout certificate and its quorum cert is at least as high
+    /// as the highest quorum cert of the timeout certificate.
+    fn safe_to_vote(
+        &self,
+        block: &Block,
+        maybe_tc: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<(), Error> {
+        let round = block.round();
+        let qc_round = block.quorum_cert().certified_block().round();
+        let tc_round = maybe_tc.map_or(0, |tc| tc.round());
+        let hqc_round = maybe_tc.map_or(0, |tc| tc.highest_hqc_round());
+        if round == next_round(qc_round)?
+            || (round == next_round(tc_round)? && qc_round >= hqc_round)
+        {
+            Ok(())
+        } else {
+            Err(Error::NotSafeToVote(round, qc_round, tc_round, hqc_round))
+        }
+    }
+
+    /// 2-chain timeout rule: a timeout can be signed for the round following the quorum cert or
+    /// the timeout certificate, as long as the quorum cert carried is at least as high as any
+    /// quorum cert observed.
+    fn safe_to_timeout(
+        &self,
+        timeout: &TwoChainTimeout,
+        maybe_tc: Option<&TwoChainTimeoutCertificate>,
+        safety_data: &SafetyData,
+    ) -> Result<(), Error> {
+        let round = timeout.round();
+        let qc_round = timeout.hqc_round();
+        let tc_round = maybe_tc.map_or(0, |tc| tc.round());
+        if (round == next_round(qc_round)? || round == next_round(tc_round)?)
+            && qc_round >= safety_data.one_chain_round
+        {
+            Ok(())
+        } else {
+            Err(Error::NotSafeToTimeout(
+                round,
+                qc_round,
+                tc_round,
+                safety_data.one_chain_round,
+            ))
+        }
+    }
+
+    fn update_highest_timeout_round(
+        &self,
+        timeout: &TwoChainTimeout,
+        safety_data: &mut SafetyData,
+    ) {
+        if timeout.round() > safety_data.highest_timeout_round {
+            safety_data.highest_timeout_round = timeout.round();
+            info!(
+                SafetyLogSchema::new(LogEntry::HighestTimeoutRound, LogEvent::Update)
+                    .highest_timeout_round(safety_data.highest_timeout_round)
+            );
+        }
+    }
+
     /// This verifies whether the author of one proposal is the validator signer
     fn verify_author(&self, author: Option<Author>) -> Result<(), Error> {
         let validator_signer_author = &self.signer()?.author();
@@ -229,6 +355,36 @@ impl SafetyRules {
         Ok(())
     }
 
+    /// This verifies a two-chain TC has valid signatures.
+    fn verify_tc(&self, tc: &TwoChainTimeoutCertificate) -> Result<(), Error> {
+        let epoch_state = self.epoch_state()?;
+
+        tc.verify(&epoch_state.verifier)
+            .map_err(|e| Error::InvalidTimeoutCertificate(e.to_string()))?;
+        Ok(())
+    }
+
+    /// This verifies the signature of the execution result unless it comes after the vote.
+    fn verify_execution_signature(
+        &self,
+        maybe_signed_vote_proposal: &MaybeSignedVoteProposal,
+    ) -> Result<(), Error> {
+        let vote_proposal = &maybe_signed_vote_proposal.vote_proposal;
+        let execution_signature = maybe_signed_vote_proposal.signature.as_ref();
+
+        // The execution result is only signed once the block is executed, which comes after the
+        // vote with decoupled execution.
+        if let (Some(public_key), false) =
+            (self.execution_public_key.as_ref(), self.decoupled_execution)
+        {
+            execution_signature
+                .ok_or(Error::VoteProposalSignatureNotFound)?
+                .verify(vote_proposal, public_key)
+                .map_err(|error| Error::InternalError(error.to_string()))?;
+        }
+        Ok(())
+    }
+
     // Internal functions mapped to the public interface to enable exhaustive logging and metrics
 
     fn guarded_consensus_state(&mut self) -> Result<ConsensusState, Error> {
@@ -340,16 +496,9 @@ impl SafetyRules {
         // Exit early if we cannot sign
         self.signer()?;
 
-        let vote_proposal = &maybe_signed_vote_proposal.vote_proposal;
-        let execution_signature = maybe_signed_vote_proposal.signature.as_ref();
-
-        if let Some(public_key) = self.execution_public_key.as_ref() {
-            execution_signature
-                .ok_or(Error::VoteProposalSignatureNotFound)?
-                .verify(vote_proposal, public_key)
-                .map_err(|error| Error::InternalError(error.to_string()))?;
-        }
+        self.verify_execution_signature(maybe_signed_vote_proposal)?;
 
+        let vote_proposal = &maybe_signed_vote_proposal.vote_proposal;
         let proposed_block = vote_proposal.block();
         let mut safety_data = self.persistent_storage.safety_data()?;
 
@@ -439,6 +588,118 @@ impl SafetyRules {
         let signature = self.sign(timeout)?;
         Ok(signature)
     }
+
+    fn guarded_construct_and_sign_vote_two_chain(
+        &mut self,
+        maybe_signed_vote_proposal: &MaybeSignedVoteProposal,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Vote, Error> {
+        // Exit early if we cannot sign
+        self.signer()?;
+
+        self.verify_execution_signature(maybe_signed_vote_proposal)?;
+
+        let vote_proposal = &maybe_signed_vote_proposal.vote_proposal;
+        let proposed_block = vote_proposal.block();
+        let mut safety_data = self.persistent_storage.safety_data()?;
+
+        self.verify_epoch(proposed_block.epoch(), &safety_data)?;
+
+        // if already voted on this round, send back the previous vote
+        // note: this needs to happen after verifying the epoch as we just check the round here
+        if let Some(vote) = safety_data.last_vote.clone() {
+            if vote.vote_data().proposed().round() == proposed_block.round() {
+                return Ok(vote);
+            }
+        }
+
+        self.verify_qc(proposed_block.quorum_cert())?;
+        if let Some(tc) = timeout_cert {
+            self.verify_tc(tc)?;
+        }
+        proposed_block
+            .validate_signature(&self.epoch_state()?.verifier)
+            .map_err(|error| Error::InternalError(error.to_string()))?;
+
+        self.verify_and_update_last_vote_round(
+            proposed_block.block_data().round(),
+            &mut safety_data,
+        )?;
+        self.safe_to_vote(proposed_block, timeout_cert)?;
+        self.observe_qc(proposed_block.quorum_cert(), &mut safety_data);
+
+        // Construct and sign vote
+        let vote_data = self.extension_check(vote_proposal)?;
+        let author = self.signer()?.author();
+        let ledger_info = self.construct_ledger_info_2chain(proposed_block, vote_data.hash())?;
+        let signature = self.sign(&ledger_info)?;
+        let vote = Vote::new_with_signature(vote_data, author, ledger_info, signature);
+
+        safety_data.last_vote = Some(vote.clone());
+        self.persistent_storage.set_safety_data(safety_data)?;
+
+        Ok(vote)
+    }
+
+    fn guarded_sign_timeout_with_qc(
+        &mut self,
+        timeout: &TwoChainTimeout,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Ed25519Signature, Error> {
+        self.signer()?;
+
+        let mut safety_data = self.persistent_storage.safety_data()?;
+        self.verify_epoch(timeout.epoch(), &safety_data)?;
+        timeout
+            .verify(&self.epoch_state()?.verifier)
+            .map_err(|e| Error::InvalidTimeout(e.to_string()))?;
+        if let Some(tc) = timeout_cert {
+            self.verify_tc(tc)?;
+        }
+
+        self.safe_to_timeout(timeout, timeout_cert, &safety_data)?;
+        if timeout.round() < safety_data.last_voted_round {
+            return Err(Error::IncorrectLastVotedRound(
+                timeout.round(),
+                safety_data.last_voted_round,
+            ));
+        }
+        if timeout.round() > safety_data.last_voted_round {
+            self.verify_and_update_last_vote_round(timeout.round(), &mut safety_data)?;
+        }
+        self.update_highest_timeout_round(timeout, &mut safety_data);
+        self.persistent_storage.set_safety_data(safety_data)?;
+
+        let signature = self.sign(&timeout.signing_format())?;
+        Ok(signature)
+    }
+
+    fn guarded_sign_commit_vote(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        self.signer()?;
+
+        let old_ledger_info = ledger_info.ledger_info();
+        if !old_ledger_info
+            .commit_info()
+            .match_ordered_only(new_ledger_info.commit_info())
+            || old_ledger_info.consensus_data_hash() != new_ledger_info.consensus_data_hash()
+        {
+            return Err(Error::InconsistentExecutionResult(
+                old_ledger_info.commit_info().to_string(),
+                new_ledger_info.commit_info().to_string(),
+            ));
+        }
+
+        // Only sign the execution result of a block that a quorum agreed to commit.
+        ledger_info
+            .verify_signatures(&self.epoch_state()?.verifier)
+            .map_err(|error| Error::InvalidOrderedLedgerInfo(error.to_string()))?;
+
+        self.sign(&new_ledger_info)
+    }
 }
 
 impl TSafetyRules for SafetyRules {
@@ -471,6 +732,49 @@ impl TSafetyRules for SafetyRules {
         let cb = || self.guarded_sign_timeout(timeout);
         run_and_log(cb, |log| log.round(timeout.round()), LogEntry::SignTimeout)
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        let round = new_ledger_info.round();
+        let cb = || self.guarded_sign_commit_vote(ledger_info, new_ledger_info);
+        run_and_log(cb, |log| log.round(round), LogEntry::SignCommitVote)
+    }
+
+    fn sign_timeout_with_qc(
+        &mut self,
+        timeout: &TwoChainTimeout,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Ed25519Signature, Error> {
+        let cb = || self.guarded_sign_timeout_with_qc(timeout, timeout_cert);
+        run_and_log(
+            cb,
+            |log| log.round(timeout.round()),
+            LogEntry::SignTimeoutWithQC,
+        )
+    }
+
+    fn construct_and_sign_vote_two_chain(
+        &mut self,
+        maybe_signed_vote_proposal: &MaybeSignedVoteProposal,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Vote, Error> {
+        let round = maybe_signed_vote_proposal.vote_proposal.block().round();
+        let cb = || {
+            self.guarded_construct_and_sign_vote_two_chain(maybe_signed_vote_proposal, timeout_cert)
+        };
+        run_and_log(
+            cb,
+            |log| log.round(round),
+            LogEntry::ConstructAndSignVoteTwoChain,
+        )
+    }
+}
+
+fn next_round(round: Round) -> Result<Round, Error> {
+    u64::checked_add(round, 1).ok_or(Error::IncorrectRound(round))
 }
 
 fn run_and_log<F, L, R>(callback: F, log_cb: L, log_entry: LogEntry) -> Result<R, Error>
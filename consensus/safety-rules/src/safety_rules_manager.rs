@@ -69,21 +69,25 @@ impl SafetyRulesManager {
         let storage = storage(config);
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
         let export_consensus_key = config.export_consensus_key;
+        let decoupled_execution = config.decoupled_execution;
         match config.service {
             SafetyRulesService::Local => Self::new_local(
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                decoupled_execution,
             ),
             SafetyRulesService::Serializer => Self::new_serializer(
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                decoupled_execution,
             ),
             SafetyRulesService::Thread => Self::new_thread(
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                decoupled_execution,
                 config.network_timeout_ms,
             ),
             _ => panic!("Unimplemented SafetyRulesService: {:?}", config.service),
@@ -94,11 +98,13 @@ impl SafetyRulesManager {
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
         export_consensus_key: bool,
+        decoupled_execution: bool,
     ) -> Self {
         let safety_rules = SafetyRules::new(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
+            decoupled_execution,
         );
         Self {
             internal_safety_rules: SafetyRulesWrapper::Local(Arc::new(RwLock::new(safety_rules))),
@@ -116,11 +122,13 @@ impl SafetyRulesManager {
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
         export_consensus_key: bool,
+        decoupled_execution: bool,
     ) -> Self {
         let safety_rules = SafetyRules::new(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
+            decoupled_execution,
         );
         let serializer_service = SerializerService::new(safety_rules);
         Self {
@@ -134,12 +142,14 @@ impl SafetyRulesManager {
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
         export_consensus_key: bool,
+        decoupled_execution: bool,
         timeout_ms: u64,
     ) -> Self {
         let thread = ThreadService::new(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
+            decoupled_execution,
             timeout_ms,
         );
         Self {
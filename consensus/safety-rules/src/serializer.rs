@@ -3,12 +3,19 @@
 
 use crate::{counters, logging::LogEntry, ConsensusState, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
+    block::Block,
+    block_data::BlockData,
+    timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
+    vote::Vote,
     vote_proposal::MaybeSignedVoteProposal,
 };
 use diem_crypto::ed25519::Ed25519Signature;
 use diem_infallible::RwLock;
-use diem_types::epoch_change::EpochChangeProof;
+use diem_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -19,6 +26,15 @@ pub enum SafetyRulesInput {
     ConstructAndSignVote(Box<MaybeSignedVoteProposal>),
     SignProposal(Box<BlockData>),
     SignTimeout(Box<Timeout>),
+    SignCommitVote(Box<LedgerInfoWithSignatures>, Box<LedgerInfo>),
+    SignTimeoutWithQC(
+        Box<TwoChainTimeout>,
+        Box<Option<TwoChainTimeoutCertificate>>,
+    ),
+    ConstructAndSignVoteTwoChain(
+        Box<MaybeSignedVoteProposal>,
+        Box<Option<TwoChainTimeoutCertificate>>,
+    ),
 }
 
 pub struct SerializerService {
@@ -45,6 +61,21 @@ impl SerializerService {
             SafetyRulesInput::SignTimeout(timeout) => {
                 bcs::to_bytes(&self.internal.sign_timeout(&timeout))
             }
+            SafetyRulesInput::SignCommitVote(ledger_info, new_ledger_info) => bcs::to_bytes(
+                &self
+                    .internal
+                    .sign_commit_vote(*ledger_info, *new_ledger_info),
+            ),
+            SafetyRulesInput::SignTimeoutWithQC(timeout, tc) => bcs::to_bytes(
+                &self
+                    .internal
+                    .sign_timeout_with_qc(&timeout, tc.as_ref().as_ref()),
+            ),
+            SafetyRulesInput::ConstructAndSignVoteTwoChain(vote_proposal, tc) => bcs::to_bytes(
+                &self
+                    .internal
+                    .construct_and_sign_vote_two_chain(&vote_proposal, tc.as_ref().as_ref()),
+            ),
         };
 
         Ok(output?)
@@ -105,6 +136,46 @@ impl TSafetyRules for SerializerClient {
         let response = self.request(SafetyRulesInput::SignTimeout(Box::new(timeout.clone())))?;
         bcs::from_bytes(&response)?
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_timer("external", LogEntry::SignCommitVote.as_str());
+        let response = self.request(SafetyRulesInput::SignCommitVote(
+            Box::new(ledger_info),
+            Box::new(new_ledger_info),
+        ))?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn sign_timeout_with_qc(
+        &mut self,
+        timeout: &TwoChainTimeout,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_timer("external", LogEntry::SignTimeoutWithQC.as_str());
+        let response = self.request(SafetyRulesInput::SignTimeoutWithQC(
+            Box::new(timeout.clone()),
+            Box::new(timeout_cert.cloned()),
+        ))?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn construct_and_sign_vote_two_chain(
+        &mut self,
+        vote_proposal: &MaybeSignedVoteProposal,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Vote, Error> {
+        let _timer =
+            counters::start_timer("external", LogEntry::ConstructAndSignVoteTwoChain.as_str());
+        let response = self.request(SafetyRulesInput::ConstructAndSignVoteTwoChain(
+            Box::new(vote_proposal.clone()),
+            Box::new(timeout_cert.cloned()),
+        ))?;
+        bcs::from_bytes(&response)?
+    }
 }
 
 pub trait TSerializerClient: Send + Sync {
@@ -3,11 +3,18 @@
 
 use crate::{ConsensusState, Error};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
+    block::Block,
+    block_data::BlockData,
+    timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
+    vote::Vote,
     vote_proposal::MaybeSignedVoteProposal,
 };
 use diem_crypto::ed25519::Ed25519Signature;
-use diem_types::epoch_change::EpochChangeProof;
+use diem_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 
 /// Interface for SafetyRules
 pub trait TSafetyRules {
@@ -34,4 +41,30 @@ pub trait TSafetyRules {
     /// As the holder of the private key, SafetyRules also signs what is effectively a
     /// timeout message. This returns the signature for that timeout message.
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error>;
+
+    /// Signs a timeout with the two-chain commit rule following the timeout rule, the timeout
+    /// carries the highest quorum cert and `timeout_cert` is the highest timeout certificate
+    /// known to the validator.
+    fn sign_timeout_with_qc(
+        &mut self,
+        timeout: &TwoChainTimeout,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Ed25519Signature, Error>;
+
+    /// Attempts to vote for a given proposal following the voting rules of the two-chain commit
+    /// rule, `timeout_cert` is the highest timeout certificate known to the validator.
+    fn construct_and_sign_vote_two_chain(
+        &mut self,
+        vote_proposal: &MaybeSignedVoteProposal,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Vote, Error>;
+
+    /// With decoupled execution, a block is executed after it's been ordered. This signs the
+    /// `new_ledger_info` carrying the execution result of the block committed by `ledger_info`,
+    /// the ordering only LedgerInfo certified by a quorum of validators.
+    fn sign_commit_vote(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<Ed25519Signature, Error>;
 }
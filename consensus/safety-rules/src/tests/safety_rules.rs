@@ -1,8 +1,8 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, tests::suite, SafetyRules};
-use diem_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use crate::{test_utils, tests::suite, Error, SafetyRules, TSafetyRules};
+use diem_crypto::{ed25519::Ed25519PrivateKey, hash::ACCUMULATOR_PLACEHOLDER_HASH, Uniform};
 use diem_types::validator_signer::ValidatorSigner;
 
 #[test]
@@ -18,6 +18,48 @@ fn test() {
     }
 }
 
+#[test]
+fn test_decoupled_execution() {
+    let signer = ValidatorSigner::from_int(0);
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+    // An unexecuted block: no execution signature, and an extension proof that doesn't extend
+    // the parent.
+    let unexecuted = test_utils::make_proposal_with_qc_and_proof(
+        vec![],
+        round + 1,
+        test_utils::Proof::new(vec![], 1, vec![]),
+        genesis_qc,
+        &signer,
+        None,
+    );
+
+    // The block is voted on for its ordering only with decoupled execution
+    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), true, false, true);
+    safety_rules.initialize(&proof).unwrap();
+    let vote = safety_rules.construct_and_sign_vote(&unexecuted).unwrap();
+    let proposed = vote.vote_data().proposed();
+    assert_eq!(proposed.id(), unexecuted.block().id());
+    assert_eq!(proposed.executed_state_id(), *ACCUMULATOR_PLACEHOLDER_HASH);
+    assert_eq!(proposed.version(), 0);
+
+    // Otherwise the execution result is checked, whatever the proposal
+    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), true, false, false);
+    safety_rules.initialize(&proof).unwrap();
+    assert_eq!(
+        safety_rules
+            .construct_and_sign_vote(&unexecuted)
+            .unwrap_err(),
+        Error::VoteProposalSignatureNotFound
+    );
+    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), false, false, false);
+    safety_rules.initialize(&proof).unwrap();
+    assert!(matches!(
+        safety_rules.construct_and_sign_vote(&unexecuted),
+        Err(Error::InvalidAccumulatorExtension(_))
+    ));
+}
+
 fn safety_rules(
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
@@ -29,6 +71,7 @@ fn safety_rules(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
+            false,
         ));
         (
             safety_rules,
@@ -3,19 +3,28 @@
 
 use crate::{test_utils, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
-    block::block_test_utils::random_payload, common::Round, quorum_cert::QuorumCert,
-    timeout::Timeout, vote_proposal::MaybeSignedVoteProposal,
+    block::block_test_utils::random_payload,
+    common::Round,
+    quorum_cert::QuorumCert,
+    timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
+    vote_proposal::MaybeSignedVoteProposal,
 };
 use diem_crypto::{
     ed25519::Ed25519PrivateKey,
     hash::{CryptoHash, HashValue},
+    traits::Signature,
 };
 use diem_global_constants::CONSENSUS_KEY;
 use diem_secure_storage::CryptoStorage;
 use diem_types::{
-    epoch_state::EpochState, validator_signer::ValidatorSigner,
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
+use std::collections::BTreeMap;
 
 type Proof = test_utils::Proof;
 
@@ -39,6 +48,18 @@ fn make_proposal_with_parent(
     test_utils::make_proposal_with_parent(vec![], round, parent, committed, signer, exec_key)
 }
 
+fn make_timeout_cert(
+    round: Round,
+    hqc: &QuorumCert,
+    signer: &ValidatorSigner,
+) -> TwoChainTimeoutCertificate {
+    let timeout = TwoChainTimeout::new(1, round, hqc.clone());
+    let mut tc = TwoChainTimeoutCertificate::new(timeout.clone());
+    let signature = timeout.sign(signer);
+    tc.add(signer.author(), timeout, signature);
+    tc
+}
+
 pub type Callback = Box<
     dyn Fn() -> (
         Box<dyn TSafetyRules + Send + Sync>,
@@ -54,6 +75,9 @@ pub fn run_test_suite(safety_rules: &Callback) {
     test_initialize(safety_rules);
     test_preferred_block_rule(safety_rules);
     test_sign_timeout(safety_rules);
+    test_sign_commit_vote(safety_rules);
+    test_2chain_rules(safety_rules);
+    test_2chain_timeout(safety_rules);
     test_voting(safety_rules);
     test_voting_potential_commit_id(safety_rules);
     test_voting_bad_epoch(safety_rules);
@@ -345,6 +369,194 @@ fn test_sign_timeout(safety_rules: &Callback) {
     assert_eq!(actual_err, expected_err);
 }
 
+/// With decoupled execution, ensure that SafetyRules only signs the execution result of a block
+/// that was committed by a quorum certified ordering LedgerInfo.
+fn test_sign_commit_vote(safety_rules: &Callback) {
+    let (mut safety_rules, signer, key) = safety_rules();
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+
+    let p0 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer, key.as_ref());
+    let p1 = make_proposal_with_parent(round + 2, &p0, None, &signer, key.as_ref());
+    let p2 = make_proposal_with_parent(round + 3, &p1, None, &signer, key.as_ref());
+    let p3 = make_proposal_with_parent(round + 4, &p2, Some(&p0), &signer, key.as_ref());
+
+    safety_rules.initialize(&proof).unwrap();
+    safety_rules.construct_and_sign_vote(&p0).unwrap();
+    safety_rules.construct_and_sign_vote(&p1).unwrap();
+    safety_rules.construct_and_sign_vote(&p2).unwrap();
+    let vote = safety_rules.construct_and_sign_vote(&p3).unwrap();
+
+    let ordered_info = vote.ledger_info().commit_info().clone();
+    assert_eq!(ordered_info.round(), p1.block().round());
+    let executed_ledger_info = |block_info: &BlockInfo| {
+        LedgerInfo::new(
+            BlockInfo::new(
+                block_info.epoch(),
+                block_info.round(),
+                block_info.id(),
+                HashValue::random(),
+                block_info.version() + 1,
+                block_info.timestamp_usecs(),
+                None,
+            ),
+            vote.ledger_info().consensus_data_hash(),
+        )
+    };
+    let mut signatures = BTreeMap::new();
+    signatures.insert(vote.author(), vote.signature().clone());
+    let ordered_ledger_info = LedgerInfoWithSignatures::new(vote.ledger_info().clone(), signatures);
+
+    // Verify the execution result of the committed block can be signed
+    let new_ledger_info = executed_ledger_info(&ordered_info);
+    let signature = safety_rules
+        .sign_commit_vote(ordered_ledger_info.clone(), new_ledger_info.clone())
+        .unwrap();
+    signature
+        .verify(&new_ledger_info, &signer.public_key())
+        .unwrap();
+
+    // Verify cannot sign the execution result of a different block
+    let p2_info = p3.block().quorum_cert().certified_block().clone();
+    assert_eq!(p2_info.round(), p2.block().round());
+    let actual_err = safety_rules
+        .sign_commit_vote(ordered_ledger_info, executed_ledger_info(&p2_info))
+        .unwrap_err();
+    assert!(matches!(
+        actual_err,
+        Error::InconsistentExecutionResult(_, _)
+    ));
+
+    // Verify cannot sign without a quorum on the ordering
+    let unsigned_ledger_info =
+        LedgerInfoWithSignatures::new(vote.ledger_info().clone(), BTreeMap::new());
+    let actual_err = safety_rules
+        .sign_commit_vote(unsigned_ledger_info, new_ledger_info)
+        .unwrap_err();
+    assert!(matches!(actual_err, Error::InvalidOrderedLedgerInfo(_)));
+}
+
+fn test_2chain_rules(safety_rules: &Callback) {
+    // build a tree of the following form:
+    // genesis---a1---a2   a4
+    //                 \__/
+    //
+    // a1 (ok), commits genesis
+    // a2 (ok), commits a1
+    // a4 without timeout certificate (not safe to vote)
+    // a4 with a timeout certificate of round 3 (ok), no commit
+    let (mut safety_rules, signer, key) = safety_rules();
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+
+    let a1 =
+        test_utils::make_proposal_with_qc(round + 1, genesis_qc.clone(), &signer, key.as_ref());
+    let a2 = make_proposal_with_parent(round + 2, &a1, None, &signer, key.as_ref());
+    let a4 = make_proposal_with_parent(round + 4, &a2, None, &signer, key.as_ref());
+
+    safety_rules.initialize(&proof).unwrap();
+
+    let mut vote = safety_rules
+        .construct_and_sign_vote_two_chain(&a1, None)
+        .unwrap();
+    assert_eq!(
+        vote.ledger_info().commit_info(),
+        genesis_qc.certified_block()
+    );
+
+    vote = safety_rules
+        .construct_and_sign_vote_two_chain(&a2, None)
+        .unwrap();
+    assert_eq!(
+        vote.ledger_info().commit_info(),
+        a2.block().quorum_cert().certified_block()
+    );
+
+    assert_eq!(
+        safety_rules.construct_and_sign_vote_two_chain(&a4, None),
+        Err(Error::NotSafeToVote(round + 4, round + 2, 0, 0))
+    );
+
+    let tc = make_timeout_cert(round + 3, a4.block().quorum_cert(), &signer);
+    vote = safety_rules
+        .construct_and_sign_vote_two_chain(&a4, Some(&tc))
+        .unwrap();
+    assert_eq!(vote.ledger_info().consensus_block_id(), HashValue::zero());
+}
+
+fn test_2chain_timeout(safety_rules: &Callback) {
+    let (mut safety_rules, signer, key) = safety_rules();
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+    let epoch = genesis_qc.certified_block().epoch();
+
+    let a1 =
+        test_utils::make_proposal_with_qc(round + 1, genesis_qc.clone(), &signer, key.as_ref());
+    let a2 = make_proposal_with_parent(round + 2, &a1, None, &signer, key.as_ref());
+    let a3 = make_proposal_with_parent(round + 3, &a2, None, &signer, key.as_ref());
+
+    safety_rules.initialize(&proof).unwrap();
+    safety_rules
+        .construct_and_sign_vote_two_chain(&a1, None)
+        .unwrap();
+    safety_rules
+        .construct_and_sign_vote_two_chain(&a2, None)
+        .unwrap();
+
+    // Verify multiple signings are the same
+    let timeout = TwoChainTimeout::new(epoch, round + 3, a3.block().quorum_cert().clone());
+    let sign1 = safety_rules.sign_timeout_with_qc(&timeout, None).unwrap();
+    let sign2 = safety_rules.sign_timeout_with_qc(&timeout, None).unwrap();
+    assert_eq!(sign1, sign2);
+
+    // Verify cannot sign a timeout that doesn't follow the quorum cert or the timeout certificate
+    let tc = make_timeout_cert(round + 3, a3.block().quorum_cert(), &signer);
+    let skip_timeout = TwoChainTimeout::new(epoch, round + 5, a3.block().quorum_cert().clone());
+    assert_eq!(
+        safety_rules.sign_timeout_with_qc(&skip_timeout, Some(&tc)),
+        Err(Error::NotSafeToTimeout(
+            round + 5,
+            round + 2,
+            round + 3,
+            round + 1
+        ))
+    );
+
+    // Verify cannot sign a timeout with a quorum cert lower than the one observed
+    let low_timeout = TwoChainTimeout::new(epoch, round + 4, genesis_qc);
+    assert_eq!(
+        safety_rules.sign_timeout_with_qc(&low_timeout, Some(&tc)),
+        Err(Error::NotSafeToTimeout(
+            round + 4,
+            round,
+            round + 3,
+            round + 1
+        ))
+    );
+
+    // Verify can sign the round following the timeout certificate
+    let next_timeout = TwoChainTimeout::new(epoch, round + 4, a3.block().quorum_cert().clone());
+    safety_rules
+        .sign_timeout_with_qc(&next_timeout, Some(&tc))
+        .unwrap();
+
+    // Verify cannot sign older rounds now
+    assert_eq!(
+        safety_rules.sign_timeout_with_qc(&timeout, None),
+        Err(Error::IncorrectLastVotedRound(round + 3, round + 4))
+    );
+
+    // Verify cannot sign for different epoch
+    let etimeout = TwoChainTimeout::new(epoch + 1, round + 5, a3.block().quorum_cert().clone());
+    assert_eq!(
+        safety_rules.sign_timeout_with_qc(&etimeout, None),
+        Err(Error::IncorrectEpoch(epoch + 1, epoch))
+    );
+}
+
 fn test_voting(safety_rules: &Callback) {
     // build a tree of the following form:
     //             _____    __________
@@ -684,7 +896,7 @@ fn test_reconcile_key(_safety_rules: &Callback) {
     let mut storage = test_utils::test_storage(&signer);
 
     let new_pub_key = storage.internal_store().rotate_key(CONSENSUS_KEY).unwrap();
-    let mut safety_rules = Box::new(SafetyRules::new(storage, false, false));
+    let mut safety_rules = Box::new(SafetyRules::new(storage, false, false, false));
 
     let (mut proof, genesis_qc) = test_utils::make_genesis(&signer);
     let round = genesis_qc.certified_block().round();
@@ -31,6 +31,7 @@ fn safety_rules(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
+            false,
             network_timeout,
         );
         let safety_rules = safety_rules_manager.client();
@@ -30,6 +30,7 @@ impl ThreadService {
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
         export_consensus_key: bool,
+        decoupled_execution: bool,
         timeout: u64,
     ) -> Self {
         let listen_port = utils::get_available_port();
@@ -42,6 +43,7 @@ impl ThreadService {
                 listen_addr,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                decoupled_execution,
                 timeout,
             )
         });
@@ -17,11 +17,12 @@ use crate::{
 };
 use anyhow::{bail, ensure, format_err, Context};
 use consensus_types::{
-    block::Block, executed_block::ExecutedBlock, quorum_cert::QuorumCert, sync_info::SyncInfo,
+    block::Block, common::Round, executed_block::ExecutedBlock, quorum_cert::QuorumCert,
+    sync_info::SyncInfo, timeout_2chain::TwoChainTimeoutCertificate,
     timeout_certificate::TimeoutCertificate,
 };
 use diem_crypto::HashValue;
-use diem_infallible::RwLock;
+use diem_infallible::{Mutex, RwLock};
 use diem_logger::prelude::*;
 use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionStatus};
 use executor_types::{Error, StateComputeResult};
@@ -94,6 +95,12 @@ pub struct BlockStore {
     storage: Arc<dyn PersistentLivenessStorage>,
     /// Used to ensure that any block stored will have a timestamp < the local time
     time_service: Arc<dyn TimeService>,
+    /// With decoupled execution the committed blocks are only ordered, they are executed and
+    /// committed to the ledger later on.
+    decoupled_execution: bool,
+    /// The blocks pruned from the tree with decoupled execution, along with the round of the new
+    /// root, waiting for the ledger to reach that round before being deleted from storage.
+    pending_prunes: Mutex<VecDeque<(Round, VecDeque<HashValue>)>>,
 }
 
 impl BlockStore {
@@ -103,8 +110,10 @@ impl BlockStore {
         state_computer: Arc<dyn StateComputer>,
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
+        decoupled_execution: bool,
     ) -> Self {
         let highest_tc = initial_data.highest_timeout_certificate();
+        let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
         let (root, root_metadata, blocks, quorum_certs) = initial_data.take();
         Self::build(
             root,
@@ -112,10 +121,12 @@ impl BlockStore {
             blocks,
             quorum_certs,
             highest_tc,
+            highest_2chain_tc,
             state_computer,
             storage,
             max_pruned_blocks_in_mem,
             time_service,
+            decoupled_execution,
         )
     }
 
@@ -125,27 +136,31 @@ impl BlockStore {
         blocks: Vec<Block>,
         quorum_certs: Vec<QuorumCert>,
         highest_timeout_cert: Option<TimeoutCertificate>,
+        highest_2chain_timeout_cert: Option<TwoChainTimeoutCertificate>,
         state_computer: Arc<dyn StateComputer>,
         storage: Arc<dyn PersistentLivenessStorage>,
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
+        decoupled_execution: bool,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_li) = root;
-        //verify root is correct
-        assert_eq!(
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-            "root qc version {} doesn't match committed trees {}",
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-        );
-        assert_eq!(
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-            "root qc state id {} doesn't match committed trees {}",
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-        );
+        //verify root is correct, the QCs only certify the order with decoupled execution
+        if !decoupled_execution {
+            assert_eq!(
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+                "root qc version {} doesn't match committed trees {}",
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+            );
+            assert_eq!(
+                root_qc.certified_block().executed_state_id(),
+                root_metadata.accu_hash,
+                "root qc state id {} doesn't match committed trees {}",
+                root_qc.certified_block().executed_state_id(),
+                root_metadata.accu_hash,
+            );
+        }
 
         let result = StateComputeResult::new(
             root_metadata.accu_hash,
@@ -170,12 +185,15 @@ impl BlockStore {
             root_li,
             max_pruned_blocks_in_mem,
             highest_timeout_cert.map(Arc::new),
+            highest_2chain_timeout_cert.map(Arc::new),
         );
         let block_store = Self {
             inner: Arc::new(RwLock::new(tree)),
             state_computer,
             storage,
             time_service,
+            decoupled_execution,
+            pending_prunes: Mutex::new(VecDeque::new()),
         };
         for block in blocks {
             block_store
@@ -249,16 +267,21 @@ impl BlockStore {
         let max_pruned_blocks_in_mem = self.inner.read().max_pruned_blocks_in_mem();
         // Rollover the previous highest TC from the old tree to the new one.
         let prev_htc = self.highest_timeout_cert().map(|tc| tc.as_ref().clone());
+        let prev_2chain_htc = self
+            .highest_2chain_timeout_cert()
+            .map(|tc| tc.as_ref().clone());
         let BlockStore { inner, .. } = Self::build(
             root,
             root_metadata,
             blocks,
             quorum_certs,
             prev_htc,
+            prev_2chain_htc,
             Arc::clone(&self.state_computer),
             Arc::clone(&self.storage),
             max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
+            self.decoupled_execution,
         );
         let to_remove = self.inner.read().get_all_block_id();
         if let Err(e) = self.storage.prune_tree(to_remove) {
@@ -377,6 +400,25 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Replace the highest two-chain timeout certificate in case the given one has a higher round.
+    /// In case a timeout certificate is updated, persist it to storage.
+    pub fn insert_2chain_timeout_certificate(
+        &self,
+        tc: Arc<TwoChainTimeoutCertificate>,
+    ) -> anyhow::Result<()> {
+        let cur_tc_round = self
+            .highest_2chain_timeout_cert()
+            .map_or(0, |tc| tc.round());
+        if tc.round() <= cur_tc_round {
+            return Ok(());
+        }
+        self.storage
+            .save_highest_2chain_timeout_cert(tc.as_ref().clone())
+            .context("Timeout certificate insert failed when persisting to DB")?;
+        self.inner.write().replace_2chain_timeout_cert(tc);
+        Ok(())
+    }
+
     /// Prune the tree up to next_root_id (keep next_root_id's block).  Any branches not part of
     /// the next_root_id's tree should be removed as well.
     ///
@@ -390,7 +432,11 @@ impl BlockStore {
     /// Returns the block ids of the blocks removed.
     fn prune_tree(&self, next_root_id: HashValue) -> VecDeque<HashValue> {
         let id_to_remove = self.inner.read().find_blocks_to_prune(next_root_id);
-        if let Err(e) = self
+        if self.decoupled_execution {
+            if let Some(next_root) = self.get_block(next_root_id) {
+                self.prune_executed_blocks(next_root.round(), id_to_remove.clone());
+            }
+        } else if let Err(e) = self
             .storage
             .prune_tree(id_to_remove.clone().into_iter().collect())
         {
@@ -404,6 +450,42 @@ impl BlockStore {
             .process_pruned_blocks(next_root_id, id_to_remove.clone());
         id_to_remove
     }
+
+    /// With decoupled execution, the recovery after a restart starts from the last block committed
+    /// to the ledger, which may be behind the root. The pruned blocks are kept in storage until the
+    /// ledger reaches the root they were pruned for.
+    fn prune_executed_blocks(&self, next_root_round: Round, id_to_remove: VecDeque<HashValue>) {
+        let mut pending_prunes = self.pending_prunes.lock();
+        pending_prunes.push_back((next_root_round, id_to_remove));
+
+        let ledger_info = match self.storage.diem_db().get_latest_ledger_info() {
+            Ok(ledger_info) => ledger_info,
+            Err(e) => {
+                error!(error = ?e, "fail to read the latest ledger info");
+                return;
+            }
+        };
+        let epoch = self.root().epoch();
+        let executed = (
+            ledger_info.ledger_info().epoch(),
+            ledger_info.ledger_info().round(),
+        );
+        let mut id_to_remove = vec![];
+        while let Some((round, _)) = pending_prunes.front() {
+            if executed < (epoch, *round) {
+                break;
+            }
+            let (_, ids) = pending_prunes.pop_front().expect("checked above");
+            id_to_remove.extend(ids);
+        }
+        if id_to_remove.is_empty() {
+            return;
+        }
+        if let Err(e) = self.storage.prune_tree(id_to_remove) {
+            // it's fine to fail here, the next restart will clean up dangling blocks.
+            error!(error = ?e, "fail to delete block");
+        }
+    }
 }
 
 impl BlockReader for BlockStore {
@@ -443,11 +525,17 @@ impl BlockReader for BlockStore {
         self.inner.read().highest_timeout_cert()
     }
 
+    fn highest_2chain_timeout_cert(&self) -> Option<Arc<TwoChainTimeoutCertificate>> {
+        self.inner.read().highest_2chain_timeout_cert()
+    }
+
     fn sync_info(&self) -> SyncInfo {
         SyncInfo::new(
             self.highest_quorum_cert().as_ref().clone(),
             self.highest_commit_cert().as_ref().clone(),
             self.highest_timeout_cert().map(|tc| tc.as_ref().clone()),
+            self.highest_2chain_timeout_cert()
+                .map(|tc| tc.as_ref().clone()),
         )
     }
 }
@@ -4,7 +4,8 @@
 use crate::counters;
 use anyhow::bail;
 use consensus_types::{
-    executed_block::ExecutedBlock, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate,
+    executed_block::ExecutedBlock, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, timeout_certificate::TimeoutCertificate,
 };
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
@@ -69,6 +70,8 @@ pub struct BlockTree {
     highest_quorum_cert: Arc<QuorumCert>,
     /// The highest timeout certificate (if any).
     highest_timeout_cert: Option<Arc<TimeoutCertificate>>,
+    /// The highest timeout certificate with the two-chain commit rule (if any).
+    highest_2chain_timeout_cert: Option<Arc<TwoChainTimeoutCertificate>>,
     /// The quorum certificate that has highest commit info.
     highest_commit_cert: Arc<QuorumCert>,
     /// Map of block id to its completed quorum certificate (2f + 1 votes)
@@ -86,6 +89,7 @@ impl BlockTree {
         root_ledger_info: QuorumCert,
         max_pruned_blocks_in_mem: usize,
         highest_timeout_cert: Option<Arc<TimeoutCertificate>>,
+        highest_2chain_timeout_cert: Option<Arc<TwoChainTimeoutCertificate>>,
     ) -> Self {
         assert_eq!(
             root.id(),
@@ -113,6 +117,7 @@ impl BlockTree {
             highest_certified_block_id: root_id,
             highest_quorum_cert: Arc::clone(&root_quorum_cert),
             highest_timeout_cert,
+            highest_2chain_timeout_cert,
             highest_commit_cert: Arc::new(root_ledger_info),
             id_to_quorum_cert,
             pruned_block_ids,
@@ -173,6 +178,15 @@ impl BlockTree {
         self.highest_timeout_cert.replace(tc);
     }
 
+    pub(super) fn highest_2chain_timeout_cert(&self) -> Option<Arc<TwoChainTimeoutCertificate>> {
+        self.highest_2chain_timeout_cert.clone()
+    }
+
+    /// Replace highest two-chain timeout cert with the given value.
+    pub(super) fn replace_2chain_timeout_cert(&mut self, tc: Arc<TwoChainTimeoutCertificate>) {
+        self.highest_2chain_timeout_cert.replace(tc);
+    }
+
     pub(super) fn highest_commit_cert(&self) -> Arc<QuorumCert> {
         Arc::clone(&self.highest_commit_cert)
     }
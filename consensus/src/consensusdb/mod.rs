@@ -58,9 +58,11 @@ impl ConsensusDB {
         Option<Vec<u8>>,
         Vec<Block>,
         Vec<QuorumCert>,
+        Option<Vec<u8>>,
     )> {
         let last_vote = self.get_last_vote()?;
         let highest_timeout_certificate = self.get_highest_timeout_certificate()?;
+        let highest_2chain_timeout_certificate = self.get_highest_2chain_timeout_certificate()?;
         let consensus_blocks = self
             .get_blocks()?
             .into_iter()
@@ -76,6 +78,7 @@ impl ConsensusDB {
             highest_timeout_certificate,
             consensus_blocks,
             consensus_qcs,
+            highest_2chain_timeout_certificate,
         ))
     }
 
@@ -92,12 +95,31 @@ impl ConsensusDB {
         Ok(())
     }
 
+    pub fn save_highest_2chain_timeout_certificate(
+        &self,
+        highest_timeout_certificate: Vec<u8>,
+    ) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<SingleEntrySchema>(
+            &SingleEntryKey::Highest2ChainTimeoutCertificate,
+            &highest_timeout_certificate,
+        )?;
+        self.commit(batch)?;
+        Ok(())
+    }
+
     pub fn save_vote(&self, last_vote: Vec<u8>) -> Result<(), DbError> {
         let mut batch = SchemaBatch::new();
         batch.put::<SingleEntrySchema>(&SingleEntryKey::LastVoteMsg, &last_vote)?;
         self.commit(batch)
     }
 
+    pub fn save_commit_vote(&self, last_commit_vote: Vec<u8>) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<SingleEntrySchema>(&SingleEntryKey::LastCommitVoteMsg, &last_commit_vote)?;
+        self.commit(batch)
+    }
+
     pub fn save_blocks_and_quorum_certificates(
         &self,
         block_data: Vec<Block>,
@@ -152,6 +174,20 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Get the latest timeout certificate with the two-chain commit rule.
+    fn get_highest_2chain_timeout_certificate(&self) -> Result<Option<Vec<u8>>, DbError> {
+        Ok(self
+            .db
+            .get::<SingleEntrySchema>(&SingleEntryKey::Highest2ChainTimeoutCertificate)?)
+    }
+
+    /// Delete the timeout certificate with the two-chain commit rule
+    pub fn delete_highest_2chain_timeout_certificate(&self) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        batch.delete::<SingleEntrySchema>(&SingleEntryKey::Highest2ChainTimeoutCertificate)?;
+        self.commit(batch)
+    }
+
     /// Get serialized latest vote (if available)
     fn get_last_vote(&self) -> Result<Option<Vec<u8>>, DbError> {
         Ok(self
@@ -166,6 +202,19 @@ impl ConsensusDB {
         Ok(())
     }
 
+    /// Get serialized latest commit vote (if available)
+    pub fn get_last_commit_vote(&self) -> Result<Option<Vec<u8>>, DbError> {
+        Ok(self
+            .db
+            .get::<SingleEntrySchema>(&SingleEntryKey::LastCommitVoteMsg)?)
+    }
+
+    pub fn delete_last_commit_vote_msg(&self) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        batch.delete::<SingleEntrySchema>(&SingleEntryKey::LastCommitVoteMsg)?;
+        self.commit(batch)
+    }
+
     /// Get all consensus blocks.
     fn get_blocks(&self) -> Result<HashMap<HashValue, Block>, DbError> {
         let mut iter = self.db.iter::<BlockSchema>(ReadOptions::default())?;
@@ -35,6 +35,10 @@ pub enum SingleEntryKey {
     HighestTimeoutCertificate = 0,
     // Used to store the last vote
     LastVoteMsg = 1,
+    // Used to store the highest timeout certificate with the two-chain commit rule
+    Highest2ChainTimeoutCertificate = 2,
+    // Used to store the last commit vote with decoupled execution
+    LastCommitVoteMsg = 3,
 }
 
 impl KeyCodec<SingleEntrySchema> for SingleEntryKey {
@@ -5,8 +5,15 @@ use crate::{
     block_storage::BlockStore,
     counters,
     error::{error_kind, DbError},
+    experimental::{
+        commit_phase::CommitPhase, execution_phase::ExecutionPhase,
+        ordering_state_computer::OrderingStateComputer,
+    },
     liveness::{
-        leader_reputation::{ActiveInactiveHeuristic, DiemDBBackend, LeaderReputation},
+        leader_reputation::{
+            ActiveInactiveHeuristic, DiemDBBackend, LeaderReputation, ProposerAndVoterHeuristic,
+            ReputationHeuristic,
+        },
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
@@ -25,10 +32,13 @@ use crate::{
 use anyhow::{bail, ensure, Context};
 use channel::diem_channel;
 use consensus_types::{
+    commit_vote::CommitVote,
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
 };
-use diem_config::config::{ConsensusConfig, ConsensusProposerType, NodeConfig};
+use diem_config::config::{
+    ConsensusConfig, ConsensusProposerType, NodeConfig, ReputationHeuristicType,
+};
 use diem_infallible::duration_since_epoch;
 use diem_logger::prelude::*;
 use diem_metrics::monitor;
@@ -36,13 +46,17 @@ use diem_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
-    on_chain_config::{OnChainConfigPayload, ValidatorSet},
+    on_chain_config::{OnChainConfigPayload, OnChainConsensusConfig, ValidatorSet},
 };
-use futures::{select, StreamExt};
+use futures::{channel::mpsc, select, StreamExt};
 use network::protocols::network::Event;
 use safety_rules::SafetyRulesManager;
 use std::{cmp::Ordering, sync::Arc, time::Duration};
 
+/// The size of the channels between consensus and the phases executing and committing the ordered
+/// blocks, with decoupled execution.
+const DECOUPLED_EXECUTION_CHANNEL_SIZE: usize = 100;
+
 /// RecoveryManager is used to process events in order to sync up with peer if we can't recover from local consensusdb
 /// RoundManager is used for normal event handling.
 /// We suppress clippy warning here because we expect most of the time we will have RoundManager
@@ -82,6 +96,10 @@ pub struct EpochManager {
     safety_rules_manager: SafetyRulesManager,
     processor: Option<RoundProcessor>,
     reconfig_events: diem_channel::Receiver<(), OnChainConfigPayload>,
+    // Sends the commit votes and decisions to the commit phase, with decoupled execution.
+    commit_msg_tx: Option<mpsc::Sender<VerifiedEvent>>,
+    // The consensus config of the current epoch agreed on-chain.
+    onchain_config: OnChainConsensusConfig,
 }
 
 impl EpochManager {
@@ -113,6 +131,8 @@ impl EpochManager {
             safety_rules_manager,
             processor: None,
             reconfig_events,
+            commit_msg_tx: None,
+            onchain_config: OnChainConsensusConfig::default(),
         }
     }
 
@@ -169,12 +189,34 @@ impl EpochManager {
                 ))
             }
             ConsensusProposerType::LeaderReputation(heuristic_config) => {
-                let backend = Box::new(DiemDBBackend::new(proposers.len(), self.storage.diem_db()));
-                let heuristic = Box::new(ActiveInactiveHeuristic::new(
-                    self.author,
-                    heuristic_config.active_weights,
-                    heuristic_config.inactive_weights,
-                ));
+                let proposer_window_size =
+                    proposers.len() * heuristic_config.proposer_window_num_validators_multiplier;
+                let voter_window_size =
+                    proposers.len() * heuristic_config.voter_window_num_validators_multiplier;
+                let (window_size, heuristic): (usize, Box<dyn ReputationHeuristic>) =
+                    match heuristic_config.heuristic {
+                        ReputationHeuristicType::ActiveInactive => (
+                            voter_window_size,
+                            Box::new(ActiveInactiveHeuristic::new(
+                                self.author,
+                                heuristic_config.active_weights,
+                                heuristic_config.inactive_weights,
+                            )),
+                        ),
+                        ReputationHeuristicType::ProposerAndVoter => (
+                            proposer_window_size.max(voter_window_size),
+                            Box::new(ProposerAndVoterHeuristic::new(
+                                self.author,
+                                heuristic_config.active_weights,
+                                heuristic_config.inactive_weights,
+                                heuristic_config.failed_weights,
+                                heuristic_config.failure_threshold_percent,
+                                proposer_window_size,
+                                voter_window_size,
+                            )),
+                        ),
+                    };
+                let backend = Box::new(DiemDBBackend::new(window_size, self.storage.diem_db()));
                 Box::new(LeaderReputation::new(proposers, backend, heuristic))
             }
             ConsensusProposerType::RoundProposer(round_proposers) => {
@@ -275,9 +317,68 @@ impl EpochManager {
         Ok(())
     }
 
+    /// With decoupled execution consensus only orders the blocks, this starts the phases executing
+    /// and committing the ordered blocks of the epoch and returns the StateComputer ordering them.
+    fn spawn_decoupled_execution(
+        &mut self,
+        epoch_state: &EpochState,
+        last_commit_vote: Option<CommitVote>,
+    ) -> Arc<dyn StateComputer> {
+        let (ordered_blocks_tx, ordered_blocks_rx) =
+            mpsc::channel(DECOUPLED_EXECUTION_CHANNEL_SIZE);
+        let (executed_blocks_tx, executed_blocks_rx) =
+            mpsc::channel(DECOUPLED_EXECUTION_CHANNEL_SIZE);
+        let (commit_msg_tx, commit_msg_rx) = mpsc::channel(DECOUPLED_EXECUTION_CHANNEL_SIZE);
+
+        let execution_phase = ExecutionPhase::new(
+            Arc::clone(&self.state_computer),
+            self.txn_manager.clone(),
+            ordered_blocks_rx,
+            executed_blocks_tx,
+        );
+
+        let mut safety_rules =
+            MetricsSafetyRules::new(self.safety_rules_manager.client(), self.storage.clone());
+        if let Err(error) = safety_rules.perform_initialize() {
+            error!(
+                epoch = epoch_state.epoch,
+                error = error,
+                "Unable to initialize safety rules for the commit phase.",
+            );
+        }
+        let network_sender = NetworkSender::new(
+            self.author,
+            self.network_sender.clone(),
+            self.self_sender.clone(),
+            epoch_state.verifier.clone(),
+        );
+        let commit_phase = CommitPhase::new(
+            self.author,
+            epoch_state.clone(),
+            Arc::clone(&self.state_computer),
+            safety_rules,
+            network_sender,
+            self.storage.clone(),
+            executed_blocks_rx,
+            commit_msg_rx,
+            last_commit_vote,
+        );
+
+        tokio::spawn(execution_phase.start());
+        tokio::spawn(commit_phase.start());
+        self.commit_msg_tx = Some(commit_msg_tx);
+
+        Arc::new(OrderingStateComputer::new(
+            ordered_blocks_tx,
+            Arc::clone(&self.state_computer),
+        ))
+    }
+
     async fn start_round_manager(&mut self, recovery_data: RecoveryData, epoch_state: EpochState) {
         // Release the previous RoundManager, especially the SafetyRule client
         self.processor = None;
+        // Stop the commit phase of the previous epoch
+        self.commit_msg_tx = None;
         let epoch = epoch_state.epoch;
         counters::EPOCH.set(epoch_state.epoch as i64);
         counters::CURRENT_EPOCH_VALIDATORS.set(epoch_state.verifier.len() as i64);
@@ -285,18 +386,37 @@ impl EpochManager {
             epoch = epoch_state.epoch,
             validators = epoch_state.verifier.to_string(),
             root_block = recovery_data.root_block(),
+            two_chain = self.onchain_config.two_chain(),
             "Starting new epoch",
         );
         let last_vote = recovery_data.last_vote();
 
+        let (state_computer, ordered_proof) = if self.config.safety_rules.decoupled_execution {
+            info!(epoch = epoch, "Start decoupled execution");
+            let ordered_proof = recovery_data.ordered_proof();
+            let state_computer =
+                self.spawn_decoupled_execution(&epoch_state, recovery_data.last_commit_vote());
+            (state_computer, ordered_proof)
+        } else {
+            (Arc::clone(&self.state_computer), None)
+        };
+
         info!(epoch = epoch, "Create BlockStore");
         let block_store = Arc::new(BlockStore::new(
             Arc::clone(&self.storage),
             recovery_data,
-            Arc::clone(&self.state_computer),
+            state_computer,
             self.config.max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
+            self.config.safety_rules.decoupled_execution,
         ));
+        // the blocks ordered before the restart but not committed to the ledger yet are sent to
+        // the execution phase again
+        if let Some(ordered_proof) = ordered_proof {
+            if let Err(e) = block_store.commit(ordered_proof).await {
+                error!(epoch = epoch, error = ?e, "Failed to execute the recovered ordered blocks");
+            }
+        }
 
         info!(epoch = epoch, "Update SafetyRules");
 
@@ -345,6 +465,8 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.storage.clone(),
             self.config.sync_only,
+            self.config.safety_rules.decoupled_execution,
+            self.onchain_config.clone(),
         );
         processor.start(last_vote).await;
         self.processor = Some(RoundProcessor::Normal(processor));
@@ -367,6 +489,7 @@ impl EpochManager {
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
         );
+        self.commit_msg_tx = None;
         self.processor = Some(RoundProcessor::Recovery(RecoveryManager::new(
             epoch_state,
             network_sender,
@@ -385,6 +508,16 @@ impl EpochManager {
             epoch: payload.epoch(),
             verifier: (&validator_set).into(),
         };
+        // Every validator of the epoch has to commit with the same rule, so the fallback can't
+        // depend on local config: without a readable on-chain config, all use the three-chain rule.
+        self.onchain_config = payload.get().unwrap_or_else(|error| {
+            warn!(
+                epoch = payload.epoch(),
+                error = ?error,
+                "Failed to read on-chain consensus config, using the three-chain commit rule",
+            );
+            OnChainConsensusConfig::default()
+        });
 
         match self.storage.start() {
             LivenessStorageData::RecoveryData(initial_data) => {
@@ -433,7 +566,11 @@ impl EpochManager {
         msg: ConsensusMsg,
     ) -> anyhow::Result<Option<UnverifiedEvent>> {
         match msg {
-            ConsensusMsg::ProposalMsg(_) | ConsensusMsg::SyncInfo(_) | ConsensusMsg::VoteMsg(_) => {
+            ConsensusMsg::ProposalMsg(_)
+            | ConsensusMsg::SyncInfo(_)
+            | ConsensusMsg::VoteMsg(_)
+            | ConsensusMsg::CommitVoteMsg(_)
+            | ConsensusMsg::CommitDecisionMsg(_) => {
                 let event: UnverifiedEvent = msg.into();
                 if event.epoch() == self.epoch() {
                     return Ok(Some(event));
@@ -484,12 +621,22 @@ impl EpochManager {
         peer_id: AccountAddress,
         event: VerifiedEvent,
     ) -> anyhow::Result<()> {
+        if matches!(
+            event,
+            VerifiedEvent::CommitVote(_) | VerifiedEvent::CommitDecision(_)
+        ) {
+            return self.forward_commit_msg(event);
+        }
+
         match self.processor_mut() {
             RoundProcessor::Recovery(p) => {
                 let recovery_data = match event {
                     VerifiedEvent::ProposalMsg(proposal) => p.process_proposal_msg(*proposal).await,
                     VerifiedEvent::VoteMsg(vote) => p.process_vote_msg(*vote).await,
                     VerifiedEvent::SyncInfo(sync_info) => p.sync_up(&sync_info, peer_id).await,
+                    VerifiedEvent::CommitVote(_) | VerifiedEvent::CommitDecision(_) => {
+                        unreachable!("[EpochManager] Commit messages go to the commit phase")
+                    }
                 }?;
                 let epoch_state = p.epoch_state().clone();
                 info!("Recovered from SyncProcessor");
@@ -507,10 +654,23 @@ impl EpochManager {
                     "process_sync_info",
                     p.process_sync_info_msg(*sync_info, peer_id).await
                 ),
+                VerifiedEvent::CommitVote(_) | VerifiedEvent::CommitDecision(_) => {
+                    unreachable!("[EpochManager] Commit messages go to the commit phase")
+                }
             },
         }
     }
 
+    fn forward_commit_msg(&mut self, event: VerifiedEvent) -> anyhow::Result<()> {
+        let commit_msg_tx = self
+            .commit_msg_tx
+            .as_mut()
+            .context("[EpochManager] Commit message received without decoupled execution")?;
+        commit_msg_tx
+            .try_send(event)
+            .map_err(|e| anyhow::anyhow!("[EpochManager] Failed to forward commit message: {}", e))
+    }
+
     fn processor_mut(&mut self) -> &mut RoundProcessor {
         self.processor
             .as_mut()
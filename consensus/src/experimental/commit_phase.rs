@@ -0,0 +1,347 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::execution_phase::ExecutedBlocks, metrics_safety_rules::MetricsSafetyRules,
+    network::NetworkSender, network_interface::ConsensusMsg,
+    persistent_liveness_storage::PersistentLivenessStorage, round_manager::VerifiedEvent,
+    state_replication::StateComputer,
+};
+use anyhow::{bail, Context};
+use consensus_types::{
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
+    common::{Author, Round},
+};
+use diem_logger::prelude::*;
+use diem_types::{
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
+use futures::{channel::mpsc, StreamExt};
+use safety_rules::TSafetyRules;
+use std::{cmp::Ordering, collections::BTreeMap, sync::Arc, time::Duration};
+use tokio::time;
+
+/// How often the commit vote of the pending blocks is broadcast again, in case the first
+/// broadcast got lost.
+const COMMIT_VOTE_REBROADCAST_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// The executed blocks waiting for a quorum of commit votes on their execution result.
+struct PendingCommit {
+    executed_blocks: ExecutedBlocks,
+    /// The LedgerInfo with the execution result of the last block, and the signatures collected
+    /// on it so far.
+    commit_ledger_info: LedgerInfoWithSignatures,
+    commit_vote: CommitVote,
+}
+
+impl PendingCommit {
+    fn round(&self) -> Round {
+        self.commit_ledger_info.ledger_info().round()
+    }
+}
+
+/// Signs the execution result of the executed blocks and commits them once a quorum of
+/// validators signed the same result.
+pub struct CommitPhase {
+    author: Author,
+    epoch_state: EpochState,
+    execution_proxy: Arc<dyn StateComputer>,
+    safety_rules: MetricsSafetyRules,
+    network_sender: NetworkSender,
+    storage: Arc<dyn PersistentLivenessStorage>,
+    executed_blocks_rx: mpsc::Receiver<ExecutedBlocks>,
+    commit_msg_rx: mpsc::Receiver<VerifiedEvent>,
+    /// The last commit vote sent, persisted so that the execution result of the blocks executed
+    /// again after a restart is never signed twice.
+    last_commit_vote: Option<CommitVote>,
+    pending_commit: Option<PendingCommit>,
+    /// The highest commit decision received for blocks that aren't executed yet.
+    pending_decision: Option<CommitDecision>,
+    /// The decision on the last blocks committed, sent again to the validators lagging behind.
+    last_decision: Option<CommitDecision>,
+    committed_round: Round,
+}
+
+impl CommitPhase {
+    pub fn new(
+        author: Author,
+        epoch_state: EpochState,
+        execution_proxy: Arc<dyn StateComputer>,
+        safety_rules: MetricsSafetyRules,
+        network_sender: NetworkSender,
+        storage: Arc<dyn PersistentLivenessStorage>,
+        executed_blocks_rx: mpsc::Receiver<ExecutedBlocks>,
+        commit_msg_rx: mpsc::Receiver<VerifiedEvent>,
+        last_commit_vote: Option<CommitVote>,
+    ) -> Self {
+        Self {
+            author,
+            epoch_state,
+            execution_proxy,
+            safety_rules,
+            network_sender,
+            storage,
+            executed_blocks_rx,
+            commit_msg_rx,
+            last_commit_vote,
+            pending_commit: None,
+            pending_decision: None,
+            last_decision: None,
+            committed_round: 0,
+        }
+    }
+
+    async fn process_executed_blocks(
+        &mut self,
+        executed_blocks: ExecutedBlocks,
+    ) -> anyhow::Result<()> {
+        let last_block = executed_blocks
+            .blocks
+            .last()
+            .context("[CommitPhase] Received no executed blocks")?;
+        if last_block.round() <= self.committed_round {
+            // already committed via state sync
+            return Ok(());
+        }
+        let commit_ledger_info = LedgerInfo::new(
+            last_block.block_info(),
+            executed_blocks
+                .ordered_proof
+                .ledger_info()
+                .consensus_data_hash(),
+        );
+
+        if let Some(decision) = self.pending_decision.take() {
+            match decision.round().cmp(&last_block.round()) {
+                Ordering::Greater => return self.sync_to_decision(decision).await,
+                Ordering::Equal => {
+                    if decision.ledger_info().ledger_info() == &commit_ledger_info {
+                        let commit_proof = decision.ledger_info().clone();
+                        return self.commit(executed_blocks, commit_proof).await;
+                    }
+                    error!(
+                        "[CommitPhase] Executed {} doesn't match the commit decision {}",
+                        commit_ledger_info, decision
+                    );
+                }
+                Ordering::Less => (),
+            }
+        }
+
+        let commit_vote = match self.last_commit_vote.as_ref() {
+            // the blocks were executed and voted on already before a restart
+            Some(vote) if vote.ledger_info() == &commit_ledger_info => vote.clone(),
+            Some(vote) if vote.round() == commit_ledger_info.round() => bail!(
+                "[CommitPhase] Executed {} doesn't match the commit vote sent before {}",
+                commit_ledger_info,
+                vote
+            ),
+            _ => {
+                let signature = self
+                    .safety_rules
+                    .sign_commit_vote(
+                        executed_blocks.ordered_proof.clone(),
+                        commit_ledger_info.clone(),
+                    )
+                    .context("[CommitPhase] SafetyRules rejected the execution result")?;
+                let commit_vote = CommitVote::new_with_signature(
+                    self.author,
+                    commit_ledger_info.clone(),
+                    signature,
+                );
+                self.storage
+                    .save_commit_vote(&commit_vote)
+                    .context("[CommitPhase] Failed to persist the commit vote")?;
+                self.last_commit_vote = Some(commit_vote.clone());
+                commit_vote
+            }
+        };
+        self.pending_commit = Some(PendingCommit {
+            executed_blocks,
+            commit_ledger_info: LedgerInfoWithSignatures::new(commit_ledger_info, BTreeMap::new()),
+            commit_vote: commit_vote.clone(),
+        });
+        self.network_sender
+            .broadcast(ConsensusMsg::CommitVoteMsg(Box::new(commit_vote)))
+            .await;
+        Ok(())
+    }
+
+    async fn process_commit_vote(&mut self, commit_vote: CommitVote) -> anyhow::Result<()> {
+        if let Some(pending_commit) = self.pending_commit.as_mut() {
+            if commit_vote.ledger_info() == pending_commit.commit_ledger_info.ledger_info() {
+                pending_commit
+                    .commit_ledger_info
+                    .add_signature(commit_vote.author(), commit_vote.signature().clone());
+                let has_quorum = self
+                    .epoch_state
+                    .verifier
+                    .check_voting_power(pending_commit.commit_ledger_info.signatures().keys())
+                    .is_ok();
+                if has_quorum {
+                    let pending_commit = self.pending_commit.take().expect("checked above");
+                    let decision = CommitDecision::new(pending_commit.commit_ledger_info.clone());
+                    self.commit(
+                        pending_commit.executed_blocks,
+                        pending_commit.commit_ledger_info,
+                    )
+                    .await?;
+                    self.network_sender
+                        .broadcast(ConsensusMsg::CommitDecisionMsg(Box::new(decision)))
+                        .await;
+                }
+                return Ok(());
+            }
+            if commit_vote.round() == pending_commit.round() {
+                warn!(
+                    remote_peer = commit_vote.author(),
+                    "[CommitPhase] Commit vote {} doesn't match the local execution result {}",
+                    commit_vote,
+                    pending_commit.commit_ledger_info.ledger_info()
+                );
+            }
+        }
+        // help the validators still waiting for the votes on blocks committed already
+        if let Some(decision) = self.last_decision.as_ref() {
+            if commit_vote.round() <= decision.round() && commit_vote.author() != self.author {
+                let msg = ConsensusMsg::CommitDecisionMsg(Box::new(decision.clone()));
+                self.network_sender.broadcast(msg).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn process_commit_decision(&mut self, decision: CommitDecision) -> anyhow::Result<()> {
+        if decision.round() <= self.committed_round {
+            return Ok(());
+        }
+        let pending_round = match self.pending_commit.as_ref() {
+            Some(pending_commit) => pending_commit.round(),
+            None => {
+                if self
+                    .pending_decision
+                    .as_ref()
+                    .map_or(true, |pending| pending.round() < decision.round())
+                {
+                    self.pending_decision = Some(decision);
+                }
+                return Ok(());
+            }
+        };
+        match decision.round().cmp(&pending_round) {
+            Ordering::Greater => {
+                self.pending_commit = None;
+                self.sync_to_decision(decision).await
+            }
+            Ordering::Equal => {
+                let pending_commit = self.pending_commit.take().expect("checked above");
+                if decision.ledger_info().ledger_info()
+                    != pending_commit.commit_ledger_info.ledger_info()
+                {
+                    error!(
+                        "[CommitPhase] Executed {} doesn't match the commit decision {}",
+                        pending_commit.commit_ledger_info.ledger_info(),
+                        decision
+                    );
+                    self.pending_commit = Some(pending_commit);
+                    return Ok(());
+                }
+                self.commit(
+                    pending_commit.executed_blocks,
+                    decision.ledger_info().clone(),
+                )
+                .await
+            }
+            Ordering::Less => Ok(()),
+        }
+    }
+
+    async fn commit(
+        &mut self,
+        executed_blocks: ExecutedBlocks,
+        commit_proof: LedgerInfoWithSignatures,
+    ) -> anyhow::Result<()> {
+        let block_ids = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+        self.execution_proxy
+            .commit(block_ids, commit_proof.clone())
+            .await
+            .context("[CommitPhase] Failed to commit the executed blocks")?;
+        self.committed(commit_proof).await;
+        Ok(())
+    }
+
+    /// The blocks up to the decision were committed by other validators, catch up via state sync
+    /// instead of waiting for the local execution.
+    async fn sync_to_decision(&mut self, decision: CommitDecision) -> anyhow::Result<()> {
+        self.execution_proxy
+            .sync_to(decision.ledger_info().clone())
+            .await
+            .context("[CommitPhase] Failed to sync to the commit decision")?;
+        self.committed(decision.ledger_info().clone()).await;
+        Ok(())
+    }
+
+    async fn committed(&mut self, commit_proof: LedgerInfoWithSignatures) {
+        info!(
+            "[CommitPhase] Committed {}",
+            commit_proof.ledger_info().commit_info()
+        );
+        self.committed_round = commit_proof.ledger_info().round();
+        self.last_decision = Some(CommitDecision::new(commit_proof.clone()));
+        // consensus only orders blocks, the epoch ends once the reconfiguration is committed
+        if commit_proof.ledger_info().ends_epoch() {
+            self.network_sender
+                .notify_epoch_change(EpochChangeProof::new(
+                    vec![commit_proof],
+                    /* more = */ false,
+                ))
+                .await;
+        }
+    }
+
+    async fn rebroadcast_commit_vote(&mut self) {
+        if let Some(pending_commit) = self.pending_commit.as_ref() {
+            let msg = ConsensusMsg::CommitVoteMsg(Box::new(pending_commit.commit_vote.clone()));
+            self.network_sender.broadcast(msg).await;
+        }
+    }
+
+    async fn process_commit_msg(&mut self, event: VerifiedEvent) -> anyhow::Result<()> {
+        match event {
+            VerifiedEvent::CommitVote(commit_vote) => self.process_commit_vote(*commit_vote).await,
+            VerifiedEvent::CommitDecision(decision) => {
+                self.process_commit_decision(*decision).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn start(mut self) {
+        let mut rebroadcast_interval = time::interval(COMMIT_VOTE_REBROADCAST_INTERVAL);
+        loop {
+            // only take the next executed blocks once the pending ones are committed
+            let waiting_for_votes = self.pending_commit.is_some();
+            let result = tokio::select! {
+                Some(executed_blocks) = self.executed_blocks_rx.next(), if !waiting_for_votes => {
+                    self.process_executed_blocks(executed_blocks).await
+                }
+                msg = self.commit_msg_rx.next() => match msg {
+                    Some(event) => self.process_commit_msg(event).await,
+                    // the epoch is over
+                    None => break,
+                },
+                _ = rebroadcast_interval.tick(), if waiting_for_votes => {
+                    self.rebroadcast_commit_vote().await;
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                error!(error = ?e, "[CommitPhase] Failed to process");
+            }
+        }
+        debug!("[CommitPhase] stopped");
+    }
+}
@@ -0,0 +1,424 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::{commit_phase::CommitPhase, execution_phase::ExecutedBlocks},
+    metrics_safety_rules::MetricsSafetyRules,
+    network::NetworkSender,
+    network_interface::{ConsensusMsg, ConsensusNetworkSender},
+    round_manager::VerifiedEvent,
+    test_utils::{consensus_runtime, timed_block_on, MockStorage, RecordingStateComputer},
+};
+use channel::{self, diem_channel, message_queues::QueueStyle};
+use consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
+    common::Round,
+    executed_block::ExecutedBlock,
+};
+use diem_crypto::{
+    ed25519::Ed25519PrivateKey, hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue, Uniform,
+};
+use diem_secure_storage::{InMemoryStorage, Storage};
+use diem_types::{
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+    waypoint::Waypoint,
+};
+use executor_types::StateComputeResult;
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use network::{
+    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::network::{Event, NewNetworkSender},
+};
+use safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use std::sync::Arc;
+
+/// The commit phase of the first validator, along with the channels to drive it.
+struct CommitPhaseSetup {
+    signers: Vec<ValidatorSigner>,
+    storage: Arc<MockStorage>,
+    executed_blocks_tx: mpsc::Sender<ExecutedBlocks>,
+    commit_msg_tx: mpsc::Sender<VerifiedEvent>,
+    self_receiver: channel::Receiver<Event<ConsensusMsg>>,
+    commit_receiver: mpsc::UnboundedReceiver<(Vec<HashValue>, LedgerInfoWithSignatures)>,
+    sync_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
+    last_commit_vote: Option<CommitVote>,
+}
+
+impl CommitPhaseSetup {
+    /// Creates the commit phase of the first of 4 validators, the 3 others are only used to sign.
+    fn new(last_commit_vote: Option<CommitVote>) -> (Self, CommitPhase) {
+        let (signers, validators) = random_validator_verifier(4, None, true);
+        let author = signers[0].author();
+        let (_, storage) = MockStorage::start_for_testing((&validators).into());
+        let waypoint =
+            Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some((&validators).into())))
+                .unwrap();
+        let safety_storage = PersistentSafetyStorage::initialize(
+            Storage::from(InMemoryStorage::new()),
+            author,
+            signers[0].private_key().clone(),
+            Ed25519PrivateKey::generate_for_testing(),
+            waypoint,
+            true,
+        );
+        let safety_rules_manager =
+            SafetyRulesManager::new_local(safety_storage, false, false, true);
+        let mut safety_rules =
+            MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
+        safety_rules.perform_initialize().unwrap();
+
+        let (network_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let (connection_reqs_tx, _) = diem_channel::new(QueueStyle::FIFO, 8, None);
+        let network_sender = ConsensusNetworkSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        let (self_sender, self_receiver) = channel::new_test(1000);
+        let network = NetworkSender::new(author, network_sender, self_sender, validators.clone());
+
+        let (commit_sender, commit_receiver) = mpsc::unbounded();
+        let (sync_sender, sync_receiver) = mpsc::unbounded();
+        let (executed_blocks_tx, executed_blocks_rx) = mpsc::channel(8);
+        let (commit_msg_tx, commit_msg_rx) = mpsc::channel(8);
+        let commit_phase = CommitPhase::new(
+            author,
+            EpochState {
+                epoch: 1,
+                verifier: validators,
+            },
+            Arc::new(RecordingStateComputer::new(commit_sender, sync_sender)),
+            safety_rules,
+            network,
+            storage.clone(),
+            executed_blocks_rx,
+            commit_msg_rx,
+            last_commit_vote,
+        );
+        (
+            Self {
+                signers,
+                storage,
+                executed_blocks_tx,
+                commit_msg_tx,
+                self_receiver,
+                commit_receiver,
+                sync_receiver,
+                last_commit_vote: None,
+            },
+            commit_phase,
+        )
+    }
+
+    /// Returns the next message broadcast, skipping the rebroadcasts of the pending commit vote.
+    async fn next_self_msg(&mut self) -> ConsensusMsg {
+        loop {
+            match self.self_receiver.next().await.unwrap() {
+                Event::Message(_, ConsensusMsg::CommitVoteMsg(vote)) => {
+                    if self.last_commit_vote.as_ref() != Some(vote.as_ref()) {
+                        self.last_commit_vote = Some(vote.as_ref().clone());
+                        return ConsensusMsg::CommitVoteMsg(vote);
+                    }
+                }
+                Event::Message(_, msg) => return msg,
+                _ => panic!("Unexpected Network Event"),
+            }
+        }
+    }
+
+    async fn next_commit_vote(&mut self) -> CommitVote {
+        match self.next_self_msg().await {
+            ConsensusMsg::CommitVoteMsg(vote) => *vote,
+            msg => panic!("Unexpected Consensus Message: {:?}", msg),
+        }
+    }
+
+    async fn next_commit_decision(&mut self) -> CommitDecision {
+        match self.next_self_msg().await {
+            ConsensusMsg::CommitDecisionMsg(decision) => *decision,
+            msg => panic!("Unexpected Consensus Message: {:?}", msg),
+        }
+    }
+
+    async fn send_commit_msg(&mut self, event: VerifiedEvent) {
+        self.commit_msg_tx.send(event).await.unwrap();
+    }
+}
+
+/// The blocks of the given rounds after execution, ordered by a quorum of the validators.
+fn make_executed_blocks(signers: &[ValidatorSigner], rounds: &[Round]) -> ExecutedBlocks {
+    let blocks: Vec<_> = rounds
+        .iter()
+        .map(|round| {
+            let block = Block::new_proposal(
+                vec![],
+                *round,
+                *round,
+                certificate_for_genesis(),
+                &signers[0],
+            );
+            ExecutedBlock::new(block, StateComputeResult::new_dummy())
+        })
+        .collect();
+    let ordered_info =
+        blocks
+            .last()
+            .unwrap()
+            .block()
+            .gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None);
+    let ordered_proof = sign_ledger_info(signers, LedgerInfo::new(ordered_info, HashValue::zero()));
+    ExecutedBlocks {
+        blocks,
+        ordered_proof,
+    }
+}
+
+fn commit_ledger_info(executed_blocks: &ExecutedBlocks) -> LedgerInfo {
+    LedgerInfo::new(
+        executed_blocks.blocks.last().unwrap().block_info(),
+        executed_blocks
+            .ordered_proof
+            .ledger_info()
+            .consensus_data_hash(),
+    )
+}
+
+/// Signs the LedgerInfo by a quorum of the 4 validators.
+fn sign_ledger_info(
+    signers: &[ValidatorSigner],
+    ledger_info: LedgerInfo,
+) -> LedgerInfoWithSignatures {
+    let signatures = signers
+        .iter()
+        .take(3)
+        .map(|signer| (signer.author(), signer.sign(&ledger_info)))
+        .collect();
+    LedgerInfoWithSignatures::new(ledger_info, signatures)
+}
+
+fn commit_vote(signer: &ValidatorSigner, ledger_info: &LedgerInfo) -> VerifiedEvent {
+    VerifiedEvent::CommitVote(Box::new(signed_commit_vote(signer, ledger_info)))
+}
+
+#[test]
+fn test_commit_vote_aggregation() {
+    let mut runtime = consensus_runtime();
+    let (mut setup, commit_phase) = CommitPhaseSetup::new(None);
+    runtime.spawn(commit_phase.start());
+    timed_block_on(&mut runtime, async {
+        let executed_blocks = make_executed_blocks(&setup.signers, &[1, 2]);
+        let block_ids: Vec<_> = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+        let ledger_info = commit_ledger_info(&executed_blocks);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+
+        // the execution result is signed, persisted and broadcast
+        let vote = setup.next_commit_vote().await;
+        assert_eq!(vote.author(), setup.signers[0].author());
+        assert_eq!(vote.ledger_info(), &ledger_info);
+        assert_eq!(
+            setup
+                .storage
+                .shared_storage
+                .last_commit_vote
+                .lock()
+                .as_ref(),
+            Some(&vote)
+        );
+
+        // the votes on another execution result don't count
+        let mut other_ledger_info = ledger_info.clone();
+        other_ledger_info.set_consensus_data_hash(HashValue::random());
+        setup
+            .send_commit_msg(VerifiedEvent::CommitVote(Box::new(vote)))
+            .await;
+        let signer = setup.signers[1].clone();
+        setup
+            .send_commit_msg(commit_vote(&signer, &other_ledger_info))
+            .await;
+        setup
+            .send_commit_msg(commit_vote(&signer, &ledger_info))
+            .await;
+        let signer = setup.signers[2].clone();
+        setup
+            .send_commit_msg(commit_vote(&signer, &other_ledger_info))
+            .await;
+
+        // the blocks are committed once a quorum signed the same execution result
+        setup
+            .send_commit_msg(commit_vote(&signer, &ledger_info))
+            .await;
+        let (committed_ids, commit_proof) = setup.commit_receiver.next().await.unwrap();
+        assert_eq!(committed_ids, block_ids);
+        assert_eq!(commit_proof.ledger_info(), &ledger_info);
+        assert_eq!(commit_proof.signatures().len(), 3);
+        let decision = setup.next_commit_decision().await;
+        assert_eq!(decision.ledger_info(), &commit_proof);
+
+        // the validators still voting on the committed blocks get the decision again
+        let signer = setup.signers[3].clone();
+        setup
+            .send_commit_msg(commit_vote(&signer, &ledger_info))
+            .await;
+        assert_eq!(setup.next_commit_decision().await, decision);
+    });
+}
+
+#[test]
+fn test_commit_decision() {
+    let mut runtime = consensus_runtime();
+    let (mut setup, commit_phase) = CommitPhaseSetup::new(None);
+    runtime.spawn(commit_phase.start());
+    timed_block_on(&mut runtime, async {
+        let executed_blocks = make_executed_blocks(&setup.signers, &[1]);
+        let block_ids: Vec<_> = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+        let ledger_info = commit_ledger_info(&executed_blocks);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+        setup.next_commit_vote().await;
+
+        // the decision of the other validators commits the pending blocks without more votes
+        let commit_proof = sign_ledger_info(&setup.signers, ledger_info);
+        setup
+            .send_commit_msg(VerifiedEvent::CommitDecision(Box::new(
+                CommitDecision::new(commit_proof.clone()),
+            )))
+            .await;
+        assert_eq!(
+            setup.commit_receiver.next().await.unwrap(),
+            (block_ids, commit_proof)
+        );
+        assert!(setup.sync_receiver.try_next().is_err());
+
+        // the decision received before the blocks are executed is applied once they are
+        let executed_blocks = make_executed_blocks(&setup.signers, &[2]);
+        let block_ids: Vec<_> = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+        let commit_proof = sign_ledger_info(&setup.signers, commit_ledger_info(&executed_blocks));
+        setup
+            .send_commit_msg(VerifiedEvent::CommitDecision(Box::new(
+                CommitDecision::new(commit_proof.clone()),
+            )))
+            .await;
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+        assert_eq!(
+            setup.commit_receiver.next().await.unwrap(),
+            (block_ids, commit_proof)
+        );
+    });
+}
+
+#[test]
+fn test_sync_to_decision() {
+    let mut runtime = consensus_runtime();
+    let (mut setup, commit_phase) = CommitPhaseSetup::new(None);
+    runtime.spawn(commit_phase.start());
+    timed_block_on(&mut runtime, async {
+        let executed_blocks = make_executed_blocks(&setup.signers, &[1]);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+        setup.next_commit_vote().await;
+
+        // the other validators committed beyond the pending blocks already
+        let later_blocks = make_executed_blocks(&setup.signers, &[2, 3]);
+        let commit_proof = sign_ledger_info(&setup.signers, commit_ledger_info(&later_blocks));
+        setup
+            .send_commit_msg(VerifiedEvent::CommitDecision(Box::new(
+                CommitDecision::new(commit_proof.clone()),
+            )))
+            .await;
+        assert_eq!(setup.sync_receiver.next().await.unwrap(), commit_proof);
+
+        // the blocks executed locally in the meantime are committed already
+        setup.executed_blocks_tx.send(later_blocks).await.unwrap();
+        let executed_blocks = make_executed_blocks(&setup.signers, &[4]);
+        let ledger_info = commit_ledger_info(&executed_blocks);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+        assert_eq!(setup.next_commit_vote().await.ledger_info(), &ledger_info);
+        assert!(setup.commit_receiver.try_next().is_err());
+    });
+}
+
+fn signed_commit_vote(signer: &ValidatorSigner, ledger_info: &LedgerInfo) -> CommitVote {
+    CommitVote::new_with_signature(
+        signer.author(),
+        ledger_info.clone(),
+        signer.sign(ledger_info),
+    )
+}
+
+#[test]
+fn test_recovered_commit_vote() {
+    let (signers, _) = random_validator_verifier(4, None, true);
+    let ledger_info = commit_ledger_info(&make_executed_blocks(&signers, &[1]));
+    let recovered_vote = signed_commit_vote(&signers[0], &ledger_info);
+
+    let mut runtime = consensus_runtime();
+    let (mut setup, commit_phase) = CommitPhaseSetup::new(Some(recovered_vote.clone()));
+    runtime.spawn(commit_phase.start());
+    timed_block_on(&mut runtime, async {
+        // the blocks executed again after a restart get the commit vote sent before it
+        let executed_blocks = make_executed_blocks(&setup.signers, &[1]);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks)
+            .await
+            .unwrap();
+        assert_eq!(setup.next_commit_vote().await, recovered_vote);
+        assert!(setup
+            .storage
+            .shared_storage
+            .last_commit_vote
+            .lock()
+            .is_none());
+    });
+}
+
+#[test]
+fn test_conflicting_recovered_commit_vote() {
+    let (signers, _) = random_validator_verifier(4, None, true);
+    let mut ledger_info = commit_ledger_info(&make_executed_blocks(&signers, &[1]));
+    ledger_info.set_consensus_data_hash(HashValue::random());
+    let recovered_vote = signed_commit_vote(&signers[0], &ledger_info);
+
+    let mut runtime = consensus_runtime();
+    let (mut setup, commit_phase) = CommitPhaseSetup::new(Some(recovered_vote));
+    runtime.spawn(commit_phase.start());
+    timed_block_on(&mut runtime, async {
+        // another execution result of a round voted on before the restart is never signed
+        let executed_blocks_1 = make_executed_blocks(&setup.signers, &[1]);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks_1)
+            .await
+            .unwrap();
+        let executed_blocks_2 = make_executed_blocks(&setup.signers, &[2]);
+        let ledger_info = commit_ledger_info(&executed_blocks_2);
+        setup
+            .executed_blocks_tx
+            .send(executed_blocks_2)
+            .await
+            .unwrap();
+        assert_eq!(setup.next_commit_vote().await.ledger_info(), &ledger_info);
+    });
+}
@@ -0,0 +1,87 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::ordering_state_computer::OrderedBlocks,
+    state_replication::{StateComputer, TxnManager},
+};
+use anyhow::Context;
+use consensus_types::executed_block::ExecutedBlock;
+use diem_logger::prelude::*;
+use diem_types::ledger_info::LedgerInfoWithSignatures;
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::sync::Arc;
+
+/// The ordered blocks after execution, along with the LedgerInfo ordering them.
+pub struct ExecutedBlocks {
+    pub blocks: Vec<ExecutedBlock>,
+    pub ordered_proof: LedgerInfoWithSignatures,
+}
+
+/// Executes the blocks ordered by consensus one batch after another and hands them over to the
+/// commit phase.
+pub struct ExecutionPhase {
+    execution_proxy: Arc<dyn StateComputer>,
+    txn_manager: Arc<dyn TxnManager>,
+    ordered_blocks_rx: mpsc::Receiver<OrderedBlocks>,
+    executed_blocks_tx: mpsc::Sender<ExecutedBlocks>,
+}
+
+impl ExecutionPhase {
+    pub fn new(
+        execution_proxy: Arc<dyn StateComputer>,
+        txn_manager: Arc<dyn TxnManager>,
+        ordered_blocks_rx: mpsc::Receiver<OrderedBlocks>,
+        executed_blocks_tx: mpsc::Sender<ExecutedBlocks>,
+    ) -> Self {
+        Self {
+            execution_proxy,
+            txn_manager,
+            ordered_blocks_rx,
+            executed_blocks_tx,
+        }
+    }
+
+    async fn execute(&self, ordered_blocks: OrderedBlocks) -> anyhow::Result<ExecutedBlocks> {
+        let OrderedBlocks {
+            blocks,
+            ordered_proof,
+        } = ordered_blocks;
+        let mut executed_blocks = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let compute_result = self
+                .execution_proxy
+                .compute(&block, block.parent_id())
+                .with_context(|| format!("[ExecutionPhase] Failed to execute {}", block))?;
+            // the executor drops the payload of the blocks following a reconfiguration, there is
+            // nothing to notify about them
+            if !compute_result.compute_status().is_empty() {
+                if let Err(e) = self.txn_manager.notify(&block, &compute_result).await {
+                    error!(
+                        error = ?e, "[ExecutionPhase] Failed to notify mempool of rejected txns",
+                    );
+                }
+            }
+            executed_blocks.push(ExecutedBlock::new(block, compute_result));
+        }
+        Ok(ExecutedBlocks {
+            blocks: executed_blocks,
+            ordered_proof,
+        })
+    }
+
+    pub async fn start(mut self) {
+        while let Some(ordered_blocks) = self.ordered_blocks_rx.next().await {
+            match self.execute(ordered_blocks).await {
+                Ok(executed_blocks) => {
+                    if self.executed_blocks_tx.send(executed_blocks).await.is_err() {
+                        // the commit phase of this epoch is over
+                        break;
+                    }
+                }
+                Err(e) => error!(error = ?e, "[ExecutionPhase] Failed to execute ordered blocks"),
+            }
+        }
+        debug!("[ExecutionPhase] stopped");
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::{
+        execution_phase::{ExecutedBlocks, ExecutionPhase},
+        ordering_state_computer::OrderedBlocks,
+    },
+    test_utils::{
+        consensus_runtime, timed_block_on, MockTransactionManager, RecordingStateComputer,
+    },
+};
+use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use diem_crypto::HashValue;
+use diem_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::{collections::BTreeMap, sync::Arc};
+
+fn ordered_blocks(rounds: &[u64]) -> OrderedBlocks {
+    let signer = ValidatorSigner::from_int(0);
+    let blocks: Vec<_> = rounds
+        .iter()
+        .map(|round| {
+            Block::new_proposal(vec![], *round, *round, certificate_for_genesis(), &signer)
+        })
+        .collect();
+    let ordered_info = blocks
+        .last()
+        .unwrap()
+        .gen_block_info(HashValue::zero(), 0, None);
+    OrderedBlocks {
+        blocks,
+        ordered_proof: LedgerInfoWithSignatures::new(
+            LedgerInfo::new(ordered_info, HashValue::zero()),
+            BTreeMap::new(),
+        ),
+    }
+}
+
+fn execution_phase() -> (
+    ExecutionPhase,
+    mpsc::Sender<OrderedBlocks>,
+    mpsc::Receiver<ExecutedBlocks>,
+) {
+    let (commit_sender, _) = mpsc::unbounded();
+    let (sync_sender, _) = mpsc::unbounded();
+    let (ordered_blocks_tx, ordered_blocks_rx) = mpsc::channel(8);
+    let (executed_blocks_tx, executed_blocks_rx) = mpsc::channel(8);
+    let execution_phase = ExecutionPhase::new(
+        Arc::new(RecordingStateComputer::new(commit_sender, sync_sender)),
+        Arc::new(MockTransactionManager::new(None)),
+        ordered_blocks_rx,
+        executed_blocks_tx,
+    );
+    (execution_phase, ordered_blocks_tx, executed_blocks_rx)
+}
+
+#[test]
+fn test_execute_ordered_blocks() {
+    let mut runtime = consensus_runtime();
+    let (execution_phase, mut ordered_blocks_tx, mut executed_blocks_rx) = execution_phase();
+    runtime.spawn(execution_phase.start());
+    timed_block_on(&mut runtime, async {
+        for rounds in [vec![1, 2], vec![3]].iter() {
+            let ordered_blocks = ordered_blocks(rounds);
+            let block_ids: Vec<_> = ordered_blocks.blocks.iter().map(|b| b.id()).collect();
+            let ordered_proof = ordered_blocks.ordered_proof.clone();
+            ordered_blocks_tx.send(ordered_blocks).await.unwrap();
+
+            // the blocks are executed in order, and handed over with the proof ordering them
+            let executed_blocks = executed_blocks_rx.next().await.unwrap();
+            let executed_ids: Vec<_> = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+            assert_eq!(executed_ids, block_ids);
+            assert_eq!(executed_blocks.ordered_proof, ordered_proof);
+        }
+    });
+}
+
+#[test]
+fn test_stop_with_commit_phase() {
+    let mut runtime = consensus_runtime();
+    let (execution_phase, mut ordered_blocks_tx, executed_blocks_rx) = execution_phase();
+    drop(executed_blocks_rx);
+    timed_block_on(&mut runtime, async move {
+        ordered_blocks_tx.send(ordered_blocks(&[1])).await.unwrap();
+        // the execution phase stops as soon as the commit phase of the epoch is gone
+        execution_phase.start().await;
+    });
+}
@@ -0,0 +1,19 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoupled execution: consensus only agrees on the order of blocks, the ordered blocks are then
+//! executed by the execution phase, and the commit phase collects a second round of signatures on
+//! the execution result before committing it.
+//!
+//! OrderingStateComputer --(OrderedBlocks)--> ExecutionPhase --(ExecutedBlocks)--> CommitPhase
+
+pub mod commit_phase;
+pub mod execution_phase;
+pub mod ordering_state_computer;
+
+#[cfg(test)]
+mod commit_phase_test;
+#[cfg(test)]
+mod execution_phase_test;
+#[cfg(test)]
+mod ordering_state_computer_test;
@@ -0,0 +1,103 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::StateSyncError, state_replication::StateComputer};
+use anyhow::Result;
+use consensus_types::block::Block;
+use diem_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue};
+use diem_infallible::Mutex;
+use diem_types::ledger_info::LedgerInfoWithSignatures;
+use executor_types::{Error as ExecutionError, StateComputeResult};
+use futures::{channel::mpsc, SinkExt};
+use std::{collections::HashMap, sync::Arc};
+
+/// The blocks committed by consensus, in order, along with the LedgerInfo ordering them.
+pub struct OrderedBlocks {
+    pub blocks: Vec<Block>,
+    pub ordered_proof: LedgerInfoWithSignatures,
+}
+
+/// StateComputer used by consensus with decoupled execution: blocks are not executed when they are
+/// inserted into the block tree, committing them hands them over to the execution phase instead.
+pub struct OrderingStateComputer {
+    executor_channel: mpsc::Sender<OrderedBlocks>,
+    state_computer_for_sync: Arc<dyn StateComputer>,
+    block_cache: Mutex<HashMap<HashValue, Block>>,
+}
+
+impl OrderingStateComputer {
+    pub fn new(
+        executor_channel: mpsc::Sender<OrderedBlocks>,
+        state_computer_for_sync: Arc<dyn StateComputer>,
+    ) -> Self {
+        Self {
+            executor_channel,
+            state_computer_for_sync,
+            block_cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StateComputer for OrderingStateComputer {
+    fn compute(
+        &self,
+        block: &Block,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult, ExecutionError> {
+        self.block_cache.lock().insert(block.id(), block.clone());
+        Ok(StateComputeResult::new_dummy())
+    }
+
+    /// Sends the ordered blocks to the execution phase, the future is fulfilled as soon as the
+    /// blocks are queued.
+    async fn commit(
+        &self,
+        block_ids: Vec<HashValue>,
+        finality_proof: LedgerInfoWithSignatures,
+    ) -> Result<(), ExecutionError> {
+        let blocks = {
+            let mut block_cache = self.block_cache.lock();
+            let blocks = block_ids
+                .into_iter()
+                .map(|id| {
+                    block_cache
+                        .remove(&id)
+                        .ok_or(ExecutionError::BlockNotFound(id))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            // the blocks of the other branches can't be committed anymore
+            let committed_round = finality_proof.ledger_info().round();
+            block_cache.retain(|_, block| block.round() > committed_round);
+            blocks
+        };
+
+        self.executor_channel
+            .clone()
+            .send(OrderedBlocks {
+                blocks,
+                ordered_proof: finality_proof,
+            })
+            .await
+            .map_err(|e| ExecutionError::InternalError {
+                error: format!(
+                    "Failed to send ordered blocks to the execution phase: {}",
+                    e
+                ),
+            })
+    }
+
+    async fn sync_to(&self, target: LedgerInfoWithSignatures) -> Result<(), StateSyncError> {
+        // The LedgerInfos certified by consensus only carry the order of the blocks, state sync
+        // needs the execution result, which is only known to the commit phase.
+        if target.ledger_info().transaction_accumulator_hash() == *ACCUMULATOR_PLACEHOLDER_HASH {
+            return Err(anyhow::anyhow!(
+                "Unable to sync to an ordered only LedgerInfo with decoupled execution: {}",
+                target.ledger_info()
+            )
+            .into());
+        }
+        self.block_cache.lock().clear();
+        self.state_computer_for_sync.sync_to(target).await
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    experimental::ordering_state_computer::{OrderedBlocks, OrderingStateComputer},
+    state_replication::StateComputer,
+    test_utils::{consensus_runtime, timed_block_on, RecordingStateComputer},
+};
+use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use diem_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue};
+use diem_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
+use executor_types::Error as ExecutionError;
+use futures::{channel::mpsc, StreamExt};
+use std::{collections::BTreeMap, sync::Arc};
+
+fn block(round: u64, timestamp_usecs: u64) -> Block {
+    Block::new_proposal(
+        vec![],
+        round,
+        timestamp_usecs,
+        certificate_for_genesis(),
+        &ValidatorSigner::from_int(0),
+    )
+}
+
+fn ledger_info(block: &Block, executed_state_id: HashValue) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            block.gen_block_info(executed_state_id, 0, None),
+            HashValue::zero(),
+        ),
+        BTreeMap::new(),
+    )
+}
+
+fn ids(blocks: &[Block]) -> Vec<HashValue> {
+    blocks.iter().map(|b| b.id()).collect()
+}
+
+#[test]
+fn test_commit_sends_ordered_blocks() {
+    let mut runtime = consensus_runtime();
+    let (ordered_blocks_tx, mut ordered_blocks_rx) = mpsc::channel(8);
+    let (commit_sender, _) = mpsc::unbounded();
+    let (sync_sender, _) = mpsc::unbounded();
+    let state_computer = OrderingStateComputer::new(
+        ordered_blocks_tx,
+        Arc::new(RecordingStateComputer::new(commit_sender, sync_sender)),
+    );
+    timed_block_on(&mut runtime, async {
+        //            ╭--> C1
+        // Genesis--> B1--> B2--> B3
+        let b1 = block(1, 1);
+        let b2 = block(2, 2);
+        let b3 = block(3, 3);
+        let c1 = block(1, 4);
+        for block in [&b1, &b2, &c1, &b3].iter() {
+            // nothing is executed while the blocks are inserted in the tree
+            let result = state_computer.compute(block, block.parent_id()).unwrap();
+            assert_eq!(result.root_hash(), *ACCUMULATOR_PLACEHOLDER_HASH);
+        }
+
+        let ordered_proof = ledger_info(&b2, *ACCUMULATOR_PLACEHOLDER_HASH);
+        let blocks = vec![b1, b2];
+        state_computer
+            .commit(ids(&blocks), ordered_proof.clone())
+            .await
+            .unwrap();
+        let OrderedBlocks {
+            blocks: ordered,
+            ordered_proof: proof,
+        } = ordered_blocks_rx.next().await.unwrap();
+        assert_eq!(ordered, blocks);
+        assert_eq!(proof, ordered_proof);
+
+        // the blocks on the other branches are dropped once their round is committed
+        assert_eq!(
+            state_computer
+                .commit(
+                    vec![c1.id()],
+                    ledger_info(&c1, *ACCUMULATOR_PLACEHOLDER_HASH)
+                )
+                .await,
+            Err(ExecutionError::BlockNotFound(c1.id()))
+        );
+        state_computer
+            .commit(
+                vec![b3.id()],
+                ledger_info(&b3, *ACCUMULATOR_PLACEHOLDER_HASH),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ordered_blocks_rx.next().await.unwrap().blocks, vec![b3]);
+    });
+}
+
+#[test]
+fn test_sync_to_decision() {
+    let mut runtime = consensus_runtime();
+    let (ordered_blocks_tx, _ordered_blocks_rx) = mpsc::channel(8);
+    let (commit_sender, _) = mpsc::unbounded();
+    let (sync_sender, mut sync_receiver) = mpsc::unbounded();
+    let state_computer = OrderingStateComputer::new(
+        ordered_blocks_tx,
+        Arc::new(RecordingStateComputer::new(commit_sender, sync_sender)),
+    );
+    timed_block_on(&mut runtime, async {
+        let b1 = block(1, 1);
+        let b2 = block(2, 2);
+        state_computer.compute(&b1, b1.parent_id()).unwrap();
+        state_computer.compute(&b2, b2.parent_id()).unwrap();
+
+        // the LedgerInfos certified by consensus don't carry the execution result to sync to
+        assert!(state_computer
+            .sync_to(ledger_info(&b1, *ACCUMULATOR_PLACEHOLDER_HASH))
+            .await
+            .is_err());
+
+        // the commit decisions do, the blocks waiting to be ordered are dropped after the sync
+        let decision = ledger_info(&b1, HashValue::random());
+        state_computer.sync_to(decision.clone()).await.unwrap();
+        assert_eq!(sync_receiver.next().await.unwrap(), decision);
+        assert_eq!(
+            state_computer
+                .commit(
+                    vec![b2.id()],
+                    ledger_info(&b2, *ACCUMULATOR_PLACEHOLDER_HASH)
+                )
+                .await,
+            Err(ExecutionError::BlockNotFound(b2.id()))
+        );
+    });
+}
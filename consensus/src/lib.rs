@@ -17,6 +17,7 @@ mod consensusdb;
 mod counters;
 mod epoch_manager;
 mod error;
+mod experimental;
 mod liveness;
 mod logging;
 mod metrics_safety_rules;
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::{COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW},
+    counters::{
+        COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW, FAILED_PROPOSALS_IN_WINDOW,
+    },
     liveness::proposer_election::{next, ProposerElection},
 };
 use consensus_types::{
@@ -12,7 +14,10 @@ use consensus_types::{
 use diem_crypto::HashValue;
 use diem_infallible::Mutex;
 use diem_logger::prelude::*;
-use diem_types::block_metadata::{new_block_event_key, NewBlockEvent};
+use diem_types::{
+    account_address::AccountAddress,
+    block_metadata::{new_block_event_key, NewBlockEvent},
+};
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
@@ -22,8 +27,8 @@ use storage_interface::{DbReader, Order};
 
 /// Interface to query committed BlockMetadata.
 pub trait MetadataBackend: Send + Sync {
-    /// Return a contiguous BlockMetadata window in which last one is at target_round or
-    /// latest committed, return all previous one if not enough.
+    /// Return a contiguous BlockMetadata window, ordered from the most recent, in which the first
+    /// one is at target_round or latest committed, return all previous one if not enough.
     fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent>;
 }
 
@@ -93,7 +98,7 @@ impl MetadataBackend for DiemDBBackend {
 
 /// Interface to calculate weights for proposers based on history.
 pub trait ReputationHeuristic: Send + Sync {
-    /// Return the weights of all candidates based on the history.
+    /// Return the weights of all candidates based on the history, ordered from the most recent.
     fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64>;
 }
 
@@ -153,6 +158,196 @@ impl ReputationHeuristic for ActiveInactiveHeuristic {
     }
 }
 
+/// In addition to the proposals and votes of the candidates, this heuristic takes the rounds in
+/// which no block got committed into account: candidates that failed to propose in more than
+/// failure_threshold_percent of their rounds in the proposer window get the failed weight, the
+/// others get the active weight if they proposed or voted (in the voter window), and the inactive
+/// weight otherwise.
+///
+/// Block metadata doesn't record who failed to propose, so the failed rounds are the gaps between
+/// the rounds of consecutive committed blocks, and each of them is attributed to the candidate
+/// that the election picks for that round based on the proposals and votes committed before it.
+/// This is an approximation of the actual election: the weights used for the attribution ignore
+/// the failed rounds before it (which would make the attribution recursive), and only cover the
+/// blocks left in the history passed in. Both agree as long as there is no failed round in the
+/// history the election of the failed round is based on.
+///
+/// At most proposer_window_size failed rounds, the most recent ones, are attributed, so that a
+/// long gap (e.g. after a network partition) neither outweighs the committed proposals in the
+/// window nor makes each election expensive.
+pub struct ProposerAndVoterHeuristic {
+    author: Author,
+    active_weight: u64,
+    inactive_weight: u64,
+    failed_weight: u64,
+    failure_threshold_percent: u32,
+    proposer_window_size: usize,
+    voter_window_size: usize,
+}
+
+impl ProposerAndVoterHeuristic {
+    pub fn new(
+        author: Author,
+        active_weight: u64,
+        inactive_weight: u64,
+        failed_weight: u64,
+        failure_threshold_percent: u32,
+        proposer_window_size: usize,
+        voter_window_size: usize,
+    ) -> Self {
+        Self {
+            author,
+            active_weight,
+            inactive_weight,
+            failed_weight,
+            failure_threshold_percent,
+            proposer_window_size,
+            voter_window_size,
+        }
+    }
+
+    fn count_proposals(history: &[NewBlockEvent]) -> HashMap<Author, u64> {
+        history.iter().fold(HashMap::new(), |mut map, meta| {
+            *map.entry(meta.proposer()).or_insert(0) += 1;
+            map
+        })
+    }
+
+    fn count_votes(history: &[NewBlockEvent]) -> HashMap<Author, u64> {
+        history.iter().fold(HashMap::new(), |mut map, meta| {
+            for vote in meta.votes() {
+                *map.entry(vote).or_insert(0) += 1;
+            }
+            map
+        })
+    }
+
+    /// Weights based on the proposals and votes only, used to attribute the failed rounds.
+    fn get_activity_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
+        let proposer_window = &history[..history.len().min(self.proposer_window_size)];
+        let voter_window = &history[..history.len().min(self.voter_window_size)];
+        let proposals = Self::count_proposals(proposer_window);
+        let votes = Self::count_votes(voter_window);
+        candidates
+            .iter()
+            .map(|author| {
+                if proposals.contains_key(author) || votes.contains_key(author) {
+                    self.active_weight
+                } else {
+                    self.inactive_weight
+                }
+            })
+            .collect()
+    }
+
+    fn count_failed_proposals(
+        &self,
+        candidates: &[Author],
+        history: &[NewBlockEvent],
+    ) -> HashMap<Author, u64> {
+        let proposer_window = &history[..history.len().min(self.proposer_window_size)];
+        let max_failed_rounds = self.proposer_window_size;
+        // Pairs of a failed round and the index of the first block committed before it, from the
+        // most recent.
+        let mut failed_rounds = vec![];
+        for (i, meta) in proposer_window.iter().enumerate() {
+            // A nil block is committed when the leader of its round failed to propose.
+            if meta.proposer() == AccountAddress::ZERO {
+                failed_rounds.push((meta.round(), i + 1));
+            }
+            if let Some(older) = proposer_window.get(i + 1) {
+                // Rounds restart from the beginning at epoch changes.
+                if meta.round() > older.round() {
+                    let remaining = max_failed_rounds.saturating_sub(failed_rounds.len());
+                    failed_rounds.extend(
+                        (older.round() + 1..meta.round())
+                            .rev()
+                            .take(remaining)
+                            .map(|r| (r, i + 1)),
+                    );
+                }
+            }
+            if failed_rounds.len() >= max_failed_rounds {
+                break;
+            }
+        }
+        failed_rounds.truncate(max_failed_rounds);
+
+        let mut failed_proposals = HashMap::new();
+        if candidates.is_empty() {
+            return failed_proposals;
+        }
+        for (round, older_index) in failed_rounds {
+            let start = history[older_index..]
+                .iter()
+                .position(|meta| meta.round() + ELECTION_ROUND_GAP <= round)
+                .map_or(history.len(), |index| index + older_index);
+            let weights = self.get_activity_weights(candidates, &history[start..]);
+            let failed_proposer = candidates[choose_index(weights, round)];
+            *failed_proposals.entry(failed_proposer).or_insert(0) += 1;
+        }
+        failed_proposals
+    }
+}
+
+impl ReputationHeuristic for ProposerAndVoterHeuristic {
+    fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
+        let proposer_window = &history[..history.len().min(self.proposer_window_size)];
+        let voter_window = &history[..history.len().min(self.voter_window_size)];
+        let proposals = Self::count_proposals(proposer_window);
+        let votes = Self::count_votes(voter_window);
+        let failed_proposals = self.count_failed_proposals(candidates, history);
+
+        let get_count = |map: &HashMap<Author, u64>, author| *map.get(author).unwrap_or(&0);
+        COMMITTED_PROPOSALS_IN_WINDOW.set(get_count(&proposals, &self.author) as i64);
+        COMMITTED_VOTES_IN_WINDOW.set(get_count(&votes, &self.author) as i64);
+        FAILED_PROPOSALS_IN_WINDOW.set(get_count(&failed_proposals, &self.author) as i64);
+
+        candidates
+            .iter()
+            .map(|author| {
+                let proposed = get_count(&proposals, author);
+                let failed = get_count(&failed_proposals, author);
+                if failed * 100 > (proposed + failed) * self.failure_threshold_percent as u64 {
+                    self.failed_weight
+                } else if proposed > 0 || get_count(&votes, author) > 0 {
+                    self.active_weight
+                } else {
+                    self.inactive_weight
+                }
+            })
+            .collect()
+    }
+}
+
+/// The election for a round is based on the blocks committed up to this many rounds before it.
+const ELECTION_ROUND_GAP: Round = 4;
+
+/// Pick an index with a probability proportional to its weight, using the round as the seed.
+pub(crate) fn choose_index(mut weights: Vec<u64>, round: Round) -> usize {
+    let mut total_weight = 0;
+    for w in &mut weights {
+        total_weight += *w;
+        *w = total_weight;
+    }
+    // All candidates are equally likely if none of them has any weight.
+    if total_weight == 0 {
+        weights = (1..=weights.len() as u64).collect();
+        total_weight = weights.len() as u64;
+    }
+    let mut state = round.to_le_bytes().to_vec();
+    let chosen_weight = next(&mut state) % total_weight;
+    weights
+        .binary_search_by(|w| {
+            if *w <= chosen_weight {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        })
+        .unwrap_err()
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
 pub struct LeaderReputation {
@@ -180,27 +375,11 @@ impl LeaderReputation {
 impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
         // TODO: configure the round gap
-        let target_round = if round >= 4 { round - 4 } else { 0 };
+        let target_round = round.saturating_sub(ELECTION_ROUND_GAP);
         let sliding_window = self.backend.get_block_metadata(target_round);
-        let mut weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
+        let weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
         assert_eq!(weights.len(), self.proposers.len());
-        let mut total_weight = 0;
-        for w in &mut weights {
-            total_weight += *w;
-            *w = total_weight;
-        }
-        let mut state = round.to_le_bytes().to_vec();
-        let chosen_weight = next(&mut state) % total_weight;
-        let chosen_index = weights
-            .binary_search_by(|w| {
-                if *w <= chosen_weight {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .unwrap_err();
-        self.proposers[chosen_index]
+        self.proposers[choose_index(weights, round)]
     }
 
     /// This function will return true for at most one proposal per valid proposer for a given round.
@@ -3,7 +3,8 @@
 
 use crate::liveness::{
     leader_reputation::{
-        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ReputationHeuristic,
+        choose_index, ActiveInactiveHeuristic, LeaderReputation, MetadataBackend,
+        ProposerAndVoterHeuristic, ReputationHeuristic,
     },
     proposer_election::{next, ProposerElection},
 };
@@ -11,7 +12,11 @@ use consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
     common::{Author, Round},
 };
-use diem_types::{block_metadata::NewBlockEvent, validator_signer::ValidatorSigner};
+use diem_types::{
+    account_address::AccountAddress, block_metadata::NewBlockEvent,
+    validator_signer::ValidatorSigner,
+};
+use std::collections::HashMap;
 
 struct MockHistory {
     window_size: usize,
@@ -31,7 +36,22 @@ impl MetadataBackend for MockHistory {
         } else {
             0
         };
-        self.data[start..].to_vec()
+        self.data[start..].iter().rev().cloned().collect()
+    }
+}
+
+/// Returns the blocks committed up to the target round, like the DiemDB backend.
+struct MockRoundHistory {
+    data: Vec<NewBlockEvent>,
+}
+
+impl MetadataBackend for MockRoundHistory {
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
+        self.data
+            .iter()
+            .filter(|meta| meta.round() <= target_round)
+            .cloned()
+            .collect()
     }
 }
 
@@ -76,6 +96,199 @@ fn test_simple_heuristic() {
     }
 }
 
+fn create_block_at_round(
+    round: Round,
+    proposer: Author,
+    voters: Vec<&ValidatorSigner>,
+) -> NewBlockEvent {
+    NewBlockEvent::new(
+        round,
+        proposer,
+        voters.iter().map(|v| v.author()).collect(),
+        0,
+    )
+}
+
+#[test]
+fn test_proposer_and_voter_heuristic_single_candidate() {
+    let signer = ValidatorSigner::random([0; 32]);
+    let proposers = vec![signer.author()];
+    let heuristic = ProposerAndVoterHeuristic::new(proposers[0], 100, 10, 1, 10, 10, 10);
+    // 1. No history
+    assert_eq!(heuristic.get_weights(&proposers, &[]), vec![10]);
+    // 2. Consecutive rounds
+    let history = vec![
+        create_block_at_round(2, proposers[0], vec![]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(heuristic.get_weights(&proposers, &history), vec![100]);
+    // 3. Rounds 2, 3 and 4 failed, 60% of the rounds of the only candidate
+    let history = vec![
+        create_block_at_round(5, proposers[0], vec![&signer]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(heuristic.get_weights(&proposers, &history), vec![1]);
+    // 4. Nil blocks are failed rounds as well
+    let history = vec![
+        create_block_at_round(2, AccountAddress::ZERO, vec![&signer]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(heuristic.get_weights(&proposers, &history), vec![1]);
+    // 5. Failed rounds out of the proposer window are ignored
+    let heuristic = ProposerAndVoterHeuristic::new(proposers[0], 100, 10, 1, 10, 1, 10);
+    let history = vec![
+        create_block_at_round(6, proposers[0], vec![&signer]),
+        create_block_at_round(5, proposers[0], vec![&signer]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(heuristic.get_weights(&proposers, &history), vec![100]);
+    // 6. Only the proposer_window_size most recent failed rounds are attributed: 2 failed rounds
+    // out of 4 are below the threshold, all 98 rounds of the gap would not be
+    let heuristic = ProposerAndVoterHeuristic::new(proposers[0], 100, 10, 1, 60, 2, 10);
+    let history = vec![
+        create_block_at_round(100, proposers[0], vec![&signer]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(heuristic.get_weights(&proposers, &history), vec![100]);
+}
+
+#[test]
+fn test_proposer_and_voter_heuristic() {
+    let (active_weight, inactive_weight, failed_weight) = (100, 10, 1);
+    let mut proposers = vec![];
+    let mut signers = vec![];
+    for i in 0..4 {
+        let signer = ValidatorSigner::random([i; 32]);
+        proposers.push(signer.author());
+        signers.push(signer);
+    }
+    let heuristic = ProposerAndVoterHeuristic::new(
+        proposers[0],
+        active_weight,
+        inactive_weight,
+        failed_weight,
+        10,
+        10,
+        10,
+    );
+    // 1. Proposers 0 and 1 proposed, 0, 1 and 2 voted
+    let history = vec![
+        create_block_at_round(3, proposers[0], vec![&signers[1], &signers[2]]),
+        create_block_at_round(2, proposers[1], vec![&signers[0]]),
+        create_block_at_round(1, proposers[0], vec![]),
+    ];
+    assert_eq!(
+        heuristic.get_weights(&proposers, &history),
+        vec![active_weight, active_weight, active_weight, inactive_weight]
+    );
+
+    // 2. Rounds 4 to 10 failed, each one is attributed to the candidate picked based on the
+    // blocks committed up to 4 rounds before it.
+    let mut history = history;
+    history.insert(
+        0,
+        create_block_at_round(10, AccountAddress::ZERO, vec![&signers[0], &signers[1]]),
+    );
+    let mut failed = HashMap::new();
+    for round in 4..=10 {
+        let weights = match round {
+            4 => vec![inactive_weight; 4],
+            5 => vec![
+                active_weight,
+                inactive_weight,
+                inactive_weight,
+                inactive_weight,
+            ],
+            6 => vec![
+                active_weight,
+                active_weight,
+                inactive_weight,
+                inactive_weight,
+            ],
+            _ => vec![active_weight, active_weight, active_weight, inactive_weight],
+        };
+        *failed
+            .entry(proposers[choose_index(weights, round)])
+            .or_insert(0u64) += 1;
+    }
+    let proposed: HashMap<_, u64> = vec![(proposers[0], 2), (proposers[1], 1)]
+        .into_iter()
+        .collect();
+    let expected_weights: Vec<_> = proposers
+        .iter()
+        .map(|author| {
+            let proposed = *proposed.get(author).unwrap_or(&0);
+            let failed = *failed.get(author).unwrap_or(&0);
+            if failed * 100 > (proposed + failed) * 10 {
+                failed_weight
+            } else if *author != proposers[3] {
+                active_weight
+            } else {
+                inactive_weight
+            }
+        })
+        .collect();
+    assert_eq!(
+        heuristic.get_weights(&proposers, &history),
+        expected_weights
+    );
+    assert!(expected_weights.contains(&failed_weight));
+}
+
+#[test]
+fn test_failed_rounds_attributed_like_election() {
+    let (active_weight, inactive_weight, failed_weight) = (100, 10, 1);
+    let signers: Vec<_> = (0..4).map(|i| ValidatorSigner::random([i; 32])).collect();
+    let proposers: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+    // A threshold of 0 gives the failed weight to every candidate with a failed round.
+    let heuristic = || {
+        ProposerAndVoterHeuristic::new(
+            proposers[0],
+            active_weight,
+            inactive_weight,
+            failed_weight,
+            0,
+            10,
+            10,
+        )
+    };
+    // Rounds 1 to 6 and 9 are committed, rounds 7 and 8 failed.
+    let mut history: Vec<_> = (1..=6)
+        .rev()
+        .map(|round| create_block_at_round(round, proposers[round as usize % 2], vec![&signers[2]]))
+        .collect();
+    history.insert(0, create_block_at_round(9, proposers[1], vec![&signers[2]]));
+
+    // There is no failed round before round 7, so the failed rounds are attributed to the
+    // proposers the election picked for them.
+    let election = LeaderReputation::new(
+        proposers.clone(),
+        Box::new(MockRoundHistory {
+            data: history.clone(),
+        }),
+        Box::new(heuristic()),
+    );
+    let failed_proposers: Vec<_> = (7..=8)
+        .map(|round| election.get_valid_proposer(round))
+        .collect();
+    let expected_weights: Vec<_> = proposers
+        .iter()
+        .map(|author| {
+            if failed_proposers.contains(author) {
+                failed_weight
+            } else if *author != proposers[3] {
+                active_weight
+            } else {
+                inactive_weight
+            }
+        })
+        .collect();
+    assert_eq!(
+        heuristic().get_weights(&proposers, &history),
+        expected_weights
+    );
+}
+
 #[test]
 fn test_api() {
     let active_weight = 9;
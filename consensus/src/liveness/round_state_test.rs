@@ -138,5 +138,5 @@ fn generate_sync_info(
     );
     let commit_cert = quorum_cert.clone();
     let timeout_cert = TimeoutCertificate::new(Timeout::new(1, timeout_round));
-    SyncInfo::new(quorum_cert, commit_cert, Some(timeout_cert))
+    SyncInfo::new(quorum_cert, commit_cert, Some(timeout_cert), None)
 }
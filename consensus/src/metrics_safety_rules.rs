@@ -3,12 +3,19 @@
 
 use crate::persistent_liveness_storage::PersistentLivenessStorage;
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
+    block::Block,
+    block_data::BlockData,
+    timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
+    vote::Vote,
     vote_proposal::MaybeSignedVoteProposal,
 };
 use diem_crypto::ed25519::Ed25519Signature;
 use diem_metrics::monitor;
-use diem_types::epoch_change::EpochChangeProof;
+use diem_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 use safety_rules::{ConsensusState, Error, TSafetyRules};
 use std::sync::Arc;
 
@@ -87,4 +94,64 @@ impl TSafetyRules for MetricsSafetyRules {
         }
         result
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        let mut result = monitor!(
+            "safety_rules",
+            self.inner
+                .sign_commit_vote(ledger_info.clone(), new_ledger_info.clone())
+        );
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!(
+                "safety_rules",
+                self.inner.sign_commit_vote(ledger_info, new_ledger_info)
+            );
+        }
+        result
+    }
+
+    fn sign_timeout_with_qc(
+        &mut self,
+        timeout: &TwoChainTimeout,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Ed25519Signature, Error> {
+        let mut result = monitor!(
+            "safety_rules",
+            self.inner.sign_timeout_with_qc(timeout, timeout_cert)
+        );
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!(
+                "safety_rules",
+                self.inner.sign_timeout_with_qc(timeout, timeout_cert)
+            );
+        }
+        result
+    }
+
+    fn construct_and_sign_vote_two_chain(
+        &mut self,
+        vote_proposal: &MaybeSignedVoteProposal,
+        timeout_cert: Option<&TwoChainTimeoutCertificate>,
+    ) -> Result<Vote, Error> {
+        let mut result = monitor!(
+            "safety_rules",
+            self.inner
+                .construct_and_sign_vote_two_chain(vote_proposal, timeout_cert)
+        );
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!(
+                "safety_rules",
+                self.inner
+                    .construct_and_sign_vote_two_chain(vote_proposal, timeout_cert)
+            );
+        }
+        result
+    }
 }
@@ -7,6 +7,8 @@ use crate::counters;
 use channel::message_queues::QueueStyle;
 use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
     epoch_retrieval::EpochRetrievalRequest,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
@@ -47,6 +49,12 @@ pub enum ConsensusMsg {
     /// VoteMsg is the struct that is ultimately sent by the voter in response for receiving a
     /// proposal.
     VoteMsg(Box<VoteMsg>),
+    /// CommitVoteMsg carries the signature of a validator on the execution result of ordered
+    /// blocks, with decoupled execution.
+    CommitVoteMsg(Box<CommitVote>),
+    /// CommitDecisionMsg carries the execution result of ordered blocks signed by a quorum of
+    /// validators, with decoupled execution.
+    CommitDecisionMsg(Box<CommitDecision>),
 }
 
 /// The interface from Network to Consensus layer.
@@ -8,7 +8,8 @@
 //! Votes are automatically dropped when the structure goes out of scope.
 
 use consensus_types::{
-    common::Author, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
+    common::Author, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate,
+    timeout_certificate::TimeoutCertificate, vote::Vote,
 };
 use diem_crypto::{hash::CryptoHash, HashValue};
 use diem_logger::prelude::*;
@@ -37,6 +38,8 @@ pub enum VoteReceptionResult {
     NewQuorumCertificate(Arc<QuorumCert>),
     /// The vote completes a new TimeoutCertificate
     NewTimeoutCertificate(Arc<TimeoutCertificate>),
+    /// The vote completes a new TwoChainTimeoutCertificate
+    New2ChainTimeoutCertificate(Arc<TwoChainTimeoutCertificate>),
     /// There might be some issues adding a vote
     ErrorAddingVote(VerifyError),
     /// The vote is not for the current round.
@@ -52,6 +55,9 @@ pub struct PendingVotes {
     /// Tracks all the signatures of the votes for the given round. In case we succeed to
     /// aggregate 2f+1 signatures a TimeoutCertificate is formed.
     maybe_partial_tc: Option<TimeoutCertificate>,
+    /// Tracks all the timeouts with the two-chain commit rule of the votes for the given round.
+    /// In case we succeed to aggregate 2f+1 signatures a TwoChainTimeoutCertificate is formed.
+    maybe_partial_2chain_tc: Option<TwoChainTimeoutCertificate>,
     /// Map of Author to vote. This is useful to discard multiple votes.
     author_to_vote: HashMap<Author, Vote>,
 }
@@ -62,6 +68,7 @@ impl PendingVotes {
         PendingVotes {
             li_digest_to_votes: HashMap::new(),
             maybe_partial_tc: None,
+            maybe_partial_2chain_tc: None,
             author_to_vote: HashMap::new(),
         }
     }
@@ -183,6 +190,38 @@ impl PendingVotes {
             }
         }
 
+        if let Some((timeout, signature)) = vote.two_chain_timeout() {
+            // if no partial 2-chain TC exist, create one
+            let partial_tc = self
+                .maybe_partial_2chain_tc
+                .get_or_insert_with(|| TwoChainTimeoutCertificate::new(timeout.clone()));
+
+            // add the timeout and its signature
+            partial_tc.add(vote.author(), timeout.clone(), signature.clone());
+
+            // did the 2-chain TC reach a threshold?
+            match validator_verifier.check_voting_power(partial_tc.signatures().keys()) {
+                // A quorum of signature was reached, a new 2-chain TC was formed!
+                Ok(_) => {
+                    return VoteReceptionResult::New2ChainTimeoutCertificate(Arc::new(
+                        partial_tc.clone(),
+                    ))
+                }
+
+                // not enough votes
+                Err(VerifyError::TooLittleVotingPower { .. }) => (),
+
+                // error
+                Err(error) => {
+                    error!(
+                        "MUST_FIX: 2-chain timeout vote received could not be added: {}, vote: {}",
+                        error, vote
+                    );
+                    return VoteReceptionResult::ErrorAddingVote(error);
+                }
+            }
+        }
+
         //
         // 5. No QC (or TC) could be formed, return the QC's voting power
         //
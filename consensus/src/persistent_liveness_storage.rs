@@ -4,19 +4,27 @@
 use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error::DbError};
 use anyhow::{format_err, Context, Result};
 use consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
+    block::Block, commit_vote::CommitVote, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, timeout_certificate::TimeoutCertificate,
+    vote::Vote,
 };
 use diem_config::config::NodeConfig;
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::{
-    block_info::Round, epoch_change::EpochChangeProof, ledger_info::LedgerInfo,
+    block_info::Round,
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::Version,
 };
 use executor_types::ExecutedTrees;
 use std::{cmp::max, collections::HashSet, sync::Arc};
 use storage_interface::DbReader;
 
+#[cfg(test)]
+#[path = "persistent_liveness_storage_test.rs"]
+mod persistent_liveness_storage_test;
+
 /// PersistentLivenessStorage is essential for maintaining liveness when a node crashes.  Specifically,
 /// upon a restart, a correct node will recover.  Even if all nodes crash, liveness is
 /// guaranteed.
@@ -32,6 +40,10 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// Persist consensus' state
     fn save_vote(&self, vote: &Vote) -> Result<()>;
 
+    /// Persist the commit vote on the execution result of the ordered blocks, with decoupled
+    /// execution
+    fn save_commit_vote(&self, commit_vote: &CommitVote) -> Result<()>;
+
     /// Construct data that can be recovered from ledger
     fn recover_from_ledger(&self) -> LedgerRecoveryData;
 
@@ -42,6 +54,13 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// to jump to this round
     fn save_highest_timeout_cert(&self, highest_timeout_cert: TimeoutCertificate) -> Result<()>;
 
+    /// Persist the highest timeout certificate with the two-chain commit rule for improved
+    /// liveness - proof for other replicas to jump to this round
+    fn save_highest_2chain_timeout_cert(
+        &self,
+        highest_timeout_cert: TwoChainTimeoutCertificate,
+    ) -> Result<()>;
+
     /// Retrieve a epoch change proof for SafetyRules so it can instantiate its
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
@@ -153,6 +172,8 @@ impl RootMetadata {
 pub struct RecoveryData {
     // The last vote message sent by this validator.
     last_vote: Option<Vote>,
+    // The last commit vote sent by this validator on blocks not committed to the ledger yet.
+    last_commit_vote: Option<CommitVote>,
     root: RootInfo,
     root_metadata: RootMetadata,
     // 1. the blocks guarantee the topological ordering - parent <- child.
@@ -163,6 +184,7 @@ pub struct RecoveryData {
 
     // Liveness data
     highest_timeout_certificate: Option<TimeoutCertificate>,
+    highest_2chain_timeout_certificate: Option<TwoChainTimeoutCertificate>,
 }
 
 impl RecoveryData {
@@ -173,6 +195,8 @@ impl RecoveryData {
         root_metadata: RootMetadata,
         mut quorum_certs: Vec<QuorumCert>,
         highest_timeout_certificate: Option<TimeoutCertificate>,
+        highest_2chain_timeout_certificate: Option<TwoChainTimeoutCertificate>,
+        last_commit_vote: Option<CommitVote>,
     ) -> Result<Self> {
         let root = ledger_recovery_data
             .find_root(&mut blocks, &mut quorum_certs)
@@ -201,11 +225,16 @@ impl RecoveryData {
             &mut quorum_certs,
         ));
         let epoch = root.0.epoch();
+        let root_round = root.0.round();
         Ok(RecoveryData {
             last_vote: match last_vote {
                 Some(v) if v.epoch() == epoch => Some(v),
                 _ => None,
             },
+            last_commit_vote: match last_commit_vote {
+                Some(v) if v.epoch() == epoch && v.round() > root_round => Some(v),
+                _ => None,
+            },
             root,
             root_metadata,
             blocks,
@@ -215,6 +244,10 @@ impl RecoveryData {
                 Some(tc) if tc.epoch() == epoch => Some(tc),
                 _ => None,
             },
+            highest_2chain_timeout_certificate: match highest_2chain_timeout_certificate {
+                Some(tc) if tc.epoch() == epoch => Some(tc),
+                _ => None,
+            },
         })
     }
 
@@ -226,6 +259,22 @@ impl RecoveryData {
         self.last_vote.clone()
     }
 
+    pub fn last_commit_vote(&self) -> Option<CommitVote> {
+        self.last_commit_vote.clone()
+    }
+
+    /// The root is the last block committed to the ledger, with decoupled execution the blocks
+    /// above it may have been ordered already. Returns the highest LedgerInfo ordering blocks
+    /// above the root, these blocks have to be executed and committed again after a restart.
+    pub fn ordered_proof(&self) -> Option<LedgerInfoWithSignatures> {
+        let root_round = self.root.0.round();
+        self.quorum_certs
+            .iter()
+            .filter(|qc| qc.commit_info().round() > root_round)
+            .max_by_key(|qc| qc.commit_info().round())
+            .map(|qc| qc.ledger_info().clone())
+    }
+
     pub fn take(self) -> (RootInfo, RootMetadata, Vec<Block>, Vec<QuorumCert>) {
         (
             self.root,
@@ -245,6 +294,10 @@ impl RecoveryData {
         self.highest_timeout_certificate.clone()
     }
 
+    pub fn highest_2chain_timeout_certificate(&self) -> Option<TwoChainTimeoutCertificate> {
+        self.highest_2chain_timeout_certificate.clone()
+    }
+
     fn find_blocks_to_prune(
         root_id: HashValue,
         blocks: &mut Vec<Block>,
@@ -301,6 +354,10 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(self.db.save_vote(bcs::to_bytes(vote)?)?)
     }
 
+    fn save_commit_vote(&self, commit_vote: &CommitVote) -> Result<()> {
+        Ok(self.db.save_commit_vote(bcs::to_bytes(commit_vote)?)?)
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         let startup_info = self
             .diem_db
@@ -325,6 +382,17 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         let highest_timeout_certificate = raw_data.1.map(|ts| {
             bcs::from_bytes(&ts[..]).expect("unable to deserialize highest timeout certificate")
         });
+        let highest_2chain_timeout_certificate = raw_data.4.map(|ts| {
+            bcs::from_bytes(&ts[..])
+                .expect("unable to deserialize highest 2-chain timeout certificate")
+        });
+        let last_commit_vote = self
+            .db
+            .get_last_commit_vote()
+            .expect("unable to recover consensus data")
+            .map(|vote_data| {
+                bcs::from_bytes(&vote_data[..]).expect("unable to deserialize last commit vote msg")
+            });
         let blocks = raw_data.2;
         let quorum_certs: Vec<_> = raw_data.3;
         let blocks_repr: Vec<String> = blocks.iter().map(|b| format!("\n\t{}", b)).collect();
@@ -365,6 +433,8 @@ impl PersistentLivenessStorage for StorageWriteProxy {
             ),
             quorum_certs,
             highest_timeout_certificate,
+            highest_2chain_timeout_certificate,
+            last_commit_vote,
         ) {
             Ok(mut initial_data) => {
                 (self as &dyn PersistentLivenessStorage)
@@ -375,11 +445,21 @@ impl PersistentLivenessStorage for StorageWriteProxy {
                         .delete_last_vote_msg()
                         .expect("unable to cleanup last vote");
                 }
+                if initial_data.last_commit_vote.is_none() {
+                    self.db
+                        .delete_last_commit_vote_msg()
+                        .expect("unable to cleanup last commit vote");
+                }
                 if initial_data.highest_timeout_certificate.is_none() {
                     self.db
                         .delete_highest_timeout_certificate()
                         .expect("unable to cleanup highest timeout cert");
                 }
+                if initial_data.highest_2chain_timeout_certificate.is_none() {
+                    self.db
+                        .delete_highest_2chain_timeout_certificate()
+                        .expect("unable to cleanup highest 2-chain timeout cert");
+                }
                 info!(
                     "Starting up the consensus state machine with recovery data - [last_vote {}], [highest timeout certificate: {}]",
                     initial_data.last_vote.as_ref().map_or("None".to_string(), |v| v.to_string()),
@@ -401,6 +481,15 @@ impl PersistentLivenessStorage for StorageWriteProxy {
             .save_highest_timeout_certificate(bcs::to_bytes(&highest_timeout_cert)?)?)
     }
 
+    fn save_highest_2chain_timeout_cert(
+        &self,
+        highest_timeout_cert: TwoChainTimeoutCertificate,
+    ) -> Result<()> {
+        Ok(self
+            .db
+            .save_highest_2chain_timeout_certificate(bcs::to_bytes(&highest_timeout_cert)?)?)
+    }
+
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
         let (_, proofs, _) = self
             .diem_db
@@ -0,0 +1,102 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::persistent_liveness_storage::{LedgerRecoveryData, RecoveryData, RootMetadata};
+use consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        Block,
+    },
+    commit_vote::CommitVote,
+    quorum_cert::QuorumCert,
+};
+use diem_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue};
+use diem_types::{
+    block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+};
+
+/// Genesis--> B1--> B2--> B3, the QC of B3 orders B1.
+fn ordered_chain(signer: &ValidatorSigner) -> (Vec<Block>, Vec<QuorumCert>) {
+    let mut blocks: Vec<Block> = vec![];
+    let mut quorum_certs = vec![];
+    let mut parent_qc = certificate_for_genesis();
+    for round in 1..=3 {
+        let block = Block::new_proposal(vec![], round, round, parent_qc.clone(), signer);
+        let committed_block = if round >= 3 {
+            Some(ordered_info(&blocks[round as usize - 3]))
+        } else {
+            None
+        };
+        let qc = gen_test_certificate(
+            vec![signer],
+            ordered_info(&block),
+            parent_qc.certified_block().clone(),
+            committed_block,
+        );
+        blocks.push(block);
+        quorum_certs.push(qc.clone());
+        parent_qc = qc;
+    }
+    (blocks, quorum_certs)
+}
+
+fn ordered_info(block: &Block) -> BlockInfo {
+    block.gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None)
+}
+
+fn recovery_data(
+    storage_ledger: LedgerInfo,
+    blocks: Vec<Block>,
+    quorum_certs: Vec<QuorumCert>,
+    last_commit_vote: Option<CommitVote>,
+) -> RecoveryData {
+    RecoveryData::new(
+        None,
+        LedgerRecoveryData::new(storage_ledger),
+        blocks,
+        RootMetadata::new_empty(),
+        quorum_certs,
+        None,
+        None,
+        last_commit_vote,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_recover_ordered_blocks() {
+    let signer = ValidatorSigner::from_int(0);
+    let (blocks, quorum_certs) = ordered_chain(&signer);
+    let executed_info = blocks[0].gen_block_info(HashValue::random(), 1, None);
+    let executed_ledger_info = LedgerInfo::new(executed_info, HashValue::zero());
+    let commit_vote = CommitVote::new_with_signature(
+        signer.author(),
+        executed_ledger_info.clone(),
+        signer.sign(&executed_ledger_info),
+    );
+
+    // B1 was ordered but not committed to the ledger before the restart
+    let data = recovery_data(
+        LedgerInfo::mock_genesis(None),
+        blocks.clone(),
+        quorum_certs.clone(),
+        Some(commit_vote.clone()),
+    );
+    assert_eq!(data.root_block().round(), 0);
+    assert_eq!(
+        data.ordered_proof().as_ref(),
+        Some(quorum_certs[2].ledger_info())
+    );
+    assert_eq!(data.last_commit_vote(), Some(commit_vote.clone()));
+
+    // B1 was committed to the ledger, nothing is left to execute
+    let data = recovery_data(
+        executed_ledger_info,
+        blocks,
+        quorum_certs,
+        Some(commit_vote),
+    );
+    assert_eq!(data.root_block().round(), 1);
+    assert!(data.ordered_proof().is_none());
+    assert!(data.last_commit_vote().is_none());
+}
@@ -25,17 +25,23 @@ use anyhow::{bail, ensure, Context, Result};
 use consensus_types::{
     block::Block,
     block_retrieval::{BlockRetrievalResponse, BlockRetrievalStatus},
+    commit_decision::CommitDecision,
+    commit_vote::CommitVote,
     common::{Author, Round},
     proposal_msg::ProposalMsg,
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
+    timeout_2chain::TwoChainTimeoutCertificate,
     timeout_certificate::TimeoutCertificate,
     vote::Vote,
     vote_msg::VoteMsg,
 };
 use diem_infallible::checked;
 use diem_logger::prelude::*;
-use diem_types::{epoch_state::EpochState, validator_verifier::ValidatorVerifier};
+use diem_types::{
+    epoch_state::EpochState, on_chain_config::OnChainConsensusConfig,
+    validator_verifier::ValidatorVerifier,
+};
 use fail::fail_point;
 #[cfg(test)]
 use safety_rules::ConsensusState;
@@ -49,6 +55,8 @@ pub enum UnverifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    CommitVote(Box<CommitVote>),
+    CommitDecision(Box<CommitDecision>),
 }
 
 impl UnverifiedEvent {
@@ -66,6 +74,14 @@ impl UnverifiedEvent {
                 s.verify(validator)?;
                 VerifiedEvent::SyncInfo(s)
             }
+            UnverifiedEvent::CommitVote(v) => {
+                v.verify(validator)?;
+                VerifiedEvent::CommitVote(v)
+            }
+            UnverifiedEvent::CommitDecision(d) => {
+                d.verify(validator)?;
+                VerifiedEvent::CommitDecision(d)
+            }
         })
     }
 
@@ -74,6 +90,8 @@ impl UnverifiedEvent {
             UnverifiedEvent::ProposalMsg(p) => p.epoch(),
             UnverifiedEvent::VoteMsg(v) => v.epoch(),
             UnverifiedEvent::SyncInfo(s) => s.epoch(),
+            UnverifiedEvent::CommitVote(v) => v.epoch(),
+            UnverifiedEvent::CommitDecision(d) => d.epoch(),
         }
     }
 }
@@ -84,6 +102,8 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::ProposalMsg(m) => UnverifiedEvent::ProposalMsg(m),
             ConsensusMsg::VoteMsg(m) => UnverifiedEvent::VoteMsg(m),
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
+            ConsensusMsg::CommitVoteMsg(m) => UnverifiedEvent::CommitVote(m),
+            ConsensusMsg::CommitDecisionMsg(m) => UnverifiedEvent::CommitDecision(m),
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -93,6 +113,8 @@ pub enum VerifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    CommitVote(Box<CommitVote>),
+    CommitDecision(Box<CommitDecision>),
 }
 
 #[cfg(test)]
@@ -190,6 +212,8 @@ pub struct RoundManager {
     txn_manager: Arc<dyn TxnManager>,
     storage: Arc<dyn PersistentLivenessStorage>,
     sync_only: bool,
+    decoupled_execution: bool,
+    onchain_config: OnChainConsensusConfig,
 }
 
 impl RoundManager {
@@ -204,6 +228,8 @@ impl RoundManager {
         txn_manager: Arc<dyn TxnManager>,
         storage: Arc<dyn PersistentLivenessStorage>,
         sync_only: bool,
+        decoupled_execution: bool,
+        onchain_config: OnChainConsensusConfig,
     ) -> Self {
         counters::OP_COUNTERS
             .gauge("sync_only")
@@ -219,6 +245,8 @@ impl RoundManager {
             txn_manager,
             storage,
             sync_only,
+            decoupled_execution,
+            onchain_config,
         }
     }
 
@@ -456,12 +484,26 @@ impl RoundManager {
         };
 
         if !timeout_vote.is_timeout() {
-            let timeout = timeout_vote.timeout();
-            let signature = self
-                .safety_rules
-                .sign_timeout(&timeout)
-                .context("[RoundManager] SafetyRules signs timeout")?;
-            timeout_vote.add_timeout_signature(signature);
+            if self.onchain_config.two_chain() {
+                let timeout = timeout_vote.generate_2chain_timeout(
+                    self.block_store.highest_quorum_cert().as_ref().clone(),
+                );
+                let signature = self
+                    .safety_rules
+                    .sign_timeout_with_qc(
+                        &timeout,
+                        self.block_store.highest_2chain_timeout_cert().as_deref(),
+                    )
+                    .context("[RoundManager] SafetyRules signs 2-chain timeout")?;
+                timeout_vote.add_2chain_timeout(timeout, signature);
+            } else {
+                let timeout = timeout_vote.timeout();
+                let signature = self
+                    .safety_rules
+                    .sign_timeout(&timeout)
+                    .context("[RoundManager] SafetyRules signs timeout")?;
+                timeout_vote.add_timeout_signature(signature);
+            }
         }
 
         self.round_state.record_vote(timeout_vote.clone());
@@ -551,16 +593,19 @@ impl RoundManager {
             .block_store
             .execute_and_insert_block(proposed_block)
             .context("[RoundManager] Failed to execute_and_insert the block")?;
-        // notify mempool about failed txn
-        let compute_result = executed_block.compute_result();
-        if let Err(e) = self
-            .txn_manager
-            .notify(executed_block.block(), compute_result)
-            .await
-        {
-            error!(
-                error = ?e, "[RoundManager] Failed to notify mempool of rejected txns",
-            );
+        // notify mempool about failed txn, the execution phase takes care of it with decoupled
+        // execution
+        if !self.decoupled_execution {
+            let compute_result = executed_block.compute_result();
+            if let Err(e) = self
+                .txn_manager
+                .notify(executed_block.block(), compute_result)
+                .await
+            {
+                error!(
+                    error = ?e, "[RoundManager] Failed to notify mempool of rejected txns",
+                );
+            }
         }
 
         // Short circuit if already voted.
@@ -576,15 +621,21 @@ impl RoundManager {
         );
 
         let maybe_signed_vote_proposal = executed_block.maybe_signed_vote_proposal();
-        let vote = self
-            .safety_rules
-            .construct_and_sign_vote(&maybe_signed_vote_proposal)
-            .context(format!(
-                "[RoundManager] SafetyRules {}Rejected{} {}",
-                Fg(Red),
-                Fg(Reset),
-                executed_block.block()
-            ))?;
+        let vote_result = if self.onchain_config.two_chain() {
+            self.safety_rules.construct_and_sign_vote_two_chain(
+                &maybe_signed_vote_proposal,
+                self.block_store.highest_2chain_timeout_cert().as_deref(),
+            )
+        } else {
+            self.safety_rules
+                .construct_and_sign_vote(&maybe_signed_vote_proposal)
+        };
+        let vote = vote_result.context(format!(
+            "[RoundManager] SafetyRules {}Rejected{} {}",
+            Fg(Red),
+            Fg(Reset),
+            executed_block.block()
+        ))?;
         observe_block(executed_block.block().timestamp_usecs(), BlockStage::VOTED);
 
         self.storage
@@ -668,6 +719,9 @@ impl RoundManager {
                 self.new_qc_aggregated(qc, vote.author()).await
             }
             VoteReceptionResult::NewTimeoutCertificate(tc) => self.new_tc_aggregated(tc).await,
+            VoteReceptionResult::New2ChainTimeoutCertificate(tc) => {
+                self.new_2chain_tc_aggregated(tc, vote.author()).await
+            }
             _ => Ok(()),
         }
     }
@@ -699,6 +753,28 @@ impl RoundManager {
         result
     }
 
+    async fn new_2chain_tc_aggregated(
+        &mut self,
+        tc: Arc<TwoChainTimeoutCertificate>,
+        preferred_peer: Author,
+    ) -> anyhow::Result<()> {
+        // The highest QC of the TC has to be known before the TC is, so that the QC of the next
+        // proposal is safe to vote for.
+        self.block_store
+            .insert_quorum_cert(
+                tc.timeout().quorum_cert(),
+                &mut self.create_block_retriever(preferred_peer),
+            )
+            .await
+            .context("[RoundManager] Failed to process the QC of a newly aggregated 2-chain TC")?;
+        let result = self
+            .block_store
+            .insert_2chain_timeout_certificate(tc)
+            .context("[RoundManager] Failed to process a newly aggregated 2-chain TC");
+        self.process_certificates().await?;
+        result
+    }
+
     /// Retrieve a n chained blocks from the block store starting from
     /// an initial parent id, returning with <n (as many as possible) if
     /// id or its ancestors can not be found.
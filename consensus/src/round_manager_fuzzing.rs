@@ -22,7 +22,7 @@ use diem_types::{
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    on_chain_config::ValidatorSet,
+    on_chain_config::{OnChainConsensusConfig, ValidatorSet},
     validator_info::ValidatorInfo,
     validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
@@ -70,6 +70,7 @@ fn build_empty_store(
         Arc::new(EmptyStateComputer),
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
+        false,
     ))
 }
 
@@ -106,7 +107,7 @@ fn create_node_for_fuzzing() -> RoundManager {
 
     // TODO: remove
     let proof = make_initial_epoch_change_proof(&signer);
-    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), false, false);
+    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), false, false, false);
     safety_rules.initialize(&proof).unwrap();
 
     // TODO: mock channels
@@ -163,6 +164,8 @@ fn create_node_for_fuzzing() -> RoundManager {
         Arc::new(MockTransactionManager::new(None)),
         storage,
         false,
+        false,
+        OnChainConsensusConfig::default(),
     )
 }
 
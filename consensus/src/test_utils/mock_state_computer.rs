@@ -134,3 +134,47 @@ impl StateComputer for EmptyStateComputer {
         Ok(())
     }
 }
+
+/// A state computer only reporting the commits and the syncs it is asked for, used in the tests
+/// of the decoupled execution phases.
+pub struct RecordingStateComputer {
+    commit_sender: mpsc::UnboundedSender<(Vec<HashValue>, LedgerInfoWithSignatures)>,
+    sync_sender: mpsc::UnboundedSender<LedgerInfoWithSignatures>,
+}
+
+impl RecordingStateComputer {
+    pub fn new(
+        commit_sender: mpsc::UnboundedSender<(Vec<HashValue>, LedgerInfoWithSignatures)>,
+        sync_sender: mpsc::UnboundedSender<LedgerInfoWithSignatures>,
+    ) -> Self {
+        Self {
+            commit_sender,
+            sync_sender,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StateComputer for RecordingStateComputer {
+    fn compute(
+        &self,
+        _block: &Block,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult, Error> {
+        Ok(StateComputeResult::new_dummy())
+    }
+
+    async fn commit(
+        &self,
+        block_ids: Vec<HashValue>,
+        commit: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        let _ = self.commit_sender.unbounded_send((block_ids, commit));
+        Ok(())
+    }
+
+    async fn sync_to(&self, commit: LedgerInfoWithSignatures) -> Result<(), StateSyncError> {
+        let _ = self.sync_sender.unbounded_send(commit);
+        Ok(())
+    }
+}
@@ -9,7 +9,9 @@ use crate::{
 };
 use anyhow::Result;
 use consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
+    block::Block, commit_vote::CommitVote, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, timeout_certificate::TimeoutCertificate,
+    vote::Vote,
 };
 use diem_crypto::HashValue;
 use diem_infallible::Mutex;
@@ -30,9 +32,11 @@ pub struct MockSharedStorage {
     pub qc: Mutex<HashMap<HashValue, QuorumCert>>,
     pub lis: Mutex<HashMap<u64, LedgerInfoWithSignatures>>,
     pub last_vote: Mutex<Option<Vote>>,
+    pub last_commit_vote: Mutex<Option<CommitVote>>,
 
     // Liveness state
     pub highest_timeout_certificate: Mutex<Option<TimeoutCertificate>>,
+    pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
     pub validator_set: ValidatorSet,
 }
 
@@ -43,7 +47,9 @@ impl MockSharedStorage {
             qc: Mutex::new(HashMap::new()),
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
+            last_commit_vote: Mutex::new(None),
             highest_timeout_certificate: Mutex::new(None),
+            highest_2chain_timeout_certificate: Mutex::new(None),
             validator_set,
         }
     }
@@ -127,6 +133,11 @@ impl MockStorage {
                 .highest_timeout_certificate
                 .lock()
                 .clone(),
+            self.shared_storage
+                .highest_2chain_timeout_certificate
+                .lock()
+                .clone(),
+            self.shared_storage.last_commit_vote.lock().clone(),
         )
     }
 
@@ -190,6 +201,14 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(())
     }
 
+    fn save_commit_vote(&self, last_commit_vote: &CommitVote) -> Result<()> {
+        self.shared_storage
+            .last_commit_vote
+            .lock()
+            .replace(last_commit_vote.clone());
+        Ok(())
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         self.get_ledger_recovery_data()
     }
@@ -212,6 +231,17 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(())
     }
 
+    fn save_highest_2chain_timeout_cert(
+        &self,
+        highest_timeout_certificate: TwoChainTimeoutCertificate,
+    ) -> Result<()> {
+        self.shared_storage
+            .highest_2chain_timeout_certificate
+            .lock()
+            .replace(highest_timeout_certificate);
+        Ok(())
+    }
+
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
         let lis = self
             .shared_storage
@@ -258,6 +288,10 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(())
     }
 
+    fn save_commit_vote(&self, _: &CommitVote) -> Result<()> {
+        Ok(())
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         LedgerRecoveryData::new(LedgerInfo::mock_genesis(None))
     }
@@ -270,6 +304,8 @@ impl PersistentLivenessStorage for EmptyStorage {
             RootMetadata::new_empty(),
             vec![],
             None,
+            None,
+            None,
         ) {
             Ok(recovery_data) => LivenessStorageData::RecoveryData(recovery_data),
             Err(e) => {
@@ -283,6 +319,10 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(())
     }
 
+    fn save_highest_2chain_timeout_cert(&self, _: TwoChainTimeoutCertificate) -> Result<()> {
+        Ok(())
+    }
+
     fn retrieve_epoch_change_proof(&self, _version: u64) -> Result<EpochChangeProof> {
         unimplemented!()
     }
@@ -23,7 +23,7 @@ mod mock_txn_manager;
 use crate::util::mock_time_service::SimulatedTimeService;
 use consensus_types::{block::block_test_utils::gen_test_certificate, common::Payload};
 use diem_types::block_info::BlockInfo;
-pub use mock_state_computer::{EmptyStateComputer, MockStateComputer};
+pub use mock_state_computer::{EmptyStateComputer, MockStateComputer, RecordingStateComputer};
 pub use mock_storage::{EmptyStorage, MockSharedStorage, MockStorage};
 pub use mock_txn_manager::MockTransactionManager;
 
@@ -65,6 +65,7 @@ pub fn build_empty_tree() -> Arc<BlockStore> {
         Arc::new(EmptyStateComputer),
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
+        false,
     ))
 }
 
@@ -166,7 +167,12 @@ pub fn placeholder_ledger_info() -> LedgerInfo {
 }
 
 pub fn placeholder_sync_info() -> SyncInfo {
-    SyncInfo::new(certificate_for_genesis(), certificate_for_genesis(), None)
+    SyncInfo::new(
+        certificate_for_genesis(),
+        certificate_for_genesis(),
+        None,
+        None,
+    )
 }
 
 fn nocapture() -> bool {
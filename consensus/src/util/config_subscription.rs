@@ -4,7 +4,9 @@
 use channel::diem_channel::Receiver;
 use diem_types::{
     account_config::NewEpochEvent,
-    on_chain_config::{OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
+    on_chain_config::{
+        OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY, OPTIONAL_ON_CHAIN_CONFIG_REGISTRY,
+    },
 };
 use subscription_service::ReconfigSubscription;
 
@@ -13,7 +15,11 @@ pub fn gen_consensus_reconfig_subscription(
 ) -> (ReconfigSubscription, Receiver<(), OnChainConfigPayload>) {
     ReconfigSubscription::subscribe_all(
         "consensus",
-        ON_CHAIN_CONFIG_REGISTRY.to_vec(),
+        ON_CHAIN_CONFIG_REGISTRY
+            .iter()
+            .chain(OPTIONAL_ON_CHAIN_CONFIG_REGISTRY)
+            .cloned()
+            .collect(),
         vec![NewEpochEvent::event_key()],
     )
 }
@@ -0,0 +1,547 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for the BLS signature scheme over the BLS12-381 curve, as defined
+//! in the [IRTF draft](https://tools.ietf.org/html/draft-irtf-cfrg-bls-signature-04), with public
+//! keys in G1 and signatures in G2 (the "minimal-pubkey-size" variant).
+//!
+//! Signatures on the same message can be aggregated into a single signature, which is verified
+//! against the aggregate of the signers' public keys. To prevent rogue-key attacks, aggregate
+//! verification is only sound for public keys whose [`BLS12381ProofOfPossession`] has been
+//! verified beforehand (the "proof of possession" scheme of the draft).
+//!
+//! As for the other schemes of this crate, structs are signed along with the seed of their
+//! `CryptoHasher`, which separates the domains of the messages.
+//!
+//! # Examples
+//!
+//! ```
+//! use diem_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use diem_crypto::{
+//!     bls12381::*,
+//!     traits::{Signature, SigningKey, Uniform},
+//! };
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+//! let private_keys: Vec<_> = (0..3).map(|_| BLS12381PrivateKey::generate(&mut rng)).collect();
+//! let public_keys: Vec<BLS12381PublicKey> = private_keys.iter().map(|key| key.into()).collect();
+//! for (private_key, public_key) in private_keys.iter().zip(public_keys.iter()) {
+//!     let pop = BLS12381ProofOfPossession::create(private_key);
+//!     assert!(pop.verify(public_key).is_ok());
+//! }
+//!
+//! let signatures = private_keys.iter().map(|key| key.sign(&message)).collect();
+//! let aggregate_signature = BLS12381Signature::aggregate(signatures).unwrap();
+//! let public_keys: Vec<_> = public_keys.iter().collect();
+//! assert!(aggregate_signature.verify_aggregate(&message, &public_keys).is_ok());
+//! ```
+//! **Note**: The above example generates a private key using a private function intended only for
+//! testing purposes. Production code should find an alternate means for secure key generation.
+
+use crate::{
+    hash::{CryptoHash, CryptoHasher},
+    traits::*,
+};
+use anyhow::{anyhow, ensure, Result};
+use blst::{min_pk, BLST_ERROR};
+use core::convert::TryFrom;
+use diem_crypto_derive::{DeserializeKey, SerializeKey, SilentDebug, SilentDisplay};
+use serde::Serialize;
+use std::fmt;
+
+/// The length of the BLS12381PrivateKey
+pub const BLS12381_PRIVATE_KEY_LENGTH: usize = 32;
+/// The length of the BLS12381PublicKey, a compressed point of G1
+pub const BLS12381_PUBLIC_KEY_LENGTH: usize = 48;
+/// The length of the BLS12381Signature, a compressed point of G2
+pub const BLS12381_SIGNATURE_LENGTH: usize = 96;
+
+/// The domain separation tag used to hash messages to G2 when signing them.
+const DST_BLS_SIG: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// The domain separation tag used to hash public keys to G2 when proving possession of their
+/// private key.
+const DST_BLS_POP: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A BLS12-381 private key
+#[derive(DeserializeKey, SerializeKey, SilentDebug, SilentDisplay)]
+pub struct BLS12381PrivateKey(min_pk::SecretKey);
+
+#[cfg(feature = "assert-private-keys-not-cloneable")]
+static_assertions::assert_not_impl_any!(BLS12381PrivateKey: Clone);
+
+#[cfg(any(test, feature = "cloneable-private-keys"))]
+impl Clone for BLS12381PrivateKey {
+    fn clone(&self) -> Self {
+        let serialized: &[u8] = &(self.to_bytes());
+        BLS12381PrivateKey::try_from(serialized).unwrap()
+    }
+}
+
+/// A BLS12-381 public key, it's only safe to aggregate once its proof of possession is verified.
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381PublicKey(min_pk::PublicKey);
+
+/// A BLS12-381 signature, either of a single signer or aggregated from the signatures of several
+/// signers on the same message.
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381Signature(min_pk::Signature);
+
+/// A proof of possession of the private key of a BLS12-381 public key, i.e. a signature of the
+/// public key under a domain separation tag distinct from the one of regular signatures.
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381ProofOfPossession(min_pk::Signature);
+
+impl BLS12381PrivateKey {
+    /// The length of the BLS12381PrivateKey
+    pub const LENGTH: usize = BLS12381_PRIVATE_KEY_LENGTH;
+
+    /// Serialize a BLS12381PrivateKey.
+    pub fn to_bytes(&self) -> [u8; BLS12381_PRIVATE_KEY_LENGTH] {
+        self.0.to_bytes()
+    }
+
+    /// Private function aimed at minimizing code duplication between sign
+    /// methods of the SigningKey implementation. This should remain private.
+    fn sign_arbitrary_message(&self, message: &[u8]) -> BLS12381Signature {
+        BLS12381Signature(self.0.sign(message, DST_BLS_SIG, &[]))
+    }
+}
+
+impl BLS12381PublicKey {
+    /// Serialize a BLS12381PublicKey.
+    pub fn to_bytes(&self) -> [u8; BLS12381_PUBLIC_KEY_LENGTH] {
+        self.0.compress()
+    }
+
+    /// Aggregates the public keys of the signers of an aggregate signature. The proof of
+    /// possession of every public key must have been verified.
+    pub fn aggregate(public_keys: &[&BLS12381PublicKey]) -> Result<BLS12381PublicKey> {
+        ensure!(!public_keys.is_empty(), "No public key to aggregate");
+        let public_keys: Vec<_> = public_keys.iter().map(|key| &key.0).collect();
+        let aggregate_public_key = min_pk::AggregatePublicKey::aggregate(&public_keys, false)
+            .map_err(|e| anyhow!("Failed to aggregate public keys: {:?}", e))?;
+        Ok(BLS12381PublicKey(aggregate_public_key.to_public_key()))
+    }
+}
+
+impl BLS12381Signature {
+    /// The length of the BLS12381Signature
+    pub const LENGTH: usize = BLS12381_SIGNATURE_LENGTH;
+
+    /// Serialize a BLS12381Signature.
+    pub fn to_bytes(&self) -> [u8; BLS12381_SIGNATURE_LENGTH] {
+        self.0.compress()
+    }
+
+    /// Aggregates the signatures of several signers into a single signature, every signature
+    /// has to be checked to lie in the prime-order subgroup of G2.
+    pub fn aggregate(signatures: Vec<BLS12381Signature>) -> Result<BLS12381Signature> {
+        ensure!(!signatures.is_empty(), "No signature to aggregate");
+        let signatures: Vec<_> = signatures.iter().map(|signature| &signature.0).collect();
+        let aggregate_signature = min_pk::AggregateSignature::aggregate(&signatures, true)
+            .map_err(|e| anyhow!("Failed to aggregate signatures: {:?}", e))?;
+        Ok(BLS12381Signature(aggregate_signature.to_signature()))
+    }
+
+    /// Verifies an aggregate signature of the signers with the given public keys on the same
+    /// message. The proof of possession of every public key must have been verified.
+    pub fn verify_aggregate<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_keys: &[&BLS12381PublicKey],
+    ) -> Result<()> {
+        ensure!(!public_keys.is_empty(), "No public key to verify against");
+        let bytes = signing_message(message)?;
+        let public_keys: Vec<_> = public_keys.iter().map(|key| &key.0).collect();
+        check_result(
+            self.0
+                .fast_aggregate_verify(true, &bytes, DST_BLS_SIG, &public_keys),
+        )
+    }
+
+    /// Verifies an aggregate signature of the signers with the given public keys on their
+    /// respective arbitrary messages, which have to be distinct.
+    pub fn verify_aggregate_arbitrary_msgs(
+        &self,
+        messages: &[&[u8]],
+        public_keys: &[&BLS12381PublicKey],
+    ) -> Result<()> {
+        ensure!(
+            !messages.is_empty() && messages.len() == public_keys.len(),
+            "Expected as many messages ({}) as public keys ({})",
+            messages.len(),
+            public_keys.len()
+        );
+        let public_keys: Vec<_> = public_keys.iter().map(|key| &key.0).collect();
+        check_result(
+            self.0
+                .aggregate_verify(true, messages, DST_BLS_SIG, &public_keys, true),
+        )
+    }
+
+    /// return a signature of an arbitrary message (for test only)
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn dummy_signature() -> Self {
+        let private_key = BLS12381PrivateKey::generate_for_testing();
+        private_key.sign_arbitrary_message(b"dummy")
+    }
+}
+
+impl BLS12381ProofOfPossession {
+    /// Creates the proof of possession of the given private key.
+    pub fn create(private_key: &BLS12381PrivateKey) -> Self {
+        let public_key = BLS12381PublicKey::from(private_key);
+        BLS12381ProofOfPossession(private_key.0.sign(&public_key.to_bytes(), DST_BLS_POP, &[]))
+    }
+
+    /// Verifies that the signer of the proof possesses the private key of the given public key.
+    pub fn verify(&self, public_key: &BLS12381PublicKey) -> Result<()> {
+        check_result(self.0.verify(
+            true,
+            &public_key.to_bytes(),
+            DST_BLS_POP,
+            &[],
+            &public_key.0,
+            false,
+        ))
+    }
+
+    /// Serialize a BLS12381ProofOfPossession.
+    pub fn to_bytes(&self) -> [u8; BLS12381_SIGNATURE_LENGTH] {
+        self.0.compress()
+    }
+}
+
+/// Returns the bytes signed for a struct: the seed of its hasher followed by its BCS bytes.
+fn signing_message<T: CryptoHash + Serialize>(message: &T) -> Result<Vec<u8>> {
+    let mut bytes = <T::Hasher as CryptoHasher>::seed().to_vec();
+    bcs::serialize_into(&mut bytes, &message)
+        .map_err(|_| CryptoMaterialError::SerializationError)?;
+    Ok(bytes)
+}
+
+fn check_result(result: BLST_ERROR) -> Result<()> {
+    if result == BLST_ERROR::BLST_SUCCESS {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "BLS12-381 signature verification failed: {:?}",
+            result
+        ))
+    }
+}
+
+///////////////////////
+// PrivateKey Traits //
+///////////////////////
+
+impl PrivateKey for BLS12381PrivateKey {
+    type PublicKeyMaterial = BLS12381PublicKey;
+}
+
+impl SigningKey for BLS12381PrivateKey {
+    type VerifyingKeyMaterial = BLS12381PublicKey;
+    type SignatureMaterial = BLS12381Signature;
+
+    fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> BLS12381Signature {
+        let bytes =
+            signing_message(message).expect("Serialization of signable material should not fail.");
+        BLS12381PrivateKey::sign_arbitrary_message(&self, bytes.as_ref())
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    fn sign_arbitrary_message(&self, message: &[u8]) -> BLS12381Signature {
+        BLS12381PrivateKey::sign_arbitrary_message(self, message)
+    }
+}
+
+impl Uniform for BLS12381PrivateKey {
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        // The key material has to be at least 32 bytes long.
+        let mut ikm = [0u8; 32];
+        rng.fill_bytes(&mut ikm);
+        let secret_key = min_pk::SecretKey::key_gen(&ikm, &[])
+            .expect("Key generation from 32 bytes should not fail");
+        BLS12381PrivateKey(secret_key)
+    }
+}
+
+impl PartialEq<Self> for BLS12381PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for BLS12381PrivateKey {}
+
+impl TryFrom<&[u8]> for BLS12381PrivateKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381PrivateKey. This method will also check that the key is a non-zero
+    /// scalar smaller than the order of the groups.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381PrivateKey, CryptoMaterialError> {
+        if bytes.len() != BLS12381_PRIVATE_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        min_pk::SecretKey::from_bytes(bytes)
+            .map(BLS12381PrivateKey)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl Length for BLS12381PrivateKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381PrivateKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// PublicKey Traits //
+//////////////////////
+
+impl From<&BLS12381PrivateKey> for BLS12381PublicKey {
+    fn from(private_key: &BLS12381PrivateKey) -> Self {
+        BLS12381PublicKey(private_key.0.sk_to_pk())
+    }
+}
+
+impl PublicKey for BLS12381PublicKey {
+    type PrivateKeyMaterial = BLS12381PrivateKey;
+}
+
+impl std::hash::Hash for BLS12381PublicKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_pubkey = self.to_bytes();
+        state.write(&encoded_pubkey);
+    }
+}
+
+impl PartialEq for BLS12381PublicKey {
+    fn eq(&self, other: &BLS12381PublicKey) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for BLS12381PublicKey {}
+
+impl VerifyingKey for BLS12381PublicKey {
+    type SigningKeyMaterial = BLS12381PrivateKey;
+    type SignatureMaterial = BLS12381Signature;
+}
+
+impl fmt::Display for BLS12381PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for BLS12381PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BLS12381PublicKey({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for BLS12381PublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381PublicKey. This method will also check that the point lies in the
+    /// prime-order subgroup of G1 and isn't the identity.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381PublicKey, CryptoMaterialError> {
+        if bytes.len() != BLS12381_PUBLIC_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        min_pk::PublicKey::key_validate(bytes)
+            .map(BLS12381PublicKey)
+            .map_err(|e| match e {
+                BLST_ERROR::BLST_POINT_NOT_ON_CURVE => CryptoMaterialError::PointNotOnCurveError,
+                BLST_ERROR::BLST_POINT_NOT_IN_GROUP => CryptoMaterialError::SmallSubgroupError,
+                BLST_ERROR::BLST_PK_IS_INFINITY => CryptoMaterialError::ValidationError,
+                _ => CryptoMaterialError::DeserializationError,
+            })
+    }
+}
+
+impl Length for BLS12381PublicKey {
+    fn length(&self) -> usize {
+        BLS12381_PUBLIC_KEY_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381PublicKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// Signature Traits //
+//////////////////////
+
+impl Signature for BLS12381Signature {
+    type VerifyingKeyMaterial = BLS12381PublicKey;
+    type SigningKeyMaterial = BLS12381PrivateKey;
+
+    /// Verifies that the provided signature is valid for the provided message, after checking
+    /// that the signature lies in the prime-order subgroup of G2.
+    fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key: &BLS12381PublicKey,
+    ) -> Result<()> {
+        let bytes = signing_message(message)?;
+        Self::verify_arbitrary_msg(self, &bytes, public_key)
+    }
+
+    /// Checks that `self` is valid for an arbitrary &[u8] `message` using `public_key`.
+    /// Outside of this crate, this particular function should only be used for native signature
+    /// verification in move
+    fn verify_arbitrary_msg(&self, message: &[u8], public_key: &BLS12381PublicKey) -> Result<()> {
+        // The public key was validated on deserialization.
+        check_result(
+            self.0
+                .verify(true, message, DST_BLS_SIG, &[], &public_key.0, false),
+        )
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Length for BLS12381Signature {
+    fn length(&self) -> usize {
+        BLS12381_SIGNATURE_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381Signature {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for BLS12381Signature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_signature = self.to_bytes();
+        state.write(&encoded_signature);
+    }
+}
+
+impl TryFrom<&[u8]> for BLS12381Signature {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381Signature. The subgroup check is deferred to the verification.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381Signature, CryptoMaterialError> {
+        if bytes.len() != BLS12381_SIGNATURE_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        min_pk::Signature::from_bytes(bytes)
+            .map(BLS12381Signature)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl PartialEq for BLS12381Signature {
+    fn eq(&self, other: &BLS12381Signature) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for BLS12381Signature {}
+
+impl fmt::Display for BLS12381Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for BLS12381Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BLS12381Signature({})", self)
+    }
+}
+
+////////////////////////////////
+// ProofOfPossession Traits //
+////////////////////////////////
+
+impl TryFrom<&[u8]> for BLS12381ProofOfPossession {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381ProofOfPossession. The subgroup check is deferred to the
+    /// verification.
+    fn try_from(
+        bytes: &[u8],
+    ) -> std::result::Result<BLS12381ProofOfPossession, CryptoMaterialError> {
+        BLS12381Signature::try_from(bytes).map(|signature| BLS12381ProofOfPossession(signature.0))
+    }
+}
+
+impl Length for BLS12381ProofOfPossession {
+    fn length(&self) -> usize {
+        BLS12381_SIGNATURE_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381ProofOfPossession {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl PartialEq for BLS12381ProofOfPossession {
+    fn eq(&self, other: &BLS12381ProofOfPossession) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for BLS12381ProofOfPossession {}
+
+impl fmt::Debug for BLS12381ProofOfPossession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BLS12381ProofOfPossession({})",
+            hex::encode(&self.to_bytes()[..])
+        )
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::test_utils::{self, KeyPair};
+
+/// Produces a uniformly random BLS12-381 keypair from a seed
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn keypair_strategy() -> impl Strategy<Value = KeyPair<BLS12381PrivateKey, BLS12381PublicKey>> {
+    test_utils::uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest::prelude::*;
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl proptest::arbitrary::Arbitrary for BLS12381PublicKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        crate::test_utils::uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+            .prop_map(|v| v.public_key)
+            .boxed()
+    }
+}
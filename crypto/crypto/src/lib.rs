@@ -7,6 +7,7 @@
 #![cfg_attr(mirai, allow(incomplete_features), feature(const_generics))]
 
 //! A library supplying various cryptographic primitives
+pub mod bls12381;
 pub mod compat;
 pub mod ed25519;
 pub mod error;
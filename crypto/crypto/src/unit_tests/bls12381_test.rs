@@ -0,0 +1,163 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bls12381::{
+        BLS12381PrivateKey, BLS12381ProofOfPossession, BLS12381PublicKey, BLS12381Signature,
+        BLS12381_PUBLIC_KEY_LENGTH, BLS12381_SIGNATURE_LENGTH,
+    },
+    test_utils::{TestDiemCrypto, TEST_SEED},
+    traits::*,
+    CryptoMaterialError::WrongLengthError,
+};
+
+use core::convert::TryFrom;
+use once_cell::sync::Lazy;
+use rand::{rngs::StdRng, SeedableRng};
+
+static MESSAGE: Lazy<TestDiemCrypto> = Lazy::new(|| TestDiemCrypto("Test Message".to_string()));
+fn message() -> &'static TestDiemCrypto {
+    &MESSAGE
+}
+
+// Helper function to generate N bls12381 private keys.
+fn generate_keys(n: usize) -> Vec<BLS12381PrivateKey> {
+    let mut rng = StdRng::from_seed(TEST_SEED);
+    (0..n)
+        .map(|_| BLS12381PrivateKey::generate(&mut rng))
+        .collect()
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let private_key = &generate_keys(1)[0];
+    let public_key = BLS12381PublicKey::from(private_key);
+    let signature = private_key.sign(message());
+    assert!(signature.verify(message(), &public_key).is_ok());
+
+    let other_public_key = BLS12381PublicKey::from(&generate_keys(2)[1]);
+    assert!(signature.verify(message(), &other_public_key).is_err());
+    let other_message = TestDiemCrypto("Other Message".to_string());
+    assert!(signature.verify(&other_message, &public_key).is_err());
+}
+
+#[test]
+fn test_serialization_roundtrip() {
+    let private_key = &generate_keys(1)[0];
+    let public_key = BLS12381PublicKey::from(private_key);
+    let signature = private_key.sign(message());
+
+    let serialized = private_key.to_bytes();
+    assert_eq!(
+        &BLS12381PrivateKey::try_from(&serialized[..]).unwrap(),
+        private_key
+    );
+
+    let serialized = public_key.to_bytes();
+    assert_eq!(serialized.len(), BLS12381_PUBLIC_KEY_LENGTH);
+    assert_eq!(
+        BLS12381PublicKey::try_from(&serialized[..]).unwrap(),
+        public_key
+    );
+    assert_eq!(
+        BLS12381PublicKey::try_from(&serialized[1..]),
+        Err(WrongLengthError)
+    );
+
+    let serialized = signature.to_bytes();
+    assert_eq!(serialized.len(), BLS12381_SIGNATURE_LENGTH);
+    assert_eq!(
+        BLS12381Signature::try_from(&serialized[..]).unwrap(),
+        signature
+    );
+    assert_eq!(
+        BLS12381Signature::try_from(&serialized[1..]),
+        Err(WrongLengthError)
+    );
+
+    let bytes = bcs::to_bytes(&public_key).unwrap();
+    assert_eq!(
+        bcs::from_bytes::<BLS12381PublicKey>(&bytes).unwrap(),
+        public_key
+    );
+}
+
+#[test]
+fn test_proof_of_possession() {
+    let private_keys = generate_keys(2);
+    let public_keys: Vec<BLS12381PublicKey> = private_keys.iter().map(|k| k.into()).collect();
+    let pop = BLS12381ProofOfPossession::create(&private_keys[0]);
+    assert!(pop.verify(&public_keys[0]).is_ok());
+    assert!(pop.verify(&public_keys[1]).is_err());
+
+    // A proof of possession is not a valid signature of the public key bytes.
+    let signature = BLS12381Signature::try_from(&pop.to_bytes()[..]).unwrap();
+    assert!(signature
+        .verify_arbitrary_msg(&public_keys[0].to_bytes(), &public_keys[0])
+        .is_err());
+}
+
+#[test]
+fn test_aggregate_and_verify() {
+    let private_keys = generate_keys(5);
+    let public_keys: Vec<BLS12381PublicKey> = private_keys.iter().map(|k| k.into()).collect();
+    let signatures: Vec<_> = private_keys.iter().map(|k| k.sign(message())).collect();
+
+    let aggregate_signature = BLS12381Signature::aggregate(signatures.clone()).unwrap();
+    let all_keys: Vec<_> = public_keys.iter().collect();
+    assert!(aggregate_signature
+        .verify_aggregate(message(), &all_keys)
+        .is_ok());
+
+    // The aggregate signature verifies against the aggregate public key.
+    let aggregate_public_key = BLS12381PublicKey::aggregate(&all_keys).unwrap();
+    assert!(aggregate_signature
+        .verify(message(), &aggregate_public_key)
+        .is_ok());
+
+    // Missing a signer fails.
+    assert!(aggregate_signature
+        .verify_aggregate(message(), &all_keys[1..])
+        .is_err());
+
+    // Aggregating a subset only verifies against that subset.
+    let partial_signature = BLS12381Signature::aggregate(signatures[..3].to_vec()).unwrap();
+    assert!(partial_signature
+        .verify_aggregate(message(), &all_keys[..3])
+        .is_ok());
+    assert!(partial_signature
+        .verify_aggregate(message(), &all_keys)
+        .is_err());
+
+    assert!(BLS12381Signature::aggregate(vec![]).is_err());
+    assert!(aggregate_signature
+        .verify_aggregate(message(), &[])
+        .is_err());
+}
+
+#[test]
+fn test_aggregate_arbitrary_msgs() {
+    let private_keys = generate_keys(3);
+    let public_keys: Vec<BLS12381PublicKey> = private_keys.iter().map(|k| k.into()).collect();
+    let messages: Vec<Vec<u8>> = (0..3u8).map(|i| vec![i; 10]).collect();
+    let signatures: Vec<_> = private_keys
+        .iter()
+        .zip(messages.iter())
+        .map(|(k, m)| k.sign_arbitrary_message(m))
+        .collect();
+
+    let aggregate_signature = BLS12381Signature::aggregate(signatures).unwrap();
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    let keys: Vec<_> = public_keys.iter().collect();
+    assert!(aggregate_signature
+        .verify_aggregate_arbitrary_msgs(&messages, &keys)
+        .is_ok());
+
+    let reversed_keys: Vec<_> = public_keys.iter().rev().collect();
+    assert!(aggregate_signature
+        .verify_aggregate_arbitrary_msgs(&messages, &reversed_keys)
+        .is_err());
+    assert!(aggregate_signature
+        .verify_aggregate_arbitrary_msgs(&messages[1..], &keys)
+        .is_err());
+}
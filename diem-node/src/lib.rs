@@ -268,6 +268,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             &node_config.storage.dir(),
             false, /* readonly */
             node_config.storage.prune_window,
+            node_config.storage.ledger_prune_window,
             node_config.storage.rocksdb_config,
         )
         .expect("DB should open."),
@@ -404,7 +405,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     let state_sync_bootstrapper = StateSyncBootstrapper::bootstrap(
         state_sync_network_handles,
         state_sync_to_mempool_sender,
-        Arc::clone(&db_rw.reader),
+        db_rw.clone(),
         chunk_executor,
         node_config,
         genesis_waypoint,
@@ -412,7 +413,13 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     );
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let rpc_runtime = bootstrap_rpc(&node_config, chain_id, diem_db.clone(), mp_client_sender);
+    let rpc_runtime = bootstrap_rpc(
+        &node_config,
+        chain_id,
+        diem_db.clone(),
+        mp_client_sender,
+        diem_db.subscribe_to_commits(),
+    );
 
     let mut consensus_runtime = None;
     let (consensus_to_mempool_sender, consensus_requests) = channel(INTRA_NODE_CHANNEL_BUFFER_SIZE);
@@ -52,6 +52,7 @@ fn main() -> Result<()> {
             &opt.db_dir,
             false,
             None, /* pruner */
+            None, /* ledger pruner */
             RocksdbConfig::default(),
         )
     } else {
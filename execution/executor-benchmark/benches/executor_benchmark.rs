@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use criterion::{criterion_group, criterion_main, measurement::Measurement, BatchSize, Criterion};
+use diem_vm::DiemVM;
 use executor_benchmark::{
     create_storage_service_and_executor, TransactionExecutor, TransactionGenerator,
 };
@@ -19,7 +20,7 @@ pub const INITIAL_BALANCE: u64 = 1000000;
 fn executor_benchmark<M: Measurement + 'static>(c: &mut Criterion<M>) {
     let (config, genesis_key) = diem_genesis_tool::test_config();
 
-    let (_db, executor) = create_storage_service_and_executor(&config);
+    let (_db, executor) = create_storage_service_and_executor::<DiemVM>(&config);
     let parent_block_id = executor.committed_block_id();
 
     let mut generator = TransactionGenerator::new(genesis_key, NUM_ACCOUNTS);
@@ -1,6 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod workload;
+
+use crate::workload::{benchmark_module, TransactionKind, Workload};
 use diem_config::{
     config::{NodeConfig, RocksdbConfig},
     utils::get_genesis_txn,
@@ -12,34 +15,36 @@ use diem_crypto::{
 };
 use diem_logger::prelude::*;
 use diem_transaction_builder::stdlib::{
-    encode_create_parent_vasp_account_script, encode_peer_to_peer_with_metadata_script,
+    encode_create_child_vasp_account_script, encode_create_parent_vasp_account_script,
+    encode_peer_to_peer_with_metadata_script,
 };
 use diem_types::{
     account_address::AccountAddress,
     account_config::{
-        testnet_dd_account_address, treasury_compliance_account_address, xus_tag, AccountResource,
-        XUS_NAME,
+        diem_root_address, testnet_dd_account_address, treasury_compliance_account_address,
+        xus_tag, AccountResource, CORE_CODE_ADDRESS, XUS_NAME,
     },
     block_info::BlockInfo,
     chain_id::ChainId,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{
-        authenticator::AuthenticationKey, RawTransaction, Script, SignedTransaction, Transaction,
-        Version,
+        authenticator::AuthenticationKey, RawTransaction, SignedTransaction, Transaction,
+        TransactionPayload, Version,
     },
 };
-use diem_vm::DiemVM;
-use diemdb::DiemDB;
+use diem_vm::{DiemVM, VMExecutor};
+use diemdb::{metrics::DIEM_STORAGE_API_LATENCY_SECONDS, DiemDB};
 use executor::{
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
     metrics::{
         DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS, DIEM_EXECUTOR_EXECUTE_BLOCK_SECONDS,
+        DIEM_EXECUTOR_PROCESS_VM_OUTPUTS_SECONDS, DIEM_EXECUTOR_UPDATE_STATE_TREE_SECONDS,
         DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS,
     },
     Executor,
 };
 use executor_types::BlockExecutor;
-use rand::{rngs::StdRng, SeedableRng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::{
     collections::BTreeMap,
     convert::TryFrom,
@@ -77,6 +82,15 @@ pub struct TransactionGenerator {
     /// For deterministic transaction generation.
     rng: StdRng,
 
+    /// The mix of transactions generated after the accounts are funded.
+    workload: Workload,
+
+    /// The sequence number of the Diem root account, which publishes the modules of the workload.
+    diem_root_sequence_number: u64,
+
+    /// The number of modules published so far, used to give each module a unique name.
+    num_published_modules: u64,
+
     /// Each generated block of transactions are sent to this channel. Using `SyncSender` to make
     /// sure if execution is slow to consume the transactions, we do not run out of memory.
     block_sender: Option<mpsc::SyncSender<Vec<Transaction>>>,
@@ -84,24 +98,33 @@ pub struct TransactionGenerator {
 
 impl TransactionGenerator {
     pub fn new(genesis_key: Ed25519PrivateKey, num_accounts: usize) -> Self {
-        Self::new_impl(genesis_key, num_accounts, None)
+        Self::new_impl(genesis_key, num_accounts, Workload::default(), 0, None)
     }
 
     pub fn new_with_sender(
         genesis_key: Ed25519PrivateKey,
         num_accounts: usize,
+        workload: Workload,
+        diem_root_sequence_number: u64,
         block_sender: mpsc::SyncSender<Vec<Transaction>>,
     ) -> Self {
-        Self::new_impl(genesis_key, num_accounts, Some(block_sender))
+        Self::new_impl(
+            genesis_key,
+            num_accounts,
+            workload,
+            diem_root_sequence_number,
+            Some(block_sender),
+        )
     }
 
     fn new_impl(
         genesis_key: Ed25519PrivateKey,
         num_accounts: usize,
+        workload: Workload,
+        diem_root_sequence_number: u64,
         block_sender: Option<mpsc::SyncSender<Vec<Transaction>>>,
     ) -> Self {
-        let seed = [1u8; 32];
-        let mut rng = StdRng::from_seed(seed);
+        let mut rng = StdRng::seed_from_u64(workload.seed);
 
         let mut accounts = Vec::with_capacity(num_accounts);
         for _i in 0..num_accounts {
@@ -121,6 +144,9 @@ impl TransactionGenerator {
             accounts,
             genesis_key,
             rng,
+            workload,
+            diem_root_sequence_number,
+            num_published_modules: 0,
             block_sender,
         }
     }
@@ -135,7 +161,7 @@ impl TransactionGenerator {
 
         self.gen_account_creations(block_size);
         self.gen_mint_transactions(init_account_balance, block_size);
-        self.gen_transfer_transactions(block_size, num_transfer_blocks);
+        self.gen_workload_transactions(block_size, num_transfer_blocks);
     }
 
     pub fn gen_account_creations(&self, block_size: usize) -> Vec<Vec<Transaction>> {
@@ -150,14 +176,14 @@ impl TransactionGenerator {
                     (i * block_size + j) as u64,
                     &self.genesis_key,
                     self.genesis_key.public_key(),
-                    encode_create_parent_vasp_account_script(
+                    TransactionPayload::Script(encode_create_parent_vasp_account_script(
                         xus_tag(),
                         0,
                         account.address,
                         account.auth_key_prefix(),
                         vec![],
                         false, /* add all currencies */
-                    ),
+                    )),
                 );
                 transactions.push(txn);
             }
@@ -187,13 +213,13 @@ impl TransactionGenerator {
                     (i * block_size + j) as u64,
                     &self.genesis_key,
                     self.genesis_key.public_key(),
-                    encode_peer_to_peer_with_metadata_script(
+                    TransactionPayload::Script(encode_peer_to_peer_with_metadata_script(
                         xus_tag(),
                         account.address,
                         init_account_balance,
                         vec![],
                         vec![],
-                    ),
+                    )),
                 );
                 transactions.push(txn);
             }
@@ -227,13 +253,13 @@ impl TransactionGenerator {
                     sender.sequence_number,
                     &sender.private_key,
                     sender.public_key.clone(),
-                    encode_peer_to_peer_with_metadata_script(
+                    TransactionPayload::Script(encode_peer_to_peer_with_metadata_script(
                         xus_tag(),
                         receiver.address,
                         1, /* amount */
                         vec![],
                         vec![],
-                    ),
+                    )),
                 );
                 transactions.push(txn);
 
@@ -248,17 +274,124 @@ impl TransactionGenerator {
         txn_block
     }
 
+    /// Generates transactions following the mix of the workload.
+    pub fn gen_workload_transactions(
+        &mut self,
+        block_size: usize,
+        num_blocks: usize,
+    ) -> Vec<Vec<Transaction>> {
+        let mut txn_block = vec![];
+        for _i in 0..num_blocks {
+            let mut transactions = Vec::with_capacity(block_size);
+            for _j in 0..block_size {
+                let kind = self.workload.sample_kind(&mut self.rng);
+                transactions.push(self.gen_workload_transaction(kind));
+            }
+            if let Some(sender) = &self.block_sender {
+                sender.send(transactions).unwrap();
+            } else {
+                txn_block.push(transactions);
+            }
+        }
+        txn_block
+    }
+
+    fn gen_workload_transaction(&mut self, kind: TransactionKind) -> Transaction {
+        let (sender_idx, payload) = match kind {
+            TransactionKind::PublishModule => return self.gen_module_publishing(),
+            TransactionKind::HotTransfer => {
+                let receiver_idx = self.rng.gen_range(0..self.workload.num_hot_accounts);
+                let mut sender_idx = self.rng.gen_range(0..self.accounts.len() - 1);
+                if sender_idx >= receiver_idx {
+                    sender_idx += 1;
+                }
+                (sender_idx, self.transfer_payload(receiver_idx))
+            }
+            TransactionKind::LargeWriteSet => {
+                let child_key = Ed25519PrivateKey::generate(&mut self.rng);
+                let child_public_key = child_key.public_key();
+                let payload = encode_create_child_vasp_account_script(
+                    xus_tag(),
+                    diem_types::account_address::from_public_key(&child_public_key),
+                    AuthenticationKey::ed25519(&child_public_key)
+                        .prefix()
+                        .to_vec(),
+                    true, /* add all currencies */
+                    0,    /* child_initial_balance */
+                );
+                (
+                    self.rng.gen_range(0..self.accounts.len()),
+                    TransactionPayload::Script(payload),
+                )
+            }
+            TransactionKind::ScriptFunction => {
+                let script_function = self
+                    .workload
+                    .script_function
+                    .as_ref()
+                    .expect("Workload must have a script function.")
+                    .script_function();
+                (
+                    self.rng.gen_range(0..self.accounts.len()),
+                    TransactionPayload::ScriptFunction(script_function),
+                )
+            }
+            TransactionKind::Transfer => {
+                let indices = rand::seq::index::sample(&mut self.rng, self.accounts.len(), 2);
+                (indices.index(0), self.transfer_payload(indices.index(1)))
+            }
+        };
+
+        let sender = &self.accounts[sender_idx];
+        let txn = create_transaction(
+            sender.address,
+            sender.sequence_number,
+            &sender.private_key,
+            sender.public_key.clone(),
+            payload,
+        );
+        self.accounts[sender_idx].sequence_number += 1;
+        txn
+    }
+
+    fn gen_module_publishing(&mut self) -> Transaction {
+        // Only the Diem root account may publish modules under the genesis publishing option,
+        // and they are published under the core code address.
+        let module = benchmark_module(CORE_CODE_ADDRESS, self.num_published_modules);
+        let txn = create_transaction(
+            diem_root_address(),
+            self.diem_root_sequence_number,
+            &self.genesis_key,
+            self.genesis_key.public_key(),
+            TransactionPayload::Module(module),
+        );
+        self.num_published_modules += 1;
+        self.diem_root_sequence_number += 1;
+        txn
+    }
+
+    fn transfer_payload(&self, receiver_idx: usize) -> TransactionPayload {
+        TransactionPayload::Script(encode_peer_to_peer_with_metadata_script(
+            xus_tag(),
+            self.accounts[receiver_idx].address,
+            1, /* amount */
+            vec![],
+            vec![],
+        ))
+    }
+
     /// Verifies the sequence numbers in storage match what we have locally.
     fn verify_sequence_number(&self, db: &dyn DbReader) {
         for account in &self.accounts {
-            let address = account.address;
-            let blob = db
-                .get_latest_account_state(address)
-                .expect("Failed to query storage.")
-                .expect("Account must exist.");
-            let account_resource = AccountResource::try_from(&blob).unwrap();
-            assert_eq!(account_resource.sequence_number(), account.sequence_number);
+            assert_eq!(
+                get_sequence_number(db, account.address),
+                account.sequence_number
+            );
         }
+        assert_eq!(
+            get_sequence_number(db, diem_root_address()),
+            self.diem_root_sequence_number
+        );
     }
 
     /// Drops the sender to notify the receiving end of the channel.
@@ -267,15 +400,15 @@ impl TransactionGenerator {
     }
 }
 
-pub struct TransactionExecutor {
-    executor: Executor<DiemVM>,
+pub struct TransactionExecutor<V> {
+    executor: Executor<V>,
     parent_block_id: HashValue,
     start_time: Instant,
     version: u64,
 }
 
-impl TransactionExecutor {
-    pub fn new(executor: Executor<DiemVM>, parent_block_id: HashValue) -> Self {
+impl<V: VMExecutor> TransactionExecutor<V> {
+    pub fn new(executor: Executor<V>, parent_block_id: HashValue) -> Self {
         Self {
             executor,
             parent_block_id,
@@ -362,14 +495,70 @@ impl TransactionExecutor {
     }
 }
 
-pub fn create_storage_service_and_executor(
+/// The cumulative time, in seconds, spent in each stage of executing and committing blocks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StageTimes {
+    /// Executing the transactions in the VM.
+    pub vm_execution: f64,
+    /// Turning the VM outputs into account states, including the state tree update below.
+    pub process_vm_outputs: f64,
+    /// Updating the in-memory sparse Merkle tree of the scratchpad.
+    pub state_tree_update: f64,
+    /// Writing the committed transactions in `DiemDB::save_transactions`.
+    pub save_transactions: f64,
+}
+
+impl StageTimes {
+    /// Reads the stage times accumulated so far in this process.
+    pub fn now() -> Self {
+        Self {
+            vm_execution: DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum(),
+            process_vm_outputs: DIEM_EXECUTOR_PROCESS_VM_OUTPUTS_SECONDS.get_sample_sum(),
+            state_tree_update: DIEM_EXECUTOR_UPDATE_STATE_TREE_SECONDS.get_sample_sum(),
+            save_transactions: DIEM_STORAGE_API_LATENCY_SECONDS
+                .with_label_values(&["save_transactions", "Ok"])
+                .get_sample_sum(),
+        }
+    }
+
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            vm_execution: self.vm_execution - earlier.vm_execution,
+            process_vm_outputs: self.process_vm_outputs - earlier.process_vm_outputs,
+            state_tree_update: self.state_tree_update - earlier.state_tree_update,
+            save_transactions: self.save_transactions - earlier.save_transactions,
+        }
+    }
+
+    fn report(&self, num_txns: u64) {
+        const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+        let per_txn = |secs: f64| secs * NANOS_PER_SEC / num_txns.max(1) as f64;
+        info!(
+            "Stage times over {} workload transactions: VM execution: {:.3} secs ({:.0} ns/txn), \
+             process_vm_outputs: {:.3} secs ({:.0} ns/txn), of which state tree update: {:.3} secs \
+             ({:.0} ns/txn), save_transactions: {:.3} secs ({:.0} ns/txn)",
+            num_txns,
+            self.vm_execution,
+            per_txn(self.vm_execution),
+            self.process_vm_outputs,
+            per_txn(self.process_vm_outputs),
+            self.state_tree_update,
+            per_txn(self.state_tree_update),
+            self.save_transactions,
+            per_txn(self.save_transactions),
+        );
+    }
+}
+
+pub fn create_storage_service_and_executor<V: VMExecutor>(
     config: &NodeConfig,
-) -> (Arc<dyn DbReader>, Executor<DiemVM>) {
+) -> (Arc<dyn DbReader>, Executor<V>) {
     let (db, db_rw) = DbReaderWriter::wrap(
         DiemDB::open(
             &config.storage.dir(),
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger pruner */
             RocksdbConfig::default(),
         )
         .expect("DB should open."),
@@ -385,21 +574,30 @@ pub fn create_storage_service_and_executor(
     (db, executor)
 }
 
-/// Runs the benchmark with given parameters.
-pub fn run_benchmark(
+/// Runs the benchmark with given parameters, executing blocks with `V`, and returns the time spent
+/// in each stage while executing the blocks of the workload.
+pub fn run_benchmark<V: VMExecutor + 'static>(
     num_accounts: usize,
     init_account_balance: u64,
     block_size: usize,
     num_transfer_blocks: usize,
     db_dir: Option<PathBuf>,
-) {
+    workload: Workload,
+) -> StageTimes {
+    workload
+        .validate(num_accounts)
+        .expect("Workload must be valid.");
+
     let (mut config, genesis_key) = diem_genesis_tool::test_config();
     if let Some(path) = db_dir {
         config.storage.dir = path;
     }
 
-    let (db, executor) = create_storage_service_and_executor(&config);
+    let (db, executor) = create_storage_service_and_executor::<V>(&config);
     let parent_block_id = executor.committed_block_id();
+    let diem_root_sequence_number = get_sequence_number(db.as_ref(), diem_root_address());
+    // Account creation and minting each take this many blocks before the workload starts.
+    let num_setup_blocks = 2 * ((num_accounts + block_size - 1) / block_size);
 
     let (block_sender, block_receiver) = mpsc::sync_channel(50 /* bound */);
 
@@ -407,8 +605,13 @@ pub fn run_benchmark(
     let gen_thread = std::thread::Builder::new()
         .name("txn_generator".to_string())
         .spawn(move || {
-            let mut generator =
-                TransactionGenerator::new_with_sender(genesis_key, num_accounts, block_sender);
+            let mut generator = TransactionGenerator::new_with_sender(
+                genesis_key,
+                num_accounts,
+                workload,
+                diem_root_sequence_number,
+                block_sender,
+            );
             generator.run(init_account_balance, block_size, num_transfer_blocks);
             generator
         })
@@ -417,10 +620,21 @@ pub fn run_benchmark(
         .name("txn_executor".to_string())
         .spawn(move || {
             let mut exe = TransactionExecutor::new(executor, parent_block_id);
+            let mut num_blocks = 0;
+            let mut workload_start = (StageTimes::now(), 0);
             while let Ok(transactions) = block_receiver.recv() {
+                if num_blocks == num_setup_blocks {
+                    workload_start = (StageTimes::now(), exe.version);
+                }
                 info!("Received block of size {:?}", transactions.len());
                 exe.execute_block(transactions);
+                num_blocks += 1;
             }
+
+            let (start_times, start_version) = workload_start;
+            let stage_times = StageTimes::now().since(&start_times);
+            stage_times.report(exe.version - start_version);
+            stage_times
         })
         .expect("Failed to spawn transaction executor thread.");
 
@@ -428,10 +642,20 @@ pub fn run_benchmark(
     let mut generator = gen_thread.join().unwrap();
     generator.drop_sender();
     // Wait until all transactions are committed.
-    exe_thread.join().unwrap();
+    let stage_times = exe_thread.join().unwrap();
 
     // Do a sanity check on the sequence number to make sure all transactions are committed.
     generator.verify_sequence_number(db.as_ref());
+
+    stage_times
+}
+
+fn get_sequence_number(db: &dyn DbReader, address: AccountAddress) -> u64 {
+    let blob = db
+        .get_latest_account_state(address)
+        .expect("Failed to query storage.")
+        .expect("Account must exist.");
+    AccountResource::try_from(&blob).unwrap().sequence_number()
 }
 
 fn create_transaction(
@@ -439,15 +663,15 @@ fn create_transaction(
     sequence_number: u64,
     private_key: &Ed25519PrivateKey,
     public_key: Ed25519PublicKey,
-    program: Script,
+    payload: TransactionPayload,
 ) -> Transaction {
     let now = diem_infallible::duration_since_epoch();
     let expiration_time = now.as_secs() + 3600;
 
-    let raw_txn = RawTransaction::new_script(
+    let raw_txn = RawTransaction::new(
         sender,
         sequence_number,
-        program,
+        payload,
         1_000_000,           /* max_gas_amount */
         0,                   /* gas_unit_price */
         XUS_NAME.to_owned(), /* gas_currency_code */
@@ -462,14 +686,66 @@ fn create_transaction(
 
 #[cfg(test)]
 mod tests {
+    use crate::workload::{parse_function_id, ScriptFunctionSpec, Workload};
+    use diem_types::{
+        account_config::testnet_dd_account_address, transaction::TransactionArgument,
+    };
+    use diem_vm::{DiemVM, ParallelDiemVM};
+    use move_core_types::parser::parse_type_tag;
+
     #[test]
     fn test_benchmark() {
-        super::run_benchmark(
+        super::run_benchmark::<DiemVM>(
+            25,   /* num_accounts */
+            10,   /* init_account_balance */
+            5,    /* block_size */
+            5,    /* num_transfer_blocks */
+            None, /* db_dir */
+            Workload::default(),
+        );
+    }
+    #[test]
+    fn test_parallel_benchmark() {
+        super::run_benchmark::<ParallelDiemVM>(
+            25,   /* num_accounts */
+            10,   /* init_account_balance */
+            5,    /* block_size */
+            5,    /* num_transfer_blocks */
+            None, /* db_dir */
+            Workload::default(),
+        );
+    }
+
+    #[test]
+    fn test_contended_benchmark() {
+        let (module, function) =
+            parse_function_id("0x1::PaymentScripts::peer_to_peer_with_metadata").unwrap();
+        let workload = Workload {
+            seed: 42,
+            num_hot_accounts: 2,
+            hot_account_ratio: 0.5,
+            module_publishing_ratio: 0.1,
+            large_write_set_ratio: 0.1,
+            script_function_ratio: 0.1,
+            script_function: Some(ScriptFunctionSpec {
+                module,
+                function,
+                ty_args: vec![parse_type_tag("0x1::XUS::XUS").unwrap()],
+                args: vec![
+                    TransactionArgument::Address(testnet_dd_account_address()),
+                    TransactionArgument::U64(1),
+                    TransactionArgument::U8Vector(vec![]),
+                    TransactionArgument::U8Vector(vec![]),
+                ],
+            }),
+        };
+        super::run_benchmark::<ParallelDiemVM>(
             25,   /* num_accounts */
             10,   /* init_account_balance */
             5,    /* block_size */
             5,    /* num_transfer_blocks */
             None, /* db_dir */
+            workload,
         );
     }
 }
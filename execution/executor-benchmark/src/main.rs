@@ -1,6 +1,14 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use diem_types::transaction::{parse_transaction_argument, TransactionArgument};
+use diem_vm::{DiemVM, ParallelDiemVM};
+use executor_benchmark::workload::{parse_function_id, ScriptFunctionSpec, Workload};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    parser::parse_type_tag,
+};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -20,6 +28,67 @@ struct Opt {
 
     #[structopt(long, parse(from_os_str))]
     db_dir: Option<PathBuf>,
+
+    /// Execute blocks with the optimistic parallel executor.
+    #[structopt(long)]
+    parallel_execution: bool,
+
+    /// Seed for generating the accounts and transactions.
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+
+    /// Number of accounts receiving the hot transfers.
+    #[structopt(long, default_value = "0")]
+    num_hot_accounts: usize,
+
+    /// Fraction of the transfers sent to one of the hot accounts.
+    #[structopt(long, default_value = "0")]
+    hot_account_ratio: f64,
+
+    /// Fraction of the transactions publishing a module.
+    #[structopt(long, default_value = "0")]
+    module_publishing_ratio: f64,
+
+    /// Fraction of the transactions creating a child account with a balance in every currency.
+    #[structopt(long, default_value = "0")]
+    large_write_set_ratio: f64,
+
+    /// Fraction of the transactions calling the script function given by `--script-function`.
+    #[structopt(long, default_value = "0")]
+    script_function_ratio: f64,
+
+    /// Script function to call, as `<address>::<module>::<function>`.
+    #[structopt(long, parse(try_from_str = parse_function_id))]
+    script_function: Option<(ModuleId, Identifier)>,
+
+    /// Type arguments of the script function.
+    #[structopt(long = "type-args", parse(try_from_str = parse_type_tag))]
+    type_args: Vec<TypeTag>,
+
+    /// Arguments of the script function.
+    #[structopt(long = "args", parse(try_from_str = parse_transaction_argument))]
+    args: Vec<TransactionArgument>,
+}
+
+impl Opt {
+    fn workload(&self) -> Workload {
+        Workload {
+            seed: self.seed,
+            num_hot_accounts: self.num_hot_accounts,
+            hot_account_ratio: self.hot_account_ratio,
+            module_publishing_ratio: self.module_publishing_ratio,
+            large_write_set_ratio: self.large_write_set_ratio,
+            script_function_ratio: self.script_function_ratio,
+            script_function: self.script_function.clone().map(|(module, function)| {
+                ScriptFunctionSpec {
+                    module,
+                    function,
+                    ty_args: self.type_args.clone(),
+                    args: self.args.clone(),
+                }
+            }),
+        }
+    }
 }
 
 fn main() {
@@ -32,11 +101,23 @@ fn main() {
         .build_global()
         .expect("Failed to build rayon global thread pool.");
 
-    executor_benchmark::run_benchmark(
-        opt.num_accounts,
-        opt.init_account_balance,
-        opt.block_size,
-        opt.num_transfer_blocks,
-        opt.db_dir,
-    );
+    if opt.parallel_execution {
+        executor_benchmark::run_benchmark::<ParallelDiemVM>(
+            opt.num_accounts,
+            opt.init_account_balance,
+            opt.block_size,
+            opt.num_transfer_blocks,
+            opt.db_dir.clone(),
+            opt.workload(),
+        );
+    } else {
+        executor_benchmark::run_benchmark::<DiemVM>(
+            opt.num_accounts,
+            opt.init_account_balance,
+            opt.block_size,
+            opt.num_transfer_blocks,
+            opt.db_dir.clone(),
+            opt.workload(),
+        );
+    }
 }
@@ -0,0 +1,245 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Workload profiles for the executor benchmark.
+//!
+//! A profile describes the mix of transactions generated once the accounts are created and funded.
+//! Each transaction is one of the kinds below, picked at random with the configured ratios. Every
+//! transaction that is not claimed by another kind is a transfer between two accounts.
+
+use anyhow::{ensure, format_err, Result};
+use diem_types::{
+    account_address::AccountAddress,
+    transaction::{Module, ScriptFunction, TransactionArgument},
+};
+use move_binary_format::file_format::empty_module;
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    transaction_argument::convert_txn_args,
+};
+use rand::Rng;
+
+/// A script function called by the script function transactions of a workload.
+#[derive(Clone, Debug)]
+pub struct ScriptFunctionSpec {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<TransactionArgument>,
+}
+
+impl ScriptFunctionSpec {
+    pub fn script_function(&self) -> ScriptFunction {
+        ScriptFunction::new(
+            self.module.clone(),
+            self.function.clone(),
+            self.ty_args.clone(),
+            convert_txn_args(&self.args),
+        )
+    }
+}
+
+/// Parses a fully qualified function name of the form `<address>::<module>::<function>`.
+pub fn parse_function_id(s: &str) -> Result<(ModuleId, Identifier)> {
+    let parts: Vec<_> = s.split("::").collect();
+    ensure!(
+        parts.len() == 3,
+        "Expected <address>::<module>::<function>, got {}",
+        s
+    );
+    let address = AccountAddress::from_hex_literal(parts[0])
+        .map_err(|_| format_err!("Invalid address: {}", parts[0]))?;
+    let module = ModuleId::new(address, Identifier::new(parts[1])?);
+    Ok((module, Identifier::new(parts[2])?))
+}
+
+#[derive(Clone, Debug)]
+pub struct Workload {
+    /// Seeds the generation of both the accounts and the transactions, so that a run can be
+    /// reproduced exactly.
+    pub seed: u64,
+
+    /// Number of accounts, from the start of the account pool, that receive the hot transfers.
+    pub num_hot_accounts: usize,
+
+    /// Fraction of the transfers sent to one of the hot accounts. These transfers write to the
+    /// same few accounts and therefore conflict with each other under parallel execution.
+    pub hot_account_ratio: f64,
+
+    /// Fraction of the transactions publishing a new module from the Diem root account.
+    pub module_publishing_ratio: f64,
+
+    /// Fraction of the transactions creating a child account with a balance in every currency,
+    /// which writes a much larger account state than a transfer.
+    pub large_write_set_ratio: f64,
+
+    /// Fraction of the transactions calling `script_function`.
+    pub script_function_ratio: f64,
+
+    pub script_function: Option<ScriptFunctionSpec>,
+}
+
+impl Default for Workload {
+    /// Transfers between uniformly random pairs of accounts.
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            num_hot_accounts: 0,
+            hot_account_ratio: 0.0,
+            module_publishing_ratio: 0.0,
+            large_write_set_ratio: 0.0,
+            script_function_ratio: 0.0,
+            script_function: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TransactionKind {
+    Transfer,
+    HotTransfer,
+    PublishModule,
+    LargeWriteSet,
+    ScriptFunction,
+}
+
+impl Workload {
+    pub fn validate(&self, num_accounts: usize) -> Result<()> {
+        for (name, ratio) in &[
+            ("hot_account_ratio", self.hot_account_ratio),
+            ("module_publishing_ratio", self.module_publishing_ratio),
+            ("large_write_set_ratio", self.large_write_set_ratio),
+            ("script_function_ratio", self.script_function_ratio),
+        ] {
+            ensure!(
+                (0.0..=1.0).contains(ratio),
+                "{} must be between 0 and 1, got {}",
+                name,
+                ratio
+            );
+        }
+        ensure!(
+            self.module_publishing_ratio + self.large_write_set_ratio + self.script_function_ratio
+                <= 1.0,
+            "The ratios of the transaction kinds must not add up to more than 1"
+        );
+        ensure!(
+            num_accounts >= 2,
+            "At least 2 accounts are needed, got {}",
+            num_accounts
+        );
+        ensure!(
+            self.hot_account_ratio == 0.0
+                || (self.num_hot_accounts > 0 && self.num_hot_accounts <= num_accounts),
+            "num_hot_accounts must be between 1 and the number of accounts, got {}",
+            self.num_hot_accounts
+        );
+        ensure!(
+            self.script_function_ratio == 0.0 || self.script_function.is_some(),
+            "script_function_ratio is set but no script function is given"
+        );
+        Ok(())
+    }
+
+    pub(crate) fn sample_kind<R: Rng>(&self, rng: &mut R) -> TransactionKind {
+        let mut x: f64 = rng.gen();
+        for (kind, ratio) in &[
+            (TransactionKind::PublishModule, self.module_publishing_ratio),
+            (TransactionKind::LargeWriteSet, self.large_write_set_ratio),
+            (TransactionKind::ScriptFunction, self.script_function_ratio),
+        ] {
+            if x < *ratio {
+                return *kind;
+            }
+            x -= ratio;
+        }
+        if self.hot_account_ratio > 0.0 && rng.gen_bool(self.hot_account_ratio) {
+            TransactionKind::HotTransfer
+        } else {
+            TransactionKind::Transfer
+        }
+    }
+}
+
+/// Creates an empty module named `BenchmarkModule<index>` at `address`.
+pub(crate) fn benchmark_module(address: AccountAddress, index: u64) -> Module {
+    let mut module = empty_module();
+    module.address_identifiers[0] = address;
+    module.identifiers[0] = Identifier::new(format!("BenchmarkModule{}", index)).unwrap();
+
+    let mut code = vec![];
+    module
+        .freeze()
+        .expect("Empty module must be valid.")
+        .serialize(&mut code)
+        .expect("Module must serialize.");
+    Module::new(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_parse_function_id() {
+        let (module, function) =
+            parse_function_id("0x1::PaymentScripts::peer_to_peer_with_metadata").unwrap();
+        assert_eq!(
+            module.address(),
+            &AccountAddress::from_hex_literal("0x1").unwrap()
+        );
+        assert_eq!(module.name().as_str(), "PaymentScripts");
+        assert_eq!(function.as_str(), "peer_to_peer_with_metadata");
+
+        assert!(parse_function_id("0x1::PaymentScripts").is_err());
+        assert!(parse_function_id("0x1::Payment-Scripts::f").is_err());
+    }
+
+    #[test]
+    fn test_sample_kind_is_reproducible() {
+        let workload = Workload {
+            num_hot_accounts: 1,
+            hot_account_ratio: 0.5,
+            module_publishing_ratio: 0.1,
+            large_write_set_ratio: 0.2,
+            ..Workload::default()
+        };
+        workload.validate(10).unwrap();
+
+        let sample = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..100)
+                .map(|_| workload.sample_kind(&mut rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sample(7), sample(7));
+        assert!(!sample(7).contains(&TransactionKind::ScriptFunction));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Workload::default().validate(2).is_ok());
+        assert!(Workload::default().validate(1).is_err());
+        assert!(Workload {
+            hot_account_ratio: 0.5,
+            ..Workload::default()
+        }
+        .validate(10)
+        .is_err());
+        assert!(Workload {
+            module_publishing_ratio: 0.6,
+            large_write_set_ratio: 0.6,
+            ..Workload::default()
+        }
+        .validate(10)
+        .is_err());
+        assert!(Workload {
+            script_function_ratio: 0.1,
+            ..Workload::default()
+        }
+        .validate(10)
+        .is_err());
+    }
+}
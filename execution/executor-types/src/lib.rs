@@ -9,7 +9,9 @@ pub use error::Error;
 use anyhow::Result;
 use diem_crypto::{
     ed25519::Ed25519Signature,
-    hash::{TransactionAccumulatorHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    hash::{
+        TransactionAccumulatorHasher, ACCUMULATOR_PLACEHOLDER_HASH, SPARSE_MERKLE_PLACEHOLDER_HASH,
+    },
     HashValue,
 };
 use diem_types::{
@@ -153,6 +155,21 @@ impl StateComputeResult {
             signature: None,
         }
     }
+
+    /// Generates a placeholder result for a block that is ordered but not executed yet, used by
+    /// consensus when execution is decoupled from ordering.
+    pub fn new_dummy() -> Self {
+        StateComputeResult::new(
+            *ACCUMULATOR_PLACEHOLDER_HASH,
+            vec![],
+            0,
+            vec![],
+            0,
+            None,
+            vec![],
+            vec![],
+        )
+    }
 }
 
 impl StateComputeResult {
@@ -21,7 +21,8 @@ use crate::{
     metrics::{
         DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS, DIEM_EXECUTOR_ERRORS,
         DIEM_EXECUTOR_EXECUTE_AND_COMMIT_CHUNK_SECONDS, DIEM_EXECUTOR_EXECUTE_BLOCK_SECONDS,
-        DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS, DIEM_EXECUTOR_TRANSACTIONS_SAVED,
+        DIEM_EXECUTOR_PROCESS_VM_OUTPUTS_SECONDS, DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS,
+        DIEM_EXECUTOR_TRANSACTIONS_SAVED, DIEM_EXECUTOR_UPDATE_STATE_TREE_SECONDS,
         DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS,
     },
     speculation_cache::SpeculationCache,
@@ -258,6 +259,8 @@ where
         vm_outputs: Vec<TransactionOutput>,
         parent_trees: &ExecutedTrees,
     ) -> Result<ProcessedVMOutput> {
+        let _timer = DIEM_EXECUTOR_PROCESS_VM_OUTPUTS_SECONDS.start_timer();
+
         // The data of each individual transaction. For convenience purpose, even for the
         // transactions that will be discarded, we will compute its in-memory Sparse Merkle Tree
         // (it will be identical to the previous one).
@@ -290,20 +293,23 @@ where
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let (txn_state_roots, current_state_tree) = parent_trees
-            .state_tree()
-            .serial_update(
-                txn_blobs
-                    .iter()
-                    .map(|m| {
-                        m.iter()
-                            .map(|(account, value)| (account.hash(), value))
-                            .collect::<Vec<_>>()
-                    })
-                    .collect(),
-                &proof_reader,
-            )
-            .expect("Failed to update state tree.");
+        let (txn_state_roots, current_state_tree) = {
+            let _timer = DIEM_EXECUTOR_UPDATE_STATE_TREE_SECONDS.start_timer();
+            parent_trees
+                .state_tree()
+                .serial_update(
+                    txn_blobs
+                        .iter()
+                        .map(|m| {
+                            m.iter()
+                                .map(|(account, value)| (account.hash(), value))
+                                .collect::<Vec<_>>()
+                        })
+                        .collect(),
+                    &proof_reader,
+                )
+                .expect("Failed to update state tree.")
+        };
 
         for ((vm_output, txn), (state_tree_hash, blobs)) in itertools::zip_eq(
             itertools::zip_eq(vm_outputs.into_iter(), transactions.iter()).take(transaction_count),
@@ -351,6 +357,7 @@ where
 
             txn_data.push(TransactionData::new(
                 blobs,
+                vm_output.write_set().clone(),
                 vm_output.events().to_vec(),
                 vm_output.status().clone(),
                 state_tree_hash,
@@ -367,6 +374,7 @@ where
                 transactions.len(),
                 TransactionData::new(
                     HashMap::new(),
+                    WriteSet::default(),
                     vec![],
                     TransactionStatus::Retry,
                     current_state_tree.root_hash(),
@@ -537,6 +545,7 @@ where
             txns_to_commit.push(TransactionToCommit::new(
                 txn,
                 txn_data.account_blobs().clone(),
+                txn_data.write_set().clone(),
                 txn_data.events().to_vec(),
                 txn_data.gas_used(),
                 recorded_status,
@@ -862,6 +871,7 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
                 txns_to_keep.push(TransactionToCommit::new(
                     txn.clone(),
                     txn_data.account_blobs().clone(),
+                    txn_data.write_set().clone(),
                     txn_data.events().to_vec(),
                     txn_data.gas_used(),
                     recorded_status.clone(),
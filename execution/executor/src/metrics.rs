@@ -24,6 +24,26 @@ pub static DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS: Lazy<Histogram> = Lazy::new(|
     .unwrap()
 });
 
+pub static DIEM_EXECUTOR_PROCESS_VM_OUTPUTS_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "diem_executor_process_vm_outputs_seconds",
+        // metric description
+        "The time spent in seconds of processing vm outputs in Diem executor"
+    )
+    .unwrap()
+});
+
+pub static DIEM_EXECUTOR_UPDATE_STATE_TREE_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "diem_executor_update_state_tree_seconds",
+        // metric description
+        "The time spent in seconds of updating the in-memory sparse merkle tree in Diem executor"
+    )
+    .unwrap()
+});
+
 pub static DIEM_EXECUTOR_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!("diem_executor_error_total", "Cumulative number of errors").unwrap()
 });
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use diem_metrics::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+use diem_metrics::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
+};
 use once_cell::sync::Lazy;
 
 /// Cumulative number of rpc requests that the JSON RPC service receives
@@ -82,3 +85,22 @@ pub static METHOD_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Number of open WebSocket connections of the streaming API
+pub static STREAM_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_client_service_stream_connections",
+        "Number of open WebSocket connections of the streaming API"
+    )
+    .unwrap()
+});
+
+/// Number of active subscriptions of the streaming API
+pub static STREAM_SUBSCRIPTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "diem_client_service_stream_subscriptions",
+        "Number of active subscriptions of the streaming API",
+        &["method"] // subscribe_to_transactions / subscribe_to_events
+    )
+    .unwrap()
+});
@@ -4,21 +4,35 @@
 use crate::{
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionListView, TransactionView,
-        TransactionsWithProofsView,
+        AccountResourcesView, AccountStateWithProofView, AccountView, BytesView, CurrencyInfoView,
+        EventView, EventWithProofView, MetadataView, MoveFieldView, MoveFunctionView,
+        MoveModuleView, MoveResourceView, MoveStructView, MoveTypeParameterView, StateProofView,
+        TransactionListView, TransactionOutputView, TransactionView, TransactionWithProofView,
+        TransactionWriteSetView, TransactionsWithProofsView, WriteOpView,
     },
 };
 use anyhow::{format_err, Result};
 use diem_crypto::HashValue;
+use diem_state_view::{StateView, StateViewId};
 use diem_types::{
+    access_path::{AccessPath, Path},
     account_address::AccountAddress,
     account_config::{diem_root_address, resources::dual_attestation::Limit, AccountResource},
     account_state::AccountState,
     chain_id::ChainId,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
+    transaction::{SignedTransaction, TransactionStatus},
+    write_set::WriteOp,
 };
+use diem_vm::{data_cache::RemoteStorage, DiemVM};
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, Visibility},
+    normalized, CompiledModule,
+};
+use move_core_types::language_storage::{ModuleId, StructTag};
+use resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator};
+use serde_json::json;
 use std::{
     cmp::min,
     convert::{TryFrom, TryInto},
@@ -239,6 +253,48 @@ pub fn get_events_with_proofs(
     Ok(views)
 }
 
+/// Returns the transactions sent by an account along with their proofs. Unlike
+/// `get_account_transactions`, the transactions are bounded by the sequence number of the
+/// account at `ledger_version`, and an account which doesn't exist has no transactions, so
+/// that the result can be checked against the account state proven at the same version.
+pub fn get_account_transactions_with_proofs(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    account: AccountAddress,
+    start: u64,
+    limit: u64,
+    include_events: bool,
+) -> Result<Vec<TransactionWithProofView>, JsonRpcError> {
+    let account_seq = match get_account_state(db, account, ledger_version)? {
+        Some(account_state) => match account_state.get_account_resource()? {
+            Some(account_resource) => account_resource.sequence_number(),
+            None => return Ok(vec![]),
+        },
+        None => return Ok(vec![]),
+    };
+
+    if start >= account_seq {
+        return Ok(vec![]);
+    }
+
+    let end = min(
+        start
+            .checked_add(limit)
+            .ok_or_else(|| format_err!("overflow!"))?,
+        account_seq,
+    );
+
+    let mut views = vec![];
+    for seq in start..end {
+        let tx = db
+            .get_txn_by_account(account, seq, ledger_version, include_events)?
+            .ok_or_else(|| format_err!("Can not find transaction for seq {}!", seq))?;
+        views.push(TransactionWithProofView::try_from(&tx)?);
+    }
+
+    Ok(views)
+}
+
 /// Returns meta information about supported currencies
 pub fn get_currencies(
     db: &dyn DbReader,
@@ -292,3 +348,318 @@ pub fn get_account_state_with_proof(
         account_state_with_proof,
     )?)
 }
+
+/// Executes the transaction on top of the state at `version` without committing it. The signature
+/// of the transaction is not checked, so that gas usage can be estimated before signing.
+pub fn simulate_transaction(
+    db: &dyn DbReader,
+    version: u64,
+    txn: SignedTransaction,
+) -> Result<TransactionOutputView, JsonRpcError> {
+    let state_view = DbStateView {
+        db,
+        version,
+        id: StateViewId::TransactionSimulation {
+            base_version: version,
+        },
+    };
+    let (_, output) = DiemVM::simulate_signed_transaction(txn, &state_view);
+    if let TransactionStatus::Discard(status) = output.status() {
+        return Err(JsonRpcError::vm_status(*status));
+    }
+    Ok(TransactionOutputView::try_from_output(version + 1, output)?)
+}
+
+/// Returns the write sets of the transactions by range. Resources are decoded with the layouts of
+/// the modules published on chain at the ledger version; values that can't be decoded are only
+/// returned as BCS.
+pub fn get_transaction_write_sets(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    start_version: u64,
+    limit: u64,
+) -> Result<Vec<TransactionWriteSetView>, JsonRpcError> {
+    if start_version > ledger_version || limit == 0 {
+        return Ok(vec![]);
+    }
+    let end_version = min(start_version.saturating_add(limit), ledger_version + 1);
+
+    // Modules are only ever added or upgraded compatibly, so the ones at the ledger version can
+    // decode the resources written by every earlier transaction.
+    let state_view = DbStateView::new(db, ledger_version);
+    let storage = RemoteStorage::new(&state_view);
+    let annotator = MoveValueAnnotator::new(&storage);
+    (start_version..end_version)
+        .map(|version| {
+            let write_set = db
+                .get_write_set(version)?
+                .iter()
+                .map(|write_op| decode_write_op(&annotator, write_op))
+                .collect();
+            Ok(TransactionWriteSetView { version, write_set })
+        })
+        .collect()
+}
+
+/// Returns all the resources under an account, decoded to JSON, and the ABIs of the modules it
+/// published
+pub fn get_account_resources(
+    db: &dyn DbReader,
+    account_address: AccountAddress,
+    version: u64,
+) -> Result<Option<AccountResourcesView>, JsonRpcError> {
+    let account_state = match get_account_state(db, account_address, version)? {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+
+    let state_view = DbStateView::new(db, version);
+    let storage = RemoteStorage::new(&state_view);
+    let annotator = MoveValueAnnotator::new(&storage);
+    let mut resources = vec![];
+    let mut modules = vec![];
+    for (path, blob) in account_state.iter() {
+        match Path::try_from(path)? {
+            Path::Resource(tag) => resources.push(resource_view(&annotator, &tag, blob)),
+            Path::Code(module_id) => modules.push(module_view(&module_id, blob)),
+        }
+    }
+    Ok(Some(AccountResourcesView { resources, modules }))
+}
+
+/// Returns the resource of the given type under an account, decoded to JSON
+pub fn get_account_resource(
+    db: &dyn DbReader,
+    account_address: AccountAddress,
+    struct_tag: StructTag,
+    version: u64,
+) -> Result<Option<MoveResourceView>, JsonRpcError> {
+    let blob = match get_account_state(db, account_address, version)?.and_then(|state| {
+        state
+            .get(&AccessPath::resource_access_vec(struct_tag.clone()))
+            .cloned()
+    }) {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+
+    let state_view = DbStateView::new(db, version);
+    let storage = RemoteStorage::new(&state_view);
+    let annotator = MoveValueAnnotator::new(&storage);
+    Ok(Some(resource_view(&annotator, &struct_tag, &blob)))
+}
+
+/// A resource that can't be decoded, e.g. because the module declaring it isn't published at the
+/// requested version, is still returned with its BCS serialized bytes.
+fn resource_view(
+    annotator: &MoveValueAnnotator,
+    struct_tag: &StructTag,
+    blob: &[u8],
+) -> MoveResourceView {
+    MoveResourceView {
+        r#type: struct_tag.to_string(),
+        value: annotator
+            .view_resource(struct_tag, blob)
+            .ok()
+            .map(|value| annotated_struct_to_json(&value)),
+        bytes: BytesView::from(blob),
+    }
+}
+
+/// Reads the account states at `version`, so that the resources can be decoded with the modules
+/// published on chain at that version.
+struct DbStateView<'a> {
+    db: &'a dyn DbReader,
+    version: u64,
+    id: StateViewId,
+}
+
+impl<'a> DbStateView<'a> {
+    fn new(db: &'a dyn DbReader, version: u64) -> Self {
+        Self {
+            db,
+            version,
+            id: StateViewId::Miscellaneous,
+        }
+    }
+}
+
+impl<'a> StateView for DbStateView<'a> {
+    fn id(&self) -> StateViewId {
+        self.id
+    }
+
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(
+            get_account_state(self.db, access_path.address, self.version)?
+                .and_then(|state| state.get(&access_path.path).cloned()),
+        )
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+/// Views a module published on chain; a module that doesn't deserialize is returned without its
+/// ABI, so that it doesn't fail the whole request.
+fn module_view(module_id: &ModuleId, blob: &[u8]) -> MoveModuleView {
+    let module = CompiledModule::deserialize(blob)
+        .ok()
+        .map(|compiled_module| normalized::Module::new(&compiled_module));
+    MoveModuleView {
+        address: *module_id.address(),
+        name: module_id.name().to_string(),
+        bytecode: BytesView::from(blob),
+        friends: module.as_ref().map(|module| {
+            module
+                .friends
+                .iter()
+                .map(|id| format!("0x{}::{}", id.address().short_str_lossless(), id.name()))
+                .collect()
+        }),
+        structs: module.as_ref().map(|module| {
+            module
+                .structs
+                .iter()
+                .map(|(name, s)| MoveStructView {
+                    name: name.to_string(),
+                    abilities: abilities_to_strings(s.abilities),
+                    type_parameters: type_parameter_views(&s.type_parameters),
+                    fields: s
+                        .fields
+                        .iter()
+                        .map(|field| MoveFieldView {
+                            name: field.name.to_string(),
+                            r#type: type_to_string(&field.type_),
+                        })
+                        .collect(),
+                })
+                .collect()
+        }),
+        functions: module.as_ref().map(|module| {
+            module
+                .exposed_functions
+                .iter()
+                .map(|(name, f)| MoveFunctionView {
+                    name: name.to_string(),
+                    visibility: match f.visibility {
+                        Visibility::Private => "private",
+                        Visibility::Public => "public",
+                        Visibility::Script => "script",
+                        Visibility::Friend => "friend",
+                    }
+                    .to_string(),
+                    type_parameters: type_parameter_views(&f.type_parameters),
+                    parameters: f.parameters.iter().map(type_to_string).collect(),
+                    return_types: f.return_.iter().map(type_to_string).collect(),
+                })
+                .collect()
+        }),
+    }
+}
+
+fn type_parameter_views(type_parameters: &[AbilitySet]) -> Vec<MoveTypeParameterView> {
+    type_parameters
+        .iter()
+        .map(|constraints| MoveTypeParameterView {
+            constraints: abilities_to_strings(*constraints),
+        })
+        .collect()
+}
+
+fn abilities_to_strings(abilities: AbilitySet) -> Vec<String> {
+    abilities
+        .into_iter()
+        .map(|ability| {
+            match ability {
+                Ability::Copy => "copy",
+                Ability::Drop => "drop",
+                Ability::Store => "store",
+                Ability::Key => "key",
+            }
+            .to_string()
+        })
+        .collect()
+}
+
+/// Formats `ty` with the Move source syntax, e.g. `vector<0x1::Diem::Diem<T0>>`.
+fn type_to_string(ty: &normalized::Type) -> String {
+    match ty {
+        normalized::Type::Bool => "bool".to_string(),
+        normalized::Type::U8 => "u8".to_string(),
+        normalized::Type::U64 => "u64".to_string(),
+        normalized::Type::U128 => "u128".to_string(),
+        normalized::Type::Address => "address".to_string(),
+        normalized::Type::Signer => "signer".to_string(),
+        normalized::Type::Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } => {
+            let name = format!("0x{}::{}::{}", address.short_str_lossless(), module, name);
+            if type_arguments.is_empty() {
+                name
+            } else {
+                let type_arguments: Vec<_> = type_arguments.iter().map(type_to_string).collect();
+                format!("{}<{}>", name, type_arguments.join(", "))
+            }
+        }
+        normalized::Type::Vector(ty) => format!("vector<{}>", type_to_string(ty)),
+        normalized::Type::TypeParameter(idx) => format!("T{}", idx),
+        normalized::Type::Reference(ty) => format!("&{}", type_to_string(ty)),
+        normalized::Type::MutableReference(ty) => format!("&mut {}", type_to_string(ty)),
+    }
+}
+
+fn decode_write_op(
+    annotator: &MoveValueAnnotator,
+    write_op: &(AccessPath, WriteOp),
+) -> WriteOpView {
+    let mut view = WriteOpView::from(write_op);
+    if let (
+        WriteOpView::Value {
+            resource_type,
+            resource,
+            ..
+        },
+        (access_path, WriteOp::Value(blob)),
+    ) = (&mut view, write_op)
+    {
+        if let Some(tag) = access_path.get_struct_tag() {
+            *resource = annotator
+                .view_resource(&tag, blob)
+                .ok()
+                .map(|value| annotated_struct_to_json(&value));
+            *resource_type = Some(tag.to_string());
+        }
+    }
+    view
+}
+
+fn annotated_struct_to_json(value: &AnnotatedMoveStruct) -> serde_json::Value {
+    serde_json::Value::Object(
+        value
+            .value
+            .iter()
+            .map(|(name, field)| (name.to_string(), annotated_value_to_json(field)))
+            .collect(),
+    )
+}
+
+fn annotated_value_to_json(value: &AnnotatedMoveValue) -> serde_json::Value {
+    match value {
+        AnnotatedMoveValue::U8(v) => json!(v),
+        AnnotatedMoveValue::U64(v) => json!(v),
+        // u128 doesn't fit in a JSON number
+        AnnotatedMoveValue::U128(v) => json!(v.to_string()),
+        AnnotatedMoveValue::Bool(v) => json!(v),
+        AnnotatedMoveValue::Address(v) => json!(v),
+        AnnotatedMoveValue::Vector(_, values) => {
+            serde_json::Value::Array(values.iter().map(annotated_value_to_json).collect())
+        }
+        AnnotatedMoveValue::Bytes(v) => json!(hex::encode(v)),
+        AnnotatedMoveValue::Struct(v) => annotated_struct_to_json(v),
+    }
+}
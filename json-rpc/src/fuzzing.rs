@@ -109,6 +109,7 @@ pub fn request_fuzzer(json_request: serde_json::Value) {
         genesis: std::collections::HashMap::new(),
         all_accounts: std::collections::HashMap::new(),
         all_txns: vec![],
+        write_sets: vec![],
         events: vec![],
         account_state_with_proof: vec![account_state_with_proof],
         timestamps: vec![1598223353000000],
@@ -11,6 +11,7 @@
 //! Module organization:
 //! ├── methods.rs        # contains all available JSON RPC method handlers
 //! ├── runtime.rs        # implementation of JSON RPC protocol over HTTP
+//! ├── stream_rpc.rs     # subscriptions to transactions and events over WebSocket
 //! ├── tests.rs          # tests
 
 #[macro_use]
@@ -20,6 +21,7 @@ mod counters;
 pub mod data;
 mod methods;
 mod runtime;
+mod stream_rpc;
 
 pub use diem_json_rpc_types::{errors, response, views};
 
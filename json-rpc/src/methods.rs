@@ -6,18 +6,22 @@ use crate::{
     data,
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionListView, TransactionView,
+        AccountResourcesView, AccountStateWithProofView, AccountView, CurrencyInfoView, EventView,
+        EventWithProofView, MetadataView, MoveResourceView, StateProofView, TransactionListView,
+        TransactionOutputView, TransactionView, TransactionWithProofView, TransactionWriteSetView,
         TransactionsWithProofsView,
     },
 };
 use anyhow::Result;
 use diem_config::config::RoleType;
+use diem_crypto::ed25519::Ed25519Signature;
 use diem_json_rpc_types::request::{
-    GetAccountParams, GetAccountStateWithProofParams, GetAccountTransactionParams,
-    GetAccountTransactionsParams, GetCurrenciesParams, GetEventsParams, GetEventsWithProofsParams,
-    GetMetadataParams, GetNetworkStatusParams, GetStateProofParams, GetTransactionsParams,
-    GetTransactionsWithProofsParams, MethodRequest, SubmitParams,
+    GetAccountParams, GetAccountResourceParams, GetAccountResourcesParams,
+    GetAccountStateWithProofParams, GetAccountTransactionParams, GetAccountTransactionsParams,
+    GetAccountTransactionsWithProofsParams, GetCurrenciesParams, GetEventsParams,
+    GetEventsWithProofsParams, GetMetadataParams, GetNetworkStatusParams, GetStateProofParams,
+    GetTransactionWriteSetsParams, GetTransactionsParams, GetTransactionsWithProofsParams,
+    MethodRequest, SimulateTransactionParams, SimulatedTransaction, SubmitParams,
 };
 use diem_mempool::{MempoolClientSender, SubmissionStatus};
 use diem_types::{
@@ -27,7 +31,7 @@ use diem_types::{
 use fail::fail_point;
 use futures::{channel::oneshot, SinkExt};
 use serde_json::Value;
-use std::{borrow::Borrow, sync::Arc};
+use std::{borrow::Borrow, convert::TryFrom, sync::Arc};
 use storage_interface::DbReader;
 
 #[derive(Clone)]
@@ -179,6 +183,21 @@ impl<'a> Handler<'a> {
             MethodRequest::GetEventsWithProofs(params) => {
                 serde_json::to_value(self.get_events_with_proofs(params).await?)?
             }
+            MethodRequest::GetAccountTransactionsWithProofs(params) => {
+                serde_json::to_value(self.get_account_transactions_with_proofs(params).await?)?
+            }
+            MethodRequest::SimulateTransaction(params) => {
+                serde_json::to_value(self.simulate_transaction(params).await?)?
+            }
+            MethodRequest::GetTransactionWriteSets(params) => {
+                serde_json::to_value(self.get_transaction_write_sets(params).await?)?
+            }
+            MethodRequest::GetAccountResources(params) => {
+                serde_json::to_value(self.get_account_resources(params).await?)?
+            }
+            MethodRequest::GetAccountResource(params) => {
+                serde_json::to_value(self.get_account_resource(params).await?)?
+            }
         };
         Ok(response)
     }
@@ -235,6 +254,49 @@ impl<'a> Handler<'a> {
         )
     }
 
+    /// Returns the write sets of transactions by range
+    async fn get_transaction_write_sets(
+        &self,
+        params: GetTransactionWriteSetsParams,
+    ) -> Result<Vec<TransactionWriteSetView>, JsonRpcError> {
+        let GetTransactionWriteSetsParams {
+            start_version,
+            limit,
+        } = params;
+
+        self.service.validate_page_size_limit(limit as usize)?;
+        data::get_transaction_write_sets(
+            self.service.db.borrow(),
+            self.version(),
+            start_version,
+            limit,
+        )
+    }
+
+    /// Returns all the resources and modules of an account
+    async fn get_account_resources(
+        &self,
+        params: GetAccountResourcesParams,
+    ) -> Result<Option<AccountResourcesView>, JsonRpcError> {
+        let account_address = params.account;
+        let version = self.version_param(params.version, "version")?;
+        data::get_account_resources(self.service.db.borrow(), account_address, version)
+    }
+
+    /// Returns the resource of the given type under an account
+    async fn get_account_resource(
+        &self,
+        params: GetAccountResourceParams,
+    ) -> Result<Option<MoveResourceView>, JsonRpcError> {
+        let GetAccountResourceParams {
+            account,
+            struct_tag,
+            version,
+        } = params;
+        let version = self.version_param(version, "version")?;
+        data::get_account_resource(self.service.db.borrow(), account, struct_tag, version)
+    }
+
     /// Returns transactions by range with proofs
     async fn get_transactions_with_proofs(
         &self,
@@ -318,6 +380,51 @@ impl<'a> Handler<'a> {
         data::get_events_with_proofs(self.service.db.borrow(), self.version(), key, start, limit)
     }
 
+    /// Returns account transactions along with their proofs
+    async fn get_account_transactions_with_proofs(
+        &self,
+        params: GetAccountTransactionsWithProofsParams,
+    ) -> Result<Vec<TransactionWithProofView>, JsonRpcError> {
+        let GetAccountTransactionsWithProofsParams {
+            account,
+            start,
+            limit,
+            include_events,
+        } = params;
+
+        self.service.validate_page_size_limit(limit as usize)?;
+        data::get_account_transactions_with_proofs(
+            self.service.db.borrow(),
+            self.version(),
+            account,
+            start,
+            limit,
+            include_events,
+        )
+    }
+
+    /// Executes a transaction against the latest state without committing it
+    async fn simulate_transaction(
+        &self,
+        params: SimulateTransactionParams,
+    ) -> Result<TransactionOutputView, JsonRpcError> {
+        let txn = match params.data {
+            SimulatedTransaction::Signed(txn) => txn,
+            SimulatedTransaction::Raw(txn) => {
+                let public_key = params.public_key.ok_or_else(|| {
+                    JsonRpcError::invalid_param(
+                        "public_key is required to simulate a RawTransaction",
+                    )
+                })?;
+                // The signature is never checked when simulating.
+                let signature = Ed25519Signature::try_from(&[0u8; Ed25519Signature::LENGTH][..])
+                    .map_err(|e| JsonRpcError::internal_error(e.to_string()))?;
+                SignedTransaction::new(txn, public_key, signature)
+            }
+        };
+        data::simulate_transaction(self.service.db.borrow(), self.version(), txn)
+    }
+
     /// Returns meta information about supported currencies
     async fn get_currencies(
         &self,
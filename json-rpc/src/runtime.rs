@@ -6,14 +6,15 @@ use crate::{
     errors::is_internal_error,
     methods::{Handler, JsonRpcService},
     response::{JsonRpcResponse, X_DIEM_CHAIN_ID, X_DIEM_TIMESTAMP_USEC_ID, X_DIEM_VERSION_ID},
+    stream_rpc::{stream_route, StreamContext},
     util::{sdk_info_from_user_agent, SdkInfo},
 };
 use anyhow::{ensure, Result};
-use diem_config::config::{NodeConfig, RoleType};
+use diem_config::config::{NodeConfig, RoleType, StreamConfig};
 use diem_json_rpc_types::Method;
 use diem_logger::{debug, Schema};
 use diem_mempool::MempoolClientSender;
-use diem_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures};
+use diem_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use futures::future::{join_all, Either};
 use rand::{rngs::OsRng, RngCore};
 use serde_json::Value;
@@ -24,7 +25,10 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::watch,
+};
 use warp::{
     http::header,
     reject::{self, Reject},
@@ -93,6 +97,7 @@ macro_rules! log_response {
 }
 
 /// Creates HTTP server (warp-based) that serves JSON RPC requests
+/// The WebSocket streaming API is only served if it's enabled and `commit_notifications` is set.
 /// Returns handle to corresponding Tokio runtime
 pub fn bootstrap(
     address: SocketAddr,
@@ -105,6 +110,8 @@ pub fn bootstrap(
     mp_sender: MempoolClientSender,
     role: RoleType,
     chain_id: ChainId,
+    stream_config: &StreamConfig,
+    commit_notifications: Option<watch::Receiver<Version>>,
 ) -> Runtime {
     let runtime = Builder::new_multi_thread()
         .thread_name("json-rpc")
@@ -112,6 +119,20 @@ pub fn bootstrap(
         .build()
         .expect("[json-rpc] failed to create runtime");
 
+    let stream_context = commit_notifications
+        .filter(|_| stream_config.enabled)
+        .map(|commits| {
+            stream_config
+                .verify()
+                .expect("[json-rpc] invalid stream config");
+            Arc::new(StreamContext::new(
+                diem_db.clone(),
+                chain_id,
+                stream_config.clone(),
+                commits,
+            ))
+        });
+
     let service = JsonRpcService::new(
         diem_db.clone(),
         mp_sender,
@@ -169,7 +190,9 @@ pub fn bootstrap(
         .and(warp::any().map(SystemTime::now))
         .and_then(health_check);
 
-    let full_route = health_route.or(route_v1.or(route_root));
+    let full_route = health_route
+        .or(stream_route(stream_context))
+        .or(route_v1.or(route_root));
 
     // Ensure that we actually bind to the socket first before spawning the
     // server tasks. This helps in tests to prevent races where a client attempts
@@ -199,6 +222,7 @@ pub fn bootstrap_from_config(
     chain_id: ChainId,
     diem_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    commit_notifications: watch::Receiver<Version>,
 ) -> Runtime {
     bootstrap(
         config.json_rpc.address,
@@ -211,6 +235,8 @@ pub fn bootstrap_from_config(
         mp_sender,
         config.base.role,
         chain_id,
+        &config.json_rpc.stream_rpc,
+        Some(commit_notifications),
     )
 }
 
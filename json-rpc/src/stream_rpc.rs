@@ -0,0 +1,287 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! WebSocket endpoint pushing newly committed transactions and events to subscribed clients.
+//!
+//! Each subscription reads from the DB until it has caught up with the latest ledger info, and then
+//! waits for the DB to notify the next commit.
+
+use crate::{counters, data, errors::JsonRpcError, response::JsonRpcResponse};
+use diem_config::config::StreamConfig;
+use diem_json_rpc_types::{
+    stream::{
+        StreamJsonRpcRequest, StreamMethodRequest, SubscribeToEventsParams,
+        SubscribeToTransactionsParams,
+    },
+    Id,
+};
+use diem_logger::debug;
+use diem_metrics::IntGauge;
+use diem_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures, transaction::Version};
+use futures::{
+    future::{abortable, AbortHandle},
+    SinkExt, StreamExt,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use storage_interface::DbReader;
+use tokio::sync::{mpsc, watch};
+use warp::{
+    filters::ws::{Message, WebSocket, Ws},
+    reject, Filter, Rejection, Reply,
+};
+
+pub(crate) struct StreamContext {
+    db: Arc<dyn DbReader>,
+    chain_id: ChainId,
+    config: StreamConfig,
+    commits: watch::Receiver<Version>,
+}
+
+impl StreamContext {
+    pub fn new(
+        db: Arc<dyn DbReader>,
+        chain_id: ChainId,
+        config: StreamConfig,
+        commits: watch::Receiver<Version>,
+    ) -> Self {
+        Self {
+            db,
+            chain_id,
+            config,
+            commits,
+        }
+    }
+}
+
+/// Route of the WebSocket endpoint. Upgrades are rejected if the streaming API is disabled, i.e.
+/// there is no `context`.
+pub(crate) fn stream_route(
+    context: Option<Arc<StreamContext>>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("v1" / "stream" / "ws")
+        .and(warp::ws())
+        .and(warp::any().map(move || context.clone()))
+        .and_then(|ws: Ws, context: Option<Arc<StreamContext>>| async move {
+            match context {
+                Some(context) => {
+                    Ok(ws.on_upgrade(move |socket| handle_connection(socket, context)))
+                }
+                None => Err(reject::not_found()),
+            }
+        })
+}
+
+async fn handle_connection(socket: WebSocket, context: Arc<StreamContext>) {
+    counters::STREAM_CONNECTIONS.inc();
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    // All the subscriptions of the connection push their responses through this channel.
+    let (sender, mut receiver) = mpsc::channel::<JsonRpcResponse>(context.config.send_queue_size);
+    tokio::spawn(async move {
+        while let Some(response) = receiver.recv().await {
+            let message = match serde_json::to_string(&response) {
+                Ok(text) => Message::text(text),
+                Err(_) => continue,
+            };
+            if ws_sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut subscriptions: Vec<AbortHandle> = vec![];
+    while let Some(Ok(message)) = ws_receiver.next().await {
+        if message.is_close() {
+            break;
+        }
+        // Pings are answered by warp, and binary messages are ignored.
+        let text = match message.to_str() {
+            Ok(text) => text,
+            Err(()) => continue,
+        };
+
+        let limit = context.config.subscriptions_per_connection_limit;
+        let (error, id) = match parse_request(text) {
+            Ok(request) if subscriptions.len() >= limit => (
+                JsonRpcError::invalid_request_with_msg(format!(
+                    "subscriptions per connection limit ({}) exceeded",
+                    limit
+                )),
+                serde_json::to_value(&request.id).ok(),
+            ),
+            Ok(request) => {
+                debug!("New {} subscription", request.method().as_str());
+                let subscription = Subscription::new(&context, request.id, sender.clone());
+                let (task, handle) = abortable(subscription.run(request.method_request));
+                tokio::spawn(task);
+                subscriptions.push(handle);
+                continue;
+            }
+            Err(error) => error,
+        };
+
+        let ledger_info = match context.db.get_latest_ledger_info() {
+            Ok(ledger_info) => ledger_info,
+            Err(_) => break,
+        };
+        let mut response = new_response(context.chain_id, &ledger_info, id);
+        response.error = Some(error);
+        if sender.send(response).await.is_err() {
+            break;
+        }
+    }
+
+    for subscription in subscriptions {
+        subscription.abort();
+    }
+    counters::STREAM_CONNECTIONS.dec();
+}
+
+fn parse_request(text: &str) -> Result<StreamJsonRpcRequest, (JsonRpcError, Option<Value>)> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|_| (JsonRpcError::invalid_format(), None))?;
+    let id = value.get("id").cloned();
+    serde_json::from_value(value).map_err(|_| (JsonRpcError::invalid_request(), id))
+}
+
+fn new_response(
+    chain_id: ChainId,
+    ledger_info: &LedgerInfoWithSignatures,
+    id: Option<Value>,
+) -> JsonRpcResponse {
+    let mut response = JsonRpcResponse::new(
+        chain_id,
+        ledger_info.ledger_info().version(),
+        ledger_info.ledger_info().timestamp_usecs(),
+    );
+    response.id = id;
+    response
+}
+
+/// Decrements the subscription gauge when the subscription ends, including when it's aborted.
+struct SubscriptionGuard(IntGauge);
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+struct Subscription {
+    db: Arc<dyn DbReader>,
+    chain_id: ChainId,
+    fetch_size: u64,
+    commits: watch::Receiver<Version>,
+    id: Value,
+    sender: mpsc::Sender<JsonRpcResponse>,
+}
+
+impl Subscription {
+    fn new(context: &StreamContext, id: Id, sender: mpsc::Sender<JsonRpcResponse>) -> Self {
+        Self {
+            db: context.db.clone(),
+            chain_id: context.chain_id,
+            fetch_size: context.config.fetch_size as u64,
+            commits: context.commits.clone(),
+            id: serde_json::to_value(&id).unwrap(),
+            sender,
+        }
+    }
+
+    async fn run(mut self, request: StreamMethodRequest) {
+        let gauge = counters::STREAM_SUBSCRIPTIONS.with_label_values(&[request.method().as_str()]);
+        gauge.inc();
+        let _guard = SubscriptionGuard(gauge);
+
+        let result = match request {
+            StreamMethodRequest::SubscribeToTransactions(params) => {
+                self.push_transactions(params).await
+            }
+            StreamMethodRequest::SubscribeToEvents(params) => self.push_events(params).await,
+        };
+        if let Err(error) = result {
+            if let Ok(ledger_info) = self.db.get_latest_ledger_info() {
+                let mut response = new_response(self.chain_id, &ledger_info, Some(self.id));
+                response.error = Some(error);
+                let _ = self.sender.send(response).await;
+            }
+        }
+    }
+
+    /// Returns once the connection is closed.
+    async fn push_transactions(
+        &mut self,
+        params: SubscribeToTransactionsParams,
+    ) -> Result<(), JsonRpcError> {
+        let mut next_version = params.starting_version;
+        loop {
+            let ledger_info = self.db.get_latest_ledger_info()?;
+            let ledger_version = ledger_info.ledger_info().version();
+            while next_version <= ledger_version {
+                let txns = data::get_transactions(
+                    self.db.as_ref(),
+                    ledger_version,
+                    next_version,
+                    self.fetch_size,
+                    params.include_events,
+                )?;
+                if txns.0.is_empty() {
+                    break;
+                }
+                for txn in txns.0 {
+                    next_version = txn.version + 1;
+                    if !self.send(&ledger_info, txn).await? {
+                        return Ok(());
+                    }
+                }
+            }
+            if !self.wait_for_commit().await {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns once the connection is closed.
+    async fn push_events(&mut self, params: SubscribeToEventsParams) -> Result<(), JsonRpcError> {
+        let mut next_seq_num = params.event_seq_num;
+        loop {
+            let ledger_info = self.db.get_latest_ledger_info()?;
+            let ledger_version = ledger_info.ledger_info().version();
+            let events = data::get_events(
+                self.db.as_ref(),
+                ledger_version,
+                params.event_key,
+                next_seq_num,
+                self.fetch_size,
+            )?;
+            let caught_up = (events.len() as u64) < self.fetch_size;
+            for event in events {
+                next_seq_num = event.sequence_number + 1;
+                if !self.send(&ledger_info, event).await? {
+                    return Ok(());
+                }
+            }
+            if caught_up && !self.wait_for_commit().await {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns false if the connection is closed.
+    async fn send<T: Serialize>(
+        &self,
+        ledger_info: &LedgerInfoWithSignatures,
+        result: T,
+    ) -> Result<bool, JsonRpcError> {
+        let mut response = new_response(self.chain_id, ledger_info, Some(self.id.clone()));
+        response.result = Some(serde_json::to_value(result)?);
+        Ok(self.sender.send(response).await.is_ok())
+    }
+
+    /// Returns false if the DB is gone.
+    async fn wait_for_commit(&mut self) -> bool {
+        self.commits.changed().await.is_ok()
+    }
+}
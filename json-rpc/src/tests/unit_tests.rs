@@ -2,34 +2,51 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    errors::ServerCode,
+    errors::{InvalidRequestCode, ServerCode},
+    response::JsonRpcResponse,
     runtime::check_latest_ledger_info_timestamp,
+    stream_rpc::{stream_route, StreamContext},
     tests::utils::{
         create_database_client_and_runtime, create_db_and_runtime, mock_db, test_bootstrap,
         MockDiemDB,
     },
     util::{sdk_info_from_user_agent, SdkInfo, SdkLang, SdkVersion},
-    views::VMStatusView,
+    views::{EventView, TransactionView, VMStatusView},
+};
+use diem_client::{
+    views::{TransactionDataView, WriteOpView},
+    BlockingClient, MethodRequest,
+};
+use diem_config::{
+    config::{StreamConfig, DEFAULT_CONTENT_LENGTH_LIMIT},
+    utils,
 };
-use diem_client::{views::TransactionDataView, BlockingClient, MethodRequest};
-use diem_config::{config::DEFAULT_CONTENT_LENGTH_LIMIT, utils};
 use diem_crypto::{ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, Uniform};
 use diem_metrics::get_all_metrics;
+use diem_transaction_builder::stdlib::encode_update_diem_version_script;
 use diem_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::AccountResource,
+    account_config::{diem_root_address, AccountResource, DesignatedDealer},
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     chain_id::ChainId,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     mempool_status::{MempoolStatus, MempoolStatusCode},
+    on_chain_config::{DiemVersion, OnChainConfig},
     proof::{SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof},
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::{Transaction, TransactionInfo, TransactionPayload},
+    transaction::{Transaction, TransactionInfo, TransactionPayload, TransactionWithProof},
     vm_status::StatusCode,
+    write_set::WriteOp,
 };
 use futures::{channel::mpsc::channel, StreamExt};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    move_resource::{MoveResource, MoveStructType},
+};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use std::{
     cmp::{max, min},
@@ -39,6 +56,8 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use storage_interface::DbReader;
+use tokio::sync::watch;
+use vm_genesis::{generate_genesis_change_set_for_testing, GenesisOptions, GENESIS_KEYPAIR};
 use vm_validator::{
     mocks::mock_vm_validator::MockVMValidator, vm_validator::TransactionValidation,
 };
@@ -1017,6 +1036,72 @@ fn test_transaction_submission() {
     assert_eq!(status_code, StatusCode::SENDING_ACCOUNT_DOES_NOT_EXIST);
 }
 
+#[test]
+fn test_simulate_transaction() {
+    let (_mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    let sequence_number = client
+        .get_account(diem_root_address())
+        .unwrap()
+        .into_inner()
+        .unwrap()
+        .sequence_number;
+    let (private_key, public_key) = &*GENESIS_KEYPAIR;
+    let txn = get_test_signed_txn(
+        diem_root_address(),
+        sequence_number,
+        private_key,
+        public_key.clone(),
+        Some(encode_update_diem_version_script(0, 7)),
+    );
+
+    let output = client.simulate(&txn).unwrap().into_inner();
+    assert_eq!(output.vm_status, VMStatusView::Executed);
+    assert!(output.gas_used > 0);
+    let diem_version_path = DiemVersion::CONFIG_ID.access_path();
+    let new_version = output
+        .write_set
+        .iter()
+        .find_map(|op| match op {
+            WriteOpView::Value {
+                address,
+                path,
+                value,
+                ..
+            } if *address == diem_version_path.address
+                && path.inner() == diem_version_path.path.as_slice() =>
+            {
+                Some(bcs::from_bytes::<DiemVersion>(value.inner()).unwrap())
+            }
+            _ => None,
+        })
+        .expect("the new Diem version should be written");
+    assert_eq!(new_version.major, 7);
+
+    // The same transaction without a signature
+    let raw_output = client
+        .simulate_raw(&txn.into_raw_transaction(), public_key.clone())
+        .unwrap()
+        .into_inner();
+    assert_eq!(raw_output, output);
+
+    // A transaction that would be discarded is rejected with its VM status
+    let txn = get_test_signed_txn(
+        diem_root_address(),
+        sequence_number + 1,
+        private_key,
+        public_key.clone(),
+        Some(encode_update_diem_version_script(0, 7)),
+    );
+    let error = client.simulate(&txn).unwrap_err();
+    let error = error.json_rpc_error().unwrap();
+    assert_eq!(error.code, ServerCode::VmValidationError as i16);
+    assert_eq!(
+        error.as_status_code(),
+        Some(StatusCode::SEQUENCE_NUMBER_TOO_NEW)
+    );
+}
+
 #[test]
 fn test_get_account() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1253,6 +1338,201 @@ fn test_get_transactions() {
     }
 }
 
+#[test]
+fn test_get_transaction_write_sets() {
+    // make the first write set the genesis one, so that it has resources the server can decode
+    let mut mock_db = mock_db();
+    mock_db.write_sets[0] = generate_genesis_change_set_for_testing(GenesisOptions::Compiled)
+        .write_set()
+        .clone();
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = test_bootstrap(
+        address.parse().unwrap(),
+        Arc::new(mock_db.clone()),
+        mp_sender,
+    );
+    let client = BlockingClient::new(format!("http://{}", address));
+
+    // the mock ledger version is one past the last transaction, which has no write set
+    let num_write_sets = mock_db.write_sets.len() as u64;
+    let page = 100u64;
+
+    for base_version in (0..num_write_sets).step_by(page as usize) {
+        let limit = min(page, num_write_sets - base_version);
+        let write_sets = client
+            .get_transaction_write_sets(base_version, limit)
+            .unwrap()
+            .into_inner();
+        assert_eq!(write_sets.len() as u64, limit);
+
+        for (i, view) in write_sets.iter().enumerate() {
+            let version = base_version + i as u64;
+            assert_eq!(view.version, version);
+
+            let expected = &mock_db.write_sets[version as usize];
+            assert_eq!(view.write_set.len(), expected.iter().count());
+            for (op_view, (access_path, op)) in view.write_set.iter().zip(expected.iter()) {
+                match (op_view, op) {
+                    (
+                        WriteOpView::Value {
+                            address,
+                            path,
+                            value,
+                            ..
+                        },
+                        WriteOp::Value(expected_value),
+                    ) => {
+                        assert_eq!(*address, access_path.address);
+                        assert_eq!(path.inner(), access_path.path.as_slice());
+                        assert_eq!(value.inner(), expected_value.as_slice());
+                    }
+                    (WriteOpView::Deletion { address, path }, WriteOp::Deletion) => {
+                        assert_eq!(*address, access_path.address);
+                        assert_eq!(path.inner(), access_path.path.as_slice());
+                    }
+                    _ => panic!("Returned value doesn't match!"),
+                }
+            }
+        }
+    }
+
+    // resources are decoded to JSON
+    let genesis_write_set = client
+        .get_transaction_write_sets(0, 1)
+        .unwrap()
+        .into_inner()
+        .remove(0);
+    let (value, resource_type, resource) = genesis_write_set
+        .write_set
+        .iter()
+        .find_map(|op| match op {
+            WriteOpView::Value {
+                address,
+                value,
+                resource_type: Some(resource_type),
+                resource,
+                ..
+            } if *address == diem_root_address()
+                && *resource_type == AccountResource::struct_tag().to_string() =>
+            {
+                Some((value, resource_type, resource))
+            }
+            _ => None,
+        })
+        .expect("genesis should publish an AccountResource under diem root");
+    let expected_account: AccountResource = bcs::from_bytes(value.inner()).unwrap();
+    let resource = resource
+        .as_ref()
+        .unwrap_or_else(|| panic!("{} should be decoded", resource_type));
+    assert_eq!(
+        resource["sequence_number"],
+        json!(expected_account.sequence_number())
+    );
+    assert_eq!(
+        resource["authentication_key"],
+        json!(hex::encode(expected_account.authentication_key()))
+    );
+}
+
+#[test]
+fn test_get_account_resources() {
+    // publish a resource of a module that doesn't exist, and a module that doesn't deserialize,
+    // which the server can't decode
+    let mut mock_db = mock_db();
+    let account = *mock_db.all_accounts.keys().next().unwrap();
+    let unknown_tag = StructTag {
+        address: AccountAddress::new([7; AccountAddress::LENGTH]),
+        module: Identifier::new("Unknown").unwrap(),
+        name: Identifier::new("Resource").unwrap(),
+        type_params: vec![],
+    };
+    let mut account_state = AccountState::try_from(&mock_db.all_accounts[&account]).unwrap();
+    account_state.insert(
+        AccessPath::resource_access_vec(unknown_tag.clone()),
+        vec![1, 2, 3],
+    );
+    let bad_module_id = ModuleId::new(account, Identifier::new("Bad").unwrap());
+    account_state.insert(
+        AccessPath::code_access_path(bad_module_id.clone()).path,
+        vec![4, 5, 6],
+    );
+    mock_db
+        .all_accounts
+        .insert(account, AccountStateBlob::try_from(&account_state).unwrap());
+    let expected_account = account_state.get_account_resource().unwrap().unwrap();
+
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), mp_sender);
+    let client = BlockingClient::new(format!("http://{}", address));
+
+    let view = client
+        .get_account_resources(account)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    assert_eq!(view.resources.len(), account_state.iter().count() - 1);
+    let account_resource = view
+        .resources
+        .iter()
+        .find(|resource| resource.r#type == AccountResource::struct_tag().to_string())
+        .expect("AccountResource should be returned");
+    assert_eq!(
+        account_resource.value.as_ref().unwrap()["sequence_number"],
+        json!(expected_account.sequence_number())
+    );
+    assert_eq!(
+        account_resource.bytes.inner(),
+        account_state
+            .get(&AccountResource::resource_path())
+            .unwrap()
+            .as_slice()
+    );
+
+    // the undecodable resource is returned with its bytes only
+    let unknown_resource = view
+        .resources
+        .iter()
+        .find(|resource| resource.r#type == unknown_tag.to_string())
+        .expect("undecodable resource should be returned");
+    assert_eq!(unknown_resource.value, None);
+    assert_eq!(unknown_resource.bytes.inner(), &[1, 2, 3]);
+
+    // so is the undeserializable module, without its ABI
+    assert_eq!(view.modules.len(), 1);
+    let bad_module = &view.modules[0];
+    assert_eq!(bad_module.address, account);
+    assert_eq!(bad_module.name, bad_module_id.name().as_str());
+    assert_eq!(bad_module.bytecode.inner(), &[4, 5, 6]);
+    assert_eq!(bad_module.structs, None);
+    assert_eq!(bad_module.functions, None);
+
+    // single resource
+    let resource = client
+        .get_account_resource(account, &AccountResource::struct_tag())
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    assert_eq!(&resource, account_resource);
+
+    let resource = client
+        .get_account_resource(account, &unknown_tag)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    assert_eq!(&resource, unknown_resource);
+
+    // resource not published under the account
+    let resource = client
+        .get_account_resource(account, &DesignatedDealer::struct_tag())
+        .unwrap()
+        .into_inner();
+    assert_eq!(resource, None);
+}
+
 #[test]
 fn test_get_account_transaction() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1338,6 +1618,37 @@ fn test_get_account_transactions() {
         assert_eq!(tx_views.len() as u64, total);
     }
 }
+
+#[test]
+fn test_get_account_transactions_with_proofs() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    for (acc, blob) in mock_db.all_accounts.iter() {
+        let total = AccountResource::try_from(blob).unwrap().sequence_number();
+        let start = total / 2;
+
+        let views = client
+            .get_account_transactions_with_proofs(*acc, start, max(1, min(1000, total * 2)), true)
+            .unwrap()
+            .into_inner();
+        assert_eq!(views.len() as u64, total - start);
+
+        for (seq, view) in (start..).zip(views.iter()) {
+            let txn = TransactionWithProof::try_from(view).unwrap();
+            let signed_txn = txn.transaction.as_signed_user_txn().unwrap();
+            assert_eq!(signed_txn.sender(), *acc);
+            assert_eq!(signed_txn.sequence_number(), seq);
+            assert!(txn.events.is_some());
+        }
+    }
+
+    // An account which doesn't exist has no transactions
+    let views = client
+        .get_account_transactions_with_proofs(AccountAddress::random(), 0, 10, false)
+        .unwrap()
+        .into_inner();
+    assert!(views.is_empty());
+}
 #[test]
 // Check that if version and ledger_version parameters are None, then the server returns the latest
 // known state.
@@ -1510,6 +1821,82 @@ fn test_check_latest_ledger_info_timestamp() {
     .is_ok());
 }
 
+#[tokio::test]
+async fn test_stream_subscriptions() {
+    let mock_db = mock_db();
+    let (_commit_notifier, commits) = watch::channel(mock_db.version);
+    let context = StreamContext::new(
+        Arc::new(mock_db.clone()),
+        ChainId::test(),
+        StreamConfig {
+            enabled: true,
+            fetch_size: 2,
+            ..StreamConfig::default()
+        },
+        commits,
+    );
+    let mut client = warp::test::ws()
+        .path("/v1/stream/ws")
+        .handshake(stream_route(Some(Arc::new(context))))
+        .await
+        .unwrap();
+
+    // Invalid request
+    client.send_text("foo").await;
+    let response = recv_stream_response(&mut client).await;
+    assert_eq!(
+        response.error.unwrap().code,
+        InvalidRequestCode::InvalidFormat as i16
+    );
+
+    // Transactions are pushed in order, across several reads from the DB
+    client
+        .send_text(
+            json!({
+                "jsonrpc": "2.0",
+                "method": "subscribe_to_transactions",
+                "params": {"starting_version": 0},
+                "id": 1,
+            })
+            .to_string(),
+        )
+        .await;
+    for version in 0..min(mock_db.all_txns.len() as u64, 5) {
+        let response = recv_stream_response(&mut client).await;
+        assert_eq!(response.id, Some(json!(1)));
+        let txn: TransactionView = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(txn.version, version);
+    }
+
+    // Events of the key
+    let (version, event) = mock_db.events[0].clone();
+    client
+        .send_text(
+            json!({
+                "jsonrpc": "2.0",
+                "method": "subscribe_to_events",
+                "params": {"event_key": event.key(), "event_seq_num": event.sequence_number()},
+                "id": 2,
+            })
+            .to_string(),
+        )
+        .await;
+    loop {
+        // Skip what remains of the transaction subscription.
+        let response = recv_stream_response(&mut client).await;
+        if response.id == Some(json!(2)) {
+            let view: EventView = serde_json::from_value(response.result.unwrap()).unwrap();
+            assert_eq!(view, EventView::try_from((version, event)).unwrap());
+            break;
+        }
+    }
+}
+
+async fn recv_stream_response(client: &mut warp::test::WsClient) -> JsonRpcResponse {
+    let message = client.recv().await.unwrap();
+    serde_json::from_str(message.to_str().unwrap()).unwrap()
+}
+
 /// Returns the first account address stored in the given mock database.
 fn get_first_account_from_mock_db(mock_db: &MockDiemDB) -> AccountAddress {
     *mock_db
@@ -4,7 +4,8 @@
 use anyhow::{format_err, Error, Result};
 use diem_config::{
     config::{
-        RoleType, DEFAULT_BATCH_SIZE_LIMIT, DEFAULT_CONTENT_LENGTH_LIMIT, DEFAULT_PAGE_SIZE_LIMIT,
+        RoleType, StreamConfig, DEFAULT_BATCH_SIZE_LIMIT, DEFAULT_CONTENT_LENGTH_LIMIT,
+        DEFAULT_PAGE_SIZE_LIMIT,
     },
     utils,
 };
@@ -30,6 +31,7 @@ use diem_types::{
         TransactionWithProof, Version,
     },
     vm_status::KeptVMStatus,
+    write_set::WriteSet,
 };
 use diemdb::test_helper::arb_blocks_to_commit;
 
@@ -76,6 +78,8 @@ pub fn test_bootstrap(
         mp_sender,
         RoleType::Validator,
         ChainId::test(),
+        &StreamConfig::default(),
+        None,
     )
 }
 
@@ -87,6 +91,7 @@ pub struct MockDiemDB {
     pub genesis: HashMap<AccountAddress, AccountStateBlob>,
     pub all_accounts: HashMap<AccountAddress, AccountStateBlob>,
     pub all_txns: Vec<(Transaction, KeptVMStatus)>,
+    pub write_sets: Vec<WriteSet>,
     pub events: Vec<(u64, ContractEvent)>,
     pub account_state_with_proof: Vec<AccountStateWithProof>,
     pub timestamps: Vec<u64>,
@@ -346,6 +351,13 @@ impl DbReader for MockDiemDB {
         })
     }
 
+    fn get_write_set(&self, version: Version) -> Result<WriteSet> {
+        self.write_sets
+            .get(version as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("WriteSet {} not found", version))
+    }
+
     fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
         Ok(HashValue::zero())
     }
@@ -361,6 +373,7 @@ pub fn mock_db() -> MockDiemDB {
     let mut version = 1;
     let mut all_accounts = HashMap::new();
     let mut all_txns = vec![];
+    let mut write_sets = vec![];
     let mut events = vec![];
     let mut timestamps = vec![0_u64];
 
@@ -392,6 +405,13 @@ pub fn mock_db() -> MockDiemDB {
             all_accounts.insert(address, AccountStateBlob::try_from(&state).unwrap());
         }
 
+        // Record all write sets.
+        write_sets.extend(
+            txns_to_commit
+                .iter()
+                .map(|txn_to_commit| txn_to_commit.write_set().clone()),
+        );
+
         // Record all transactions.
         all_txns.extend(txns_to_commit.iter().map(|txn_to_commit| {
             (
@@ -425,6 +445,7 @@ pub fn mock_db() -> MockDiemDB {
         genesis,
         all_accounts,
         all_txns,
+        write_sets,
         events,
         account_state_with_proof,
         timestamps,
@@ -51,6 +51,8 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+    MempoolGasPriceBumpTooLow = -32013,
+    MempoolGasPriceTooLowToEvict = -32014,
 }
 
 /// JSON RPC server error codes for invalid request
@@ -189,6 +191,8 @@ impl JsonRpcError {
             MempoolStatusCode::InvalidUpdate => ServerCode::MempoolInvalidUpdate,
             MempoolStatusCode::VmError => ServerCode::MempoolVmError,
             MempoolStatusCode::UnknownStatus => ServerCode::MempoolUnknownError,
+            MempoolStatusCode::GasPriceBumpTooLow => ServerCode::MempoolGasPriceBumpTooLow,
+            MempoolStatusCode::GasPriceTooLowToEvict => ServerCode::MempoolGasPriceTooLowToEvict,
             MempoolStatusCode::Accepted => {
                 return Err(anyhow::format_err!(
                     "[JSON RPC] cannot create mempool error for mempool accepted status"
@@ -302,6 +306,14 @@ mod tests {
             MempoolStatusCode::UnknownStatus,
             ServerCode::MempoolUnknownError,
         );
+        assert_map_code(
+            MempoolStatusCode::GasPriceBumpTooLow,
+            ServerCode::MempoolGasPriceBumpTooLow,
+        );
+        assert_map_code(
+            MempoolStatusCode::GasPriceTooLowToEvict,
+            ServerCode::MempoolGasPriceTooLowToEvict,
+        );
     }
 
     #[test]
@@ -4,6 +4,7 @@
 pub mod errors;
 pub mod request;
 pub mod response;
+pub mod stream;
 pub mod views;
 
 use serde::{Deserialize, Serialize};
@@ -44,6 +45,11 @@ pub enum Method {
     GetAccountStateWithProof,
     GetTransactionsWithProofs,
     GetEventsWithProofs,
+    GetAccountTransactionsWithProofs,
+    SimulateTransaction,
+    GetTransactionWriteSets,
+    GetAccountResources,
+    GetAccountResource,
 }
 
 impl Method {
@@ -62,6 +68,11 @@ impl Method {
             Method::GetAccountStateWithProof => "get_account_state_with_proof",
             Method::GetTransactionsWithProofs => "get_transactions_with_proofs",
             Method::GetEventsWithProofs => "get_events_with_proofs",
+            Method::GetAccountTransactionsWithProofs => "get_account_transactions_with_proofs",
+            Method::SimulateTransaction => "simulate_transaction",
+            Method::GetTransactionWriteSets => "get_transaction_write_sets",
+            Method::GetAccountResources => "get_account_resources",
+            Method::GetAccountResource => "get_account_resource",
         }
     }
 }
@@ -3,8 +3,15 @@
 
 use super::{Id, JsonRpcVersion, Method};
 use crate::{errors::JsonRpcError, views::BytesView};
+use diem_crypto::ed25519::Ed25519PublicKey;
 use diem_types::{
-    account_address::AccountAddress, event::EventKey, transaction::SignedTransaction,
+    account_address::AccountAddress,
+    event::EventKey,
+    transaction::{RawTransaction, SignedTransaction},
+};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    parser::parse_type_tag,
 };
 use serde::{de, Deserialize, Serialize};
 use std::fmt;
@@ -80,6 +87,11 @@ pub enum MethodRequest {
     GetAccountStateWithProof(GetAccountStateWithProofParams),
     GetTransactionsWithProofs(GetTransactionsWithProofsParams),
     GetEventsWithProofs(GetEventsWithProofsParams),
+    GetAccountTransactionsWithProofs(GetAccountTransactionsWithProofsParams),
+    SimulateTransaction(SimulateTransactionParams),
+    GetTransactionWriteSets(GetTransactionWriteSetsParams),
+    GetAccountResources(GetAccountResourcesParams),
+    GetAccountResource(GetAccountResourceParams),
 }
 
 impl MethodRequest {
@@ -112,6 +124,21 @@ impl MethodRequest {
             Method::GetEventsWithProofs => {
                 MethodRequest::GetEventsWithProofs(serde_json::from_value(value)?)
             }
+            Method::GetAccountTransactionsWithProofs => {
+                MethodRequest::GetAccountTransactionsWithProofs(serde_json::from_value(value)?)
+            }
+            Method::SimulateTransaction => {
+                MethodRequest::SimulateTransaction(serde_json::from_value(value)?)
+            }
+            Method::GetTransactionWriteSets => {
+                MethodRequest::GetTransactionWriteSets(serde_json::from_value(value)?)
+            }
+            Method::GetAccountResources => {
+                MethodRequest::GetAccountResources(serde_json::from_value(value)?)
+            }
+            Method::GetAccountResource => {
+                MethodRequest::GetAccountResource(serde_json::from_value(value)?)
+            }
         };
 
         Ok(method_request)
@@ -132,6 +159,13 @@ impl MethodRequest {
             MethodRequest::GetAccountStateWithProof(_) => Method::GetAccountStateWithProof,
             MethodRequest::GetTransactionsWithProofs(_) => Method::GetTransactionsWithProofs,
             MethodRequest::GetEventsWithProofs(_) => Method::GetEventsWithProofs,
+            MethodRequest::GetAccountTransactionsWithProofs(_) => {
+                Method::GetAccountTransactionsWithProofs
+            }
+            MethodRequest::SimulateTransaction(_) => Method::SimulateTransaction,
+            MethodRequest::GetTransactionWriteSets(_) => Method::GetTransactionWriteSets,
+            MethodRequest::GetAccountResources(_) => Method::GetAccountResources,
+            MethodRequest::GetAccountResource(_) => Method::GetAccountResource,
         }
     }
 }
@@ -329,6 +363,108 @@ pub struct GetEventsWithProofsParams {
     pub limit: u64,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAccountTransactionsWithProofsParams {
+    pub account: AccountAddress,
+    pub start: u64,
+    pub limit: u64,
+    pub include_events: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SimulateTransactionParams {
+    pub data: SimulatedTransaction,
+    /// Public key of the sender, required to simulate a `RawTransaction`
+    #[serde(default)]
+    pub public_key: Option<Ed25519PublicKey>,
+}
+
+/// A transaction to simulate, which doesn't need to be signed
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulatedTransaction {
+    /// A signed transaction, whose signature isn't checked
+    Signed(SignedTransaction),
+    /// An unsigned transaction
+    Raw(RawTransaction),
+}
+
+impl Serialize for SimulatedTransaction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::Error;
+        let bytes = match self {
+            SimulatedTransaction::Signed(txn) => bcs::to_bytes(txn),
+            SimulatedTransaction::Raw(txn) => bcs::to_bytes(txn),
+        };
+        BytesView::new(bytes.map_err(S::Error::custom)?).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SimulatedTransaction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let error = || D::Error::custom("expected hex-encoded SignedTransaction or RawTransaction");
+        let bytes = BytesView::deserialize(deserializer).map_err(|_| error())?;
+        // BCS rejects trailing bytes, so a RawTransaction never decodes as a SignedTransaction
+        // and vice versa.
+        if let Ok(txn) = bcs::from_bytes(bytes.inner()) {
+            return Ok(SimulatedTransaction::Signed(txn));
+        }
+        bcs::from_bytes(bytes.inner())
+            .map(SimulatedTransaction::Raw)
+            .map_err(|_| error())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetTransactionWriteSetsParams {
+    pub start_version: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAccountResourcesParams {
+    pub account: AccountAddress,
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAccountResourceParams {
+    pub account: AccountAddress,
+    #[serde(serialize_with = "serialize_struct_tag")]
+    #[serde(deserialize_with = "deserialize_struct_tag")]
+    pub struct_tag: StructTag,
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+fn serialize_struct_tag<S>(struct_tag: &StructTag, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    struct_tag.to_string().serialize(serializer)
+}
+
+fn deserialize_struct_tag<'de, D>(deserializer: D) -> Result<StructTag, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    let s = String::deserialize(deserializer)?;
+    match parse_type_tag(&s) {
+        Ok(TypeTag::Struct(struct_tag)) => Ok(struct_tag),
+        _ => Err(D::Error::custom(
+            "expected a struct type, e.g. 0x1::DiemAccount::DiemAccount",
+        )),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -869,4 +1005,150 @@ mod test {
         });
         serde_json::from_value::<GetEventsWithProofsParams>(value).unwrap();
     }
+
+    #[test]
+    fn get_account_transactions_with_proofs() {
+        let account = "1668f6be25668c1a17cd8caf6b8d2f25";
+
+        // Array with all params
+        let value = serde_json::json!([account, 10, 11, false]);
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap();
+
+        // Array with too many params
+        let value = serde_json::json!([account, 10, 11, false, "foo"]);
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap_err();
+
+        // Array with wrong param
+        let value = serde_json::json!(["foo", 10, 11, false]);
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap_err();
+
+        // Array with too few params
+        let value = serde_json::json!([account, 10, 11]);
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "account": account,
+            "start": 10,
+            "limit": 11,
+            "include_events": true,
+        });
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap();
+
+        // Object without all params
+        let value = serde_json::json!({
+            "account": account,
+            "include_events": true,
+        });
+        serde_json::from_value::<GetAccountTransactionsWithProofsParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn simulate_transaction() {
+        use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
+        use diem_types::{chain_id::ChainId, transaction::Script};
+        use std::convert::TryFrom;
+
+        let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+        let raw_txn = RawTransaction::new_script(
+            AccountAddress::random(),
+            0,
+            Script::new(vec![], vec![], vec![]),
+            1_000_000,
+            0,
+            "XUS".to_owned(),
+            0,
+            ChainId::test(),
+        );
+        let txn = raw_txn
+            .clone()
+            .sign(&private_key, private_key.public_key())
+            .unwrap()
+            .into_inner();
+        let data = hex::encode(bcs::to_bytes(&txn).unwrap());
+        let raw_data = hex::encode(bcs::to_bytes(&raw_txn).unwrap());
+        let public_key = private_key.public_key().to_encoded_string().unwrap();
+
+        // Array params
+        let value = serde_json::json!([data]);
+        let params = serde_json::from_value::<SimulateTransactionParams>(value).unwrap();
+        assert_eq!(params.data, SimulatedTransaction::Signed(txn));
+        assert_eq!(params.public_key, None);
+
+        // Object params
+        let value = serde_json::json!({ "data": data });
+        serde_json::from_value::<SimulateTransactionParams>(value).unwrap();
+
+        // Unsigned transaction with the public key of the sender
+        let value = serde_json::json!([raw_data, public_key]);
+        let params = serde_json::from_value::<SimulateTransactionParams>(value).unwrap();
+        assert_eq!(params.data, SimulatedTransaction::Raw(raw_txn));
+        assert_eq!(params.public_key, Some(private_key.public_key()));
+
+        // Invalid public key
+        let value = serde_json::json!([raw_data, "0102"]);
+        serde_json::from_value::<SimulateTransactionParams>(value).unwrap_err();
+
+        // Not hex
+        let value = serde_json::json!(["foo"]);
+        serde_json::from_value::<SimulateTransactionParams>(value).unwrap_err();
+
+        // Hex but neither a SignedTransaction nor a RawTransaction
+        let value = serde_json::json!(["0102"]);
+        serde_json::from_value::<SimulateTransactionParams>(value).unwrap_err();
+
+        // Empty array without required params should fail
+        let value = serde_json::json!([]);
+        serde_json::from_value::<SimulateTransactionParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_transaction_write_sets() {
+        // Array params
+        let value = serde_json::json!([10, 20]);
+        let params = serde_json::from_value::<GetTransactionWriteSetsParams>(value).unwrap();
+        assert_eq!(params.start_version, 10);
+        assert_eq!(params.limit, 20);
+
+        // Object params
+        let value = serde_json::json!({ "start_version": 10, "limit": 20 });
+        serde_json::from_value::<GetTransactionWriteSetsParams>(value).unwrap();
+
+        // Missing limit
+        let value = serde_json::json!([10]);
+        serde_json::from_value::<GetTransactionWriteSetsParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_account_resource() {
+        let address = "00000000000000000000000000000001";
+
+        // Without version
+        let value = serde_json::json!([address, "0x1::DiemAccount::Balance<0x1::XUS::XUS>"]);
+        let params = serde_json::from_value::<GetAccountResourceParams>(value).unwrap();
+        assert_eq!(params.struct_tag.module.as_str(), "DiemAccount");
+        assert_eq!(params.struct_tag.name.as_str(), "Balance");
+        assert_eq!(params.struct_tag.type_params.len(), 1);
+        assert_eq!(params.version, None);
+
+        // With version
+        let value = serde_json::json!([address, "0x1::DiemAccount::DiemAccount", 10]);
+        let params = serde_json::from_value::<GetAccountResourceParams>(value).unwrap();
+        assert_eq!(params.version, Some(10));
+
+        // Round trip
+        let value = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            value["struct_tag"],
+            serde_json::json!("0x1::DiemAccount::DiemAccount")
+        );
+
+        // Not a struct type
+        let value = serde_json::json!([address, "u64"]);
+        serde_json::from_value::<GetAccountResourceParams>(value).unwrap_err();
+
+        // Not a type
+        let value = serde_json::json!([address, "foo bar"]);
+        serde_json::from_value::<GetAccountResourceParams>(value).unwrap_err();
+    }
 }
@@ -0,0 +1,141 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Requests of the WebSocket streaming API.
+//!
+//! A client sends subscription requests over a WebSocket connection, and the server pushes a
+//! `JsonRpcResponse` with the id of the subscription request for every new transaction or event.
+
+use super::{Id, JsonRpcVersion};
+use diem_types::event::EventKey;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamMethod {
+    SubscribeToTransactions,
+    SubscribeToEvents,
+}
+
+impl StreamMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            StreamMethod::SubscribeToTransactions => "subscribe_to_transactions",
+            StreamMethod::SubscribeToEvents => "subscribe_to_events",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StreamJsonRpcRequest {
+    jsonrpc: JsonRpcVersion,
+    #[serde(flatten)]
+    pub method_request: StreamMethodRequest,
+    pub id: Id,
+}
+
+impl StreamJsonRpcRequest {
+    pub fn new(method_request: StreamMethodRequest, id: Id) -> Self {
+        Self {
+            jsonrpc: JsonRpcVersion::V2,
+            method_request,
+            id,
+        }
+    }
+
+    pub fn method(&self) -> StreamMethod {
+        self.method_request.method()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum StreamMethodRequest {
+    SubscribeToTransactions(SubscribeToTransactionsParams),
+    SubscribeToEvents(SubscribeToEventsParams),
+}
+
+impl StreamMethodRequest {
+    pub fn method(&self) -> StreamMethod {
+        match self {
+            StreamMethodRequest::SubscribeToTransactions(_) => {
+                StreamMethod::SubscribeToTransactions
+            }
+            StreamMethodRequest::SubscribeToEvents(_) => StreamMethod::SubscribeToEvents,
+        }
+    }
+}
+
+/// Pushes every committed transaction from `starting_version` on, as a `TransactionView`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubscribeToTransactionsParams {
+    pub starting_version: u64,
+    #[serde(default)]
+    pub include_events: bool,
+}
+
+/// Pushes every committed event of `event_key` from sequence number `event_seq_num` on, as an
+/// `EventView`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubscribeToEventsParams {
+    pub event_key: EventKey,
+    pub event_seq_num: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn subscribe_to_transactions() {
+        let value = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "subscribe_to_transactions",
+            "params": {"starting_version": 10},
+            "id": 1,
+        });
+        let request = serde_json::from_value::<StreamJsonRpcRequest>(value).unwrap();
+        assert_eq!(request.method(), StreamMethod::SubscribeToTransactions);
+        assert_eq!(request.id, Id::Number(1));
+
+        // Array params
+        let value = serde_json::json!([10, true]);
+        serde_json::from_value::<SubscribeToTransactionsParams>(value).unwrap();
+
+        // Missing starting version
+        let value = serde_json::json!({"include_events": true});
+        serde_json::from_value::<SubscribeToTransactionsParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn subscribe_to_events() {
+        let key = "13000000000000000000000000000000000000000a550c18";
+
+        let value = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "subscribe_to_events",
+            "params": {"event_key": key, "event_seq_num": 0},
+            "id": "foo",
+        });
+        let request = serde_json::from_value::<StreamJsonRpcRequest>(value).unwrap();
+        assert_eq!(request.method(), StreamMethod::SubscribeToEvents);
+
+        // Wrong event key
+        let value = serde_json::json!({"event_key": "foo", "event_seq_num": 0});
+        serde_json::from_value::<SubscribeToEventsParams>(value).unwrap_err();
+
+        // Missing sequence number
+        let value = serde_json::json!({ "event_key": key });
+        serde_json::from_value::<SubscribeToEventsParams>(value).unwrap_err();
+
+        // Unknown method
+        let value = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "subscribe_to_foo",
+            "params": {},
+            "id": 1,
+        });
+        serde_json::from_value::<StreamJsonRpcRequest>(value).unwrap_err();
+    }
+}
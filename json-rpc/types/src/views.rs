@@ -5,6 +5,7 @@ use anyhow::{ensure, format_err, Error, Result};
 use diem_crypto::hash::{CryptoHash, HashValue};
 use diem_transaction_builder::{error_explain, stdlib::ScriptCall};
 use diem_types::{
+    access_path::AccessPath,
     account_config::{
         AccountResource, AccountRole, AdminTransactionEvent, BalanceResource, BaseUrlRotationEvent,
         BurnEvent, CancelBurnEvent, ComplianceKeyRotationEvent, CreateAccountEvent,
@@ -25,9 +26,11 @@ use diem_types::{
     },
     transaction::{
         Script, ScriptFunction, Transaction, TransactionArgument, TransactionInfo,
-        TransactionListWithProof, TransactionPayload,
+        TransactionListWithProof, TransactionOutput, TransactionPayload, TransactionStatus,
+        TransactionWithProof,
     },
     vm_status::KeptVMStatus,
+    write_set::WriteOp,
 };
 use hex::FromHex;
 use move_core_types::{
@@ -294,6 +297,29 @@ impl TryFrom<&EventWithProof> for EventWithProofView {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransactionWithProofView {
+    pub transaction_with_proof: BytesView,
+}
+
+impl TryFrom<&TransactionWithProofView> for TransactionWithProof {
+    type Error = Error;
+
+    fn try_from(view: &TransactionWithProofView) -> Result<Self> {
+        Ok(bcs::from_bytes(&view.transaction_with_proof)?)
+    }
+}
+
+impl TryFrom<&TransactionWithProof> for TransactionWithProofView {
+    type Error = Error;
+
+    fn try_from(txn: &TransactionWithProof) -> Result<Self> {
+        Ok(Self {
+            transaction_with_proof: BytesView::from(bcs::to_bytes(txn)?),
+        })
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum EventDataView {
@@ -756,6 +782,147 @@ impl TransactionView {
     }
 }
 
+/// The output of a simulated transaction, which was executed but not committed.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionOutputView {
+    pub vm_status: VMStatusView,
+    pub gas_used: u64,
+    pub events: Vec<EventView>,
+    pub write_set: Vec<WriteOpView>,
+}
+
+impl TransactionOutputView {
+    /// `version` is the version the transaction would have if it was committed; it's used for the
+    /// events.
+    pub fn try_from_output(version: u64, output: TransactionOutput) -> Result<Self> {
+        let vm_status = match output.status() {
+            TransactionStatus::Keep(status) => VMStatusView::from(status),
+            status => return Err(format_err!("transaction was not kept: {:?}", status)),
+        };
+        let gas_used = output.gas_used();
+        let write_set = output.write_set().iter().map(WriteOpView::from).collect();
+        let events = output
+            .events()
+            .iter()
+            .cloned()
+            .map(|event| EventView::try_from((version, event)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TransactionOutputView {
+            vm_status,
+            gas_used,
+            events,
+            write_set,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum WriteOpView {
+    Value {
+        address: AccountAddress,
+        path: BytesView,
+        value: BytesView,
+        /// Type of the resource at `path`, if it's a resource path.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resource_type: Option<String>,
+        /// `value` decoded to JSON, if it's a resource the server knows the layout of.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resource: Option<serde_json::Value>,
+    },
+    Deletion {
+        address: AccountAddress,
+        path: BytesView,
+    },
+}
+
+impl From<&(AccessPath, WriteOp)> for WriteOpView {
+    fn from((access_path, op): &(AccessPath, WriteOp)) -> Self {
+        let address = access_path.address;
+        let path = BytesView::from(access_path.path.as_slice());
+        match op {
+            WriteOp::Value(value) => WriteOpView::Value {
+                address,
+                path,
+                value: BytesView::from(value.as_slice()),
+                resource_type: None,
+                resource: None,
+            },
+            WriteOp::Deletion => WriteOpView::Deletion { address, path },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionWriteSetView {
+    pub version: u64,
+    pub write_set: Vec<WriteOpView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AccountResourcesView {
+    pub resources: Vec<MoveResourceView>,
+    pub modules: Vec<MoveModuleView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveResourceView {
+    /// Move type of the resource, e.g. `0x1::DiemAccount::DiemAccount`.
+    pub r#type: String,
+    /// The resource decoded to JSON, if the server could decode it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// BCS serialized bytes of the resource.
+    pub bytes: BytesView,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveModuleView {
+    pub address: AccountAddress,
+    pub name: String,
+    pub bytecode: BytesView,
+    /// The ABI of the module, i.e. `friends`, `structs` and `functions`, is only set if the server
+    /// could deserialize the module.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friends: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structs: Option<Vec<MoveStructView>>,
+    /// The public, script and friend functions of the module.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub functions: Option<Vec<MoveFunctionView>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveStructView {
+    pub name: String,
+    pub abilities: Vec<String>,
+    pub type_parameters: Vec<MoveTypeParameterView>,
+    pub fields: Vec<MoveFieldView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveFieldView {
+    pub name: String,
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveFunctionView {
+    pub name: String,
+    pub visibility: String,
+    pub type_parameters: Vec<MoveTypeParameterView>,
+    pub parameters: Vec<String>,
+    pub return_types: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveTypeParameterView {
+    /// Abilities the type argument is required to have.
+    pub constraints: Vec<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct TransactionListView(pub Vec<TransactionView>);
 
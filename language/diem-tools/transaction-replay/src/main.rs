@@ -10,6 +10,7 @@ use diem_types::{
 };
 use difference::Changeset;
 use move_core_types::effects::ChangeSet;
+use move_vm_runtime::gas_profiler;
 use std::{fs, path::PathBuf};
 use structopt::StructOpt;
 
@@ -24,6 +25,10 @@ struct Opt {
     /// If true, persist the effects of replaying transactions via `cmd` to disk in a format understood by the Move CLI
     #[structopt(short = "s", global = true)]
     save_write_sets: bool,
+    /// If set, print where the replayed transactions spent gas, and write it to this file as
+    /// folded stacks for flame graph tools
+    #[structopt(long, global = true, parse(from_os_str))]
+    profile_gas: Option<PathBuf>,
     #[structopt(subcommand)] // Note that we mark a field as a subcommand
     cmd: Command,
 }
@@ -100,6 +105,12 @@ fn main() -> Result<()> {
 
     println!("Connection Succeeded");
 
+    if opt.profile_gas.is_some() {
+        if !move_vm_runtime::DEBUGGING_ENABLED {
+            bail!("Release builds only profile gas with the `debugging` feature");
+        }
+        gas_profiler::start();
+    }
     match opt.cmd {
         Command::ReplayTransactions { start, limit } => {
             println!(
@@ -233,5 +244,10 @@ fn main() -> Result<()> {
             )
         ),
     }
+    if let Some(path) = opt.profile_gas {
+        let profile = gas_profiler::finish();
+        print!("{}", profile);
+        profile.write_folded(fs::File::create(path)?)?;
+    }
     Ok(())
 }
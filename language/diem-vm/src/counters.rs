@@ -46,6 +46,16 @@ pub static BLOCK_TRANSACTION_COUNT: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count the number of transactions re-executed by the parallel executor because they read data
+/// that was modified by a preceding transaction of the block.
+pub static PARALLEL_REEXECUTED_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_vm_parallel_reexecuted_transactions",
+        "Number of transactions re-executed by the parallel executor"
+    )
+    .unwrap()
+});
+
 pub static TXN_TOTAL_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "diem_vm_txn_total_seconds",
@@ -4,7 +4,9 @@
 use crate::{
     counters::*,
     data_cache::StateViewCache,
-    diem_transaction_validator::validate_signature_checked_transaction,
+    diem_transaction_validator::{
+        validate_signature_checked_transaction, validate_unchecked_transaction,
+    },
     diem_vm::{
         charge_global_write_gas_usage, convert_changeset_and_events, get_transaction_output,
         DiemVMImpl, DiemVMInternals,
@@ -24,8 +26,9 @@ use diem_types::{
     block_metadata::BlockMetadata,
     on_chain_config::DIEM_VERSION_3,
     transaction::{
-        ChangeSet, Module, SignatureCheckedTransaction, Transaction, TransactionArgument,
-        TransactionOutput, TransactionPayload, TransactionStatus, WriteSetPayload,
+        ChangeSet, Module, SignatureCheckedTransaction, SignedTransaction, Transaction,
+        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
+        WriteSetPayload,
     },
     vm_status::{KeptVMStatus, StatusCode, VMStatus},
     write_set::{WriteSet, WriteSetMut},
@@ -38,7 +41,9 @@ use move_core_types::{
     transaction_argument::convert_txn_args,
     value::{serialize_values, MoveValue},
 };
-use move_vm_runtime::{data_cache::MoveStorage, logging::LogContext, session::Session};
+use move_vm_runtime::{
+    data_cache::MoveStorage, gas_profiler, logging::LogContext, session::Session,
+};
 use move_vm_types::gas_schedule::GasStatus;
 use rayon::prelude::*;
 use std::{
@@ -46,6 +51,9 @@ use std::{
     convert::{AsMut, AsRef},
 };
 
+/// The frame that gas profiles show the gas charged for the size of a transaction under
+const INTRINSIC_GAS_FRAME: &str = "[transaction size]";
+
 pub struct DiemVM(DiemVMImpl);
 
 impl DiemVM {
@@ -170,9 +178,10 @@ impl DiemVM {
 
         // Run the execution logic
         {
-            gas_status
-                .charge_intrinsic_gas(txn_data.transaction_size())
-                .map_err(|e| e.into_vm_status())?;
+            gas_profiler::record_charge(INTRINSIC_GAS_FRAME, gas_status, |gas_status| {
+                gas_status.charge_intrinsic_gas(txn_data.transaction_size())
+            })
+            .map_err(|e| e.into_vm_status())?;
 
             match payload {
                 TransactionPayload::Script(script) => {
@@ -265,9 +274,10 @@ impl DiemVM {
             account_config::CORE_CODE_ADDRESS
         };
 
-        gas_status
-            .charge_intrinsic_gas(txn_data.transaction_size())
-            .map_err(|e| e.into_vm_status())?;
+        gas_profiler::record_charge(INTRINSIC_GAS_FRAME, gas_status, |gas_status| {
+            gas_status.charge_intrinsic_gas(txn_data.transaction_size())
+        })
+        .map_err(|e| e.into_vm_status())?;
         session
             .publish_module(
                 module.code().to_vec(),
@@ -293,6 +303,17 @@ impl DiemVM {
         storage: &S,
         txn: &SignatureCheckedTransaction,
         log_context: &impl LogContext,
+    ) -> (VMStatus, TransactionOutput) {
+        self.execute_unchecked_user_transaction(storage, txn, log_context)
+    }
+
+    /// Executes a user transaction without checking its signature. The output must never be
+    /// committed.
+    fn execute_unchecked_user_transaction<S: MoveStorage>(
+        &self,
+        storage: &S,
+        txn: &SignedTransaction,
+        log_context: &impl LogContext,
     ) -> (VMStatus, TransactionOutput) {
         macro_rules! unwrap_or_discard {
             ($res: expr) => {
@@ -305,7 +326,7 @@ impl DiemVM {
 
         // Revalidate the transaction.
         let mut session = self.0.new_session(storage);
-        let account_currency_symbol = match validate_signature_checked_transaction(
+        let account_currency_symbol = match validate_unchecked_transaction(
             &self.0,
             &mut session,
             txn,
@@ -826,6 +847,21 @@ impl DiemVM {
         let vm = DiemVM::new(&state_view_cache);
         vm.execute_block_impl(transactions, &mut state_view_cache)
     }
+
+    /// Executes a user transaction on top of `state_view` without checking its signature, for
+    /// gas estimation and abort previews. The output must never be committed.
+    pub fn simulate_signed_transaction(
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let state_view_cache = StateViewCache::new(state_view);
+        let vm = DiemVM::new(&state_view_cache);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        if let TransactionPayload::WriteSet(_) = txn.payload() {
+            return discard_error_vm_status(VMStatus::Error(StatusCode::REJECTED_WRITE_SET));
+        }
+        vm.execute_unchecked_user_transaction(&state_view_cache, &txn, &log_context)
+    }
 }
 
 /// Check the signature (if any) of a transaction. If the signature is OK, the result
@@ -851,7 +887,7 @@ pub(crate) fn preprocess_transaction(txn: Transaction) -> PreprocessedTransactio
     }
 }
 
-fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
+pub(crate) fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
     let new_epoch_event_key = diem_types::on_chain_config::new_epoch_event_key();
     vm_output
         .events()
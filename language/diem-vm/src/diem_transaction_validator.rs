@@ -120,11 +120,31 @@ fn get_account_role(sender: AccountAddress, remote_cache: &StateViewCache) -> Go
 
 pub(crate) fn validate_signature_checked_transaction<S: MoveStorage>(
     vm: &DiemVMImpl,
-    mut session: &mut Session<S>,
+    session: &mut Session<S>,
     transaction: &SignatureCheckedTransaction,
     remote_cache: &S,
     allow_too_new: bool,
     log_context: &impl LogContext,
+) -> Result<(u64, Identifier), VMStatus> {
+    validate_unchecked_transaction(
+        vm,
+        session,
+        transaction,
+        remote_cache,
+        allow_too_new,
+        log_context,
+    )
+}
+
+/// Validates a transaction without checking its signature. This must only be used for
+/// transactions that are simulated, whose outputs are never committed.
+pub(crate) fn validate_unchecked_transaction<S: MoveStorage>(
+    vm: &DiemVMImpl,
+    mut session: &mut Session<S>,
+    transaction: &SignedTransaction,
+    remote_cache: &S,
+    allow_too_new: bool,
+    log_context: &impl LogContext,
 ) -> Result<(u64, Identifier), VMStatus> {
     if transaction.is_multi_agent() && vm.get_diem_version()? < DIEM_VERSION_3 {
         // Multi agent is not allowed under this version
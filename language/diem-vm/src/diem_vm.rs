@@ -35,6 +35,7 @@ use move_core_types::{
 };
 use move_vm_runtime::{
     data_cache::MoveStorage,
+    gas_profiler,
     logging::{expect_no_verification_errors, LogContext},
     move_vm::MoveVM,
     session::Session,
@@ -569,9 +570,10 @@ pub(crate) fn charge_global_write_gas_usage<R: MoveStorage>(
             .global_memory_per_byte_write_cost
             .mul(gas_status.cost_table().gas_constants.default_account_size)
             .get();
-    gas_status
-        .deduct_gas(InternalGasUnits::new(total_cost))
-        .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())
+    gas_profiler::record_charge("[storage writes]", gas_status, |gas_status| {
+        gas_status.deduct_gas(InternalGasUnits::new(total_cost))
+    })
+    .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())
 }
 
 pub(crate) fn get_transaction_output<A: AccessPathCache, S: MoveStorage>(
@@ -117,6 +117,7 @@ pub mod transaction_metadata;
 pub mod diem_transaction_executor;
 pub mod diem_transaction_validator;
 pub mod logging;
+pub mod parallel_executor;
 pub mod script_to_script_function;
 pub mod system_module_names;
 
@@ -125,7 +126,7 @@ mod unit_tests;
 
 pub use crate::{
     diem_transaction_executor::DiemVM, diem_transaction_validator::DiemVMValidator,
-    diem_vm::convert_changeset_and_events,
+    diem_vm::convert_changeset_and_events, parallel_executor::ParallelDiemVM,
 };
 
 use diem_state_view::StateView;
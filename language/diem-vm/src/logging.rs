@@ -23,6 +23,7 @@ pub struct AdapterLogSchema {
     // StateViewId::ChunkExecution - state sync
     first_version: Option<Version>,
     // StateViewId::TransactionValidation - validation
+    // StateViewId::TransactionSimulation - simulation
     base_version: Option<Version>,
 
     // transaction position in the list of transactions in the block,
@@ -54,6 +55,13 @@ impl AdapterLogSchema {
                 base_version: Some(base_version),
                 txn_id,
             },
+            StateViewId::TransactionSimulation { base_version } => Self {
+                name: LogEntry::Simulation,
+                block_id: None,
+                first_version: None,
+                base_version: Some(base_version),
+                txn_id,
+            },
             StateViewId::Miscellaneous => Self {
                 name: LogEntry::Miscellaneous,
                 block_id: None,
@@ -77,5 +85,6 @@ impl LogContext for AdapterLogSchema {
 pub enum LogEntry {
     Execution,
     Validation,
+    Simulation,
     Miscellaneous, // usually testing
 }
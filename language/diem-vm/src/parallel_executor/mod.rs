@@ -0,0 +1,230 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optimistic parallel execution of a block of transactions.
+//!
+//! Transactions are executed speculatively in rounds. In every round, the transactions whose
+//! output is unknown or stale are executed in parallel against a `MultiVersionCache`, which
+//! exposes to each transaction the writes of the transactions preceding it in the block. Every
+//! read is recorded along with the version it observed. Outputs are then committed in block
+//! order: a transaction is committed once all the outputs preceding it are committed and its
+//! reads still match the versions visible at its position in the block, which means it saw
+//! exactly the data sequential execution would have given it. Transactions that fail this
+//! check are re-executed in the next round.
+//!
+//! The first uncommitted transaction always observes final data, so every round commits at
+//! least one transaction. When a round commits less than half of what it executed (e.g. a block
+//! made of transactions from a single sender), the remaining transactions are executed one at a
+//! time instead, to avoid wasting work on outputs that are bound to be invalidated.
+//!
+//! Blocks containing transactions that publish modules or apply write sets are handed to the
+//! sequential `DiemVM`: the Move loader caches code for the whole block, so it must never see
+//! speculative modules.
+
+mod mvhashmap;
+mod storage_wrapper;
+
+use crate::{
+    counters::*,
+    data_cache::StateViewCache,
+    diem_transaction_executor::{
+        is_reconfiguration, preprocess_transaction, PreprocessedTransaction,
+    },
+    logging::AdapterLogSchema,
+    parallel_executor::{
+        mvhashmap::{Incarnation, MultiVersionCache, ReadDescriptor, TxnIndex},
+        storage_wrapper::VersionedView,
+    },
+    DiemVM, VMExecutor,
+};
+use diem_logger::prelude::*;
+use diem_state_view::StateView;
+use diem_types::{
+    transaction::{Transaction, TransactionOutput, TransactionPayload, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+    write_set::WriteSet,
+};
+use rayon::prelude::*;
+
+/// A `VMExecutor` that executes user transactions of a block in parallel and produces the same
+/// output as `DiemVM`.
+pub struct ParallelDiemVM;
+
+/// The result of a speculative execution of a transaction.
+struct SpeculativeOutput {
+    incarnation: Incarnation,
+    reads: Vec<ReadDescriptor>,
+    result: Result<(VMStatus, TransactionOutput), VMStatus>,
+}
+
+impl ParallelDiemVM {
+    /// Alternate form of 'execute_block' that keeps the vm_status before it goes into the
+    /// `TransactionOutput`
+    pub fn execute_block_and_keep_vm_status(
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<Vec<(VMStatus, TransactionOutput)>, VMStatus> {
+        if !transactions.iter().all(is_parallelizable) {
+            return DiemVM::execute_block_and_keep_vm_status(transactions, state_view);
+        }
+
+        let count = transactions.len();
+        let state_view_cache = StateViewCache::new(state_view);
+        let vm = DiemVM::new(&state_view_cache);
+
+        info!(
+            AdapterLogSchema::new(state_view.id(), 0),
+            "Executing block in parallel, transaction count: {}", count
+        );
+
+        let signature_verified_block: Vec<PreprocessedTransaction> = transactions
+            .into_par_iter()
+            .map(preprocess_transaction)
+            .collect();
+
+        let mut cache = MultiVersionCache::new(count);
+        let mut speculative_outputs: Vec<Option<SpeculativeOutput>> =
+            (0..count).map(|_| None).collect();
+        let mut result = Vec::with_capacity(count);
+        let mut should_restart = false;
+        let mut sequential = false;
+
+        while result.len() < count && !should_restart {
+            let committed = result.len();
+
+            // Pick the transactions that were never executed or read data that changed since.
+            let to_execute: Vec<(TxnIndex, Incarnation)> = (committed..count)
+                .filter_map(|idx| match &speculative_outputs[idx] {
+                    None => Some((idx, 0)),
+                    Some(output) if !cache.validate(idx, &output.reads) => {
+                        Some((idx, output.incarnation + 1))
+                    }
+                    Some(_) => None,
+                })
+                .take(if sequential { 1 } else { count })
+                .collect();
+
+            let outputs: Vec<(TxnIndex, SpeculativeOutput)> = to_execute
+                .par_iter()
+                .map(|(idx, incarnation)| {
+                    let output = execute_speculatively(
+                        &vm,
+                        &signature_verified_block[*idx],
+                        state_view,
+                        &cache,
+                        *idx,
+                        *incarnation,
+                    );
+                    (*idx, output)
+                })
+                .collect();
+
+            for (idx, output) in outputs {
+                if output.incarnation > 0 {
+                    PARALLEL_REEXECUTED_TRANSACTIONS.inc();
+                }
+                match &output.result {
+                    Ok((_, txn_output)) if !txn_output.status().is_discarded() => {
+                        cache.write(idx, output.incarnation, txn_output.write_set())
+                    }
+                    _ => cache.write(idx, output.incarnation, &WriteSet::default()),
+                }
+                speculative_outputs[idx] = Some(output);
+            }
+
+            // Commit, in order, every transaction whose reads are still valid.
+            for idx in committed..count {
+                let is_valid = match &speculative_outputs[idx] {
+                    Some(output) => cache.validate(idx, &output.reads),
+                    None => false,
+                };
+                if !is_valid {
+                    break;
+                }
+                let (vm_status, output) = speculative_outputs[idx]
+                    .take()
+                    .expect("Validated transaction must have an output")
+                    .result?;
+                if is_reconfiguration(&output) {
+                    info!(
+                        AdapterLogSchema::new(state_view.id(), idx),
+                        "Reconfiguration occurred: restart required",
+                    );
+                    should_restart = true;
+                }
+                result.push((vm_status, output));
+                if should_restart {
+                    break;
+                }
+            }
+
+            if !sequential && (result.len() - committed) * 2 < to_execute.len() {
+                debug!(
+                    AdapterLogSchema::new(state_view.id(), result.len()),
+                    "Too many conflicts, executing the rest of the block sequentially"
+                );
+                sequential = true;
+            }
+        }
+
+        // Every transaction after a reconfiguration has to be retried in the next epoch.
+        let remaining = count - result.len();
+        result.extend((0..remaining).map(|_| {
+            (
+                VMStatus::Error(StatusCode::UNKNOWN_STATUS),
+                TransactionOutput::new(WriteSet::default(), vec![], 0, TransactionStatus::Retry),
+            )
+        }));
+
+        // Record the histogram count for transactions per block.
+        BLOCK_TRANSACTION_COUNT.observe(count as f64);
+
+        Ok(result)
+    }
+}
+
+fn execute_speculatively(
+    vm: &DiemVM,
+    txn: &PreprocessedTransaction,
+    base_view: &dyn StateView,
+    cache: &MultiVersionCache,
+    txn_idx: TxnIndex,
+    incarnation: Incarnation,
+) -> SpeculativeOutput {
+    let view = VersionedView::new(base_view, cache, txn_idx);
+    let log_context = AdapterLogSchema::new(base_view.id(), txn_idx);
+    let result = vm
+        .execute_single_transaction(txn, &view, &log_context)
+        .map(|(vm_status, output, _sender)| (vm_status, output));
+    SpeculativeOutput {
+        incarnation,
+        reads: view.into_reads(),
+        result,
+    }
+}
+
+/// Returns true if `txn` can be executed speculatively, i.e. it does not publish code nor
+/// write directly to storage.
+fn is_parallelizable(txn: &Transaction) -> bool {
+    match txn {
+        Transaction::BlockMetadata(_) => true,
+        Transaction::UserTransaction(txn) => matches!(
+            txn.payload(),
+            TransactionPayload::Script(_) | TransactionPayload::ScriptFunction(_)
+        ),
+        Transaction::GenesisTransaction(_) => false,
+    }
+}
+
+impl VMExecutor for ParallelDiemVM {
+    fn execute_block(
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let output = Self::execute_block_and_keep_vm_status(transactions, state_view)?;
+        Ok(output
+            .into_iter()
+            .map(|(_vm_status, txn_output)| txn_output)
+            .collect())
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Multi-version data cache used by the parallel executor.
+//!
+//! Every transaction of the block owns a slot in the cache. A read issued on behalf of
+//! transaction `i` observes the value written by the closest transaction preceding `i` in the
+//! block, or falls through to the base `StateView` when no preceding transaction wrote to the
+//! access path. This is exactly the view sequential execution would give transaction `i`, as
+//! long as the outputs of all the preceding transactions are final.
+
+use diem_types::{
+    access_path::AccessPath,
+    write_set::{WriteOp, WriteSet},
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Position of a transaction in the block.
+pub type TxnIndex = usize;
+
+/// Number of times a transaction has been re-executed.
+pub type Incarnation = usize;
+
+/// Identifies a value in the cache: the transaction that wrote it and the incarnation of that
+/// transaction.
+pub type Version = (TxnIndex, Incarnation);
+
+/// A read performed during execution: the access path and the version that was observed.
+/// `None` means the value came from the base `StateView`.
+pub type ReadDescriptor = (AccessPath, Option<Version>);
+
+pub struct MultiVersionCache {
+    data: HashMap<AccessPath, BTreeMap<TxnIndex, (Incarnation, Option<Vec<u8>>)>>,
+    // Access paths written by the latest incarnation of each transaction.
+    writes: Vec<Vec<AccessPath>>,
+}
+
+impl MultiVersionCache {
+    pub fn new(num_txns: usize) -> Self {
+        Self {
+            data: HashMap::new(),
+            writes: vec![vec![]; num_txns],
+        }
+    }
+
+    /// Returns the value visible to `txn_idx` for `access_path` together with its version, or
+    /// `None` if the value has to be read from the base `StateView`.
+    pub fn read(
+        &self,
+        access_path: &AccessPath,
+        txn_idx: TxnIndex,
+    ) -> Option<(Version, &Option<Vec<u8>>)> {
+        self.data
+            .get(access_path)?
+            .range(..txn_idx)
+            .next_back()
+            .map(|(idx, (incarnation, value))| ((*idx, *incarnation), value))
+    }
+
+    /// Replaces everything previously written by `txn_idx` with the content of `write_set`.
+    pub fn write(&mut self, txn_idx: TxnIndex, incarnation: Incarnation, write_set: &WriteSet) {
+        for access_path in std::mem::take(&mut self.writes[txn_idx]) {
+            if let Some(versions) = self.data.get_mut(&access_path) {
+                versions.remove(&txn_idx);
+            }
+        }
+
+        let mut written = Vec::with_capacity(write_set.iter().len());
+        for (access_path, write_op) in write_set.iter() {
+            let value = match write_op {
+                WriteOp::Value(blob) => Some(blob.clone()),
+                WriteOp::Deletion => None,
+            };
+            self.data
+                .entry(access_path.clone())
+                .or_default()
+                .insert(txn_idx, (incarnation, value));
+            written.push(access_path.clone());
+        }
+        self.writes[txn_idx] = written;
+    }
+
+    /// Returns true if every read in `reads` would observe the same version if issued by
+    /// `txn_idx` against the current content of the cache.
+    pub fn validate(&self, txn_idx: TxnIndex, reads: &[ReadDescriptor]) -> bool {
+        reads.iter().all(|(access_path, version)| {
+            self.read(access_path, txn_idx).map(|(version, _)| version) == *version
+        })
+    }
+}
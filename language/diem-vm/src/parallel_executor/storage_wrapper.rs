@@ -0,0 +1,91 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    data_cache::RemoteStorage,
+    parallel_executor::mvhashmap::{MultiVersionCache, ReadDescriptor, TxnIndex},
+};
+use diem_infallible::Mutex;
+use diem_state_view::{StateView, StateViewId};
+use diem_types::{access_path::AccessPath, on_chain_config::ConfigStorage};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use move_vm_runtime::data_cache::MoveStorage;
+
+/// The view of the data store given to a single transaction during parallel execution.
+///
+/// Reads are served from the `MultiVersionCache` if a preceding transaction wrote to the access
+/// path and from the base `StateView` otherwise. Every read is recorded so that the executor can
+/// later check whether the transaction observed the same data sequential execution would have.
+pub struct VersionedView<'a> {
+    base_view: &'a dyn StateView,
+    cache: &'a MultiVersionCache,
+    txn_idx: TxnIndex,
+    reads: Mutex<Vec<ReadDescriptor>>,
+}
+
+impl<'a> VersionedView<'a> {
+    pub fn new(
+        base_view: &'a dyn StateView,
+        cache: &'a MultiVersionCache,
+        txn_idx: TxnIndex,
+    ) -> Self {
+        Self {
+            base_view,
+            cache,
+            txn_idx,
+            reads: Mutex::new(vec![]),
+        }
+    }
+
+    /// Consumes the view and returns all the reads issued through it.
+    pub fn into_reads(self) -> Vec<ReadDescriptor> {
+        std::mem::take(&mut *self.reads.lock())
+    }
+}
+
+impl<'a> StateView for VersionedView<'a> {
+    fn get(&self, access_path: &AccessPath) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.cache.read(access_path, self.txn_idx) {
+            Some((version, value)) => {
+                self.reads.lock().push((access_path.clone(), Some(version)));
+                Ok(value.clone())
+            }
+            None => {
+                self.reads.lock().push((access_path.clone(), None));
+                self.base_view.get(access_path)
+            }
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base_view.is_genesis()
+    }
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+}
+
+impl<'a> MoveStorage for VersionedView<'a> {
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        RemoteStorage::new(self).get_module(module_id)
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        RemoteStorage::new(self).get_resource(address, tag)
+    }
+}
+
+impl<'a> ConfigStorage for VersionedView<'a> {
+    fn fetch_config(&self, access_path: AccessPath) -> Option<Vec<u8>> {
+        self.get(&access_path).ok()?
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_types::{
+    transaction::{SignedTransaction, TransactionStatus},
+    vm_status::KeptVMStatus,
+};
+use language_e2e_tests::{
+    account::Account, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+};
+
+const INITIAL_BALANCE: u64 = 1_000_000;
+const INITIAL_SEQ_NUM: u64 = 10;
+
+// Executes `txns` both sequentially and in parallel and checks that the outputs are identical.
+fn assert_same_output(executor: &FakeExecutor, txns: Vec<SignedTransaction>) {
+    let sequential_output = executor.execute_block(txns.clone()).unwrap();
+    let parallel_output = executor.execute_block_in_parallel(txns).unwrap();
+    assert_eq!(sequential_output, parallel_output);
+}
+
+#[test]
+fn parallel_disjoint_transfers() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts = executor.create_accounts(40, INITIAL_BALANCE, INITIAL_SEQ_NUM);
+
+    let txns = accounts
+        .chunks(2)
+        .map(|pair| peer_to_peer_txn(&pair[0], &pair[1], INITIAL_SEQ_NUM, 1_000))
+        .collect::<Vec<_>>();
+    let output = executor.execute_block_in_parallel(txns.clone()).unwrap();
+    for txn_output in &output {
+        assert_eq!(
+            txn_output.status(),
+            &TransactionStatus::Keep(KeptVMStatus::Executed)
+        );
+    }
+    assert_same_output(&executor, txns);
+}
+
+#[test]
+fn parallel_cyclic_transfers() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts = executor.create_accounts(20, INITIAL_BALANCE, INITIAL_SEQ_NUM);
+
+    let count = accounts.len();
+    let txns = (0..count)
+        .map(|i| {
+            peer_to_peer_txn(
+                &accounts[i],
+                &accounts[(i + 1) % count],
+                INITIAL_SEQ_NUM,
+                1_000,
+            )
+        })
+        .collect();
+    assert_same_output(&executor, txns);
+}
+
+#[test]
+fn parallel_same_sender_transfers() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts = executor.create_accounts(20, INITIAL_BALANCE, INITIAL_SEQ_NUM);
+
+    let sender = &accounts[0];
+    let txns = accounts[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, receiver)| peer_to_peer_txn(sender, receiver, INITIAL_SEQ_NUM + i as u64, 1_000))
+        .collect();
+    assert_same_output(&executor, txns);
+}
+
+#[test]
+fn parallel_failed_and_discarded_transfers() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts = executor.create_accounts(10, INITIAL_BALANCE, INITIAL_SEQ_NUM);
+    let unknown = Account::new();
+
+    let txns = vec![
+        // Drains most of the balance of the sender of the next transaction.
+        peer_to_peer_txn(&accounts[0], &accounts[1], INITIAL_SEQ_NUM, 900_000),
+        peer_to_peer_txn(&accounts[0], &accounts[2], INITIAL_SEQ_NUM + 1, 900_000),
+        // Sequence number too new, discarded.
+        peer_to_peer_txn(&accounts[3], &accounts[4], INITIAL_SEQ_NUM + 5, 1_000),
+        // Receiver does not exist, aborts.
+        peer_to_peer_txn(&accounts[5], &unknown, INITIAL_SEQ_NUM, 1_000),
+        // Receives the funds of the first transaction before spending them.
+        peer_to_peer_txn(&accounts[1], &accounts[6], INITIAL_SEQ_NUM, 1_500_000),
+        peer_to_peer_txn(&accounts[7], &accounts[8], INITIAL_SEQ_NUM, 1_000),
+    ];
+    assert_same_output(&executor, txns);
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// A comment of the source, including its delimiters
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Whether the comment follows some other text on the same line
+    pub trailing: bool,
+}
+
+/// Collect all comments of `input`, in order. Like `strip_comments`, this skips over the contents
+/// of byte strings and supports nested block comments. The input is expected to have been verified
+/// to only contain permitted characters, so byte offsets and character offsets coincide.
+pub fn scan_comments(input: &str) -> Vec<Comment> {
+    let bytes = input.as_bytes();
+    let mut comments = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match (bytes[pos], bytes.get(pos + 1)) {
+            (b'"', _) => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != b'"' {
+                    // Skip over the escaped character (e.g., a quote or another backslash)
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos += 1;
+            }
+            (b'/', Some(b'/')) => {
+                let end = input[pos..].find('\n').map_or(input.len(), |idx| pos + idx);
+                comments.push(make_comment(input, pos, end));
+                pos = end;
+            }
+            (b'/', Some(b'*')) => {
+                let start = pos;
+                let mut nest = 0;
+                pos += 2;
+                while pos < bytes.len() {
+                    match (bytes[pos], bytes.get(pos + 1)) {
+                        (b'/', Some(b'*')) => {
+                            nest += 1;
+                            pos += 2;
+                        }
+                        (b'*', Some(b'/')) if nest == 0 => {
+                            pos += 2;
+                            break;
+                        }
+                        (b'*', Some(b'/')) => {
+                            nest -= 1;
+                            pos += 2;
+                        }
+                        _ => pos += 1,
+                    }
+                }
+                let end = pos.min(input.len());
+                comments.push(make_comment(input, start, end));
+            }
+            _ => pos += 1,
+        }
+    }
+    comments
+}
+
+fn make_comment(input: &str, start: usize, end: usize) -> Comment {
+    let line_start = input[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    Comment {
+        start,
+        end,
+        text: input[start..end].trim_end().to_string(),
+        trailing: !input[line_start..start].trim().is_empty(),
+    }
+}
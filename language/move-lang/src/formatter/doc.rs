@@ -0,0 +1,192 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small pretty printing document in the style of Wadler's "prettier printer".
+//!
+//! A `Doc` describes the text to print together with the places where a line may be broken.
+//! Line breaks are decided per `Group`: a group is printed on a single line if it fits in the
+//! remaining width, and otherwise every `Line` and `SoftLine` directly inside of it is broken.
+
+#[derive(Debug, Clone)]
+pub enum Doc {
+    Text(String),
+    /// A space, or a line break if the enclosing group is broken
+    Line,
+    /// Nothing, or a line break if the enclosing group is broken
+    SoftLine,
+    /// A line break that is always taken. Every group containing it is broken.
+    HardLine,
+    /// An empty line, emitted ahead of the following line break
+    BlankLine,
+    /// Text that is only printed if the enclosing group is broken
+    IfBreak(&'static str),
+    /// Increases the indentation of the line breaks inside by the given amount
+    Nest(usize, Box<Doc>),
+    /// The contents, and whether they contain a `HardLine`
+    Group(Box<Doc>, bool),
+    Concat(Vec<Doc>),
+}
+
+impl Doc {
+    pub fn text(s: impl Into<String>) -> Doc {
+        Doc::Text(s.into())
+    }
+
+    pub fn concat(docs: Vec<Doc>) -> Doc {
+        Doc::Concat(docs)
+    }
+
+    pub fn nest(indent: usize, doc: Doc) -> Doc {
+        Doc::Nest(indent, Box::new(doc))
+    }
+
+    pub fn group(doc: Doc) -> Doc {
+        let has_hard_line = doc.has_hard_line();
+        Doc::Group(Box::new(doc), has_hard_line)
+    }
+
+    /// Separate `docs` by `sep`
+    pub fn join(docs: Vec<Doc>, sep: Doc) -> Doc {
+        let mut result = vec![];
+        for (idx, doc) in docs.into_iter().enumerate() {
+            if idx > 0 {
+                result.push(sep.clone());
+            }
+            result.push(doc);
+        }
+        Doc::Concat(result)
+    }
+
+    /// A comma separated list in between `open` and `close`. If the list does not fit on one line,
+    /// every item is put on its own line, followed by a comma.
+    pub fn list(open: &str, items: Vec<Doc>, close: &str, padded: bool) -> Doc {
+        if items.is_empty() {
+            return Doc::text(format!("{}{}", open, close));
+        }
+        let line = || if padded { Doc::Line } else { Doc::SoftLine };
+        Doc::group(Doc::concat(vec![
+            Doc::text(open),
+            Doc::nest(
+                4,
+                Doc::concat(vec![
+                    line(),
+                    Doc::join(items, Doc::concat(vec![Doc::text(","), Doc::Line])),
+                    Doc::IfBreak(","),
+                ]),
+            ),
+            line(),
+            Doc::text(close),
+        ]))
+    }
+
+    fn has_hard_line(&self) -> bool {
+        match self {
+            Doc::HardLine => true,
+            Doc::Nest(_, doc) => doc.has_hard_line(),
+            Doc::Group(_, has_hard_line) => *has_hard_line,
+            Doc::Concat(docs) => docs.iter().any(|doc| doc.has_hard_line()),
+            Doc::Text(_) | Doc::Line | Doc::SoftLine | Doc::BlankLine | Doc::IfBreak(_) => false,
+        }
+    }
+
+    /// Render the document, breaking lines that would be longer than `width`
+    pub fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        let mut column = 0;
+        let mut stack = vec![(0, Mode::Break, self)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(s) => {
+                    out.push_str(s);
+                    column = match s.rfind('\n') {
+                        Some(idx) => s.len() - idx - 1,
+                        None => column + s.len(),
+                    };
+                }
+                Doc::Line if mode == Mode::Flat => {
+                    out.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine | Doc::IfBreak(_) if mode == Mode::Flat => (),
+                Doc::IfBreak(s) => {
+                    out.push_str(s);
+                    column += s.len();
+                }
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    new_line(&mut out);
+                    (0..indent).for_each(|_| out.push(' '));
+                    column = indent;
+                }
+                Doc::BlankLine => new_line(&mut out),
+                Doc::Nest(inc, doc) => stack.push((indent + inc, mode, doc)),
+                Doc::Group(doc, has_hard_line) => {
+                    let mode = if mode == Mode::Flat
+                        || (!has_hard_line
+                            && fits(
+                                (indent, Mode::Flat, doc),
+                                &stack,
+                                width as isize - column as isize,
+                            )) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.push((indent, mode, doc))
+                }
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            }
+        }
+        new_line(&mut out);
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Start a new line, dropping the trailing spaces of the current one
+fn new_line(out: &mut String) {
+    let len = out.trim_end_matches(' ').len();
+    out.truncate(len);
+    out.push('\n');
+}
+
+/// Check whether `next`, followed by the remaining documents in `rest`, fits in `width` columns
+/// up to the next line break
+fn fits(next: (usize, Mode, &Doc), rest: &[(usize, Mode, &Doc)], mut width: isize) -> bool {
+    let mut stack = vec![next];
+    let mut rest_idx = rest.len();
+    loop {
+        if width < 0 {
+            return false;
+        }
+        let (indent, mode, doc) = match stack.pop() {
+            Some(entry) => entry,
+            None if rest_idx == 0 => return true,
+            None => {
+                rest_idx -= 1;
+                rest[rest_idx]
+            }
+        };
+        match doc {
+            Doc::IfBreak(s) if mode == Mode::Break => width -= s.len() as isize,
+            Doc::IfBreak(_) => (),
+            Doc::Text(s) => match s.find('\n') {
+                Some(idx) => return width >= idx as isize,
+                None => width -= s.len() as isize,
+            },
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::SoftLine if mode == Mode::Flat => (),
+            Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::BlankLine => return true,
+            Doc::Nest(inc, doc) => stack.push((indent + inc, mode, doc)),
+            Doc::Group(doc, has_hard_line) => {
+                let mode = if *has_hard_line { Mode::Break } else { mode };
+                stack.push((indent, mode, doc))
+            }
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+        }
+    }
+}
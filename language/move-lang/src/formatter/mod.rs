@@ -0,0 +1,1243 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Formatting of Move source files.
+//!
+//! The formatter re-emits the parser AST of a file in a canonical style: four space indentation,
+//! one member or statement per line, and lists that are broken one item per line when they do not
+//! fit in `MAX_WIDTH` columns. Comments are not part of the AST. They are put back in front of the
+//! next member, statement, or closing brace that follows them in the source, either on their own
+//! line or, if they followed some code on the same line, at the end of the preceding line. Single
+//! blank lines between members and statements are kept. Formatting a formatted file leaves it
+//! unchanged.
+
+mod comments;
+mod doc;
+
+use crate::{
+    errors::Errors,
+    parser::{ast::*, syntax::parse_file_string},
+    shared::{Identifier, Name},
+    strip_comments_and_verify,
+};
+use comments::{scan_comments, Comment};
+use doc::Doc;
+use move_ir_types::location::*;
+
+/// The width lines are broken at, if possible
+pub const MAX_WIDTH: usize = 100;
+
+/// Format the Move source `input` of the file `fname`. Returns the parsing errors if the input is
+/// not a valid Move file.
+pub fn format_string(fname: &'static str, input: &str) -> Result<String, Errors> {
+    let (no_comments_buffer, comment_map) = strip_comments_and_verify(fname, input)?;
+    let (defs, _) = parse_file_string(fname, &no_comments_buffer, comment_map)?;
+    let mut formatter = Formatter {
+        source: input,
+        comments: scan_comments(input),
+        next_comment: 0,
+    };
+    let doc = formatter.file(&defs);
+    Ok(doc.render(MAX_WIDTH).trim_start_matches('\n').to_string())
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment>,
+    /// Index of the first comment that has not been emitted yet
+    next_comment: usize,
+}
+
+// Precedences of the expressions that are not binary operators. Binary operators use the
+// precedences of the parser, which lie in between.
+const EXP_PRECEDENCE: u32 = 0;
+const UNARY_PRECEDENCE: u32 = 13;
+const TERM_PRECEDENCE: u32 = 14;
+
+/// Names of specification variables that must be declared with an explicit `local`, because they
+/// would otherwise start a different spec block member
+const SPEC_MEMBER_KEYWORDS: &[&str] = &[
+    "assert",
+    "assume",
+    "decreases",
+    "aborts_if",
+    "aborts_with",
+    "succeeds_if",
+    "modifies",
+    "emits",
+    "ensures",
+    "requires",
+    "axiom",
+    "include",
+    "apply",
+    "pragma",
+    "global",
+    "local",
+];
+
+fn start(loc: Loc) -> usize {
+    loc.span().start().to_usize()
+}
+
+fn end(loc: Loc) -> usize {
+    loc.span().end().to_usize()
+}
+
+fn text(s: impl Into<String>) -> Doc {
+    Doc::text(s)
+}
+
+/// A block in between braces, with `body` indented by `indent`
+fn braces(indent: usize, body: Vec<Doc>) -> Doc {
+    if body.is_empty() {
+        return text("{}");
+    }
+    Doc::concat(vec![
+        text("{"),
+        Doc::nest(indent, Doc::concat(body)),
+        Doc::HardLine,
+        text("}"),
+    ])
+}
+
+fn paren(doc: Doc) -> Doc {
+    Doc::concat(vec![text("("), doc, text(")")])
+}
+
+fn binop_precedence(op: BinOp_) -> u32 {
+    use BinOp_ as B;
+    match op {
+        B::Implies => 2,
+        B::Or => 3,
+        B::And => 4,
+        B::Eq | B::Neq | B::Lt | B::Gt | B::Le | B::Ge => 5,
+        B::Range => 6,
+        B::BitOr => 7,
+        B::Xor => 8,
+        B::BitAnd => 9,
+        B::Shl | B::Shr => 10,
+        B::Add | B::Sub => 11,
+        B::Mul | B::Div | B::Mod => 12,
+    }
+}
+
+fn precedence(e: &Exp_) -> u32 {
+    match e {
+        Exp_::Lambda(..)
+        | Exp_::Quant(..)
+        | Exp_::IfElse(..)
+        | Exp_::While(..)
+        | Exp_::Loop(..)
+        | Exp_::Return(..)
+        | Exp_::Abort(..)
+        | Exp_::Assign(..) => EXP_PRECEDENCE,
+        Exp_::BinopExp(_, op, _) => binop_precedence(op.value),
+        Exp_::UnaryExp(..)
+        | Exp_::Borrow(..)
+        | Exp_::Dereference(..)
+        | Exp_::Move(..)
+        | Exp_::Copy(..) => UNARY_PRECEDENCE,
+        _ => TERM_PRECEDENCE,
+    }
+}
+
+/// Surround the parameter types of a function type by pipes. Without parameters, the pipes are
+/// separated by a space, as `||` is the boolean or.
+fn pipes(params: String) -> String {
+    if params.is_empty() {
+        "| |".to_string()
+    } else {
+        format!("|{}|", params)
+    }
+}
+
+/// Whether `e` ends with an `if` without an `else`, which would take over a following `else`
+fn ends_with_open_if(e: &Exp_) -> bool {
+    match e {
+        Exp_::IfElse(_, _, None) => true,
+        Exp_::IfElse(_, _, Some(e))
+        | Exp_::While(_, e)
+        | Exp_::Loop(e)
+        | Exp_::Return(Some(e))
+        | Exp_::Abort(e)
+        | Exp_::Assign(_, e)
+        | Exp_::Lambda(_, e)
+        | Exp_::Quant(_, _, _, _, e) => ends_with_open_if(&e.value),
+        _ => false,
+    }
+}
+
+impl<'a> Formatter<'a> {
+    //**********************************************************************************************
+    // Comments and line structure
+    //**********************************************************************************************
+
+    /// Start a new line in `body` for an item that begins at `pos`, emitting the comments in front
+    /// of it first
+    fn start_line(&mut self, body: &mut Vec<Doc>, pos: usize) {
+        let mut first = body.is_empty();
+        self.flush_comments(body, pos, &mut first);
+        if !first && self.blank_line_before(pos) {
+            body.push(Doc::BlankLine);
+        }
+        body.push(Doc::HardLine);
+    }
+
+    /// Emit the remaining comments of a block in `body`, up to its closing brace at `pos`
+    fn close_block(&mut self, body: &mut Vec<Doc>, pos: usize) {
+        let mut first = body.is_empty();
+        self.flush_comments(body, pos, &mut first);
+    }
+
+    fn flush_comments(&mut self, body: &mut Vec<Doc>, pos: usize, first: &mut bool) {
+        let mut after_line_comment = false;
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.start >= pos {
+                break;
+            }
+            if comment.trailing && !after_line_comment {
+                body.push(text(format!(" {}", comment.text)));
+            } else {
+                if !*first && self.blank_line_before(comment.start) {
+                    body.push(Doc::BlankLine);
+                }
+                body.push(Doc::HardLine);
+                body.push(text(comment.text.clone()));
+                *first = false;
+            }
+            after_line_comment = comment.text.starts_with("//");
+            self.next_comment += 1;
+        }
+    }
+
+    /// Whether the source has an empty line right in front of `pos`
+    fn blank_line_before(&self, pos: usize) -> bool {
+        let before = &self.source[..pos];
+        let whitespace = &before[before.trim_end().len()..];
+        whitespace.matches('\n').count() > 1
+    }
+
+    /// The position of `keyword` if it directly precedes `pos`, for items whose location starts
+    /// after their keyword
+    fn keyword_start(&self, pos: usize, keyword: &str) -> usize {
+        let before = self.source[..pos].trim_end();
+        if before.ends_with(keyword) {
+            before.len() - keyword.len()
+        } else {
+            pos
+        }
+    }
+
+    /// The position of the first token at or after `pos`, skipping over whitespace and comments
+    fn next_token(&self, mut pos: usize) -> usize {
+        let bytes = self.source.as_bytes();
+        loop {
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            match self.comments.binary_search_by_key(&pos, |c| c.start) {
+                Ok(idx) => pos = self.comments[idx].end,
+                Err(_) => return pos,
+            }
+        }
+    }
+
+    //**********************************************************************************************
+    // Definitions
+    //**********************************************************************************************
+
+    fn file(&mut self, defs: &[Definition]) -> Doc {
+        let mut body = vec![];
+        for def in defs {
+            match def {
+                Definition::Module(m) => {
+                    self.attributes(&mut body, &m.attributes);
+                    self.start_line(&mut body, start(m.loc));
+                    body.push(self.module(m));
+                }
+                Definition::Address(a) => {
+                    self.attributes(&mut body, &a.attributes);
+                    let pos = self.keyword_start(start(a.loc), "address");
+                    self.start_line(&mut body, pos);
+                    body.push(self.address(a));
+                }
+                Definition::Script(s) => {
+                    self.attributes(&mut body, &s.attributes);
+                    self.start_line(&mut body, start(s.loc));
+                    body.push(self.script(s));
+                }
+            }
+        }
+        self.close_block(&mut body, self.source.len());
+        Doc::concat(body)
+    }
+
+    fn address(&mut self, a: &AddressDefinition) -> Doc {
+        let mut header = format!("address {}", self.leading_name_access(&a.addr));
+        if let Some(value) = &a.addr_value {
+            header.push_str(&format!(" = {}", self.source_text(value.loc)));
+        }
+        let after_header =
+            self.next_token(a.addr_value.as_ref().map_or(end(a.loc), |v| end(v.loc)));
+        if self.source[after_header..].starts_with(';') {
+            return text(format!("{};", header));
+        }
+        let mut body = vec![];
+        for m in &a.modules {
+            self.attributes(&mut body, &m.attributes);
+            self.start_line(&mut body, start(m.loc));
+            body.push(self.module(m));
+        }
+        let last_end = a.modules.last().map_or(after_header + 1, |m| end(m.loc));
+        let close = self.next_token(last_end);
+        self.close_block(&mut body, close);
+        // modules are not indented inside of an address block
+        Doc::concat(vec![text(format!("{} ", header)), braces(0, body)])
+    }
+
+    fn module(&mut self, m: &ModuleDefinition) -> Doc {
+        let keyword = if m.is_spec_module { "spec" } else { "module" };
+        let address = match &m.address {
+            Some(addr) => format!("{}::", self.leading_name_access(addr)),
+            None => "".to_string(),
+        };
+        let header = format!("{} {}{} ", keyword, address, m.name);
+        let mut body = vec![];
+        for member in &m.members {
+            self.module_member(&mut body, member);
+        }
+        self.close_block(&mut body, end(m.loc) - 1);
+        Doc::concat(vec![text(header), braces(4, body)])
+    }
+
+    fn module_member(&mut self, body: &mut Vec<Doc>, member: &ModuleMember) {
+        match member {
+            ModuleMember::Function(f) => {
+                self.attributes(body, &f.attributes);
+                self.start_line(body, start(f.loc));
+                let doc = self.function(f);
+                body.push(doc);
+            }
+            ModuleMember::Struct(s) => {
+                self.attributes(body, &s.attributes);
+                self.start_line(body, start(s.loc));
+                let doc = self.struct_definition(s);
+                body.push(doc);
+            }
+            ModuleMember::Use(u) => self.use_line(body, u),
+            ModuleMember::Friend(f) => {
+                self.attributes(body, &f.attributes);
+                self.start_line(body, start(f.loc));
+                body.push(text(format!(
+                    "friend {};",
+                    self.name_access_chain(&f.friend)
+                )));
+            }
+            ModuleMember::Constant(c) => {
+                self.attributes(body, &c.attributes);
+                self.start_line(body, start(c.loc));
+                let doc = self.constant(c);
+                body.push(doc);
+            }
+            ModuleMember::Spec(s) => self.spec_block_line(body, s),
+        }
+    }
+
+    fn script(&mut self, s: &Script) -> Doc {
+        let mut body = vec![];
+        for u in &s.uses {
+            self.use_line(&mut body, u);
+        }
+        for c in &s.constants {
+            self.attributes(&mut body, &c.attributes);
+            self.start_line(&mut body, start(c.loc));
+            let doc = self.constant(c);
+            body.push(doc);
+        }
+        self.attributes(&mut body, &s.function.attributes);
+        self.start_line(&mut body, start(s.function.loc));
+        let doc = self.function(&s.function);
+        body.push(doc);
+        for spec in &s.specs {
+            self.spec_block_line(&mut body, spec);
+        }
+        self.close_block(&mut body, end(s.loc) - 1);
+        Doc::concat(vec![text("script "), braces(4, body)])
+    }
+
+    fn attributes(&mut self, body: &mut Vec<Doc>, attributes: &[Attributes]) {
+        for attrs in attributes {
+            self.start_line(body, start(attrs.loc));
+            let attrs = attrs
+                .value
+                .iter()
+                .map(|attr| self.attribute(attr))
+                .collect::<Vec<_>>();
+            body.push(text(format!("#[{}]", attrs.join(", "))));
+        }
+    }
+
+    fn attribute(&self, attr: &Attribute) -> String {
+        match &attr.value {
+            Attribute_::Name(n) => n.value.clone(),
+            Attribute_::Assigned(n, value) => {
+                let value = match &value.value {
+                    AttributeValue_::Value(v) => self.value(v),
+                    AttributeValue_::ModuleAccess(chain) => self.name_access_chain(chain),
+                };
+                format!("{} = {}", n, value)
+            }
+            Attribute_::Parameterized(n, attrs) => {
+                let attrs = attrs
+                    .value
+                    .iter()
+                    .map(|attr| self.attribute(attr))
+                    .collect::<Vec<_>>();
+                format!("{}({})", n, attrs.join(", "))
+            }
+        }
+    }
+
+    fn use_line(&mut self, body: &mut Vec<Doc>, u: &UseDecl) {
+        self.attributes(body, &u.attributes);
+        let ident = match &u.use_ {
+            Use::Module(ident, _) | Use::Members(ident, _) => ident,
+        };
+        let pos = self.keyword_start(start(ident.loc), "use");
+        self.start_line(body, pos);
+        body.push(self.use_decl(u));
+    }
+
+    fn use_decl(&self, u: &UseDecl) -> Doc {
+        let member = |(name, alias): &(Name, Option<Name>)| match alias {
+            Some(alias) => format!("{} as {}", name, alias),
+            None => name.value.clone(),
+        };
+        match &u.use_ {
+            Use::Module(ident, None) => text(format!("use {};", self.module_ident(ident))),
+            Use::Module(ident, Some(alias)) => {
+                text(format!("use {} as {};", self.module_ident(ident), alias))
+            }
+            Use::Members(ident, members) if members.len() == 1 => text(format!(
+                "use {}::{};",
+                self.module_ident(ident),
+                member(&members[0])
+            )),
+            Use::Members(ident, members) => Doc::concat(vec![
+                text(format!("use {}::", self.module_ident(ident))),
+                Doc::list(
+                    "{",
+                    members.iter().map(|m| text(member(m))).collect(),
+                    "}",
+                    false,
+                ),
+                text(";"),
+            ]),
+        }
+    }
+
+    fn function(&mut self, f: &Function) -> Doc {
+        let mut header = String::new();
+        if let FunctionBody_::Native = f.body.value {
+            header.push_str("native ");
+        }
+        if f.visibility != Visibility::Internal {
+            header.push_str(&format!("{} ", f.visibility));
+        }
+        header.push_str(&format!("fun {}", f.name));
+        let mut doc = self.signature(header, &f.signature, false);
+        if !f.acquires.is_empty() {
+            let acquires = f
+                .acquires
+                .iter()
+                .map(|chain| self.name_access_chain(chain))
+                .collect::<Vec<_>>();
+            doc.push(text(format!(" acquires {}", acquires.join(", "))));
+        }
+        match &f.body.value {
+            FunctionBody_::Native => doc.push(text(";")),
+            FunctionBody_::Defined(seq) => {
+                doc.push(text(" "));
+                doc.push(self.sequence(seq, end(f.body.loc) - 1));
+            }
+        }
+        Doc::concat(doc)
+    }
+
+    /// The type parameters, parameters, and return type of a function after `header`. A unit
+    /// return type is only printed if `explicit_unit` is set.
+    fn signature(
+        &self,
+        header: String,
+        signature: &FunctionSignature,
+        explicit_unit: bool,
+    ) -> Vec<Doc> {
+        let header = format!(
+            "{}{}",
+            header,
+            self.type_parameters(&signature.type_parameters)
+        );
+        let parameters = signature
+            .parameters
+            .iter()
+            .map(|(v, ty)| text(format!("{}: {}", v, self.type_(ty))))
+            .collect();
+        let mut doc = vec![text(header), Doc::list("(", parameters, ")", false)];
+        if explicit_unit || signature.return_type.value != Type_::Unit {
+            doc.push(text(format!(": {}", self.type_(&signature.return_type))));
+        }
+        doc
+    }
+
+    fn struct_definition(&mut self, s: &StructDefinition) -> Doc {
+        let mut header = String::new();
+        if let StructFields::Native(_) = s.fields {
+            header.push_str("native ");
+        }
+        header.push_str(&format!(
+            "struct {}{}",
+            s.name,
+            self.type_parameters(&s.type_parameters)
+        ));
+        if !s.abilities.is_empty() {
+            let abilities = s
+                .abilities
+                .iter()
+                .map(|a| a.value.to_string())
+                .collect::<Vec<_>>();
+            header.push_str(&format!(" has {}", abilities.join(", ")));
+        }
+        match &s.fields {
+            StructFields::Native(_) => text(format!("{};", header)),
+            StructFields::Defined(fields) => {
+                let mut body = vec![];
+                for (field, ty) in fields {
+                    self.start_line(&mut body, start(field.loc()));
+                    body.push(text(format!("{}: {},", field, self.type_(ty))));
+                }
+                self.close_block(&mut body, end(s.loc) - 1);
+                Doc::concat(vec![text(format!("{} ", header)), braces(4, body)])
+            }
+        }
+    }
+
+    fn constant(&mut self, c: &Constant) -> Doc {
+        Doc::concat(vec![
+            text(format!("const {}: {} = ", c.name, self.type_(&c.signature))),
+            self.exp(&c.value),
+            text(";"),
+        ])
+    }
+
+    //**********************************************************************************************
+    // Specifications
+    //**********************************************************************************************
+
+    fn spec_block_line(&mut self, body: &mut Vec<Doc>, block: &SpecBlock) {
+        self.attributes(body, &block.value.attributes);
+        self.start_line(body, start(block.loc));
+        let doc = self.spec_block(block);
+        body.push(doc);
+    }
+
+    fn spec_block(&mut self, block: &SpecBlock) -> Doc {
+        let SpecBlock_ {
+            attributes: _,
+            target,
+            uses,
+            members,
+        } = &block.value;
+        // A module level `invariant` or `spec fun` is parsed as a module spec block with an empty
+        // target
+        if target.value == SpecBlockTarget_::Module && start(target.loc) == end(target.loc) {
+            let member = self.spec_block_member(&members[0]);
+            return match &members[0].value {
+                SpecBlockMember_::Function { .. } => Doc::concat(vec![text("spec "), member]),
+                _ => member,
+            };
+        }
+
+        let header = match &target.value {
+            SpecBlockTarget_::Code => vec![text("spec ")],
+            SpecBlockTarget_::Module => vec![text("spec module ")],
+            SpecBlockTarget_::Member(name, None) => vec![text(format!("spec {} ", name))],
+            SpecBlockTarget_::Member(name, Some(signature)) => {
+                let mut doc = self.signature(format!("spec {}", name), signature, false);
+                doc.push(text(" "));
+                doc
+            }
+            SpecBlockTarget_::Schema(name, type_parameters) => vec![text(format!(
+                "spec schema {}{} ",
+                name,
+                self.type_parameters(type_parameters)
+            ))],
+        };
+        let mut body = vec![];
+        for u in uses {
+            self.use_line(&mut body, u);
+        }
+        for member in members {
+            self.start_line(&mut body, start(member.loc));
+            let doc = self.spec_block_member(member);
+            body.push(doc);
+        }
+        self.close_block(&mut body, end(block.loc) - 1);
+        Doc::concat(vec![Doc::concat(header), braces(4, body)])
+    }
+
+    fn spec_block_member(&mut self, member: &SpecBlockMember) -> Doc {
+        match &member.value {
+            SpecBlockMember_::Condition {
+                kind,
+                properties,
+                exp,
+                additional_exps,
+            } => {
+                let mut doc = vec![text(format!(
+                    "{}{}",
+                    condition_keyword(kind),
+                    self.properties(properties)
+                ))];
+                match kind {
+                    SpecConditionKind::AbortsWith | SpecConditionKind::Modifies => {
+                        let exps = additional_exps.iter().map(|e| self.exp(e)).collect();
+                        doc.push(Doc::nest(
+                            4,
+                            Doc::group(Doc::concat(vec![
+                                Doc::Line,
+                                Doc::join(exps, Doc::concat(vec![text(","), Doc::Line])),
+                            ])),
+                        ));
+                    }
+                    SpecConditionKind::AbortsIf => {
+                        doc.push(text(" "));
+                        doc.push(self.exp(exp));
+                        if let Some(code) = additional_exps.first() {
+                            doc.push(text(" with "));
+                            doc.push(self.exp(code));
+                        }
+                    }
+                    SpecConditionKind::Emits => {
+                        doc.push(text(" "));
+                        doc.push(self.exp(exp));
+                        doc.push(text(" to "));
+                        doc.push(self.exp(&additional_exps[0]));
+                        if let Some(cond) = additional_exps.get(1) {
+                            doc.push(text(" if "));
+                            doc.push(self.exp(cond));
+                        }
+                    }
+                    _ => {
+                        doc.push(text(" "));
+                        doc.push(self.exp(exp));
+                    }
+                }
+                doc.push(text(";"));
+                Doc::concat(doc)
+            }
+            SpecBlockMember_::Function {
+                uninterpreted,
+                name,
+                signature,
+                body,
+            } => {
+                let header = match &body.value {
+                    FunctionBody_::Native if !uninterpreted => format!("native fun {}", name),
+                    _ => format!("fun {}", name),
+                };
+                let mut doc = self.signature(header, signature, true);
+                match &body.value {
+                    FunctionBody_::Native => doc.push(text(";")),
+                    FunctionBody_::Defined(seq) => {
+                        doc.push(text(" "));
+                        doc.push(self.sequence(seq, end(body.loc) - 1));
+                    }
+                }
+                Doc::concat(doc)
+            }
+            SpecBlockMember_::Variable {
+                is_global,
+                name,
+                type_parameters,
+                type_,
+            } => {
+                let keyword = if *is_global {
+                    "global "
+                } else if SPEC_MEMBER_KEYWORDS.contains(&name.value.as_str()) {
+                    "local "
+                } else {
+                    ""
+                };
+                text(format!(
+                    "{}{}{}: {};",
+                    keyword,
+                    name,
+                    self.type_parameters(type_parameters),
+                    self.type_(type_)
+                ))
+            }
+            SpecBlockMember_::Let {
+                name,
+                post_state,
+                def,
+            } => Doc::concat(vec![
+                text(format!(
+                    "let {}{} = ",
+                    if *post_state { "post " } else { "" },
+                    name
+                )),
+                self.exp(def),
+                text(";"),
+            ]),
+            SpecBlockMember_::Include { properties, exp } => Doc::concat(vec![
+                text(format!("include{} ", self.properties(properties))),
+                self.exp(exp),
+                text(";"),
+            ]),
+            SpecBlockMember_::Apply {
+                exp,
+                patterns,
+                exclusion_patterns,
+            } => {
+                let mut doc = vec![text("apply "), self.exp(exp)];
+                let pattern_docs = |patterns: &[SpecApplyPattern]| {
+                    patterns
+                        .iter()
+                        .map(|p| text(self.spec_apply_pattern(p)))
+                        .collect::<Vec<_>>()
+                };
+                // the patterns of a clause are put one per line if they do not fit
+                let clause = |keyword: &str, patterns: &[SpecApplyPattern]| {
+                    Doc::group(Doc::concat(vec![
+                        text(keyword),
+                        Doc::nest(
+                            4,
+                            Doc::concat(vec![
+                                Doc::Line,
+                                Doc::join(
+                                    pattern_docs(patterns),
+                                    Doc::concat(vec![text(","), Doc::Line]),
+                                ),
+                            ]),
+                        ),
+                    ]))
+                };
+                let mut rest = vec![Doc::Line, clause("to", patterns)];
+                if !exclusion_patterns.is_empty() {
+                    rest.push(Doc::Line);
+                    rest.push(clause("except", exclusion_patterns));
+                }
+                doc.push(Doc::group(Doc::nest(4, Doc::concat(rest))));
+                doc.push(text(";"));
+                Doc::concat(doc)
+            }
+            SpecBlockMember_::Pragma { properties } => {
+                let properties = properties
+                    .iter()
+                    .map(|p| self.property(p))
+                    .collect::<Vec<_>>();
+                text(format!("pragma {};", properties.join(", ")))
+            }
+        }
+    }
+
+    fn spec_apply_pattern(&self, pattern: &SpecApplyPattern) -> String {
+        let SpecApplyPattern_ {
+            visibility,
+            name_pattern,
+            type_parameters,
+        } = &pattern.value;
+        let mut s = match visibility {
+            Some(Visibility::Internal) => "internal ".to_string(),
+            Some(vis) => format!("{} ", vis),
+            None => "".to_string(),
+        };
+        for fragment in name_pattern {
+            match &fragment.value {
+                SpecApplyFragment_::Wildcard => s.push('*'),
+                SpecApplyFragment_::NamePart(n) => s.push_str(&n.value),
+            }
+        }
+        s.push_str(&self.type_parameters(type_parameters));
+        s
+    }
+
+    /// The properties of a condition, e.g. ` [concrete, timeout = 10]`
+    fn properties(&self, properties: &[PragmaProperty]) -> String {
+        if properties.is_empty() {
+            return "".to_string();
+        }
+        let properties = properties
+            .iter()
+            .map(|p| self.property(p))
+            .collect::<Vec<_>>();
+        format!(" [{}]", properties.join(", "))
+    }
+
+    fn property(&self, property: &PragmaProperty) -> String {
+        let PragmaProperty_ { name, value } = &property.value;
+        match value {
+            None => name.value.clone(),
+            Some(PragmaValue::Literal(v)) => format!("{} = {}", name, self.value(v)),
+            Some(PragmaValue::Ident(chain)) => {
+                format!("{} = {}", name, self.name_access_chain(chain))
+            }
+        }
+    }
+
+    //**********************************************************************************************
+    // Expressions
+    //**********************************************************************************************
+
+    /// A block containing `seq`, whose closing brace is at `close`
+    fn sequence(&mut self, seq: &Sequence, close: usize) -> Doc {
+        let (uses, items, _, final_exp) = seq;
+        let mut body = vec![];
+        for u in uses {
+            self.use_line(&mut body, u);
+        }
+        for item in items {
+            self.start_line(&mut body, start(item.loc));
+            let doc = self.sequence_item(item);
+            body.push(doc);
+            body.push(text(";"));
+        }
+        if let Some(e) = &**final_exp {
+            self.start_line(&mut body, start(e.loc));
+            let doc = self.exp(e);
+            body.push(doc);
+        }
+        self.close_block(&mut body, close);
+        braces(4, body)
+    }
+
+    fn sequence_item(&mut self, item: &SequenceItem) -> Doc {
+        match &item.value {
+            SequenceItem_::Seq(e) => self.exp(e),
+            SequenceItem_::Declare(binds, ty_opt) => Doc::concat(vec![
+                text("let "),
+                self.bind_list(binds),
+                text(self.type_annotation(ty_opt)),
+            ]),
+            SequenceItem_::Bind(binds, ty_opt, e) => Doc::concat(vec![
+                text("let "),
+                self.bind_list(binds),
+                text(format!("{} = ", self.type_annotation(ty_opt))),
+                self.exp(e),
+            ]),
+        }
+    }
+
+    fn type_annotation(&self, ty_opt: &Option<Type>) -> String {
+        match ty_opt {
+            Some(ty) => format!(": {}", self.type_(ty)),
+            None => "".to_string(),
+        }
+    }
+
+    fn bind_list(&self, binds: &BindList) -> Doc {
+        if binds.value.len() == 1 {
+            return self.bind(&binds.value[0]);
+        }
+        let binds = binds.value.iter().map(|b| self.bind(b)).collect();
+        Doc::list("(", binds, ")", false)
+    }
+
+    fn bind(&self, bind: &Bind) -> Doc {
+        match &bind.value {
+            Bind_::Var(v) => text(v.to_string()),
+            Bind_::Unpack(chain, ty_args, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, bind)| match &bind.value {
+                        Bind_::Var(v) if v.value() == field.value() => text(v.to_string()),
+                        _ => Doc::concat(vec![text(format!("{}: ", field)), self.bind(bind)]),
+                    })
+                    .collect();
+                Doc::concat(vec![
+                    text(format!(
+                        "{}{} ",
+                        self.name_access_chain(chain),
+                        self.type_arguments(ty_args)
+                    )),
+                    Doc::list("{", fields, "}", true),
+                ])
+            }
+        }
+    }
+
+    fn exp(&mut self, e: &Exp) -> Doc {
+        self.exp_prec(e, EXP_PRECEDENCE)
+    }
+
+    /// Print `e`, in parentheses if it binds weaker than `min_precedence`
+    fn exp_prec(&mut self, e: &Exp, min_precedence: u32) -> Doc {
+        if precedence(&e.value) < min_precedence {
+            paren(self.exp_(e))
+        } else {
+            self.exp_(e)
+        }
+    }
+
+    fn exp_(&mut self, e: &Exp) -> Doc {
+        match &e.value {
+            Exp_::Value(v) => text(self.value(v)),
+            Exp_::Move(v) => text(format!("move {}", v)),
+            Exp_::Copy(v) => text(format!("copy {}", v)),
+            Exp_::Name(chain, ty_args) => text(format!(
+                "{}{}",
+                self.name_access_chain(chain),
+                self.type_arguments(ty_args)
+            )),
+            Exp_::Call(chain, ty_args, args) => {
+                let args = args.value.iter().map(|arg| self.exp(arg)).collect();
+                Doc::concat(vec![
+                    text(format!(
+                        "{}{}",
+                        self.name_access_chain(chain),
+                        self.type_arguments(ty_args)
+                    )),
+                    Doc::list("(", args, ")", false),
+                ])
+            }
+            Exp_::Pack(chain, ty_args, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, e)| match &e.value {
+                        Exp_::Name(sp!(_, NameAccessChain_::One(n)), None)
+                            if n.value == field.value() =>
+                        {
+                            text(n.value.clone())
+                        }
+                        _ => Doc::concat(vec![text(format!("{}: ", field)), self.exp(e)]),
+                    })
+                    .collect();
+                Doc::concat(vec![
+                    text(format!(
+                        "{}{} ",
+                        self.name_access_chain(chain),
+                        self.type_arguments(ty_args)
+                    )),
+                    Doc::list("{", fields, "}", true),
+                ])
+            }
+            Exp_::IfElse(cond, if_true, if_false) => {
+                // Unless a branch is a block, the branches move to their own lines if the `if`
+                // does not fit on one line
+                let is_block = |e: &Exp| matches!(e.value, Exp_::Block(_));
+                let has_block =
+                    is_block(if_true) || if_false.as_ref().map_or(false, |e| is_block(e));
+                let sep = if has_block { text(" ") } else { Doc::Line };
+                let branch = |doc: Doc| {
+                    if has_block {
+                        Doc::concat(vec![text(" "), doc])
+                    } else {
+                        Doc::nest(4, Doc::concat(vec![Doc::Line, doc]))
+                    }
+                };
+                let true_doc = if if_false.is_some() && ends_with_open_if(&if_true.value) {
+                    paren(self.exp(if_true))
+                } else {
+                    self.exp(if_true)
+                };
+                let mut doc = vec![text("if ("), self.exp(cond), text(")"), branch(true_doc)];
+                if let Some(if_false) = if_false {
+                    doc.extend(vec![sep, text("else")]);
+                    match &if_false.value {
+                        // `else if` chains stay at the same indentation
+                        Exp_::IfElse(..) => doc.extend(vec![text(" "), self.exp(if_false)]),
+                        _ => doc.push(branch(self.exp(if_false))),
+                    }
+                }
+                Doc::group(Doc::concat(doc))
+            }
+            Exp_::While(cond, body) => Doc::concat(vec![
+                text("while ("),
+                self.exp(cond),
+                text(") "),
+                self.exp(body),
+            ]),
+            Exp_::Loop(body) => Doc::concat(vec![text("loop "), self.exp(body)]),
+            Exp_::Block(seq) => self.sequence(seq, end(e.loc) - 1),
+            Exp_::Lambda(binds, body) => {
+                let binds = binds.value.iter().map(|b| self.bind(b)).collect::<Vec<_>>();
+                // like in `pipes`, `||` would be the boolean or
+                let params = if binds.is_empty() {
+                    text("| |")
+                } else {
+                    Doc::concat(vec![text("|"), Doc::join(binds, text(", ")), text("|")])
+                };
+                Doc::concat(vec![params, text(" "), self.exp(body)])
+            }
+            Exp_::Quant(kind, binds, triggers, cond, body) => {
+                self.quant(kind, binds, triggers, cond, body)
+            }
+            Exp_::ExpList(es) => {
+                let es = es.iter().map(|e| self.exp(e)).collect();
+                Doc::list("(", es, ")", false)
+            }
+            Exp_::Unit => text("()"),
+            Exp_::Assign(lhs, rhs) => Doc::concat(vec![
+                self.exp_prec(lhs, UNARY_PRECEDENCE),
+                text(" = "),
+                self.exp(rhs),
+            ]),
+            Exp_::Return(None) => text("return"),
+            Exp_::Return(Some(e)) => Doc::concat(vec![text("return "), self.exp(e)]),
+            Exp_::Abort(e) => Doc::concat(vec![text("abort "), self.exp(e)]),
+            Exp_::Break => text("break"),
+            Exp_::Continue => text("continue"),
+            Exp_::Dereference(e) => {
+                Doc::concat(vec![text("*"), self.exp_prec(e, UNARY_PRECEDENCE)])
+            }
+            Exp_::UnaryExp(op, e) => Doc::concat(vec![
+                text(op.value.symbol()),
+                self.exp_prec(e, UNARY_PRECEDENCE),
+            ]),
+            Exp_::BinopExp(..) => self.binop(e),
+            Exp_::Borrow(mut_, e) => {
+                let op = match (mut_, &e.value) {
+                    (true, _) => "&mut ",
+                    // `&&` would be read as a logical and
+                    (false, Exp_::Borrow(..)) => "& ",
+                    (false, _) => "&",
+                };
+                Doc::concat(vec![text(op), self.exp_prec(e, UNARY_PRECEDENCE)])
+            }
+            Exp_::Dot(e, n) => Doc::concat(vec![
+                self.exp_prec(e, TERM_PRECEDENCE),
+                text(format!(".{}", n)),
+            ]),
+            Exp_::Index(e, i) => Doc::concat(vec![
+                self.exp_prec(e, TERM_PRECEDENCE),
+                text("["),
+                self.exp(i),
+                text("]"),
+            ]),
+            Exp_::Cast(e, ty) => paren(Doc::concat(vec![
+                self.exp(e),
+                text(format!(" as {}", self.type_(ty))),
+            ])),
+            Exp_::Annotate(e, ty) => paren(Doc::concat(vec![
+                self.exp(e),
+                text(format!(": {}", self.type_(ty))),
+            ])),
+            Exp_::Spec(block) => self.spec_block(block),
+            Exp_::UnresolvedError => unreachable!("the parser does not produce error expressions"),
+        }
+    }
+
+    /// A chain of binary operators of the same precedence, which is broken before the operators
+    /// if it does not fit on one line
+    fn binop(&mut self, e: &Exp) -> Doc {
+        let op_precedence = match &e.value {
+            Exp_::BinopExp(_, op, _) => binop_precedence(op.value),
+            _ => unreachable!(),
+        };
+        // the parser associates operators of the same precedence to the left
+        let mut rhs = vec![];
+        let mut lhs = e;
+        while let Exp_::BinopExp(l, op, r) = &lhs.value {
+            if binop_precedence(op.value) != op_precedence {
+                break;
+            }
+            rhs.push((op.value, r));
+            lhs = &**l;
+        }
+        let mut doc = vec![self.exp_prec(lhs, op_precedence)];
+        let mut rest = vec![];
+        for (op, r) in rhs.into_iter().rev() {
+            let r = self.exp_prec(r, op_precedence + 1);
+            if op == BinOp_::Range {
+                doc.push(text(op.symbol()));
+                doc.push(r);
+            } else {
+                rest.push(Doc::Line);
+                rest.push(text(format!("{} ", op.symbol())));
+                rest.push(r);
+            }
+        }
+        doc.push(Doc::nest(4, Doc::concat(rest)));
+        Doc::group(Doc::concat(doc))
+    }
+
+    fn quant(
+        &mut self,
+        kind: &QuantKind,
+        binds: &BindWithRangeList,
+        triggers: &[Vec<Exp>],
+        cond: &Option<Box<Exp>>,
+        body: &Exp,
+    ) -> Doc {
+        let keyword = match kind.value {
+            QuantKind_::Forall => "forall",
+            QuantKind_::Exists => "exists",
+            QuantKind_::Choose => "choose",
+            QuantKind_::ChooseMin => "choose min",
+        };
+        let mut doc = vec![text(format!("{} ", keyword))];
+        let mut binds_doc = vec![];
+        for sp!(_, (bind, range)) in &binds.value {
+            let bind = self.bind(bind);
+            binds_doc.push(match &range.value {
+                // a quantifier over all values of a type, `x: T`
+                Exp_::Call(sp!(_, NameAccessChain_::One(n)), Some(tys), args)
+                    if n.value == "$spec_domain" && tys.len() == 1 && args.value.is_empty() =>
+                {
+                    Doc::concat(vec![bind, text(format!(": {}", self.type_(&tys[0])))])
+                }
+                _ => Doc::concat(vec![bind, text(" in "), self.exp(range)]),
+            });
+        }
+        doc.push(Doc::join(binds_doc, text(", ")));
+        if matches!(kind.value, QuantKind_::Choose | QuantKind_::ChooseMin) {
+            doc.push(self.where_clause(body));
+            return Doc::concat(doc);
+        }
+        for trigger in triggers {
+            let trigger = trigger.iter().map(|e| self.exp(e)).collect();
+            doc.push(text(" "));
+            doc.push(Doc::list("{", trigger, "}", false));
+        }
+        if let Some(cond) = cond {
+            doc.push(self.where_clause(cond));
+        }
+        doc.push(text(":"));
+        doc.push(Doc::nest(
+            4,
+            Doc::group(Doc::concat(vec![Doc::Line, self.exp(body)])),
+        ));
+        Doc::concat(doc)
+    }
+
+    /// The condition of a quantifier, moved to the next line if it does not fit
+    fn where_clause(&mut self, cond: &Exp) -> Doc {
+        Doc::group(Doc::nest(
+            4,
+            Doc::concat(vec![Doc::Line, text("where "), self.exp(cond)]),
+        ))
+    }
+
+    //**********************************************************************************************
+    // Names, types, and values
+    //**********************************************************************************************
+
+    /// The source text at `loc`, used for literals whose spelling is not kept in the AST
+    fn source_text(&self, loc: Loc) -> &str {
+        &self.source[start(loc)..end(loc)]
+    }
+
+    fn leading_name_access(&self, access: &LeadingNameAccess) -> String {
+        match &access.value {
+            LeadingNameAccess_::Name(n) => n.value.clone(),
+            LeadingNameAccess_::AnonymousAddress(_) => self.source_text(access.loc).to_string(),
+        }
+    }
+
+    fn module_ident(&self, ident: &ModuleIdent) -> String {
+        format!(
+            "{}::{}",
+            self.leading_name_access(&ident.value.address),
+            ident.value.module
+        )
+    }
+
+    fn name_access_chain(&self, chain: &NameAccessChain) -> String {
+        match &chain.value {
+            NameAccessChain_::One(n) => n.value.clone(),
+            NameAccessChain_::Two(ln, n) => format!("{}::{}", self.leading_name_access(ln), n),
+            NameAccessChain_::Three(sp!(_, (ln, n2)), n3) => {
+                format!("{}::{}::{}", self.leading_name_access(ln), n2, n3)
+            }
+        }
+    }
+
+    fn value(&self, v: &Value) -> String {
+        match &v.value {
+            Value_::Address(addr) => format!("@{}", self.leading_name_access(addr)),
+            Value_::Num(s) => s.clone(),
+            Value_::Bool(b) => b.to_string(),
+            Value_::HexString(s) => format!("x\"{}\"", s),
+            Value_::ByteString(s) => format!("b\"{}\"", s),
+        }
+    }
+
+    fn type_(&self, ty: &Type) -> String {
+        match &ty.value {
+            Type_::Apply(chain, tys) => {
+                if tys.is_empty() {
+                    self.name_access_chain(chain)
+                } else {
+                    format!("{}<{}>", self.name_access_chain(chain), self.types(tys))
+                }
+            }
+            Type_::Ref(mut_, inner) => {
+                let op = match (mut_, &inner.value) {
+                    (true, _) => "&mut ",
+                    (false, Type_::Ref(..)) => "& ",
+                    (false, _) => "&",
+                };
+                format!("{}{}", op, self.type_(inner))
+            }
+            Type_::Fun(args, result) => {
+                format!("{} {}", pipes(self.types(args)), self.type_(result))
+            }
+            Type_::Unit => "()".to_string(),
+            Type_::Multiple(tys) => format!("({})", self.types(tys)),
+        }
+    }
+
+    fn types(&self, tys: &[Type]) -> String {
+        tys.iter()
+            .map(|ty| self.type_(ty))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn type_arguments(&self, ty_args: &Option<Vec<Type>>) -> String {
+        match ty_args {
+            Some(tys) => format!("<{}>", self.types(tys)),
+            None => "".to_string(),
+        }
+    }
+
+    fn type_parameters(&self, type_parameters: &[(Name, Vec<Ability>)]) -> String {
+        if type_parameters.is_empty() {
+            return "".to_string();
+        }
+        let type_parameters = type_parameters
+            .iter()
+            .map(|(name, abilities)| {
+                if abilities.is_empty() {
+                    name.value.clone()
+                } else {
+                    let abilities = abilities
+                        .iter()
+                        .map(|a| a.value.to_string())
+                        .collect::<Vec<_>>();
+                    format!("{}: {}", name, abilities.join(" + "))
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("<{}>", type_parameters.join(", "))
+    }
+}
+
+fn condition_keyword(kind: &SpecConditionKind) -> &'static str {
+    use SpecConditionKind as K;
+    match kind {
+        K::Assert => "assert",
+        K::Assume => "assume",
+        K::Decreases => "decreases",
+        K::AbortsIf => "aborts_if",
+        K::AbortsWith => "aborts_with",
+        K::SucceedsIf => "succeeds_if",
+        K::Modifies => "modifies",
+        K::Emits => "emits",
+        K::Ensures => "ensures",
+        K::Requires => "requires",
+        K::Invariant => "invariant",
+        K::InvariantUpdate => "invariant update",
+        K::Axiom => "axiom",
+    }
+}
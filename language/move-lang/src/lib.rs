@@ -11,6 +11,7 @@ pub mod command_line;
 pub mod compiled_unit;
 pub mod errors;
 pub mod expansion;
+pub mod formatter;
 pub mod hlir;
 pub mod interface_generator;
 pub mod ir_translation;
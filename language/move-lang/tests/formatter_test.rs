@@ -0,0 +1,87 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_lang::{
+    formatter::format_string,
+    move_parse,
+    shared::{CompilationEnv, Flags},
+};
+use regex::Regex;
+use std::fs;
+
+/// Parse `file` and render its definitions and doc comments, without locations. The formatter
+/// drops trailing whitespace, so that is not part of the doc comments either.
+fn parse_without_locations(file: &str) -> String {
+    let env = CompilationEnv::new(Flags::empty());
+    let (_, res) = move_parse(&env, &[file.to_string()], &[], None).unwrap();
+    let (comments, prog) = res.unwrap_or_else(|_| panic!("Unable to parse {}", file));
+    let doc_comments = comments
+        .values()
+        .flat_map(|m| m.values())
+        .map(|c| c.lines().map(str::trim_end).collect::<Vec<_>>().join("\n"))
+        .collect::<Vec<_>>();
+    let rendered = format!("{:?}\n{:?}", prog.source_definitions, doc_comments);
+    let loc = Regex::new(r#"Loc \{ file: "[^"]*", span: (Span \{[^}]*\}|[^}]*) \}"#).unwrap();
+    loc.replace_all(&rendered, "Loc").to_string()
+}
+
+fn count_comment_delimiters(s: &str) -> (usize, usize) {
+    (s.matches("//").count(), s.matches("/*").count())
+}
+
+/// Format every file in `files`, and check that the result parses to the same program, keeps all
+/// of the comments, and is left unchanged by formatting it again
+fn check_formatting(files: Vec<String>) {
+    let dir = tempfile::tempdir().unwrap();
+    for (idx, file) in files.iter().enumerate() {
+        let fname: &'static str = Box::leak(Box::new(file.clone()));
+        let source = fs::read_to_string(file).unwrap();
+        let formatted =
+            format_string(fname, &source).unwrap_or_else(|_| panic!("Unable to format {}", file));
+
+        let formatted_file = dir.path().join(format!("{}.move", idx));
+        fs::write(&formatted_file, &formatted).unwrap();
+        let formatted_fname = formatted_file.to_string_lossy().to_string();
+        assert_eq!(
+            parse_without_locations(file),
+            parse_without_locations(&formatted_fname),
+            "Formatting changes the program in {}",
+            file
+        );
+        assert_eq!(
+            count_comment_delimiters(&source),
+            count_comment_delimiters(&formatted),
+            "Formatting changes the comments in {}",
+            file
+        );
+
+        let fname: &'static str = Box::leak(Box::new(formatted_fname));
+        assert_eq!(
+            format_string(fname, &formatted).unwrap(),
+            formatted,
+            "Formatting is not idempotent for {}",
+            file
+        );
+    }
+}
+
+#[test]
+fn test_format_stdlib() {
+    let mut files = diem_framework::diem_stdlib_files();
+    files.extend(move_stdlib::move_nursery_files());
+    check_formatting(files)
+}
+
+#[test]
+fn test_format_move_check_testsuite() {
+    // only the tests that parse can be formatted
+    let files = move_lang::find_move_filenames(&["tests/move_check".to_string()], false).unwrap();
+    let parsing = files
+        .into_iter()
+        .filter(|file| {
+            let env = CompilationEnv::new(Flags::empty());
+            matches!(move_parse(&env, &[file.clone()], &[], None), Ok((_, Ok(_))))
+        })
+        .collect();
+    check_formatting(parsing)
+}
@@ -826,6 +826,16 @@ impl GlobalEnv {
         self.diag_count(Severity::Error)
     }
 
+    /// Returns the diagnostics of given or higher severity, whether reported or not.
+    pub fn get_diags(&self, min_severity: Severity) -> Vec<Diagnostic> {
+        self.diags
+            .borrow()
+            .iter()
+            .filter(|(d, _)| d.severity >= min_severity)
+            .map(|(d, _)| d.clone())
+            .collect()
+    }
+
     /// Returns true if diagnostics have warning severity or worse.
     pub fn has_warnings(&self) -> bool {
         self.diags
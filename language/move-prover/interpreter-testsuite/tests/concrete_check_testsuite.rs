@@ -23,6 +23,9 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
         report_storage_on_error: false,
         report_statistics: false,
         list: false,
+        debug: false,
+        debug_adapter: None,
+        profile_gas: None,
         verbose: read_bool_env_var("VERBOSE"),
     };
 
@@ -0,0 +1,112 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_schedule::{GasAlgebra, GasCost, GasUnits},
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::MoveValue,
+};
+use move_vm_runtime::{gas_profiler, logging::NoContextLog, move_vm::MoveVM};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::{zero_cost_schedule, GasStatus};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([43; AccountAddress::LENGTH]);
+
+#[test]
+fn profile_calls_and_natives() {
+    let code = format!(
+        r#"
+        module 0x1::Vector {{
+            native public fun empty<Element>(): vector<Element>;
+            native public fun length<Element>(v: &vector<Element>): u64;
+            native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+        }}
+
+        module 0x{}::Profiled {{
+            use 0x1::Vector;
+
+            fun square(x: u64): u64 {{
+                x * x
+            }}
+
+            public fun squares(n: u64): u64 {{
+                let v = Vector::empty();
+                let i = 0;
+                while (i < n) {{
+                    Vector::push_back(&mut v, square(i));
+                    i = i + 1;
+                }};
+                Vector::length(&v)
+            }}
+        }}
+    "#,
+        TEST_ADDR
+    );
+
+    let mut storage = InMemoryStorage::new();
+    for unit in compile_units(&code).unwrap() {
+        let m = as_module(unit);
+        let mut blob = vec![];
+        m.serialize(&mut blob).unwrap();
+        storage.publish_or_overwrite_module(m.self_id(), blob);
+    }
+
+    let mut cost_table = zero_cost_schedule();
+    for cost in cost_table
+        .instruction_table
+        .iter_mut()
+        .chain(cost_table.native_table.iter_mut())
+    {
+        *cost = GasCost::new(1, 1);
+    }
+    let mut gas_status = GasStatus::new(&cost_table, GasUnits::new(1_000_000));
+    let gas_before = gas_status.remaining_internal_gas().get();
+
+    let vm = MoveVM::new();
+    let mut sess = vm.new_session(&storage);
+    gas_profiler::start();
+    sess.execute_function(
+        &ModuleId::new(TEST_ADDR, Identifier::new("Profiled").unwrap()),
+        &Identifier::new("squares").unwrap(),
+        vec![],
+        vec![MoveValue::U64(3).simple_serialize().unwrap()],
+        &mut gas_status,
+        &NoContextLog::new(),
+    )
+    .unwrap();
+    let profile = gas_profiler::finish();
+    let gas_used = gas_before - gas_status.remaining_internal_gas().get();
+
+    // Other tests may run VMs while the profiler records, so only look at this test's functions
+    let squares = format!("0x{}::Profiled::squares", TEST_ADDR.short_str_lossless());
+    let square = format!("0x{}::Profiled::square", TEST_ADDR.short_str_lossless());
+    let functions = profile.functions();
+    let function = |name: &str| {
+        functions
+            .iter()
+            .find(|function| function.name == name)
+            .unwrap_or_else(|| panic!("no gas recorded for {}", name))
+    };
+
+    assert!(gas_used > 0);
+    assert_eq!(function(&squares).calls, 1);
+    assert_eq!(function(&squares).total_gas, gas_used);
+    assert_eq!(function(&square).calls, 3);
+    assert_eq!(function(&square).self_gas, function(&square).total_gas);
+    assert_eq!(function("0x1::Vector::push_back").calls, 3);
+    assert!(function("0x1::Vector::push_back").self_gas > 0);
+    assert_eq!(function("0x1::Vector::length").calls, 1);
+    assert!(profile
+        .instructions()
+        .iter()
+        .any(|instruction| instruction.function == square && instruction.executions == 3));
+
+    let mut folded = vec![];
+    profile.write_folded(&mut folded).unwrap();
+    let folded = String::from_utf8(folded).unwrap();
+    assert!(folded.contains(&format!("\n{};{} ", squares, square)));
+    assert!(folded.contains(&format!("\n{};0x1::Vector::push_back ", squares)));
+}
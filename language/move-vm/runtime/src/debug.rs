@@ -1,195 +1,277 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+//! Hooks for debuggers of Move programs.
+//!
+//! A [`Debugger`] attached with [`attach`] is called by the interpreter before every instruction
+//! it executes. It sees the state of the execution through an [`ExecutionState`]: the call stack,
+//! the locals of every frame, and global storage as the executing session sees it.
+//!
+//! The interpreter only calls debuggers in debug builds, or when this crate is built with the
+//! `debugging` feature. Otherwise attaching a debugger has no effect.
+
+use move_binary_format::{
+    errors::VMResult,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
 use crate::{
-    interpreter::Interpreter,
     loader::{Function, Loader},
     logging::LogContext,
 };
-use move_binary_format::file_format::Bytecode;
-use move_vm_types::values::{self, Locals};
-use std::{
-    collections::BTreeSet,
-    io::{self, Write},
-    str::FromStr,
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use move_binary_format::errors::{Location, PartialVMError, PartialVMResult};
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use move_core_types::vm_status::StatusCode;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use move_vm_types::{
+    data_store::DataStore,
+    loaded_data::runtime_types::Type,
+    values::{Locals, Reference, Value},
 };
 
-#[derive(Debug)]
-enum DebugCommand {
-    PrintStack,
-    Step,
-    Continue,
-    Breakpoint(String),
-    DeleteBreakpoint(String),
-    PrintBreakpoints,
+/// A debugger, called before the interpreter executes an instruction
+pub trait Debugger: Send {
+    /// Called before the instruction at the top of the call stack of `state` executes. The
+    /// instruction executes once this returns.
+    fn on_instruction(&mut self, state: &mut dyn ExecutionState);
 }
 
-impl DebugCommand {
-    pub fn debug_string(&self) -> &str {
-        match self {
-            Self::PrintStack => "stack",
-            Self::Step => "step",
-            Self::Continue => "continue",
-            Self::Breakpoint(_) => "breakpoint ",
-            Self::DeleteBreakpoint(_) => "delete ",
-            Self::PrintBreakpoints => "breakpoints",
-        }
-    }
+/// The state of an execution, stopped before an instruction
+pub trait ExecutionState {
+    /// The call stack, outermost frame first. The last frame is the one about to execute.
+    fn frames(&self) -> &[DebugFrame];
+
+    /// The values of the parameters and locals of the frame at `frame` in the call stack, in the
+    /// order of their indexes. Locals that hold no value are `None`.
+    fn locals(&self, frame: usize) -> VMResult<Vec<Option<DebugValue>>>;
+
+    /// The resource of type `tag` stored under `address`, if any
+    fn global(&mut self, address: AccountAddress, tag: &StructTag) -> VMResult<Option<DebugValue>>;
+}
+
+/// A frame of the call stack
+#[derive(Clone, Debug)]
+pub struct DebugFrame {
+    /// The module of the function, or `None` for a script
+    pub module_id: Option<ModuleId>,
+    pub function_name: Identifier,
+    pub function_index: FunctionDefinitionIndex,
+    pub type_arguments: Vec<TypeTag>,
+    /// The offset of the next instruction of the frame. For the callers of the frame about to
+    /// execute, this is the call they are in.
+    pub pc: CodeOffset,
+    pub instruction: Bytecode,
+}
+
+/// A value, copied out of the execution
+#[derive(Clone, Debug)]
+pub struct DebugValue {
+    /// The type of the value, or of the value it refers to for a reference
+    pub type_tag: TypeTag,
+    /// The kind of the reference if the value is a reference
+    pub reference: Option<ReferenceKind>,
+    /// The BCS encoding of the value, or of the value it refers to for a reference
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Immutable,
+    Mutable,
+}
+
+static ATTACHED: AtomicBool = AtomicBool::new(false);
 
-    pub fn commands() -> Vec<DebugCommand> {
-        vec![
-            Self::PrintStack,
-            Self::Step,
-            Self::Continue,
-            Self::Breakpoint("".to_string()),
-            Self::DeleteBreakpoint("".to_string()),
-            Self::PrintBreakpoints,
-        ]
+static DEBUGGER: Lazy<Mutex<Option<Box<dyn Debugger>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Attach `debugger` to the interpreters of this process, in place of the one attached before,
+/// which is returned
+pub fn attach(debugger: Box<dyn Debugger>) -> Option<Box<dyn Debugger>> {
+    let mut attached = DEBUGGER.lock().unwrap();
+    ATTACHED.store(true, Ordering::SeqCst);
+    attached.replace(debugger)
+}
+
+/// Detach the attached debugger, if any, and return it
+pub fn detach() -> Option<Box<dyn Debugger>> {
+    let mut attached = DEBUGGER.lock().unwrap();
+    ATTACHED.store(false, Ordering::SeqCst);
+    attached.take()
+}
+
+/// A frame of the call stack, as the interpreter keeps it
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub(crate) struct FrameRef<'a> {
+    pub(crate) function: &'a Function,
+    pub(crate) pc: u16,
+    pub(crate) locals: &'a Locals,
+    pub(crate) ty_args: &'a [Type],
+}
+
+/// Call the attached debugger, if any, before the last frame of `frames` executes
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub(crate) fn on_instruction<'a, D: DataStore, L: LogContext>(
+    frames: impl FnOnce() -> Vec<FrameRef<'a>>,
+    loader: &'a Loader,
+    data_store: &'a mut D,
+    log_context: &'a L,
+) {
+    if !ATTACHED.load(Ordering::Relaxed) {
+        return;
     }
+    let mut attached = DEBUGGER.lock().unwrap();
+    let debugger = match attached.as_mut() {
+        Some(debugger) => debugger,
+        None => return,
+    };
+    let frame_refs = frames();
+    let frames = match frame_refs
+        .iter()
+        .map(|frame| debug_frame(loader, frame))
+        .collect::<PartialVMResult<Vec<_>>>()
+    {
+        Ok(frames) => frames,
+        // Not a state a debugger can show, which only happens if the VM has a bug
+        Err(_) => return,
+    };
+    let mut state = InterpreterState {
+        frames,
+        frame_refs,
+        loader,
+        data_store,
+        log_context,
+    };
+    debugger.on_instruction(&mut state);
+}
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+fn debug_frame(loader: &Loader, frame: &FrameRef) -> PartialVMResult<DebugFrame> {
+    let function = frame.function;
+    Ok(DebugFrame {
+        module_id: function.module_id().cloned(),
+        function_name: Identifier::new(function.name())
+            .map_err(|_| PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))?,
+        function_index: function.index(),
+        type_arguments: frame
+            .ty_args
+            .iter()
+            .map(|ty| loader.type_to_type_tag(ty))
+            .collect::<PartialVMResult<_>>()?,
+        pc: frame.pc,
+        instruction: function.code()[frame.pc as usize].clone(),
+    })
+}
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+struct InterpreterState<'a, D, L> {
+    frames: Vec<DebugFrame>,
+    frame_refs: Vec<FrameRef<'a>>,
+    loader: &'a Loader,
+    data_store: &'a mut D,
+    log_context: &'a L,
 }
 
-impl FromStr for DebugCommand {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use DebugCommand::*;
-        let s = s.trim();
-        if s.starts_with(PrintStack.debug_string()) {
-            return Ok(PrintStack);
-        }
-        if s.starts_with(Step.debug_string()) {
-            return Ok(Step);
-        }
-        if s.starts_with(Continue.debug_string()) {
-            return Ok(Continue);
-        }
-        if let Some(breakpoint) = s.strip_prefix(Breakpoint("".to_owned()).debug_string()) {
-            return Ok(Breakpoint(breakpoint.to_owned()));
-        }
-        if let Some(breakpoint) = s.strip_prefix(DeleteBreakpoint("".to_owned()).debug_string()) {
-            return Ok(DeleteBreakpoint(breakpoint.to_owned()));
-        }
-        if s.starts_with(PrintBreakpoints.debug_string()) {
-            return Ok(PrintBreakpoints);
-        }
-        Err(format!(
-            "Unrecognized command: {}\nAvailable commands: {}",
-            s,
-            Self::commands()
-                .iter()
-                .map(|command| command.debug_string())
-                .collect::<Vec<_>>()
-                .join(", ")
-        ))
+#[cfg(any(debug_assertions, feature = "debugging"))]
+impl<'a, D: DataStore, L: LogContext> InterpreterState<'a, D, L> {
+    fn local(&self, ty: &Type, locals: &Locals, idx: usize) -> PartialVMResult<Option<DebugValue>> {
+        let value = match locals.copy_loc(idx) {
+            Ok(value) => value,
+            // The local was never assigned, or was moved out of
+            Err(_) => return Ok(None),
+        };
+        let (ty, reference, value) = match ty {
+            Type::Reference(ty) => (&**ty, Some(ReferenceKind::Immutable), read_ref(value)?),
+            Type::MutableReference(ty) => (&**ty, Some(ReferenceKind::Mutable), read_ref(value)?),
+            _ => (ty, None, value),
+        };
+        Ok(Some(self.debug_value(ty, reference, &value)?))
+    }
+
+    fn debug_value(
+        &self,
+        ty: &Type,
+        reference: Option<ReferenceKind>,
+        value: &Value,
+    ) -> PartialVMResult<DebugValue> {
+        let layout = self.loader.type_to_type_layout(ty)?;
+        let bytes = value.simple_serialize(&layout).ok_or_else(|| {
+            PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR)
+                .with_message(format!("cannot serialize a value of type {:?}", ty))
+        })?;
+        Ok(DebugValue {
+            type_tag: self.loader.type_to_type_tag(ty)?,
+            reference,
+            bytes,
+        })
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct DebugContext {
-    breakpoints: BTreeSet<String>,
-    should_take_input: bool,
+#[cfg(any(debug_assertions, feature = "debugging"))]
+fn read_ref(value: Value) -> PartialVMResult<Value> {
+    value.value_as::<Reference>()?.read_ref()
 }
 
-impl DebugContext {
-    pub(crate) fn new() -> Self {
-        Self {
-            breakpoints: BTreeSet::new(),
-            should_take_input: true,
-        }
+#[cfg(any(debug_assertions, feature = "debugging"))]
+impl<'a, D: DataStore, L: LogContext> ExecutionState for InterpreterState<'a, D, L> {
+    fn frames(&self) -> &[DebugFrame] {
+        &self.frames
     }
 
-    pub(crate) fn debug_loop(
-        &mut self,
-        function_desc: &Function,
-        locals: &Locals,
-        pc: u16,
-        instr: &Bytecode,
-        resolver: &Loader,
-        interp: &Interpreter<impl LogContext>,
-    ) {
-        let instr_string = format!("{:?}", instr);
-        let function_string = function_desc.pretty_string();
-        let breakpoint_hit = self.breakpoints.contains(&function_string)
-            || self
-                .breakpoints
-                .iter()
-                .any(|bp| instr_string[..].starts_with(bp.as_str()));
-
-        if self.should_take_input || breakpoint_hit {
-            self.should_take_input = true;
-            if breakpoint_hit {
-                let bp_match = self
-                    .breakpoints
-                    .iter()
-                    .find(|bp| instr_string.starts_with(bp.as_str()))
-                    .unwrap()
-                    .clone();
-                println!(
-                    "Breakpoint {} hit with instruction {}",
-                    bp_match, instr_string
-                );
-            }
-            println!(
-                "function >> {}\ninstruction >> {:?}\nprogram counter >> {}",
-                function_string, instr, pc
-            );
-            loop {
-                print!("> ");
-                std::io::stdout().flush().unwrap();
-                let mut input = String::new();
-                match io::stdin().read_line(&mut input) {
-                    Ok(_) => match input.parse::<DebugCommand>() {
-                        Err(err) => println!("{}", err),
-                        Ok(command) => match command {
-                            DebugCommand::Step => {
-                                self.should_take_input = true;
-                                break;
-                            }
-                            DebugCommand::Continue => {
-                                self.should_take_input = false;
-                                break;
-                            }
-                            DebugCommand::Breakpoint(breakpoint) => {
-                                self.breakpoints.insert(breakpoint.to_string());
-                            }
-                            DebugCommand::DeleteBreakpoint(breakpoint) => {
-                                self.breakpoints.remove(&breakpoint);
-                            }
-                            DebugCommand::PrintBreakpoints => self
-                                .breakpoints
-                                .iter()
-                                .enumerate()
-                                .for_each(|(i, bp)| println!("[{}] {}", i, bp)),
-                            DebugCommand::PrintStack => {
-                                let mut s = String::new();
-                                interp.debug_print_stack_trace(&mut s, resolver).unwrap();
-                                println!("{}", s);
-                                println!("Current frame: {}\n", function_string);
-                                let code = function_desc.code();
-                                println!("        Code:");
-                                for (i, instr) in code.iter().enumerate() {
-                                    if i as u16 == pc {
-                                        println!("          > [{}] {:?}", pc, instr);
-                                    } else {
-                                        println!("            [{}] {:?}", i, instr);
-                                    }
-                                }
-                                println!("        Locals:");
-                                if function_desc.local_count() > 0 {
-                                    let mut s = String::new();
-                                    values::debug::print_locals(&mut s, locals).unwrap();
-                                    println!("{}", s);
-                                } else {
-                                    println!("            (none)");
-                                }
-                            }
-                        },
-                    },
-                    Err(err) => {
-                        println!("Error reading input: {}", err);
-                        break;
-                    }
-                }
+    fn locals(&self, frame: usize) -> VMResult<Vec<Option<DebugValue>>> {
+        let frame = self.frame_refs.get(frame).ok_or_else(|| {
+            PartialVMError::new(StatusCode::INDEX_OUT_OF_BOUNDS).finish(Location::Undefined)
+        })?;
+        let location = match frame.function.module_id() {
+            Some(module_id) => Location::Module(module_id.clone()),
+            None => Location::Script,
+        };
+        let types = frame
+            .function
+            .get_resolver(self.loader)
+            .local_types(frame.function, frame.ty_args)
+            .map_err(|e| e.finish(location.clone()))?;
+        types
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| {
+                self.local(ty, frame.locals, idx)
+                    .map_err(|e| e.finish(location.clone()))
+            })
+            .collect()
+    }
+
+    fn global(&mut self, address: AccountAddress, tag: &StructTag) -> VMResult<Option<DebugValue>> {
+        let type_tag = TypeTag::Struct(tag.clone());
+        let ty = self
+            .loader
+            .load_type(&type_tag, self.data_store, self.log_context)?;
+        let value = {
+            let global = self
+                .data_store
+                .load_resource(address, &ty)
+                .map_err(|e| e.finish(Location::Undefined))?;
+            if !global.exists().map_err(|e| e.finish(Location::Undefined))? {
+                return Ok(None);
             }
-        }
+            global
+                .borrow_global()
+                .and_then(read_ref)
+                .map_err(|e| e.finish(Location::Undefined))?
+        };
+        self.debug_value(&ty, None, &value)
+            .map(Some)
+            .map_err(|e| e.finish(Location::Undefined))
     }
 }
@@ -0,0 +1,371 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A gas profiler for Move programs.
+//!
+//! Between [`start`] and [`finish`], the interpreter reports the gas every instruction it executes
+//! charges, under the call stack it executes on. The gas a native function charges goes to a frame
+//! of its own, on top of its caller. Clients of the VM report the gas they charge outside of Move
+//! code with [`record_charge`], as for the size of a transaction or the storage it writes.
+//!
+//! Gas is measured in the internal units of the gas schedule, so executions without gas metering
+//! record no gas. The interpreter only reports instructions in debug builds, or when this crate is
+//! built with the `debugging` feature.
+
+use move_binary_format::file_format::CodeOffset;
+use move_core_types::gas_schedule::GasAlgebra;
+use move_vm_types::gas_schedule::GasStatus;
+use once_cell::sync::Lazy;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Write},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use crate::loader::Function;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use move_binary_format::file_format::{Bytecode, FunctionDefinitionIndex};
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use move_core_types::language_storage::ModuleId;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use std::cell::RefCell;
+
+/// The number of instructions the summary of a profile shows
+const SUMMARY_INSTRUCTIONS: usize = 20;
+
+/// The gas an execution used, by call stack, function, and instruction
+#[derive(Clone, Debug, Default)]
+pub struct GasProfile {
+    /// Gas by call stack, as the names of its functions, outermost first
+    stacks: BTreeMap<Vec<String>, u64>,
+    /// The number of calls of each function
+    calls: BTreeMap<String, u64>,
+    /// The instruction, number of executions, and gas of each code offset of each function
+    instructions: BTreeMap<String, BTreeMap<CodeOffset, (String, u64, u64)>>,
+}
+
+/// The gas a function used
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionGas {
+    pub name: String,
+    pub calls: u64,
+    /// The gas of the instructions of the function, or of the function itself if it is native
+    pub self_gas: u64,
+    /// The gas of the function and of everything it calls
+    pub total_gas: u64,
+}
+
+/// The gas an instruction used
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstructionGas {
+    pub function: String,
+    pub offset: CodeOffset,
+    pub instruction: String,
+    pub executions: u64,
+    pub gas: u64,
+}
+
+impl GasProfile {
+    pub fn total_gas(&self) -> u64 {
+        self.stacks.values().sum()
+    }
+
+    /// The functions that used gas, the most expensive first
+    pub fn functions(&self) -> Vec<FunctionGas> {
+        let mut functions: BTreeMap<&str, FunctionGas> = BTreeMap::new();
+        for (stack, gas) in &self.stacks {
+            for (depth, name) in stack.iter().enumerate() {
+                let function = functions
+                    .entry(name.as_str())
+                    .or_insert_with(|| FunctionGas {
+                        name: name.clone(),
+                        calls: self.calls.get(name).copied().unwrap_or(0),
+                        self_gas: 0,
+                        total_gas: 0,
+                    });
+                // A recursive function gets the gas of a stack only once
+                if !stack[..depth].contains(name) {
+                    function.total_gas += gas;
+                }
+                if depth + 1 == stack.len() {
+                    function.self_gas += gas;
+                }
+            }
+        }
+        let mut functions: Vec<_> = functions.values().cloned().collect();
+        functions.sort_by(|f1, f2| f2.total_gas.cmp(&f1.total_gas));
+        functions
+    }
+
+    /// The instructions that executed, the most expensive first
+    pub fn instructions(&self) -> Vec<InstructionGas> {
+        let mut instructions: Vec<_> = self
+            .instructions
+            .iter()
+            .flat_map(|(function, offsets)| {
+                offsets.iter().map(
+                    move |(offset, (instruction, executions, gas))| InstructionGas {
+                        function: function.clone(),
+                        offset: *offset,
+                        instruction: instruction.clone(),
+                        executions: *executions,
+                        gas: *gas,
+                    },
+                )
+            })
+            .collect();
+        instructions.sort_by(|i1, i2| i2.gas.cmp(&i1.gas));
+        instructions
+    }
+
+    /// Write the gas of every call stack in the folded format of flame graph tools, as in
+    /// `0x1::M::f;0x1::M::g 42`
+    pub fn write_folded<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for (stack, gas) in &self.stacks {
+            if *gas > 0 {
+                writeln!(w, "{} {}", stack.join(";"), gas)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_stack(&mut self, stack: &[String], gas: u64) {
+        match self.stacks.get_mut(stack) {
+            Some(total) => *total += gas,
+            None => {
+                self.stacks.insert(stack.to_vec(), gas);
+            }
+        }
+    }
+
+    fn add_call(&mut self, name: &str) {
+        match self.calls.get_mut(name) {
+            Some(calls) => *calls += 1,
+            None => {
+                self.calls.insert(name.to_string(), 1);
+            }
+        }
+    }
+}
+
+/// A summary of the profile: the gas of every function, and of the most expensive instructions
+impl fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Total gas: {}", self.total_gas())?;
+        writeln!(f)?;
+        writeln!(f, "{:>12} {:>12} {:>8}  Function", "Total", "Self", "Calls")?;
+        for function in self.functions() {
+            writeln!(
+                f,
+                "{:>12} {:>12} {:>8}  {}",
+                function.total_gas, function.self_gas, function.calls, function.name
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:>12} {:>10}  Instruction", "Gas", "Executions")?;
+        for instruction in self.instructions().iter().take(SUMMARY_INSTRUCTIONS) {
+            writeln!(
+                f,
+                "{:>12} {:>10}  {}+{}: {}",
+                instruction.gas,
+                instruction.executions,
+                instruction.function,
+                instruction.offset,
+                instruction.instruction
+            )?;
+        }
+        Ok(())
+    }
+}
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+static PROFILE: Lazy<Mutex<GasProfile>> = Lazy::new(|| Mutex::new(GasProfile::default()));
+
+/// Start recording the gas that executions in this process use, in a new profile
+pub fn start() {
+    *PROFILE.lock().unwrap() = GasProfile::default();
+    RECORDING.store(true, Ordering::SeqCst);
+}
+
+/// Stop recording, and return what was recorded since `start`
+pub fn finish() -> GasProfile {
+    RECORDING.store(false, Ordering::SeqCst);
+    mem::take(&mut *PROFILE.lock().unwrap())
+}
+
+/// Run `charge`, which charges `gas_status` for something other than Move code, and record the
+/// gas it charged under a frame of its own called `name`
+pub fn record_charge<T>(
+    name: &str,
+    gas_status: &mut GasStatus,
+    charge: impl FnOnce(&mut GasStatus) -> T,
+) -> T {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return charge(gas_status);
+    }
+    let gas_left = gas_status.remaining_internal_gas().get();
+    let result = charge(gas_status);
+    let gas = gas_left.saturating_sub(gas_status.remaining_internal_gas().get());
+    let mut profile = PROFILE.lock().unwrap();
+    profile.add_stack(&[name.to_string()], gas);
+    profile.add_call(name);
+    result
+}
+
+/// What the gas charged since the last event of a thread is for
+#[cfg(any(debug_assertions, feature = "debugging"))]
+enum Pending {
+    Instruction(CodeOffset, Bytecode),
+    Native(String),
+}
+
+/// The executions of a thread, as the interpreter reports them
+#[cfg(any(debug_assertions, feature = "debugging"))]
+#[derive(Default)]
+struct Recorder {
+    /// The functions on the call stack of the last instruction, outermost first
+    stack: Vec<(Option<ModuleId>, FunctionDefinitionIndex)>,
+    names: Vec<String>,
+    /// What executed last, and the gas left before it did
+    pending: Option<(Pending, u64)>,
+}
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+thread_local! {
+    static RECORDER: RefCell<Recorder> = RefCell::new(Recorder::default());
+}
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+impl Recorder {
+    /// Charge the gas used since the last event to what executed then
+    fn close(&mut self, profile: &mut GasProfile, gas_left: u64) {
+        let (pending, pending_gas_left) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let gas = pending_gas_left.saturating_sub(gas_left);
+        match pending {
+            Pending::Instruction(offset, instruction) => {
+                profile.add_stack(&self.names, gas);
+                let function = match self.names.last() {
+                    Some(function) => function,
+                    None => return,
+                };
+                if !profile.instructions.contains_key(function.as_str()) {
+                    profile
+                        .instructions
+                        .insert(function.clone(), BTreeMap::new());
+                }
+                let entry = profile
+                    .instructions
+                    .get_mut(function.as_str())
+                    .unwrap()
+                    .entry(offset)
+                    .or_insert_with(|| (format!("{:?}", instruction), 0, 0));
+                entry.1 += 1;
+                entry.2 += gas;
+            }
+            Pending::Native(name) => {
+                self.names.push(name);
+                profile.add_stack(&self.names, gas);
+                self.names.pop();
+            }
+        }
+    }
+
+    /// Move to the call stack of `frames`, counting the calls of the frames not on the last one
+    fn enter(&mut self, profile: &mut GasProfile, frames: &[&Function]) {
+        let common = self
+            .stack
+            .iter()
+            .zip(frames)
+            .take_while(|((module_id, index), function)| {
+                function.module_id() == module_id.as_ref() && function.index() == *index
+            })
+            .count();
+        self.stack.truncate(common);
+        self.names.truncate(common);
+        for function in &frames[common..] {
+            let name = function_name(function);
+            profile.add_call(&name);
+            self.stack
+                .push((function.module_id().cloned(), function.index()));
+            self.names.push(name);
+        }
+    }
+}
+
+#[cfg(any(debug_assertions, feature = "debugging"))]
+fn function_name(function: &Function) -> String {
+    match function.module_id() {
+        Some(module_id) => format!(
+            "0x{}::{}::{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            function.name()
+        ),
+        None => "Script::main".to_string(),
+    }
+}
+
+/// Record that the instruction at `pc` of the last function of `frames` is about to execute
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub(crate) fn on_instruction<'a>(
+    frames: impl FnOnce() -> Vec<&'a Function>,
+    pc: CodeOffset,
+    instruction: &Bytecode,
+    gas_status: &GasStatus,
+) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let gas_left = gas_status.remaining_internal_gas().get();
+    let frames = frames();
+    let mut profile = PROFILE.lock().unwrap();
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.close(&mut profile, gas_left);
+        recorder.enter(&mut profile, &frames);
+        recorder.pending = Some((Pending::Instruction(pc, instruction.clone()), gas_left));
+    })
+}
+
+/// Record that the native `function` is about to be called by the last instruction
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub(crate) fn on_native_call(function: &Function, gas_status: &GasStatus) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let gas_left = gas_status.remaining_internal_gas().get();
+    let name = function_name(function);
+    let mut profile = PROFILE.lock().unwrap();
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.close(&mut profile, gas_left);
+        profile.add_call(&name);
+        recorder.pending = Some((Pending::Native(name), gas_left));
+    })
+}
+
+/// Record that the execution of this thread is done
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub(crate) fn on_exit(gas_status: &GasStatus) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let gas_left = gas_status.remaining_internal_gas().get();
+    let mut profile = PROFILE.lock().unwrap();
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.close(&mut profile, gas_left);
+        recorder.stack.clear();
+        recorder.names.clear();
+    })
+}
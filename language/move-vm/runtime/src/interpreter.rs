@@ -107,7 +107,11 @@ impl<L: LogContext> Interpreter<L> {
     ) -> VMResult<Vec<Value>> {
         // No unwinding of the call stack and value stack need to be done here -- the context will
         // take care of that.
-        self.execute_main(loader, data_store, gas_status, function, ty_args, args)
+        let result = self.execute_main(loader, data_store, gas_status, function, ty_args, args);
+        // Only include this code in debug releases, or with the `debugging` feature
+        #[cfg(any(debug_assertions, feature = "debugging"))]
+        crate::gas_profiler::on_exit(gas_status);
+        result
     }
 
     /// Main loop for the execution of a function.
@@ -269,6 +273,8 @@ impl<L: LogContext> Interpreter<L> {
         for _ in 0..expected_args {
             arguments.push_front(self.operand_stack.pop()?);
         }
+        #[cfg(any(debug_assertions, feature = "debugging"))]
+        crate::gas_profiler::on_native_call(&function, gas_status);
         let mut native_context = FunctionContext::new(self, data_store, gas_status, resolver);
         let native_function = function.get_native()?;
         let result = native_function.dispatch(&mut native_context, ty_args, arguments)?;
@@ -485,6 +491,26 @@ impl<L: LogContext> Interpreter<L> {
         Ok(())
     }
 
+    /// The frames of the callers of the current function, outermost first, for debuggers
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_frames(&self) -> Vec<crate::debug::FrameRef> {
+        self.call_stack
+            .0
+            .iter()
+            .map(|frame| crate::debug::FrameRef {
+                function: &frame.function,
+                pc: frame.pc,
+                locals: &frame.locals,
+                ty_args: &frame.ty_args,
+            })
+            .collect()
+    }
+
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn log_context(&self) -> &L {
+        &self.log_context
+    }
+
     #[allow(dead_code)]
     pub(crate) fn debug_print_stack_trace<B: Write>(
         &self,
@@ -699,10 +725,13 @@ impl Frame {
                 trace!(
                     &self.function,
                     &self.locals,
+                    &self.ty_args,
                     self.pc,
                     instruction,
                     &resolver,
-                    &interpreter
+                    &interpreter,
+                    data_store,
+                    gas_status
                 );
 
                 fail_point!("move_vm::interpreter_loop", |_| {
@@ -13,6 +13,8 @@
 extern crate mirai_annotations;
 
 pub mod data_cache;
+pub mod debug;
+pub mod gas_profiler;
 mod interpreter;
 mod loader;
 pub mod logging;
@@ -23,9 +25,9 @@ pub mod session;
 #[macro_use]
 mod tracing;
 
-// Only include debugging functionality in debug builds
-#[cfg(debug_assertions)]
-mod debug;
-
 #[cfg(test)]
 mod unit_tests;
+
+/// Whether the interpreter reports to debuggers and gas profilers, which it only does in debug
+/// builds, or when this crate is built with the `debugging` feature
+pub const DEBUGGING_ENABLED: bool = cfg!(any(debug_assertions, feature = "debugging"));
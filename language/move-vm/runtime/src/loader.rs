@@ -756,7 +756,7 @@ impl Loader {
     // Helpers for loading and verification
     //
 
-    fn load_type(
+    pub(crate) fn load_type(
         &self,
         type_tag: &TypeTag,
         data_store: &mut impl DataStore,
@@ -1146,6 +1146,31 @@ impl<'a> Resolver<'a> {
     pub(crate) fn loader(&self) -> &Loader {
         &self.loader
     }
+
+    /// The types of the parameters and locals of `function`, which belongs to the binary of this
+    /// resolver, instantiated with `ty_args`
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn local_types(
+        &self,
+        function: &Function,
+        ty_args: &[Type],
+    ) -> PartialVMResult<Vec<Type>> {
+        let cache = self.loader.module_cache.read();
+        let script_module;
+        let module = match &self.binary {
+            BinaryType::Module(module) => module.module(),
+            BinaryType::Script(script) => {
+                script_module = script.script.clone().into_module().1;
+                &script_module
+            }
+        };
+        function
+            .locals
+            .0
+            .iter()
+            .map(|tok| cache.make_type(module, tok)?.subst(ty_args))
+            .collect()
+    }
 }
 
 // A Module is very similar to a binary Module but data is "transformed" to a representation
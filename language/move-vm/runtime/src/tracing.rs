@@ -1,13 +1,8 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(debug_assertions)]
-use crate::debug::DebugContext;
-
 #[cfg(debug_assertions)]
 use ::{
-    move_binary_format::file_format::Bytecode,
-    move_vm_types::values::Locals,
     once_cell::sync::Lazy,
     std::{
         env,
@@ -19,18 +14,26 @@ use ::{
     },
 };
 
+#[cfg(any(debug_assertions, feature = "debugging"))]
 use crate::{
+    debug::{self, FrameRef},
+    gas_profiler,
     interpreter::Interpreter,
     loader::{Function, Loader},
     logging::LogContext,
 };
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use ::{
+    move_binary_format::file_format::Bytecode,
+    move_vm_types::{
+        data_store::DataStore, gas_schedule::GasStatus, loaded_data::runtime_types::Type,
+        values::Locals,
+    },
+};
 
 #[cfg(debug_assertions)]
 const MOVE_VM_TRACING_ENV_VAR_NAME: &str = "MOVE_VM_TRACE";
 
-#[cfg(debug_assertions)]
-const MOVE_VM_STEPPING_ENV_VAR_NAME: &str = "MOVE_VM_STEP";
-
 #[cfg(debug_assertions)]
 static FILE_PATH: Lazy<String> = Lazy::new(|| {
     env::var(MOVE_VM_TRACING_ENV_VAR_NAME).unwrap_or_else(|_| "move_vm_trace.trace".to_string())
@@ -39,10 +42,6 @@ static FILE_PATH: Lazy<String> = Lazy::new(|| {
 #[cfg(debug_assertions)]
 static TRACING_ENABLED: Lazy<bool> = Lazy::new(|| env::var(MOVE_VM_TRACING_ENV_VAR_NAME).is_ok());
 
-#[cfg(debug_assertions)]
-static DEBUGGING_ENABLED: Lazy<bool> =
-    Lazy::new(|| env::var(MOVE_VM_STEPPING_ENV_VAR_NAME).is_ok());
-
 #[cfg(debug_assertions)]
 static LOGGING_FILE: Lazy<Mutex<File>> = Lazy::new(|| {
     Mutex::new(
@@ -55,19 +54,55 @@ static LOGGING_FILE: Lazy<Mutex<File>> = Lazy::new(|| {
     )
 });
 
-#[cfg(debug_assertions)]
-static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugContext::new()));
-
-// Only include in debug builds
-#[cfg(debug_assertions)]
+// Only include in debug builds, or in builds with the `debugging` feature
+#[cfg(any(debug_assertions, feature = "debugging"))]
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn trace<L: LogContext>(
     function_desc: &Function,
     locals: &Locals,
+    ty_args: &[Type],
     pc: u16,
     instr: &Bytecode,
     loader: &Loader,
     interp: &Interpreter<L>,
+    data_store: &mut impl DataStore,
+    gas_status: &GasStatus,
 ) {
+    #[cfg(debug_assertions)]
+    trace_to_file(function_desc, pc, instr);
+    gas_profiler::on_instruction(
+        || {
+            let mut frames: Vec<_> = interp
+                .debug_frames()
+                .into_iter()
+                .map(|frame| frame.function)
+                .collect();
+            frames.push(function_desc);
+            frames
+        },
+        pc,
+        instr,
+        gas_status,
+    );
+    debug::on_instruction(
+        || {
+            let mut frames = interp.debug_frames();
+            frames.push(FrameRef {
+                function: function_desc,
+                pc,
+                locals,
+                ty_args,
+            });
+            frames
+        },
+        loader,
+        data_store,
+        interp.log_context(),
+    );
+}
+
+#[cfg(debug_assertions)]
+fn trace_to_file(function_desc: &Function, pc: u16, instr: &Bytecode) {
     if *TRACING_ENABLED {
         let f = &mut *LOGGING_FILE.lock().unwrap();
         writeln!(
@@ -81,26 +116,33 @@ pub(crate) fn trace<L: LogContext>(
         )
         .unwrap();
     }
-    if *DEBUGGING_ENABLED {
-        DEBUG_CONTEXT
-            .lock()
-            .unwrap()
-            .debug_loop(function_desc, locals, pc, instr, loader, interp);
-    }
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
-        // Only include this code in debug releases
-        #[cfg(debug_assertions)]
+    (
+        $function_desc:expr,
+        $locals:expr,
+        $ty_args:expr,
+        $pc:expr,
+        $instr:tt,
+        $resolver:expr,
+        $interp:expr,
+        $data_store:expr,
+        $gas_status:expr
+    ) => {
+        // Only include this code in debug releases, or with the `debugging` feature
+        #[cfg(any(debug_assertions, feature = "debugging"))]
         crate::tracing::trace(
             &$function_desc,
             $locals,
+            $ty_args,
             $pc,
             &$instr,
             $resolver.loader(),
             $interp,
+            $data_store,
+            $gas_status,
         )
     };
 }
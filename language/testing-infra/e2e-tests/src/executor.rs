@@ -37,8 +37,8 @@ use diem_types::{
     write_set::WriteSet,
 };
 use diem_vm::{
-    convert_changeset_and_events, data_cache::RemoteStorage, DiemVM, DiemVMValidator, VMExecutor,
-    VMValidator,
+    convert_changeset_and_events, data_cache::RemoteStorage, DiemVM, DiemVMValidator,
+    ParallelDiemVM, VMExecutor, VMValidator,
 };
 use move_core_types::{
     identifier::Identifier,
@@ -307,6 +307,22 @@ impl FakeExecutor {
         )
     }
 
+    /// Executes the given block of transactions with the parallel executor.
+    ///
+    /// The output is expected to be identical to the one of `execute_block`.
+    pub fn execute_block_in_parallel(
+        &self,
+        txn_block: Vec<SignedTransaction>,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        ParallelDiemVM::execute_block(
+            txn_block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+            &self.data_store,
+        )
+    }
+
     /// Executes the transaction as a singleton block and applies the resulting write set to the
     /// data store. Panics if execution fails
     pub fn execute_and_apply(&mut self, transaction: SignedTransaction) -> TransactionOutput {
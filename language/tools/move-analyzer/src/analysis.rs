@@ -0,0 +1,369 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checking the Move sources of the workspace.
+//!
+//! Every analysis compiles the sources as they are in the editor: the compiler reads the files
+//! that are open from copies in a temporary directory, and all other files from disk. The
+//! dependencies are compiled once and reused, unless one of them is edited, in which case they
+//! are compiled together with the sources.
+
+use crate::{files::SourceFiles, symbols::Symbols, vfs::VirtualFileSystem};
+use anyhow::Result;
+use codespan_reporting::diagnostic::{Diagnostic as ModelDiagnostic, Label, Severity};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity};
+use move_ir_types::location::Loc;
+use move_lang::{
+    extension_equals, find_move_filenames, move_construct_pre_compiled_lib, move_continue_up_to,
+    move_parse, path_to_string,
+    shared::{CompilationEnv, Flags},
+    FullyCompiledProgram, Pass, PassResult, MOVE_EXTENSION,
+};
+use move_model::{
+    model::{GlobalEnv, Loc as ModelLoc},
+    run_model_builder,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// Where to find the Move files, as passed in the initialization options of the client
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Files and directories with the sources to check. Defaults to the root of the workspace.
+    pub sources: Vec<String>,
+    /// Files and directories with the dependencies of the sources. Defaults to the Diem framework.
+    pub dependencies: Option<Vec<String>>,
+}
+
+impl Config {
+    pub fn new(root: Option<&Path>, options: Option<serde_json::Value>) -> Result<Self> {
+        let mut config: Config = match options {
+            Some(options) if !options.is_null() => serde_json::from_value(options)?,
+            _ => Config::default(),
+        };
+        if let Some(root) = root {
+            if config.sources.is_empty() {
+                config.sources.push(path_to_string(root)?);
+            }
+            // Relative paths are relative to the root of the workspace
+            let resolve = |path: &mut String| -> Result<()> {
+                *path = path_to_string(&root.join(&path))?;
+                Ok(())
+            };
+            config.sources.iter_mut().try_for_each(resolve)?;
+            if let Some(dependencies) = &mut config.dependencies {
+                dependencies.iter_mut().try_for_each(resolve)?;
+            }
+        }
+        if config.dependencies.is_none() {
+            config.dependencies = Some(diem_framework::diem_stdlib_files());
+        }
+        Ok(config)
+    }
+}
+
+/// The dependencies compiled on their own, together with their symbols
+struct CompiledDeps {
+    program: FullyCompiledProgram,
+    files: SourceFiles,
+    symbols: Symbols,
+}
+
+pub struct Analysis {
+    /// The diagnostics of every file that has any
+    pub diagnostics: BTreeMap<PathBuf, Vec<Diagnostic>>,
+    /// The symbols of the program, if it type checks
+    pub symbols: Option<Symbols>,
+}
+
+pub struct Analyzer {
+    config: Config,
+    /// Holds the copies of the open files
+    overlay: TempDir,
+    /// The copy of each open file that has been analyzed
+    copies: BTreeMap<PathBuf, PathBuf>,
+    /// The names of the dependency files, and the dependencies compiled from them, if they compile
+    compiled_deps: Option<(Vec<String>, Option<CompiledDeps>)>,
+    /// The model of the program, as of the last time it was built
+    model: Option<GlobalEnv>,
+}
+
+impl Analyzer {
+    pub fn new(config: Config) -> Result<Self> {
+        Ok(Self {
+            config,
+            overlay: tempfile::tempdir()?,
+            copies: BTreeMap::new(),
+            compiled_deps: None,
+            model: None,
+        })
+    }
+
+    pub fn model(&self) -> Option<&GlobalEnv> {
+        self.model.as_ref()
+    }
+
+    /// Check the sources, and any other Move file open in `vfs`. Building the move model takes
+    /// longer than compiling, so the model is only rebuilt if `build_model` is set, or if there
+    /// is none yet.
+    pub fn analyze(&mut self, vfs: &VirtualFileSystem, build_model: bool) -> Result<Analysis> {
+        let mut targets = canonical_files(&self.config.sources)?;
+        let deps: BTreeSet<_> =
+            canonical_files(self.config.dependencies.as_deref().unwrap_or(&[]))?
+                .difference(&targets)
+                .cloned()
+                .collect();
+        for (path, _) in vfs.iter() {
+            if extension_equals(path, MOVE_EXTENSION) && !deps.contains(path) {
+                targets.insert(path.to_path_buf());
+            }
+        }
+
+        // The names of the files for the compiler, and the files they stand for
+        let mut paths = BTreeMap::new();
+        let mut target_names = vec![];
+        for path in &targets {
+            let name = match vfs.get(path) {
+                Some(text) => self.write_copy(path, text)?,
+                None => path_to_string(path)?,
+            };
+            paths.insert(name.clone(), path.clone());
+            target_names.push(name);
+        }
+        let mut dep_names = vec![];
+        let mut deps_edited = false;
+        for path in &deps {
+            let name = match vfs.get(path) {
+                Some(text) if fs::read_to_string(path).ok().as_deref() != Some(text) => {
+                    deps_edited = true;
+                    self.write_copy(path, text)?
+                }
+                _ => path_to_string(path)?,
+            };
+            paths.insert(name.clone(), path.clone());
+            dep_names.push(name);
+        }
+
+        let compiled_deps = if deps_edited {
+            None
+        } else {
+            compile_deps(&mut self.compiled_deps, &dep_names, &paths)?
+        };
+        let (pre_compiled_lib, deps_to_parse) = match compiled_deps {
+            Some(deps) => (Some(&deps.program), &[][..]),
+            None => (None, &dep_names[..]),
+        };
+
+        let mut compilation_env = CompilationEnv::new(Flags::testing());
+        let (files, pprog_and_comments_res) =
+            move_parse(&compilation_env, &target_names, deps_to_parse, None)?;
+        let mut files = SourceFiles::new(files, &paths);
+        let mut symbols = None;
+        let result = match pprog_and_comments_res {
+            Err(errors) => Err(errors),
+            Ok((_comments, pprog)) => match move_continue_up_to(
+                &mut compilation_env,
+                pre_compiled_lib,
+                PassResult::Parser(pprog),
+                Pass::Typing,
+            ) {
+                Ok(PassResult::Typing(tprog)) => {
+                    let mut program_symbols = match compiled_deps {
+                        Some(deps) => deps.symbols.clone(),
+                        None => Symbols::new(),
+                    };
+                    program_symbols.add_program(files.clone(), &tprog);
+                    symbols = Some(program_symbols);
+                    move_continue_up_to(
+                        &mut compilation_env,
+                        pre_compiled_lib,
+                        PassResult::Typing(tprog),
+                        Pass::CFGIR,
+                    )
+                }
+                Ok(_) => unreachable!(),
+                Err(errors) => Err(errors),
+            },
+        };
+        if let Some(deps) = compiled_deps {
+            files.extend(deps.files.clone());
+        }
+
+        let mut diagnostics = BTreeMap::new();
+        let mut add_diagnostic = |diagnostic: Option<(PathBuf, Diagnostic)>| {
+            if let Some((path, diagnostic)) = diagnostic {
+                diagnostics
+                    .entry(path)
+                    .or_insert_with(Vec::new)
+                    .push(diagnostic)
+            }
+        };
+        match result {
+            Err(errors) => {
+                for error in errors {
+                    add_diagnostic(compiler_diagnostic(&files, error));
+                }
+            }
+            Ok(_) if build_model || self.model.is_none() => {
+                match run_model_builder(&target_names, &dep_names) {
+                    Ok(env) => {
+                        for diag in env.get_diags(Severity::Warning) {
+                            add_diagnostic(model_diagnostic(&env, &files, diag));
+                        }
+                        self.model = Some(env);
+                    }
+                    Err(err) => eprintln!("Unable to build the move model: {}", err),
+                }
+            }
+            Ok(_) => (),
+        }
+        Ok(Analysis {
+            diagnostics,
+            symbols,
+        })
+    }
+
+    /// Write `text`, the contents of the open file at `path`, to the copy of that file that the
+    /// compiler reads, and return the name of the copy. The copy keeps the name of the file.
+    fn write_copy(&mut self, path: &Path, text: &str) -> Result<String> {
+        let copy = match self.copies.get(path) {
+            Some(copy) => copy.clone(),
+            None => {
+                let dir = self.overlay.path().join(self.copies.len().to_string());
+                fs::create_dir_all(&dir)?;
+                let copy = dir.join(path.file_name().unwrap_or_default());
+                self.copies.insert(path.to_path_buf(), copy.clone());
+                copy
+            }
+        };
+        fs::write(&copy, text)?;
+        path_to_string(&copy)
+    }
+}
+
+/// The dependencies compiled from `dep_names`, which are only compiled again when the names
+/// change. Returns `None` if they do not compile on their own.
+fn compile_deps<'a>(
+    compiled_deps: &'a mut Option<(Vec<String>, Option<CompiledDeps>)>,
+    dep_names: &[String],
+    paths: &BTreeMap<String, PathBuf>,
+) -> Result<Option<&'a CompiledDeps>> {
+    if !matches!(compiled_deps, Some((names, _)) if names == dep_names) {
+        let mut compilation_env = CompilationEnv::new(Flags::testing());
+        let compiled = match move_construct_pre_compiled_lib(&mut compilation_env, dep_names, None)?
+        {
+            Ok(program) => {
+                let files = SourceFiles::new(program.files.clone(), paths);
+                let mut symbols = Symbols::new();
+                symbols.add_program(files.clone(), &program.typing);
+                Some(CompiledDeps {
+                    program,
+                    files,
+                    symbols,
+                })
+            }
+            Err(_) => None,
+        };
+        *compiled_deps = Some((dep_names.to_vec(), compiled));
+    }
+    Ok(compiled_deps.as_ref().and_then(|(_, deps)| deps.as_ref()))
+}
+
+/// The Move files in `paths`, which may also contain directories
+fn canonical_files(paths: &[String]) -> Result<BTreeSet<PathBuf>> {
+    Ok(find_move_filenames(paths, true)?
+        .iter()
+        .map(|file| canonical_path(Path::new(file)))
+        .collect())
+}
+
+/// The form of `path` that is used to identify files
+pub fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The first part of a compiler error is the error itself, and the others point at related code
+fn compiler_diagnostic(
+    files: &SourceFiles,
+    error: Vec<(Loc, String)>,
+) -> Option<(PathBuf, Diagnostic)> {
+    let mut parts = error.into_iter();
+    let (loc, message) = parts.next()?;
+    let related = parts
+        .filter_map(|(loc, message)| {
+            Some(DiagnosticRelatedInformation {
+                location: files.location(loc)?,
+                message,
+            })
+        })
+        .collect();
+    diagnostic(files, loc, DiagnosticSeverity::Error, message, related)
+}
+
+fn model_diagnostic(
+    env: &GlobalEnv,
+    files: &SourceFiles,
+    diag: ModelDiagnostic,
+) -> Option<(PathBuf, Diagnostic)> {
+    let location = |label: &Label| {
+        let (fname, _) = env.get_file_and_location(&ModelLoc::new(label.file_id, label.span))?;
+        files.loc(&fname, label.span)
+    };
+    let severity = match diag.severity {
+        Severity::Bug | Severity::Error => DiagnosticSeverity::Error,
+        Severity::Warning => DiagnosticSeverity::Warning,
+        Severity::Note => DiagnosticSeverity::Information,
+        Severity::Help => DiagnosticSeverity::Hint,
+    };
+    let mut message = diag.message.clone();
+    for note in &diag.notes {
+        message.push('\n');
+        message.push_str(note);
+    }
+    let related = diag
+        .secondary_labels
+        .iter()
+        .filter_map(|label| {
+            Some(DiagnosticRelatedInformation {
+                location: files.location(location(label)?)?,
+                message: label.message.clone(),
+            })
+        })
+        .collect();
+    diagnostic(
+        files,
+        location(&diag.primary_label)?,
+        severity,
+        message,
+        related,
+    )
+}
+
+fn diagnostic(
+    files: &SourceFiles,
+    loc: Loc,
+    severity: DiagnosticSeverity,
+    message: String,
+    related: Vec<DiagnosticRelatedInformation>,
+) -> Option<(PathBuf, Diagnostic)> {
+    let diagnostic = Diagnostic::new(
+        files.range(loc)?,
+        Some(severity),
+        None,
+        Some("move".to_string()),
+        message,
+        if related.is_empty() {
+            None
+        } else {
+            Some(related)
+        },
+        None,
+    );
+    Some((files.path(loc.file())?.to_path_buf(), diagnostic))
+}
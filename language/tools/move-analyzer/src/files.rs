@@ -0,0 +1,116 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use codespan::Span;
+use lsp_types::{Location, Position, Range, Url};
+use move_ir_types::location::Loc;
+use move_lang::errors::FilesSourceText;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// The texts of the files seen by the compiler, together with the files they stand for. The
+/// compiler reads open files from a copy on disk, so the name it uses for a file is not
+/// necessarily the file's own path.
+#[derive(Debug, Clone, Default)]
+pub struct SourceFiles {
+    texts: HashMap<&'static str, String>,
+    paths: HashMap<&'static str, PathBuf>,
+    names: HashMap<PathBuf, &'static str>,
+}
+
+impl SourceFiles {
+    /// Collect the texts of `files`, keeping the ones that stand for a file in `paths`
+    pub fn new(files: FilesSourceText, paths: &BTreeMap<String, PathBuf>) -> Self {
+        let mut source_files = Self::default();
+        for (fname, text) in files {
+            if let Some(path) = paths.get(fname) {
+                source_files.texts.insert(fname, text);
+                source_files.paths.insert(fname, path.clone());
+                source_files.names.insert(path.clone(), fname);
+            }
+        }
+        source_files
+    }
+
+    pub fn extend(&mut self, other: SourceFiles) {
+        self.texts.extend(other.texts);
+        self.paths.extend(other.paths);
+        self.names.extend(other.names);
+    }
+
+    /// The name under which the compiler knows the file at `path`
+    pub fn name(&self, path: &Path) -> Option<&'static str> {
+        self.names.get(path).copied()
+    }
+
+    pub fn path(&self, fname: &str) -> Option<&Path> {
+        self.paths.get(fname).map(PathBuf::as_path)
+    }
+
+    pub fn text(&self, fname: &str) -> Option<&str> {
+        self.texts.get(fname).map(String::as_str)
+    }
+
+    /// The location of `span` in the file the compiler knows as `fname`
+    pub fn loc(&self, fname: &str, span: Span) -> Option<Loc> {
+        let (fname, _) = self.texts.get_key_value(fname)?;
+        Some(Loc::new(fname, span))
+    }
+
+    /// The text covered by `loc`, as it was compiled
+    pub fn source(&self, loc: Loc) -> Option<&str> {
+        let span = loc.span();
+        self.text(loc.file())?
+            .get(span.start().to_usize()..span.end().to_usize())
+    }
+
+    pub fn range(&self, loc: Loc) -> Option<Range> {
+        let text = self.text(loc.file())?;
+        Some(Range::new(
+            position_of(text, loc.span().start().to_usize()),
+            position_of(text, loc.span().end().to_usize()),
+        ))
+    }
+
+    pub fn location(&self, loc: Loc) -> Option<Location> {
+        let uri = Url::from_file_path(self.path(loc.file())?).ok()?;
+        Some(Location::new(uri, self.range(loc)?))
+    }
+}
+
+/// The byte offset of `pos` in `text`. Positions past the end of a line are clamped to the end of
+/// the line, and positions past the end of the text to the end of the text.
+pub fn offset_of(text: &str, pos: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..pos.line {
+        match text[line_start..].find('\n') {
+            Some(idx) => line_start += idx + 1,
+            None => return text.len(),
+        }
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |idx| line_start + idx);
+    // Columns count UTF-16 code units
+    let mut column = 0;
+    for (idx, c) in text[line_start..line_end].char_indices() {
+        if column >= pos.character as usize {
+            return line_start + idx;
+        }
+        column += c.len_utf16();
+    }
+    line_end
+}
+
+/// The position of the byte offset `offset` in `text`
+pub fn position_of(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
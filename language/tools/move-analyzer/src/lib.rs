@@ -0,0 +1,11 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#[macro_use(sp)]
+extern crate move_ir_types;
+
+pub mod analysis;
+pub mod files;
+pub mod server;
+pub mod symbols;
+pub mod vfs;
@@ -0,0 +1,12 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use lsp_server::Connection;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    move_analyzer::server::run(connection)?;
+    io_threads.join()?;
+    Ok(())
+}
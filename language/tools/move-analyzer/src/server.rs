@@ -0,0 +1,229 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The language server loop. Notifications about the open files update the virtual file system,
+//! and the sources are analyzed again once there are no more messages waiting, or before
+//! answering a request.
+
+use crate::{
+    analysis::{canonical_path, Analysis, Analyzer, Config},
+    symbols::Symbols,
+    vfs::VirtualFileSystem,
+};
+use anyhow::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as _},
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, HoverProviderCapability,
+    InitializeParams, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeSet, path::PathBuf};
+
+struct State {
+    vfs: VirtualFileSystem,
+    analyzer: Analyzer,
+    /// The symbols of the last version of the program that type checked
+    symbols: Option<Symbols>,
+    /// The files with diagnostics, which are cleared once they have none
+    files_with_diagnostics: BTreeSet<PathBuf>,
+    /// Whether the files changed since the last analysis
+    dirty: bool,
+    /// Whether a file was opened or saved since the last analysis
+    saved: bool,
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::Incremental,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string(), ".".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Serve the client at the other end of `connection`, until it shuts the server down
+pub fn run(connection: Connection) -> Result<()> {
+    let params = connection.initialize(serde_json::to_value(capabilities())?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let root = params
+        .root_uri
+        .and_then(|uri| uri.to_file_path().ok())
+        .map(|path| canonical_path(&path));
+    let config = Config::new(root.as_deref(), params.initialization_options)?;
+    eprintln!("Starting move-analyzer with {:?}", config);
+
+    let mut state = State {
+        vfs: VirtualFileSystem::new(),
+        analyzer: Analyzer::new(config)?,
+        symbols: None,
+        files_with_diagnostics: BTreeSet::new(),
+        dirty: true,
+        saved: true,
+    };
+    loop {
+        if state.dirty && connection.receiver.is_empty() {
+            state.analyze(&connection)?;
+        }
+        match connection.receiver.recv()? {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                if state.dirty {
+                    state.analyze(&connection)?;
+                }
+                let response = state.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => state.handle_notification(notification),
+            Message::Response(_) => (),
+        }
+    }
+}
+
+impl State {
+    fn analyze(&mut self, connection: &Connection) -> Result<()> {
+        let Analysis {
+            diagnostics,
+            symbols,
+        } = self.analyzer.analyze(&self.vfs, self.saved)?;
+        self.dirty = false;
+        self.saved = false;
+        if symbols.is_some() {
+            self.symbols = symbols;
+        }
+
+        let cleared: Vec<_> = self
+            .files_with_diagnostics
+            .iter()
+            .filter(|path| !diagnostics.contains_key(*path))
+            .map(|path| (path.clone(), vec![]))
+            .collect();
+        self.files_with_diagnostics = diagnostics.keys().cloned().collect();
+        for (path, diagnostics) in diagnostics.into_iter().chain(cleared) {
+            let uri = match Url::from_file_path(&path) {
+                Ok(uri) => uri,
+                Err(()) => continue,
+            };
+            let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+            let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+            connection
+                .sender
+                .send(Message::Notification(notification))?;
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let env = self.analyzer.model();
+        match request.method.as_str() {
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |params| {
+                let position = params.text_document_position_params;
+                let (symbols, path, text) = self.query(&position.text_document.uri)?;
+                let location = symbols.definition(&path, &text, position.position)?;
+                Some(GotoDefinitionResponse::Scalar(location))
+            }),
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| {
+                let position = params.text_document_position_params;
+                let (symbols, path, text) = self.query(&position.text_document.uri)?;
+                symbols.hover(&path, &text, position.position, env)
+            }),
+            Completion::METHOD => respond::<Completion>(request, |params| {
+                let position = params.text_document_position;
+                let (symbols, path, text) = self.query(&position.text_document.uri)?;
+                let items = symbols.completion(&path, &text, position.position, env);
+                Some(CompletionResponse::Array(items))
+            }),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request {}", request.method),
+            ),
+        }
+    }
+
+    /// The symbols, together with the path and the current text of the file at `uri`
+    fn query(&self, uri: &Url) -> Option<(&Symbols, PathBuf, String)> {
+        let symbols = self.symbols.as_ref()?;
+        let path = file_path(uri)?;
+        let text = self.vfs.read(&path).ok()?;
+        Some((symbols, path, text))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Some(params) = params::<DidOpenTextDocument>(notification) {
+                    if let Some(path) = file_path(&params.text_document.uri) {
+                        self.vfs.open(path, params.text_document.text);
+                        self.saved = true;
+                    }
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Some(params) = params::<DidChangeTextDocument>(notification) {
+                    if let Some(path) = file_path(&params.text_document.uri) {
+                        self.vfs.change(&path, params.content_changes);
+                    }
+                }
+            }
+            DidSaveTextDocument::METHOD => self.saved = true,
+            DidCloseTextDocument::METHOD => {
+                if let Some(params) = params::<DidCloseTextDocument>(notification) {
+                    if let Some(path) = file_path(&params.text_document.uri) {
+                        self.vfs.close(&path);
+                    }
+                }
+            }
+            _ => return,
+        }
+        self.dirty = true;
+    }
+}
+
+/// Answer `request` with the result of `handler` for its parameters
+fn respond<R>(request: Request, handler: impl FnOnce(R::Params) -> R::Result) -> Response
+where
+    R: lsp_types::request::Request,
+    R::Params: DeserializeOwned,
+    R::Result: Serialize,
+{
+    match serde_json::from_value(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(err) => Response::new_err(
+            request.id,
+            ErrorCode::InvalidParams as i32,
+            format!("Invalid parameters for {}: {}", R::METHOD, err),
+        ),
+    }
+}
+
+fn params<N>(notification: Notification) -> Option<N::Params>
+where
+    N: lsp_types::notification::Notification,
+    N::Params: DeserializeOwned,
+{
+    match serde_json::from_value(notification.params) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            eprintln!("Invalid parameters for {}: {}", N::METHOD, err);
+            None
+        }
+    }
+}
+
+fn file_path(uri: &Url) -> Option<PathBuf> {
+    Some(canonical_path(&uri.to_file_path().ok()?))
+}
@@ -0,0 +1,899 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The definitions of a Move program and the references to them, for navigating the program.
+//!
+//! Symbols are collected from the typed program, where every name has been resolved to what it
+//! stands for. Each definition and each reference to it is recorded at the location of the name,
+//! which is what go-to-definition and hover look up. Documentation comments are not kept in the
+//! typed program, and are instead looked up in the move model. The model only has the comments of
+//! the sources, so those of the dependencies are read from the text above their definitions.
+
+use crate::files::{offset_of, SourceFiles};
+use codespan::Span;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Documentation, Hover, HoverContents, Location,
+    MarkupContent, MarkupKind, Position,
+};
+use move_ir_types::location::*;
+use move_lang::{
+    expansion::ast::{Address, ModuleIdent, ModuleIdent_},
+    naming::ast::{StructDefinition, StructFields, TParam, Type, TypeName_, Type_},
+    parser::ast::{ConstantName, Field, FunctionName, StructName, Var, Visibility},
+    shared::{AddressBytes, Identifier},
+    typing::ast as T,
+};
+use move_model::{ast::ModuleName, model::GlobalEnv};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+type DefId = usize;
+
+/// A struct, by its module and its name
+type StructKey = (ModuleIdent_, String);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DefKind {
+    Module,
+    Function,
+    Struct,
+    Field,
+    Constant,
+    Local,
+}
+
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub kind: DefKind,
+    pub name: String,
+    /// Location of the name in the definition
+    pub loc: Loc,
+    /// A one line description, like the signature of a function or the type of a local
+    pub detail: String,
+    /// The module that is defined, or that the definition belongs to
+    module: Option<ModuleIdent_>,
+    /// The struct a field belongs to
+    parent: Option<String>,
+    /// Whether the definition can be used outside of its module
+    public: bool,
+    /// The struct held by a local or a field, directly or through a reference
+    struct_type: Option<StructKey>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ModuleSymbols {
+    def: DefId,
+    functions: BTreeMap<String, DefId>,
+    structs: BTreeMap<String, DefId>,
+    fields: BTreeMap<String, BTreeMap<String, DefId>>,
+    constants: BTreeMap<String, DefId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Reference {
+    loc: Loc,
+    def: DefId,
+}
+
+/// The body of a function, and the locals defined in it
+#[derive(Debug, Clone)]
+struct FunctionScope {
+    loc: Loc,
+    locals: Vec<DefId>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    files: SourceFiles,
+    addresses: BTreeMap<String, AddressBytes>,
+    defs: Vec<Definition>,
+    modules: BTreeMap<ModuleIdent_, ModuleSymbols>,
+    /// The references in each file. A definition also counts as a reference to itself.
+    references: HashMap<&'static str, Vec<Reference>>,
+    functions: HashMap<&'static str, Vec<FunctionScope>>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the symbols of `prog`, whose sources are in `files`. The modules that `prog` uses must
+    /// either be part of it or have been added before.
+    pub fn add_program(&mut self, files: SourceFiles, prog: &T::Program) {
+        self.files.extend(files);
+        for (name, bytes) in prog.addresses.key_cloned_iter() {
+            self.addresses.insert(name.value, *bytes);
+        }
+        // Declare all module members first, so that uses can refer to members of any module
+        for (mident, mdef) in prog.modules.key_cloned_iter() {
+            self.declare_module(mident, mdef);
+        }
+        for (mident, mdef) in prog.modules.key_cloned_iter() {
+            let constants = self.modules[&mident.value].constants.clone();
+            let mut context = Context::new(self, Some(mident.value.clone()), constants);
+            context.module(mdef);
+        }
+        for script in prog.scripts.values() {
+            let mut context = Context::new(self, None, BTreeMap::new());
+            context.script(script);
+        }
+    }
+
+    fn define(&mut self, def: Definition) -> DefId {
+        let id = self.defs.len();
+        self.add_reference(def.loc, id);
+        self.defs.push(def);
+        id
+    }
+
+    fn add_reference(&mut self, loc: Loc, def: DefId) {
+        self.references
+            .entry(loc.file())
+            .or_default()
+            .push(Reference { loc, def });
+    }
+
+    fn declare_module(&mut self, mident: ModuleIdent, mdef: &T::ModuleDefinition) {
+        let module = mident.value;
+        let mut symbols = ModuleSymbols {
+            def: self.define(Definition {
+                kind: DefKind::Module,
+                name: module.module.to_string(),
+                loc: mident.loc,
+                detail: format!("module {}", module),
+                module: Some(module.clone()),
+                parent: None,
+                public: true,
+                struct_type: None,
+            }),
+            ..ModuleSymbols::default()
+        };
+        let member = |kind, name: &dyn Identifier, detail, public| Definition {
+            kind,
+            name: name.value().to_string(),
+            loc: name.loc(),
+            detail,
+            module: Some(module.clone()),
+            parent: None,
+            public,
+            struct_type: None,
+        };
+
+        for (name, sdef) in mdef.structs.key_cloned_iter() {
+            let detail = struct_detail(&name, sdef);
+            let id = self.define(member(DefKind::Struct, &name, detail, true));
+            symbols.structs.insert(name.to_string(), id);
+            let mut fields = BTreeMap::new();
+            if let StructFields::Defined(defined) = &sdef.fields {
+                for (field, (_, ty)) in defined.key_cloned_iter() {
+                    let detail = format!("{}: {}", field, display_type(ty));
+                    let id = self.define(Definition {
+                        parent: Some(name.to_string()),
+                        struct_type: struct_of(ty),
+                        ..member(DefKind::Field, &field, detail, true)
+                    });
+                    fields.insert(field.to_string(), id);
+                }
+            }
+            symbols.fields.insert(name.to_string(), fields);
+        }
+        for (name, constant) in mdef.constants.key_cloned_iter() {
+            let detail = format!("const {}: {}", name, display_type(&constant.signature));
+            let id = self.define(member(DefKind::Constant, &name, detail, false));
+            symbols.constants.insert(name.to_string(), id);
+        }
+        for (name, function) in mdef.functions.key_cloned_iter() {
+            let detail = function_detail(&name, function, mdef.is_source_module);
+            let public = !matches!(function.visibility, Visibility::Internal);
+            let id = self.define(member(DefKind::Function, &name, detail, public));
+            symbols.functions.insert(name.to_string(), id);
+        }
+        self.modules.insert(module, symbols);
+    }
+
+    //**********************************************************************************************
+    // Queries
+    //**********************************************************************************************
+
+    /// The definition that the text at `pos` refers to. `text` is the current text of the file at
+    /// `path`, which may have been edited since the symbols were collected. References are only
+    /// trusted if their text is still the same.
+    fn definition_at(&self, path: &Path, text: &str, pos: Position) -> Option<&Definition> {
+        let fname = self.files.name(path)?;
+        let offset = offset_of(text, pos);
+        self.references
+            .get(fname)?
+            .iter()
+            .filter(|r| {
+                let (start, end) = span_offsets(r.loc);
+                start <= offset && offset <= end && text.get(start..end) == self.files.source(r.loc)
+            })
+            .min_by_key(|r| {
+                let (start, end) = span_offsets(r.loc);
+                end - start
+            })
+            .map(|r| &self.defs[r.def])
+    }
+
+    pub fn definition(&self, path: &Path, text: &str, pos: Position) -> Option<Location> {
+        let def = self.definition_at(path, text, pos)?;
+        self.files.location(def.loc)
+    }
+
+    pub fn hover(
+        &self,
+        path: &Path,
+        text: &str,
+        pos: Position,
+        env: Option<&GlobalEnv>,
+    ) -> Option<Hover> {
+        let def = self.definition_at(path, text, pos)?;
+        let mut value = format!("```move\n{}\n```", def.detail);
+        if let Some(doc) = self.doc(env, def) {
+            value.push_str("\n\n");
+            value.push_str(&doc);
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        })
+    }
+
+    /// The completions at `pos`:
+    /// - after `M::`, the members of module `M` that can be used from the current module
+    /// - after `a.b.`, the fields of the struct that `a.b` holds
+    /// - otherwise, the members of the current module, the locals in scope and the modules
+    pub fn completion(
+        &self,
+        path: &Path,
+        text: &str,
+        pos: Position,
+        env: Option<&GlobalEnv>,
+    ) -> Vec<CompletionItem> {
+        let fname = match self.files.name(path) {
+            Some(fname) => fname,
+            None => return vec![],
+        };
+        let offset = offset_of(text, pos);
+        // The context of the completion is what precedes the name being typed
+        let context = text[..offset].trim_end_matches(is_name_char);
+        let current = self.enclosing_module(fname, offset);
+
+        let defs = if let Some(qualifier) = context.strip_suffix("::") {
+            let name = &qualifier[qualifier.trim_end_matches(is_name_char).len()..];
+            self.module_members(name, current)
+        } else if let Some(receiver) = context.strip_suffix('.') {
+            let start = receiver
+                .trim_end_matches(|c| is_name_char(c) || c == '.')
+                .len();
+            self.fields_of(&receiver[start..], fname, offset)
+        } else {
+            let mut defs = match current {
+                Some(module) => self.module_members("Self", Some(module)),
+                None => vec![],
+            };
+            defs.extend(self.locals_at(fname, offset).values().copied());
+            defs.extend(self.modules.values().map(|symbols| symbols.def));
+            defs
+        };
+        defs.into_iter()
+            .map(|id| {
+                let def = &self.defs[id];
+                let documentation = self.doc(env, def).map(Documentation::String);
+                CompletionItem {
+                    label: def.name.clone(),
+                    kind: Some(completion_kind(def.kind)),
+                    detail: Some(def.detail.clone()),
+                    documentation,
+                    ..CompletionItem::default()
+                }
+            })
+            .collect()
+    }
+
+    /// The module defined in `fname` whose definition most closely precedes `offset`
+    fn enclosing_module(&self, fname: &str, offset: usize) -> Option<&ModuleIdent_> {
+        self.modules
+            .iter()
+            .map(|(module, symbols)| (module, self.defs[symbols.def].loc))
+            .filter(|(_, loc)| loc.file() == fname && span_offsets(*loc).0 <= offset)
+            .max_by_key(|(_, loc)| span_offsets(*loc).0)
+            .map(|(module, _)| module)
+    }
+
+    /// The members of the modules called `name` that can be used from `current`
+    fn module_members(&self, name: &str, current: Option<&ModuleIdent_>) -> Vec<DefId> {
+        let mut members = vec![];
+        for (module, symbols) in &self.modules {
+            let is_current = Some(module) == current;
+            if !(name == "Self" && is_current || module.module.value() == name) {
+                continue;
+            }
+            let functions = symbols.functions.values();
+            members.extend(functions.filter(|id| is_current || self.defs[**id].public));
+            members.extend(symbols.structs.values());
+            if is_current {
+                members.extend(symbols.constants.values());
+            }
+        }
+        members
+    }
+
+    /// The fields of the struct held by `receiver`, a local followed by a path of fields
+    fn fields_of(&self, receiver: &str, fname: &str, offset: usize) -> Vec<DefId> {
+        let mut path = receiver.split('.');
+        let local = match path.next() {
+            Some(name) => self
+                .locals_at(fname, offset)
+                .into_iter()
+                .find(|(local, _)| *local == name),
+            None => None,
+        };
+        let mut struct_type = local.and_then(|(_, id)| self.defs[id].struct_type.clone());
+        for field in path {
+            struct_type = struct_type
+                .and_then(|key| self.fields(&key)?.get(field))
+                .and_then(|id| self.defs[*id].struct_type.clone());
+        }
+        struct_type
+            .and_then(|key| self.fields(&key))
+            .map(|fields| fields.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn fields(&self, (module, name): &StructKey) -> Option<&BTreeMap<String, DefId>> {
+        self.modules.get(module)?.fields.get(name)
+    }
+
+    /// The locals defined before `offset` in the function around it, by name. Only the latest
+    /// definition of a name is kept.
+    fn locals_at(&self, fname: &str, offset: usize) -> BTreeMap<&str, DefId> {
+        let mut locals = BTreeMap::new();
+        let scope = self.functions.get(fname).and_then(|functions| {
+            functions.iter().find(|f| {
+                let (start, end) = span_offsets(f.loc);
+                start <= offset && offset <= end
+            })
+        });
+        if let Some(scope) = scope {
+            for id in &scope.locals {
+                let def = &self.defs[*id];
+                if span_offsets(def.loc).1 <= offset {
+                    locals.insert(def.name.as_str(), *id);
+                }
+            }
+        }
+        locals
+    }
+
+    /// The documentation comment of `def`, if it has one
+    fn doc(&self, env: Option<&GlobalEnv>, def: &Definition) -> Option<String> {
+        let doc = match env.and_then(|env| self.model_doc(env, def)) {
+            Some(doc) if !doc.trim().is_empty() => doc,
+            _ => self.doc_comment(def.loc)?,
+        };
+        Some(doc.trim().to_string()).filter(|doc| !doc.is_empty())
+    }
+
+    /// The `///` comment on the lines right above `loc`
+    fn doc_comment(&self, loc: Loc) -> Option<String> {
+        let text = self.files.text(loc.file())?;
+        let line_start = text[..span_offsets(loc).0].rfind('\n')?;
+        let lines = text[..line_start]
+            .lines()
+            .rev()
+            .map(str::trim)
+            .take_while(|line| line.starts_with("///"))
+            .map(|line| line.trim_start_matches('/').trim())
+            .collect::<Vec<_>>();
+        Some(lines.into_iter().rev().collect::<Vec<_>>().join("\n"))
+    }
+
+    /// The documentation comment of `def`, as found by the move model
+    fn model_doc(&self, env: &GlobalEnv, def: &Definition) -> Option<String> {
+        let module = def.module.as_ref()?;
+        let address = match &module.address {
+            Address::Anonymous(sp!(_, bytes)) => *bytes,
+            Address::Named(name) => *self.addresses.get(&name.value)?,
+        };
+        let pool = env.symbol_pool();
+        let module_name = pool.make(module.module.value());
+        let module_env = env.find_module(&ModuleName::from_address_bytes_and_name(
+            address,
+            module_name,
+        ))?;
+        let name = pool.make(&def.name);
+        let doc = match def.kind {
+            DefKind::Module => module_env.get_doc().to_string(),
+            DefKind::Function => module_env.find_function(name)?.get_doc().to_string(),
+            DefKind::Struct => module_env.find_struct(name)?.get_doc().to_string(),
+            DefKind::Field => {
+                let struct_env = module_env.find_struct(pool.make(def.parent.as_ref()?))?;
+                let doc = struct_env.find_field(name)?.get_doc().to_string();
+                doc
+            }
+            DefKind::Constant => module_env.find_named_constant(name)?.get_doc().to_string(),
+            DefKind::Local => return None,
+        };
+        Some(doc)
+    }
+}
+
+//**************************************************************************************************
+// Function bodies
+//**************************************************************************************************
+
+/// The state of collecting the symbols of a module or a script
+struct Context<'a> {
+    symbols: &'a mut Symbols,
+    module: Option<ModuleIdent_>,
+    /// The constants of the current module or script
+    constants: BTreeMap<String, DefId>,
+    /// The locals in scope, in nested blocks
+    scopes: Vec<BTreeMap<String, DefId>>,
+    /// All locals of the current function
+    locals: Vec<DefId>,
+}
+
+impl<'a> Context<'a> {
+    fn new(
+        symbols: &'a mut Symbols,
+        module: Option<ModuleIdent_>,
+        constants: BTreeMap<String, DefId>,
+    ) -> Self {
+        Self {
+            symbols,
+            module,
+            constants,
+            scopes: vec![],
+            locals: vec![],
+        }
+    }
+
+    fn module(&mut self, mdef: &T::ModuleDefinition) {
+        for (_, _, sdef) in &mdef.structs {
+            if let StructFields::Defined(fields) = &sdef.fields {
+                for (_, _, (_, ty)) in fields {
+                    self.type_references(ty);
+                }
+            }
+        }
+        for (_, _, constant) in &mdef.constants {
+            self.constant(constant);
+        }
+        for (_, _, function) in &mdef.functions {
+            self.function(function);
+        }
+    }
+
+    fn script(&mut self, script: &T::Script) {
+        for (name, constant) in script.constants.key_cloned_iter() {
+            let detail = format!("const {}: {}", name, display_type(&constant.signature));
+            let id = self.symbols.define(Definition {
+                kind: DefKind::Constant,
+                name: name.to_string(),
+                loc: name.loc(),
+                detail,
+                module: None,
+                parent: None,
+                public: false,
+                struct_type: None,
+            });
+            self.constants.insert(name.to_string(), id);
+        }
+        for (_, _, constant) in &script.constants {
+            self.constant(constant);
+        }
+        let name = &script.function_name;
+        self.symbols.define(Definition {
+            kind: DefKind::Function,
+            name: name.to_string(),
+            loc: name.loc(),
+            detail: function_detail(name, &script.function, true),
+            module: None,
+            parent: None,
+            public: true,
+            struct_type: None,
+        });
+        self.function(&script.function);
+    }
+
+    fn constant(&mut self, constant: &T::Constant) {
+        self.type_references(&constant.signature);
+        self.exp(&constant.value);
+    }
+
+    fn function(&mut self, function: &T::Function) {
+        self.scopes.push(BTreeMap::new());
+        for (var, ty) in &function.signature.parameters {
+            self.type_references(ty);
+            self.declare_local(var, ty);
+        }
+        self.type_references(&function.signature.return_type);
+        if let Some(module) = self.module.clone() {
+            for (name, loc) in &function.acquires {
+                self.struct_reference(&module, name, *loc);
+            }
+        }
+        if let T::FunctionBody_::Defined(seq) = &function.body.value {
+            self.sequence(seq);
+        }
+        self.scopes.pop();
+
+        let locals = std::mem::take(&mut self.locals);
+        let loc = function.body.loc;
+        self.symbols
+            .functions
+            .entry(loc.file())
+            .or_default()
+            .push(FunctionScope { loc, locals });
+    }
+
+    fn declare_local(&mut self, var: &Var, ty: &Type) {
+        let name = var.value();
+        // Skip the locals introduced by the compiler
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return;
+        }
+        let id = self.symbols.define(Definition {
+            kind: DefKind::Local,
+            name: name.to_string(),
+            loc: var.loc(),
+            detail: format!("{}: {}", name, display_type(ty)),
+            module: None,
+            parent: None,
+            public: false,
+            struct_type: struct_of(ty),
+        });
+        self.scopes.last_mut().unwrap().insert(name.to_string(), id);
+        self.locals.push(id);
+    }
+
+    fn local_reference(&mut self, var: &Var) {
+        let def = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var.value()).copied());
+        if let Some(def) = def {
+            self.symbols.add_reference(var.loc(), def)
+        }
+    }
+
+    fn module_symbols(&self, module: &ModuleIdent_) -> Option<&ModuleSymbols> {
+        self.symbols.modules.get(module)
+    }
+
+    /// The location of `name` where it is used, somewhere within `site`. Member names that are not
+    /// qualified by a module are resolved through the aliases of the module, and carry the location
+    /// of their declaration instead of the location of their use.
+    fn use_loc(&self, name: &dyn Identifier, site: Loc) -> Option<Loc> {
+        let (start, end) = span_offsets(name.loc());
+        let (site_start, site_end) = span_offsets(site);
+        if name.loc().file() == site.file() && site_start <= start && end <= site_end {
+            return Some(name.loc());
+        }
+        let source = self.symbols.files.source(site)?;
+        let offset = source.match_indices(name.value()).find_map(|(idx, _)| {
+            let before = source[..idx].chars().next_back();
+            let after = source[idx + name.value().len()..].chars().next();
+            let bounded = |c: Option<char>| !matches!(c, Some(c) if is_name_char(c));
+            if bounded(before) && bounded(after) {
+                Some(site_start + idx)
+            } else {
+                None
+            }
+        })?;
+        let span = Span::new(offset as u32, (offset + name.value().len()) as u32);
+        self.symbols.files.loc(site.file(), span)
+    }
+
+    fn member_reference(&mut self, name: &dyn Identifier, site: Loc, def: Option<DefId>) {
+        if let (Some(def), Some(loc)) = (def, self.use_loc(name, site)) {
+            self.symbols.add_reference(loc, def)
+        }
+    }
+
+    fn function_reference(&mut self, module: &ModuleIdent_, name: &FunctionName, site: Loc) {
+        let def = self
+            .module_symbols(module)
+            .and_then(|symbols| symbols.functions.get(name.value()).copied());
+        self.member_reference(name, site, def)
+    }
+
+    fn struct_reference(&mut self, module: &ModuleIdent_, name: &StructName, site: Loc) {
+        let def = self
+            .module_symbols(module)
+            .and_then(|symbols| symbols.structs.get(name.value()).copied());
+        self.member_reference(name, site, def)
+    }
+
+    fn field_reference(&mut self, (module, name): &StructKey, field: &Field) {
+        let def = self
+            .module_symbols(module)
+            .and_then(|symbols| symbols.fields.get(name)?.get(field.value()).copied());
+        if let Some(def) = def {
+            self.symbols.add_reference(field.loc(), def)
+        }
+    }
+
+    fn constant_reference(&mut self, module: Option<&ModuleIdent>, name: &ConstantName, site: Loc) {
+        let def = match module {
+            Some(module) => self
+                .module_symbols(&module.value)
+                .and_then(|symbols| symbols.constants.get(name.value()).copied()),
+            None => self.constants.get(name.value()).copied(),
+        };
+        self.member_reference(name, site, def)
+    }
+
+    /// References to the structs named in `ty`
+    fn type_references(&mut self, ty: &Type) {
+        match &ty.value {
+            Type_::Ref(_, inner) => self.type_references(inner),
+            Type_::Apply(_, sp!(_, name), args) => {
+                if let TypeName_::ModuleType(module, name) = name {
+                    self.struct_reference(&module.value, name, ty.loc);
+                }
+                for arg in args {
+                    self.type_references(arg);
+                }
+            }
+            Type_::Unit | Type_::Param(_) | Type_::Var(_) | Type_::Anything => (),
+            Type_::UnresolvedError => (),
+        }
+    }
+
+    fn sequence(&mut self, seq: &T::Sequence) {
+        self.scopes.push(BTreeMap::new());
+        for item in seq {
+            match &item.value {
+                T::SequenceItem_::Seq(e) => self.exp(e),
+                T::SequenceItem_::Declare(lvalues) => self.lvalues(lvalues, true),
+                T::SequenceItem_::Bind(lvalues, _, e) => {
+                    self.exp(e);
+                    self.lvalues(lvalues, true)
+                }
+            }
+        }
+        self.scopes.pop();
+    }
+
+    /// Declare the locals bound by `lvalues`, or refer to them if they are assigned to
+    fn lvalues(&mut self, lvalues: &T::LValueList, declare: bool) {
+        for lvalue in &lvalues.value {
+            self.lvalue(lvalue, declare)
+        }
+    }
+
+    fn lvalue(&mut self, lvalue: &T::LValue, declare: bool) {
+        match &lvalue.value {
+            T::LValue_::Ignore => (),
+            T::LValue_::Var(var, ty) if declare => self.declare_local(var, ty),
+            T::LValue_::Var(var, _) => self.local_reference(var),
+            T::LValue_::Unpack(module, name, _, fields)
+            | T::LValue_::BorrowUnpack(_, module, name, _, fields) => {
+                self.struct_reference(&module.value, name, lvalue.loc);
+                let key = (module.value.clone(), name.to_string());
+                for (field, (_, (_, lvalue))) in fields.key_cloned_iter() {
+                    self.field_reference(&key, &field);
+                    self.lvalue(lvalue, declare);
+                }
+            }
+        }
+    }
+
+    fn exp(&mut self, e: &T::Exp) {
+        use T::UnannotatedExp_ as E;
+        match &e.exp.value {
+            E::Use(var) | E::Move { var, .. } | E::Copy { var, .. } | E::BorrowLocal(_, var) => {
+                self.local_reference(var)
+            }
+            E::Constant(module, name) => self.constant_reference(module.as_ref(), name, e.exp.loc),
+
+            E::ModuleCall(call) => {
+                self.function_reference(&call.module.value, &call.name, e.exp.loc);
+                self.exp(&call.arguments);
+            }
+            E::Builtin(builtin, arg) => {
+                use T::BuiltinFunction_ as B;
+                match &builtin.value {
+                    B::MoveTo(ty)
+                    | B::MoveFrom(ty)
+                    | B::BorrowGlobal(_, ty)
+                    | B::Exists(ty)
+                    | B::Freeze(ty) => self.type_references(ty),
+                    B::Assert => (),
+                }
+                self.exp(arg)
+            }
+
+            E::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                self.exp(if_false);
+            }
+            E::While(e1, e2) | E::Mutate(e1, e2) | E::BinopExp(e1, _, _, e2) => {
+                self.exp(e1);
+                self.exp(e2);
+            }
+            E::Loop { body: e, .. }
+            | E::Return(e)
+            | E::Abort(e)
+            | E::Dereference(e)
+            | E::UnaryExp(_, e)
+            | E::TempBorrow(_, e) => self.exp(e),
+            E::Block(seq) => self.sequence(seq),
+            E::Assign(lvalues, _, e) => {
+                self.exp(e);
+                self.lvalues(lvalues, false);
+            }
+
+            E::Pack(module, name, _, fields) => {
+                self.struct_reference(&module.value, name, e.exp.loc);
+                let key = (module.value.clone(), name.to_string());
+                for (field, (_, (_, e))) in fields.key_cloned_iter() {
+                    self.field_reference(&key, &field);
+                    self.exp(e);
+                }
+            }
+            E::ExpList(items) => {
+                for item in items {
+                    match item {
+                        T::ExpListItem::Single(e, _) | T::ExpListItem::Splat(_, e, _) => {
+                            self.exp(e)
+                        }
+                    }
+                }
+            }
+            E::Borrow(_, e, field) => {
+                self.exp(e);
+                if let Some(key) = struct_of(&e.ty) {
+                    self.field_reference(&key, field);
+                }
+            }
+            E::Cast(e, ty) | E::Annotate(e, ty) => {
+                self.exp(e);
+                self.type_references(ty);
+            }
+
+            E::Unit { .. } | E::Value(_) | E::Break | E::Continue | E::Spec(_, _) => (),
+            E::UnresolvedError => (),
+        }
+    }
+}
+
+//**************************************************************************************************
+// Utils
+//**************************************************************************************************
+
+fn span_offsets(loc: Loc) -> (usize, usize) {
+    (loc.span().start().to_usize(), loc.span().end().to_usize())
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn completion_kind(kind: DefKind) -> CompletionItemKind {
+    match kind {
+        DefKind::Module => CompletionItemKind::Module,
+        DefKind::Function => CompletionItemKind::Function,
+        DefKind::Struct => CompletionItemKind::Struct,
+        DefKind::Field => CompletionItemKind::Field,
+        DefKind::Constant => CompletionItemKind::Constant,
+        DefKind::Local => CompletionItemKind::Variable,
+    }
+}
+
+/// The struct that a value of type `ty` holds, directly or through a reference
+fn struct_of(ty: &Type) -> Option<StructKey> {
+    match &ty.value {
+        Type_::Ref(_, inner) => struct_of(inner),
+        Type_::Apply(_, sp!(_, TypeName_::ModuleType(module, name)), _) => {
+            Some((module.value.clone(), name.to_string()))
+        }
+        _ => None,
+    }
+}
+
+fn display_type(ty: &Type) -> String {
+    match &ty.value {
+        Type_::Unit => "()".to_string(),
+        Type_::Ref(true, inner) => format!("&mut {}", display_type(inner)),
+        Type_::Ref(false, inner) => format!("&{}", display_type(inner)),
+        Type_::Param(tparam) => tparam.user_specified_name.to_string(),
+        Type_::Apply(_, sp!(_, name), args) => {
+            let args = args.iter().map(display_type).collect::<Vec<_>>();
+            match name {
+                TypeName_::Multiple(_) => format!("({})", args.join(", ")),
+                TypeName_::Builtin(builtin) => with_type_arguments(builtin.to_string(), &args),
+                TypeName_::ModuleType(module, name) => {
+                    with_type_arguments(format!("{}::{}", module.value.module, name), &args)
+                }
+            }
+        }
+        Type_::Var(_) | Type_::Anything | Type_::UnresolvedError => "_".to_string(),
+    }
+}
+
+fn with_type_arguments(name: String, args: &[String]) -> String {
+    if args.is_empty() {
+        name
+    } else {
+        format!("{}<{}>", name, args.join(", "))
+    }
+}
+
+fn display_type_parameters(tparams: &[TParam]) -> String {
+    if tparams.is_empty() {
+        return String::new();
+    }
+    let tparams = tparams
+        .iter()
+        .map(|tparam| {
+            let abilities = tparam
+                .abilities
+                .iter()
+                .map(|ability| ability.to_string())
+                .collect::<Vec<_>>();
+            if abilities.is_empty() {
+                tparam.user_specified_name.to_string()
+            } else {
+                format!("{}: {}", tparam.user_specified_name, abilities.join(" + "))
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", tparams.join(", "))
+}
+
+/// The signature of `function`. The compiler drops the bodies of functions in dependencies, so they
+/// can only be told apart from native functions in `source` modules.
+fn function_detail(name: &FunctionName, function: &T::Function, source: bool) -> String {
+    let mut detail = String::new();
+    if source && matches!(function.body.value, T::FunctionBody_::Native) {
+        detail.push_str("native ");
+    }
+    if !matches!(function.visibility, Visibility::Internal) {
+        detail.push_str(&format!("{} ", function.visibility));
+    }
+    let signature = &function.signature;
+    let parameters = signature
+        .parameters
+        .iter()
+        .map(|(var, ty)| format!("{}: {}", var, display_type(ty)))
+        .collect::<Vec<_>>();
+    detail.push_str(&format!(
+        "fun {}{}({})",
+        name,
+        display_type_parameters(&signature.type_parameters),
+        parameters.join(", ")
+    ));
+    if !matches!(signature.return_type.value, Type_::Unit) {
+        detail.push_str(&format!(": {}", display_type(&signature.return_type)));
+    }
+    detail
+}
+
+fn struct_detail(name: &StructName, sdef: &StructDefinition) -> String {
+    let mut detail = format!(
+        "struct {}{}",
+        name,
+        display_type_parameters(&sdef.type_parameters)
+    );
+    let abilities = sdef
+        .abilities
+        .iter()
+        .map(|ability| ability.to_string())
+        .collect::<Vec<_>>();
+    if !abilities.is_empty() {
+        detail.push_str(&format!(" has {}", abilities.join(", ")));
+    }
+    detail
+}
@@ -0,0 +1,68 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The contents of the files open in the editor. While a file is open, the editor owns its
+//! contents, and they take precedence over the contents of the file on disk.
+
+use crate::files::offset_of;
+use lsp_types::TextDocumentContentChangeEvent;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Default)]
+pub struct VirtualFileSystem {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl VirtualFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, path: PathBuf, text: String) {
+        self.files.insert(path, text);
+    }
+
+    pub fn close(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Apply the edits in `changes`, in order. A change without a range replaces the whole file.
+    pub fn change(&mut self, path: &Path, changes: Vec<TextDocumentContentChangeEvent>) {
+        let text = match self.files.get_mut(path) {
+            Some(text) => text,
+            None => return,
+        };
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = offset_of(text, range.start);
+                    let end = offset_of(text, range.end).max(start);
+                    text.replace_range(start..end, &change.text);
+                }
+                None => *text = change.text,
+            }
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.files.get(path).map(String::as_str)
+    }
+
+    /// The contents of the file at `path`, from the editor if it is open and from disk otherwise
+    pub fn read(&self, path: &Path) -> io::Result<String> {
+        match self.get(path) {
+            Some(text) => Ok(text.to_string()),
+            None => fs::read_to_string(path),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.files
+            .iter()
+            .map(|(path, text)| (path.as_path(), text.as_str()))
+    }
+}
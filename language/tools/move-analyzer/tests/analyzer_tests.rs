@@ -0,0 +1,119 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use lsp_types::{HoverContents, MarkupContent, Position, Range, TextDocumentContentChangeEvent};
+use move_analyzer::{
+    analysis::{canonical_path, Analyzer, Config},
+    files::{offset_of, position_of},
+    symbols::Symbols,
+    vfs::VirtualFileSystem,
+};
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+const COIN: &str = "address 0x42 {
+module M {
+    /// A coin
+    struct Coin has copy, drop { value: u64 }
+
+    public fun make(value: u64): Coin { Coin { value } }
+
+    fun get(c: &Coin): u64 {
+        let x = make(1);
+        x.value + c.value
+    }
+}
+}
+";
+
+/// An analyzer for a workspace with `COIN` as its only source, and the path of that source
+fn setup() -> (TempDir, Analyzer, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = canonical_path(dir.path());
+    let path = root.join("M.move");
+    fs::write(&path, COIN).unwrap();
+    let config = Config::new(Some(&root), Some(json!({ "dependencies": [] }))).unwrap();
+    (dir, Analyzer::new(config).unwrap(), path)
+}
+
+fn hover(symbols: &Symbols, analyzer: &Analyzer, path: &Path, text: &str, pos: Position) -> String {
+    let hover = symbols
+        .hover(path, text, pos, analyzer.model())
+        .expect("nothing to hover");
+    match hover.contents {
+        HoverContents::Markup(MarkupContent { value, .. }) => value,
+        contents => panic!("unexpected hover {:?}", contents),
+    }
+}
+
+#[test]
+fn offsets_and_positions() {
+    let text = "ab\n\u{1F600}c\nd";
+    // The emoji takes 4 bytes, and 2 UTF-16 code units
+    assert_eq!(offset_of(text, Position::new(1, 2)), 7);
+    assert_eq!(position_of(text, 7), Position::new(1, 2));
+    assert_eq!(offset_of(text, Position::new(0, 10)), 2);
+    assert_eq!(offset_of(text, Position::new(5, 0)), text.len());
+}
+
+#[test]
+fn navigation() {
+    let (_dir, mut analyzer, path) = setup();
+    let vfs = VirtualFileSystem::new();
+    let analysis = analyzer.analyze(&vfs, true).unwrap();
+    assert!(analysis.diagnostics.is_empty());
+    let symbols = analysis.symbols.unwrap();
+
+    let call = Position::new(8, 17);
+    let value = hover(&symbols, &analyzer, &path, COIN, call);
+    assert!(value.contains("public fun make(value: u64): M::Coin"));
+    let location = symbols.definition(&path, COIN, call).unwrap();
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(5, 15), Position::new(5, 19))
+    );
+
+    let value = hover(&symbols, &analyzer, &path, COIN, Position::new(7, 16));
+    assert!(value.contains("struct Coin has copy, drop"));
+    assert!(value.contains("A coin"));
+
+    let fields = symbols.completion(&path, COIN, Position::new(9, 10), analyzer.model());
+    let labels: Vec<_> = fields.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["value"]);
+}
+
+#[test]
+fn edits_of_open_files() {
+    let (_dir, mut analyzer, path) = setup();
+    let mut vfs = VirtualFileSystem::new();
+    vfs.open(path.clone(), COIN.to_string());
+    let symbols = analyzer.analyze(&vfs, true).unwrap().symbols.unwrap();
+
+    // Use an unbound local instead of `x`
+    let change = TextDocumentContentChangeEvent {
+        range: Some(Range::new(Position::new(9, 8), Position::new(9, 9))),
+        range_length: None,
+        text: "y".to_string(),
+    };
+    vfs.change(&path, vec![change]);
+    let analysis = analyzer.analyze(&vfs, false).unwrap();
+    assert!(analysis.symbols.is_none());
+    assert!(!analysis.diagnostics[&path].is_empty());
+
+    // The previous symbols still answer for the text that did not change
+    let text = vfs.get(&path).unwrap();
+    let value = hover(&symbols, &analyzer, &path, text, Position::new(9, 18));
+    assert!(value.contains("c: &M::Coin"));
+    assert!(symbols
+        .hover(&path, text, Position::new(9, 8), None)
+        .is_none());
+
+    vfs.close(&path);
+    let analysis = analyzer.analyze(&vfs, false).unwrap();
+    assert!(analysis.diagnostics.is_empty());
+    assert!(analysis.symbols.is_some());
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::on_disk_state_view::OnDiskStateView;
+use crate::{on_disk_state_view::OnDiskStateView, resolution::ResolvedGraph};
 use move_binary_format::{
     access::ModuleAccess,
     compatibility::Compatibility,
@@ -19,12 +19,25 @@ use move_core_types::{
     transaction_argument::{convert_txn_args, TransactionArgument},
     vm_status::{AbortLocation, StatusCode, VMStatus},
 };
-use move_lang::{self, compiled_unit::CompiledUnit, shared::Flags, MOVE_COMPILED_EXTENSION};
-use move_vm_runtime::{logging::NoContextLog, move_vm::MoveVM};
+use move_debugger::{source::SourceIndex, Interface};
+use move_lang::{
+    self,
+    compiled_unit::CompiledUnit,
+    errors::{report_errors, Errors, FilesSourceText},
+    formatter,
+    shared::Flags,
+    MOVE_COMPILED_EXTENSION,
+};
+use move_vm_runtime::{gas_profiler, logging::NoContextLog, move_vm::MoveVM};
 use move_vm_types::gas_schedule::GasStatus;
 
 use anyhow::{anyhow, bail, Result};
-use std::{collections::BTreeMap, ffi::OsStr, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    fs,
+    path::Path,
+};
 
 /// Return `true` if `path` is a Move bytecode file based on its extension
 fn is_bytecode_file(path: &Path) -> bool {
@@ -102,6 +115,80 @@ pub fn publish(
         }
     }
 
+    publish_modules(state, modules, ignore_breaking_changes, verbose)
+}
+
+/// Type check the package `graph` was resolved from, along with all of its dependencies.
+/// `lib_sources` holds the sources of the library modules available to every package.
+pub fn check_package(graph: &ResolvedGraph, lib_sources: &[String], verbose: bool) -> Result<()> {
+    if verbose {
+        println!(
+            "Checking package {} and its dependencies...",
+            graph.root().manifest.name
+        );
+    }
+    graph.compile(lib_sources)?;
+    Ok(())
+}
+
+/// Compile the package `graph` was resolved from, along with all of its dependencies, and publish
+/// the modules of every package in dependency order
+pub fn publish_package(
+    state: &OnDiskStateView,
+    graph: &ResolvedGraph,
+    lib_sources: &[String],
+    republish: bool,
+    ignore_breaking_changes: bool,
+    verbose: bool,
+) -> Result<()> {
+    if verbose {
+        println!(
+            "Compiling package {} and its dependencies...",
+            graph.root().manifest.name
+        );
+    }
+
+    let mut modules = vec![];
+    for (package, units) in graph.compile(lib_sources)? {
+        for unit in units {
+            match unit {
+                CompiledUnit::Script { loc, .. } => {
+                    if verbose {
+                        println!(
+                            "Warning: Found script in package {}. But scripts cannot be \
+                             published. Script found in: {}",
+                            package,
+                            loc.file()
+                        )
+                    }
+                }
+                CompiledUnit::Module { module, .. } => {
+                    if !republish && state.has_module(&module.self_id()) {
+                        bail!(
+                            "Module {} of package {} already exists in global storage",
+                            module.self_id(),
+                            package
+                        )
+                    }
+                    modules.push(module)
+                }
+            }
+        }
+    }
+    if verbose {
+        println!("Found and compiled {} modules", modules.len())
+    }
+
+    publish_modules(state, modules, ignore_breaking_changes, verbose)
+}
+
+/// Publish `modules` in global storage, in the given order
+fn publish_modules(
+    state: &OnDiskStateView,
+    modules: Vec<CompiledModule>,
+    ignore_breaking_changes: bool,
+    verbose: bool,
+) -> Result<()> {
     // use the the publish_module API frm the VM if we do not allow breaking changes
     if !ignore_breaking_changes {
         let vm = MoveVM::new();
@@ -162,17 +249,20 @@ pub fn run(
     vm_type_args: Vec<TypeTag>,
     gas_budget: Option<u64>,
     dry_run: bool,
+    debug: Option<DebugOptions>,
+    profile_gas: Option<String>,
     verbose: bool,
 ) -> Result<()> {
+    /// Compile the script in `script_file`, and return it with the unit holding its source map
     fn compile_script(
         state: &OnDiskStateView,
         script_file: &str,
         verbose: bool,
-    ) -> Result<Option<CompiledScript>> {
+    ) -> Result<(FilesSourceText, Option<(CompiledScript, CompiledUnit)>)> {
         if verbose {
             println!("Compiling transaction script...")
         }
-        let (_files, compiled_units) = move_lang::move_compile_and_report(
+        let (files, compiled_units) = move_lang::move_compile_and_report(
             &[script_file.to_string()],
             &[state.interface_files_dir()?],
             None,
@@ -182,11 +272,11 @@ pub fn run(
         let mut script_opt = None;
         for c in compiled_units {
             match c {
-                CompiledUnit::Script { script, .. } => {
+                CompiledUnit::Script { ref script, .. } => {
                     if script_opt.is_some() {
                         bail!("Error: Found more than one script")
                     }
-                    script_opt = Some(script)
+                    script_opt = Some((script.clone(), c))
                 }
                 CompiledUnit::Module { ident, .. } => {
                     if verbose {
@@ -200,13 +290,14 @@ pub fn run(
             }
         }
 
-        Ok(script_opt)
+        Ok((files, script_opt))
     }
 
     let path = Path::new(script_file);
     if !path.exists() {
         bail!("Script file {:?} does not exist", path)
     };
+    let mut script_source = None;
     let bytecode = if is_bytecode_file(path) {
         assert!(
             state.is_module_path(path) || !contains_module(path),
@@ -218,11 +309,12 @@ move run` must be applied to a module inside `storage/`",
         fs::read(path)?
     } else {
         // script source file; compile first and then extract bytecode
-        let script_opt = compile_script(&state, script_file, verbose)?;
+        let (files, script_opt) = compile_script(&state, script_file, verbose)?;
         match script_opt {
-            Some(script) => {
+            Some((script, unit)) => {
                 let mut script_bytes = vec![];
                 script.serialize(&mut script_bytes)?;
+                script_source = Some((files, unit));
                 script_bytes
             }
             None => bail!("Unable to find script in file {:?}", script_file),
@@ -236,11 +328,31 @@ move run` must be applied to a module inside `storage/`",
     // TODO: parse Value's directly instead of going through the indirection of TransactionArgument?
     let vm_args: Vec<Vec<u8>> = convert_txn_args(&txn_args);
 
+    let gas_budget = match gas_budget {
+        // Unmetered execution charges no gas, so there would be nothing to profile
+        None if profile_gas.is_some() => Some(max_gas_budget() - 1),
+        gas_budget => gas_budget,
+    };
+
     let vm = MoveVM::new();
     let mut gas_status = get_gas_status(gas_budget)?;
     let log_context = NoContextLog::new();
     let mut session = vm.new_session(state);
 
+    let debug_handle = match debug {
+        Some(options) => {
+            let index = debug_index(state, &options.sources, script_source)?;
+            Some(move_debugger::start(index, &options.interface)?)
+        }
+        None => None,
+    };
+    if profile_gas.is_some() {
+        if !move_vm_runtime::DEBUGGING_ENABLED {
+            bail!("Release builds only profile gas with the `debugging` feature");
+        }
+        gas_profiler::start();
+    }
+
     let script_type_parameters = vec![];
     let script_parameters = vec![];
     let res = match script_name_opt {
@@ -269,6 +381,14 @@ move run` must be applied to a module inside `storage/`",
             &log_context,
         ),
     };
+    if let Some(handle) = debug_handle {
+        handle.finish();
+    }
+    if let Some(path) = profile_gas {
+        let profile = gas_profiler::finish();
+        print!("{}", profile);
+        profile.write_folded(fs::File::create(&path)?)?;
+    }
 
     if let Err(err) = res {
         explain_execution_error(
@@ -289,12 +409,65 @@ move run` must be applied to a module inside `storage/`",
     }
 }
 
+/// Where `move run` takes debugger commands from, and the sources of the modules to debug
+pub struct DebugOptions {
+    pub interface: Interface,
+    /// Sources of modules in storage. Modules they no longer compile to are debugged without
+    /// sources.
+    pub sources: Vec<String>,
+}
+
+/// Build the debugger's map from the modules in storage and the script to run back to `sources`
+/// and the script's source
+fn debug_index(
+    state: &OnDiskStateView,
+    sources: &[String],
+    script_source: Option<(FilesSourceText, CompiledUnit)>,
+) -> Result<SourceIndex> {
+    let (mut files, units) = if sources.is_empty() {
+        (FilesSourceText::new(), vec![])
+    } else {
+        move_lang::move_compile_and_report(
+            sources,
+            &[state.interface_files_dir()?],
+            None,
+            Flags::empty().set_sources_shadow_deps(true),
+        )?
+    };
+    if let Some((script_files, _)) = &script_source {
+        files.extend(
+            script_files
+                .iter()
+                .map(|(fname, text)| (*fname, text.clone())),
+        );
+    }
+    let mut index = SourceIndex::new(files);
+    let compiled: BTreeMap<_, _> = units
+        .iter()
+        .filter_map(|unit| match unit {
+            CompiledUnit::Module {
+                module, source_map, ..
+            } => Some((module.self_id(), (module, source_map))),
+            CompiledUnit::Script { .. } => None,
+        })
+        .collect();
+    for module in state.get_code_cache()?.all_modules() {
+        let source_map = match compiled.get(&module.self_id()) {
+            Some((compiled_module, source_map)) if *compiled_module == module => Some(*source_map),
+            _ => None,
+        };
+        index.add_module(module, source_map);
+    }
+    if let Some((_, unit)) = &script_source {
+        index.add_unit(unit);
+    }
+    Ok(index)
+}
+
 fn get_gas_status(gas_budget: Option<u64>) -> Result<GasStatus<'static>> {
     let gas_status = if let Some(gas_budget) = gas_budget {
         let gas_schedule = &vm_genesis::genesis_gas_schedule::INITIAL_GAS_SCHEDULE;
-        let max_gas_budget = u64::MAX
-            .checked_div(gas_schedule.gas_constants.gas_unit_scaling_factor)
-            .unwrap();
+        let max_gas_budget = max_gas_budget();
         if gas_budget >= max_gas_budget {
             bail!("Gas budget set too high; maximum is {}", max_gas_budget)
         }
@@ -306,6 +479,17 @@ fn get_gas_status(gas_budget: Option<u64>) -> Result<GasStatus<'static>> {
     Ok(gas_status)
 }
 
+/// The bound on gas budgets, in gas units of the genesis gas schedule
+fn max_gas_budget() -> u64 {
+    u64::MAX
+        .checked_div(
+            vm_genesis::genesis_gas_schedule::INITIAL_GAS_SCHEDULE
+                .gas_constants
+                .gas_unit_scaling_factor,
+        )
+        .unwrap()
+}
+
 fn explain_publish_changeset(changeset: &ChangeSet, state: &OnDiskStateView) {
     // publish effects should contain no resources
     assert!(changeset.resources().next().is_none());
@@ -710,6 +894,48 @@ pub fn view(state: &OnDiskStateView, file: &str) -> Result<()> {
     Ok(())
 }
 
+/// Format the Move source files in `files`, which may also name directories holding them. With
+/// `check` set, the files are left untouched and the command fails if any of them is not formatted.
+pub fn fmt(files: &[String], check: bool, verbose: bool) -> Result<()> {
+    let files = move_lang::find_move_filenames(files, true)?;
+    let mut sources: FilesSourceText = HashMap::new();
+    let mut errors: Errors = vec![];
+    let mut formatted = vec![];
+    for file in files {
+        let fname: &'static str = Box::leak(Box::new(file));
+        let source = fs::read_to_string(fname)?;
+        match formatter::format_string(fname, &source) {
+            Ok(output) => formatted.push((fname, source != output, output)),
+            Err(errs) => errors.extend(errs),
+        }
+        sources.insert(fname, source);
+    }
+    // nothing is rewritten unless all the files parse
+    if !errors.is_empty() {
+        report_errors(sources, errors)
+    }
+
+    let mut unformatted = 0;
+    for (fname, changed, output) in formatted {
+        if !changed {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", fname);
+            unformatted += 1;
+        } else {
+            if verbose {
+                println!("Formatting {}", fname);
+            }
+            fs::write(fname, output)?;
+        }
+    }
+    if unformatted > 0 {
+        bail!("{} file(s) are not formatted", unformatted)
+    }
+    Ok(())
+}
+
 /// Run sanity checks on storage and build dirs. This is primarily intended for testing the CLI;
 /// doctor should never fail unless `publish --ignore-breaking changes` is used or files under
 /// `storage` or `build` are modified manually. This runs the following checks:
@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod commands;
+pub mod manifest;
 pub mod mode;
 pub mod on_disk_state_view;
 pub mod package;
+pub mod resolution;
 pub mod test;
 
 /// Default directory where saved Move resources live
@@ -16,6 +18,9 @@ pub const DEFAULT_SOURCE_DIR: &str = "src";
 /// Default directory where Move packages live under build_dir
 pub const DEFAULT_PACKAGE_DIR: &str = "package";
 
+/// Default directory where git dependencies of Move packages are checked out under build_dir
+pub const DEFAULT_GIT_DEPENDENCY_DIR: &str = "git";
+
 /// Default dependency inclusion mode
 pub const DEFAULT_DEP_MODE: &str = "stdlib";
 
@@ -4,13 +4,21 @@
 use anyhow::Result;
 use move_cli::{
     commands,
+    manifest::{MANIFEST_FILE_NAME, PACKAGE_SOURCE_DIR},
     mode::{Mode, ModeType},
-    test, DEFAULT_BUILD_DIR, DEFAULT_DEP_MODE, DEFAULT_SOURCE_DIR, DEFAULT_STORAGE_DIR,
+    resolution::ResolvedGraph,
+    test, DEFAULT_BUILD_DIR, DEFAULT_DEP_MODE, DEFAULT_GIT_DEPENDENCY_DIR, DEFAULT_PACKAGE_DIR,
+    DEFAULT_SOURCE_DIR, DEFAULT_STORAGE_DIR,
 };
 use move_core_types::{
     language_storage::TypeTag, parser, transaction_argument::TransactionArgument,
 };
-use std::{fs, path::Path};
+use move_debugger::Interface;
+use move_lang::path_to_string;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -43,10 +51,12 @@ pub struct Move {
 
 #[derive(StructOpt)]
 pub enum Command {
-    /// Type check and verify the specified script and modules against the modules in `storage`
+    /// Type check and verify the specified script and modules against the modules in `storage`.
+    /// If given a package directory containing a `Move.toml` manifest, or run from one, type check
+    /// the package and its dependencies instead.
     #[structopt(name = "check")]
     Check {
-        /// The source files to check
+        /// The source files to check, or a package directory
         #[structopt(
             name = "PATH_TO_SOURCE_FILE",
             default_value = DEFAULT_SOURCE_DIR,
@@ -56,10 +66,12 @@ pub enum Command {
         #[structopt(long = "no-republish")]
         no_republish: bool,
     },
-    /// Compile the specified modules and publish the resulting bytecodes in global storage
+    /// Compile the specified modules and publish the resulting bytecodes in global storage.
+    /// If given a package directory containing a `Move.toml` manifest, or run from one, publish
+    /// the package and its dependencies instead.
     #[structopt(name = "publish")]
     Publish {
-        /// The source files containing modules to publish
+        /// The source files containing modules to publish, or a package directory
         #[structopt(
             name = "PATH_TO_SOURCE_FILE",
             default_value = DEFAULT_SOURCE_DIR,
@@ -114,8 +126,24 @@ pub enum Command {
         /// deleted resources) will NOT be committed to disk.
         #[structopt(long = "dry-run", short = "n")]
         dry_run: bool,
+        /// Run `script_file` in a debugger, which reads commands from the standard input
+        #[structopt(long = "debug")]
+        debug: bool,
+        /// Run `script_file` in a debugger, which a Debug Adapter Protocol client drives by
+        /// connecting to this address, as in 127.0.0.1:4711
+        #[structopt(long = "debug-adapter", conflicts_with = "debug")]
+        debug_adapter: Option<String>,
+        /// Sources of the modules in `storage` to debug, in addition to the library modules.
+        /// Modules whose sources changed since they were published are debugged without sources.
+        #[structopt(long = "debug-sources")]
+        debug_sources: Vec<String>,
+        /// Print where execution spent gas, and write it to this file as folded stacks for flame
+        /// graph tools. Without a `gas-budget`, execution is metered with the largest budget.
+        #[structopt(long = "profile-gas")]
+        profile_gas: Option<String>,
     },
-    /// Run expected value tests using the given batch file
+    /// Run expected value tests using the given batch file. Package manifests are not resolved:
+    /// a package directory is run as a plain test directory
     #[structopt(name = "test")]
     Test {
         /// a directory path in which all the tests will be executed
@@ -129,6 +157,20 @@ pub enum Command {
         #[structopt(long = "create")]
         create: bool,
     },
+    /// Format Move source files in the canonical style. If given a package directory containing a
+    /// `Move.toml` manifest, or run from one, format the sources of the package instead.
+    #[structopt(name = "fmt")]
+    Fmt {
+        /// The source files to format, or directories holding them
+        #[structopt(
+            name = "PATH_TO_SOURCE_FILE",
+            default_value = DEFAULT_SOURCE_DIR,
+        )]
+        source_files: Vec<String>,
+        /// If set, do not rewrite any file, but fail if some file is not formatted
+        #[structopt(long = "check")]
+        check: bool,
+    },
     /// View Move resources, events files, and modules stored on disk
     #[structopt(name = "view")]
     View {
@@ -173,6 +215,32 @@ pub enum AnalysisCommand {
     },
 }
 
+/// Return the root directory of the package designated by `source_files`, if any: either a single
+/// directory holding a package manifest, or the default source directory when the current
+/// directory holds a package manifest
+fn package_dir(source_files: &[String]) -> Option<PathBuf> {
+    match source_files {
+        [path] if Path::new(path).join(MANIFEST_FILE_NAME).is_file() => Some(PathBuf::from(path)),
+        [path] if path == DEFAULT_SOURCE_DIR && Path::new(MANIFEST_FILE_NAME).is_file() => {
+            Some(PathBuf::from("."))
+        }
+        _ => None,
+    }
+}
+
+/// Resolve the package rooted at `package_dir` and return it together with the sources of the
+/// library modules of `mode`
+fn resolve_package(
+    mode: &Mode,
+    build_dir: &str,
+    package_dir: &Path,
+) -> Result<(ResolvedGraph, Vec<String>)> {
+    let build_dir = Path::new(build_dir);
+    let graph = ResolvedGraph::resolve(package_dir, &build_dir.join(DEFAULT_GIT_DEPENDENCY_DIR))?;
+    let lib_sources = mode.source_files(&build_dir.join(DEFAULT_PACKAGE_DIR))?;
+    Ok((graph, lib_sources))
+}
+
 fn main() -> Result<()> {
     let move_args = Move::from_args();
     let mode = Mode::new(move_args.mode);
@@ -183,7 +251,14 @@ fn main() -> Result<()> {
             no_republish,
         } => {
             let state = mode.prepare_state(&move_args.build_dir, &move_args.storage_dir)?;
-            commands::check(&state, !*no_republish, &source_files, move_args.verbose)
+            match package_dir(source_files) {
+                Some(package_dir) => {
+                    let (graph, lib_sources) =
+                        resolve_package(&mode, &move_args.build_dir, &package_dir)?;
+                    commands::check_package(&graph, &lib_sources, move_args.verbose)
+                }
+                None => commands::check(&state, !*no_republish, &source_files, move_args.verbose),
+            }
         }
         Command::Publish {
             source_files,
@@ -191,13 +266,27 @@ fn main() -> Result<()> {
             ignore_breaking_changes,
         } => {
             let state = mode.prepare_state(&move_args.build_dir, &move_args.storage_dir)?;
-            commands::publish(
-                &state,
-                source_files,
-                !*no_republish,
-                *ignore_breaking_changes,
-                move_args.verbose,
-            )
+            match package_dir(source_files) {
+                Some(package_dir) => {
+                    let (graph, lib_sources) =
+                        resolve_package(&mode, &move_args.build_dir, &package_dir)?;
+                    commands::publish_package(
+                        &state,
+                        &graph,
+                        &lib_sources,
+                        !*no_republish,
+                        *ignore_breaking_changes,
+                        move_args.verbose,
+                    )
+                }
+                None => commands::publish(
+                    &state,
+                    source_files,
+                    !*no_republish,
+                    *ignore_breaking_changes,
+                    move_args.verbose,
+                ),
+            }
         }
         Command::Run {
             script_file,
@@ -207,8 +296,26 @@ fn main() -> Result<()> {
             type_args,
             gas_budget,
             dry_run,
+            debug,
+            debug_adapter,
+            debug_sources,
+            profile_gas,
         } => {
             let state = mode.prepare_state(&move_args.build_dir, &move_args.storage_dir)?;
+            let interface = match debug_adapter {
+                Some(address) => Some(Interface::Adapter(address.clone())),
+                None if *debug => Some(Interface::Repl),
+                None => None,
+            };
+            let debug = match interface {
+                Some(interface) => {
+                    let package_dir = Path::new(&move_args.build_dir).join(DEFAULT_PACKAGE_DIR);
+                    let mut sources = mode.source_files(&package_dir)?;
+                    sources.extend(debug_sources.iter().cloned());
+                    Some(commands::DebugOptions { interface, sources })
+                }
+                None => None,
+            };
             commands::run(
                 &state,
                 script_file,
@@ -218,6 +325,8 @@ fn main() -> Result<()> {
                 type_args.to_vec(),
                 *gas_budget,
                 *dry_run,
+                debug,
+                profile_gas.clone(),
                 move_args.verbose,
             )
         }
@@ -235,6 +344,16 @@ fn main() -> Result<()> {
             &std::env::current_exe()?.to_string_lossy(),
             *track_cov,
         ),
+        Command::Fmt {
+            source_files,
+            check,
+        } => match package_dir(source_files) {
+            Some(package_dir) => {
+                let sources = path_to_string(&package_dir.join(PACKAGE_SOURCE_DIR))?;
+                commands::fmt(&[sources], *check, move_args.verbose)
+            }
+            None => commands::fmt(source_files, *check, move_args.verbose),
+        },
         Command::View { file } => {
             let state = mode.prepare_state(&move_args.build_dir, &move_args.storage_dir)?;
             commands::view(&state, file)
@@ -0,0 +1,201 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `Move.toml` manifest of a Move package.
+//!
+//! A package is a directory holding a `Move.toml` manifest and a `sources` directory with the
+//! package's `.move` files. The manifest looks like
+//!
+//! ```toml
+//! [package]
+//! name = "Example"
+//! version = "0.1.0"
+//!
+//! [addresses]
+//! Example = "0x42"
+//!
+//! [dependencies]
+//! Shared = { local = "../shared" }
+//! Remote = { git = "../remote-repo", rev = "main", subdir = "packages/remote" }
+//! ```
+//!
+//! The names under `[dependencies]` are the names of the depended-on packages. A `local`
+//! dependency points to a package directory, relative to the depending package. A `git`
+//! dependency is cloned from the given repository, which may also be a path on the local file
+//! system, and checked out at `rev`; `subdir` locates the package inside the repository.
+
+use anyhow::{bail, format_err, Context, Result};
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// File name of the manifest in the root directory of a package
+pub const MANIFEST_FILE_NAME: &str = "Move.toml";
+
+/// Directory holding the source files of a package, relative to its root directory
+pub const PACKAGE_SOURCE_DIR: &str = "sources";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Dependency {
+    /// A package on the local file system
+    Local(PathBuf),
+    /// A package inside a git repository, checked out at `rev`
+    Git {
+        url: String,
+        rev: String,
+        subdir: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: Version,
+    /// Named address assignments of the package
+    pub addresses: BTreeMap<String, AccountAddress>,
+    /// Dependencies of the package, keyed by package name
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    package: RawPackageInfo,
+    #[serde(default)]
+    addresses: BTreeMap<String, String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, RawDependency>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPackageInfo {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDependency {
+    local: Option<PathBuf>,
+    git: Option<String>,
+    rev: Option<String>,
+    subdir: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// Read the manifest of the package rooted at `package_dir`
+    pub fn read(package_dir: &Path) -> Result<Self> {
+        let path = package_dir.join(MANIFEST_FILE_NAME);
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Unable to read {:?}", path))?;
+        contents
+            .parse()
+            .with_context(|| format!("Invalid package manifest {:?}", path))
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw: RawManifest = toml::from_str(s)?;
+        check_name(&raw.package.name)?;
+
+        let mut addresses = BTreeMap::new();
+        for (name, value) in raw.addresses {
+            check_name(&name)?;
+            let address = AccountAddress::from_hex_literal(&value).map_err(|_| {
+                format_err!("Invalid value for named address `{}`: {}", name, value)
+            })?;
+            addresses.insert(name, address);
+        }
+
+        let mut dependencies = BTreeMap::new();
+        for (name, dep) in raw.dependencies {
+            check_name(&name)?;
+            let dep = match dep {
+                RawDependency {
+                    local: Some(path),
+                    git: None,
+                    rev: None,
+                    subdir: None,
+                } => Dependency::Local(path),
+                RawDependency {
+                    local: None,
+                    git: Some(url),
+                    rev: Some(rev),
+                    subdir,
+                } => Dependency::Git { url, rev, subdir },
+                RawDependency {
+                    local: None,
+                    git: Some(_),
+                    rev: None,
+                    ..
+                } => bail!("Git dependency `{}` must specify a `rev`", name),
+                _ => bail!(
+                    "Dependency `{}` must be either `{{ local = <path> }}` or \
+                     `{{ git = <url>, rev = <rev> }}`",
+                    name
+                ),
+            };
+            dependencies.insert(name, dep);
+        }
+
+        Ok(Manifest {
+            name: raw.package.name,
+            version: raw.package.version.parse()?,
+            addresses,
+            dependencies,
+        })
+    }
+}
+
+/// Package and address names follow the rules of Move identifiers
+fn check_name(name: &str) -> Result<()> {
+    if !Identifier::is_valid(name) {
+        bail!(
+            "Invalid name `{}`: names must be valid Move identifiers",
+            name
+        )
+    }
+    Ok(())
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s
+            .split('.')
+            .map(u64::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format_err!("Invalid version `{}`", s))?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Version {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+            }),
+            _ => bail!("Invalid version `{}`: expected <major>.<minor>.<patch>", s),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolution and compilation of the dependency graph of a Move package.
+
+use crate::manifest::{Dependency, Manifest, PACKAGE_SOURCE_DIR};
+use anyhow::{bail, Context, Result};
+use move_binary_format::access::ModuleAccess;
+use move_core_types::account_address::AccountAddress;
+use move_lang::{
+    compiled_unit::CompiledUnit, move_compile_and_report, path_to_string, shared::Flags,
+};
+use petgraph::graphmap::DiGraphMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+pub struct ResolvedPackage {
+    pub manifest: Manifest,
+    /// Canonical path of the root directory of the package
+    pub root: PathBuf,
+}
+
+impl ResolvedPackage {
+    pub fn source_dir(&self) -> Result<String> {
+        path_to_string(&self.root.join(PACKAGE_SOURCE_DIR))
+    }
+}
+
+/// A package together with all of its direct and transitive dependencies
+pub struct ResolvedGraph {
+    /// Name of the package the graph was resolved from
+    pub root_package: String,
+    /// Every package in the graph, keyed by name
+    pub packages: BTreeMap<String, ResolvedPackage>,
+    /// Named address assignments of all the packages in the graph
+    pub addresses: BTreeMap<String, AccountAddress>,
+    /// Package names in topological order--every package comes after its dependencies
+    order: Vec<String>,
+}
+
+impl ResolvedGraph {
+    /// Resolve the dependency graph of the package rooted at `root_dir`. Git dependencies are
+    /// checked out under `git_dir`.
+    pub fn resolve(root_dir: &Path, git_dir: &Path) -> Result<Self> {
+        let mut packages = BTreeMap::new();
+        let root_package = load_package(None, root_dir, git_dir, &mut packages)?;
+
+        // order the packages so that dependencies come first
+        let names: Vec<&String> = packages.keys().collect();
+        let mut graph = DiGraphMap::new();
+        for idx in 0..names.len() {
+            graph.add_node(idx);
+        }
+        for (idx, name) in names.iter().enumerate() {
+            for dep in packages[*name].manifest.dependencies.keys() {
+                let dep_idx = names.binary_search(&dep).unwrap();
+                graph.add_edge(dep_idx, idx, ());
+            }
+        }
+        let order = match petgraph::algo::toposort(&graph, None) {
+            Err(cycle) => bail!(
+                "Circular dependency detected involving package `{}`",
+                names[cycle.node_id()]
+            ),
+            Ok(ordered_idxs) => ordered_idxs
+                .into_iter()
+                .map(|idx| names[idx].clone())
+                .collect::<Vec<_>>(),
+        };
+
+        // a named address must be assigned the same value by every package that assigns it
+        let mut addresses = BTreeMap::new();
+        let mut assigned_by: BTreeMap<&str, &str> = BTreeMap::new();
+        for name in &order {
+            for (addr_name, addr) in &packages[name].manifest.addresses {
+                match addresses.insert(addr_name.clone(), *addr) {
+                    Some(prev) if prev != *addr => bail!(
+                        "Conflicting assignments for named address `{}`: 0x{} in package `{}` \
+                         and 0x{} in package `{}`",
+                        addr_name,
+                        prev.short_str_lossless(),
+                        assigned_by[addr_name.as_str()],
+                        addr.short_str_lossless(),
+                        name
+                    ),
+                    Some(_) => (),
+                    None => {
+                        assigned_by.insert(addr_name.as_str(), name.as_str());
+                    }
+                }
+            }
+        }
+
+        Ok(ResolvedGraph {
+            root_package,
+            packages,
+            addresses,
+            order,
+        })
+    }
+
+    pub fn root(&self) -> &ResolvedPackage {
+        &self.packages[&self.root_package]
+    }
+
+    /// Return the packages in topological order--every package comes after its dependencies
+    pub fn packages_in_order(&self) -> impl Iterator<Item = &ResolvedPackage> {
+        self.order.iter().map(move |name| &self.packages[name])
+    }
+
+    /// Return the names of all the packages `name` depends on, directly or transitively
+    pub fn transitive_dependencies(&self, name: &str) -> BTreeSet<&str> {
+        let mut deps = BTreeSet::new();
+        let mut worklist = vec![name];
+        while let Some(current) = worklist.pop() {
+            for dep in self.packages[current].manifest.dependencies.keys() {
+                if deps.insert(dep.as_str()) {
+                    worklist.push(dep.as_str());
+                }
+            }
+        }
+        deps
+    }
+
+    /// Compile every package in the graph in dependency order. Each package is compiled against
+    /// the sources in `deps` (e.g., the library modules of the current mode) and the sources of
+    /// its own dependencies. Returns the compiled units of each package, in the same order.
+    pub fn compile(&self, deps: &[String]) -> Result<Vec<(String, Vec<CompiledUnit>)>> {
+        let mut compiled = vec![];
+        for package in self.packages_in_order() {
+            let name = &package.manifest.name;
+            let mut package_deps = deps.to_vec();
+            for dep in self.transitive_dependencies(name) {
+                package_deps.push(self.packages[dep].source_dir()?);
+            }
+
+            let (_files, units) = move_compile_and_report(
+                &[package.source_dir()?],
+                &package_deps,
+                None,
+                Flags::empty().set_sources_shadow_deps(false),
+            )?;
+            check_module_addresses(package, &units)?;
+            compiled.push((name.clone(), units));
+        }
+        Ok(compiled)
+    }
+}
+
+/// Load the package at `dir` and, recursively, its dependencies into `packages`. Returns the name
+/// of the package.
+fn load_package(
+    expected_name: Option<&str>,
+    dir: &Path,
+    git_dir: &Path,
+    packages: &mut BTreeMap<String, ResolvedPackage>,
+) -> Result<String> {
+    let root = dir
+        .canonicalize()
+        .with_context(|| format!("Unable to find package directory {:?}", dir))?;
+    let manifest = Manifest::read(&root)?;
+    let name = manifest.name.clone();
+    if let Some(expected) = expected_name {
+        if expected != name {
+            bail!(
+                "Dependency `{}` refers to package `{}` at {:?}",
+                expected,
+                name,
+                root
+            )
+        }
+    }
+
+    // a package reachable along several paths is loaded once, but it must be the same package
+    if let Some(existing) = packages.get(&name) {
+        if existing.root != root {
+            bail!(
+                "Package `{}` is found at two different locations: {:?} and {:?}",
+                name,
+                existing.root,
+                root
+            )
+        }
+        return Ok(name);
+    }
+
+    let dependencies = manifest.dependencies.clone();
+    packages.insert(
+        name.clone(),
+        ResolvedPackage {
+            manifest,
+            root: root.clone(),
+        },
+    );
+    for (dep_name, dep) in dependencies {
+        let dep_dir = match dep {
+            Dependency::Local(path) => root.join(path),
+            Dependency::Git { url, rev, subdir } => {
+                let checkout = checkout_git(&dep_name, &url, &rev, &root, git_dir)?;
+                subdir.map_or(checkout.clone(), |subdir| checkout.join(subdir))
+            }
+        };
+        load_package(Some(&dep_name), &dep_dir, git_dir, packages)?;
+    }
+    Ok(name)
+}
+
+/// Clone the repository at `url` under `git_dir` and check out `rev`, unless it has already been
+/// checked out. A relative `url` naming an existing path is resolved against `package_root`.
+fn checkout_git(
+    name: &str,
+    url: &str,
+    rev: &str,
+    package_root: &Path,
+    git_dir: &Path,
+) -> Result<PathBuf> {
+    let local_repo = package_root.join(url);
+    let url = if Path::new(url).is_relative() && local_repo.exists() {
+        path_to_string(&local_repo)?
+    } else {
+        url.to_string()
+    };
+
+    let checkout = git_dir.join(format!("{}-{}", name, rev.replace('/', "_")));
+    if !checkout.exists() {
+        fs::create_dir_all(git_dir)?;
+        run_git(
+            Command::new("git")
+                .args(&["clone", "--quiet", url.as_str()])
+                .arg(&checkout),
+        )
+        .with_context(|| format!("Unable to clone {} for dependency `{}`", url, name))?;
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(&checkout)
+                .args(&["checkout", "--quiet", rev]),
+        )
+        .with_context(|| format!("Unable to check out {} for dependency `{}`", rev, name))?;
+    }
+    Ok(checkout)
+}
+
+fn run_git(cmd: &mut Command) -> Result<()> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim())
+    }
+    Ok(())
+}
+
+/// If a package assigns named addresses, its modules must be published at one of them
+fn check_module_addresses(package: &ResolvedPackage, units: &[CompiledUnit]) -> Result<()> {
+    let assigned = &package.manifest.addresses;
+    if assigned.is_empty() {
+        return Ok(());
+    }
+    for unit in units {
+        if let CompiledUnit::Module { module, .. } = unit {
+            let id = module.self_id();
+            if !assigned.values().any(|addr| addr == id.address()) {
+                bail!(
+                    "Module `{}` of package `{}` is published at 0x{}, which is not an address \
+                     assigned in the package manifest",
+                    id.name(),
+                    package.manifest.name,
+                    id.address().short_str_lossless()
+                )
+            }
+        }
+    }
+    Ok(())
+}
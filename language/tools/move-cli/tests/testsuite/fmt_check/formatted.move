@@ -0,0 +1,26 @@
+address 0x2 {
+module M {
+    use 0x1::Vector;
+    struct S has copy, drop {
+        f: u64,
+        g: vector<u8>,
+    }
+
+    // sums up the elements of `v`
+    public fun sum(v: &vector<u64>): u64 {
+        let i = 0;
+        let s = 0;
+        while (i < Vector::length(v)) {
+            s = s + *Vector::borrow(v, i);
+            i = i + 1;
+        }; // done
+        s
+    }
+    spec sum {
+        aborts_if false;
+    }
+    fun make(f: u64): S {
+        S { f, g: x"00" }
+    }
+}
+}
@@ -0,0 +1,3 @@
+address 0x3 {
+module B {}
+}
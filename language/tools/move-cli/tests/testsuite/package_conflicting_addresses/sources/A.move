@@ -0,0 +1,3 @@
+address 0x2 {
+module A {}
+}
@@ -0,0 +1,7 @@
+address 0x3 {
+module Util {
+    public fun double(x: u64): u64 {
+        x * 2
+    }
+}
+}
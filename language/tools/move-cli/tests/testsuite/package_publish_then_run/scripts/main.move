@@ -0,0 +1,7 @@
+script {
+use 0x2::App;
+
+fun main() {
+    assert(App::quadruple(2) == 8, 42);
+}
+}
@@ -0,0 +1,9 @@
+address 0x2 {
+module App {
+    use 0x3::Util;
+
+    public fun quadruple(x: u64): u64 {
+        Util::double(Util::double(x))
+    }
+}
+}
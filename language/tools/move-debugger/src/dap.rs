@@ -0,0 +1,431 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A frontend for clients of the Debug Adapter Protocol, such as editors. The debugger listens for
+//! one client on a TCP socket, so that the output of the program does not mix with the messages
+//! of the protocol.
+//!
+//! Requests are only read while the execution is stopped, or before it starts: a client cannot
+//! pause a running program, and breakpoints it sets while the program runs take effect at the
+//! next stop.
+
+use crate::{
+    session::{
+        type_name, BreakpointKind, Breakpoints, Frontend, Resume, StopReason, Stopped, Variable,
+    },
+    source::{SourceIndex, SourceLocation},
+};
+use anyhow::{anyhow, bail, Result};
+use move_core_types::language_storage::TypeTag;
+use resource_viewer::AnnotatedMoveValue;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+/// The only thread of a Move program
+const THREAD_ID: u64 = 1;
+
+pub struct DebugAdapter {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: u64,
+    /// The children of the values the client can expand, by their `variablesReference` minus 1.
+    /// They are only valid until the execution resumes.
+    handles: Vec<Vec<Variable>>,
+}
+
+/// What the client asks for while the execution is stopped
+enum Action {
+    Resume(Resume),
+    Answer(Value),
+}
+
+impl DebugAdapter {
+    /// Wait for a client to connect at `address`
+    pub fn listen(address: &str) -> Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        eprintln!(
+            "Waiting for a debug adapter client at {}",
+            listener.local_addr()?
+        );
+        let (stream, _) = listener.accept()?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            seq: 0,
+            handles: vec![],
+        })
+    }
+
+    /// The next message of the client, or `None` once it disconnected
+    fn read_message(&mut self) -> Result<Option<Value>> {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            if self.reader.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = Some(length.trim().parse::<usize>()?);
+            }
+        }
+        let length = content_length.ok_or_else(|| anyhow!("Message without a Content-Length"))?;
+        let mut content = vec![0; length];
+        self.reader.read_exact(&mut content)?;
+        Ok(Some(serde_json::from_slice(&content)?))
+    }
+
+    fn send(&mut self, mut message: Value) -> Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let content = serde_json::to_string(&message)?;
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn respond(&mut self, request: &Value, body: Result<Value>) -> Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": body.is_ok(),
+        });
+        match body {
+            Ok(body) => response["body"] = body,
+            Err(err) => response["message"] = json!(err.to_string()),
+        }
+        self.send(response)
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    /// Answer the requests the client sends before the program starts, until it is done setting
+    /// the program up. Returns whether to stop on entry.
+    fn configure(&mut self, index: &SourceIndex, breakpoints: &mut Breakpoints) -> Result<bool> {
+        let mut stop_on_entry = false;
+        loop {
+            let request = self
+                .read_message()?
+                .ok_or_else(|| anyhow!("The debug adapter client disconnected"))?;
+            let args = &request["arguments"];
+            let body = match request["command"].as_str().unwrap_or_default() {
+                "initialize" => {
+                    self.respond(
+                        &request,
+                        Ok(json!({
+                            "supportsConfigurationDoneRequest": true,
+                            "supportsFunctionBreakpoints": true,
+                            "supportsTerminateRequest": true,
+                        })),
+                    )?;
+                    self.event("initialized", json!({}))?;
+                    continue;
+                }
+                "launch" | "attach" => {
+                    stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                    Ok(json!({}))
+                }
+                "setBreakpoints" => set_breakpoints(args, index, breakpoints),
+                "setFunctionBreakpoints" => set_function_breakpoints(args, index, breakpoints),
+                "setExceptionBreakpoints" => Ok(json!({})),
+                "threads" => Ok(threads()),
+                "configurationDone" => {
+                    self.respond(&request, Ok(json!({})))?;
+                    return Ok(stop_on_entry);
+                }
+                "disconnect" | "terminate" => {
+                    self.respond(&request, Ok(json!({})))?;
+                    bail!("The debug adapter client disconnected")
+                }
+                command => Err(anyhow!(
+                    "{} is not available before the program starts",
+                    command
+                )),
+            };
+            self.respond(&request, body)?;
+        }
+    }
+
+    fn handle(&mut self, request: &Value, stopped: &mut Stopped) -> Result<Action> {
+        let args = &request["arguments"];
+        Ok(match request["command"].as_str().unwrap_or_default() {
+            "continue" => Action::Resume(Resume::Continue),
+            "next" => Action::Resume(Resume::StepOver),
+            "stepIn" => Action::Resume(Resume::StepIn),
+            "stepOut" => Action::Resume(Resume::StepOut),
+            "disconnect" | "terminate" => Action::Resume(Resume::Detach),
+            "threads" => Action::Answer(threads()),
+            "stackTrace" => Action::Answer(stack_trace(stopped)),
+            "scopes" => {
+                let frame = args["frameId"].as_u64().unwrap_or(0) as usize;
+                let locals = stopped.locals(frame)?;
+                let reference = self.handle_for(locals);
+                Action::Answer(json!({
+                    "scopes": [{
+                        "name": "Locals",
+                        "variablesReference": reference,
+                        "expensive": false,
+                    }]
+                }))
+            }
+            "variables" => {
+                let reference = args["variablesReference"].as_u64().unwrap_or(0) as usize;
+                let variables = reference
+                    .checked_sub(1)
+                    .and_then(|idx| self.handles.get(idx))
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No variables {}", reference))?;
+                let variables: Vec<_> = variables
+                    .iter()
+                    .map(|variable| {
+                        json!({
+                            "name": variable.name,
+                            "value": summary(variable),
+                            "type": variable.type_name(),
+                            "variablesReference": self.children(variable),
+                        })
+                    })
+                    .collect();
+                Action::Answer(json!({ "variables": variables }))
+            }
+            "evaluate" => {
+                let expression = args["expression"].as_str().unwrap_or_default();
+                let frame = args["frameId"].as_u64().unwrap_or(0) as usize;
+                let variable = stopped.evaluate(expression, frame)?;
+                Action::Answer(json!({
+                    "result": summary(&variable),
+                    "type": variable.type_name(),
+                    "variablesReference": self.children(&variable),
+                }))
+            }
+            "setBreakpoints" => {
+                let index = stopped.index();
+                Action::Answer(set_breakpoints(args, index, stopped.breakpoints)?)
+            }
+            "setFunctionBreakpoints" => {
+                let index = stopped.index();
+                Action::Answer(set_function_breakpoints(args, index, stopped.breakpoints)?)
+            }
+            "setExceptionBreakpoints" | "pause" => Action::Answer(json!({})),
+            command => bail!("Unsupported request {}", command),
+        })
+    }
+
+    fn handle_for(&mut self, variables: Vec<Variable>) -> usize {
+        self.handles.push(variables);
+        self.handles.len()
+    }
+
+    /// The reference to the fields or elements of `variable`, or 0 if it has none
+    fn children(&mut self, variable: &Variable) -> usize {
+        let children: Vec<_> = match &variable.value {
+            AnnotatedMoveValue::Struct(s) => s
+                .value
+                .iter()
+                .map(|(field, value)| child(field.to_string(), value.clone()))
+                .collect(),
+            AnnotatedMoveValue::Vector(_, elements) => elements
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| child(format!("[{}]", idx), value.clone()))
+                .collect(),
+            _ => return 0,
+        };
+        self.handle_for(children)
+    }
+}
+
+fn child(name: String, value: AnnotatedMoveValue) -> Variable {
+    Variable {
+        name,
+        type_tag: value.get_type(),
+        reference: None,
+        value,
+    }
+}
+
+/// A one line description of the value of `variable`
+fn summary(variable: &Variable) -> String {
+    match (&variable.type_tag, &variable.value) {
+        (TypeTag::Signer, AnnotatedMoveValue::Address(address)) => {
+            format!("signer(0x{})", address.short_str_lossless())
+        }
+        (_, AnnotatedMoveValue::Address(address)) => format!("0x{}", address.short_str_lossless()),
+        (ty, AnnotatedMoveValue::Struct(_)) => format!("{} {{..}}", type_name(ty)),
+        (_, AnnotatedMoveValue::Vector(_, elements)) => format!("vector[{}]", elements.len()),
+        (_, AnnotatedMoveValue::Bytes(bytes)) => format!("x\"{}\"", hex::encode(bytes)),
+        (_, value) => value.to_string(),
+    }
+}
+
+fn threads() -> Value {
+    json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })
+}
+
+fn source(location: &SourceLocation) -> Value {
+    let path = fs::canonicalize(location.file)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| location.file.to_string());
+    json!({ "name": location.file, "path": path })
+}
+
+fn stack_trace(stopped: &Stopped) -> Value {
+    let frames: Vec<_> = stopped
+        .frames()
+        .iter()
+        .enumerate()
+        .map(|(idx, frame)| match &frame.location {
+            Some(location) => json!({
+                "id": idx,
+                "name": frame.function,
+                "source": source(location),
+                "line": location.line,
+                "column": location.column,
+            }),
+            None => json!({
+                "id": idx,
+                "name": format!("{} (pc {})", frame.function, frame.pc),
+                "line": 0,
+                "column": 0,
+            }),
+        })
+        .collect();
+    json!({ "stackFrames": frames, "totalFrames": frames.len() })
+}
+
+/// Replace the line breakpoints of a file by the ones the client asks for
+fn set_breakpoints(
+    args: &Value,
+    index: &SourceIndex,
+    breakpoints: &mut Breakpoints,
+) -> Result<Value> {
+    let path = args["source"]["path"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Breakpoints without a source path"))?;
+    if let Some(file) = index.file_name(path) {
+        breakpoints.remove_if(|breakpoint| {
+            matches!(breakpoint.kind, BreakpointKind::Line(bp_file, _) if bp_file == file)
+        });
+    }
+    let requested = args["breakpoints"].as_array().cloned().unwrap_or_default();
+    let set: Vec<_> = requested
+        .iter()
+        .map(|requested| {
+            let line = requested["line"].as_u64().unwrap_or(0) as usize;
+            match breakpoints.add_line(index, path, line) {
+                Ok(breakpoint) => match breakpoint.kind {
+                    BreakpointKind::Line(_, line) => {
+                        json!({ "id": breakpoint.id, "verified": true, "line": line })
+                    }
+                    BreakpointKind::Function(_) => unreachable!(),
+                },
+                Err(err) => json!({ "verified": false, "line": line, "message": err.to_string() }),
+            }
+        })
+        .collect();
+    Ok(json!({ "breakpoints": set }))
+}
+
+/// Replace the function breakpoints by the ones the client asks for
+fn set_function_breakpoints(
+    args: &Value,
+    index: &SourceIndex,
+    breakpoints: &mut Breakpoints,
+) -> Result<Value> {
+    breakpoints.remove_if(|breakpoint| matches!(breakpoint.kind, BreakpointKind::Function(_)));
+    let requested = args["breakpoints"].as_array().cloned().unwrap_or_default();
+    let set: Vec<_> = requested
+        .iter()
+        .map(|requested| {
+            let name = requested["name"].as_str().unwrap_or_default();
+            match breakpoints.add(index, name) {
+                Ok(breakpoint) => json!({ "id": breakpoint.id, "verified": true }),
+                Err(err) => json!({ "verified": false, "message": err.to_string() }),
+            }
+        })
+        .collect();
+    Ok(json!({ "breakpoints": set }))
+}
+
+impl Frontend for DebugAdapter {
+    fn start(&mut self, index: &SourceIndex, breakpoints: &mut Breakpoints) -> Result<bool> {
+        self.configure(index, breakpoints)
+    }
+
+    fn stopped(&mut self, reason: StopReason, stopped: &mut Stopped) -> Resume {
+        let mut event = json!({
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        match reason {
+            StopReason::Entry => event["reason"] = json!("entry"),
+            StopReason::Step => event["reason"] = json!("step"),
+            StopReason::Abort => {
+                event["reason"] = json!("exception");
+                event["description"] = json!("Stopped before an abort");
+            }
+            StopReason::Breakpoint(id) => {
+                event["reason"] = json!("breakpoint");
+                event["hitBreakpointIds"] = json!([id]);
+            }
+        }
+        if self.event("stopped", event).is_err() {
+            return Resume::Detach;
+        }
+        loop {
+            let request = match self.read_message() {
+                Ok(Some(request)) => request,
+                Ok(None) | Err(_) => return Resume::Detach,
+            };
+            let (body, resume) = match self.handle(&request, stopped) {
+                Ok(Action::Resume(resume)) => {
+                    (Ok(json!({ "allThreadsContinued": true })), Some(resume))
+                }
+                Ok(Action::Answer(body)) => (Ok(body), None),
+                Err(err) => (Err(err), None),
+            };
+            if self.respond(&request, body).is_err() {
+                return Resume::Detach;
+            }
+            if let Some(resume) = resume {
+                self.handles.clear();
+                return resume;
+            }
+        }
+    }
+
+    fn finished(&mut self) {
+        if self.event("terminated", json!({})).is_err()
+            || self.event("exited", json!({ "exitCode": 0 })).is_err()
+        {
+            return;
+        }
+        // Let the client disconnect
+        while let Ok(Some(request)) = self.read_message() {
+            let disconnect = matches!(request["command"].as_str(), Some("disconnect"));
+            let body = if disconnect {
+                Ok(json!({}))
+            } else {
+                Err(anyhow!("The program finished"))
+            };
+            if self.respond(&request, body).is_err() || disconnect {
+                return;
+            }
+        }
+    }
+}
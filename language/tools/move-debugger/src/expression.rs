@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The expressions the debugger evaluates: a local, or a resource in global storage, followed by
+//! any number of field accesses and vector indexes, as in `coin.value`, `v[2]`, or
+//! `global<0x1::Diem::Info>(0x1).scaling_factor`.
+
+use anyhow::{anyhow, bail, Result};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+    parser::parse_type_tag,
+};
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression {
+    Local(String),
+    Global(StructTag, AccountAddress),
+    Field(Box<Expression>, String),
+    Index(Box<Expression>, usize),
+}
+
+impl FromStr for Expression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (mut expression, mut rest) = if let Some(rest) = s.strip_prefix("global<") {
+            parse_global(rest)?
+        } else {
+            let (name, rest) = split_identifier(s);
+            if name.is_empty() {
+                bail!(
+                    "Expected the name of a local or `global<T>(address)`: {}",
+                    s
+                )
+            }
+            (Expression::Local(name.to_string()), rest)
+        };
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                return Ok(expression);
+            }
+            if let Some(field) = rest.strip_prefix('.') {
+                let (name, after) = split_identifier(field.trim_start());
+                if name.is_empty() {
+                    bail!("Expected a field name after `.`: {}", s)
+                }
+                expression = Expression::Field(Box::new(expression), name.to_string());
+                rest = after;
+            } else if let Some(index) = rest.strip_prefix('[') {
+                let end = index
+                    .find(']')
+                    .ok_or_else(|| anyhow!("Missing `]`: {}", s))?;
+                let idx = index[..end]
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("Expected an index: {}", &index[..end]))?;
+                expression = Expression::Index(Box::new(expression), idx);
+                rest = &index[end + 1..];
+            } else {
+                bail!("Unexpected `{}` in {}", rest, s)
+            }
+        }
+    }
+}
+
+/// Parse the rest of `global<T>(address)` after `global<`, and return what follows it
+fn parse_global(s: &str) -> Result<(Expression, &str)> {
+    let mut depth = 1;
+    let end = s
+        .char_indices()
+        .find(|(_, c)| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => (),
+            }
+            depth == 0
+        })
+        .map(|(idx, _)| idx)
+        .ok_or_else(|| anyhow!("Missing `>` after the type of `global`"))?;
+    let tag = match parse_type_tag(&s[..end])? {
+        TypeTag::Struct(tag) => tag,
+        ty => bail!("Expected a resource type for `global`, found {}", ty),
+    };
+    let args = s[end + 1..]
+        .trim_start()
+        .strip_prefix('(')
+        .ok_or_else(|| anyhow!("Expected `(address)` after `global<{}>`", &s[..end]))?;
+    let close = args
+        .find(')')
+        .ok_or_else(|| anyhow!("Missing `)` after the address of `global`"))?;
+    let literal = args[..close].trim();
+    let address = AccountAddress::from_hex_literal(literal.trim_start_matches('@'))
+        .map_err(|_| anyhow!("Expected an address, as in 0x1: {}", literal))?;
+    Ok((Expression::Global(tag, address), &args[close + 1..]))
+}
+
+fn split_identifier(s: &str) -> (&str, &str) {
+    let end = s
+        .char_indices()
+        .find(|(idx, c)| {
+            !(c.is_ascii_alphanumeric() || *c == '_') || (*idx == 0 && c.is_ascii_digit())
+        })
+        .map_or(s.len(), |(idx, _)| idx);
+    s.split_at(end)
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Local(name) => write!(f, "{}", name),
+            Expression::Global(tag, address) => {
+                write!(f, "global<{}>(0x{})", tag, address.short_str_lossless())
+            }
+            Expression::Field(expression, field) => write!(f, "{}.{}", expression, field),
+            Expression::Index(expression, idx) => write!(f, "{}[{}]", expression, idx),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An interactive debugger for Move code running in the Move VM. It stops at breakpoints on
+//! source lines or functions, steps through the source line by line, and shows the locals of
+//! the call stack, resources in global storage, and watch expressions over them.
+//!
+//! The debugger attaches to the VM through `move_vm_runtime::debug`, and talks to its user either
+//! on the command line, or as a Debug Adapter Protocol server.
+
+pub mod dap;
+pub mod expression;
+pub mod repl;
+pub mod session;
+pub mod source;
+
+use crate::{
+    dap::DebugAdapter,
+    repl::Repl,
+    session::{DebugHandle, DebugSession},
+    source::SourceIndex,
+};
+use anyhow::{bail, Result};
+use std::io::{self, BufReader};
+
+/// How the user drives the debugger
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Interface {
+    /// Commands on the standard input
+    Repl,
+    /// A Debug Adapter Protocol client, which connects at this address
+    Adapter(String),
+}
+
+/// Start debugging the code `index` maps to the sources, until the returned handle finishes
+pub fn start(index: SourceIndex, interface: &Interface) -> Result<DebugHandle> {
+    if !move_vm_runtime::DEBUGGING_ENABLED {
+        bail!("Release builds only support debugging with the `debugging` feature");
+    }
+    let session = match interface {
+        Interface::Repl => DebugSession::new(
+            index,
+            Box::new(Repl::new(BufReader::new(io::stdin()), io::stdout())),
+        ),
+        Interface::Adapter(address) => {
+            DebugSession::new(index, Box::new(DebugAdapter::listen(address)?))
+        }
+    };
+    session.start()
+}
@@ -0,0 +1,219 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A command line frontend: shows where the execution stopped, and reads commands until one of
+//! them resumes it.
+
+use crate::{
+    session::{Breakpoints, Frontend, Resume, StopReason, Stopped},
+    source::SourceIndex,
+};
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  continue, c               run to the next breakpoint
+  step, s                   run to the next line, stepping into calls
+  next, n                   run to the next line of this function or a caller
+  out, o                    run to the caller of this function
+  break, b <file:line>      stop at a line
+  break, b <function>       stop at the start of a function, as in 0x1::Vector::length
+  delete <id>               delete a breakpoint
+  breakpoints               list the breakpoints
+  backtrace, bt             show the call stack
+  frame, f <n>              select frame n of the call stack, 0 being the innermost
+  locals, l                 show the locals of the selected frame
+  print, p <expression>     show a local, field, vector element, or global<T>(address)
+  watch, w <expression>     show an expression at every stop
+  unwatch <n>               stop showing the watch expression n
+  list                      show the source around the line of the selected frame
+  quit, q                   run to the end without stopping
+  help, h                   show this help";
+
+/// The number of lines `list` shows before and after the current line
+const LIST_CONTEXT: usize = 3;
+
+pub struct Repl<R, W> {
+    input: R,
+    output: W,
+    watches: Vec<String>,
+    /// The frame that commands inspect, counting from the innermost one
+    frame: usize,
+}
+
+impl<R: BufRead + Send, W: Write + Send> Repl<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            watches: vec![],
+            frame: 0,
+        }
+    }
+
+    fn show_stop(&mut self, reason: &StopReason, stopped: &mut Stopped) -> io::Result<()> {
+        let why = match reason {
+            StopReason::Entry => "Stopped".to_string(),
+            StopReason::Breakpoint(id) => format!("Stopped at breakpoint {}", id),
+            StopReason::Step => "Stepped".to_string(),
+            StopReason::Abort => "Stopped before an abort".to_string(),
+        };
+        let frames = stopped.frames();
+        let frame = &frames[0];
+        match frame.location {
+            Some(location) => {
+                writeln!(
+                    self.output,
+                    "{} in {} at {}:{}",
+                    why, frame.function, location.file, location.line
+                )?;
+                if let Some(text) = stopped.index().line_text(location.file, location.line) {
+                    writeln!(self.output, "{:>5} | {}", location.line, text)?;
+                }
+            }
+            None => writeln!(
+                self.output,
+                "{} in {} at pc {}",
+                why, frame.function, frame.pc
+            )?,
+        }
+        for (idx, watch) in self.watches.iter().enumerate() {
+            match stopped.evaluate(watch, 0) {
+                Ok(variable) => writeln!(self.output, "  [{}] {}", idx, variable)?,
+                Err(err) => writeln!(self.output, "  [{}] {}: {}", idx, watch, err)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `command`, and return how to resume if it resumes the execution
+    fn command(&mut self, command: &str, stopped: &mut Stopped) -> io::Result<Option<Resume>> {
+        let (name, arg) = match command.find(char::is_whitespace) {
+            Some(idx) => (&command[..idx], command[idx..].trim()),
+            None => (command, ""),
+        };
+        match name {
+            "" => (),
+            "continue" | "c" => return Ok(Some(Resume::Continue)),
+            "step" | "s" => return Ok(Some(Resume::StepIn)),
+            "next" | "n" => return Ok(Some(Resume::StepOver)),
+            "out" | "o" => return Ok(Some(Resume::StepOut)),
+            "quit" | "q" => return Ok(Some(Resume::Detach)),
+            "break" | "b" => {
+                let index = stopped.index();
+                match stopped.breakpoints.add(index, arg) {
+                    Ok(breakpoint) => writeln!(self.output, "Breakpoint {}", breakpoint)?,
+                    Err(err) => writeln!(self.output, "{}", err)?,
+                }
+            }
+            "delete" => match arg.parse() {
+                Ok(id) if stopped.breakpoints.remove(id) => (),
+                _ => writeln!(self.output, "No breakpoint {}", arg)?,
+            },
+            "breakpoints" => {
+                for breakpoint in stopped.breakpoints.iter() {
+                    writeln!(self.output, "{}", breakpoint)?;
+                }
+            }
+            "backtrace" | "bt" => {
+                for (idx, frame) in stopped.frames().iter().enumerate() {
+                    let marker = if idx == self.frame { '>' } else { ' ' };
+                    match frame.location {
+                        Some(location) => writeln!(
+                            self.output,
+                            "{}{:>3} {} at {}:{}",
+                            marker, idx, frame.function, location.file, location.line
+                        )?,
+                        None => writeln!(
+                            self.output,
+                            "{}{:>3} {} at pc {}",
+                            marker, idx, frame.function, frame.pc
+                        )?,
+                    }
+                }
+            }
+            "frame" | "f" => match arg.parse() {
+                Ok(frame) if frame < stopped.frames().len() => self.frame = frame,
+                _ => writeln!(self.output, "No frame {}", arg)?,
+            },
+            "locals" | "l" => match stopped.locals(self.frame) {
+                Ok(locals) => {
+                    for local in locals {
+                        writeln!(self.output, "{}", local)?;
+                    }
+                }
+                Err(err) => writeln!(self.output, "{}", err)?,
+            },
+            "print" | "p" => match stopped.evaluate(arg, self.frame) {
+                Ok(variable) => writeln!(self.output, "{}", variable)?,
+                Err(err) => writeln!(self.output, "{}", err)?,
+            },
+            "watch" | "w" => {
+                writeln!(self.output, "Watch {}: {}", self.watches.len(), arg)?;
+                self.watches.push(arg.to_string());
+            }
+            "unwatch" => match arg.parse::<usize>() {
+                Ok(idx) if idx < self.watches.len() => {
+                    self.watches.remove(idx);
+                }
+                _ => writeln!(self.output, "No watch expression {}", arg)?,
+            },
+            "list" => match stopped.frames()[self.frame].location {
+                Some(location) => {
+                    let first = location.line.saturating_sub(LIST_CONTEXT).max(1);
+                    for line in first..=location.line + LIST_CONTEXT {
+                        if let Some(text) = stopped.index().line_text(location.file, line) {
+                            let marker = if line == location.line { '>' } else { ' ' };
+                            writeln!(self.output, "{}{:>4} | {}", marker, line, text)?;
+                        }
+                    }
+                }
+                None => writeln!(self.output, "No source for this frame")?,
+            },
+            "help" | "h" => writeln!(self.output, "{}", HELP)?,
+            _ => writeln!(self.output, "Unknown command {}, try `help`", name)?,
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> Frontend for Repl<R, W> {
+    fn start(&mut self, _index: &SourceIndex, _breakpoints: &mut Breakpoints) -> Result<bool> {
+        writeln!(
+            self.output,
+            "Debugging Move code. Type `help` for the commands."
+        )?;
+        Ok(true)
+    }
+
+    fn stopped(&mut self, reason: StopReason, stopped: &mut Stopped) -> Resume {
+        self.frame = 0;
+        if self.show_stop(&reason, stopped).is_err() {
+            return Resume::Detach;
+        }
+        loop {
+            if write!(self.output, "(debug) ")
+                .and_then(|_| self.output.flush())
+                .is_err()
+            {
+                return Resume::Detach;
+            }
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                // Without input, nothing else can resume the execution
+                Ok(0) | Err(_) => return Resume::Detach,
+                Ok(_) => (),
+            }
+            match self.command(line.trim(), stopped) {
+                Ok(Some(resume)) => return resume,
+                Ok(None) => (),
+                Err(_) => return Resume::Detach,
+            }
+        }
+    }
+
+    fn finished(&mut self) {
+        let _ = writeln!(self.output, "Debugging session ended");
+    }
+}
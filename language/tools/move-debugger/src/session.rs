@@ -0,0 +1,518 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A debugging session: decides where the execution stops, and lets a frontend inspect it there.
+//!
+//! Execution stops at breakpoints, after steps, and before aborts, but only at instructions that
+//! map to a source line. Stepping goes by source lines: stepping over runs until another line of
+//! the same frame or of a caller, stepping in until any other line, and stepping out until a line
+//! of a caller.
+
+use crate::{
+    expression::Expression,
+    source::{FunctionKey, SourceIndex, SourceLocation},
+};
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::file_format::{Bytecode, CodeOffset};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+};
+use move_vm_runtime::debug::{
+    self, DebugFrame, DebugValue, Debugger, ExecutionState, ReferenceKind,
+};
+use resource_viewer::AnnotatedMoveValue;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// How a stopped execution resumes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+    /// Run to the end without stopping again
+    Detach,
+}
+
+/// Why an execution stopped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// At the first line that executes
+    Entry,
+    /// At the breakpoint with this id
+    Breakpoint(usize),
+    Step,
+    /// Before an `abort`
+    Abort,
+}
+
+/// Where the user of the debugger sees stopped executions, and decides how they resume
+pub trait Frontend: Send {
+    /// Called once before anything executes, to set breakpoints up. Returns whether to stop at
+    /// the first line that executes.
+    fn start(&mut self, index: &SourceIndex, breakpoints: &mut Breakpoints) -> Result<bool>;
+
+    /// Called when the execution stops, which resumes as this returns
+    fn stopped(&mut self, reason: StopReason, stopped: &mut Stopped) -> Resume;
+
+    /// Called once the program being debugged is done
+    fn finished(&mut self);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakpointKind {
+    /// A line of a file, as the compiler names it
+    Line(&'static str, usize),
+    /// The start of a function, named as in `0x1::Vector::length`
+    Function(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub kind: BreakpointKind,
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            BreakpointKind::Line(file, line) => write!(f, "[{}] {}:{}", self.id, file, line),
+            BreakpointKind::Function(name) => write!(f, "[{}] {}", self.id, name),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Breakpoints {
+    next_id: usize,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Breakpoints {
+    /// Add a breakpoint at `spec`, either `file:line` or a function name as in
+    /// `0x1::Vector::length`
+    pub fn add(&mut self, index: &SourceIndex, spec: &str) -> Result<&Breakpoint> {
+        let spec = spec.trim();
+        if let Some((file, line)) = spec.rsplit_once(':') {
+            if let Ok(line) = line.parse() {
+                return self.add_line(index, file, line);
+            }
+        }
+        if !index.has_function(spec) {
+            bail!("No function {} with a source map", spec)
+        }
+        Ok(self.insert(BreakpointKind::Function(spec.to_string())))
+    }
+
+    /// Add a breakpoint at the first line with code at or after `line` in `file`
+    pub fn add_line(
+        &mut self,
+        index: &SourceIndex,
+        file: &str,
+        line: usize,
+    ) -> Result<&Breakpoint> {
+        let file = index
+            .file_name(file)
+            .ok_or_else(|| anyhow!("No source file {}", file))?;
+        let line = index
+            .code_line(file, line)
+            .ok_or_else(|| anyhow!("No code at or after {}:{}", file, line))?;
+        Ok(self.insert(BreakpointKind::Line(file, line)))
+    }
+
+    fn insert(&mut self, kind: BreakpointKind) -> &Breakpoint {
+        self.next_id += 1;
+        self.breakpoints.push(Breakpoint {
+            id: self.next_id,
+            kind,
+        });
+        self.breakpoints.last().unwrap()
+    }
+
+    /// Remove the breakpoint with id `id`, and return whether there was one
+    pub fn remove(&mut self, id: usize) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.len() != len
+    }
+
+    /// Remove the breakpoints that `f` selects
+    pub fn remove_if(&mut self, mut f: impl FnMut(&Breakpoint) -> bool) {
+        self.breakpoints.retain(|breakpoint| !f(breakpoint))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.iter()
+    }
+
+    /// The breakpoint, if any, at the start of `position`
+    fn hit(&self, position: &Position, function: &str, pc: CodeOffset) -> Option<usize> {
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| match &breakpoint.kind {
+                BreakpointKind::Line(file, line) => position.line == Some((*file, *line)),
+                BreakpointKind::Function(name) => pc == 0 && name == function,
+            })
+            .map(|breakpoint| breakpoint.id)
+    }
+}
+
+/// A frame of the call stack of a stopped execution
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// The function, with its type arguments
+    pub function: String,
+    pub location: Option<SourceLocation>,
+    pub pc: CodeOffset,
+    pub instruction: Bytecode,
+}
+
+/// A value of a stopped execution
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub type_tag: TypeTag,
+    pub reference: Option<ReferenceKind>,
+    pub value: AnnotatedMoveValue,
+}
+
+impl Variable {
+    /// The type of the variable, in Move syntax
+    pub fn type_name(&self) -> String {
+        let prefix = match self.reference {
+            Some(ReferenceKind::Immutable) => "&",
+            Some(ReferenceKind::Mutable) => "&mut ",
+            None => "",
+        };
+        format!("{}{}", prefix, type_name(&self.type_tag))
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} = ", self.name, self.type_name())?;
+        match (&self.type_tag, &self.value) {
+            (TypeTag::Signer, AnnotatedMoveValue::Address(address)) => {
+                write!(f, "signer(0x{})", address.short_str_lossless())
+            }
+            (_, value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// `ty` in Move syntax
+pub fn type_name(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Bool => "bool".to_string(),
+        TypeTag::U8 => "u8".to_string(),
+        TypeTag::U64 => "u64".to_string(),
+        TypeTag::U128 => "u128".to_string(),
+        TypeTag::Address => "address".to_string(),
+        TypeTag::Signer => "signer".to_string(),
+        TypeTag::Vector(ty) => format!("vector<{}>", type_name(ty)),
+        TypeTag::Struct(tag) => {
+            let mut name = format!(
+                "0x{}::{}::{}",
+                tag.address.short_str_lossless(),
+                tag.module,
+                tag.name
+            );
+            if !tag.type_params.is_empty() {
+                let type_args: Vec<_> = tag.type_params.iter().map(type_name).collect();
+                name = format!("{}<{}>", name, type_args.join(", "));
+            }
+            name
+        }
+    }
+}
+
+fn function_name(frame: &DebugFrame) -> String {
+    match &frame.module_id {
+        Some(module_id) => format!(
+            "0x{}::{}::{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            frame.function_name
+        ),
+        None => frame.function_name.to_string(),
+    }
+}
+
+/// The name of a local in the source, or `None` for a temporary the compiler introduced
+fn source_name(local_name: &str) -> Option<&str> {
+    if local_name.starts_with("tmp%") {
+        return None;
+    }
+    // The compiler makes the names of locals unique with a `#` suffix
+    Some(local_name.split('#').next().unwrap_or(local_name))
+}
+
+/// A stopped execution, as frontends see it. Frames are numbered from the innermost one, 0.
+pub struct Stopped<'a> {
+    state: &'a mut dyn ExecutionState,
+    index: &'a SourceIndex,
+    pub breakpoints: &'a mut Breakpoints,
+}
+
+impl<'a> Stopped<'a> {
+    pub fn index(&self) -> &'a SourceIndex {
+        self.index
+    }
+
+    pub fn frames(&self) -> Vec<Frame> {
+        self.state
+            .frames()
+            .iter()
+            .rev()
+            .map(|frame| {
+                let mut function = function_name(frame);
+                if !frame.type_arguments.is_empty() {
+                    let type_args: Vec<_> = frame.type_arguments.iter().map(type_name).collect();
+                    function = format!("{}<{}>", function, type_args.join(", "));
+                }
+                Frame {
+                    function,
+                    location: self.index.location(frame),
+                    pc: frame.pc,
+                    instruction: frame.instruction.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// The parameters and locals of `frame` that hold a value, by their names in the source
+    pub fn locals(&self, frame: usize) -> Result<Vec<Variable>> {
+        let frames = self.state.frames();
+        let vm_frame = frames
+            .len()
+            .checked_sub(frame + 1)
+            .ok_or_else(|| anyhow!("No frame {}", frame))?;
+        let names = self.index.local_names(&frames[vm_frame]).unwrap_or(&[]);
+        let values = self
+            .state
+            .locals(vm_frame)
+            .map_err(|e| anyhow!("Cannot read the locals: {:?}", e.into_vm_status()))?;
+        let mut variables = vec![];
+        for (idx, value) in values.into_iter().enumerate() {
+            let name = match names.get(idx) {
+                Some(name) => match source_name(name) {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                },
+                None => format!("local{}", idx),
+            };
+            if let Some(value) = value {
+                variables.push(self.variable(name, value)?);
+            }
+        }
+        Ok(variables)
+    }
+
+    /// The resource of type `tag` under `address`, if any
+    pub fn global(&mut self, address: AccountAddress, tag: &StructTag) -> Result<Option<Variable>> {
+        let value = self
+            .state
+            .global(address, tag)
+            .map_err(|e| anyhow!("Cannot read global storage: {:?}", e.into_vm_status()))?;
+        let name = Expression::Global(tag.clone(), address).to_string();
+        value.map(|value| self.variable(name, value)).transpose()
+    }
+
+    fn variable(&self, name: String, value: DebugValue) -> Result<Variable> {
+        let annotator = self.index.annotator();
+        // The annotator has no signers, which are addresses in disguise
+        let annotated = match &value.type_tag {
+            TypeTag::Signer => annotator.view_value(&TypeTag::Address, &value.bytes)?,
+            ty => annotator.view_value(ty, &value.bytes)?,
+        };
+        Ok(Variable {
+            name,
+            type_tag: value.type_tag,
+            reference: value.reference,
+            value: annotated,
+        })
+    }
+
+    /// Evaluate `expression` in `frame`
+    pub fn evaluate(&mut self, expression: &str, frame: usize) -> Result<Variable> {
+        let expression: Expression = expression.parse()?;
+        self.evaluate_expression(&expression, frame)
+    }
+
+    fn evaluate_expression(&mut self, expression: &Expression, frame: usize) -> Result<Variable> {
+        let name = expression.to_string();
+        match expression {
+            // The innermost of locals with the same name in the source is declared last
+            Expression::Local(local) => self
+                .locals(frame)?
+                .into_iter()
+                .rev()
+                .find(|variable| &variable.name == local)
+                .ok_or_else(|| anyhow!("No local {} holds a value", local)),
+            Expression::Global(tag, address) => self
+                .global(*address, tag)?
+                .ok_or_else(|| anyhow!("No resource {}", name)),
+            Expression::Field(inner, field) => {
+                let variable = self.evaluate_expression(inner, frame)?;
+                let fields = match variable.value {
+                    AnnotatedMoveValue::Struct(s) => s.value,
+                    _ => bail!("{} is not a struct", inner),
+                };
+                let (_, value) = fields
+                    .into_iter()
+                    .find(|(ident, _)| ident.as_str() == field)
+                    .ok_or_else(|| anyhow!("{} has no field {}", inner, field))?;
+                Ok(Variable {
+                    name,
+                    type_tag: value.get_type(),
+                    reference: None,
+                    value,
+                })
+            }
+            Expression::Index(inner, idx) => {
+                let variable = self.evaluate_expression(inner, frame)?;
+                let value = match variable.value {
+                    AnnotatedMoveValue::Vector(_, mut elements) if *idx < elements.len() => {
+                        elements.swap_remove(*idx)
+                    }
+                    AnnotatedMoveValue::Bytes(bytes) if *idx < bytes.len() => {
+                        AnnotatedMoveValue::U8(bytes[*idx])
+                    }
+                    AnnotatedMoveValue::Vector(..) | AnnotatedMoveValue::Bytes(_) => {
+                        bail!("{} is out of bounds", name)
+                    }
+                    _ => bail!("{} is not a vector", inner),
+                };
+                Ok(Variable {
+                    name,
+                    type_tag: value.get_type(),
+                    reference: None,
+                    value,
+                })
+            }
+        }
+    }
+}
+
+/// Where an instruction is: the depth of its frame, its function, and its source line
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Position {
+    depth: usize,
+    function: FunctionKey,
+    line: Option<(&'static str, usize)>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Continue,
+    Stop,
+    StepIn(Position),
+    StepOver(Position),
+    StepOut(usize),
+    Detached,
+}
+
+/// A debugging session, which a frontend drives
+pub struct DebugSession {
+    index: SourceIndex,
+    breakpoints: Breakpoints,
+    frontend: Box<dyn Frontend>,
+    mode: Mode,
+    /// The position of the last instruction, to stop at breakpoints once per visit of a line
+    last: Option<Position>,
+}
+
+impl DebugSession {
+    pub fn new(index: SourceIndex, frontend: Box<dyn Frontend>) -> Self {
+        Self {
+            index,
+            breakpoints: Breakpoints::default(),
+            frontend,
+            mode: Mode::Continue,
+            last: None,
+        }
+    }
+
+    /// Let the frontend set the session up, and attach the session to the VM
+    pub fn start(mut self) -> Result<DebugHandle> {
+        if self.frontend.start(&self.index, &mut self.breakpoints)? {
+            self.mode = Mode::Stop;
+        }
+        let session = Arc::new(Mutex::new(self));
+        debug::attach(Box::new(Attached(session.clone())));
+        Ok(DebugHandle(session))
+    }
+
+    fn on_instruction(&mut self, state: &mut dyn ExecutionState) {
+        let frame = match state.frames().last() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let position = Position {
+            depth: state.frames().len(),
+            function: (frame.module_id.clone(), frame.function_index.0),
+            line: self
+                .index
+                .location(frame)
+                .map(|location| (location.file, location.line)),
+        };
+        let moved = self.last.as_ref() != Some(&position);
+        let reason = if position.line.is_none() {
+            None
+        } else {
+            match &self.mode {
+                Mode::Detached => None,
+                Mode::Stop => Some(StopReason::Entry),
+                Mode::StepIn(from) if &position != from => Some(StopReason::Step),
+                Mode::StepOver(from) if position.depth <= from.depth && &position != from => {
+                    Some(StopReason::Step)
+                }
+                Mode::StepOut(depth) if position.depth < *depth => Some(StopReason::Step),
+                _ if frame.instruction == Bytecode::Abort => Some(StopReason::Abort),
+                _ if moved => self
+                    .breakpoints
+                    .hit(&position, &function_name(frame), frame.pc)
+                    .map(StopReason::Breakpoint),
+                _ => None,
+            }
+        };
+        if let Some(reason) = reason {
+            let mut stopped = Stopped {
+                state,
+                index: &self.index,
+                breakpoints: &mut self.breakpoints,
+            };
+            self.mode = match self.frontend.stopped(reason, &mut stopped) {
+                Resume::Continue => Mode::Continue,
+                Resume::StepIn => Mode::StepIn(position.clone()),
+                Resume::StepOver => Mode::StepOver(position.clone()),
+                Resume::StepOut => Mode::StepOut(position.depth),
+                Resume::Detach => Mode::Detached,
+            };
+        }
+        self.last = Some(position);
+    }
+}
+
+struct Attached(Arc<Mutex<DebugSession>>);
+
+impl Debugger for Attached {
+    fn on_instruction(&mut self, state: &mut dyn ExecutionState) {
+        self.0.lock().unwrap().on_instruction(state)
+    }
+}
+
+/// A session attached to the VM
+pub struct DebugHandle(Arc<Mutex<DebugSession>>);
+
+impl DebugHandle {
+    /// Detach the session from the VM, and tell its frontend the program is done
+    pub fn finish(self) {
+        debug::detach();
+        self.0.lock().unwrap().frontend.finished();
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use bytecode_source_map::source_map::{FunctionSourceMap, SourceMap};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{PartialVMResult, VMResult},
+    file_format::{CodeOffset, CompiledModule, FunctionDefinitionIndex, TableIndex},
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use move_ir_types::location::Loc;
+use move_lang::{compiled_unit::CompiledUnit, errors::FilesSourceText};
+use move_vm_runtime::{data_cache::MoveStorage, debug::DebugFrame};
+use resource_viewer::MoveValueAnnotator;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+/// A function, as the VM knows it: the module declaring it, or `None` for a script, and the index
+/// of its definition
+pub type FunctionKey = (Option<ModuleId>, TableIndex);
+
+/// A position in a source file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: &'static str,
+    /// The line, counting from 1
+    pub line: usize,
+    /// The column, counting from 1
+    pub column: usize,
+}
+
+#[derive(Debug)]
+struct FunctionSource {
+    name: String,
+    code_map: BTreeMap<CodeOffset, Loc>,
+    local_names: Vec<String>,
+}
+
+/// Maps the code the VM executes back to the Move sources it was compiled from, using the source
+/// maps the compiler produces
+#[derive(Debug, Default)]
+pub struct SourceIndex {
+    files: FilesSourceText,
+    /// The byte offsets at which the lines of each file start
+    line_starts: HashMap<&'static str, Vec<usize>>,
+    /// The lines of each file on which some code starts
+    code_lines: HashMap<&'static str, BTreeSet<usize>>,
+    functions: BTreeMap<FunctionKey, FunctionSource>,
+    modules: Modules,
+}
+
+impl SourceIndex {
+    /// An index of the code compiled from `files`
+    pub fn new(files: FilesSourceText) -> Self {
+        let line_starts = files
+            .iter()
+            .map(|(fname, text)| {
+                let starts = std::iter::once(0)
+                    .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+                    .collect();
+                (*fname, starts)
+            })
+            .collect();
+        Self {
+            files,
+            line_starts,
+            ..Self::default()
+        }
+    }
+
+    /// Add `module`, whose code maps to the sources through `source_map` if it is given. Modules
+    /// without a source map are still needed to show the values of the types they declare.
+    pub fn add_module(&mut self, module: &CompiledModule, source_map: Option<&SourceMap<Loc>>) {
+        let module_id = module.self_id();
+        let mut bytes = vec![];
+        if module.serialize(&mut bytes).is_ok() {
+            self.modules.0.insert(module_id.clone(), bytes);
+        }
+        let source_map = match source_map {
+            Some(source_map) => source_map,
+            None => return,
+        };
+        for idx in 0..module.function_defs().len() {
+            let idx = FunctionDefinitionIndex(idx as TableIndex);
+            let handle = module.function_handle_at(module.function_def_at(idx).function);
+            let name = format!(
+                "0x{}::{}::{}",
+                module_id.address().short_str_lossless(),
+                module_id.name(),
+                module.identifier_at(handle.name)
+            );
+            if let Ok(function_map) = source_map.get_function_source_map(idx) {
+                let function = FunctionSource {
+                    name,
+                    code_map: function_map.code_map.clone(),
+                    local_names: local_names(function_map),
+                };
+                self.add_function((Some(module_id.clone()), idx.0), function);
+            }
+        }
+    }
+
+    /// Add the script whose code maps to the sources through `source_map`
+    pub fn add_script(&mut self, source_map: &SourceMap<Loc>) {
+        if let Ok(function_map) = source_map.get_function_source_map(FunctionDefinitionIndex(0)) {
+            let function = FunctionSource {
+                name: "main".to_string(),
+                code_map: function_map.code_map.clone(),
+                local_names: local_names(function_map),
+            };
+            self.add_function((None, 0), function);
+        }
+    }
+
+    pub fn add_unit(&mut self, unit: &CompiledUnit) {
+        match unit {
+            CompiledUnit::Module {
+                module, source_map, ..
+            } => self.add_module(module, Some(source_map)),
+            CompiledUnit::Script { source_map, .. } => self.add_script(source_map),
+        }
+    }
+
+    fn add_function(&mut self, key: FunctionKey, function: FunctionSource) {
+        for loc in function.code_map.values() {
+            if let Some(location) = self.source_location(*loc) {
+                self.code_lines
+                    .entry(location.file)
+                    .or_default()
+                    .insert(location.line);
+            }
+        }
+        self.functions.insert(key, function);
+    }
+
+    /// The location of the instruction `frame` is at, if its function has a source map
+    pub fn location(&self, frame: &DebugFrame) -> Option<SourceLocation> {
+        let (_, loc) = self
+            .function(frame)?
+            .code_map
+            .range(..=frame.pc)
+            .next_back()?;
+        self.source_location(*loc)
+    }
+
+    /// The names of the parameters and locals of the function of `frame`, in the order of their
+    /// indexes
+    pub fn local_names(&self, frame: &DebugFrame) -> Option<&[String]> {
+        Some(&self.function(frame)?.local_names)
+    }
+
+    fn function(&self, frame: &DebugFrame) -> Option<&FunctionSource> {
+        let key = match &frame.module_id {
+            Some(module_id) => (Some(module_id.clone()), frame.function_index.0),
+            None => (None, 0),
+        };
+        self.functions.get(&key)
+    }
+
+    fn source_location(&self, loc: Loc) -> Option<SourceLocation> {
+        let line_starts = self.line_starts.get(loc.file())?;
+        let offset = loc.span().start().to_usize();
+        let line = match line_starts.binary_search(&offset) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        };
+        Some(SourceLocation {
+            file: loc.file(),
+            line: line + 1,
+            column: offset - line_starts[line] + 1,
+        })
+    }
+
+    /// The text of `line` in `file`, without the line break
+    pub fn line_text(&self, file: &str, line: usize) -> Option<&str> {
+        let text = self.files.get(file)?;
+        let line_starts = self.line_starts.get(file)?;
+        let start = *line_starts.get(line.checked_sub(1)?)?;
+        let end = line_starts.get(line).map_or(text.len(), |end| end - 1);
+        Some(text[start..end].trim_end_matches('\r'))
+    }
+
+    /// The name under which the compiler knows `path`: the name itself, or a name for the same
+    /// file, or the only name that ends with `path`
+    pub fn file_name(&self, path: &str) -> Option<&'static str> {
+        if let Some((fname, _)) = self.files.get_key_value(path) {
+            return Some(fname);
+        }
+        let canonical = fs::canonicalize(path).ok();
+        let mut suffix_matches = vec![];
+        for fname in self.files.keys() {
+            if canonical.is_some() && fs::canonicalize(fname).ok() == canonical {
+                return Some(fname);
+            }
+            if Path::new(fname).ends_with(path) {
+                suffix_matches.push(*fname);
+            }
+        }
+        match suffix_matches.as_slice() {
+            [fname] => Some(fname),
+            _ => None,
+        }
+    }
+
+    /// The first line at or after `line` in `file` on which some code starts
+    pub fn code_line(&self, file: &str, line: usize) -> Option<usize> {
+        self.code_lines.get(file)?.range(line..).next().copied()
+    }
+
+    /// Whether a function called `name` has a source map. Names are fully qualified, as in
+    /// `0x1::Vector::length`.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.functions
+            .values()
+            .any(|function| function.name == name)
+    }
+
+    /// An annotator for the values of the types the modules of this index declare
+    pub(crate) fn annotator(&self) -> MoveValueAnnotator {
+        MoveValueAnnotator::new_no_stdlib(&self.modules)
+    }
+}
+
+fn local_names(function_map: &FunctionSourceMap<Loc>) -> Vec<String> {
+    function_map
+        .parameters
+        .iter()
+        .chain(&function_map.locals)
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// The modules of a `SourceIndex`, as storage for the value annotator
+#[derive(Debug, Default)]
+struct Modules(BTreeMap<ModuleId, Vec<u8>>);
+
+impl MoveStorage for Modules {
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        Ok(self.0.get(module_id).cloned())
+    }
+
+    fn get_resource(
+        &self,
+        _address: &AccountAddress,
+        _tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    value::MoveValue,
+};
+use move_debugger::{repl::Repl, session::DebugSession, source::SourceIndex};
+use move_lang::{compiled_unit::CompiledUnit, shared::Flags};
+use move_vm_runtime::{logging::NoContextLog, move_vm::MoveVM};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+use std::{
+    io::{self, Cursor, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// The output of a `Repl`, which the test reads once the session is done
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `Counter::start` under a REPL that reads `commands`, and return what the REPL printed
+fn debug_counter(commands: &[&str]) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sources/Counter.move");
+    let (files, units) = move_lang::move_compile_and_report(
+        &[path.to_string_lossy().to_string()],
+        &[],
+        None,
+        Flags::empty().set_sources_shadow_deps(false),
+    )
+    .unwrap();
+    let mut index = SourceIndex::new(files);
+    let mut storage = InMemoryStorage::new();
+    for unit in &units {
+        index.add_unit(unit);
+        if let CompiledUnit::Module { module, .. } = unit {
+            let mut blob = vec![];
+            module.serialize(&mut blob).unwrap();
+            storage.publish_or_overwrite_module(module.self_id(), blob);
+        }
+    }
+
+    let input = Cursor::new(format!("{}\n", commands.join("\n")));
+    let output = SharedOutput::default();
+    let session = DebugSession::new(index, Box::new(Repl::new(input, output.clone())));
+    let handle = session.start().unwrap();
+
+    let vm = MoveVM::new();
+    let mut session = vm.new_session(&storage);
+    let args = vec![
+        MoveValue::Signer(AccountAddress::from_hex_literal("0x3").unwrap()),
+        MoveValue::U64(21),
+    ];
+    session
+        .execute_function(
+            &ModuleId::new(
+                AccountAddress::from_hex_literal("0x2").unwrap(),
+                Identifier::new("Counter").unwrap(),
+            ),
+            &Identifier::new("start").unwrap(),
+            vec![],
+            args.iter()
+                .map(|arg| arg.simple_serialize().unwrap())
+                .collect(),
+            &mut GasStatus::new_unmetered(),
+            &NoContextLog::new(),
+        )
+        .unwrap();
+    handle.finish();
+
+    let output = output.0.lock().unwrap();
+    String::from_utf8(output.clone()).unwrap()
+}
+
+// The debugger attaches to every VM in the process, so sessions must not overlap: all the
+// scenarios run in this one test.
+#[test]
+fn debug_session() {
+    let output = debug_counter(&[
+        "break Counter.move:12",
+        "step",
+        "locals",
+        "out",
+        "watch doubled",
+        "continue",
+        "print global<0x2::Counter::Counter>(0x3).value",
+        "print account",
+        "bt",
+        "quit",
+    ]);
+    assert!(output.contains("Stopped in 0x2::Counter::start at "));
+    assert!(output.contains("Breakpoint [1] "));
+    assert!(output.contains("Stepped in 0x2::Counter::double at "));
+    assert!(output.contains("x: u64 = 21"));
+    assert!(output.contains("Stopped at breakpoint 1 in 0x2::Counter::start at "));
+    assert!(output.contains("  [0] doubled: u64 = 42"));
+    assert!(output.contains("global<0x2::Counter::Counter>(0x3).value: u64 = 42"));
+    assert!(output.contains("account: signer = signer(0x3)"));
+    assert!(output.ends_with("Debugging session ended\n"));
+
+    // Without commands, the session stops at the entry and detaches
+    let output = debug_counter(&[]);
+    assert!(output.contains("Stopped in 0x2::Counter::start at "));
+    assert!(output.ends_with("Debugging session ended\n"));
+}
@@ -0,0 +1,15 @@
+module 0x2::Counter {
+    struct Counter has key { value: u64 }
+
+    fun double(x: u64): u64 {
+        let y = x + x;
+        y
+    }
+
+    public fun start(account: signer, value: u64) {
+        let doubled = double(value);
+        move_to(&account, Counter { value: doubled });
+        let total = doubled + 1;
+        assert(total > 1, 42);
+    }
+}
@@ -6,14 +6,17 @@ pub mod test_reporter;
 pub mod test_runner;
 use crate::test_runner::TestRunner;
 use move_core_types::language_storage::ModuleId;
+use move_debugger::{source::SourceIndex, Interface};
 use move_lang::{
     errors,
     shared::{CompilationEnv, Flags},
     unit_test::{self, TestPlan},
     Pass, PassResult,
 };
+use move_vm_runtime::gas_profiler;
 use std::{
-    io::{Result, Write},
+    fs::File,
+    io::{Error, ErrorKind, Result, Write},
     marker::Send,
     sync::Mutex,
 };
@@ -68,6 +71,20 @@ pub struct UnitTestingConfig {
     /// Verbose mode
     #[structopt(short = "v", long = "verbose")]
     pub verbose: bool,
+
+    /// Run the tests one at a time in a debugger, which reads commands from the standard input
+    #[structopt(long = "debug")]
+    pub debug: bool,
+
+    /// Run the tests one at a time in a debugger, which a Debug Adapter Protocol client drives by
+    /// connecting to this address, as in 127.0.0.1:4711
+    #[structopt(long = "debug-adapter", conflicts_with = "debug")]
+    pub debug_adapter: Option<String>,
+
+    /// Report where the tests spent gas, and write it to this file as folded stacks for flame
+    /// graph tools. Every instruction costs the same, as for `instructions`.
+    #[structopt(long = "profile-gas")]
+    pub profile_gas: Option<String>,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -91,6 +108,17 @@ impl UnitTestingConfig {
             check_stackless_vm: false,
             verbose: false,
             list: false,
+            debug: false,
+            debug_adapter: None,
+            profile_gas: None,
+        }
+    }
+
+    fn debug_interface(&self) -> Option<Interface> {
+        match &self.debug_adapter {
+            Some(address) => Some(Interface::Adapter(address.clone())),
+            None if self.debug => Some(Interface::Repl),
+            None => None,
         }
     }
 
@@ -157,10 +185,29 @@ impl UnitTestingConfig {
             return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
+        let debug_handle = match self.debug_interface() {
+            Some(interface) => {
+                let mut index = SourceIndex::new(test_plan.files.clone());
+                for (module, source_map) in test_plan.module_info.values() {
+                    index.add_module(module, Some(source_map));
+                }
+                let handle = move_debugger::start(index, &interface)
+                    .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+                Some(handle)
+            }
+            None => None,
+        };
+        // A debugger follows one test at a time
+        let num_threads = if debug_handle.is_some() {
+            1
+        } else {
+            self.num_threads
+        };
+
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
         let mut test_runner = TestRunner::new(
             self.instruction_execution_bound,
-            self.num_threads,
+            num_threads,
             self.check_stackless_vm,
             self.verbose,
             self.report_storage_on_error,
@@ -172,10 +219,31 @@ impl UnitTestingConfig {
             test_runner.filter(filter_str)
         }
 
+        if self.profile_gas.is_some() {
+            if !move_vm_runtime::DEBUGGING_ENABLED {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "Release builds only profile gas with the `debugging` feature",
+                ));
+            }
+            gas_profiler::start();
+        }
         let test_results = test_runner.run(&shared_writer).unwrap();
+        if let Some(handle) = debug_handle {
+            handle.finish();
+        }
         if self.report_statistics {
             test_results.report_statistics(&shared_writer)?;
         }
+        if let Some(path) = &self.profile_gas {
+            let profile = gas_profiler::finish();
+            write!(
+                shared_writer.lock().unwrap(),
+                "\nGas Profile:\n\n{}",
+                profile
+            )?;
+            profile.write_folded(File::create(path)?)?;
+        }
         let all_tests_passed = test_results.summarize(&shared_writer)?;
 
         let writer = shared_writer.into_inner().unwrap();
@@ -114,6 +114,9 @@ fn run_test(path: &Path) -> datatest_stable::Result<()> {
         report_statistics: false,
         report_storage_on_error: false,
         list: false,
+        debug: false,
+        debug_adapter: None,
+        profile_gas: None,
     };
 
     for ((buffer, _), exp_path) in run_test_with_modifiers(unit_test_config, path)? {
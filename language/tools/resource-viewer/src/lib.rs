@@ -122,12 +122,16 @@ impl<'a> MoveValueAnnotator<'a> {
     }
 
     pub fn view_contract_event(&self, event: &ContractEvent) -> Result<AnnotatedMoveValue> {
-        let ty = self.cache.resolve_type(event.type_tag())?;
+        self.view_value(event.type_tag(), event.event_data())
+    }
+
+    pub fn view_value(&self, ty_tag: &TypeTag, blob: &[u8]) -> Result<AnnotatedMoveValue> {
+        let ty = self.cache.resolve_type(ty_tag)?;
         let move_ty = (&ty)
             .try_into()
             .map_err(|e: PartialVMError| e.finish(Location::Undefined).into_vm_status())?;
 
-        let move_value = MoveValue::simple_deserialize(event.event_data(), &move_ty)?;
+        let move_value = MoveValue::simple_deserialize(blob, &move_ty)?;
         self.annotate_value(&move_value, &ty)
     }
 
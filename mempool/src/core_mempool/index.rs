@@ -65,6 +65,15 @@ impl PriorityIndex {
         self.data.iter().rev()
     }
 
+    /// Returns the lowest ranked transaction if `txn` would be ranked above it.
+    pub(crate) fn get_evictable(&self, txn: &MempoolTransaction) -> Option<TxnPointer> {
+        self.data
+            .iter()
+            .next()
+            .filter(|lowest| self.make_key(txn) > **lowest)
+            .map(TxnPointer::from)
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
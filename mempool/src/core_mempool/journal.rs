@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An append-only on-disk journal of the transactions accepted by mempool, so that pending
+//! transactions survive node restarts.
+//!
+//! Each record is a BCS serialized `SignedTransaction` prefixed by its length (as a little endian
+//! u32). Removals are never journaled: the journal is instead periodically rewritten from the
+//! current mempool contents, and committed or expired transactions are dropped when it's
+//! replayed.
+
+use anyhow::Result;
+use diem_logger::prelude::*;
+use diem_types::transaction::SignedTransaction;
+use std::{
+    convert::TryFrom,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const JOURNAL_FILE_NAME: &str = "txns.journal";
+const COMPACTION_FILE_NAME: &str = "txns.journal.tmp";
+// Records larger than this can't be transactions, so the journal must be corrupt from there on.
+const MAX_RECORD_SIZE: usize = 1 << 24;
+
+pub struct TransactionJournal {
+    dir: PathBuf,
+    file: File,
+}
+
+impl TransactionJournal {
+    /// Opens the journal in `dir` (creating it if needed) and returns it along with the
+    /// transactions it holds.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<(Self, Vec<SignedTransaction>)> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(JOURNAL_FILE_NAME);
+        let (txns, valid_len) = if path.exists() {
+            read_transactions(&path)?
+        } else {
+            (vec![], 0)
+        };
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        // Drop anything after the last complete record so that new records stay readable.
+        file.set_len(valid_len)?;
+
+        Ok((Self { dir, file }, txns))
+    }
+
+    /// Appends a transaction to the journal.
+    pub fn append(&mut self, txn: &SignedTransaction) -> Result<()> {
+        let record = encode_record(txn)?;
+        // A single write per record, so that a crash can only leave the last record torn.
+        self.file.write_all(&record)?;
+        Ok(())
+    }
+
+    /// Atomically replaces the journal content with `txns`.
+    pub fn compact<'a>(&mut self, txns: impl Iterator<Item = &'a SignedTransaction>) -> Result<()> {
+        let compaction_path = self.dir.join(COMPACTION_FILE_NAME);
+        let path = self.dir.join(JOURNAL_FILE_NAME);
+
+        let mut writer = BufWriter::new(File::create(&compaction_path)?);
+        for txn in txns {
+            writer.write_all(&encode_record(txn)?)?;
+        }
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&compaction_path, &path)?;
+
+        self.file = OpenOptions::new().append(true).open(&path)?;
+        Ok(())
+    }
+}
+
+fn encode_record(txn: &SignedTransaction) -> Result<Vec<u8>> {
+    let bytes = bcs::to_bytes(txn)?;
+    let mut record = u32::try_from(bytes.len())?.to_le_bytes().to_vec();
+    record.extend(bytes);
+    Ok(record)
+}
+
+/// Reads all the transactions in the journal file at `path`, along with the length of the
+/// readable part of the file. A torn record at the end of the file (e.g., the node crashed in the
+/// middle of a write) is ignored, and so are records that fail to deserialize.
+fn read_transactions(path: &Path) -> Result<(Vec<SignedTransaction>, u64)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut txns = vec![];
+    let mut valid_len = 0;
+    loop {
+        let mut len_bytes = [0u8; 4];
+        match reader.read_exact(&mut len_bytes) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        if len > MAX_RECORD_SIZE {
+            warn!(
+                "Ignoring the rest of mempool journal {:?} after a record of size {}",
+                path, len
+            );
+            break;
+        }
+        let mut bytes = vec![0u8; len];
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                warn!(
+                    "Ignoring torn record at the end of mempool journal {:?}",
+                    path
+                );
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        valid_len += (len_bytes.len() + len) as u64;
+        match bcs::from_bytes(&bytes) {
+            Ok(txn) => txns.push(txn),
+            Err(e) => warn!(
+                "Skipping invalid record in mempool journal {:?}: {}",
+                path, e
+            ),
+        }
+    }
+    Ok((txns, valid_len))
+}
@@ -23,6 +23,7 @@ use diem_types::{
 use std::{
     cmp::max,
     collections::HashSet,
+    mem,
     time::{Duration, SystemTime},
 };
 
@@ -36,6 +37,9 @@ pub struct Mempool {
     // takes to pick it up by consensus.
     pub(crate) metrics_cache: TtlCache<(AccountAddress, u64), SystemTime>,
     pub system_transaction_timeout: Duration,
+    // Transactions inserted since they were last handed out for journaling, if they're persisted
+    // across restarts.
+    journal_queue: Option<Vec<SignedTransaction>>,
 }
 
 impl Mempool {
@@ -47,6 +51,7 @@ impl Mempool {
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            journal_queue: None,
         }
     }
 
@@ -136,6 +141,14 @@ impl Mempool {
                 .insert((txn.sender(), txn.sequence_number()), SystemTime::now());
         }
 
+        // Resubmissions of a transaction that's already in mempool are accepted, but it's only
+        // journaled once.
+        let journaled_txn = self.journal_queue.as_ref().and_then(|_| {
+            match self.transactions.get(&txn.sender(), txn.sequence_number()) {
+                Some(current_txn) if current_txn == txn => None,
+                _ => Some(txn.clone()),
+            }
+        });
         let txn_info = MempoolTransaction::new(
             txn,
             expiration_time,
@@ -145,7 +158,13 @@ impl Mempool {
             governance_role,
         );
 
-        self.transactions.insert(txn_info, sequence_number)
+        let status = self.transactions.insert(txn_info, sequence_number);
+        if let (MempoolStatusCode::Accepted, Some(journal_queue), Some(txn)) =
+            (status.code, self.journal_queue.as_mut(), journaled_txn)
+        {
+            journal_queue.push(txn);
+        }
+        status
     }
 
     /// Fetches next block of transactions for consensus.
@@ -262,6 +281,25 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Starts queueing the inserted transactions for journaling.
+    pub(crate) fn enable_journal(&mut self) {
+        self.journal_queue.get_or_insert_with(Vec::new);
+    }
+
+    /// Takes the transactions inserted since the last call, so that they can be journaled
+    /// without holding the mempool lock.
+    pub(crate) fn take_journal_queue(&mut self) -> Vec<SignedTransaction> {
+        self.journal_queue
+            .as_mut()
+            .map(mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Returns all the transactions currently in mempool, to rewrite the journal with.
+    pub(crate) fn get_journal_snapshot(&self) -> Vec<SignedTransaction> {
+        self.transactions.iter_txns().cloned().collect()
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
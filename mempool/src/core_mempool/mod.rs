@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod index;
+mod journal;
 mod mempool;
 mod transaction;
 mod transaction_store;
@@ -9,4 +10,7 @@ mod ttl_cache;
 
 #[cfg(test)]
 pub use self::ttl_cache::TtlCache;
-pub use self::{index::TxnPointer, mempool::Mempool as CoreMempool, transaction::TimelineState};
+pub use self::{
+    index::TxnPointer, journal::TransactionJournal, mempool::Mempool as CoreMempool,
+    transaction::TimelineState,
+};
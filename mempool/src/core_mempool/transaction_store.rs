@@ -22,6 +22,7 @@ use diem_types::{
 };
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     ops::Bound,
     time::{Duration, SystemTime},
 };
@@ -46,6 +47,9 @@ pub struct TransactionStore {
     // configuration
     capacity: usize,
     capacity_per_user: usize,
+    replace_by_fee_enabled: bool,
+    replace_by_fee_min_bump_pct: u64,
+    evict_ready_txns_when_full: bool,
 }
 
 impl TransactionStore {
@@ -66,6 +70,9 @@ impl TransactionStore {
             // configuration
             capacity: config.capacity,
             capacity_per_user: config.capacity_per_user,
+            replace_by_fee_enabled: config.replace_by_fee_enabled,
+            replace_by_fee_min_bump_pct: config.replace_by_fee_min_bump_pct,
+            evict_ready_txns_when_full: config.evict_ready_txns_when_full,
         }
     }
 
@@ -103,25 +110,46 @@ impl TransactionStore {
                 if current_version.txn == txn.txn {
                     return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
-                if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
-                    && current_version.txn.payload() == txn.txn.payload()
-                    && current_version.txn.expiration_timestamp_secs()
-                        == txn.txn.expiration_timestamp_secs()
-                    && current_version.get_gas_price() < txn.get_gas_price()
-                {
-                    if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
-                        self.index_remove(&txn);
+                if self.replace_by_fee_enabled {
+                    // any field can change, as long as the gas price is bumped enough
+                    let min_gas_price = min_replacement_gas_price(
+                        current_version.get_gas_price(),
+                        self.replace_by_fee_min_bump_pct,
+                    );
+                    if txn.get_gas_price() < min_gas_price {
+                        return MempoolStatus::new(MempoolStatusCode::GasPriceBumpTooLow)
+                            .with_message(format!(
+                                "gas price: {}, min gas price of replacement: {}",
+                                txn.get_gas_price(),
+                                min_gas_price,
+                            ));
                     }
-                } else {
+                } else if current_version.txn.max_gas_amount() != txn.txn.max_gas_amount()
+                    || current_version.txn.payload() != txn.txn.payload()
+                    || current_version.txn.expiration_timestamp_secs()
+                        != txn.txn.expiration_timestamp_secs()
+                    || current_version.get_gas_price() >= txn.get_gas_price()
+                {
                     return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
                         format!("Failed to update gas price to {}", txn.get_gas_price()),
                     );
                 }
+                if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
+                    self.index_remove(&txn);
+                }
             }
         }
 
         if self.check_is_full_after_eviction(&txn, current_sequence_number) {
-            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+            // only ready transactions can evict ready ones
+            let code = if self.evict_ready_txns_when_full
+                && self.check_txn_ready(&txn, current_sequence_number)
+            {
+                MempoolStatusCode::GasPriceTooLowToEvict
+            } else {
+                MempoolStatusCode::MempoolIsFull
+            };
+            return MempoolStatus::new(code).with_message(format!(
                 "mempool size: {}, capacity: {}",
                 self.system_ttl_index.size(),
                 self.capacity,
@@ -180,7 +208,8 @@ impl TransactionStore {
     }
 
     /// Checks if Mempool is full.
-    /// If it's full, tries to free some space by evicting transactions from the ParkingLot.
+    /// If it's full, tries to free some space by evicting transactions from the ParkingLot, and
+    /// then, if `evict_ready_txns_when_full` is set, the lowest ranked ready transactions.
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion.
     fn check_is_full_after_eviction(
         &mut self,
@@ -192,24 +221,50 @@ impl TransactionStore {
         {
             // try to free some space in Mempool from ParkingLot by evicting a non-ready txn
             if let Some((address, sequence_number)) = self.parking_lot_index.get_poppable() {
-                if let Some(txn) = self
-                    .transactions
-                    .get_mut(&address)
-                    .and_then(|txns| txns.remove(&sequence_number))
-                {
-                    debug!(
-                        LogSchema::new(LogEntry::MempoolFullEvictedTxn).txns(TxnsLog::new_txn(
-                            txn.get_sender(),
-                            txn.get_sequence_number()
-                        ))
-                    );
-                    self.index_remove(&txn);
+                self.evict(&address, sequence_number);
+            }
+            // try to evict ready txns ranked below `txn`; evicting txns of the same account would
+            // make `txn` non-ready
+            while self.evict_ready_txns_when_full && self.system_ttl_index.size() >= self.capacity {
+                match self.priority_index.get_evictable(txn) {
+                    Some((address, sequence_number)) if address != txn.get_sender() => {
+                        self.evict(&address, sequence_number);
+                        self.park_following_txns(&address, sequence_number);
+                    }
+                    _ => break,
                 }
             }
         }
         self.system_ttl_index.size() >= self.capacity
     }
 
+    fn evict(&mut self, address: &AccountAddress, sequence_number: u64) {
+        if let Some(txn) = self
+            .transactions
+            .get_mut(address)
+            .and_then(|txns| txns.remove(&sequence_number))
+        {
+            debug!(
+                LogSchema::new(LogEntry::MempoolFullEvictedTxn).txns(TxnsLog::new_txn(
+                    txn.get_sender(),
+                    txn.get_sequence_number()
+                ))
+            );
+            self.index_remove(&txn);
+        }
+    }
+
+    /// Marks the txns following a removed ready txn of the account as non-ready.
+    fn park_following_txns(&mut self, address: &AccountAddress, sequence_number: u64) {
+        if let Some(txns) = self.transactions.get(address) {
+            for (_, t) in txns.range((Bound::Excluded(sequence_number), Bound::Unbounded)) {
+                self.parking_lot_index.insert(t);
+                self.priority_index.remove(t);
+                self.timeline_index.remove(t);
+            }
+        }
+    }
+
     /// Check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it).
     /// Two ways this can happen:
     /// 1. txn sequence number == curr_sequence_number
@@ -472,6 +527,13 @@ impl TransactionStore {
         self.priority_index.iter()
     }
 
+    /// Iterates over all the transactions in the store, in no particular order.
+    pub(crate) fn iter_txns(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values().map(|txn| &txn.txn))
+    }
+
     pub(crate) fn gen_snapshot(
         &self,
         metrics_cache: &TtlCache<(AccountAddress, u64), SystemTime>,
@@ -496,3 +558,12 @@ impl TransactionStore {
         self.parking_lot_index.size()
     }
 }
+
+/// Returns the min gas price of a transaction replacing a pending one with `gas_price`. It's always
+/// higher than `gas_price`, so that the same transaction can't be resubmitted with different
+/// content for free.
+fn min_replacement_gas_price(gas_price: u64, min_bump_pct: u64) -> u64 {
+    let bump = u64::try_from(u128::from(gas_price) * u128::from(min_bump_pct) / 100)
+        .unwrap_or(u64::max_value());
+    gas_price.saturating_add(bump.max(1))
+}
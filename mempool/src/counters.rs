@@ -76,6 +76,11 @@ pub const SENT_LABEL: &str = "sent";
 pub const INVALID_REQUEST_ID: &str = "invalid_req_id";
 pub const UNKNOWN_PEER: &str = "unknown_peer";
 
+// journal replay result labels
+pub const JOURNAL_REPLAY_ACCEPTED_LABEL: &str = "accepted";
+pub const JOURNAL_REPLAY_EXPIRED_LABEL: &str = "expired";
+pub const JOURNAL_REPLAY_REJECTED_LABEL: &str = "rejected";
+
 /// Counter tracking size of various indices in core mempool
 static CORE_MEMPOOL_INDEX_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -410,6 +415,25 @@ pub static DB_ERROR: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter for number of times writing to the mempool journal failed
+pub static JOURNAL_ERROR_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_mempool_journal_error_count",
+        "Number of times a mempool journal write error was encountered"
+    )
+    .unwrap()
+});
+
+/// Counter for the transactions replayed from the mempool journal on startup, by result
+pub static JOURNAL_REPLAY_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_mempool_journal_replay_txns_count",
+        "Number of transactions replayed from the mempool journal",
+        &["result"]
+    )
+    .unwrap()
+});
+
 /// Gauge for the preference ranking of the current chosen upstream network
 /// to broadcast to
 static UPSTREAM_NETWORK: Lazy<IntGauge> = Lazy::new(|| {
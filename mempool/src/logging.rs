@@ -149,6 +149,7 @@ pub enum LogEntry {
     UpstreamNetwork,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
+    Journal,
 }
 
 #[derive(Clone, Copy, Serialize)]
@@ -171,5 +172,9 @@ pub enum LogEvent {
     SystemTTLExpiration,
     ClientExpiration,
 
+    // journal events
+    Replay,
+    Compaction,
+
     Success,
 }
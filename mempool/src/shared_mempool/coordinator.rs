@@ -279,3 +279,30 @@ pub(crate) async fn snapshot_job(mempool: Arc<Mutex<CoreMempool>>, snapshot_inte
         debug!(LogSchema::new(LogEntry::MempoolSnapshot).txns(snapshot));
     }
 }
+
+/// Replays the transactions reloaded from the mempool journal, then periodically compacts the
+/// journal so that it doesn't grow with transactions that have since left mempool.
+pub(crate) async fn journal_job<V>(
+    smp: SharedMempool<V>,
+    journaled_txns: Vec<SignedTransaction>,
+    compaction_interval_secs: u64,
+) where
+    V: TransactionValidation,
+{
+    info!(LogSchema::event_log(LogEntry::Journal, LogEvent::Start));
+    tasks::process_journaled_transactions(&smp, journaled_txns).await;
+
+    // The first tick is immediate, which drops the replayed transactions that were rejected.
+    let mut interval = IntervalStream::new(interval(Duration::from_secs(compaction_interval_secs)));
+    while let Some(_interval) = interval.next().await {
+        if let Err(e) = tasks::compact_journal(&smp) {
+            counters::JOURNAL_ERROR_COUNT.inc();
+            error!(LogSchema::event_log(LogEntry::Journal, LogEvent::Compaction).error(&e));
+        }
+    }
+
+    error!(LogSchema::event_log(
+        LogEntry::Journal,
+        LogEvent::Terminated
+    ));
+}
@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, TransactionJournal},
+    logging::{LogEntry, LogSchema},
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{coordinator, gc_coordinator, journal_job, snapshot_job},
         peer_manager::PeerManager,
         types::{SharedMempool, SharedMempoolNotification},
     },
@@ -15,6 +16,7 @@ use anyhow::Result;
 use channel::diem_channel;
 use diem_config::{config::NodeConfig, network_id::NodeNetworkId};
 use diem_infallible::{Mutex, RwLock};
+use diem_logger::prelude::*;
 use diem_types::{on_chain_config::OnChainConfigPayload, transaction::SignedTransaction};
 use futures::channel::{
     mpsc::{self, Receiver, UnboundedSender},
@@ -30,6 +32,7 @@ use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - journal_task (task that replays and compacts the transaction journal, if enabled).
 pub(crate) fn start_shared_mempool<V>(
     executor: &Handle,
     config: &NodeConfig,
@@ -56,6 +59,25 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders.insert(network_id, network_sender);
     }
 
+    // Open the journal before any transaction is accepted, so that none is missing from it.
+    let (journal, journaled_txns) = if config.mempool.journal_enabled {
+        match TransactionJournal::open(config.mempool.journal_dir()) {
+            Ok((journal, txns)) => {
+                mempool.lock().enable_journal();
+                (Some(Arc::new(Mutex::new(journal))), Some(txns))
+            }
+            Err(e) => {
+                error!(
+                    LogSchema::new(LogEntry::Journal).error(&e),
+                    "Failed to open the mempool journal, transactions won't be persisted"
+                );
+                (None, None)
+            }
+        }
+    } else {
+        (None, None)
+    };
+
     let smp = SharedMempool {
         mempool: mempool.clone(),
         config: config.mempool.clone(),
@@ -64,8 +86,17 @@ pub(crate) fn start_shared_mempool<V>(
         validator,
         peer_manager,
         subscribers,
+        journal,
     };
 
+    if let Some(journaled_txns) = journaled_txns {
+        executor.spawn(journal_job(
+            smp.clone(),
+            journaled_txns,
+            config.mempool.journal_compaction_interval_secs,
+        ));
+    }
+
     executor.spawn(coordinator(
         smp,
         executor.clone(),
@@ -146,7 +146,10 @@ fn gen_ack_response(
     let mut retry = false;
     for r in results.into_iter() {
         let submission_status = r.1;
-        if submission_status.0.code == MempoolStatusCode::MempoolIsFull {
+        if matches!(
+            submission_status.0.code,
+            MempoolStatusCode::MempoolIsFull | MempoolStatusCode::GasPriceTooLowToEvict
+        ) {
             backoff = true;
         }
         if is_txn_retryable(submission_status) {
@@ -195,6 +198,21 @@ pub(crate) async fn process_incoming_transactions<V>(
     transactions: Vec<SignedTransaction>,
     timeline_state: TimelineState,
 ) -> Vec<SubmissionStatusBundle>
+where
+    V: TransactionValidation,
+{
+    let (statuses, inserted_txns) = add_transactions(smp, transactions, timeline_state);
+    journal_transactions(smp, &inserted_txns);
+    statuses
+}
+
+/// Validates and adds a list of SignedTransaction to the local mempool. Returns the statuses of
+/// the transactions along with the ones newly inserted, which have yet to be journaled.
+fn add_transactions<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    timeline_state: TimelineState,
+) -> (Vec<SubmissionStatusBundle>, Vec<SignedTransaction>)
 where
     V: TransactionValidation,
 {
@@ -258,7 +276,7 @@ where
         .collect::<Vec<_>>();
     vm_validation_timer.stop_and_record();
 
-    {
+    let inserted_txns = {
         let mut mempool = smp.mempool.lock();
         for (idx, (transaction, sequence_number)) in transactions.into_iter().enumerate() {
             if let Ok(validation_result) = &validation_results[idx] {
@@ -289,9 +307,88 @@ where
                 }
             }
         }
-    }
+        mempool.take_journal_queue()
+    };
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
-    statuses
+    (statuses, inserted_txns)
+}
+
+/// Appends the transactions newly inserted into mempool to the journal, if any.
+fn journal_transactions<V>(smp: &SharedMempool<V>, transactions: &[SignedTransaction])
+where
+    V: TransactionValidation,
+{
+    if let (Some(journal), false) = (&smp.journal, transactions.is_empty()) {
+        let mut journal = journal.lock();
+        for txn in transactions {
+            if let Err(e) = journal.append(txn) {
+                counters::JOURNAL_ERROR_COUNT.inc();
+                error!(LogSchema::new(LogEntry::Journal).error(&e));
+            }
+        }
+    }
+}
+
+/// Rewrites the journal (if any) with the transactions currently in mempool, dropping the ones
+/// that have been committed or garbage collected since they were journaled.
+pub(crate) fn compact_journal<V>(smp: &SharedMempool<V>) -> Result<()>
+where
+    V: TransactionValidation,
+{
+    if let Some(journal) = &smp.journal {
+        // The journal is locked before mempool is read, so that the transactions inserted after
+        // the read are appended to the compacted journal.
+        let mut journal = journal.lock();
+        let txns = smp.mempool.lock().get_journal_snapshot();
+        journal.compact(txns.iter())?;
+    }
+    Ok(())
+}
+
+/// Re-validates and re-adds the transactions reloaded from the mempool journal on startup.
+/// Transactions that have expired, have been committed or are otherwise no longer valid are
+/// dropped.
+pub(crate) async fn process_journaled_transactions<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+) where
+    V: TransactionValidation,
+{
+    let now = diem_infallible::duration_since_epoch();
+    let (transactions, expired): (Vec<_>, Vec<_>) = transactions
+        .into_iter()
+        .partition(|t| Duration::from_secs(t.expiration_timestamp_secs()) > now);
+
+    let num_txns = transactions.len();
+    let num_accepted = if transactions.is_empty() {
+        0
+    } else {
+        // The transactions are already in the journal, so they aren't appended again.
+        add_transactions(smp, transactions, TimelineState::NotReady)
+            .0
+            .iter()
+            .filter(|(_, (mempool_status, _))| mempool_status.code == MempoolStatusCode::Accepted)
+            .count()
+    };
+    let num_rejected = num_txns - num_accepted;
+
+    for (label, count) in [
+        (counters::JOURNAL_REPLAY_ACCEPTED_LABEL, num_accepted),
+        (counters::JOURNAL_REPLAY_EXPIRED_LABEL, expired.len()),
+        (counters::JOURNAL_REPLAY_REJECTED_LABEL, num_rejected),
+    ]
+    .iter()
+    {
+        counters::JOURNAL_REPLAY_TXNS
+            .with_label_values(&[label])
+            .inc_by(*count as u64);
+    }
+    info!(
+        LogSchema::event_log(LogEntry::Journal, LogEvent::Replay),
+        accepted = num_accepted,
+        expired = expired.len(),
+        rejected = num_rejected
+    );
 }
 
 fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<PeerNetworkId>) {
@@ -4,7 +4,7 @@
 //! Objects used by/related to shared mempool
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, TransactionJournal},
     shared_mempool::{network::MempoolNetworkSender, peer_manager::PeerManager},
 };
 use anyhow::Result;
@@ -45,6 +45,8 @@ where
     pub validator: Arc<RwLock<V>>,
     pub peer_manager: Arc<PeerManager>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    // Journal of the transactions inserted into mempool, if they're persisted across restarts.
+    pub journal: Option<Arc<Mutex<TransactionJournal>>>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
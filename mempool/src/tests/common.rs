@@ -29,6 +29,8 @@ static ACCOUNTS: Lazy<Vec<AccountAddress>> = Lazy::new(|| {
         AccountAddress::random(),
         AccountAddress::random(),
         AccountAddress::random(),
+        AccountAddress::random(),
+        AccountAddress::random(),
     ]
 });
 
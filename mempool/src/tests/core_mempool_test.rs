@@ -2,14 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{CoreMempool, TimelineState, TransactionJournal, TtlCache},
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
     },
 };
 use diem_config::config::NodeConfig;
-use diem_types::transaction::{GovernanceRole, SignedTransaction};
+use diem_temppath::TempPath;
+use diem_types::{
+    mempool_status::MempoolStatusCode,
+    transaction::{GovernanceRole, SignedTransaction},
+};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -188,6 +192,46 @@ fn test_update_invalid_transaction_in_mempool() {
     assert_eq!(next_tnx[0].gas_unit_price(), 1);
 }
 
+#[test]
+fn test_replace_by_fee() {
+    let mut config = NodeConfig::random();
+    config.mempool.replace_by_fee_enabled = true;
+    config.mempool.replace_by_fee_min_bump_pct = 10;
+    let mut pool = CoreMempool::new(&config);
+    let insert = |pool: &mut CoreMempool, txn: SignedTransaction| {
+        let gas_price = txn.gas_unit_price();
+        pool.add_txn(
+            txn,
+            0,
+            gas_price,
+            0,
+            TimelineState::NotReady,
+            GovernanceRole::NonGovernanceRole,
+        )
+        .code
+    };
+    add_txn(&mut pool, TestTransaction::new(0, 0, 100)).unwrap();
+
+    // Any field can change, but the gas price must be bumped by at least 10%.
+    let txn = TestTransaction::new(0, 0, 109).make_signed_transaction_with_max_gas_amount(200);
+    assert_eq!(
+        insert(&mut pool, txn),
+        MempoolStatusCode::GasPriceBumpTooLow
+    );
+    let txn = TestTransaction::new(0, 0, 110).make_signed_transaction_with_max_gas_amount(200);
+    assert_eq!(insert(&mut pool, txn.clone()), MempoolStatusCode::Accepted);
+    assert_eq!(pool.get_block(2, HashSet::new()), vec![txn]);
+
+    // A zero gas price still needs a bump.
+    add_txn(&mut pool, TestTransaction::new(1, 0, 0)).unwrap();
+    let txn = TestTransaction::new(1, 0, 0).make_signed_transaction_with_max_gas_amount(200);
+    assert_eq!(
+        insert(&mut pool, txn),
+        MempoolStatusCode::GasPriceBumpTooLow
+    );
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 0, 1)).is_ok());
+}
+
 #[test]
 fn test_remove_transaction() {
     let (mut pool, mut consensus) = setup_mempool();
@@ -397,6 +441,61 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     }
 }
 
+#[test]
+fn test_evict_ready_txns_when_full() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.evict_ready_txns_when_full = true;
+    let mut pool = CoreMempool::new(&config);
+    let code = |pool: &mut CoreMempool, txn: TestTransaction| {
+        let txn = txn.make_signed_transaction();
+        let gas_price = txn.gas_unit_price();
+        pool.add_txn(
+            txn,
+            0,
+            gas_price,
+            0,
+            TimelineState::NotReady,
+            GovernanceRole::NonGovernanceRole,
+        )
+        .code
+    };
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 5)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(2, 5, 1)).unwrap();
+
+    // The parking lot is evicted first, then the lowest ranked ready txn.
+    assert_eq!(
+        code(&mut pool, TestTransaction::new(3, 0, 3)),
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(
+        code(&mut pool, TestTransaction::new(4, 0, 4)),
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(pool.get_parking_lot_size(), 0);
+    // The sequence number cache is as large as the mempool, so it no longer knows every sender
+    // and `get_block` can't be used to list the txns left.
+    let (timeline, _) = pool.read_timeline(0, 10);
+    let mut gas_prices: Vec<_> = timeline
+        .iter()
+        .map(SignedTransaction::gas_unit_price)
+        .collect();
+    gas_prices.sort_unstable();
+    assert_eq!(gas_prices, vec![3, 4, 5]);
+
+    // Txns ranked below every ready txn are rejected.
+    assert_eq!(
+        code(&mut pool, TestTransaction::new(5, 0, 2)),
+        MempoolStatusCode::GasPriceTooLowToEvict
+    );
+    // Non-ready txns don't evict ready ones.
+    assert_eq!(
+        code(&mut pool, TestTransaction::new(5, 1, 10)),
+        MempoolStatusCode::MempoolIsFull
+    );
+}
+
 #[test]
 fn test_gc_ready_transaction() {
     let mut pool = setup_mempool().0;
@@ -479,3 +578,40 @@ fn test_ttl_cache() {
         .unwrap());
     assert_eq!(cache.size(), 0);
 }
+
+#[test]
+fn test_journal() {
+    let tmp_dir = TempPath::new();
+    let (mut pool, _) = setup_mempool();
+    let (mut journal, journaled_txns) = TransactionJournal::open(tmp_dir.path()).unwrap();
+    assert!(journaled_txns.is_empty());
+
+    // Nothing is queued for journaling until it's enabled.
+    let mut txns = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 1)]);
+    assert!(pool.take_journal_queue().is_empty());
+    pool.enable_journal();
+
+    // Inserted transactions are queued, rejected and resubmitted ones aren't.
+    let inserted_txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 1, 1), TestTransaction::new(1, 0, 1)],
+    );
+    add_txn(&mut pool, TestTransaction::new(0, 1, 0)).unwrap_err();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    assert_eq!(pool.take_journal_queue(), inserted_txns);
+    assert!(pool.take_journal_queue().is_empty());
+    txns.extend(inserted_txns);
+    for txn in &txns {
+        journal.append(txn).unwrap();
+    }
+    let (_, journaled_txns) = TransactionJournal::open(tmp_dir.path()).unwrap();
+    assert_eq!(journaled_txns, txns);
+
+    // Compaction drops the transactions that have left mempool.
+    pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    journal.compact(pool.get_journal_snapshot().iter()).unwrap();
+    let (_, journaled_txns) = TransactionJournal::open(tmp_dir.path()).unwrap();
+    let journaled_txns: HashSet<_> = journaled_txns.into_iter().collect();
+    let expected_txns: HashSet<_> = txns[1..].iter().cloned().collect();
+    assert_eq!(journaled_txns, expected_txns);
+}
@@ -2,15 +2,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    core_mempool::{CoreMempool, TransactionJournal},
     mocks::MockSharedMempool,
+    shared_mempool::{peer_manager::PeerManager, tasks, types::SharedMempool},
     tests::common::{batch_add_signed_txn, TestTransaction},
     CommitNotification, CommittedTransaction, ConsensusRequest,
 };
+use diem_config::config::NodeConfig;
+use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use diem_infallible::{Mutex, RwLock};
+use diem_temppath::TempPath;
+use diem_types::{
+    account_address::AccountAddress,
+    account_config::XUS_NAME,
+    chain_id::ChainId,
+    transaction::{RawTransaction, Script},
+};
 use futures::{
     channel::{mpsc, oneshot},
     executor::block_on,
     sink::SinkExt,
 };
+use std::{collections::HashMap, sync::Arc};
+use storage_interface::mock::MockDbReader;
+use vm_validator::mocks::mock_vm_validator::MockVMValidator;
 
 #[test]
 fn test_consensus_events_rejected_txns() {
@@ -96,3 +111,49 @@ fn test_state_sync_events_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_process_journaled_transactions() {
+    let tmp_dir = TempPath::new();
+    let (journal, _) = TransactionJournal::open(tmp_dir.path()).unwrap();
+    let config = NodeConfig::default();
+    let smp = SharedMempool {
+        mempool: Arc::new(Mutex::new(CoreMempool::new(&config))),
+        config: config.mempool.clone(),
+        network_senders: HashMap::new(),
+        db: Arc::new(MockDbReader),
+        validator: Arc::new(RwLock::new(MockVMValidator)),
+        peer_manager: Arc::new(PeerManager::new(config.base.role, config.mempool)),
+        subscribers: vec![],
+        journal: Some(Arc::new(Mutex::new(journal))),
+    };
+    smp.mempool.lock().enable_journal();
+
+    let expired_txn = TestTransaction::new(0, 0, 1).make_signed_transaction_with_expiration_time(0);
+    let kept_txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    // The mock validator reports the transactions of this sender as already committed.
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let committed_txn = RawTransaction::new_script(
+        AccountAddress::new([4; AccountAddress::LENGTH]),
+        0,
+        Script::new(vec![], vec![], vec![]),
+        100,
+        1,
+        XUS_NAME.to_owned(),
+        u64::max_value(),
+        ChainId::test(),
+    )
+    .sign(&private_key, private_key.public_key())
+    .unwrap()
+    .into_inner();
+
+    block_on(tasks::process_journaled_transactions(
+        &smp,
+        vec![expired_txn, committed_txn, kept_txn.clone()],
+    ));
+
+    // Only the valid transaction is re-added, and it isn't appended to the journal again.
+    assert_eq!(smp.mempool.lock().get_journal_snapshot(), vec![kept_txn]);
+    let (_, journaled_txns) = TransactionJournal::open(tmp_dir.path()).unwrap();
+    assert!(journaled_txns.is_empty());
+}
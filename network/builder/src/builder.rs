@@ -12,10 +12,11 @@
 use channel::{self, message_queues::QueueStyle};
 use diem_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RateLimitConfig, RoleType,
-        CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS,
-        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
-        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        CompressionConfig, DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, PriorityConfig,
+        RateLimitConfig, RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
+        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, MAX_MESSAGE_SIZE,
+        NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -40,9 +41,13 @@ use network::{
     ProtocolId,
 };
 use network_simple_onchain_discovery::{
-    builder::ValidatorSetChangeListenerBuilder, gen_simple_discovery_reconfig_subscription,
+    builder::{
+        DnsDiscoveryListenerBuilder, FileDiscoveryListenerBuilder,
+        ValidatorSetChangeListenerBuilder,
+    },
+    gen_simple_discovery_reconfig_subscription,
 };
-use std::{clone::Clone, collections::HashMap, sync::Arc};
+use std::{clone::Clone, collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 use subscription_service::ReconfigSubscription;
 use tokio::runtime::Handle;
 
@@ -65,6 +70,8 @@ pub struct NetworkBuilder {
     network_context: Arc<NetworkContext>,
 
     validator_set_listener_builder: Option<ValidatorSetChangeListenerBuilder>,
+    file_discovery_listener_builders: Vec<FileDiscoveryListenerBuilder>,
+    dns_discovery_listener_builders: Vec<DnsDiscoveryListenerBuilder>,
     connectivity_manager_builder: Option<ConnectivityManagerBuilder>,
     health_checker_builder: Option<HealthCheckerBuilder>,
     peer_manager_builder: PeerManagerBuilder,
@@ -84,6 +91,9 @@ impl NetworkBuilder {
         listen_address: NetworkAddress,
         authentication_mode: AuthenticationMode,
         max_frame_size: usize,
+        max_message_size: usize,
+        compression_config: CompressionConfig,
+        priority_config: PriorityConfig,
         enable_proxy_protocol: bool,
         network_channel_size: usize,
         max_concurrent_network_reqs: usize,
@@ -103,6 +113,9 @@ impl NetworkBuilder {
             network_channel_size,
             max_concurrent_network_reqs,
             max_frame_size,
+            max_message_size,
+            compression_config,
+            priority_config,
             enable_proxy_protocol,
             inbound_connection_limit,
             inbound_rate_limit_config,
@@ -115,6 +128,8 @@ impl NetworkBuilder {
             time_service,
             network_context,
             validator_set_listener_builder: None,
+            file_discovery_listener_builders: Vec::new(),
+            dns_discovery_listener_builders: Vec::new(),
             connectivity_manager_builder: None,
             health_checker_builder: None,
             peer_manager_builder,
@@ -141,6 +156,9 @@ impl NetworkBuilder {
             listen_address,
             authentication_mode,
             MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+            CompressionConfig::default(),
+            PriorityConfig::default(),
             false, /* Disable proxy protocol */
             NETWORK_CHANNEL_SIZE,
             MAX_CONCURRENT_NETWORK_REQS,
@@ -196,6 +214,9 @@ impl NetworkBuilder {
             config.listen_address.clone(),
             authentication_mode,
             config.max_frame_size,
+            config.max_message_size,
+            config.compression.clone(),
+            config.priority.clone(),
             config.enable_proxy_protocol,
             config.network_channel_size,
             config.max_concurrent_network_reqs,
@@ -223,7 +244,7 @@ impl NetworkBuilder {
         // TODO(philiphayes): could probably use a better way to specify these cases
         // TODO:  Why not add ConnectivityManager always?
         if config.mutual_authentication
-            || config.discovery_method != DiscoveryMethod::None
+            || !config.discovery_methods().is_empty()
             || !config.seed_addrs.is_empty()
             || !config.seeds.is_empty()
         {
@@ -241,11 +262,26 @@ impl NetworkBuilder {
             );
         }
 
-        match &config.discovery_method {
-            DiscoveryMethod::Onchain => {
-                network_builder.add_validator_set_listener(pubkey, config.encryptor());
+        for discovery_method in config.discovery_methods() {
+            match discovery_method {
+                DiscoveryMethod::Onchain => {
+                    network_builder.add_validator_set_listener(pubkey, config.encryptor());
+                }
+                DiscoveryMethod::File(file_discovery) => {
+                    network_builder.add_file_discovery_listener(
+                        file_discovery.path.clone(),
+                        Duration::from_secs(file_discovery.interval_secs),
+                    );
+                }
+                DiscoveryMethod::Dns(dns_discovery) => {
+                    network_builder.add_dns_discovery_listener(
+                        dns_discovery.domain.clone(),
+                        dns_discovery.role,
+                        Duration::from_secs(dns_discovery.interval_secs),
+                    );
+                }
+                DiscoveryMethod::None => {}
             }
-            DiscoveryMethod::None => {}
         }
 
         network_builder
@@ -296,6 +332,22 @@ impl NetworkBuilder {
                 "{} Started validator set listener", self.network_context
             );
         }
+
+        for file_discovery_listener_builder in self.file_discovery_listener_builders.iter_mut() {
+            file_discovery_listener_builder.start(executor);
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started file discovery listener", self.network_context
+            );
+        }
+
+        for dns_discovery_listener_builder in self.dns_discovery_listener_builders.iter_mut() {
+            dns_discovery_listener_builder.start(executor);
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started DNS discovery listener", self.network_context
+            );
+        }
         self
     }
 
@@ -381,6 +433,40 @@ impl NetworkBuilder {
         self
     }
 
+    fn add_file_discovery_listener(&mut self, path: PathBuf, interval: Duration) -> &mut Self {
+        let conn_mgr_reqs_tx = self
+            .conn_mgr_reqs_tx()
+            .expect("ConnectivityManager must be installed for file discovery");
+        self.file_discovery_listener_builders
+            .push(FileDiscoveryListenerBuilder::create(
+                self.network_context.clone(),
+                path,
+                interval,
+                conn_mgr_reqs_tx,
+            ));
+        self
+    }
+
+    fn add_dns_discovery_listener(
+        &mut self,
+        domain: String,
+        role: PeerRole,
+        interval: Duration,
+    ) -> &mut Self {
+        let conn_mgr_reqs_tx = self
+            .conn_mgr_reqs_tx()
+            .expect("ConnectivityManager must be installed for DNS discovery");
+        self.dns_discovery_listener_builders
+            .push(DnsDiscoveryListenerBuilder::create(
+                self.network_context.clone(),
+                domain,
+                role,
+                interval,
+                conn_mgr_reqs_tx,
+            ));
+        self
+    }
+
     /// Add a HealthChecker to the network.
     fn add_connection_monitoring(
         &mut self,
@@ -1,14 +1,14 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::ValidatorSetChangeListener;
+use crate::{dns::DnsDiscoveryListener, file::FileDiscoveryListener, ValidatorSetChangeListener};
 use channel::diem_channel;
-use diem_config::network_id::NetworkContext;
+use diem_config::{config::PeerRole, network_id::NetworkContext};
 use diem_crypto::x25519::PublicKey;
 use diem_network_address_encryption::Encryptor;
 use diem_types::on_chain_config::OnChainConfigPayload;
 use network::connectivity_manager::ConnectivityRequest;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 
 pub struct ValidatorSetChangeListenerBuilder {
@@ -40,3 +40,61 @@ impl ValidatorSetChangeListenerBuilder {
         self
     }
 }
+
+pub struct FileDiscoveryListenerBuilder {
+    listener: Option<FileDiscoveryListener>,
+}
+
+impl FileDiscoveryListenerBuilder {
+    pub fn create(
+        network_context: Arc<NetworkContext>,
+        path: PathBuf,
+        interval: Duration,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> FileDiscoveryListenerBuilder {
+        Self {
+            listener: Some(FileDiscoveryListener::new(
+                network_context,
+                path,
+                interval,
+                conn_mgr_reqs_tx,
+            )),
+        }
+    }
+
+    pub fn start(&mut self, executor: &Handle) -> &mut Self {
+        let listener = self.listener.take().expect("Listener must be built");
+        executor.spawn(listener.start());
+        self
+    }
+}
+
+pub struct DnsDiscoveryListenerBuilder {
+    listener: Option<DnsDiscoveryListener>,
+}
+
+impl DnsDiscoveryListenerBuilder {
+    pub fn create(
+        network_context: Arc<NetworkContext>,
+        domain: String,
+        role: PeerRole,
+        interval: Duration,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> DnsDiscoveryListenerBuilder {
+        Self {
+            listener: Some(DnsDiscoveryListener::new(
+                network_context,
+                domain,
+                role,
+                interval,
+                conn_mgr_reqs_tx,
+            )),
+        }
+    }
+
+    pub fn start(&mut self, executor: &Handle) -> &mut Self {
+        let listener = self.listener.take().expect("Listener must be built");
+        executor.spawn(listener.start());
+        self
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{send_update, DISCOVERY_COUNTS};
+use anyhow::{anyhow, Context};
+use diem_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use diem_crypto::{traits::ValidCryptoMaterialStringExt, x25519};
+use diem_logger::prelude::*;
+use diem_types::{account_address::from_identity_public_key, network_address::NetworkAddress};
+use network::{
+    connectivity_manager::{ConnectivityRequest, DiscoverySource},
+    counters::inc_by_with_context,
+    logging::NetworkSchema,
+};
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
+use trust_dns_resolver::{
+    error::{ResolveError, ResolveErrorKind},
+    TokioAsyncResolver,
+};
+
+/// Listener which periodically resolves the DNS records of a domain into peers and sends them to
+/// the ConnectivityManager when they change.
+///
+/// Peers are discovered from two kinds of records:
+/// * TXT records of the domain, each one being a full `NetworkAddress` of a peer, e.g.
+///   `/dns/node.example.com/tcp/6180/ln-noise-ik/<pubkey>/ln-handshake/0`.
+/// * SRV records of the domain, each one pointing to the host and port of a peer, whose TXT
+///   record is the hex encoded x25519 public key of the peer.
+///
+/// In both cases, the peer id is derived from the public key of the peer.
+pub struct DnsDiscoveryListener {
+    network_context: Arc<NetworkContext>,
+    domain: String,
+    role: PeerRole,
+    interval: Duration,
+    conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+}
+
+/// Parses the content of a TXT record holding the `NetworkAddress` of a peer.
+pub fn parse_txt_address(txt: &str) -> anyhow::Result<NetworkAddress> {
+    let addr = NetworkAddress::from_str(txt.trim())
+        .with_context(|| format!("Invalid network address in TXT record: {}", txt))?;
+    if !addr.is_diemnet_addr() {
+        return Err(anyhow!("Not a DiemNet address in TXT record: {}", txt));
+    }
+    Ok(addr)
+}
+
+/// Builds the `NetworkAddress` of a peer from the target and port of its SRV record and the
+/// content of the TXT record of the target.
+pub fn srv_address(target: &str, port: u16, txt: &str) -> anyhow::Result<NetworkAddress> {
+    let pubkey = x25519::PublicKey::from_encoded_string(txt.trim())
+        .map_err(|err| anyhow!("Invalid public key in TXT record of {}: {}", target, err))?;
+    let addr = NetworkAddress::from_str(&format!(
+        "/dns/{}/tcp/{}",
+        target.trim_end_matches('.'),
+        port
+    ))
+    .with_context(|| format!("Invalid SRV record target: {}", target))?;
+    Ok(addr.append_prod_protos(pubkey, HANDSHAKE_VERSION))
+}
+
+/// Groups addresses by peer, the peer id being derived from the key of the addresses.
+pub fn peer_set_from_addrs(role: PeerRole, addrs: Vec<NetworkAddress>) -> PeerSet {
+    let mut peer_set = PeerSet::new();
+    for addr in addrs {
+        if let Some(pubkey) = addr.find_noise_proto() {
+            let peer = peer_set
+                .entry(from_identity_public_key(pubkey))
+                .or_insert_with(|| Peer::new(vec![], HashSet::new(), role));
+            peer.keys.insert(pubkey);
+            peer.addresses.push(addr);
+        }
+    }
+    peer_set
+}
+
+fn is_no_records_found(err: &ResolveError) -> bool {
+    matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. })
+}
+
+/// Concatenates the character strings of a TXT record.
+fn txt_to_string(txt: &trust_dns_resolver::proto::rr::rdata::TXT) -> String {
+    txt.txt_data()
+        .iter()
+        .map(|data| String::from_utf8_lossy(data).into_owned())
+        .collect()
+}
+
+impl DnsDiscoveryListener {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        domain: String,
+        role: PeerRole,
+        interval: Duration,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> Self {
+        Self {
+            network_context,
+            domain,
+            role,
+            interval,
+            conn_mgr_reqs_tx,
+        }
+    }
+
+    fn log_invalid_record(&self, err: anyhow::Error) {
+        inc_by_with_context(
+            &DISCOVERY_COUNTS,
+            &self.network_context,
+            "dns_invalid_record",
+            1,
+        );
+        warn!(
+            NetworkSchema::new(&self.network_context),
+            "DnsDiscovery: {:#}", err
+        );
+    }
+
+    async fn resolve_txt(&self, resolver: &TokioAsyncResolver) -> anyhow::Result<Vec<String>> {
+        match resolver.txt_lookup(self.domain.as_str()).await {
+            Ok(lookup) => Ok(lookup.iter().map(txt_to_string).collect()),
+            Err(err) if is_no_records_found(&err) => Ok(vec![]),
+            Err(err) => Err(err).with_context(|| format!("Failed to resolve TXT {}", self.domain)),
+        }
+    }
+
+    async fn resolve_srv(
+        &self,
+        resolver: &TokioAsyncResolver,
+    ) -> anyhow::Result<Vec<NetworkAddress>> {
+        let lookup = match resolver.srv_lookup(self.domain.as_str()).await {
+            Ok(lookup) => lookup,
+            Err(err) if is_no_records_found(&err) => return Ok(vec![]),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to resolve SRV {}", self.domain))
+            }
+        };
+
+        let mut addrs = vec![];
+        for srv in lookup.iter() {
+            let target = srv.target().to_utf8();
+            let txts = match resolver.txt_lookup(target.as_str()).await {
+                Ok(txts) => txts,
+                Err(err) => {
+                    self.log_invalid_record(
+                        anyhow::Error::new(err)
+                            .context(format!("Failed to resolve TXT {}", target)),
+                    );
+                    continue;
+                }
+            };
+            match txts
+                .iter()
+                .find_map(|txt| srv_address(&target, srv.port(), &txt_to_string(txt)).ok())
+            {
+                Some(addr) => addrs.push(addr),
+                None => self.log_invalid_record(anyhow!("No public key in TXT {}", target)),
+            }
+        }
+        Ok(addrs)
+    }
+
+    async fn resolve_peers(&self, resolver: &TokioAsyncResolver) -> anyhow::Result<PeerSet> {
+        let mut addrs = vec![];
+        for txt in self.resolve_txt(resolver).await? {
+            match parse_txt_address(&txt) {
+                Ok(addr) => addrs.push(addr),
+                Err(err) => self.log_invalid_record(err),
+            }
+        }
+        addrs.extend(self.resolve_srv(resolver).await?);
+        Ok(peer_set_from_addrs(self.role, addrs))
+    }
+
+    /// Starts the listener to resolve the records at every interval.
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Starting DNS Discovery actor on {}", self.network_context, self.domain
+        );
+
+        let resolver = match TokioAsyncResolver::tokio_from_system_conf() {
+            Ok(resolver) => resolver,
+            Err(err) => {
+                error!(
+                    NetworkSchema::new(&self.network_context),
+                    "DnsDiscovery: Failed to create resolver: {}", err
+                );
+                return;
+            }
+        };
+
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_peer_set = None;
+        loop {
+            interval.tick().await;
+
+            match self.resolve_peers(&resolver).await {
+                Ok(peer_set) => {
+                    if last_peer_set.as_ref() == Some(&peer_set) {
+                        continue;
+                    }
+                    info!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Resolved {} peers from {}",
+                        self.network_context,
+                        peer_set.len(),
+                        self.domain
+                    );
+                    last_peer_set = Some(peer_set.clone());
+                    let request =
+                        ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Dns, peer_set);
+                    send_update(&self.network_context, &mut self.conn_mgr_reqs_tx, request).await;
+                }
+                Err(err) => {
+                    // Keep the last peers on transient resolution failures.
+                    inc_by_with_context(
+                        &DISCOVERY_COUNTS,
+                        &self.network_context,
+                        "dns_resolve_failure",
+                        1,
+                    );
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "DnsDiscovery: {:#}", err
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBKEY: &str = "080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120";
+
+    #[test]
+    fn parse_txt_records() {
+        let txt = format!(
+            "/dns/node.example.com/tcp/6180/ln-noise-ik/{}/ln-handshake/0",
+            PUBKEY
+        );
+        let addr = parse_txt_address(&txt).unwrap();
+        assert_eq!(addr.to_string(), txt);
+
+        // Addresses without keys can't be dialed
+        assert!(parse_txt_address("/dns/node.example.com/tcp/6180").is_err());
+        assert!(parse_txt_address("v=spf1 -all").is_err());
+    }
+
+    #[test]
+    fn build_srv_addresses() {
+        let addr = srv_address("node.example.com.", 6180, PUBKEY).unwrap();
+        assert_eq!(
+            addr.to_string(),
+            format!(
+                "/dns/node.example.com/tcp/6180/ln-noise-ik/{}/ln-handshake/0",
+                PUBKEY
+            )
+        );
+        assert!(srv_address("node.example.com.", 6180, "not a key").is_err());
+    }
+
+    #[test]
+    fn group_addresses_by_peer() {
+        let addr_1 = srv_address("node-1.example.com.", 6180, PUBKEY).unwrap();
+        let addr_2 = srv_address("node-2.example.com.", 6180, PUBKEY).unwrap();
+        let peer_set =
+            peer_set_from_addrs(PeerRole::Upstream, vec![addr_1.clone(), addr_2.clone()]);
+
+        assert_eq!(peer_set.len(), 1);
+        let pubkey = x25519::PublicKey::from_encoded_string(PUBKEY).unwrap();
+        let peer = peer_set.get(&from_identity_public_key(pubkey)).unwrap();
+        assert_eq!(peer.addresses, vec![addr_1, addr_2]);
+        assert_eq!(peer.keys, vec![pubkey].into_iter().collect());
+        assert_eq!(peer.role, PeerRole::Upstream);
+    }
+}
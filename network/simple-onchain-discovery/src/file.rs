@@ -0,0 +1,203 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{send_update, DISCOVERY_COUNTS};
+use anyhow::Context;
+use diem_config::{
+    config::{Peer, PeerSet},
+    network_id::NetworkContext,
+};
+use diem_logger::prelude::*;
+use diem_types::network_address::NetworkAddress;
+use network::{
+    connectivity_manager::{ConnectivityRequest, DiscoverySource},
+    counters::inc_by_with_context,
+    logging::NetworkSchema,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// Listener which watches a local file in the `PeerSet` format of `NetworkConfig::seeds` and
+/// sends its peers to the ConnectivityManager every time it changes.
+pub struct FileDiscoveryListener {
+    network_context: Arc<NetworkContext>,
+    path: PathBuf,
+    interval: Duration,
+    conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+}
+
+/// Reads a `PeerSet` from a file, pulling the public keys out of the peers' addresses.
+pub fn load_peer_set(path: &Path) -> anyhow::Result<PeerSet> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read peer set file {:?}", path))?;
+    let peer_set: PeerSet = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Failed to parse peer set file {:?}", path))?;
+    Ok(peer_set
+        .into_iter()
+        .map(|(peer_id, peer)| {
+            let keys = peer
+                .addresses
+                .iter()
+                .filter_map(NetworkAddress::find_noise_proto)
+                .chain(peer.keys.into_iter())
+                .collect();
+            (peer_id, Peer::new(peer.addresses, keys, peer.role))
+        })
+        .collect())
+}
+
+impl FileDiscoveryListener {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        path: PathBuf,
+        interval: Duration,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> Self {
+        Self {
+            network_context,
+            path,
+            interval,
+            conn_mgr_reqs_tx,
+        }
+    }
+
+    fn modified(&self) -> std::io::Result<SystemTime> {
+        std::fs::metadata(&self.path)?.modified()
+    }
+
+    /// Starts the listener to check the file for changes at every interval.
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Starting File Discovery actor on {:?}", self.network_context, self.path
+        );
+
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_modified = None;
+        loop {
+            interval.tick().await;
+
+            let modified = match self.modified() {
+                Ok(modified) => modified,
+                Err(err) => {
+                    inc_by_with_context(
+                        &DISCOVERY_COUNTS,
+                        &self.network_context,
+                        "file_read_failure",
+                        1,
+                    );
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "FileDiscovery: Failed to read metadata of {:?}: {}", self.path, err
+                    );
+                    continue;
+                }
+            };
+            if last_modified == Some(modified) {
+                continue;
+            }
+
+            match load_peer_set(&self.path) {
+                Ok(peer_set) => {
+                    info!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Loaded {} peers from {:?}",
+                        self.network_context,
+                        peer_set.len(),
+                        self.path
+                    );
+                    last_modified = Some(modified);
+                    let request =
+                        ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::File, peer_set);
+                    send_update(&self.network_context, &mut self.conn_mgr_reqs_tx, request).await;
+                }
+                Err(err) => {
+                    // Keep the last peers until the file is fixed.
+                    inc_by_with_context(
+                        &DISCOVERY_COUNTS,
+                        &self.network_context,
+                        "file_read_failure",
+                        1,
+                    );
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "FileDiscovery: {:#}", err
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_config::config::{PeerRole, HANDSHAKE_VERSION};
+    use diem_crypto::{x25519::PrivateKey, Uniform};
+    use diem_temppath::TempPath;
+    use diem_types::PeerId;
+    use futures::StreamExt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{collections::HashSet, str::FromStr};
+    use tokio::runtime::Runtime;
+
+    fn write_peer_set(path: &Path, peer_set: &PeerSet) {
+        std::fs::write(path, serde_yaml::to_string(peer_set).unwrap()).unwrap();
+    }
+
+    fn test_peer_set(seed: u8) -> (PeerId, PeerSet) {
+        let mut rng: StdRng = SeedableRng::from_seed([seed; 32]);
+        let pubkey = PrivateKey::generate(&mut rng).public_key();
+        let peer_id = diem_types::account_address::from_identity_public_key(pubkey);
+        let addr = NetworkAddress::from_str("/dns/example.com/tcp/6180")
+            .unwrap()
+            .append_prod_protos(pubkey, HANDSHAKE_VERSION);
+        let peer = Peer::new(vec![addr], HashSet::new(), PeerRole::Upstream);
+        (peer_id, vec![(peer_id, peer)].into_iter().collect())
+    }
+
+    #[test]
+    fn load_peer_set_extracts_keys() {
+        let path = TempPath::new();
+        let (peer_id, peer_set) = test_peer_set(0);
+        let mut written = peer_set.clone();
+        written.get_mut(&peer_id).unwrap().keys.clear();
+        write_peer_set(path.path(), &written);
+
+        assert_eq!(load_peer_set(path.path()).unwrap(), peer_set);
+    }
+
+    #[test]
+    fn reloads_on_change() {
+        let runtime = Runtime::new().unwrap();
+        let path = TempPath::new();
+        let (_, peer_set) = test_peer_set(0);
+        write_peer_set(path.path(), &peer_set);
+
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = channel::new_test(1);
+        let listener = FileDiscoveryListener::new(
+            NetworkContext::mock(),
+            path.path().to_path_buf(),
+            Duration::from_millis(10),
+            conn_mgr_reqs_tx,
+        );
+        runtime.spawn(listener.start());
+
+        let expect_update = |rx: &mut channel::Receiver<ConnectivityRequest>| match runtime
+            .block_on(rx.next())
+        {
+            Some(ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::File, peers)) => peers,
+            request => panic!("Unexpected request: {:?}", request),
+        };
+        assert_eq!(expect_update(&mut conn_mgr_reqs_rx), peer_set);
+
+        // Make sure the modification time changes on coarse grained file systems.
+        std::thread::sleep(Duration::from_millis(1100));
+        let (_, peer_set) = test_peer_set(1);
+        write_peer_set(path.path(), &peer_set);
+        assert_eq!(expect_update(&mut conn_mgr_reqs_rx), peer_set);
+    }
+}
@@ -26,6 +26,8 @@ use std::{collections::HashSet, sync::Arc};
 use subscription_service::ReconfigSubscription;
 
 pub mod builder;
+pub mod dns;
+pub mod file;
 
 /// Histogram of idle time of spent in event processing loop
 pub static EVENT_PROCESSING_LOOP_IDLE_DURATION_S: Lazy<DurationHistogram> = Lazy::new(|| {
@@ -67,6 +69,21 @@ pub static NETWORK_KEY_MISMATCH: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Sends an update of the discovered peers to the ConnectivityManager.
+pub(crate) async fn send_update(
+    network_context: &NetworkContext,
+    conn_mgr_reqs_tx: &mut channel::Sender<ConnectivityRequest>,
+    update: ConnectivityRequest,
+) {
+    if let Err(e) = conn_mgr_reqs_tx.send(update).await {
+        inc_by_with_context(&DISCOVERY_COUNTS, network_context, "send_failure", 1);
+        warn!(
+            NetworkSchema::new(network_context),
+            "Failed to send update to ConnectivityManager {}", e
+        )
+    }
+}
+
 /// Listener which converts published  updates from the OnChainConfig to ConnectivityRequests
 /// for the ConnectivityManager.
 pub struct ValidatorSetChangeListener {
@@ -220,21 +237,7 @@ impl ValidatorSetChangeListener {
         );
 
         for update in updates {
-            match self.conn_mgr_reqs_tx.send(update).await {
-                Ok(()) => (),
-                Err(e) => {
-                    inc_by_with_context(
-                        &DISCOVERY_COUNTS,
-                        &self.network_context,
-                        "send_failure",
-                        1,
-                    );
-                    warn!(
-                        NetworkSchema::new(&self.network_context),
-                        "Failed to send update to ConnectivityManager {}", e
-                    )
-                }
-            }
+            send_update(&self.network_context, &mut self.conn_mgr_reqs_tx, update).await;
         }
     }
 
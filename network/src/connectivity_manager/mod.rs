@@ -9,11 +9,13 @@
 //! Consensus actor informs the ConnectivityManager of eligible nodes.
 //!
 //! Different discovery sources notify the ConnectivityManager of updates to
-//! peers' addresses. Currently, there are 2 discovery sources (ordered by
+//! peers' addresses. Currently, there are 4 discovery sources (ordered by
 //! decreasing dial priority, i.e., first is highest priority):
 //!
 //! 1. Onchain discovery protocol
 //! 2. Seed peers from config
+//! 3. Peers from a local file
+//! 4. Peers from DNS records
 //!
 //! In other words, if a we have some addresses discovered via onchain discovery
 //! and some seed addresses from our local config, we will try the onchain
@@ -116,12 +118,14 @@ pub struct ConnectivityManager<TBackoff> {
 }
 
 /// Different sources for peer addresses, ordered by priority (Onchain=highest,
-/// Config=lowest).
+/// Dns=lowest).
 #[repr(u8)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, NumVariants, Serialize)]
 pub enum DiscoverySource {
     OnChainValidatorSet,
     Config,
+    File,
+    Dns,
 }
 
 impl fmt::Debug for DiscoverySource {
@@ -138,6 +142,8 @@ impl fmt::Display for DiscoverySource {
             match self {
                 DiscoverySource::OnChainValidatorSet => "OnChainValidatorSet",
                 DiscoverySource::Config => "Config",
+                DiscoverySource::File => "File",
+                DiscoverySource::Dns => "Dns",
             }
         )
     }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::protocols::wire::handshake::v1::ProtocolId;
-use diem_config::network_id::NetworkContext;
+use diem_config::{config::PriorityClass, network_id::NetworkContext};
 use diem_metrics::{
     register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
     Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
@@ -16,6 +16,10 @@ use short_hex_str::AsShortHexStr;
 pub const REQUEST_LABEL: &str = "request";
 pub const RESPONSE_LABEL: &str = "response";
 
+// some compression labels
+pub const RAW_LABEL: &str = "raw";
+pub const COMPRESSED_LABEL: &str = "compressed";
+
 // some state labels
 pub const CANCELED_LABEL: &str = "canceled";
 pub const DECLINED_LABEL: &str = "declined";
@@ -303,6 +307,74 @@ pub fn direct_send_bytes(
     ])
 }
 
+pub static DIEM_NETWORK_COMPRESSION_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_compression_bytes",
+        "Number of bytes of messages of compressed protocols, before (raw) and after encoding",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "protocol_id",
+            "direction",
+            "state"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn compression_bytes(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    direction_label: &'static str,
+    state_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_COMPRESSION_BYTES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+        direction_label,
+        state_label,
+    ])
+}
+
+pub static DIEM_NETWORK_OUTBOUND_QUEUE_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "diem_network_outbound_queue_size",
+        "Number of outbound messages queued by the peer writers, by priority class",
+        &["role_type", "network_id", "peer_id", "priority"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queue_size(network_context: &NetworkContext, class: PriorityClass) -> IntGauge {
+    DIEM_NETWORK_OUTBOUND_QUEUE_SIZE.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        class.as_str(),
+    ])
+}
+
+pub static DIEM_NETWORK_OUTBOUND_QUEUE_WAIT: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "diem_network_outbound_queue_wait_seconds",
+        "Time outbound messages wait in the peer writer queues, by priority class",
+        &["role_type", "network_id", "peer_id", "priority"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queue_wait(network_context: &NetworkContext, class: PriorityClass) -> Histogram {
+    DIEM_NETWORK_OUTBOUND_QUEUE_WAIT.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        class.as_str(),
+    ])
+}
+
 /// Counters(queued,dequeued,dropped) related to inbound network notifications for RPCs and
 /// DirectSends.
 pub static PENDING_NETWORK_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
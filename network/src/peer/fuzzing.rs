@@ -13,7 +13,10 @@ use crate::{
     ProtocolId,
 };
 use channel::{diem_channel, message_queues::QueueStyle};
-use diem_config::{config::PeerRole, network_id::NetworkContext};
+use diem_config::{
+    config::{CompressionConfig, PeerRole, PriorityConfig},
+    network_id::NetworkContext,
+};
 use diem_proptest_helpers::ValueGenerator;
 use diem_time_service::TimeService;
 use diem_types::{network_address::NetworkAddress, PeerId};
@@ -107,6 +110,9 @@ pub fn fuzz(data: &[u8]) {
         constants::MAX_CONCURRENT_INBOUND_RPCS,
         constants::MAX_CONCURRENT_OUTBOUND_RPCS,
         constants::MAX_FRAME_SIZE,
+        constants::MAX_MESSAGE_SIZE,
+        CompressionConfig::default(),
+        PriorityConfig::default(),
         None,
         None,
     );
@@ -19,11 +19,14 @@ use crate::{
     logging::NetworkSchema,
     peer_manager::{PeerManagerError, TransportNotification},
     protocols::{
+        compression::PayloadCompression,
         direct_send::Message,
+        priority::{self, PriorityQueues, ProtocolPriorities},
         rpc::{InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
+        stream::{InboundStreams, OutboundStreams, StreamError},
         wire::messaging::v1::{
             DirectSendMsg, ErrorCode, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
-            Priority, ReadError, WriteError,
+            ReadError, WriteError,
         },
     },
     transport::{self, Connection, ConnectionMetadata},
@@ -31,7 +34,10 @@ use crate::{
 };
 use bytes::Bytes;
 use channel::diem_channel;
-use diem_config::network_id::NetworkContext;
+use diem_config::{
+    config::{CompressionConfig, PriorityClass, PriorityConfig},
+    network_id::NetworkContext,
+};
 use diem_logger::prelude::*;
 use diem_rate_limiter::rate_limit::SharedBucket;
 use diem_time_service::{TimeService, TimeServiceTrait};
@@ -57,6 +63,10 @@ mod test;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+/// Maximum number of outbound messages the writer queues for scheduling, before leaving the next
+/// ones in its request channel.
+const MAX_QUEUED_MESSAGES: usize = 1024;
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -97,6 +107,89 @@ impl fmt::Display for DisconnectReason {
     }
 }
 
+/// Frames queued by the writer task.
+enum OutboundFrame<T> {
+    /// A message, along with its ack, e.g. the channel acknowledging it was sent.
+    Message(NetworkMessage, T),
+    /// The next frame of the messages streamed with the given class.
+    StreamFrame(PriorityClass),
+}
+
+/// Outbound messages of a connection, scheduled by priority class. Messages larger than the max
+/// frame size are streamed one at a time per class, so that the frames of each streamed message
+/// are scheduled with its class.
+struct OutboundQueues<T> {
+    queues: PriorityQueues<OutboundFrame<T>>,
+    /// The messages streamed with each class, indexed by class.
+    streams: Vec<OutboundStreams<T>>,
+}
+
+impl<T> OutboundQueues<T> {
+    fn new(
+        network_context: Arc<NetworkContext>,
+        time_service: TimeService,
+        priority_config: &PriorityConfig,
+        max_frame_size: usize,
+        max_message_size: usize,
+    ) -> Self {
+        Self {
+            queues: PriorityQueues::new(network_context, time_service, priority_config),
+            streams: PriorityClass::all()
+                .iter()
+                .map(|class| {
+                    OutboundStreams::new(
+                        max_frame_size,
+                        max_message_size,
+                        priority::wire_priority(*class),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.queues.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    /// Queues a message after the other messages of its class.
+    fn push(&mut self, message: NetworkMessage, ack: T) {
+        let class = priority::message_class(&message);
+        self.queues
+            .push(class, OutboundFrame::Message(message, ack));
+    }
+
+    /// Returns the next frame to send, along with the ack of the message it completes, or `None`
+    /// if there's nothing left to send. A message which fails to be streamed is dropped.
+    fn pop(&mut self) -> Result<Option<(NetworkMessage, Option<T>)>, StreamError> {
+        while let Some(frame) = self.queues.pop() {
+            match frame {
+                OutboundFrame::Message(message, ack) => {
+                    let class = priority::message_class(&message);
+                    let streams = &mut self.streams[class as usize];
+                    if !streams.should_stream(&message) {
+                        return Ok(Some((message, Some(ack))));
+                    }
+                    streams.push(&message, ack)?;
+                    self.queues.push(class, OutboundFrame::StreamFrame(class));
+                }
+                // Each streamed message keeps a frame queued until all the streams of its class
+                // are sent.
+                OutboundFrame::StreamFrame(class) => {
+                    if let Some(frame) = self.streams[class as usize].next_frame() {
+                        self.queues.push(class, OutboundFrame::StreamFrame(class));
+                        return Ok(Some(frame));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
 enum State {
     Connected,
     ShuttingDown(DisconnectReason),
@@ -128,8 +221,18 @@ pub struct Peer<TSocket> {
     /// Flag to indicate if the actor is being shut down.
     state: State,
     /// The maximum size of an inbound or outbound request frame
-    /// Currently, requests are only a single frame
     max_frame_size: usize,
+    /// The maximum size of an inbound or outbound message, larger messages than the max frame
+    /// size being streamed as fragments
+    max_message_size: usize,
+    /// Reassembles the messages currently streamed by the remote peer.
+    inbound_streams: InboundStreams,
+    /// Compression of the messages of the protocols compressed on this connection.
+    compression: Arc<PayloadCompression>,
+    /// Scheduling of the outbound messages by priority.
+    priority_config: PriorityConfig,
+    /// Priorities of the outbound messages of each protocol.
+    priorities: ProtocolPriorities,
     /// Optional inbound rate limiter
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
@@ -152,6 +255,9 @@ where
         max_concurrent_inbound_rpcs: u32,
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
+        max_message_size: usize,
+        compression_config: CompressionConfig,
+        priority_config: PriorityConfig,
         inbound_rate_limiter: Option<SharedBucket>,
         outbound_rate_limiter: Option<SharedBucket>,
     ) -> Self {
@@ -160,6 +266,11 @@ where
             socket,
         } = connection;
         let remote_peer_id = connection_metadata.remote_peer_id;
+        let compression = Arc::new(PayloadCompression::new(
+            network_context.clone(),
+            connection_metadata.application_protocols.clone(),
+            &compression_config,
+        ));
         Self {
             network_context: network_context.clone(),
             executor,
@@ -175,15 +286,22 @@ where
                 remote_peer_id,
                 inbound_rpc_timeout,
                 max_concurrent_inbound_rpcs,
+                compression.clone(),
             ),
             outbound_rpcs: OutboundRpcs::new(
                 network_context,
                 time_service,
                 remote_peer_id,
                 max_concurrent_outbound_rpcs,
+                compression.clone(),
             ),
             state: State::Connected,
             max_frame_size,
+            max_message_size,
+            inbound_streams: InboundStreams::new(max_message_size),
+            compression,
+            priorities: ProtocolPriorities::new(&priority_config),
+            priority_config,
             inbound_rate_limiter,
             outbound_rate_limiter,
         }
@@ -229,6 +347,9 @@ where
             self.connection_metadata.clone(),
             self.network_context.clone(),
             writer,
+            self.max_frame_size,
+            self.max_message_size,
+            self.priority_config.clone(),
         );
 
         // Start main Peer event loop.
@@ -299,12 +420,19 @@ where
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    // Outbound messages are queued by priority class and sent by a weighted round robin, see
+    // [`PriorityQueues`]. Messages larger than the max frame size are streamed as fragments, which
+    // are scheduled one at a time with the class of their message so that large messages don't
+    // hold up the small or more urgent ones.
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
         connection_metadata: ConnectionMetadata,
         network_context: Arc<NetworkContext>,
         mut writer: NetworkMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
+        max_frame_size: usize,
+        max_message_size: usize,
+        priority_config: PriorityConfig,
     ) -> (
         channel::Sender<(
             NetworkMessage,
@@ -323,29 +451,72 @@ where
         let (close_tx, close_rx) = oneshot::channel();
         let writer_task = async move {
             let mut close_rx = close_rx.into_stream();
+            let mut outbound_queues = OutboundQueues::new(
+                network_context.clone(),
+                time_service.clone(),
+                &priority_config,
+                max_frame_size,
+                max_message_size,
+            );
             loop {
-                futures::select! {
-                    (message, ack_ch) = write_reqs_rx.select_next_some() => {
-                        if let Err(err) = writer
-                            .send(&message)
-                            .map_ok(|_| ack_ch.send(Ok(())))
-                            .await
-                        {
-                            warn!(
-                                NetworkSchema::new(&network_context)
-                                    .connection_metadata(&connection_metadata),
-                                error = %err,
-                                "{} Error in sending message to peer: {}, error: {}",
-                                network_context,
-                                remote_peer_id.short_str(),
-                                err
-                            );
-                            break;
-                        }
-                    },
-                    _ = close_rx.select_next_some() => {
-                        break;
+                // Wait for new messages while there's nothing to send. Otherwise, move the pending
+                // messages to the queues, up to their capacity, before scheduling the next frame.
+                if outbound_queues.is_empty() {
+                    futures::select! {
+                        (message, ack_ch) = write_reqs_rx.select_next_some() => {
+                            outbound_queues.push(message, ack_ch);
+                        },
+                        _ = close_rx.select_next_some() => break,
+                    }
+                    continue;
+                } else if outbound_queues.len() < MAX_QUEUED_MESSAGES {
+                    futures::select_biased! {
+                        _ = close_rx.select_next_some() => break,
+                        (message, ack_ch) = write_reqs_rx.select_next_some() => {
+                            outbound_queues.push(message, ack_ch);
+                            continue;
+                        },
+                        default => {},
+                    }
+                } else {
+                    futures::select_biased! {
+                        _ = close_rx.select_next_some() => break,
+                        default => {},
+                    }
+                }
+
+                let (message, ack_ch) = match outbound_queues.pop() {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        warn!(
+                            NetworkSchema::new(&network_context)
+                                .connection_metadata(&connection_metadata),
+                            error = %err,
+                            "{} Error in streaming message to peer: {}, error: {}",
+                            network_context,
+                            remote_peer_id.short_str(),
+                            err
+                        );
+                        continue;
                     }
+                };
+
+                if let Err(err) = writer
+                    .send(&message)
+                    .map_ok(|_| ack_ch.map(|ack_ch| ack_ch.send(Ok(()))))
+                    .await
+                {
+                    warn!(
+                        NetworkSchema::new(&network_context)
+                            .connection_metadata(&connection_metadata),
+                        error = %err,
+                        "{} Error in sending message to peer: {}, error: {}",
+                        network_context,
+                        remote_peer_id.short_str(),
+                        err
+                    );
+                    break;
                 }
             }
             info!(
@@ -438,6 +609,11 @@ where
             },
         };
 
+        self.handle_inbound_network_message(message);
+        Ok(())
+    }
+
+    fn handle_inbound_network_message(&mut self, message: NetworkMessage) {
         match message {
             NetworkMessage::DirectSendMsg(message) => self.handle_inbound_direct_send(message),
             NetworkMessage::Error(error_msg) => {
@@ -451,7 +627,10 @@ where
                     error_msg,
                 );
             }
-            NetworkMessage::RpcRequest(request) => {
+            NetworkMessage::RpcRequest(mut request) => {
+                // Responses are sent with the priority we give to their protocol, whichever
+                // priority the remote peer requested, so that it can't promote its requests.
+                request.priority = self.priorities.priority(request.protocol_id);
                 if let Err(err) = self
                     .inbound_rpcs
                     .handle_inbound_request(&mut self.peer_notifs_tx, request)
@@ -469,8 +648,38 @@ where
             NetworkMessage::RpcResponse(response) => {
                 self.outbound_rpcs.handle_inbound_response(response)
             }
+            NetworkMessage::StreamHeader(header) => {
+                let class = priority::priority_class(header.priority);
+                if let Err(err) = self.inbound_streams.new_stream(class, header) {
+                    warn!(
+                        NetworkSchema::new(&self.network_context)
+                            .connection_metadata(&self.connection_metadata),
+                        error = %err,
+                        "{} Error handling inbound stream header: {}",
+                        self.network_context,
+                        err
+                    );
+                }
+            }
+            NetworkMessage::StreamFragment(fragment) => {
+                let class = priority::priority_class(fragment.priority);
+                match self.inbound_streams.append_fragment(class, fragment) {
+                    // Stream messages are never nested, so this doesn't recurse further.
+                    Ok(Some(message)) => self.handle_inbound_network_message(message),
+                    Ok(None) => {}
+                    Err(err) => {
+                        warn!(
+                            NetworkSchema::new(&self.network_context)
+                                .connection_metadata(&self.connection_metadata),
+                            error = %err,
+                            "{} Error handling inbound stream fragment: {}",
+                            self.network_context,
+                            err
+                        );
+                    }
+                }
+            }
         };
-        Ok(())
     }
 
     /// Handle an inbound DirectSendMsg from the remote peer. There's not much to
@@ -494,10 +703,21 @@ where
         counters::direct_send_bytes(&self.network_context, RECEIVED_LABEL)
             .inc_by(data.len() as u64);
 
-        let notif = PeerNotification::RecvMessage(Message {
-            protocol_id,
-            mdata: Bytes::from(data),
-        });
+        let mdata = match self.compression.decompress(protocol_id, Bytes::from(data)) {
+            Ok(mdata) => mdata,
+            Err(err) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    error = %err,
+                    "{} Failed to decompress inbound DirectSend message for protocol {}. Error: {}",
+                    self.network_context,
+                    protocol_id,
+                    err
+                );
+                return;
+            }
+        };
+        let notif = PeerNotification::RecvMessage(Message { protocol_id, mdata });
 
         if let Err(err) = self.peer_notifs_tx.push(protocol_id, notif) {
             warn!(
@@ -527,12 +747,13 @@ where
             // To send an outbound DirectSendMsg, we just bump some counters and
             // push it onto our outbound writer queue.
             PeerRequest::SendDirectSend(message) => {
-                let message_len = message.mdata.len();
                 let protocol_id = message.protocol_id;
+                let mdata = self.compression.compress(protocol_id, message.mdata);
+                let message_len = mdata.len();
                 let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
                     protocol_id,
-                    priority: Priority::default(),
-                    raw_msg: Vec::from(message.mdata.as_ref()),
+                    priority: self.priorities.priority(protocol_id),
+                    raw_msg: Vec::from(mdata.as_ref()),
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
 
@@ -557,9 +778,10 @@ where
             }
             PeerRequest::SendRpc(request) => {
                 let protocol_id = request.protocol_id;
+                let priority = self.priorities.priority(protocol_id);
                 if let Err(e) = self
                     .outbound_rpcs
-                    .handle_outbound_request(request, write_reqs_tx)
+                    .handle_outbound_request(request, priority, write_reqs_tx)
                     .await
                 {
                     warn!(
@@ -4,17 +4,19 @@
 use crate::{
     constants::{
         INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
-        MAX_FRAME_SIZE, NETWORK_CHANNEL_SIZE,
+        MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, NETWORK_CHANNEL_SIZE,
     },
-    peer::{DisconnectReason, Peer, PeerNotification, PeerRequest},
+    peer::{DisconnectReason, OutboundQueues, Peer, PeerNotification, PeerRequest},
     peer_manager::TransportNotification,
     protocols::{
         direct_send::Message,
+        priority::{self, ProtocolPriorities},
         rpc::{error::RpcError, InboundRpcRequest, OutboundRpcRequest},
+        stream::InboundStream,
         wire::{
             handshake::v1::MessagingProtocolVersion,
             messaging::v1::{
-                DirectSendMsg, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
+                DirectSendMsg, NetworkMessage, NetworkMessageSink, NetworkMessageStream, Priority,
                 RpcRequest, RpcResponse,
             },
         },
@@ -24,7 +26,10 @@ use crate::{
 };
 use bytes::Bytes;
 use channel::{self, diem_channel, message_queues::QueueStyle};
-use diem_config::{config::PeerRole, network_id::NetworkContext};
+use diem_config::{
+    config::{CompressionConfig, PeerRole, PriorityClass, PriorityConfig},
+    network_id::NetworkContext,
+};
 use diem_time_service::{MockTimeService, TimeService};
 use diem_types::{network_address::NetworkAddress, PeerId};
 use futures::{
@@ -44,6 +49,11 @@ use tokio_util::compat::{
 
 static PROTOCOL: ProtocolId = ProtocolId::MempoolDirectSend;
 
+/// Priority of the messages of `PROTOCOL` sent by the test peers.
+fn protocol_priority() -> Priority {
+    ProtocolPriorities::new(&PriorityConfig::default()).priority(PROTOCOL)
+}
+
 fn build_test_peer(
     executor: Handle,
     time_service: TimeService,
@@ -88,6 +98,9 @@ fn build_test_peer(
         MAX_CONCURRENT_INBOUND_RPCS,
         MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE,
+        MAX_MESSAGE_SIZE,
+        CompressionConfig::default(),
+        PriorityConfig::default(),
         None,
         None,
     );
@@ -214,7 +227,7 @@ fn peer_send_message() {
     };
     let recv_msg = NetworkMessage::DirectSendMsg(DirectSendMsg {
         protocol_id: PROTOCOL,
-        priority: 0,
+        priority: protocol_priority(),
         raw_msg: Vec::from("hello world"),
     });
 
@@ -336,6 +349,107 @@ fn peers_send_message_concurrent() {
     rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
 }
 
+// Messages larger than the max frame size are streamed, and don't hold up smaller messages.
+#[test]
+fn peers_send_large_message() {
+    ::diem_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (
+        (peer_a, mut peer_handle_a, _connection_notifs_rx_a, _peer_notifs_rx_a),
+        (peer_b, _peer_handle_b, _connection_notifs_rx_b, mut peer_notifs_rx_b),
+    ) = build_test_connected_peers(rt.handle().clone(), TimeService::mock());
+
+    let test = async move {
+        let large_msg = Message {
+            protocol_id: PROTOCOL,
+            mdata: Bytes::from(vec![7; 2 * MAX_FRAME_SIZE]),
+        };
+        let small_msg = Message {
+            protocol_id: PROTOCOL,
+            mdata: Bytes::from("hello world"),
+        };
+
+        // Peer A -> large_msg, small_msg -> Peer B
+        peer_handle_a.send_direct_send(large_msg.clone());
+        peer_handle_a.send_direct_send(small_msg.clone());
+
+        // Peer B receives both messages, in whichever order they were completed
+        let mut notifs = vec![];
+        for _ in 0..2 {
+            match peer_notifs_rx_b.next().await {
+                Some(PeerNotification::RecvMessage(msg)) => notifs.push(msg),
+                notif => panic!("Unexpected notification: {:?}", notif),
+            }
+        }
+        notifs.sort_by_key(|msg| msg.mdata.len());
+        assert_eq!(notifs, vec![small_msg, large_msg]);
+
+        drop(peer_handle_a);
+    };
+
+    rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
+}
+
+// The frames of streamed messages are scheduled with the class of their message, so that a large
+// high priority message isn't held up by a large low priority message streamed before it.
+#[test]
+fn outbound_streams_scheduled_by_class() {
+    let max_frame_size = 256;
+    let priority_config = PriorityConfig::default();
+    let mut queues = OutboundQueues::new(
+        NetworkContext::mock(),
+        TimeService::mock(),
+        &priority_config,
+        max_frame_size,
+        MAX_MESSAGE_SIZE,
+    );
+    let direct_send = |protocol_id, size| {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: ProtocolPriorities::new(&priority_config).priority(protocol_id),
+            raw_msg: vec![7; size],
+        })
+    };
+    let low_msg = direct_send(ProtocolId::StateSyncDirectSend, 8 * max_frame_size);
+    let high_msg = direct_send(ProtocolId::ConsensusDirectSend, 2 * max_frame_size);
+
+    // The low priority message starts being streamed first.
+    queues.push(low_msg.clone(), "low");
+    let mut frames = vec![queues.pop().unwrap().unwrap().0];
+    queues.push(high_msg.clone(), "high");
+    let mut acks = vec![];
+    while let Some((frame, ack)) = queues.pop().unwrap() {
+        frames.push(frame);
+        acks.extend(ack);
+    }
+    assert_eq!(acks, vec!["high", "low"]);
+
+    // The receiver reassembles the interleaved messages with one stream per class.
+    let mut inbound_streams: Vec<_> = PriorityClass::all()
+        .iter()
+        .map(|_| InboundStream::new(MAX_MESSAGE_SIZE))
+        .collect();
+    let mut messages = vec![];
+    for frame in frames {
+        match frame {
+            NetworkMessage::StreamHeader(header) => {
+                let class = priority::priority_class(header.priority);
+                inbound_streams[class as usize].new_stream(header).unwrap();
+            }
+            NetworkMessage::StreamFragment(fragment) => {
+                let class = priority::priority_class(fragment.priority);
+                messages.extend(
+                    inbound_streams[class as usize]
+                        .append_fragment(fragment)
+                        .unwrap(),
+                );
+            }
+            frame => panic!("Unexpected frame: {:?}", frame),
+        }
+    }
+    assert_eq!(messages, vec![high_msg, low_msg]);
+}
+
 #[test]
 fn peer_recv_rpc() {
     ::diem_logger::Logger::init_for_testing();
@@ -361,7 +475,7 @@ fn peer_recv_rpc() {
     });
     let resp_msg = NetworkMessage::RpcResponse(RpcResponse {
         request_id: 123,
-        priority: 0,
+        priority: protocol_priority(),
         raw_response: Vec::from("goodbye world"),
     });
 
@@ -395,6 +509,52 @@ fn peer_recv_rpc() {
     rt.block_on(future::join3(peer.start(), server, client));
 }
 
+// Rpc responses are sent with the priority of their protocol, whichever priority the remote peer
+// requested.
+#[test]
+fn peer_recv_rpc_local_priority() {
+    ::diem_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (peer, _peer_handle, mut connection, _connection_notifs_rx, mut peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let client = async move {
+        for (request_id, priority) in vec![(1, 0), (2, 255)] {
+            let request = NetworkMessage::RpcRequest(RpcRequest {
+                request_id,
+                protocol_id: PROTOCOL,
+                priority,
+                raw_request: Vec::from("hello world"),
+            });
+            client_sink.send(&request).await.unwrap();
+            let expected = NetworkMessage::RpcResponse(RpcResponse {
+                request_id,
+                priority: protocol_priority(),
+                raw_response: Vec::from("goodbye world"),
+            });
+            let received = client_stream.next().await.unwrap().unwrap();
+            assert_eq!(received, expected);
+        }
+        client_sink.close().await.unwrap();
+    };
+    let server = async move {
+        for _ in 0..2 {
+            match peer_notifs_rx.next().await.unwrap() {
+                PeerNotification::RecvRpc(req) => {
+                    req.res_tx.send(Ok(Bytes::from("goodbye world"))).unwrap()
+                }
+                notif => panic!("Unexpected PeerNotification: {:?}", notif),
+            }
+        }
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
 #[test]
 fn peer_recv_rpc_concurrent() {
     ::diem_logger::Logger::init_for_testing();
@@ -420,7 +580,7 @@ fn peer_recv_rpc_concurrent() {
     });
     let resp_msg = NetworkMessage::RpcResponse(RpcResponse {
         request_id: 123,
-        priority: 0,
+        priority: protocol_priority(),
         raw_response: Vec::from("goodbye world"),
     });
 
@@ -610,7 +770,7 @@ fn peer_send_rpc() {
             };
 
             assert_eq!(received.protocol_id, PROTOCOL);
-            assert_eq!(received.priority, 0);
+            assert_eq!(received.priority, protocol_priority());
             assert_eq!(received.raw_request, b"hello world");
 
             assert!(
@@ -679,7 +839,7 @@ fn peer_send_rpc_concurrent() {
             };
 
             assert_eq!(received.protocol_id, PROTOCOL);
-            assert_eq!(received.priority, 0);
+            assert_eq!(received.priority, protocol_priority());
             assert_eq!(received.raw_request, b"hello world");
 
             assert!(
@@ -734,7 +894,7 @@ fn peer_send_rpc_cancel() {
         };
 
         assert_eq!(received.protocol_id, PROTOCOL);
-        assert_eq!(received.priority, 0);
+        assert_eq!(received.priority, protocol_priority());
         assert_eq!(received.raw_request, b"hello world");
 
         // Request should still be live. Ok(_) means the sender is not dropped.
@@ -795,7 +955,7 @@ fn peer_send_rpc_timeout() {
         };
 
         assert_eq!(received.protocol_id, PROTOCOL);
-        assert_eq!(received.priority, 0);
+        assert_eq!(received.priority, protocol_priority());
         assert_eq!(received.raw_request, b"hello world");
 
         // Request should still be live. Ok(_) means the sender is not dropped.
@@ -15,7 +15,7 @@ use crate::{
 };
 use channel::{self, diem_channel, message_queues::QueueStyle};
 use diem_config::{
-    config::{PeerSet, RateLimitConfig, HANDSHAKE_VERSION},
+    config::{CompressionConfig, PeerSet, PriorityConfig, RateLimitConfig, HANDSHAKE_VERSION},
     network_id::NetworkContext,
 };
 use diem_crypto::x25519;
@@ -55,6 +55,7 @@ struct TransportContext {
     authentication_mode: AuthenticationMode,
     trusted_peers: Arc<RwLock<PeerSet>>,
     enable_proxy_protocol: bool,
+    compressed_protocols: Vec<String>,
 }
 
 impl TransportContext {
@@ -65,6 +66,7 @@ impl TransportContext {
         authentication_mode: AuthenticationMode,
         trusted_peers: Arc<RwLock<PeerSet>>,
         enable_proxy_protocol: bool,
+        compressed_protocols: Vec<String>,
     ) -> Self {
         Self {
             chain_id,
@@ -73,14 +75,17 @@ impl TransportContext {
             authentication_mode,
             trusted_peers,
             enable_proxy_protocol,
+            compressed_protocols,
         }
     }
 
     fn supported_protocols(&self) -> SupportedProtocols {
-        self.direct_send_protocols
-            .iter()
-            .chain(&self.rpc_protocols)
-            .into()
+        let protocols = || self.direct_send_protocols.iter().chain(&self.rpc_protocols);
+        SupportedProtocols::from(protocols()).with_compression(protocols().filter(|protocol| {
+            self.compressed_protocols
+                .iter()
+                .any(|name| name == protocol.as_str())
+        }))
     }
 
     fn augment_direct_send_protocols(
@@ -112,6 +117,9 @@ struct PeerManagerContext {
     max_concurrent_network_reqs: usize,
     channel_size: usize,
     max_frame_size: usize,
+    max_message_size: usize,
+    compression_config: CompressionConfig,
+    priority_config: PriorityConfig,
     inbound_connection_limit: usize,
     inbound_rate_limit_config: Option<RateLimitConfig>,
     outbound_rate_limit_config: Option<RateLimitConfig>,
@@ -134,6 +142,9 @@ impl PeerManagerContext {
         max_concurrent_network_reqs: usize,
         channel_size: usize,
         max_frame_size: usize,
+        max_message_size: usize,
+        compression_config: CompressionConfig,
+        priority_config: PriorityConfig,
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
@@ -151,6 +162,9 @@ impl PeerManagerContext {
             max_concurrent_network_reqs,
             channel_size,
             max_frame_size,
+            max_message_size,
+            compression_config,
+            priority_config,
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
@@ -207,6 +221,9 @@ impl PeerManagerBuilder {
         channel_size: usize,
         max_concurrent_network_reqs: usize,
         max_frame_size: usize,
+        max_message_size: usize,
+        compression_config: CompressionConfig,
+        priority_config: PriorityConfig,
         enable_proxy_protocol: bool,
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
@@ -232,6 +249,7 @@ impl PeerManagerBuilder {
                 authentication_mode,
                 trusted_peers.clone(),
                 enable_proxy_protocol,
+                compression_config.protocols.clone(),
             )),
             peer_manager_context: Some(PeerManagerContext::new(
                 pm_reqs_tx,
@@ -244,6 +262,9 @@ impl PeerManagerBuilder {
                 max_concurrent_network_reqs,
                 channel_size,
                 max_frame_size,
+                max_message_size,
+                compression_config,
+                priority_config,
                 inbound_connection_limit,
                 inbound_rate_limit_config,
                 outbound_rate_limit_config,
@@ -373,6 +394,9 @@ impl PeerManagerBuilder {
             pm_context.max_concurrent_network_reqs,
             pm_context.channel_size,
             pm_context.max_frame_size,
+            pm_context.max_message_size,
+            pm_context.compression_config,
+            pm_context.priority_config,
             pm_context.inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
@@ -58,7 +58,7 @@ mod error;
 mod tests;
 
 pub use self::error::PeerManagerError;
-use diem_config::config::{PeerRole, PeerSet};
+use diem_config::config::{CompressionConfig, PeerRole, PeerSet, PriorityConfig};
 use diem_infallible::RwLock;
 
 /// Request received by PeerManager from upstream actors.
@@ -290,6 +290,12 @@ where
     channel_size: usize,
     /// Max network frame size
     max_frame_size: usize,
+    /// Max network message size
+    max_message_size: usize,
+    /// Compression of the messages of the protocols compressed with each peer
+    compression_config: CompressionConfig,
+    /// Scheduling of the outbound messages of each peer by priority
+    priority_config: PriorityConfig,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
     /// Keyed storage of all inbound rate limiters
@@ -322,6 +328,9 @@ where
         channel_size: usize,
         max_concurrent_network_reqs: usize,
         max_frame_size: usize,
+        max_message_size: usize,
+        compression_config: CompressionConfig,
+        priority_config: PriorityConfig,
         inbound_connection_limit: usize,
         inbound_rate_limiters: IpAddrTokenBucketLimiter,
         outbound_rate_limiters: IpAddrTokenBucketLimiter,
@@ -365,6 +374,9 @@ where
             max_concurrent_network_reqs,
             channel_size,
             max_frame_size,
+            max_message_size,
+            compression_config,
+            priority_config,
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
@@ -864,6 +876,9 @@ where
             constants::MAX_CONCURRENT_INBOUND_RPCS,
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
+            self.max_message_size,
+            self.compression_config.clone(),
+            self.priority_config.clone(),
             Some(inbound_rate_limiter),
             Some(outbound_rate_limiter),
         );
@@ -20,7 +20,7 @@ use anyhow::anyhow;
 use bytes::Bytes;
 use channel::{diem_channel, message_queues::QueueStyle};
 use diem_config::{
-    config::{PeerRole, MAX_INBOUND_CONNECTIONS},
+    config::{CompressionConfig, PeerRole, PriorityConfig, MAX_INBOUND_CONNECTIONS},
     network_id::NetworkContext,
 };
 use diem_infallible::RwLock;
@@ -111,6 +111,9 @@ fn build_test_peer_manager(
         constants::NETWORK_CHANNEL_SIZE,
         constants::MAX_CONCURRENT_NETWORK_REQS,
         constants::MAX_FRAME_SIZE,
+        constants::MAX_MESSAGE_SIZE,
+        CompressionConfig::default(),
+        PriorityConfig::default(),
         MAX_INBOUND_CONNECTIONS,
         TokenBucketRateLimiter::open("inbound"),
         TokenBucketRateLimiter::open("outbound"),
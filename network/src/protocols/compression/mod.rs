@@ -0,0 +1,159 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the payloads of application messages.
+//!
+//! Compression is negotiated per application protocol during the handshake, see
+//! [`SupportedProtocols::with_compression`]. On a connection where both ends compress a protocol,
+//! every payload of that protocol (direct-send messages, rpc requests and rpc responses) starts
+//! with a byte telling how the rest of the payload is encoded: payloads smaller than the
+//! configured threshold are sent raw, larger ones are compressed with LZ4. Other protocols are
+//! left untouched, so applications never see the difference.
+
+use crate::{
+    counters::{self, COMPRESSED_LABEL, RAW_LABEL, RECEIVED_LABEL, SENT_LABEL},
+    protocols::wire::handshake::v1::{ProtocolId, SupportedProtocols},
+};
+use bytes::Bytes;
+use diem_config::{config::CompressionConfig, network_id::NetworkContext};
+use lz4_flex::block;
+use std::{convert::TryInto, sync::Arc};
+use thiserror::Error;
+
+#[cfg(test)]
+mod test;
+
+/// The payload is not compressed.
+const RAW: u8 = 0;
+/// The payload is compressed with LZ4, prefixed with its decompressed size as a little endian u32.
+const LZ4: u8 = 1;
+/// Size of the decompressed size prefix of LZ4 payloads.
+const LZ4_SIZE_PREFIX: usize = 4;
+/// LZ4 can't encode more than 255 bytes per compressed byte, so a larger decompressed size comes
+/// from a forged size prefix and would only make us allocate memory for nothing.
+const LZ4_MAX_COMPRESSION_RATIO: usize = 255;
+
+#[derive(Debug, Error)]
+pub enum CompressionError {
+    #[error("Payload is truncated")]
+    Truncated,
+
+    #[error("Unknown compression scheme: {0}")]
+    UnknownScheme(u8),
+
+    #[error("Decompressed size of {0} bytes exceeds the maximum of {1} bytes")]
+    TooLarge(usize, usize),
+
+    #[error("Decompressed size of {0} bytes can't be reached from {1} compressed bytes")]
+    BadSize(usize, usize),
+
+    #[error("Decompression error: {0}")]
+    DecompressError(#[from] block::DecompressError),
+}
+
+/// Compresses and decompresses the payloads of the protocols compressed on a connection.
+pub struct PayloadCompression {
+    network_context: Arc<NetworkContext>,
+    /// The protocols negotiated during the handshake.
+    protocols: SupportedProtocols,
+    min_compression_size: usize,
+    max_decompressed_size: usize,
+}
+
+impl PayloadCompression {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        protocols: SupportedProtocols,
+        config: &CompressionConfig,
+    ) -> Self {
+        Self {
+            network_context,
+            protocols,
+            min_compression_size: config.min_compression_size,
+            max_decompressed_size: config.max_decompressed_size,
+        }
+    }
+
+    /// Encodes an outbound payload of the given protocol.
+    pub fn compress(&self, protocol_id: ProtocolId, data: Bytes) -> Bytes {
+        if !self.protocols.supports_compression(protocol_id) {
+            return data;
+        }
+
+        let mut payload;
+        if data.len() < self.min_compression_size {
+            payload = Vec::with_capacity(1 + data.len());
+            payload.push(RAW);
+            payload.extend_from_slice(&data);
+        } else {
+            let compressed = block::compress_prepend_size(&data);
+            payload = Vec::with_capacity(1 + compressed.len());
+            payload.push(LZ4);
+            payload.extend_from_slice(&compressed);
+        }
+        self.record(protocol_id, SENT_LABEL, data.len(), payload.len());
+        Bytes::from(payload)
+    }
+
+    /// Decodes an inbound payload of the given protocol, refusing to decompress payloads larger
+    /// than the maximum decompressed size, or than what their compressed size allows.
+    pub fn decompress(
+        &self,
+        protocol_id: ProtocolId,
+        data: Bytes,
+    ) -> Result<Bytes, CompressionError> {
+        if !self.protocols.supports_compression(protocol_id) {
+            return Ok(data);
+        }
+
+        match data.first() {
+            None => Err(CompressionError::Truncated),
+            Some(&RAW) => {
+                self.record(protocol_id, RECEIVED_LABEL, data.len() - 1, data.len());
+                Ok(data.slice(1..))
+            }
+            Some(&LZ4) => {
+                let compressed = &data[1..];
+                if compressed.len() < LZ4_SIZE_PREFIX {
+                    return Err(CompressionError::Truncated);
+                }
+                let (size, compressed) = compressed.split_at(LZ4_SIZE_PREFIX);
+                let size = u32::from_le_bytes(size.try_into().expect("size prefix is 4 bytes"));
+                let size = size as usize;
+                if size > self.max_decompressed_size {
+                    return Err(CompressionError::TooLarge(size, self.max_decompressed_size));
+                }
+                if size > compressed.len().saturating_mul(LZ4_MAX_COMPRESSION_RATIO) {
+                    return Err(CompressionError::BadSize(size, compressed.len()));
+                }
+                let decompressed = block::decompress(compressed, size)?;
+                self.record(protocol_id, RECEIVED_LABEL, decompressed.len(), data.len());
+                Ok(Bytes::from(decompressed))
+            }
+            Some(scheme) => Err(CompressionError::UnknownScheme(*scheme)),
+        }
+    }
+
+    fn record(
+        &self,
+        protocol_id: ProtocolId,
+        direction_label: &'static str,
+        raw_len: usize,
+        compressed_len: usize,
+    ) {
+        counters::compression_bytes(
+            &self.network_context,
+            protocol_id,
+            direction_label,
+            RAW_LABEL,
+        )
+        .inc_by(raw_len as u64);
+        counters::compression_bytes(
+            &self.network_context,
+            protocol_id,
+            direction_label,
+            COMPRESSED_LABEL,
+        )
+        .inc_by(compressed_len as u64);
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+const PROTOCOL: ProtocolId = ProtocolId::StateSyncDirectSend;
+
+fn compression(compressed_protocols: &[ProtocolId]) -> PayloadCompression {
+    let protocols = SupportedProtocols::from(ProtocolId::all().iter())
+        .with_compression(compressed_protocols.iter());
+    let config = CompressionConfig {
+        protocols: vec![],
+        min_compression_size: 64,
+        max_decompressed_size: 1024,
+    };
+    PayloadCompression::new(NetworkContext::mock(), protocols, &config)
+}
+
+#[test]
+fn uncompressed_protocols_are_untouched() {
+    let compression = compression(&[]);
+    let data = Bytes::from(vec![0; 512]);
+    assert_eq!(compression.compress(PROTOCOL, data.clone()), data);
+    assert_eq!(
+        compression.decompress(PROTOCOL, data.clone()).unwrap(),
+        data
+    );
+}
+
+#[test]
+fn compression_roundtrip() {
+    let compression = compression(&[PROTOCOL]);
+
+    // Small payloads are sent raw
+    let data = Bytes::from(vec![7; 10]);
+    let payload = compression.compress(PROTOCOL, data.clone());
+    assert_eq!(payload[0], RAW);
+    assert_eq!(payload.len(), data.len() + 1);
+    assert_eq!(compression.decompress(PROTOCOL, payload).unwrap(), data);
+
+    // Large payloads are compressed
+    let data = Bytes::from(vec![7; 1024]);
+    let payload = compression.compress(PROTOCOL, data.clone());
+    assert_eq!(payload[0], LZ4);
+    assert!(payload.len() < data.len());
+    assert_eq!(compression.decompress(PROTOCOL, payload).unwrap(), data);
+
+    // Other protocols are not compressed
+    let payload = compression.compress(ProtocolId::ConsensusRpc, data.clone());
+    assert_eq!(payload, data);
+}
+
+#[test]
+fn compression_metrics() {
+    // Counters are global, so use a protocol the other tests don't compress
+    let protocol = ProtocolId::MempoolDirectSend;
+    let compression = compression(&[protocol]);
+    let bytes = |direction, state| {
+        counters::compression_bytes(&compression.network_context, protocol, direction, state).get()
+    };
+
+    // Raw payloads are recorded as well as compressed ones
+    for len in &[10, 1024] {
+        let (raw, compressed) = (
+            bytes(SENT_LABEL, RAW_LABEL),
+            bytes(SENT_LABEL, COMPRESSED_LABEL),
+        );
+        let payload = compression.compress(protocol, Bytes::from(vec![7; *len]));
+        assert_eq!(bytes(SENT_LABEL, RAW_LABEL) - raw, *len as u64);
+        assert_eq!(
+            bytes(SENT_LABEL, COMPRESSED_LABEL) - compressed,
+            payload.len() as u64
+        );
+
+        let (raw, compressed) = (
+            bytes(RECEIVED_LABEL, RAW_LABEL),
+            bytes(RECEIVED_LABEL, COMPRESSED_LABEL),
+        );
+        let payload_len = payload.len();
+        compression.decompress(protocol, payload).unwrap();
+        assert_eq!(bytes(RECEIVED_LABEL, RAW_LABEL) - raw, *len as u64);
+        assert_eq!(
+            bytes(RECEIVED_LABEL, COMPRESSED_LABEL) - compressed,
+            payload_len as u64
+        );
+    }
+}
+
+#[test]
+fn decompression_limits() {
+    let compression = compression(&[PROTOCOL]);
+
+    // The declared size is checked before decompressing
+    let bomb = compression.compress(PROTOCOL, Bytes::from(vec![0; 4096]));
+    assert!(matches!(
+        compression.decompress(PROTOCOL, bomb),
+        Err(CompressionError::TooLarge(4096, 1024))
+    ));
+
+    // So is its ratio to the compressed size
+    let mut forged = vec![LZ4];
+    forged.extend_from_slice(&1000u32.to_le_bytes());
+    forged.push(0);
+    assert!(matches!(
+        compression.decompress(PROTOCOL, Bytes::from(forged)),
+        Err(CompressionError::BadSize(1000, 1))
+    ));
+
+    // Invalid payloads are rejected
+    assert!(matches!(
+        compression.decompress(PROTOCOL, Bytes::new()),
+        Err(CompressionError::Truncated)
+    ));
+    assert!(matches!(
+        compression.decompress(PROTOCOL, Bytes::from(vec![LZ4, 0])),
+        Err(CompressionError::Truncated)
+    ));
+    assert!(matches!(
+        compression.decompress(PROTOCOL, Bytes::from(vec![42, 0])),
+        Err(CompressionError::UnknownScheme(42))
+    ));
+
+    // The decompressed data can't exceed the declared size
+    let mut payload = compression
+        .compress(PROTOCOL, Bytes::from(vec![0; 512]))
+        .to_vec();
+    payload[1..1 + LZ4_SIZE_PREFIX].copy_from_slice(&100u32.to_le_bytes());
+    assert!(compression
+        .decompress(PROTOCOL, Bytes::from(payload))
+        .is_err());
+}
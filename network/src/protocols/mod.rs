@@ -4,9 +4,12 @@
 //! Protocols used by network module for external APIs and internal functionality
 //!
 //! Each protocol corresponds to a certain order of messages
+pub mod compression;
 pub mod direct_send;
 pub mod network;
+pub mod priority;
 pub mod rpc;
+pub mod stream;
 
 pub mod health_checker;
 pub mod identity;
@@ -0,0 +1,190 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Priority scheduling of the outbound messages of a connection.
+//!
+//! Each application protocol is assigned a [`PriorityClass`] by the [`PriorityConfig`], which is
+//! carried on the wire in the `priority` field of its messages. Rpc responses are sent with the
+//! priority of their request. The writer of each connection keeps one queue per priority class
+//! in [`PriorityQueues`] and picks the next message to send with a weighted round robin: while
+//! several classes have pending messages, each class sends as many messages in turn as its
+//! weight, from the highest priority class to the lowest. A burst of low priority messages
+//! (e.g. state sync) therefore can't hold up the high priority ones (e.g. consensus votes),
+//! while still getting its share of the connection.
+
+use crate::{
+    counters,
+    protocols::wire::{
+        handshake::v1::ProtocolId,
+        messaging::v1::{NetworkMessage, Priority},
+    },
+};
+use diem_config::{
+    config::{PriorityClass, PriorityConfig},
+    network_id::NetworkContext,
+};
+use diem_time_service::{TimeService, TimeServiceTrait};
+use std::{
+    cmp::max,
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Instant,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Returns the priority sent on the wire for the messages of the given class.
+pub fn wire_priority(class: PriorityClass) -> Priority {
+    match class {
+        PriorityClass::High => 255,
+        PriorityClass::Normal => 128,
+        PriorityClass::Low => 0,
+    }
+}
+
+/// Returns the class of the given wire priority. Peers which don't schedule messages by priority
+/// send the default priority, which is the lowest.
+pub fn priority_class(priority: Priority) -> PriorityClass {
+    match priority {
+        192..=255 => PriorityClass::High,
+        64..=191 => PriorityClass::Normal,
+        _ => PriorityClass::Low,
+    }
+}
+
+/// Returns the class an outbound message is scheduled with.
+pub fn message_class(message: &NetworkMessage) -> PriorityClass {
+    match message {
+        NetworkMessage::DirectSendMsg(message) => priority_class(message.priority),
+        NetworkMessage::RpcRequest(request) => priority_class(request.priority),
+        NetworkMessage::RpcResponse(response) => priority_class(response.priority),
+        // Errors are rare and small, and streams are scheduled by the writer itself.
+        NetworkMessage::Error(_)
+        | NetworkMessage::StreamHeader(_)
+        | NetworkMessage::StreamFragment(_) => PriorityClass::High,
+    }
+}
+
+/// The priorities of the messages of each application protocol.
+#[derive(Clone, Debug)]
+pub struct ProtocolPriorities(HashMap<ProtocolId, Priority>);
+
+impl ProtocolPriorities {
+    pub fn new(config: &PriorityConfig) -> Self {
+        Self(
+            ProtocolId::all()
+                .iter()
+                .map(|protocol_id| {
+                    let class = config
+                        .protocols
+                        .get(protocol_id.as_str())
+                        .copied()
+                        .unwrap_or_default();
+                    (*protocol_id, wire_priority(class))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn priority(&self, protocol_id: ProtocolId) -> Priority {
+        self.0
+            .get(&protocol_id)
+            .copied()
+            .unwrap_or_else(|| wire_priority(PriorityClass::default()))
+    }
+}
+
+/// Outbound queues of a connection, one per priority class.
+pub struct PriorityQueues<T> {
+    network_context: Arc<NetworkContext>,
+    time_service: TimeService,
+    /// The queue of each class, indexed by class, along with the time each item was queued.
+    queues: Vec<VecDeque<(T, Instant)>>,
+    /// Number of items each class sends per round.
+    weights: Vec<u32>,
+    /// Number of items each class can still send in the current round.
+    credits: Vec<u32>,
+    len: usize,
+}
+
+impl<T> PriorityQueues<T> {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        time_service: TimeService,
+        config: &PriorityConfig,
+    ) -> Self {
+        // A class with a null weight would never be sent.
+        let weights: Vec<_> = PriorityClass::all()
+            .iter()
+            .map(|class| max(config.weight(*class), 1))
+            .collect();
+        Self {
+            network_context,
+            time_service,
+            queues: PriorityClass::all()
+                .iter()
+                .map(|_| VecDeque::new())
+                .collect(),
+            credits: weights.clone(),
+            weights,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Queues an item after the other items of its class.
+    pub fn push(&mut self, class: PriorityClass, item: T) {
+        self.queues[class as usize].push_back((item, self.time_service.now()));
+        self.len += 1;
+        counters::outbound_queue_size(&self.network_context, class).inc();
+    }
+
+    /// Returns the next item to send, or `None` if all the queues are empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // Start a new round once the pending classes have used up their credits.
+        let queues = &self.queues;
+        if self
+            .credits
+            .iter()
+            .enumerate()
+            .all(|(index, credits)| *credits == 0 || queues[index].is_empty())
+        {
+            self.credits.copy_from_slice(&self.weights);
+        }
+        let index = (0..queues.len())
+            .find(|index| self.credits[*index] > 0 && !queues[*index].is_empty())?;
+        self.credits[index] -= 1;
+        let (item, queued_at) = self.queues[index].pop_front()?;
+        self.len -= 1;
+
+        let class = PriorityClass::all()[index];
+        counters::outbound_queue_size(&self.network_context, class).dec();
+        counters::outbound_queue_wait(&self.network_context, class).observe(
+            self.time_service
+                .now()
+                .saturating_duration_since(queued_at)
+                .as_secs_f64(),
+        );
+        Some(item)
+    }
+}
+
+impl<T> Drop for PriorityQueues<T> {
+    fn drop(&mut self) {
+        for (class, queue) in PriorityClass::all().iter().zip(&self.queues) {
+            counters::outbound_queue_size(&self.network_context, *class).sub(queue.len() as i64);
+        }
+    }
+}
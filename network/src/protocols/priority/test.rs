@@ -0,0 +1,83 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::protocols::wire::messaging::v1::{DirectSendMsg, RpcResponse};
+
+fn queues(high_weight: u32, normal_weight: u32, low_weight: u32) -> PriorityQueues<&'static str> {
+    let config = PriorityConfig {
+        high_weight,
+        normal_weight,
+        low_weight,
+        ..PriorityConfig::default()
+    };
+    PriorityQueues::new(NetworkContext::mock(), TimeService::mock(), &config)
+}
+
+fn drain(queues: &mut PriorityQueues<&'static str>) -> Vec<&'static str> {
+    std::iter::from_fn(|| queues.pop()).collect()
+}
+
+#[test]
+fn wire_priorities() {
+    for class in PriorityClass::all() {
+        assert_eq!(priority_class(wire_priority(*class)), *class);
+    }
+    assert_eq!(priority_class(Priority::default()), PriorityClass::Low);
+
+    let priorities = ProtocolPriorities::new(&PriorityConfig::default());
+    assert_eq!(
+        priorities.priority(ProtocolId::ConsensusDirectSend),
+        wire_priority(PriorityClass::High)
+    );
+    assert_eq!(
+        priorities.priority(ProtocolId::StateSyncDirectSend),
+        wire_priority(PriorityClass::Low)
+    );
+
+    let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::MempoolDirectSend,
+        priority: priorities.priority(ProtocolId::MempoolDirectSend),
+        raw_msg: vec![],
+    });
+    assert_eq!(message_class(&message), PriorityClass::Normal);
+    let message = NetworkMessage::RpcResponse(RpcResponse {
+        request_id: 0,
+        priority: priorities.priority(ProtocolId::ConsensusRpc),
+        raw_response: vec![],
+    });
+    assert_eq!(message_class(&message), PriorityClass::High);
+}
+
+#[test]
+fn weighted_round_robin() {
+    let mut queues = queues(2, 1, 1);
+    for _ in 0..4 {
+        queues.push(PriorityClass::Low, "low");
+        queues.push(PriorityClass::Normal, "normal");
+        queues.push(PriorityClass::High, "high");
+    }
+    assert_eq!(queues.len(), 12);
+    assert_eq!(
+        drain(&mut queues),
+        vec![
+            "high", "high", "normal", "low", "high", "high", "normal", "low", "normal", "low",
+            "normal", "low",
+        ]
+    );
+    assert!(queues.is_empty());
+}
+
+#[test]
+fn idle_classes_are_skipped() {
+    let mut queues = queues(4, 1, 0);
+    queues.push(PriorityClass::Low, "low 1");
+    queues.push(PriorityClass::Low, "low 2");
+    assert_eq!(queues.pop(), Some("low 1"));
+
+    // Higher priority messages go first, even in the middle of a round.
+    queues.push(PriorityClass::High, "high");
+    queues.push(PriorityClass::Normal, "normal");
+    assert_eq!(drain(&mut queues), vec!["high", "normal", "low 2"]);
+    assert_eq!(queues.pop(), None);
+}
@@ -3,7 +3,7 @@
 
 //! Rpc protocol errors
 
-use crate::peer_manager::PeerManagerError;
+use crate::{peer_manager::PeerManagerError, protocols::compression::CompressionError};
 use anyhow::anyhow;
 use diem_types::PeerId;
 use futures::channel::{mpsc, oneshot};
@@ -21,6 +21,9 @@ pub enum RpcError {
     #[error("Bcs error: {0:?}")]
     BcsError(#[from] bcs::Error),
 
+    #[error("Compression error: {0}")]
+    CompressionError(#[from] CompressionError),
+
     #[error("Failed to open substream, not connected with peer: {0}")]
     NotConnected(PeerId),
 
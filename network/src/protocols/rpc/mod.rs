@@ -51,8 +51,9 @@ use crate::{
     logging::NetworkSchema,
     peer::PeerNotification,
     peer_manager::PeerManagerError,
-    protocols::wire::messaging::v1::{
-        NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse,
+    protocols::{
+        compression::PayloadCompression,
+        wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
     },
     ProtocolId,
 };
@@ -178,6 +179,8 @@ pub struct InboundRpcs {
     /// Only allow this many concurrent inbound rpcs at one time from this remote
     /// peer.  New inbound requests exceeding this limit will be dropped.
     max_concurrent_inbound_rpcs: u32,
+    /// Decompresses the requests and compresses the responses of the compressed protocols.
+    compression: Arc<PayloadCompression>,
 }
 
 impl InboundRpcs {
@@ -187,6 +190,7 @@ impl InboundRpcs {
         remote_peer_id: PeerId,
         inbound_rpc_timeout: Duration,
         max_concurrent_inbound_rpcs: u32,
+        compression: Arc<PayloadCompression>,
     ) -> Self {
         Self {
             network_context,
@@ -195,6 +199,7 @@ impl InboundRpcs {
             inbound_rpc_tasks: FuturesUnordered::new(),
             inbound_rpc_timeout,
             max_concurrent_inbound_rpcs,
+            compression,
         }
     }
 
@@ -230,6 +235,9 @@ impl InboundRpcs {
         // Collect counters for received request.
         counters::rpc_messages(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc();
         counters::rpc_bytes(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc_by(req_len);
+        let data = self
+            .compression
+            .decompress(protocol_id, Bytes::from(request.raw_request))?;
         let timer =
             counters::inbound_rpc_handler_latency(network_context, protocol_id).start_timer();
 
@@ -237,7 +245,7 @@ impl InboundRpcs {
         let (response_tx, response_rx) = oneshot::channel();
         let notif = PeerNotification::RecvRpc(InboundRpcRequest {
             protocol_id,
-            data: Bytes::from(request.raw_request),
+            data,
             res_tx: response_tx,
         });
         if let Err(err) = peer_notifs_tx.push(protocol_id, notif) {
@@ -246,17 +254,21 @@ impl InboundRpcs {
         }
 
         // Create a new task that waits for a response from the upper layer with a timeout.
+        let compression = self.compression.clone();
         let inbound_rpc_task = self
             .time_service
             .timeout(self.inbound_rpc_timeout, response_rx)
             .map(move |result| {
                 // Flatten the errors
                 let maybe_response = match result {
-                    Ok(Ok(Ok(response_bytes))) => Ok(RpcResponse {
-                        request_id,
-                        priority,
-                        raw_response: Vec::from(response_bytes.as_ref()),
-                    }),
+                    Ok(Ok(Ok(response_bytes))) => {
+                        let response_bytes = compression.compress(protocol_id, response_bytes);
+                        Ok(RpcResponse {
+                            request_id,
+                            priority,
+                            raw_response: Vec::from(response_bytes.as_ref()),
+                        })
+                    }
                     Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
@@ -354,6 +366,8 @@ pub struct OutboundRpcs {
     /// Only allow this many concurrent outbound rpcs at one time from this remote
     /// peer. New outbound requests exceeding this limit will be dropped.
     max_concurrent_outbound_rpcs: u32,
+    /// Compresses the requests and decompresses the responses of the compressed protocols.
+    compression: Arc<PayloadCompression>,
 }
 
 impl OutboundRpcs {
@@ -362,6 +376,7 @@ impl OutboundRpcs {
         time_service: TimeService,
         remote_peer_id: PeerId,
         max_concurrent_outbound_rpcs: u32,
+        compression: Arc<PayloadCompression>,
     ) -> Self {
         Self {
             network_context,
@@ -371,6 +386,7 @@ impl OutboundRpcs {
             outbound_rpc_tasks: FuturesUnordered::new(),
             pending_outbound_rpcs: HashMap::new(),
             max_concurrent_outbound_rpcs,
+            compression,
         }
     }
 
@@ -378,6 +394,7 @@ impl OutboundRpcs {
     pub async fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
+        priority: Priority,
         write_reqs_tx: &mut channel::Sender<(
             NetworkMessage,
             oneshot::Sender<Result<(), PeerManagerError>>,
@@ -426,10 +443,11 @@ impl OutboundRpcs {
             counters::outbound_rpc_request_latency(network_context, protocol_id).start_timer();
 
         // Enqueue rpc request message onto outbound write queue.
+        let request_data = self.compression.compress(protocol_id, request_data);
         let message = NetworkMessage::RpcRequest(RpcRequest {
             protocol_id,
             request_id,
-            priority: Priority::default(),
+            priority,
             raw_request: Vec::from(request_data.as_ref()),
         });
         let (ack_tx, _) = oneshot::channel();
@@ -449,17 +467,22 @@ impl OutboundRpcs {
         // A future that waits for the rpc response with a timeout. We create the
         // timeout out here to start the timer as soon as we push onto the queue
         // (as opposed to whenever it first gets polled on the queue).
-        let wait_for_response = self
-            .time_service
-            .timeout(timeout, response_rx)
-            .map(|result| {
-                // Flatten errors.
-                match result {
-                    Ok(Ok(response)) => Ok(Bytes::from(response.raw_response)),
-                    Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
-                    Err(timeout::Elapsed) => Err(RpcError::TimedOut),
-                }
-            });
+        let compression = self.compression.clone();
+        let wait_for_response =
+            self.time_service
+                .timeout(timeout, response_rx)
+                .map(move |result| {
+                    // Flatten errors.
+                    match result {
+                        Ok(Ok(response)) => compression
+                            .decompress(protocol_id, Bytes::from(response.raw_response))
+                            .map_err(RpcError::from),
+                        Ok(Err(oneshot::Canceled)) => {
+                            Err(RpcError::UnexpectedResponseChannelCancel)
+                        }
+                        Err(timeout::Elapsed) => Err(RpcError::TimedOut),
+                    }
+                });
 
         // A future that waits for the response and sends it to the application.
         let notify_application = async move {
@@ -0,0 +1,318 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming of messages larger than the maximum frame size.
+//!
+//! A `NetworkMessage` whose serialized size exceeds the maximum frame size is sent as a
+//! [`StreamHeader`] followed by the [`StreamFragment`]s of its serialized bytes. On the sending
+//! side, [`OutboundStreams`] queues such messages and hands out one frame at a time, so the writer
+//! can interleave them with smaller messages (e.g. consensus direct-sends) instead of blocking the
+//! connection until the whole message is written. Messages of the same priority are streamed one
+//! after the other, so on the receiving side [`InboundStreams`] only ever reassembles a single
+//! message per peer and priority class. The messages reassembled at once share the maximum
+//! message size as their budget, so a peer streaming in every class can't make us buffer more.
+
+use crate::protocols::wire::messaging::v1::{
+    NetworkMessage, Priority, StreamFragment, StreamHeader, StreamId,
+};
+use diem_config::config::PriorityClass;
+use std::{collections::VecDeque, convert::TryFrom};
+use thiserror::Error;
+
+#[cfg(test)]
+mod test;
+
+/// Number of bytes of each frame reserved for the framing of a `StreamFragment`, i.e. everything
+/// besides the fragment data itself.
+pub const FRAGMENT_OVERHEAD: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("Message of {0} bytes exceeds the maximum message size of {1} bytes")]
+    MessageTooLarge(usize, usize),
+
+    #[error("Message of {0} bytes exceeds the {1} bytes left of the inbound stream budget")]
+    OverBudget(usize, usize),
+
+    #[error("Invalid stream header: {0:?}")]
+    InvalidHeader(StreamHeader),
+
+    #[error("Stream {0} interrupted by a new stream")]
+    Interrupted(StreamId),
+
+    #[error("Unexpected fragment {1} for stream {0}")]
+    UnexpectedFragment(StreamId, u32),
+
+    #[error("Streamed message is itself a stream message")]
+    NestedStream,
+
+    #[error("Bcs error: {0:?}")]
+    BcsError(#[from] bcs::Error),
+}
+
+/// A message being sent as a stream.
+struct OutboundStream<T> {
+    header: StreamHeader,
+    data: Vec<u8>,
+    /// Next fragment to send, `None` if the header was not sent yet.
+    next_fragment_id: Option<u32>,
+    /// Returned along with the last fragment, e.g. to acknowledge the message was sent.
+    ack: T,
+}
+
+/// Queue of the outbound messages which are too large to be sent in a single frame.
+pub struct OutboundStreams<T> {
+    max_frame_size: usize,
+    max_message_size: usize,
+    /// Priority sent with the frames of the streamed messages.
+    priority: Priority,
+    next_stream_id: StreamId,
+    streams: VecDeque<OutboundStream<T>>,
+}
+
+impl<T> OutboundStreams<T> {
+    pub fn new(max_frame_size: usize, max_message_size: usize, priority: Priority) -> Self {
+        assert!(
+            max_frame_size > FRAGMENT_OVERHEAD,
+            "Max frame size {} is too small to stream messages",
+            max_frame_size
+        );
+        Self {
+            max_frame_size,
+            max_message_size,
+            priority,
+            next_stream_id: 0,
+            streams: VecDeque::new(),
+        }
+    }
+
+    fn fragment_size(&self) -> usize {
+        self.max_frame_size - FRAGMENT_OVERHEAD
+    }
+
+    /// Whether the message has to be streamed, i.e. it does not fit in a single frame.
+    pub fn should_stream(&self, message: &NetworkMessage) -> bool {
+        bcs::serialized_size(message).map_or(false, |size| size > self.max_frame_size)
+    }
+
+    /// Serializes the message and queues it to be streamed after the previously queued messages.
+    /// `ack` is handed back with the last fragment of the message.
+    pub fn push(&mut self, message: &NetworkMessage, ack: T) -> Result<(), StreamError> {
+        let data = bcs::to_bytes(message)?;
+        if data.len() > self.max_message_size {
+            return Err(StreamError::MessageTooLarge(
+                data.len(),
+                self.max_message_size,
+            ));
+        }
+        let message_size = u32::try_from(data.len())
+            .map_err(|_| StreamError::MessageTooLarge(data.len(), self.max_message_size))?;
+        let num_fragments = (data.len() + self.fragment_size() - 1) / self.fragment_size();
+        let header = StreamHeader {
+            stream_id: self.next_stream_id,
+            priority: self.priority,
+            message_size,
+            num_fragments: num_fragments as u32,
+        };
+        self.next_stream_id = self.next_stream_id.wrapping_add(1);
+        self.streams.push_back(OutboundStream {
+            header,
+            data,
+            next_fragment_id: None,
+            ack,
+        });
+        Ok(())
+    }
+
+    /// Whether there are no frames left to send.
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Returns the next frame to send: the header of a stream, or one of its fragments in order.
+    /// The ack of a message is returned along with its last fragment.
+    pub fn next_frame(&mut self) -> Option<(NetworkMessage, Option<T>)> {
+        let fragment_size = self.fragment_size();
+        let stream = self.streams.front_mut()?;
+        let fragment_id = match stream.next_fragment_id {
+            None => {
+                stream.next_fragment_id = Some(0);
+                return Some((NetworkMessage::StreamHeader(stream.header.clone()), None));
+            }
+            Some(fragment_id) => fragment_id,
+        };
+
+        let start = fragment_id as usize * fragment_size;
+        let end = std::cmp::min(start + fragment_size, stream.data.len());
+        let fragment = NetworkMessage::StreamFragment(StreamFragment {
+            stream_id: stream.header.stream_id,
+            priority: stream.header.priority,
+            fragment_id,
+            raw_data: stream.data[start..end].to_vec(),
+        });
+        if fragment_id + 1 < stream.header.num_fragments {
+            stream.next_fragment_id = Some(fragment_id + 1);
+            Some((fragment, None))
+        } else {
+            let stream = self.streams.pop_front()?;
+            Some((fragment, Some(stream.ack)))
+        }
+    }
+}
+
+/// Reassembles the message being streamed by a peer.
+pub struct InboundStream {
+    max_message_size: usize,
+    /// Header of the message being received, along with the next expected fragment.
+    current: Option<(StreamHeader, u32)>,
+    data: Vec<u8>,
+}
+
+impl InboundStream {
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            current: None,
+            data: Vec::new(),
+        }
+    }
+
+    /// Starts receiving a new message. If a message was still being received, it is dropped and
+    /// an error is returned, but the new stream is started nevertheless.
+    pub fn new_stream(&mut self, header: StreamHeader) -> Result<(), StreamError> {
+        let message_size = header.message_size as usize;
+        if message_size > self.max_message_size {
+            self.reset();
+            return Err(StreamError::MessageTooLarge(
+                message_size,
+                self.max_message_size,
+            ));
+        }
+        if header.num_fragments == 0 || header.num_fragments as usize > message_size {
+            self.reset();
+            return Err(StreamError::InvalidHeader(header));
+        }
+
+        let interrupted = self.current.take().map(|(header, _)| header.stream_id);
+        // The buffer grows with the fragments actually received rather than the size announced
+        // by the header, so a peer can't make us allocate the maximum message size for nothing.
+        self.data = Vec::new();
+        self.current = Some((header, 0));
+        match interrupted {
+            Some(stream_id) => Err(StreamError::Interrupted(stream_id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends a fragment to the message being received, returning the message once complete.
+    /// Any unexpected fragment drops the message.
+    pub fn append_fragment(
+        &mut self,
+        fragment: StreamFragment,
+    ) -> Result<Option<NetworkMessage>, StreamError> {
+        let (header, next_fragment_id) = match &mut self.current {
+            Some((header, next_fragment_id))
+                if header.stream_id == fragment.stream_id
+                    && header.priority == fragment.priority
+                    && *next_fragment_id == fragment.fragment_id
+                    && self.data.len() + fragment.raw_data.len()
+                        <= header.message_size as usize =>
+            {
+                (header, next_fragment_id)
+            }
+            _ => {
+                self.reset();
+                return Err(StreamError::UnexpectedFragment(
+                    fragment.stream_id,
+                    fragment.fragment_id,
+                ));
+            }
+        };
+        self.data.extend_from_slice(&fragment.raw_data);
+        *next_fragment_id += 1;
+        if *next_fragment_id < header.num_fragments {
+            return Ok(None);
+        }
+
+        let complete = self.data.len() == header.message_size as usize;
+        let data = std::mem::take(&mut self.data);
+        self.reset();
+        if !complete {
+            return Err(StreamError::UnexpectedFragment(
+                fragment.stream_id,
+                fragment.fragment_id,
+            ));
+        }
+        match bcs::from_bytes(&data)? {
+            NetworkMessage::StreamHeader(_) | NetworkMessage::StreamFragment(_) => {
+                Err(StreamError::NestedStream)
+            }
+            message => Ok(Some(message)),
+        }
+    }
+
+    /// Size of the message being received, which bounds the size of its buffer.
+    fn message_size(&self) -> usize {
+        self.current
+            .as_ref()
+            .map_or(0, |(header, _)| header.message_size as usize)
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+        self.data = Vec::new();
+    }
+}
+
+/// Reassembles the messages being streamed by a peer, one per priority class, within a budget of
+/// the maximum message size for all of them.
+pub struct InboundStreams {
+    max_message_size: usize,
+    streams: Vec<InboundStream>,
+}
+
+impl InboundStreams {
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            streams: PriorityClass::all()
+                .iter()
+                .map(|_| InboundStream::new(max_message_size))
+                .collect(),
+        }
+    }
+
+    /// Starts receiving a new message of the given class, see [`InboundStream::new_stream`]. The
+    /// message is rejected if it doesn't fit in the budget left by the other classes.
+    pub fn new_stream(
+        &mut self,
+        class: PriorityClass,
+        header: StreamHeader,
+    ) -> Result<(), StreamError> {
+        let used: usize = self
+            .streams
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != class as usize)
+            .map(|(_, stream)| stream.message_size())
+            .sum();
+        let available = self.max_message_size.saturating_sub(used);
+        let message_size = header.message_size as usize;
+        let stream = &mut self.streams[class as usize];
+        if message_size <= self.max_message_size && message_size > available {
+            stream.reset();
+            return Err(StreamError::OverBudget(message_size, available));
+        }
+        stream.new_stream(header)
+    }
+
+    /// Appends a fragment to the message of the given class, see
+    /// [`InboundStream::append_fragment`].
+    pub fn append_fragment(
+        &mut self,
+        class: PriorityClass,
+        fragment: StreamFragment,
+    ) -> Result<Option<NetworkMessage>, StreamError> {
+        self.streams[class as usize].append_fragment(fragment)
+    }
+}
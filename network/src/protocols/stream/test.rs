@@ -0,0 +1,246 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::protocols::wire::{
+    handshake::v1::ProtocolId,
+    messaging::v1::{DirectSendMsg, RpcResponse},
+};
+
+const MAX_FRAME_SIZE: usize = 128;
+const MAX_MESSAGE_SIZE: usize = 1024;
+const PRIORITY: Priority = 128;
+
+fn direct_send(size: usize) -> NetworkMessage {
+    NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::ConsensusDirectSend,
+        priority: 0,
+        raw_msg: vec![7; size],
+    })
+}
+
+fn rpc_response(size: usize) -> NetworkMessage {
+    NetworkMessage::RpcResponse(RpcResponse {
+        request_id: 1,
+        priority: 0,
+        raw_response: (0..size).map(|i| i as u8).collect(),
+    })
+}
+
+/// Drains all the frames of the queue, checking the acks come with the last fragments.
+fn drain(outbound: &mut OutboundStreams<u32>) -> (Vec<NetworkMessage>, Vec<u32>) {
+    let mut frames = vec![];
+    let mut acks = vec![];
+    while let Some((frame, ack)) = outbound.next_frame() {
+        assert!(bcs::serialized_size(&frame).unwrap() <= MAX_FRAME_SIZE);
+        match &frame {
+            NetworkMessage::StreamHeader(header) => assert_eq!(header.priority, PRIORITY),
+            NetworkMessage::StreamFragment(fragment) => assert_eq!(fragment.priority, PRIORITY),
+            frame => panic!("Unexpected frame: {:?}", frame),
+        }
+        frames.push(frame);
+        acks.extend(ack);
+    }
+    (frames, acks)
+}
+
+fn receive(inbound: &mut InboundStream, frames: Vec<NetworkMessage>) -> Vec<NetworkMessage> {
+    let mut messages = vec![];
+    for frame in frames {
+        match frame {
+            NetworkMessage::StreamHeader(header) => inbound.new_stream(header).unwrap(),
+            NetworkMessage::StreamFragment(fragment) => {
+                messages.extend(inbound.append_fragment(fragment).unwrap())
+            }
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+    messages
+}
+
+#[test]
+fn should_stream() {
+    let outbound = OutboundStreams::<u32>::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    assert!(!outbound.should_stream(&direct_send(10)));
+    assert!(outbound.should_stream(&direct_send(MAX_FRAME_SIZE)));
+}
+
+#[test]
+fn stream_roundtrip() {
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    let mut inbound = InboundStream::new(MAX_MESSAGE_SIZE);
+    let messages = vec![rpc_response(500), direct_send(300), rpc_response(1000)];
+    for (i, message) in messages.iter().enumerate() {
+        outbound.push(message, i as u32).unwrap();
+    }
+
+    let (frames, acks) = drain(&mut outbound);
+    assert!(outbound.is_empty());
+    assert_eq!(acks, vec![0, 1, 2]);
+    assert_eq!(receive(&mut inbound, frames), messages);
+}
+
+#[test]
+fn stream_ids_are_distinct() {
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    outbound.push(&direct_send(300), 0).unwrap();
+    outbound.push(&direct_send(300), 1).unwrap();
+
+    let (frames, _) = drain(&mut outbound);
+    let stream_ids: Vec<_> = frames
+        .iter()
+        .filter_map(|frame| match frame {
+            NetworkMessage::StreamHeader(header) => Some(header.stream_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stream_ids, vec![0, 1]);
+}
+
+#[test]
+fn message_too_large() {
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    assert!(matches!(
+        outbound.push(&direct_send(MAX_MESSAGE_SIZE), 0),
+        Err(StreamError::MessageTooLarge(_, MAX_MESSAGE_SIZE))
+    ));
+    assert!(outbound.is_empty());
+
+    // The receiver enforces its own limit whatever the sender's is.
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, 2 * MAX_MESSAGE_SIZE, PRIORITY);
+    outbound.push(&direct_send(MAX_MESSAGE_SIZE), 0).unwrap();
+    let (frames, _) = drain(&mut outbound);
+    let mut inbound = InboundStream::new(MAX_MESSAGE_SIZE);
+    match frames.into_iter().next() {
+        Some(NetworkMessage::StreamHeader(header)) => assert!(matches!(
+            inbound.new_stream(header),
+            Err(StreamError::MessageTooLarge(_, MAX_MESSAGE_SIZE))
+        )),
+        frame => panic!("Unexpected frame: {:?}", frame),
+    }
+}
+
+#[test]
+fn unexpected_fragments() {
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    outbound.push(&rpc_response(500), 0).unwrap();
+    let (frames, _) = drain(&mut outbound);
+    let mut fragments = frames.iter().filter_map(|frame| match frame {
+        NetworkMessage::StreamFragment(fragment) => Some(fragment.clone()),
+        _ => None,
+    });
+    let header = match &frames[0] {
+        NetworkMessage::StreamHeader(header) => header.clone(),
+        frame => panic!("Unexpected frame: {:?}", frame),
+    };
+
+    // A fragment without a header is rejected.
+    let mut inbound = InboundStream::new(MAX_MESSAGE_SIZE);
+    let first = fragments.next().unwrap();
+    assert!(inbound.append_fragment(first.clone()).is_err());
+
+    // A missing fragment drops the message.
+    inbound.new_stream(header.clone()).unwrap();
+    assert!(inbound.append_fragment(first.clone()).unwrap().is_none());
+    fragments.next().unwrap();
+    assert!(inbound.append_fragment(fragments.next().unwrap()).is_err());
+
+    // A new stream interrupts the current one.
+    inbound.new_stream(header.clone()).unwrap();
+    assert!(inbound.append_fragment(first.clone()).unwrap().is_none());
+    assert!(matches!(
+        inbound.new_stream(header.clone()),
+        Err(StreamError::Interrupted(stream_id)) if stream_id == header.stream_id
+    ));
+
+    // A fragment with another priority than its header is rejected.
+    let other_priority = StreamFragment {
+        priority: PRIORITY + 1,
+        ..first
+    };
+    assert!(matches!(
+        inbound.append_fragment(other_priority),
+        Err(StreamError::UnexpectedFragment(_, 0))
+    ));
+
+    // Headers with more fragments than bytes are invalid.
+    let invalid_header = StreamHeader {
+        num_fragments: header.message_size + 1,
+        ..header
+    };
+    assert!(matches!(
+        inbound.new_stream(invalid_header),
+        Err(StreamError::InvalidHeader(_))
+    ));
+}
+
+#[test]
+fn buffer_grows_with_fragments() {
+    let mut outbound = OutboundStreams::new(MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, PRIORITY);
+    outbound.push(&rpc_response(1000), 0).unwrap();
+    let (frames, _) = drain(&mut outbound);
+    let mut frames = frames.into_iter();
+
+    // Nothing is allocated for the announced message size until fragments arrive.
+    let mut inbound = InboundStream::new(MAX_MESSAGE_SIZE);
+    let header = match frames.next() {
+        Some(NetworkMessage::StreamHeader(header)) => header,
+        frame => panic!("Unexpected frame: {:?}", frame),
+    };
+    let message_size = header.message_size as usize;
+    inbound.new_stream(header).unwrap();
+    assert_eq!(inbound.data.capacity(), 0);
+
+    match frames.next() {
+        Some(NetworkMessage::StreamFragment(fragment)) => {
+            let fragment_size = fragment.raw_data.len();
+            assert!(inbound.append_fragment(fragment).unwrap().is_none());
+            assert_eq!(inbound.data.len(), fragment_size);
+            assert!(inbound.data.capacity() < message_size);
+        }
+        frame => panic!("Unexpected frame: {:?}", frame),
+    }
+}
+
+#[test]
+fn inbound_budget_is_shared() {
+    let header = |stream_id, message_size: usize| StreamHeader {
+        stream_id,
+        priority: PRIORITY,
+        message_size: message_size as u32,
+        num_fragments: 1,
+    };
+    let message = direct_send(300);
+    let data = bcs::to_bytes(&message).unwrap();
+    let mut inbound = InboundStreams::new(MAX_MESSAGE_SIZE);
+
+    // Streams of all the classes together can't exceed the maximum message size.
+    inbound
+        .new_stream(PriorityClass::High, header(0, 600))
+        .unwrap();
+    inbound
+        .new_stream(PriorityClass::Normal, header(1, data.len()))
+        .unwrap();
+    let available = MAX_MESSAGE_SIZE - 600 - data.len();
+    assert!(matches!(
+        inbound.new_stream(PriorityClass::Low, header(2, available + 1)),
+        Err(StreamError::OverBudget(_, left)) if left == available
+    ));
+
+    // The budget is released once the message is received.
+    let fragment = StreamFragment {
+        stream_id: 1,
+        priority: PRIORITY,
+        fragment_id: 0,
+        raw_data: data.clone(),
+    };
+    assert_eq!(
+        inbound
+            .append_fragment(PriorityClass::Normal, fragment)
+            .unwrap(),
+        Some(message)
+    );
+    inbound
+        .new_stream(PriorityClass::Low, header(2, available + data.len()))
+        .unwrap();
+}
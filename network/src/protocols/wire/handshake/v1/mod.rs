@@ -82,6 +82,12 @@ impl fmt::Display for ProtocolId {
 // SupportedProtocols
 //
 
+/// Offset of the bits advertising compression support in [`SupportedProtocols`]: bit
+/// `COMPRESSION_OFFSET + p` is set when the node compresses the messages of protocol `p`. Nodes
+/// without compression support never set these bits, so the intersection computed during the
+/// handshake only keeps them for the protocols both ends compress.
+pub const COMPRESSION_OFFSET: u8 = 128;
+
 /// A bit vector of supported [`ProtocolId`]s.
 #[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
@@ -93,7 +99,7 @@ impl TryInto<Vec<ProtocolId>> for SupportedProtocols {
     fn try_into(self) -> bcs::Result<Vec<ProtocolId>> {
         let mut protocols = Vec::with_capacity(self.0.count_ones() as usize);
         if let Some(last_bit) = self.0.last_set_bit() {
-            for i in 0..=last_bit {
+            for i in 0..=std::cmp::min(last_bit, COMPRESSION_OFFSET - 1) {
                 if self.0.is_set(i) {
                     let protocol: ProtocolId = bcs::from_bytes(&[i])?;
                     protocols.push(protocol);
@@ -117,6 +123,22 @@ impl SupportedProtocols {
     fn intersection(self, other: SupportedProtocols) -> SupportedProtocols {
         SupportedProtocols(self.0 & other.0)
     }
+
+    /// Returns whether the given protocol is supported.
+    pub fn contains(&self, protocol: ProtocolId) -> bool {
+        self.0.is_set(protocol as u8)
+    }
+
+    /// Advertises compression support for the given protocols.
+    pub fn with_compression<'a>(mut self, protocols: impl Iterator<Item = &'a ProtocolId>) -> Self {
+        protocols.for_each(|p| self.0.set(COMPRESSION_OFFSET + *p as u8));
+        self
+    }
+
+    /// Returns whether the messages of the given protocol are compressed.
+    pub fn supports_compression(&self, protocol: ProtocolId) -> bool {
+        self.0.is_set(COMPRESSION_OFFSET + protocol as u8)
+    }
 }
 
 //
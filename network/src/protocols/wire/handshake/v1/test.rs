@@ -27,6 +27,33 @@ fn protocols_to_from_vec() {
     );
 }
 
+#[test]
+fn compression_negotiation() {
+    let protocols = [ProtocolId::ConsensusRpc, ProtocolId::StateSyncDirectSend];
+    let supported_protocols = SupportedProtocols::from(protocols.iter())
+        .with_compression([ProtocolId::StateSyncDirectSend].iter());
+    assert!(supported_protocols.supports_compression(ProtocolId::StateSyncDirectSend));
+    assert!(!supported_protocols.supports_compression(ProtocolId::ConsensusRpc));
+
+    // Compression bits are not protocols.
+    assert_eq!(
+        (supported_protocols.clone().try_into() as Result<Vec<ProtocolId>, _>).unwrap(),
+        protocols.to_vec()
+    );
+
+    // Compression is only kept if both ends support it.
+    let without_compression = SupportedProtocols::from(protocols.iter());
+    let common = supported_protocols
+        .clone()
+        .intersection(without_compression);
+    assert!(common.contains(ProtocolId::StateSyncDirectSend));
+    assert!(!common.supports_compression(ProtocolId::StateSyncDirectSend));
+    let common = supported_protocols
+        .clone()
+        .intersection(supported_protocols);
+    assert!(common.supports_compression(ProtocolId::StateSyncDirectSend));
+}
+
 #[test]
 fn represents_same_network() {
     let mut handshake_msg = HandshakeMsg::new_for_testing();
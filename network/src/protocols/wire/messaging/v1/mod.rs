@@ -43,6 +43,8 @@ pub enum NetworkMessage {
     RpcRequest(RpcRequest),
     RpcResponse(RpcResponse),
     DirectSendMsg(DirectSendMsg),
+    StreamHeader(StreamHeader),
+    StreamFragment(StreamFragment),
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -123,6 +125,39 @@ pub struct DirectSendMsg {
     pub raw_msg: Vec<u8>,
 }
 
+/// Create alias StreamId for u32.
+pub type StreamId = u32;
+
+/// Announces a message too large to fit in a single frame. The serialized `NetworkMessage` is
+/// split into `num_fragments` `StreamFragment`s which immediately follow the header on the same
+/// stream, possibly interleaved with other messages.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct StreamHeader {
+    /// StreamId shared by the header and all the fragments of the message.
+    pub stream_id: StreamId,
+    /// Priority of the message, shared by the header and all the fragments of the message.
+    pub priority: Priority,
+    /// Size of the serialized message in bytes.
+    pub message_size: u32,
+    /// Number of fragments the serialized message is split into.
+    pub num_fragments: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct StreamFragment {
+    /// StreamId of the corresponding header.
+    pub stream_id: StreamId,
+    /// Priority of the corresponding header.
+    pub priority: Priority,
+    /// Index of the fragment in the range 0..num_fragments. Fragments are sent in order.
+    pub fragment_id: u32,
+    /// Slice of the serialized message.
+    #[serde(with = "serde_bytes")]
+    pub raw_data: Vec<u8>,
+}
+
 /// Errors from reading and deserializing network messages off the wire.
 #[derive(Debug, Error)]
 pub enum ReadError {
@@ -11,18 +11,23 @@ use crate::{
     error::WaitForTransactionError,
     move_deserialize::{self, Event},
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionView, TransactionsWithProofsView,
+        AccountResourcesView, AccountStateWithProofView, AccountView, CurrencyInfoView, EventView,
+        EventWithProofView, MetadataView, MoveResourceView, StateProofView, TransactionOutputView,
+        TransactionView, TransactionWithProofView, TransactionWriteSetView,
+        TransactionsWithProofsView,
     },
     Error, Result, Retry, State,
 };
-use diem_crypto::{hash::CryptoHash, HashValue};
+use diem_crypto::{ed25519::Ed25519PublicKey, hash::CryptoHash, HashValue};
 use diem_types::{
     account_address::AccountAddress,
     event::EventKey,
-    transaction::{SignedTransaction, Transaction},
+    transaction::{RawTransaction, SignedTransaction, Transaction},
+};
+use move_core_types::{
+    language_storage::StructTag,
+    move_resource::{MoveResource, MoveStructType},
 };
-use move_core_types::move_resource::{MoveResource, MoveStructType};
 use serde::{de::DeserializeOwned, Serialize};
 use std::time::Duration;
 
@@ -132,6 +137,22 @@ impl BlockingClient {
         self.send_without_retry(&request, true)
     }
 
+    /// Executes `txn` on top of the latest state without committing it. The signature of `txn`
+    /// isn't checked, so this can be used to estimate gas usage before signing.
+    pub fn simulate(&self, txn: &SignedTransaction) -> Result<Response<TransactionOutputView>> {
+        self.send(MethodRequest::simulate_transaction(txn).map_err(Error::request)?)
+    }
+
+    /// Executes the unsigned `txn` on top of the latest state without committing it.
+    /// `public_key` is the public key of the sender, whose authentication key is still checked.
+    pub fn simulate_raw(
+        &self,
+        txn: &RawTransaction,
+        public_key: Ed25519PublicKey,
+    ) -> Result<Response<TransactionOutputView>> {
+        self.send(MethodRequest::simulate_raw_transaction(txn, public_key).map_err(Error::request)?)
+    }
+
     pub fn get_metadata_by_version(&self, version: u64) -> Result<Response<MetadataView>> {
         self.send(MethodRequest::get_metadata_by_version(version))
     }
@@ -165,6 +186,58 @@ impl BlockingClient {
         ))
     }
 
+    /// Returns the write sets of the transactions from `start_version`, with a max of `limit`
+    /// results.
+    pub fn get_transaction_write_sets(
+        &self,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<TransactionWriteSetView>>> {
+        self.send(MethodRequest::get_transaction_write_sets(
+            start_version,
+            limit,
+        ))
+    }
+
+    /// Returns all the resources under `address`, decoded to JSON, and the ABIs of the modules
+    /// published by the account.
+    pub fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Option<AccountResourcesView>>> {
+        self.send(MethodRequest::get_account_resources(address))
+    }
+
+    pub fn get_account_resources_by_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Option<AccountResourcesView>>> {
+        self.send(MethodRequest::get_account_resources_by_version(
+            address, version,
+        ))
+    }
+
+    /// Returns the resource of type `struct_tag` under `address`, decoded to JSON.
+    pub fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_account_resource(address, struct_tag))
+    }
+
+    pub fn get_account_resource_by_version(
+        &self,
+        address: AccountAddress,
+        struct_tag: &StructTag,
+        version: u64,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_account_resource_by_version(
+            address, struct_tag, version,
+        ))
+    }
+
     pub fn get_account_transaction(
         &self,
         address: AccountAddress,
@@ -253,6 +326,21 @@ impl BlockingClient {
         self.send(MethodRequest::get_events_with_proofs(key, start_seq, limit))
     }
 
+    pub fn get_account_transactions_with_proofs(
+        &self,
+        address: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Response<Vec<TransactionWithProofView>>> {
+        self.send(MethodRequest::get_account_transactions_with_proofs(
+            address,
+            start_seq,
+            limit,
+            include_events,
+        ))
+    }
+
     /// Return the events of type `T` that have been emitted to `event_key` since `start_seq`, with a max of `limit`
     /// results
     /// Returns an empty vector if there are no such event
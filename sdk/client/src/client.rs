@@ -11,18 +11,23 @@ use crate::{
     error::WaitForTransactionError,
     move_deserialize::{self, Event},
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionView, TransactionsWithProofsView,
+        AccountResourcesView, AccountStateWithProofView, AccountView, CurrencyInfoView, EventView,
+        EventWithProofView, MetadataView, MoveResourceView, StateProofView, TransactionOutputView,
+        TransactionView, TransactionWithProofView, TransactionWriteSetView,
+        TransactionsWithProofsView,
     },
     Error, Result, Retry, State,
 };
-use diem_crypto::{hash::CryptoHash, HashValue};
+use diem_crypto::{ed25519::Ed25519PublicKey, hash::CryptoHash, HashValue};
 use diem_types::{
     account_address::AccountAddress,
     event::EventKey,
-    transaction::{SignedTransaction, Transaction},
+    transaction::{RawTransaction, SignedTransaction, Transaction},
+};
+use move_core_types::{
+    language_storage::StructTag,
+    move_resource::{MoveResource, MoveStructType},
 };
-use move_core_types::move_resource::{MoveResource, MoveStructType};
 use reqwest::Client as ReqwestClient;
 use serde::{de::DeserializeOwned, Serialize};
 use std::time::Duration;
@@ -139,6 +144,27 @@ impl Client {
         self.send_without_retry(&request, true).await
     }
 
+    /// Executes `txn` on top of the latest state without committing it. The signature of `txn`
+    /// isn't checked, so this can be used to estimate gas usage before signing.
+    pub async fn simulate(
+        &self,
+        txn: &SignedTransaction,
+    ) -> Result<Response<TransactionOutputView>> {
+        self.send(MethodRequest::simulate_transaction(txn).map_err(Error::request)?)
+            .await
+    }
+
+    /// Executes the unsigned `txn` on top of the latest state without committing it.
+    /// `public_key` is the public key of the sender, whose authentication key is still checked.
+    pub async fn simulate_raw(
+        &self,
+        txn: &RawTransaction,
+        public_key: Ed25519PublicKey,
+    ) -> Result<Response<TransactionOutputView>> {
+        self.send(MethodRequest::simulate_raw_transaction(txn, public_key).map_err(Error::request)?)
+            .await
+    }
+
     pub async fn get_metadata_by_version(&self, version: u64) -> Result<Response<MetadataView>> {
         self.send(MethodRequest::get_metadata_by_version(version))
             .await
@@ -178,6 +204,63 @@ impl Client {
         .await
     }
 
+    /// Returns the write sets of the transactions from `start_version`, with a max of `limit`
+    /// results.
+    pub async fn get_transaction_write_sets(
+        &self,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<TransactionWriteSetView>>> {
+        self.send(MethodRequest::get_transaction_write_sets(
+            start_version,
+            limit,
+        ))
+        .await
+    }
+
+    /// Returns all the resources under `address`, decoded to JSON, and the ABIs of the modules
+    /// published by the account.
+    pub async fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Option<AccountResourcesView>>> {
+        self.send(MethodRequest::get_account_resources(address))
+            .await
+    }
+
+    pub async fn get_account_resources_by_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Option<AccountResourcesView>>> {
+        self.send(MethodRequest::get_account_resources_by_version(
+            address, version,
+        ))
+        .await
+    }
+
+    /// Returns the resource of type `struct_tag` under `address`, decoded to JSON.
+    pub async fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_account_resource(address, struct_tag))
+            .await
+    }
+
+    pub async fn get_account_resource_by_version(
+        &self,
+        address: AccountAddress,
+        struct_tag: &StructTag,
+        version: u64,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_account_resource_by_version(
+            address, struct_tag, version,
+        ))
+        .await
+    }
+
     pub async fn get_account_transaction(
         &self,
         address: AccountAddress,
@@ -273,6 +356,22 @@ impl Client {
             .await
     }
 
+    pub async fn get_account_transactions_with_proofs(
+        &self,
+        address: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Response<Vec<TransactionWithProofView>>> {
+        self.send(MethodRequest::get_account_transactions_with_proofs(
+            address,
+            start_seq,
+            limit,
+            include_events,
+        ))
+        .await
+    }
+
     /// Return the events of type `T` that have been emitted to `event_key` since `start_seq`, with a max of `limit`
     /// results
     /// Returns an empty vector if there are no such events
@@ -367,3 +466,40 @@ impl Client {
         response.json().await.map_err(Error::from_reqwest_error)
     }
 }
+
+cfg_websocket! {
+    use crate::stream::{self, SubscriptionStream};
+    use diem_json_rpc_types::stream::{
+        StreamMethodRequest, SubscribeToEventsParams, SubscribeToTransactionsParams,
+    };
+
+    impl Client {
+        /// Subscribes to the transactions committed from `starting_version` on, through the
+        /// WebSocket streaming API of the server. The streaming API must be enabled on the server.
+        pub async fn subscribe_to_transactions(
+            &self,
+            starting_version: u64,
+            include_events: bool,
+        ) -> Result<SubscriptionStream<TransactionView>> {
+            let request = StreamMethodRequest::SubscribeToTransactions(SubscribeToTransactionsParams {
+                starting_version,
+                include_events,
+            });
+            stream::subscribe(&self.url, request).await
+        }
+
+        /// Subscribes to the events emitted to `event_key` from `event_seq_num` on, through the
+        /// WebSocket streaming API of the server. The streaming API must be enabled on the server.
+        pub async fn subscribe_to_events(
+            &self,
+            event_key: EventKey,
+            event_seq_num: u64,
+        ) -> Result<SubscriptionStream<EventView>> {
+            let request = StreamMethodRequest::SubscribeToEvents(SubscribeToEventsParams {
+                event_key,
+                event_seq_num,
+            });
+            stream::subscribe(&self.url, request).await
+        }
+    }
+}
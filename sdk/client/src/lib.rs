@@ -21,7 +21,7 @@ cfg_async! {
     // to use it until it stabilizes further
     // TODO(philiphayes): make this pub once verifying_client is stable.
     #[doc(hidden)]
-    pub use verifying_client::{Storage, InMemoryStorage, VerifyingClient};
+    pub use verifying_client::{FileStorage, InMemoryStorage, Storage, VerifyingClient};
 }
 
 cfg_faucet! {
@@ -29,6 +29,11 @@ cfg_faucet! {
     pub use faucet::FaucetClient;
 }
 
+cfg_websocket! {
+    mod stream;
+    pub use stream::SubscriptionStream;
+}
+
 mod request;
 pub use request::{JsonRpcRequest, MethodRequest};
 
@@ -81,6 +86,11 @@ pub enum Method {
     GetAccountStateWithProof,
     GetTransactionsWithProofs,
     GetEventsWithProofs,
+    GetAccountTransactionsWithProofs,
+    SimulateTransaction,
+    GetTransactionWriteSets,
+    GetAccountResources,
+    GetAccountResource,
 }
 
 cfg_async_or_blocking! {
@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{JsonRpcVersion, Method};
+use diem_crypto::ed25519::Ed25519PublicKey;
 use diem_types::{
-    account_address::AccountAddress, event::EventKey, transaction::SignedTransaction,
+    account_address::AccountAddress,
+    event::EventKey,
+    transaction::{RawTransaction, SignedTransaction},
 };
+use move_core_types::language_storage::StructTag;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::AtomicU64;
 
@@ -29,6 +33,11 @@ pub enum MethodRequest {
     GetAccountStateWithProof(AccountAddress, Option<u64>, Option<u64>),
     GetTransactionsWithProofs(u64, u64, bool),
     GetEventsWithProofs(EventKey, u64, u64),
+    GetAccountTransactionsWithProofs(AccountAddress, u64, u64, bool),
+    SimulateTransaction(String, Option<Ed25519PublicKey>),
+    GetTransactionWriteSets(u64, u64),
+    GetAccountResources(AccountAddress, Option<u64>),
+    GetAccountResource(AccountAddress, String, Option<u64>),
 }
 
 impl MethodRequest {
@@ -113,6 +122,52 @@ impl MethodRequest {
         Self::GetEventsWithProofs(key, start_seq, limit)
     }
 
+    pub fn get_account_transactions_with_proofs(
+        address: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Self {
+        Self::GetAccountTransactionsWithProofs(address, start_seq, limit, include_events)
+    }
+
+    pub fn simulate_transaction(txn: &SignedTransaction) -> Result<Self, bcs::Error> {
+        let txn_payload = hex::encode(bcs::to_bytes(txn)?);
+        Ok(Self::SimulateTransaction(txn_payload, None))
+    }
+
+    pub fn simulate_raw_transaction(
+        txn: &RawTransaction,
+        public_key: Ed25519PublicKey,
+    ) -> Result<Self, bcs::Error> {
+        let txn_payload = hex::encode(bcs::to_bytes(txn)?);
+        Ok(Self::SimulateTransaction(txn_payload, Some(public_key)))
+    }
+
+    pub fn get_transaction_write_sets(start_version: u64, limit: u64) -> Self {
+        Self::GetTransactionWriteSets(start_version, limit)
+    }
+
+    pub fn get_account_resources(address: AccountAddress) -> Self {
+        Self::GetAccountResources(address, None)
+    }
+
+    pub fn get_account_resources_by_version(address: AccountAddress, version: u64) -> Self {
+        Self::GetAccountResources(address, Some(version))
+    }
+
+    pub fn get_account_resource(address: AccountAddress, struct_tag: &StructTag) -> Self {
+        Self::GetAccountResource(address, struct_tag.to_string(), None)
+    }
+
+    pub fn get_account_resource_by_version(
+        address: AccountAddress,
+        struct_tag: &StructTag,
+        version: u64,
+    ) -> Self {
+        Self::GetAccountResource(address, struct_tag.to_string(), Some(version))
+    }
+
     pub fn method(&self) -> Method {
         match self {
             MethodRequest::Submit(_) => Method::Submit,
@@ -128,6 +183,13 @@ impl MethodRequest {
             MethodRequest::GetAccountStateWithProof(_, _, _) => Method::GetAccountStateWithProof,
             MethodRequest::GetTransactionsWithProofs(_, _, _) => Method::GetTransactionsWithProofs,
             MethodRequest::GetEventsWithProofs(_, _, _) => Method::GetEventsWithProofs,
+            MethodRequest::GetAccountTransactionsWithProofs(_, _, _, _) => {
+                Method::GetAccountTransactionsWithProofs
+            }
+            MethodRequest::SimulateTransaction(..) => Method::SimulateTransaction,
+            MethodRequest::GetTransactionWriteSets(_, _) => Method::GetTransactionWriteSets,
+            MethodRequest::GetAccountResources(_, _) => Method::GetAccountResources,
+            MethodRequest::GetAccountResource(_, _, _) => Method::GetAccountResource,
         }
     }
 }
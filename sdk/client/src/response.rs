@@ -9,7 +9,10 @@ use crate::{
     },
     Error, State,
 };
-use diem_json_rpc_types::views::{EventWithProofView, TransactionsWithProofsView};
+use diem_json_rpc_types::views::{
+    AccountResourcesView, EventWithProofView, MoveResourceView, TransactionOutputView,
+    TransactionWithProofView, TransactionWriteSetView, TransactionsWithProofsView,
+};
 use serde_json::Value;
 
 #[derive(Debug)]
@@ -69,6 +72,11 @@ pub enum MethodResponse {
     GetAccountStateWithProof(AccountStateWithProofView),
     GetTransactionsWithProofs(Option<TransactionsWithProofsView>),
     GetEventsWithProofs(Vec<EventWithProofView>),
+    GetAccountTransactionsWithProofs(Vec<TransactionWithProofView>),
+    SimulateTransaction(TransactionOutputView),
+    GetTransactionWriteSets(Vec<TransactionWriteSetView>),
+    GetAccountResources(Option<AccountResourcesView>),
+    GetAccountResource(Option<MoveResourceView>),
 }
 
 impl MethodResponse {
@@ -101,6 +109,21 @@ impl MethodResponse {
             Method::GetEventsWithProofs => {
                 MethodResponse::GetEventsWithProofs(serde_json::from_value(json)?)
             }
+            Method::GetAccountTransactionsWithProofs => {
+                MethodResponse::GetAccountTransactionsWithProofs(serde_json::from_value(json)?)
+            }
+            Method::SimulateTransaction => {
+                MethodResponse::SimulateTransaction(serde_json::from_value(json)?)
+            }
+            Method::GetTransactionWriteSets => {
+                MethodResponse::GetTransactionWriteSets(serde_json::from_value(json)?)
+            }
+            Method::GetAccountResources => {
+                MethodResponse::GetAccountResources(serde_json::from_value(json)?)
+            }
+            Method::GetAccountResource => {
+                MethodResponse::GetAccountResource(serde_json::from_value(json)?)
+            }
         };
 
         Ok(response)
@@ -121,6 +144,13 @@ impl MethodResponse {
             MethodResponse::GetAccountStateWithProof(_) => Method::GetAccountStateWithProof,
             MethodResponse::GetTransactionsWithProofs(_) => Method::GetTransactionsWithProofs,
             MethodResponse::GetEventsWithProofs(_) => Method::GetEventsWithProofs,
+            MethodResponse::GetAccountTransactionsWithProofs(_) => {
+                Method::GetAccountTransactionsWithProofs
+            }
+            MethodResponse::SimulateTransaction(_) => Method::SimulateTransaction,
+            MethodResponse::GetTransactionWriteSets(_) => Method::GetTransactionWriteSets,
+            MethodResponse::GetAccountResources(_) => Method::GetAccountResources,
+            MethodResponse::GetAccountResource(_) => Method::GetAccountResource,
         }
     }
 
@@ -145,6 +175,16 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_get_metadata(self) -> Result<MetadataView, Error> {
+        match self {
+            MethodResponse::GetMetadata(metadata) => Ok(metadata),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetMetadata found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_account(self) -> Result<Option<AccountView>, Error> {
         match self {
             MethodResponse::GetAccount(account_view) => Ok(account_view),
@@ -165,6 +205,26 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_get_account_transaction(self) -> Result<Option<TransactionView>, Error> {
+        match self {
+            MethodResponse::GetAccountTransaction(tx) => Ok(tx),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetAccountTransaction found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_account_transactions(self) -> Result<Vec<TransactionView>, Error> {
+        match self {
+            MethodResponse::GetAccountTransactions(txs) => Ok(txs),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetAccountTransactions found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_events(self) -> Result<Vec<EventView>, Error> {
         match self {
             MethodResponse::GetEvents(events) => Ok(events),
@@ -194,4 +254,48 @@ impl MethodResponse {
             ))),
         }
     }
+
+    pub fn try_into_get_account_state_with_proof(self) -> Result<AccountStateWithProofView, Error> {
+        match self {
+            MethodResponse::GetAccountStateWithProof(account) => Ok(account),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetAccountStateWithProof found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_transactions_with_proofs(
+        self,
+    ) -> Result<Option<TransactionsWithProofsView>, Error> {
+        match self {
+            MethodResponse::GetTransactionsWithProofs(txs) => Ok(txs),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetTransactionsWithProofs found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_events_with_proofs(self) -> Result<Vec<EventWithProofView>, Error> {
+        match self {
+            MethodResponse::GetEventsWithProofs(events) => Ok(events),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetEventsWithProofs found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_account_transactions_with_proofs(
+        self,
+    ) -> Result<Vec<TransactionWithProofView>, Error> {
+        match self {
+            MethodResponse::GetAccountTransactionsWithProofs(txs) => Ok(txs),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetAccountTransactionsWithProofs found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
 }
@@ -0,0 +1,67 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, Response, Result, State};
+use diem_json_rpc_types::{
+    response::JsonRpcResponse,
+    stream::{StreamJsonRpcRequest, StreamMethodRequest},
+    Id,
+};
+use futures::{future, SinkExt, Stream, StreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::pin::Pin;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// The transactions or events pushed by the server for a subscription. The stream ends when the
+/// server closes the connection.
+pub type SubscriptionStream<T> = Pin<Box<dyn Stream<Item = Result<Response<T>>> + Send>>;
+
+/// Opens a WebSocket connection to the streaming API of the server at `url` (the JSON-RPC url) and
+/// sends the subscription `request`.
+pub(crate) async fn subscribe<T: DeserializeOwned + Send + 'static>(
+    url: &str,
+    request: StreamMethodRequest,
+) -> Result<SubscriptionStream<T>> {
+    let url = stream_url(url)?;
+    let (mut ws, _) = connect_async(url.as_str()).await.map_err(Error::request)?;
+
+    // There is a single subscription per connection, so any id works.
+    let request = StreamJsonRpcRequest::new(request, Id::Number(0));
+    let text = serde_json::to_string(&request).map_err(Error::request)?;
+    ws.send(Message::Text(text)).await.map_err(Error::request)?;
+
+    let stream = ws.filter_map(|message| {
+        future::ready(match message {
+            Ok(Message::Text(text)) => Some(parse_response(&text)),
+            Ok(_) => None,
+            Err(e) => Some(Err(Error::unknown(e))),
+        })
+    });
+    Ok(Box::pin(stream))
+}
+
+fn parse_response<T: DeserializeOwned>(text: &str) -> Result<Response<T>> {
+    let resp: JsonRpcResponse = serde_json::from_str(text).map_err(Error::decode)?;
+    if let Some(err) = resp.error {
+        return Err(Error::json_rpc(err));
+    }
+    let state = State::from_response(&resp);
+    let result = resp
+        .result
+        .ok_or_else(|| Error::rpc_response("missing result"))?;
+    let inner = serde_json::from_value(result).map_err(Error::decode)?;
+    Ok(Response::new(inner, state))
+}
+
+/// Returns the url of the streaming API, e.g. `ws://localhost:8080/v1/stream/ws` for
+/// `http://localhost:8080/v1`.
+fn stream_url(url: &str) -> Result<Url> {
+    let mut url = Url::parse(url).map_err(Error::request)?;
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme)
+        .map_err(|()| Error::request(format!("invalid url: {}", url)))?;
+    url.set_path("/v1/stream/ws");
+    Ok(url)
+}
+
@@ -4,38 +4,45 @@
 use crate::{
     client::Client,
     error::{Error, Result},
+    move_deserialize::{self, Event},
     request::MethodRequest,
     response::{MethodResponse, Response},
     state::State,
 };
 use diem_json_rpc_types::views::{
-    AccountView, CurrencyInfoView, EventView, TransactionListView, TransactionView,
+    AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
+    MetadataView, TransactionListView, TransactionView, TransactionWithProofView,
+    TransactionsWithProofsView,
 };
 use diem_types::{
     account_address::AccountAddress,
-    account_config::diem_root_address,
+    account_config::{diem_root_address, resources::dual_attestation::Limit},
     account_state::AccountState,
     account_state_blob::AccountStateWithProof,
+    chain_id::ChainId,
     contract_event::EventWithProof,
     epoch_change::EpochChangeProof,
     event::EventKey,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::AccumulatorConsistencyProof,
-    transaction::Version,
+    transaction::{TransactionListWithProof, TransactionWithProof, Version},
     trusted_state::TrustedState,
     waypoint::Waypoint,
 };
+use move_core_types::move_resource::{MoveResource, MoveStructType};
+use serde::de::DeserializeOwned;
 use std::{
-    collections::HashMap,
+    cmp::min,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     fmt::Debug,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
     sync::{Arc, RwLock},
 };
 
 // TODO(philiphayes): figure out retry strategy
-// TODO(philiphayes): real on-disk waypoint persistence
-// TODO(philiphayes): fill out rest of the methods
-// TODO(philiphayes): all clients should validate chain id (allow users to trust-on-first-use or pre-configure)
 // TODO(philiphayes): we could abstract the async client so VerifyingClient takes a dyn Trait?
 
 // TODO(philiphayes): we should really add a real StateProof type (not alias) that
@@ -65,12 +72,56 @@ impl<S: Storage> VerifyingClient<S> {
         })
     }
 
-    pub fn new_with_state(inner: Client, trusted_state: TrustedState, storage: S) -> Self {
-        let trusted_state_store = TrustedStateStore::new_with_state(trusted_state, storage);
-        Self {
+    /// Create a client which trusts `trusted_state`, or the state already in `storage` if it is
+    /// newer and was verified starting from the same waypoint. Fails if `storage` holds a state
+    /// verified from another waypoint, which may not even be on the same chain.
+    pub fn new_with_state(inner: Client, trusted_state: TrustedState, storage: S) -> Result<Self> {
+        let trusted_state_store = TrustedStateStore::new_with_state(trusted_state, storage)?;
+        Ok(Self {
             inner,
             trusted_state_store: Arc::new(RwLock::new(trusted_state_store)),
+        })
+    }
+
+    /// Create a client which trusts the state already in `storage` or, if there is none, the
+    /// genesis of the chain the remote node is on (trust-on-first-use). The trusted state is
+    /// then synced to the remote node's current version and persisted, so later clients using
+    /// the same storage only trust this chain.
+    pub async fn new_trust_on_first_use(inner: Client, storage: S) -> Result<Self> {
+        if storage.get(TRUSTED_STATE_KEY).is_ok() {
+            return Self::new(inner, storage);
         }
+
+        let (state_proof_view, state) = inner.get_state_proof(0).await?.into_parts();
+        let state_proof = StateProof::try_from(&state_proof_view).map_err(Error::decode)?;
+        verify_latest_li_matches_state(state_proof.0.ledger_info(), &state)?;
+
+        // The epoch change proof from version 0 starts with the genesis LedgerInfo.
+        let genesis_li = state_proof
+            .1
+            .ledger_info_with_sigs
+            .first()
+            .ok_or_else(|| Error::invalid_proof("missing genesis LedgerInfo in state proof"))?;
+        let trusted_state =
+            TrustedState::try_from(genesis_li.ledger_info()).map_err(Error::invalid_proof)?;
+
+        let client = Self::new_with_state(inner, trusted_state, storage)?;
+        client.verify_chain_id(state.chain_id)?;
+        client.verify_and_ratchet(&state_proof)?;
+        client.trusted_state_store.write().unwrap().persist()?;
+        Ok(client)
+    }
+
+    /// Only accept responses from the chain with the given [`ChainId`]. Fails if we already
+    /// trust another chain.
+    pub fn with_chain_id(self, chain_id: ChainId) -> Result<Self> {
+        self.verify_chain_id(chain_id.id())?;
+        Ok(self)
+    }
+
+    /// Get the [`ChainId`] of the chain we trust, if we received any response yet.
+    pub fn chain_id(&self) -> Result<Option<ChainId>> {
+        self.trusted_state_store.read().unwrap().chain_id()
     }
 
     /// Get a snapshot of our current trusted ledger [`Version`].
@@ -112,7 +163,9 @@ impl<S: Storage> VerifyingClient<S> {
             .into_parts();
         let state_proof = StateProof::try_from(&state_proof_view).map_err(Error::decode)?;
 
-        // check the response metadata matches the state proof
+        // check the response comes from the chain we trust and its metadata matches the
+        // state proof
+        self.verify_chain_id(state.chain_id)?;
         verify_latest_li_matches_state(state_proof.0.ledger_info(), &state)?;
 
         // try to ratchet our trusted state using the state proof
@@ -121,6 +174,15 @@ impl<S: Storage> VerifyingClient<S> {
         Ok(state_proof.1.more)
     }
 
+    /// Check that a response comes from the chain we trust. The first chain id we see is
+    /// trusted from then on if none was configured.
+    fn verify_chain_id(&self, chain_id: u8) -> Result<()> {
+        self.trusted_state_store
+            .write()
+            .unwrap()
+            .verify_chain_id(chain_id)
+    }
+
     /// Verify and ratchet forward our trusted state using a state proof.
     pub fn verify_and_ratchet(&self, state_proof: &StateProof) -> Result<()> {
         let (latest_li, epoch_change_proof, _) = state_proof;
@@ -144,6 +206,19 @@ impl<S: Storage> VerifyingClient<S> {
         Ok(())
     }
 
+    /// Only the metadata at the latest version can be verified.
+    pub async fn get_metadata_by_version(&self, version: u64) -> Result<Response<MetadataView>> {
+        self.request(MethodRequest::get_metadata_by_version(version))
+            .await?
+            .and_then(MethodResponse::try_into_get_metadata)
+    }
+
+    pub async fn get_metadata(&self) -> Result<Response<MetadataView>> {
+        self.request(MethodRequest::get_metadata())
+            .await?
+            .and_then(MethodResponse::try_into_get_metadata)
+    }
+
     pub async fn get_account(
         &self,
         address: AccountAddress,
@@ -178,6 +253,38 @@ impl<S: Storage> VerifyingClient<S> {
         .and_then(MethodResponse::try_into_get_transactions)
     }
 
+    pub async fn get_account_transaction(
+        &self,
+        address: AccountAddress,
+        seq: u64,
+        include_events: bool,
+    ) -> Result<Response<Option<TransactionView>>> {
+        self.request(MethodRequest::get_account_transaction(
+            address,
+            seq,
+            include_events,
+        ))
+        .await?
+        .and_then(MethodResponse::try_into_get_account_transaction)
+    }
+
+    pub async fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Response<Vec<TransactionView>>> {
+        self.request(MethodRequest::get_account_transactions(
+            address,
+            start_seq,
+            limit,
+            include_events,
+        ))
+        .await?
+        .and_then(MethodResponse::try_into_get_account_transactions)
+    }
+
     pub async fn get_events(
         &self,
         key: EventKey,
@@ -201,6 +308,102 @@ impl<S: Storage> VerifyingClient<S> {
             .and_then(MethodResponse::try_into_get_network_status)
     }
 
+    /// Only proofs against the latest ledger version can be verified, so `to_version` must be
+    /// `None` or the latest version.
+    pub async fn get_account_state_with_proof(
+        &self,
+        address: AccountAddress,
+        from_version: Option<u64>,
+        to_version: Option<u64>,
+    ) -> Result<Response<AccountStateWithProofView>> {
+        self.request(MethodRequest::get_account_state_with_proof(
+            address,
+            from_version,
+            to_version,
+        ))
+        .await?
+        .and_then(MethodResponse::try_into_get_account_state_with_proof)
+    }
+
+    pub async fn get_transactions_with_proofs(
+        &self,
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Response<Option<TransactionsWithProofsView>>> {
+        self.request(MethodRequest::get_transactions_with_proofs(
+            start_version,
+            limit,
+            include_events,
+        ))
+        .await?
+        .and_then(MethodResponse::try_into_get_transactions_with_proofs)
+    }
+
+    pub async fn get_events_with_proofs(
+        &self,
+        key: EventKey,
+        start_seq: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventWithProofView>>> {
+        self.request(MethodRequest::get_events_with_proofs(key, start_seq, limit))
+            .await?
+            .and_then(MethodResponse::try_into_get_events_with_proofs)
+    }
+
+    pub async fn get_account_transactions_with_proofs(
+        &self,
+        address: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Response<Vec<TransactionWithProofView>>> {
+        self.request(MethodRequest::get_account_transactions_with_proofs(
+            address,
+            start_seq,
+            limit,
+            include_events,
+        ))
+        .await?
+        .and_then(MethodResponse::try_into_get_account_transactions_with_proofs)
+    }
+
+    /// Return the events of type `T` that have been emitted to `event_key` since `start_seq`,
+    /// with a max of `limit` results
+    /// Returns an empty vector if there are no such events
+    /// The type `T` must match the event types associated with `event_key`
+    pub async fn get_deserialized_events<T: MoveStructType + DeserializeOwned>(
+        &self,
+        event_key: &EventKey,
+        start_seq: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<Event<T>>>> {
+        let (events, state) = self
+            .get_events_with_proofs(*event_key, start_seq, limit)
+            .await?
+            .into_parts();
+        Ok(Response::new(
+            move_deserialize::get_events::<T>(events)?,
+            state,
+        ))
+    }
+
+    /// Deserialize and return the resource value of type `T` stored under `address`
+    /// Returns None if there is no such value
+    pub async fn get_deserialized_resource<T: MoveResource>(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Option<T>>> {
+        let (account, state) = self
+            .get_account_state_with_proof(address, None, None)
+            .await?
+            .into_parts();
+        Ok(Response::new(
+            move_deserialize::get_resource(account)?,
+            state,
+        ))
+    }
+
     /// Send a single request via `VerifyingClient::batch`.
     pub async fn request(&self, request: MethodRequest) -> Result<Response<MethodResponse>> {
         let mut responses = self.batch(vec![request]).await?.into_iter();
@@ -248,7 +451,9 @@ impl<S: Storage> VerifyingClient<S> {
         let state_proof_view = state_proof_response.try_into_get_state_proof()?;
         let state_proof = StateProof::try_from(&state_proof_view).map_err(Error::decode)?;
 
-        // check the response metadata matches the state proof
+        // check the responses come from the chain we trust and their metadata matches the
+        // state proof
+        self.verify_chain_id(state.chain_id)?;
         verify_latest_li_matches_state(state_proof.0.ledger_info(), &state)?;
 
         // try to ratchet our trusted state using the state proof
@@ -360,7 +565,6 @@ struct RequestContext<'a> {
     #[allow(dead_code)]
     start_version: Version,
 
-    #[allow(dead_code)]
     state: &'a State,
 
     state_proof: &'a StateProof,
@@ -433,16 +637,51 @@ impl VerifyingRequest {
 impl From<MethodRequest> for VerifyingRequest {
     fn from(request: MethodRequest) -> Self {
         match request {
+            MethodRequest::GetMetadata((version,)) => verifying_get_metadata(version),
             MethodRequest::GetAccount(address, version) => verifying_get_account(address, version),
             MethodRequest::GetTransactions(start_version, limit, include_events) => {
                 verifying_get_transactions(start_version, limit, include_events)
             }
+            MethodRequest::GetAccountTransaction(address, seq, include_events) => {
+                verifying_get_account_transaction(address, seq, include_events)
+            }
+            MethodRequest::GetAccountTransactions(address, start_seq, limit, include_events) => {
+                verifying_get_account_transactions(address, start_seq, limit, include_events)
+            }
             MethodRequest::GetEvents(key, start_seq, limit) => {
                 verifying_get_events(key, start_seq, limit)
             }
             MethodRequest::GetCurrencies([]) => verifying_get_currencies(),
             MethodRequest::GetNetworkStatus([]) => verifying_get_network_status(),
-            _ => todo!(),
+            MethodRequest::GetAccountStateWithProof(address, version, ledger_version) => {
+                verifying_get_account_state_with_proof(address, version, ledger_version)
+            }
+            MethodRequest::GetTransactionsWithProofs(start_version, limit, include_events) => {
+                verifying_get_transactions_with_proofs(start_version, limit, include_events)
+            }
+            MethodRequest::GetEventsWithProofs(key, start_seq, limit) => {
+                verifying_get_events_with_proofs(key, start_seq, limit)
+            }
+            MethodRequest::GetAccountTransactionsWithProofs(
+                address,
+                start_seq,
+                limit,
+                include_events,
+            ) => verifying_get_account_transactions_with_proofs(
+                address,
+                start_seq,
+                limit,
+                include_events,
+            ),
+            // These responses aren't (yet) provable from the ledger state.
+            request @ MethodRequest::Submit(_)
+            | request @ MethodRequest::GetStateProof(_)
+            | request @ MethodRequest::SimulateTransaction(..)
+            | request @ MethodRequest::GetTransactionWriteSets(_, _)
+            | request @ MethodRequest::GetAccountResources(_, _)
+            | request @ MethodRequest::GetAccountResource(_, _, _) => {
+                verifying_unsupported(request)
+            }
         }
     }
 }
@@ -461,6 +700,81 @@ impl From<MethodRequest> for VerifyingRequest {
 // would allow the from(MethodRequest) above to call a method on the enum inner
 // instead of these ad-hoc methods i think
 
+fn verifying_get_metadata(version: Option<Version>) -> VerifyingRequest {
+    let request = MethodRequest::GetMetadata((version,));
+    let subrequests = vec![MethodRequest::GetAccountStateWithProof(
+        diem_root_address(),
+        None,
+        None,
+    )];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let diem_root = match subresponses {
+            [MethodResponse::GetAccountStateWithProof(ref diem_root)] => diem_root,
+            subresponses => {
+                return Err(Error::rpc_response(format!(
+                    "expected [GetAccountStateWithProof] subresponses, received: {:?}",
+                    subresponses,
+                )))
+            }
+        };
+
+        let version = match ctxt.request {
+            MethodRequest::GetMetadata((version,)) => *version,
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        let ledger_version = latest_li.version();
+
+        // We can only verify the accumulator root hash and timestamp of the latest version.
+        if let Some(version) = version {
+            if version != ledger_version {
+                return Err(Error::unknown(format!(
+                    "cannot verify metadata at version {}, only at the latest version {}",
+                    version, ledger_version,
+                )));
+            }
+        }
+
+        let diem_root_with_proof =
+            AccountStateWithProof::try_from(diem_root).map_err(Error::decode)?;
+        diem_root_with_proof
+            .verify(latest_li, ledger_version, diem_root_address())
+            .map_err(Error::invalid_proof)?;
+
+        let mut metadata_view = MetadataView {
+            version: ledger_version,
+            accumulator_root_hash: latest_li.transaction_accumulator_hash(),
+            timestamp: latest_li.timestamp_usecs(),
+            chain_id: ctxt.state.chain_id,
+            script_hash_allow_list: None,
+            module_publishing_allowed: None,
+            diem_version: None,
+            dual_attestation_limit: None,
+        };
+
+        // Pull out the on-chain configs of the DiemRoot account, if it exists.
+        if let Some(diem_root_blob) = diem_root_with_proof.blob {
+            let diem_root = AccountState::try_from(&diem_root_blob).map_err(Error::decode)?;
+            if let Some(vm_publishing_option) = diem_root
+                .get_vm_publishing_option()
+                .map_err(Error::decode)?
+            {
+                metadata_view.script_hash_allow_list = Some(vm_publishing_option.script_allow_list);
+                metadata_view.module_publishing_allowed = Some(vm_publishing_option.is_open_module);
+            }
+            if let Some(diem_version) = diem_root.get_diem_version().map_err(Error::decode)? {
+                metadata_view.diem_version = Some(diem_version.major);
+            }
+            if let Some(limit) = diem_root.get_resource::<Limit>().map_err(Error::decode)? {
+                metadata_view.dual_attestation_limit = Some(limit.micro_xdx_limit);
+            }
+        }
+
+        Ok(MethodResponse::GetMetadata(metadata_view))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
 fn verifying_get_account(address: AccountAddress, version: Option<Version>) -> VerifyingRequest {
     let request = MethodRequest::GetAccount(address, version);
     let subrequests = vec![MethodRequest::GetAccountStateWithProof(
@@ -506,6 +820,56 @@ fn verifying_get_account(address: AccountAddress, version: Option<Version>) -> V
     VerifyingRequest::new(request, subrequests, callback)
 }
 
+fn verifying_get_account_state_with_proof(
+    address: AccountAddress,
+    version: Option<Version>,
+    ledger_version: Option<Version>,
+) -> VerifyingRequest {
+    let request = MethodRequest::GetAccountStateWithProof(address, version, ledger_version);
+    // We can only verify proofs against the latest ledger info.
+    let subrequests = vec![MethodRequest::GetAccountStateWithProof(
+        address, version, None,
+    )];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let account = match subresponses {
+            [MethodResponse::GetAccountStateWithProof(ref account)] => account,
+            subresponses => {
+                return Err(Error::rpc_response(format!(
+                    "expected [GetAccountStateWithProof] subresponses, received: {:?}",
+                    subresponses,
+                )))
+            }
+        };
+
+        let (address, version, ledger_version) = match ctxt.request {
+            MethodRequest::GetAccountStateWithProof(address, version, ledger_version) => {
+                (*address, *version, *ledger_version)
+            }
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        if let Some(ledger_version) = ledger_version {
+            if ledger_version != latest_li.version() {
+                return Err(Error::unknown(format!(
+                    "cannot verify proofs against ledger version {}, only against the latest \
+                     version {}",
+                    ledger_version,
+                    latest_li.version(),
+                )));
+            }
+        }
+        let version = version.unwrap_or_else(|| latest_li.version());
+
+        AccountStateWithProof::try_from(account)
+            .map_err(Error::decode)?
+            .verify(latest_li, version, address)
+            .map_err(Error::invalid_proof)?;
+
+        Ok(MethodResponse::GetAccountStateWithProof(account.clone()))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
 fn verifying_get_transactions(
     start_version: Version,
     limit: u64,
@@ -536,46 +900,334 @@ fn verifying_get_transactions(
             request => panic!("programmer error: unexpected request: {:?}", request),
         };
 
-        // We don't guarantee that our response contains _all_ possible transactions
-        // in the range (start_version..start_version + min(limit, ledger_version - start_version + 1)).
-        // Instead, the remote server may return any prefix in the above range;
-        // however, our verification here _will_ verify the prefix.
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        let txn_list_with_proof = match verify_transactions(
+            latest_li,
+            start_version,
+            include_events,
+            maybe_txs_with_proofs_view.as_ref(),
+        )? {
+            Some(txn_list_with_proof) => txn_list_with_proof,
+            None => return Ok(MethodResponse::GetTransactions(Vec::new())),
+        };
+
+        // Project into a list of TransactionView's.
+        let txn_list_view =
+            TransactionListView::try_from(txn_list_with_proof).map_err(Error::decode)?;
+
+        Ok(MethodResponse::GetTransactions(txn_list_view.0))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
+fn verifying_get_transactions_with_proofs(
+    start_version: Version,
+    limit: u64,
+    include_events: bool,
+) -> VerifyingRequest {
+    let request = MethodRequest::GetTransactionsWithProofs(start_version, limit, include_events);
+    let subrequests = vec![request.clone()];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let maybe_txs_with_proofs_view = match subresponses {
+            [MethodResponse::GetTransactionsWithProofs(ref txs)] => txs,
+            subresponses => {
+                return Err(Error::rpc_response(format!(
+                    "expected [GetTransactionsWithProofs] subresponses, received: {:?}",
+                    subresponses,
+                )))
+            }
+        };
+
+        let (start_version, include_events) = match ctxt.request {
+            MethodRequest::GetTransactionsWithProofs(start_version, _limit, include_events) => {
+                (*start_version, *include_events)
+            }
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
+
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        verify_transactions(
+            latest_li,
+            start_version,
+            include_events,
+            maybe_txs_with_proofs_view.as_ref(),
+        )?;
+
+        Ok(MethodResponse::GetTransactionsWithProofs(
+            maybe_txs_with_proofs_view.clone(),
+        ))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
+/// Verify a `get_transactions_with_proofs` response against the latest ledger info. Returns
+/// `None` if there are no transactions from `start_version`.
+fn verify_transactions(
+    latest_li: &LedgerInfo,
+    start_version: Version,
+    include_events: bool,
+    maybe_txs_with_proofs_view: Option<&TransactionsWithProofsView>,
+) -> Result<Option<TransactionListWithProof>> {
+    // We don't guarantee that our response contains _all_ possible transactions
+    // in the range (start_version..start_version + min(limit, ledger_version - start_version + 1)).
+    // Instead, the remote server may return any prefix in the above range;
+    // however, our verification here _will_ verify the prefix.
+
+    let txs_with_proofs_view = if let Some(txs_with_proofs_view) = maybe_txs_with_proofs_view {
+        txs_with_proofs_view
+    } else {
+        return Ok(None);
+    };
+
+    // Check that the presence of events in the response matches our expectation.
+    let has_events = txs_with_proofs_view.serialized_events.is_some();
+    if include_events != has_events {
+        return Err(Error::rpc_response(format!(
+            "expected events: {}, received events: {}",
+            include_events, has_events
+        )));
+    }
+
+    // Deserialize the diem-types from the json-rpc-types view.
+    let txn_list_with_proof = txs_with_proofs_view
+        .try_into_txn_list_with_proof(start_version)
+        .map_err(Error::decode)?;
+
+    // Verify the proofs
+    txn_list_with_proof
+        .verify(latest_li, Some(start_version))
+        .map_err(Error::invalid_proof)?;
 
-        let txs_with_proofs_view = if let Some(txs_with_proofs_view) = maybe_txs_with_proofs_view {
-            txs_with_proofs_view
-        } else {
-            return Ok(MethodResponse::GetTransactions(Vec::new()));
+    Ok(Some(txn_list_with_proof))
+}
+
+fn verifying_get_account_transaction(
+    address: AccountAddress,
+    seq: u64,
+    include_events: bool,
+) -> VerifyingRequest {
+    let request = MethodRequest::GetAccountTransaction(address, seq, include_events);
+    let subrequests = vec![
+        MethodRequest::GetAccountTransactionsWithProofs(address, seq, 1, include_events),
+        MethodRequest::GetAccountStateWithProof(address, None, None),
+    ];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let (txn_views, account) = account_transactions_subresponses(subresponses)?;
+
+        let (address, seq, include_events) = match ctxt.request {
+            MethodRequest::GetAccountTransaction(address, seq, include_events) => {
+                (*address, *seq, *include_events)
+            }
+            request => panic!("programmer error: unexpected request: {:?}", request),
         };
 
-        // Check that the presence of events in the response matches our expectation.
-        let has_events = txs_with_proofs_view.serialized_events.is_some();
-        if include_events != has_events {
-            return Err(Error::rpc_response(format!(
-                "expected events: {}, received events: {}",
-                include_events, has_events
-            )));
-        }
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        let maybe_txn_view = verify_account_transactions(
+            latest_li,
+            address,
+            seq,
+            1,
+            include_events,
+            account,
+            txn_views,
+        )?
+        .unwrap_or_default()
+        .into_iter()
+        .next()
+        .map(project_account_transaction)
+        .transpose()?;
+
+        Ok(MethodResponse::GetAccountTransaction(maybe_txn_view))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
 
-        // Deserialize the diem-types from the json-rpc-types view.
-        let txn_list_with_proof = txs_with_proofs_view
-            .try_into_txn_list_with_proof(start_version)
-            .map_err(Error::decode)?;
+fn verifying_get_account_transactions(
+    address: AccountAddress,
+    start_seq: u64,
+    limit: u64,
+    include_events: bool,
+) -> VerifyingRequest {
+    let request = MethodRequest::GetAccountTransactions(address, start_seq, limit, include_events);
+    let subrequests = vec![
+        MethodRequest::GetAccountTransactionsWithProofs(address, start_seq, limit, include_events),
+        MethodRequest::GetAccountStateWithProof(address, None, None),
+    ];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let (txn_views, account) = account_transactions_subresponses(subresponses)?;
+
+        let (address, start_seq, limit, include_events) = match ctxt.request {
+            MethodRequest::GetAccountTransactions(address, start_seq, limit, include_events) => {
+                (*address, *start_seq, *limit, *include_events)
+            }
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
 
-        // Verify the proofs
         let latest_li = ctxt.state_proof.0.ledger_info();
-        txn_list_with_proof
-            .verify(latest_li, Some(start_version))
-            .map_err(Error::invalid_proof)?;
+        let txns = verify_account_transactions(
+            latest_li,
+            address,
+            start_seq,
+            limit,
+            include_events,
+            account,
+            txn_views,
+        )?
+        .ok_or_else(|| Error::unknown(format!("could not find account by address {}", address)))?;
 
-        // Project into a list of TransactionView's.
-        let txn_list_view =
-            TransactionListView::try_from(txn_list_with_proof).map_err(Error::decode)?;
+        let txn_views = txns
+            .into_iter()
+            .map(project_account_transaction)
+            .collect::<Result<Vec<_>>>()?;
 
-        Ok(MethodResponse::GetTransactions(txn_list_view.0))
+        Ok(MethodResponse::GetAccountTransactions(txn_views))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
+fn verifying_get_account_transactions_with_proofs(
+    address: AccountAddress,
+    start_seq: u64,
+    limit: u64,
+    include_events: bool,
+) -> VerifyingRequest {
+    let request =
+        MethodRequest::GetAccountTransactionsWithProofs(address, start_seq, limit, include_events);
+    let subrequests = vec![
+        request.clone(),
+        MethodRequest::GetAccountStateWithProof(address, None, None),
+    ];
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let (txn_views, account) = account_transactions_subresponses(subresponses)?;
+
+        let (address, start_seq, limit, include_events) = match ctxt.request {
+            MethodRequest::GetAccountTransactionsWithProofs(
+                address,
+                start_seq,
+                limit,
+                include_events,
+            ) => (*address, *start_seq, *limit, *include_events),
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
+
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        verify_account_transactions(
+            latest_li,
+            address,
+            start_seq,
+            limit,
+            include_events,
+            account,
+            txn_views,
+        )?;
+
+        Ok(MethodResponse::GetAccountTransactionsWithProofs(
+            txn_views.to_vec(),
+        ))
     };
     VerifyingRequest::new(request, subrequests, callback)
 }
 
+fn account_transactions_subresponses(
+    subresponses: &[MethodResponse],
+) -> Result<(&[TransactionWithProofView], &AccountStateWithProofView)> {
+    if let [txns, account] = subresponses {
+        if let (
+            MethodResponse::GetAccountTransactionsWithProofs(txns),
+            MethodResponse::GetAccountStateWithProof(account),
+        ) = (txns, account)
+        {
+            return Ok((txns, account));
+        }
+    }
+    Err(Error::rpc_response(format!(
+        "expected [GetAccountTransactionsWithProofs, GetAccountStateWithProof] subresponses, \
+         received: {:?}",
+        subresponses,
+    )))
+}
+
+/// Verify the transactions sent by `address` from `start_seq` against the latest ledger info.
+/// Since the account state proves the account sequence number, we can also check that the
+/// remote didn't omit any transaction. Returns `None` if the account doesn't exist.
+fn verify_account_transactions(
+    latest_li: &LedgerInfo,
+    address: AccountAddress,
+    start_seq: u64,
+    limit: u64,
+    include_events: bool,
+    account: &AccountStateWithProofView,
+    txn_views: &[TransactionWithProofView],
+) -> Result<Option<Vec<TransactionWithProof>>> {
+    let account_state_with_proof =
+        AccountStateWithProof::try_from(account).map_err(Error::decode)?;
+    account_state_with_proof
+        .verify(latest_li, latest_li.version(), address)
+        .map_err(Error::invalid_proof)?;
+
+    let account_blob = match account_state_with_proof.blob {
+        Some(account_blob) => account_blob,
+        None if txn_views.is_empty() => return Ok(None),
+        None => {
+            return Err(Error::rpc_response(format!(
+                "received {} transactions from account {} which doesn't exist",
+                txn_views.len(),
+                address,
+            )))
+        }
+    };
+    let account_seq = AccountState::try_from(&account_blob)
+        .and_then(|account_state| account_state.get_account_resource())
+        .map_err(Error::decode)?
+        .ok_or_else(|| Error::unknown(format!("missing account resource for {}", address)))?
+        .sequence_number();
+
+    let num_expected = min(limit, account_seq.saturating_sub(start_seq));
+    let num_received = txn_views.len() as u64;
+    if num_received != num_expected {
+        return Err(Error::rpc_response(format!(
+            "expected {} transactions, received {} transactions",
+            num_expected, num_received,
+        )));
+    }
+
+    txn_views
+        .iter()
+        .enumerate()
+        .map(|(offset, txn_view)| {
+            // Deserialize the diem-core type from the json-rpc view type.
+            let txn = TransactionWithProof::try_from(txn_view).map_err(Error::decode)?;
+
+            // Check that the presence of events in the response matches our expectation.
+            let has_events = txn.events.is_some();
+            if include_events != has_events {
+                return Err(Error::rpc_response(format!(
+                    "expected events: {}, received events: {}",
+                    include_events, has_events
+                )));
+            }
+
+            // Once verified, we're guaranteed that this transaction was sent by `address`
+            // with the given sequence number and committed at `txn.version`.
+            txn.verify_user_txn(latest_li, txn.version, address, start_seq + offset as u64)
+                .map_err(Error::invalid_proof)?;
+
+            Ok(txn)
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+fn project_account_transaction(txn: TransactionWithProof) -> Result<TransactionView> {
+    TransactionView::try_from_tx_and_events(
+        txn.version,
+        txn.transaction,
+        txn.proof.transaction_info,
+        txn.events.unwrap_or_default(),
+    )
+    .map_err(Error::decode)
+}
+
 fn verifying_get_events(key: EventKey, start_seq: u64, limit: u64) -> VerifyingRequest {
     let request = MethodRequest::GetEvents(key, start_seq, limit);
     let subrequests = vec![MethodRequest::GetEventsWithProofs(key, start_seq, limit)];
@@ -596,45 +1248,13 @@ fn verifying_get_events(key: EventKey, start_seq: u64, limit: u64) -> VerifyingR
             request => panic!("programmer error: unexpected request: {:?}", request),
         };
 
-        // Make sure we didn't get more than we requested. Note that remote can
-        // always return a shorter prefix than is on-chain and we don't consider
-        // that an invalid response.
-        let num_received = event_with_proof_views.len() as u64;
-        if num_received > limit {
-            return Err(Error::rpc_response(format!(
-                "more events than limit: limit {} events, received {} events",
-                limit, num_received,
-            )));
-        }
-
         let latest_li = ctxt.state_proof.0.ledger_info();
-        let event_views = event_with_proof_views
-            .iter()
-            .enumerate()
-            .map(|(offset, event_with_proof_view)| {
-                // Deserialize the diem-core type from the json-rpc view type.
-                let event_with_proof =
-                    EventWithProof::try_from(event_with_proof_view).map_err(Error::decode)?;
-
-                // Actually verify the proof. Once verified, we should be guaranteed
-                // that this event exists on-chain in the `key` event stream with
-                // the given sequence number and transaction version.
-                let txn_version = event_with_proof.transaction_version;
-                event_with_proof
-                    .verify(
-                        latest_li,
-                        key,
-                        start_seq + offset as u64,
-                        txn_version,
-                        event_with_proof.event_index,
-                    )
-                    .map_err(Error::invalid_proof)?;
-
+        let event_views = verify_events(latest_li, key, start_seq, limit, event_with_proof_views)?
+            .into_iter()
+            .map(|event_with_proof| {
                 // Project into the json-rpc type
-                let event_view = EventView::try_from((txn_version, event_with_proof.event))
-                    .map_err(Error::decode)?;
-
-                Ok(event_view)
+                EventView::try_from((event_with_proof.transaction_version, event_with_proof.event))
+                    .map_err(Error::decode)
             })
             .collect::<Result<Vec<_>>>()?;
 
@@ -643,6 +1263,81 @@ fn verifying_get_events(key: EventKey, start_seq: u64, limit: u64) -> VerifyingR
     VerifyingRequest::new(request, subrequests, callback)
 }
 
+fn verifying_get_events_with_proofs(key: EventKey, start_seq: u64, limit: u64) -> VerifyingRequest {
+    let request = MethodRequest::GetEventsWithProofs(key, start_seq, limit);
+    let subrequests = vec![request.clone()];
+
+    let callback: RequestCallback = |ctxt, subresponses| {
+        let event_with_proof_views = match subresponses {
+            [MethodResponse::GetEventsWithProofs(ref inner)] => inner,
+            subresponses => {
+                return Err(Error::rpc_response(format!(
+                    "expected [GetEventsWithProofs] subresponses, received: {:?}",
+                    subresponses,
+                )))
+            }
+        };
+
+        let (key, start_seq, limit) = match ctxt.request {
+            MethodRequest::GetEventsWithProofs(key, start_seq, limit) => (key, *start_seq, *limit),
+            request => panic!("programmer error: unexpected request: {:?}", request),
+        };
+
+        let latest_li = ctxt.state_proof.0.ledger_info();
+        verify_events(latest_li, key, start_seq, limit, event_with_proof_views)?;
+
+        Ok(MethodResponse::GetEventsWithProofs(
+            event_with_proof_views.clone(),
+        ))
+    };
+    VerifyingRequest::new(request, subrequests, callback)
+}
+
+/// Verify the events of the `key` event stream from `start_seq` against the latest ledger info.
+fn verify_events(
+    latest_li: &LedgerInfo,
+    key: &EventKey,
+    start_seq: u64,
+    limit: u64,
+    event_with_proof_views: &[EventWithProofView],
+) -> Result<Vec<EventWithProof>> {
+    // Make sure we didn't get more than we requested. Note that remote can
+    // always return a shorter prefix than is on-chain and we don't consider
+    // that an invalid response.
+    let num_received = event_with_proof_views.len() as u64;
+    if num_received > limit {
+        return Err(Error::rpc_response(format!(
+            "more events than limit: limit {} events, received {} events",
+            limit, num_received,
+        )));
+    }
+
+    event_with_proof_views
+        .iter()
+        .enumerate()
+        .map(|(offset, event_with_proof_view)| {
+            // Deserialize the diem-core type from the json-rpc view type.
+            let event_with_proof =
+                EventWithProof::try_from(event_with_proof_view).map_err(Error::decode)?;
+
+            // Actually verify the proof. Once verified, we should be guaranteed
+            // that this event exists on-chain in the `key` event stream with
+            // the given sequence number and transaction version.
+            event_with_proof
+                .verify(
+                    latest_li,
+                    key,
+                    start_seq + offset as u64,
+                    event_with_proof.transaction_version,
+                    event_with_proof.event_index,
+                )
+                .map_err(Error::invalid_proof)?;
+
+            Ok(event_with_proof)
+        })
+        .collect()
+}
+
 fn verifying_get_currencies() -> VerifyingRequest {
     let request = MethodRequest::GetCurrencies([]);
     let subrequests = vec![MethodRequest::GetAccountStateWithProof(
@@ -705,10 +1400,21 @@ fn verifying_get_network_status() -> VerifyingRequest {
     VerifyingRequest::new(request, subrequests, callback)
 }
 
+fn verifying_unsupported(request: MethodRequest) -> VerifyingRequest {
+    let callback: RequestCallback = |ctxt, _subresponses| {
+        Err(Error::unknown(format!(
+            "cannot verify {:?} responses",
+            ctxt.request.method(),
+        )))
+    };
+    VerifyingRequest::new(request, Vec::new(), callback)
+}
+
 mod private {
     pub trait Sealed {}
 
     impl Sealed for super::InMemoryStorage {}
+    impl Sealed for super::FileStorage {}
 }
 
 // TODO(philiphayes): unseal `Storage` trait once verifying client stabilizes.
@@ -744,11 +1450,63 @@ impl Storage for InMemoryStorage {
     }
 }
 
+/// A [`Storage`] backed by a file, so that the trusted state survives restarts.
+#[derive(Debug)]
+pub struct FileStorage {
+    path: PathBuf,
+    data: BTreeMap<String, Vec<u8>>,
+}
+
+impl FileStorage {
+    /// Load the storage from the file at `path`, which is created on the first write if it
+    /// doesn't exist.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let data = match fs::read(&path) {
+            Ok(bytes) => bcs::from_bytes(&bytes).map_err(Error::decode)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(Error::unknown(err)),
+        };
+        Ok(Self { path, data })
+    }
+
+    /// Atomically replace the file contents: the data is written to a temporary file which is
+    /// then renamed over the file, so a crash never leaves a partially written file behind.
+    fn persist(&self) -> Result<()> {
+        let bytes = bcs::to_bytes(&self.data).map_err(Error::decode)?;
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+
+        let mut file = fs::File::create(&tmp_path).map_err(Error::unknown)?;
+        file.write_all(&bytes).map_err(Error::unknown)?;
+        file.sync_all().map_err(Error::unknown)?;
+        fs::rename(&tmp_path, &self.path).map_err(Error::unknown)
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        self.data
+            .get(key)
+            .map(Clone::clone)
+            .ok_or_else(|| Error::unknown("key not set"))
+    }
+
+    fn set(&mut self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.data.insert(key.to_owned(), value);
+        self.persist()
+    }
+}
+
 pub const TRUSTED_STATE_KEY: &str = "trusted_state";
+pub const ROOT_WAYPOINT_KEY: &str = "root_waypoint";
+pub const CHAIN_ID_KEY: &str = "chain_id";
 
 #[derive(Debug)]
 struct TrustedStateStore<S> {
     trusted_state: TrustedState,
+    /// The waypoint the trusted state was verified from, if known.
+    root_waypoint: Option<Waypoint>,
     storage: S,
 }
 
@@ -757,32 +1515,49 @@ impl<S: Storage> TrustedStateStore<S> {
         let trusted_state = storage
             .get(TRUSTED_STATE_KEY)
             .and_then(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode))?;
+        let root_waypoint = Self::stored_root_waypoint(&storage)?;
 
         Ok(Self {
             trusted_state,
+            root_waypoint,
             storage,
         })
     }
 
-    fn new_with_state(trusted_state: TrustedState, storage: S) -> Self {
-        let maybe_stored_state: Result<TrustedState> = storage
-            .get(TRUSTED_STATE_KEY)
-            .and_then(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode));
-
-        let trusted_state = if let Ok(stored_state) = maybe_stored_state {
-            if trusted_state.version() > stored_state.version() {
-                trusted_state
-            } else {
-                stored_state
+    fn new_with_state(trusted_state: TrustedState, storage: S) -> Result<Self> {
+        let root_waypoint = trusted_state.waypoint();
+        let trusted_state = match storage.get(TRUSTED_STATE_KEY) {
+            Ok(bytes) => {
+                let stored_state: TrustedState = bcs::from_bytes(&bytes).map_err(Error::decode)?;
+                if stored_state.version() <= trusted_state.version() {
+                    trusted_state
+                } else if Self::stored_root_waypoint(&storage)? == Some(root_waypoint) {
+                    // The newer stored state was verified starting from this waypoint.
+                    stored_state
+                } else {
+                    return Err(Error::invalid_proof(format!(
+                        "trusted state in storage at version {} was not verified from waypoint {}",
+                        stored_state.version(),
+                        root_waypoint,
+                    )));
+                }
             }
-        } else {
-            trusted_state
+            Err(_) => trusted_state,
         };
 
-        Self {
+        Ok(Self {
             trusted_state,
+            root_waypoint: Some(root_waypoint),
             storage,
-        }
+        })
+    }
+
+    fn stored_root_waypoint(storage: &S) -> Result<Option<Waypoint>> {
+        storage
+            .get(ROOT_WAYPOINT_KEY)
+            .ok()
+            .map(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode))
+            .transpose()
     }
 
     fn version(&self) -> Version {
@@ -800,10 +1575,153 @@ impl<S: Storage> TrustedStateStore<S> {
     fn ratchet(&mut self, new_state: TrustedState) -> Result<()> {
         if new_state.version() > self.trusted_state.version() {
             self.trusted_state = new_state;
-            let trusted_state_bytes = bcs::to_bytes(&self.trusted_state).map_err(Error::decode)?;
-            self.storage.set(TRUSTED_STATE_KEY, trusted_state_bytes)?;
+            self.persist()?;
         }
 
         Ok(())
     }
+
+    fn persist(&mut self) -> Result<()> {
+        if let Some(root_waypoint) = self.root_waypoint {
+            if Self::stored_root_waypoint(&self.storage)? != Some(root_waypoint) {
+                let root_waypoint_bytes = bcs::to_bytes(&root_waypoint).map_err(Error::decode)?;
+                self.storage.set(ROOT_WAYPOINT_KEY, root_waypoint_bytes)?;
+            }
+        }
+        let trusted_state_bytes = bcs::to_bytes(&self.trusted_state).map_err(Error::decode)?;
+        self.storage.set(TRUSTED_STATE_KEY, trusted_state_bytes)
+    }
+
+    fn chain_id(&self) -> Result<Option<ChainId>> {
+        self.storage
+            .get(CHAIN_ID_KEY)
+            .ok()
+            .map(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode))
+            .transpose()
+    }
+
+    fn verify_chain_id(&mut self, chain_id: u8) -> Result<()> {
+        match self.chain_id()? {
+            Some(trusted_chain_id) if trusted_chain_id.id() != chain_id => {
+                Err(Error::chain_id(trusted_chain_id.id(), chain_id))
+            }
+            Some(_) => Ok(()),
+            // Trust the first chain id we see.
+            None => {
+                let chain_id_bytes =
+                    bcs::to_bytes(&ChainId::new(chain_id)).map_err(Error::decode)?;
+                self.storage.set(CHAIN_ID_KEY, chain_id_bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use diem_crypto::HashValue;
+    use diem_temppath::TempPath;
+    use diem_types::{block_info::BlockInfo, ledger_info::LedgerInfo};
+
+    fn waypoint(version: Version, executed_state_id: HashValue) -> Waypoint {
+        let block_info =
+            BlockInfo::new(0, 0, HashValue::zero(), executed_state_id, version, 0, None);
+        Waypoint::new_any(&LedgerInfo::new(block_info, HashValue::zero()))
+    }
+
+    #[test]
+    fn test_file_storage_persist_and_reload() {
+        let path = TempPath::new();
+
+        let mut storage = FileStorage::new(path.path()).unwrap();
+        storage.get("key").unwrap_err();
+        storage.set("key", vec![1, 2, 3]).unwrap();
+        storage.set("other_key", vec![4]).unwrap();
+        storage.set("key", vec![5, 6]).unwrap();
+
+        let mut tmp_path = path.path().as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        assert!(!PathBuf::from(tmp_path).exists());
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        assert_eq!(storage.get("key").unwrap(), vec![5, 6]);
+        assert_eq!(storage.get("other_key").unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn test_file_storage_rejects_corrupt_file() {
+        let path = TempPath::new();
+        fs::write(path.path(), [0xff; 3]).unwrap();
+        FileStorage::new(path.path()).unwrap_err();
+    }
+
+    #[test]
+    fn test_trusted_state_store_reload() {
+        let path = TempPath::new();
+        let root_waypoint = waypoint(0, HashValue::zero());
+        let new_waypoint = waypoint(10, HashValue::zero());
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        let mut store =
+            TrustedStateStore::new_with_state(TrustedState::from(root_waypoint), storage).unwrap();
+        store.ratchet(TrustedState::from(new_waypoint)).unwrap();
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        let store = TrustedStateStore::new(storage).unwrap();
+        assert_eq!(store.waypoint(), new_waypoint);
+        assert_eq!(store.root_waypoint, Some(root_waypoint));
+
+        // The newer stored state was verified from the configured waypoint, so it wins.
+        let storage = FileStorage::new(path.path()).unwrap();
+        let store =
+            TrustedStateStore::new_with_state(TrustedState::from(root_waypoint), storage).unwrap();
+        assert_eq!(store.waypoint(), new_waypoint);
+
+        // A configured waypoint newer than the stored state wins and becomes the new root.
+        let newest_waypoint = waypoint(20, HashValue::zero());
+        let storage = FileStorage::new(path.path()).unwrap();
+        let mut store =
+            TrustedStateStore::new_with_state(TrustedState::from(newest_waypoint), storage)
+                .unwrap();
+        assert_eq!(store.waypoint(), newest_waypoint);
+        store.persist().unwrap();
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        TrustedStateStore::new_with_state(TrustedState::from(root_waypoint), storage).unwrap_err();
+    }
+
+    #[test]
+    fn test_trusted_state_store_rejects_other_waypoint() {
+        let path = TempPath::new();
+        let root_waypoint = waypoint(0, HashValue::zero());
+        let other_waypoint = waypoint(0, HashValue::sha3_256_of(b"other chain"));
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        let mut store =
+            TrustedStateStore::new_with_state(TrustedState::from(root_waypoint), storage).unwrap();
+        store
+            .ratchet(TrustedState::from(waypoint(10, HashValue::zero())))
+            .unwrap();
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        TrustedStateStore::new_with_state(TrustedState::from(other_waypoint), storage).unwrap_err();
+    }
+
+    #[test]
+    fn test_trusted_state_store_chain_id_mismatch() {
+        let path = TempPath::new();
+        let root_waypoint = waypoint(0, HashValue::zero());
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        let mut store =
+            TrustedStateStore::new_with_state(TrustedState::from(root_waypoint), storage).unwrap();
+        store.verify_chain_id(1).unwrap();
+        store.persist().unwrap();
+
+        let storage = FileStorage::new(path.path()).unwrap();
+        let mut store = TrustedStateStore::new(storage).unwrap();
+        assert_eq!(store.chain_id().unwrap(), Some(ChainId::new(1)));
+        store.verify_chain_id(2).unwrap_err();
+        store.verify_chain_id(1).unwrap();
+    }
 }
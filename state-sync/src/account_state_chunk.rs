@@ -0,0 +1,111 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_crypto::HashValue;
+use diem_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionListWithProof, Version},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use storage_interface::AccountStateChunkWithProof;
+
+/// A request for a chunk of the account state snapshot at an epoch ending version. Snapshots
+/// are used by nodes bootstrapping to their waypoint, instead of replaying the whole chain.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GetAccountStateChunkRequest {
+    /// The version of the snapshot, which must end an epoch.
+    pub version: Version,
+    /// The response should start with the first account after this key (or with the leftmost
+    /// account if `None`).
+    pub last_key: Option<HashValue>,
+    /// Max number of accounts in the response.
+    pub limit: u64,
+}
+
+impl GetAccountStateChunkRequest {
+    pub fn new(version: Version, last_key: Option<HashValue>, limit: u64) -> Self {
+        Self {
+            version,
+            last_key,
+            limit,
+        }
+    }
+}
+
+impl fmt::Debug for GetAccountStateChunkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for GetAccountStateChunkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[AccountStateChunkRequest: version: {}, last key: {}, limit: {}]",
+            self.version,
+            self.last_key
+                .map_or_else(|| String::from("None"), |key| key.to_string()),
+            self.limit,
+        )
+    }
+}
+
+/// The response carries everything needed to verify the chunk against the epoch ending ledger
+/// info at the snapshot version, so that the requester doesn't have to track which peer sent the
+/// snapshot root.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GetAccountStateChunkResponse {
+    /// The ledger info ending the epoch at the snapshot version.
+    pub ledger_info: LedgerInfoWithSignatures,
+    /// The transaction at the snapshot version, whose `TransactionInfo` carries the state root
+    /// hash, with its proof relative to `ledger_info`.
+    pub txn_list_with_proof: TransactionListWithProof,
+    /// The frozen subtree roots of the transaction accumulator before the snapshot version.
+    pub frozen_subtrees: Vec<HashValue>,
+    /// The accounts with the proof of their range relative to the state root hash.
+    pub account_chunk: AccountStateChunkWithProof,
+}
+
+impl GetAccountStateChunkResponse {
+    pub fn new(
+        ledger_info: LedgerInfoWithSignatures,
+        txn_list_with_proof: TransactionListWithProof,
+        frozen_subtrees: Vec<HashValue>,
+        account_chunk: AccountStateChunkWithProof,
+    ) -> Self {
+        Self {
+            ledger_info,
+            txn_list_with_proof,
+            frozen_subtrees,
+            account_chunk,
+        }
+    }
+
+    /// The version of the snapshot the chunk belongs to.
+    pub fn version(&self) -> Version {
+        self.ledger_info.ledger_info().version()
+    }
+}
+
+impl fmt::Debug for GetAccountStateChunkResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for GetAccountStateChunkResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[AccountStateChunkResponse: version: {}, accounts: {}, last key: {}, is last chunk: {}]",
+            self.version(),
+            self.account_chunk.account_blobs.len(),
+            self.account_chunk
+                .last_key()
+                .map_or_else(|| String::from("None"), |key| key.to_string()),
+            self.account_chunk.is_last_chunk,
+        )
+    }
+}
@@ -10,8 +10,8 @@ use diem_config::{config::NodeConfig, network_id::NodeNetworkId};
 use diem_types::waypoint::Waypoint;
 use executor_types::ChunkExecutor;
 use futures::channel::mpsc;
-use std::{boxed::Box, collections::HashMap, sync::Arc};
-use storage_interface::DbReader;
+use std::{boxed::Box, collections::HashMap};
+use storage_interface::DbReaderWriter;
 use subscription_service::ReconfigSubscription;
 use tokio::runtime::{Builder, Runtime};
 
@@ -26,7 +26,7 @@ impl StateSyncBootstrapper {
     pub fn bootstrap(
         network: Vec<(NodeNetworkId, StateSyncSender, StateSyncEvents)>,
         state_sync_to_mempool_sender: mpsc::Sender<diem_mempool::CommitNotification>,
-        storage: Arc<dyn DbReader>,
+        storage: DbReaderWriter,
         executor: Box<dyn ChunkExecutor>,
         node_config: &NodeConfig,
         waypoint: Waypoint,
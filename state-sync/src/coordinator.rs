@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    account_state_chunk::{GetAccountStateChunkRequest, GetAccountStateChunkResponse},
     chunk_request::{GetChunkRequest, TargetType},
     chunk_response::{GetChunkResponse, ResponseLedgerInfo},
     client::{CoordinatorMessage, SyncRequest},
@@ -14,12 +15,14 @@ use crate::{
     shared_components::SyncState,
 };
 use diem_config::{
-    config::{NodeConfig, PeerNetworkId, RoleType, StateSyncConfig},
+    config::{BootstrappingMode, NodeConfig, PeerNetworkId, RoleType, StateSyncConfig},
     network_id::NodeNetworkId,
 };
+use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_mempool::{CommitResponse, CommittedTransaction};
 use diem_types::{
+    account_state_blob::AccountStateBlob,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{Transaction, TransactionListWithProof, Version},
@@ -38,6 +41,7 @@ use std::{
     collections::HashMap,
     time::{Duration, SystemTime},
 };
+use storage_interface::StateSnapshotReceiver;
 use tokio::time::{interval, timeout};
 use tokio_stream::wrappers::IntervalStream;
 
@@ -50,6 +54,21 @@ struct PendingRequestInfo {
     chunk_limit: u64,
 }
 
+/// An account state snapshot that is being applied in order to bootstrap to the waypoint (see
+/// `BootstrappingMode::ApplyStateSnapshot`).
+struct PendingStateSnapshot {
+    // The ledger info ending the epoch at the snapshot version (verified against the waypoint)
+    ledger_info: LedgerInfoWithSignatures,
+    // The transaction at the snapshot version (verified against `ledger_info`)
+    txn_list_with_proof: TransactionListWithProof,
+    // The frozen subtree roots of the transaction accumulator before the snapshot version
+    frozen_subtrees: Vec<HashValue>,
+    // Writes the verified account chunks to storage
+    receiver: Box<dyn StateSnapshotReceiver<AccountStateBlob>>,
+    // The key of the last account applied to the snapshot (if any)
+    last_key: Option<HashValue>,
+}
+
 /// Coordination of the state sync process is driven by StateSyncCoordinator. The `start()`
 /// function runs an infinite event loop and triggers actions based on external and internal
 /// (local) requests. The coordinator works in two modes (depending on the role):
@@ -89,6 +108,8 @@ pub(crate) struct StateSyncCoordinator<T> {
     // queue of incoming long polling requests
     // peer will be notified about new chunk of transactions if it's available before expiry time
     subscriptions: HashMap<PeerNetworkId, PendingRequestInfo>,
+    // The account state snapshot being applied (if we're bootstrapping from a snapshot)
+    pending_state_snapshot: Option<PendingStateSnapshot>,
     executor_proxy: T,
 }
 
@@ -135,6 +156,7 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             sync_request: None,
             target_ledger_info: None,
             initialization_listener: None,
+            pending_state_snapshot: None,
             executor_proxy,
         })
     }
@@ -301,6 +323,45 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
                 // Process chunk response
                 self.process_chunk_response(&peer, *response).await
             }
+            StateSyncMessage::GetAccountStateChunkRequest(request) => {
+                // Time request handling
+                let _timer = counters::PROCESS_MSG_LATENCY
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::ACCOUNT_STATE_CHUNK_REQUEST_MSG_LABEL,
+                    ])
+                    .start_timer();
+
+                // Process account state chunk request
+                let request_version = request.version;
+                let process_result =
+                    self.process_account_state_chunk_request(peer.clone(), *request);
+                if let Err(ref error) = process_result {
+                    error!(LogSchema::event_log(
+                        LogEntry::ProcessAccountStateChunkRequest,
+                        LogEvent::Fail
+                    )
+                    .peer(&peer)
+                    .error(&error.clone())
+                    .local_li_version(self.local_state.committed_version())
+                    .request_version(request_version));
+                }
+                process_result
+            }
+            StateSyncMessage::GetAccountStateChunkResponse(response) => {
+                // Time response handling
+                let _timer = counters::PROCESS_MSG_LATENCY
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::ACCOUNT_STATE_CHUNK_RESPONSE_MSG_LABEL,
+                    ])
+                    .start_timer();
+
+                // Process account state chunk response
+                self.process_account_state_chunk_response(&peer, *response)
+            }
         }
     }
 
@@ -850,6 +911,101 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         )
     }
 
+    /// Serves a chunk of the account state snapshot at an epoch ending version. Only epoch ending
+    /// snapshots are served, as the requester verifies the ledger info against its waypoint.
+    fn process_account_state_chunk_request(
+        &mut self,
+        peer: PeerNetworkId,
+        request: GetAccountStateChunkRequest,
+    ) -> Result<(), Error> {
+        debug!(LogSchema::event_log(
+            LogEntry::ProcessAccountStateChunkRequest,
+            LogEvent::Received
+        )
+        .peer(&peer)
+        .request_version(request.version)
+        .local_li_version(self.local_state.committed_version()));
+        self.sync_state_with_local_storage()?;
+
+        // Verify the request is valid before trying to process it. If it's invalid,
+        // penalize the peer's score.
+        if request.limit == 0 || request.version == 0 {
+            self.request_manager.process_invalid_chunk_request(&peer);
+            return Err(Error::InvalidChunkRequest(format!(
+                "Account state chunk request has limit {} at version {}. Discarding request.",
+                request.limit, request.version
+            )));
+        }
+        if self.local_state.committed_version() < request.version {
+            return Err(Error::UnexpectedError(format!(
+                "Local version {} < requested snapshot version {}.",
+                self.local_state.committed_version(),
+                request.version
+            )));
+        }
+        let ledger_info = self
+            .executor_proxy
+            .get_epoch_ending_ledger_info(request.version)?;
+        if ledger_info.ledger_info().version() != request.version {
+            self.request_manager.process_invalid_chunk_request(&peer);
+            return Err(Error::InvalidChunkRequest(format!(
+                "Requested snapshot version {} doesn't end an epoch. Discarding request.",
+                request.version
+            )));
+        }
+
+        // The transaction at the snapshot version carries the state root hash
+        let txn_list_with_proof =
+            self.executor_proxy
+                .get_chunk(request.version - 1, 1, request.version)?;
+        let frozen_subtrees = self
+            .executor_proxy
+            .get_accumulator_frozen_subtrees(request.version)?;
+        let limit = cmp::min(request.limit, self.config.max_chunk_limit);
+        let account_chunk = self.executor_proxy.get_account_state_chunk(
+            request.version,
+            request.last_key,
+            limit,
+        )?;
+
+        let response = GetAccountStateChunkResponse::new(
+            ledger_info,
+            txn_list_with_proof,
+            frozen_subtrees,
+            account_chunk,
+        );
+        let log = LogSchema::event_log(
+            LogEntry::ProcessAccountStateChunkRequest,
+            LogEvent::DeliverChunk,
+        )
+        .peer(&peer)
+        .request_version(request.version)
+        .count(response.account_chunk.account_blobs.len());
+        let msg = StateSyncMessage::GetAccountStateChunkResponse(Box::new(response));
+        let send_result = self.request_manager.send_chunk_response(&peer, msg);
+        let send_result_label = if send_result.is_err() {
+            counters::SEND_FAIL_LABEL
+        } else {
+            debug!(log);
+            counters::SEND_SUCCESS_LABEL
+        };
+        counters::RESPONSES_SENT
+            .with_label_values(&[
+                &peer.raw_network_id().to_string(),
+                &peer.peer_id().to_string(),
+                send_result_label,
+            ])
+            .inc();
+
+        send_result.map_err(|e| {
+            error!(log.error(&e));
+            Error::UnexpectedError(format!(
+                "Network error in sending account state chunk response to {}",
+                peer
+            ))
+        })
+    }
+
     /// Generate and send the ChunkResponse to the given peer.
     /// The chunk response contains transactions from the local storage with the proofs relative to
     /// the given target ledger info.
@@ -1451,6 +1607,266 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             .execute_chunk(txn_list_with_proof, target, intermediate_end_of_epoch_li)
     }
 
+    /// Returns true if the node should bootstrap to its waypoint by applying the account state
+    /// snapshot at the waypoint version (instead of executing all transactions up to it). This
+    /// is only possible if nothing has been synced beyond the local committed ledger info.
+    fn is_applying_state_snapshot(&self) -> bool {
+        self.config.bootstrapping_mode == BootstrappingMode::ApplyStateSnapshot
+            && !self.is_initialized()
+            && self.local_state.synced_version() == self.local_state.committed_version()
+    }
+
+    /// Processes a chunk of the account state snapshot at the waypoint version:
+    /// * Verifies the chunk and applies it to the pending snapshot.
+    /// * Requests the next chunk, or finalizes the snapshot if this was the last one.
+    fn process_account_state_chunk_response(
+        &mut self,
+        peer: &PeerNetworkId,
+        response: GetAccountStateChunkResponse,
+    ) -> Result<(), Error> {
+        debug!(LogSchema::event_log(
+            LogEntry::ProcessAccountStateChunkResponse,
+            LogEvent::Received
+        )
+        .peer(peer)
+        .request_version(response.version())
+        .count(response.account_chunk.account_blobs.len()));
+
+        // Verify response comes from known peer
+        if !self.request_manager.is_known_state_sync_peer(peer) {
+            counters::RESPONSE_FROM_DOWNSTREAM_COUNT
+                .with_label_values(&[
+                    &peer.raw_network_id().to_string(),
+                    &peer.peer_id().to_string(),
+                ])
+                .inc();
+            self.request_manager.process_chunk_from_downstream(&peer);
+            return Err(Error::ReceivedChunkFromDownstream(peer.to_string()));
+        }
+
+        // Verify we're still applying a snapshot and that the chunk isn't a late duplicate of one
+        // we've already applied (e.g., from a multicasted request).
+        if !self.is_applying_state_snapshot() {
+            return Err(Error::ReceivedWrongChunkType(
+                "Received an account state chunk, but we're not applying a state snapshot!".into(),
+            ));
+        }
+        let last_key = self
+            .pending_state_snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.last_key);
+        if let (Some(last_key), Some((first_key, _))) =
+            (last_key, response.account_chunk.account_blobs.first())
+        {
+            if *first_key <= last_key {
+                return Err(Error::ReceivedChunkForOutdatedRequest(
+                    peer.to_string(),
+                    last_key.to_string(),
+                    first_key.to_string(),
+                ));
+            }
+        }
+
+        let num_accounts = response.account_chunk.account_blobs.len();
+        match self.apply_account_state_chunk(response) {
+            Ok(is_snapshot_complete) => {
+                counters::APPLY_ACCOUNT_STATE_CHUNK_COUNT
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::SUCCESS_LABEL,
+                    ])
+                    .inc();
+                counters::STATE_SNAPSHOT_ACCOUNTS.inc_by(num_accounts as u64);
+                debug!(LogSchema::event_log(
+                    LogEntry::ProcessAccountStateChunkResponse,
+                    LogEvent::ApplyAccountStateChunkSuccess
+                )
+                .peer(peer)
+                .count(num_accounts));
+                self.request_manager.process_success_response(peer);
+
+                if is_snapshot_complete {
+                    self.process_completed_state_snapshot()
+                } else {
+                    self.send_account_state_chunk_request_and_log_error(
+                        LogEntry::ProcessAccountStateChunkResponse,
+                    )
+                }
+            }
+            Err(error) => {
+                error!(LogSchema::event_log(
+                    LogEntry::ProcessAccountStateChunkResponse,
+                    LogEvent::ApplyAccountStateChunkFail
+                )
+                .peer(peer)
+                .error(&error));
+                counters::APPLY_ACCOUNT_STATE_CHUNK_COUNT
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::FAIL_LABEL,
+                    ])
+                    .inc();
+                self.request_manager.process_invalid_chunk(&peer);
+                Err(error)
+            }
+        }
+    }
+
+    /// Verifies the account state chunk and applies it to the pending snapshot (starting a new
+    /// snapshot on the first chunk). Once the last chunk is applied, the snapshot is committed
+    /// to storage. Returns true iff the snapshot has been committed.
+    ///
+    /// A chunk that fails to apply may have been partially written, so on any error the pending
+    /// snapshot is discarded and restarted from scratch.
+    fn apply_account_state_chunk(
+        &mut self,
+        response: GetAccountStateChunkResponse,
+    ) -> Result<bool, Error> {
+        let snapshot_version = self.waypoint.version();
+        if response.version() != snapshot_version {
+            return Err(Error::ProcessInvalidChunk(format!(
+                "Received an account state chunk at version {}, but the waypoint version is {}!",
+                response.version(),
+                snapshot_version
+            )));
+        }
+
+        let mut snapshot = match self.pending_state_snapshot.take() {
+            Some(snapshot) => snapshot,
+            None => self.create_pending_state_snapshot(&response)?,
+        };
+        let last_key = response.account_chunk.last_key();
+        snapshot
+            .receiver
+            .add_chunk(
+                response.account_chunk.account_blobs,
+                response.account_chunk.proof,
+            )
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))?;
+        snapshot.last_key = last_key;
+        if !response.account_chunk.is_last_chunk {
+            self.pending_state_snapshot = Some(snapshot);
+            return Ok(false);
+        }
+
+        // All accounts have been applied: write the snapshot root and commit the snapshot.
+        snapshot
+            .receiver
+            .finish_box()
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))?;
+        self.executor_proxy.finalize_state_snapshot(
+            snapshot_version,
+            snapshot.txn_list_with_proof,
+            snapshot.frozen_subtrees,
+            snapshot.ledger_info,
+        )?;
+        Ok(true)
+    }
+
+    /// Verifies the ledger info and transaction carried by the first chunk of a snapshot and
+    /// creates the receiver that writes the snapshot to storage.
+    fn create_pending_state_snapshot(
+        &self,
+        response: &GetAccountStateChunkResponse,
+    ) -> Result<PendingStateSnapshot, Error> {
+        let ledger_info = response.ledger_info.ledger_info();
+        self.waypoint.verify(ledger_info).map_err(|error| {
+            Error::ProcessInvalidChunk(format!("Waypoint verification failed: {}", error))
+        })?;
+        response
+            .txn_list_with_proof
+            .verify(ledger_info, Some(ledger_info.version()))
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))?;
+        let txn_info = match response.txn_list_with_proof.proof.transaction_infos() {
+            [txn_info] => txn_info,
+            txn_infos => {
+                return Err(Error::ProcessInvalidChunk(format!(
+                    "Expected the snapshot transaction only, but got {} transactions!",
+                    txn_infos.len()
+                )))
+            }
+        };
+
+        let receiver = self
+            .executor_proxy
+            .get_state_snapshot_receiver(ledger_info.version(), txn_info.state_root_hash())?;
+        info!(
+            LogSchema::event_log(LogEntry::StateSnapshot, LogEvent::Initialize)
+                .ledger_info(response.ledger_info.clone())
+        );
+        Ok(PendingStateSnapshot {
+            ledger_info: response.ledger_info.clone(),
+            txn_list_with_proof: response.txn_list_with_proof.clone(),
+            frozen_subtrees: response.frozen_subtrees.clone(),
+            receiver,
+            last_key: None,
+        })
+    }
+
+    /// Syncs the local state with the committed snapshot and switches to syncing transactions
+    /// (if we need to go beyond the waypoint).
+    fn process_completed_state_snapshot(&mut self) -> Result<(), Error> {
+        self.sync_state_with_local_storage()?;
+        self.update_sync_state_metrics_and_logs()?;
+
+        let synced_version = self.local_state.synced_version();
+        info!(
+            LogSchema::event_log(LogEntry::StateSnapshot, LogEvent::Complete)
+                .local_li_version(self.local_state.committed_version())
+                .local_synced_version(synced_version)
+                .local_epoch(self.local_state.trusted_epoch())
+        );
+        self.request_manager.remove_requests(synced_version);
+        self.check_initialized_or_sync_request_completed(synced_version)
+    }
+
+    /// Sends a request for the next chunk of the account state snapshot at the waypoint version.
+    /// Immediately logs any errors returned by the operation using the given log entry.
+    fn send_account_state_chunk_request_and_log_error(
+        &mut self,
+        log_entry: LogEntry,
+    ) -> Result<(), Error> {
+        let known_version = self.local_state.synced_version();
+        if let Err(error) = self.send_account_state_chunk_request(known_version) {
+            error!(
+                LogSchema::event_log(log_entry, LogEvent::SendChunkRequestFail)
+                    .version(known_version)
+                    .request_version(self.waypoint.version())
+                    .error(&error)
+            );
+            Err(error)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sends a request for the account state chunk following the last applied account.
+    fn send_account_state_chunk_request(&mut self, known_version: u64) -> Result<(), Error> {
+        if self.request_manager.no_available_peers() {
+            warn!(LogSchema::event_log(
+                LogEntry::SendChunkRequest,
+                LogEvent::MissingPeers
+            ));
+            return Err(Error::NoAvailablePeers(
+                "No peers to send account state chunk request to!".into(),
+            ));
+        }
+
+        let snapshot_version = self.waypoint.version();
+        counters::set_version(counters::VersionType::Target, snapshot_version);
+
+        let last_key = self
+            .pending_state_snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.last_key);
+        let req =
+            GetAccountStateChunkRequest::new(snapshot_version, last_key, self.config.chunk_limit);
+        self.request_manager
+            .send_account_state_chunk_request(known_version, req)
+    }
+
     /// Returns true if consensus is currently executing and state sync should
     /// therefore not write to storage. Reads are still permitted (e.g., to
     /// handle chunk requests).
@@ -1509,6 +1925,10 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             counters::TIMEOUT.inc();
             warn!(LogSchema::new(LogEntry::Timeout).version(known_version));
 
+            if self.is_applying_state_snapshot() {
+                return self.send_account_state_chunk_request_and_log_error(LogEntry::Timeout);
+            }
+
             let trusted_epoch = self.local_state.trusted_epoch();
             let chunk_target = if !self.is_initialized() {
                 self.create_waypoint_chunk_target()
@@ -1682,17 +2102,18 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
 #[cfg(test)]
 mod tests {
     use crate::{
+        account_state_chunk::GetAccountStateChunkResponse,
         chunk_request::{GetChunkRequest, TargetType},
         chunk_response::{GetChunkResponse, ResponseLedgerInfo},
         client::SyncRequest,
         coordinator::StateSyncCoordinator,
         error::Error,
-        executor_proxy::ExecutorProxy,
+        executor_proxy::{ExecutorProxy, ExecutorProxyTrait},
         network::StateSyncMessage,
         shared_components::{test_utils, test_utils::create_coordinator_with_config_and_waypoint},
     };
     use diem_config::{
-        config::{NodeConfig, PeerNetworkId, PeerRole, RoleType},
+        config::{BootstrappingMode, NodeConfig, PeerNetworkId, PeerRole, RoleType},
         network_id::{NetworkId, NodeNetworkId},
     };
     use diem_crypto::{
@@ -1700,23 +2121,37 @@ mod tests {
         HashValue, PrivateKey, Uniform,
     };
     use diem_mempool::CommitResponse;
+    use diem_transaction_builder::stdlib::encode_update_diem_version_script;
     use diem_types::{
         account_address::AccountAddress,
+        account_config::diem_root_address,
         block_info::BlockInfo,
+        block_metadata::BlockMetadata,
         chain_id::ChainId,
         ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
         proof::TransactionListProof,
         transaction::{
             RawTransaction, Script, SignedTransaction, Transaction, TransactionListWithProof,
-            TransactionPayload, Version,
+            TransactionPayload, Version, WriteSetPayload,
         },
         waypoint::Waypoint,
         PeerId,
     };
+    use diem_vm::DiemVM;
+    use diemdb::DiemDB;
+    use executor::Executor;
+    use executor_test_helpers::{
+        bootstrap_genesis, gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
+    };
+    use executor_types::BlockExecutor;
     use futures::{channel::oneshot, executor::block_on};
     use netcore::transport::ConnectionOrigin;
     use network::transport::ConnectionMetadata;
     use std::{collections::BTreeMap, time::SystemTime};
+    use storage_interface::DbReaderWriter;
+
+    // Enough to fetch all accounts of a test snapshot in a single chunk
+    const MAX_ACCOUNTS: u64 = 1000;
 
     #[test]
     fn test_process_sync_request() {
@@ -2271,6 +2706,161 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_apply_state_snapshot() {
+        // Create a storage that has moved to a new epoch after genesis
+        let (source_proxy, epoch_ending_ledger_info) = create_storage_at_new_epoch();
+        let snapshot_version = epoch_ending_ledger_info.ledger_info().version();
+
+        // Create a validator coordinator that bootstraps to the new epoch by applying a snapshot
+        let mut node_config = NodeConfig::default();
+        node_config.base.role = RoleType::Validator;
+        node_config.state_sync.bootstrapping_mode = BootstrappingMode::ApplyStateSnapshot;
+        let waypoint =
+            Waypoint::new_epoch_boundary(epoch_ending_ledger_info.ledger_info()).unwrap();
+        let mut validator_coordinator =
+            create_coordinator_with_config_and_waypoint(node_config, waypoint);
+        let peer_network_id = PeerNetworkId::random_validator();
+        process_new_peer_event(&mut validator_coordinator, &peer_network_id);
+
+        // Wait for initialization
+        let (callback_sender, mut callback_receiver) = oneshot::channel();
+        validator_coordinator
+            .wait_for_initialization(callback_sender)
+            .unwrap();
+
+        // Verify a snapshot at the wrong version is rejected
+        let mut response =
+            create_account_state_chunk_response(&source_proxy, epoch_ending_ledger_info.clone());
+        let account_chunk = response.account_chunk.clone();
+        response.ledger_info = create_ledger_info_at_version(snapshot_version + 1);
+        let result = block_on(validator_coordinator.process_chunk_message(
+            peer_network_id.network_id(),
+            peer_network_id.peer_id(),
+            StateSyncMessage::GetAccountStateChunkResponse(Box::new(response)),
+        ));
+        if !matches!(result, Err(Error::ProcessInvalidChunk(..))) {
+            panic!("Expected invalid chunk error, but got: {:?}", result);
+        }
+
+        // Apply the snapshot of all accounts and verify the node is initialized
+        assert!(account_chunk.is_last_chunk);
+        let response =
+            create_account_state_chunk_response(&source_proxy, epoch_ending_ledger_info.clone());
+        block_on(validator_coordinator.process_chunk_message(
+            peer_network_id.network_id(),
+            peer_network_id.peer_id(),
+            StateSyncMessage::GetAccountStateChunkResponse(Box::new(response)),
+        ))
+        .unwrap();
+        match callback_receiver.try_recv() {
+            Ok(Some(result)) => {
+                assert!(result.is_ok())
+            }
+            result => panic!("Expected okay but got: {:?}", result),
+        };
+
+        // Verify the local storage matches the snapshot
+        let (callback_sender, mut callback_receiver) = oneshot::channel();
+        validator_coordinator
+            .get_sync_state(callback_sender)
+            .unwrap();
+        match callback_receiver.try_recv() {
+            Ok(Some(sync_state)) => {
+                assert_eq!(sync_state.committed_version(), snapshot_version);
+                assert_eq!(sync_state.synced_version(), snapshot_version);
+                assert_eq!(
+                    sync_state.trusted_epoch(),
+                    epoch_ending_ledger_info.ledger_info().next_block_epoch()
+                );
+            }
+            result => panic!("Expected okay but got: {:?}", result),
+        };
+        let local_account_chunk = validator_coordinator
+            .executor_proxy
+            .get_account_state_chunk(snapshot_version, None, MAX_ACCOUNTS)
+            .unwrap();
+        assert_eq!(
+            local_account_chunk.account_blobs,
+            account_chunk.account_blobs
+        );
+
+        // Verify further snapshot chunks are no longer accepted
+        let response = create_account_state_chunk_response(&source_proxy, epoch_ending_ledger_info);
+        let result = block_on(validator_coordinator.process_chunk_message(
+            peer_network_id.network_id(),
+            peer_network_id.peer_id(),
+            StateSyncMessage::GetAccountStateChunkResponse(Box::new(response)),
+        ));
+        if !matches!(result, Err(Error::ReceivedWrongChunkType(..))) {
+            panic!("Expected wrong type error, but got: {:?}", result);
+        }
+    }
+
+    /// Creates a storage that has committed a block reconfiguring the genesis epoch. Returns the
+    /// storage's executor proxy and the ledger info ending the epoch.
+    fn create_storage_at_new_epoch() -> (ExecutorProxy, LedgerInfoWithSignatures) {
+        // Create test diem database and bootstrap the genesis transaction
+        let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
+        let db_path = diem_temppath::TempPath::new();
+        db_path.create_as_dir().unwrap();
+        let db_rw = DbReaderWriter::new(DiemDB::new_for_test(db_path.path()));
+        let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+        bootstrap_genesis::<DiemVM>(&db_rw, &genesis_txn).unwrap();
+
+        // Execute and commit a block that bumps the timer and changes the Diem version
+        let mut block_executor = Executor::<DiemVM>::new(db_rw.clone());
+        let block_id = gen_block_id(1);
+        let genesis_key = vm_genesis::GENESIS_KEYPAIR.0.clone();
+        let block = vec![
+            Transaction::BlockMetadata(BlockMetadata::new(
+                block_id,
+                1,
+                100_000_010,
+                vec![],
+                validators[0].owner_address,
+            )),
+            get_test_signed_transaction(
+                diem_root_address(),
+                1, /* sequence_number */
+                genesis_key.clone(),
+                genesis_key.public_key(),
+                Some(encode_update_diem_version_script(0, 7)),
+            ),
+        ];
+        let output = block_executor
+            .execute_block((block_id, block), block_executor.committed_block_id())
+            .unwrap();
+        assert!(output.has_reconfiguration());
+        let ledger_info_with_sigs = gen_ledger_info_with_sigs(1, output, block_id, vec![]);
+        block_executor
+            .commit_blocks(vec![block_id], ledger_info_with_sigs.clone())
+            .unwrap();
+
+        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
+        let executor_proxy = ExecutorProxy::new(db_rw, chunk_executor, vec![]);
+        (executor_proxy, ledger_info_with_sigs)
+    }
+
+    /// Creates a response carrying all accounts of the snapshot at the given ledger info's
+    /// version, as served by the given executor proxy.
+    fn create_account_state_chunk_response(
+        executor_proxy: &ExecutorProxy,
+        ledger_info: LedgerInfoWithSignatures,
+    ) -> GetAccountStateChunkResponse {
+        let version = ledger_info.ledger_info().version();
+        GetAccountStateChunkResponse::new(
+            ledger_info,
+            executor_proxy.get_chunk(version - 1, 1, version).unwrap(),
+            executor_proxy
+                .get_accumulator_frozen_subtrees(version)
+                .unwrap(),
+            executor_proxy
+                .get_account_state_chunk(version, None, MAX_ACCOUNTS)
+                .unwrap(),
+        )
+    }
+
     fn create_test_transaction() -> Transaction {
         let private_key = Ed25519PrivateKey::generate_for_testing();
         let public_key = private_key.public_key();
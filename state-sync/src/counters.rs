@@ -17,6 +17,8 @@ pub const SYNC_MSG_LABEL: &str = "sync";
 pub const COMMIT_MSG_LABEL: &str = "commit";
 pub const CHUNK_REQUEST_MSG_LABEL: &str = "chunk_request";
 pub const CHUNK_RESPONSE_MSG_LABEL: &str = "chunk_response";
+pub const ACCOUNT_STATE_CHUNK_REQUEST_MSG_LABEL: &str = "account_state_chunk_request";
+pub const ACCOUNT_STATE_CHUNK_RESPONSE_MSG_LABEL: &str = "account_state_chunk_response";
 
 pub fn set_timestamp(timestamp_type: TimestampType, time_as_usecs: u64) {
     TIMESTAMP
@@ -145,6 +147,25 @@ pub static APPLY_CHUNK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of attempts to apply a chunk of an account state snapshot
+pub static APPLY_ACCOUNT_STATE_CHUNK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_state_sync_apply_account_state_chunk_total",
+        "Number of Success results of applying a chunk of an account state snapshot",
+        &["network", "sender", "result"]
+    )
+    .unwrap()
+});
+
+/// Number of accounts restored from a state snapshot
+pub static STATE_SNAPSHOT_ACCOUNTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_state_sync_state_snapshot_accounts_total",
+        "Number of accounts restored from an account state snapshot"
+    )
+    .unwrap()
+});
+
 pub static PROCESS_CHUNK_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "diem_state_sync_process_chunk_request_total",
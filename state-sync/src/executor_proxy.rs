@@ -7,19 +7,30 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     shared_components::SyncState,
 };
+use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::{
     account_state::AccountState,
+    account_state_blob::AccountStateBlob,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
-    on_chain_config::{config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
+    on_chain_config::{
+        config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY,
+        OPTIONAL_ON_CHAIN_CONFIG_REGISTRY,
+    },
     transaction::TransactionListWithProof,
 };
 use executor_types::{ChunkExecutor, ExecutedTrees};
 use itertools::Itertools;
-use std::{collections::HashSet, convert::TryFrom, sync::Arc};
-use storage_interface::DbReader;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
+use storage_interface::{
+    AccountStateChunkWithProof, DbReader, DbReaderWriter, DbWriter, StateSnapshotReceiver,
+};
 use subscription_service::ReconfigSubscription;
 
 /// Proxies interactions with execution and storage for state synchronization
@@ -55,10 +66,44 @@ pub trait ExecutorProxyTrait: Send {
 
     /// publishes on-chain config updates to subscribed components
     fn publish_on_chain_config_updates(&mut self, events: Vec<ContractEvent>) -> Result<(), Error>;
+
+    /// Gets at most `limit` accounts of the state snapshot at `version`, starting after
+    /// `last_key`.
+    fn get_account_state_chunk(
+        &self,
+        version: u64,
+        last_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof, Error>;
+
+    /// Gets the frozen subtree roots of the transaction accumulator with `num_transactions`
+    /// leaves.
+    fn get_accumulator_frozen_subtrees(
+        &self,
+        num_transactions: u64,
+    ) -> Result<Vec<HashValue>, Error>;
+
+    /// Gets a receiver that restores the state snapshot at `version` into storage.
+    fn get_state_snapshot_receiver(
+        &self,
+        version: u64,
+        expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>, Error>;
+
+    /// Commits a fully restored state snapshot (see `DbWriter::finalize_state_snapshot`) and
+    /// notifies the subscribed components of the on-chain configs at the snapshot version.
+    fn finalize_state_snapshot(
+        &mut self,
+        version: u64,
+        output_with_proof: TransactionListWithProof,
+        frozen_subtrees: Vec<HashValue>,
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<(), Error>;
 }
 
 pub(crate) struct ExecutorProxy {
     storage: Arc<dyn DbReader>,
+    storage_writer: Arc<dyn DbWriter>,
     executor: Box<dyn ChunkExecutor>,
     reconfig_subscriptions: Vec<ReconfigSubscription>,
     on_chain_configs: OnChainConfigPayload,
@@ -66,10 +111,14 @@ pub(crate) struct ExecutorProxy {
 
 impl ExecutorProxy {
     pub(crate) fn new(
-        storage: Arc<dyn DbReader>,
+        storage: DbReaderWriter,
         executor: Box<dyn ChunkExecutor>,
         mut reconfig_subscriptions: Vec<ReconfigSubscription>,
     ) -> Self {
+        let DbReaderWriter {
+            reader: storage,
+            writer: storage_writer,
+        } = storage;
         let on_chain_configs = Self::fetch_all_configs(&*storage)
             .expect("[state sync] Failed initial read of on-chain configs");
         for subscription in reconfig_subscriptions.iter_mut() {
@@ -79,6 +128,7 @@ impl ExecutorProxy {
         }
         Self {
             storage,
+            storage_writer,
             executor,
             reconfig_subscriptions,
             on_chain_configs,
@@ -107,33 +157,30 @@ impl ExecutorProxy {
                     error
                 ))
             })?
-            .0;
-        let epoch = account_state_blob
-            .map(|blob| {
-                AccountState::try_from(&blob).and_then(|state| {
-                    Ok(state
-                        .get_configuration_resource()?
-                        .ok_or_else(|| {
-                            Error::UnexpectedError("Configuration resource does not exist".into())
-                        })?
-                        .epoch())
-                })
-            })
-            .ok_or_else(|| Error::UnexpectedError("Missing account state blob".into()))?
+            .0
+            .ok_or_else(|| Error::UnexpectedError("Missing account state blob".into()))?;
+        let account_state = AccountState::try_from(&account_state_blob).map_err(|error| {
+            Error::UnexpectedError(format!("Failed to fetch configuration resource: {}", error))
+        })?;
+        let epoch = account_state
+            .get_configuration_resource()
             .map_err(|error| {
                 Error::UnexpectedError(format!("Failed to fetch configuration resource: {}", error))
-            })?;
+            })?
+            .ok_or_else(|| Error::UnexpectedError("Configuration resource does not exist".into()))?
+            .epoch();
 
-        Ok(OnChainConfigPayload::new(
-            epoch,
-            Arc::new(
-                ON_CHAIN_CONFIG_REGISTRY
-                    .iter()
-                    .cloned()
-                    .zip_eq(configs)
-                    .collect(),
-            ),
-        ))
+        let mut configs: HashMap<_, _> = ON_CHAIN_CONFIG_REGISTRY
+            .iter()
+            .cloned()
+            .zip_eq(configs)
+            .collect();
+        for config_id in OPTIONAL_ON_CHAIN_CONFIG_REGISTRY {
+            if let Some(config) = account_state.get(&config_id.access_path().path) {
+                configs.insert(*config_id, config.clone());
+            }
+        }
+        Ok(OnChainConfigPayload::new(epoch, Arc::new(configs)))
     }
 }
 
@@ -262,10 +309,11 @@ impl ExecutorProxyTrait for ExecutorProxy {
         let changed_configs = new_configs
             .configs()
             .iter()
-            .filter(|(id, cfg)| {
-                &self.on_chain_configs.configs().get(id).unwrap_or_else(|| {
-                    panic!("Missing on-chain config value in local copy: {}", id)
-                }) != cfg
+            .filter(|(id, cfg)| match self.on_chain_configs.configs().get(id) {
+                Some(local_cfg) => local_cfg != *cfg,
+                // The optional config has just been published
+                None if OPTIONAL_ON_CHAIN_CONFIG_REGISTRY.contains(id) => true,
+                None => panic!("Missing on-chain config value in local copy: {}", id),
             })
             .map(|(id, _)| *id)
             .collect::<HashSet<_>>();
@@ -311,6 +359,79 @@ impl ExecutorProxyTrait for ExecutorProxy {
             ))
         }
     }
+
+    fn get_account_state_chunk(
+        &self,
+        version: u64,
+        last_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof, Error> {
+        self.storage
+            .get_account_chunk_with_proof(version, last_key, limit as usize)
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to get account state chunk from storage {}",
+                    error
+                ))
+            })
+    }
+
+    fn get_accumulator_frozen_subtrees(
+        &self,
+        num_transactions: u64,
+    ) -> Result<Vec<HashValue>, Error> {
+        self.storage
+            .get_accumulator_frozen_subtrees(num_transactions)
+            .map_err(|error| Error::UnexpectedError(error.to_string()))
+    }
+
+    fn get_state_snapshot_receiver(
+        &self,
+        version: u64,
+        expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>, Error> {
+        self.storage_writer
+            .get_state_snapshot_receiver(version, expected_root_hash)
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to create state snapshot receiver: {}",
+                    error
+                ))
+            })
+    }
+
+    fn finalize_state_snapshot(
+        &mut self,
+        version: u64,
+        output_with_proof: TransactionListWithProof,
+        frozen_subtrees: Vec<HashValue>,
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        self.storage_writer
+            .finalize_state_snapshot(
+                version,
+                output_with_proof,
+                &frozen_subtrees,
+                &ledger_info_with_sigs,
+            )
+            .map_err(|error| {
+                Error::UnexpectedError(format!("Failed to finalize state snapshot: {}", error))
+            })?;
+
+        // The configs might have changed in any way since the last ones we know of, so every
+        // subscriber is notified.
+        let new_configs = Self::fetch_all_configs(&*self.storage)?;
+        for subscription in self.reconfig_subscriptions.iter_mut() {
+            subscription.publish(new_configs.clone()).map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to publish on-chain configs to subscription {}: {}",
+                    subscription.name, error
+                ))
+            })?;
+        }
+        self.on_chain_configs = new_configs;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -629,7 +750,7 @@ mod tests {
         // Create test diem database
         let db_path = diem_temppath::TempPath::new();
         db_path.create_as_dir().unwrap();
-        let (db, db_rw) = DbReaderWriter::wrap(DiemDB::new_for_test(db_path.path()));
+        let db_rw = DbReaderWriter::new(DiemDB::new_for_test(db_path.path()));
 
         // Boostrap the genesis transaction
         let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
@@ -637,8 +758,8 @@ mod tests {
 
         // Create executor proxy with given subscription
         let block_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
-        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw));
-        let executor_proxy = ExecutorProxy::new(db, chunk_executor, vec![subscription]);
+        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
+        let executor_proxy = ExecutorProxy::new(db_rw, chunk_executor, vec![subscription]);
 
         // Verify initial reconfiguration notification is sent
         assert!(
@@ -7,6 +7,7 @@
 //! Used for node restarts, network partitions, full node syncs
 #![recursion_limit = "1024"]
 
+pub mod account_state_chunk;
 pub mod bootstrapper;
 pub mod chunk_request;
 pub mod chunk_response;
@@ -102,6 +102,9 @@ pub enum LogEntry {
     ProcessChunkRequest,
     ProcessChunkResponse,
     ProcessChunkMessage,
+    ProcessAccountStateChunkRequest,
+    ProcessAccountStateChunkResponse,
+    StateSnapshot,
     NetworkError,
     EpochChange,
     CommitFlow,
@@ -140,6 +143,10 @@ pub enum LogEvent {
     PastEpochRequested,
     DeliverChunk,
 
+    // StateSnapshot events
+    ApplyAccountStateChunkSuccess,
+    ApplyAccountStateChunkFail,
+
     // Multicast network events
     Failover,
     Recover,
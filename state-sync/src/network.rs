@@ -4,7 +4,11 @@
 //! Interface between State Sync and Network layers.
 
 use crate::{
-    chunk_request::GetChunkRequest, chunk_response::GetChunkResponse, counters, error::Error,
+    account_state_chunk::{GetAccountStateChunkRequest, GetAccountStateChunkResponse},
+    chunk_request::GetChunkRequest,
+    chunk_response::GetChunkResponse,
+    counters,
+    error::Error,
 };
 use channel::message_queues::QueueStyle;
 use diem_metrics::IntCounterVec;
@@ -23,6 +27,8 @@ const STATE_SYNC_MAX_BUFFER_SIZE: usize = 1;
 pub enum StateSyncMessage {
     GetChunkRequest(Box<GetChunkRequest>),
     GetChunkResponse(Box<GetChunkResponse>),
+    GetAccountStateChunkRequest(Box<GetAccountStateChunkRequest>),
+    GetAccountStateChunkResponse(Box<GetAccountStateChunkResponse>),
 }
 
 /// The interface from Network to StateSync layer.
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    account_state_chunk::GetAccountStateChunkRequest,
     chunk_request::GetChunkRequest,
     counters,
     error::Error,
@@ -257,7 +258,32 @@ impl RequestManager {
 
     pub fn send_chunk_request(&mut self, req: GetChunkRequest) -> Result<(), Error> {
         let log = LogSchema::new(LogEntry::SendChunkRequest).chunk_request(req.clone());
+        let known_version = req.known_version;
+        let msg = StateSyncMessage::GetChunkRequest(Box::new(req));
+        self.send_request(known_version, msg, log)
+    }
 
+    /// Sends a request for a chunk of an account state snapshot. While applying the snapshot the
+    /// local synced version doesn't move, so the request is tracked under `known_version` like a
+    /// chunk request: timeouts and peer scores are handled the same way.
+    pub fn send_account_state_chunk_request(
+        &mut self,
+        known_version: u64,
+        req: GetAccountStateChunkRequest,
+    ) -> Result<(), Error> {
+        let log = LogSchema::new(LogEntry::SendChunkRequest)
+            .version(known_version)
+            .request_version(req.version);
+        let msg = StateSyncMessage::GetAccountStateChunkRequest(Box::new(req));
+        self.send_request(known_version, msg, log)
+    }
+
+    fn send_request(
+        &mut self,
+        known_version: u64,
+        msg: StateSyncMessage,
+        log: LogSchema,
+    ) -> Result<(), Error> {
         let peers = self.pick_peers();
         if peers.is_empty() {
             warn!(log.event(LogEvent::MissingPeers));
@@ -266,13 +292,12 @@ impl RequestManager {
             ));
         }
 
-        let req_info = self.add_request(req.known_version, peers.clone());
+        let req_info = self.add_request(known_version, peers.clone());
         debug!(log
             .clone()
             .event(LogEvent::ChunkRequestInfo)
             .chunk_req_info(&req_info));
 
-        let msg = StateSyncMessage::GetChunkRequest(Box::new(req));
         let mut failed_peer_sends = vec![];
 
         for peer in peers {
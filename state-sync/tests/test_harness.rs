@@ -16,6 +16,7 @@ use diem_transaction_builder::stdlib::encode_peer_to_peer_with_metadata_script;
 use diem_types::{
     account_address::AccountAddress,
     account_config::xus_tag,
+    account_state_blob::AccountStateBlob,
     block_info::BlockInfo,
     chain_id::ChainId,
     contract_event::ContractEvent,
@@ -72,6 +73,7 @@ use std::{
     ops::DerefMut,
     sync::Arc,
 };
+use storage_interface::{AccountStateChunkWithProof, StateSnapshotReceiver};
 use tokio::runtime::Runtime;
 use vm_genesis::GENESIS_KEYPAIR;
 
@@ -884,4 +886,47 @@ impl ExecutorProxyTrait for MockExecutorProxy {
     ) -> Result<(), Error> {
         Ok(())
     }
+
+    fn get_account_state_chunk(
+        &self,
+        _version: u64,
+        _last_key: Option<HashValue>,
+        _limit: u64,
+    ) -> Result<AccountStateChunkWithProof, Error> {
+        // State snapshots aren't supported by the mock storage
+        Err(Error::UnexpectedError(
+            "Account state chunks aren't supported by the mock executor!".into(),
+        ))
+    }
+
+    fn get_accumulator_frozen_subtrees(
+        &self,
+        _num_transactions: u64,
+    ) -> Result<Vec<HashValue>, Error> {
+        Err(Error::UnexpectedError(
+            "Accumulator frozen subtrees aren't supported by the mock executor!".into(),
+        ))
+    }
+
+    fn get_state_snapshot_receiver(
+        &self,
+        _version: u64,
+        _expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>, Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots aren't supported by the mock executor!".into(),
+        ))
+    }
+
+    fn finalize_state_snapshot(
+        &mut self,
+        _version: u64,
+        _output_with_proof: TransactionListWithProof,
+        _frozen_subtrees: Vec<HashValue>,
+        _ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots aren't supported by the mock executor!".into(),
+        ))
+    }
 }
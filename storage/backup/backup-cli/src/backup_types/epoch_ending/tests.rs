@@ -106,6 +106,7 @@ fn end_to_end() {
         &tgt_db_dir,
         true, /* read_only */
         None, /* pruner */
+        None, /* ledger pruner */
         RocksdbConfig::default(),
     )
     .unwrap();
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+    backup_types::state_snapshot::manifest::{
+        StateSnapshotBackup, StateSnapshotChunk, StateSnapshotChunkBackup,
+        StateSnapshotDeltaBackup, StateSnapshotDeltaChunk, StateSnapshotManifest,
+    },
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
@@ -10,7 +13,7 @@ use crate::{
         should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
     },
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use bytes::Bytes;
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
@@ -19,7 +22,7 @@ use diem_types::{
     proof::TransactionInfoWithProof, transaction::Version,
 };
 use once_cell::sync::Lazy;
-use std::{convert::TryInto, str::FromStr, sync::Arc};
+use std::{collections::VecDeque, convert::TryInto, str::FromStr, sync::Arc};
 use structopt::StructOpt;
 use tokio::io::AsyncWriteExt;
 
@@ -30,13 +33,22 @@ pub struct StateSnapshotBackupOpt {
         help = "Version at which a state snapshot to be taken."
     )]
     pub version: Version,
+    #[structopt(
+        long = "base-state-manifest",
+        help = "Manifest of an earlier state snapshot. If set, an incremental snapshot is taken, \
+        which only carries the accounts changed since the base snapshot."
+    )]
+    pub base_manifest: Option<FileHandle>,
 }
 
 pub struct StateSnapshotBackupController {
     version: Version,
+    base_manifest: Option<FileHandle>,
     max_chunk_size: usize,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
+    /// Chunks written by an interrupted backup of the same snapshot, to be reused.
+    completed_chunks: Vec<StateSnapshotChunkBackup>,
 }
 
 impl StateSnapshotBackupController {
@@ -48,16 +60,26 @@ impl StateSnapshotBackupController {
     ) -> Self {
         Self {
             version: opt.version,
+            base_manifest: opt.base_manifest,
             max_chunk_size: global_opt.max_chunk_size,
             client,
             storage,
+            completed_chunks: Vec::new(),
         }
     }
 
+    /// Resumes an interrupted backup of the same snapshot, reusing the chunks it had written.
+    pub fn resume_from(mut self, completed_chunks: Vec<StateSnapshotChunkBackup>) -> Self {
+        self.completed_chunks = completed_chunks;
+        self
+    }
+
     pub async fn run(self) -> Result<FileHandle> {
         info!(
-            "State snapshot backup started, for version {}.",
+            "State snapshot backup started, for version {}. Base manifest: {:?}, chunks already written: {}.",
             self.version,
+            self.base_manifest,
+            self.completed_chunks.len(),
         );
         let ret = self
             .run_impl()
@@ -68,62 +90,237 @@ impl StateSnapshotBackupController {
     }
 
     async fn run_impl(self) -> Result<FileHandle> {
+        match &self.base_manifest {
+            Some(base_manifest) => {
+                let base: StateSnapshotManifest =
+                    self.storage.load_json_file(base_manifest).await?;
+                self.backup_delta(base_manifest, base).await
+            }
+            None => self.backup_full().await,
+        }
+    }
+
+    async fn backup_full(&self) -> Result<FileHandle> {
         let backup_handle = self
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
 
-        let mut chunks = vec![];
+        let mut chunks = self
+            .completed_chunks
+            .iter()
+            .map(|chunk| match chunk {
+                StateSnapshotChunkBackup::Full(c) => Ok(c.clone()),
+                StateSnapshotChunkBackup::Delta(_) => {
+                    bail!("Can't resume a full state snapshot from incremental chunks.")
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (start_key, mut current_idx) = match chunks.last() {
+            Some(chunk) => (Some(chunk.last_key), chunk.last_idx + 1),
+            None => (None, 0),
+        };
 
-        let mut state_snapshot_file = self.client.get_state_snapshot(self.version).await?;
-        let mut prev_record_bytes = state_snapshot_file
-            .read_record_bytes()
-            .await?
-            .ok_or_else(|| anyhow!("State is empty."))?;
-        let mut chunk_bytes = (prev_record_bytes.len() as u32).to_be_bytes().to_vec();
-        chunk_bytes.extend(&prev_record_bytes);
-        let mut chunk_first_key = Self::parse_key(&prev_record_bytes)?;
-        let mut current_idx: usize = 0;
-        let mut chunk_first_idx: usize = 0;
+        let mut state_snapshot_file = self
+            .client
+            .get_state_snapshot_from(self.version, start_key.unwrap_or_else(HashValue::zero))
+            .await?;
+        let mut chunk_bytes = vec![];
+        let mut chunk_first_key = HashValue::zero();
+        let mut chunk_first_idx = current_idx;
+        let mut prev_key = HashValue::zero();
 
         while let Some(record_bytes) = state_snapshot_file.read_record_bytes().await? {
+            let key = Self::parse_key(&record_bytes)?;
+            if Some(key) == start_key {
+                // Already in the last completed chunk.
+                continue;
+            }
             if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
                         &chunk_bytes,
                         chunk_first_idx,
-                        current_idx,
+                        current_idx - 1,
                         chunk_first_key,
-                        Self::parse_key(&prev_record_bytes)?,
+                        prev_key,
                     )
                     .await?;
+                self.record_chunk(
+                    None,
+                    chunks.len(),
+                    StateSnapshotChunkBackup::Full(chunk.clone()),
+                )
+                .await;
                 chunks.push(chunk);
                 chunk_bytes = vec![];
-                chunk_first_idx = current_idx + 1;
-                chunk_first_key = Self::parse_key(&record_bytes)?;
+            }
+            if chunk_bytes.is_empty() {
+                chunk_first_idx = current_idx;
+                chunk_first_key = key;
             }
 
             current_idx += 1;
             chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
             chunk_bytes.extend(&record_bytes);
-            prev_record_bytes = record_bytes;
+            prev_key = key;
+        }
+
+        if !chunk_bytes.is_empty() {
+            let chunk = self
+                .write_chunk(
+                    &backup_handle,
+                    &chunk_bytes,
+                    chunk_first_idx,
+                    current_idx - 1,
+                    chunk_first_key,
+                    prev_key,
+                )
+                .await?;
+            self.record_chunk(
+                None,
+                chunks.len(),
+                StateSnapshotChunkBackup::Full(chunk.clone()),
+            )
+            .await;
+            chunks.push(chunk);
         }
+        ensure!(!chunks.is_empty(), "State is empty.");
+
+        let (root_hash, proof) = self.write_state_root_proof(&backup_handle).await?;
+        self.write_manifest(
+            &backup_handle,
+            StateSnapshotManifest::Full(StateSnapshotBackup {
+                version: self.version,
+                root_hash,
+                chunks,
+                proof,
+            }),
+        )
+        .await
+    }
+
+    async fn backup_delta(
+        &self,
+        base_manifest: &FileHandle,
+        base: StateSnapshotManifest,
+    ) -> Result<FileHandle> {
+        let base_version = base.version();
+        ensure!(
+            base_version < self.version,
+            "Base state snapshot at version {} is not older than version {}.",
+            base_version,
+            self.version,
+        );
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&self.delta_backup_name(base_version))
+            .await?;
+
+        let mut chunks = self
+            .completed_chunks
+            .iter()
+            .map(|chunk| match chunk {
+                StateSnapshotChunkBackup::Delta(c) => Ok(c.clone()),
+                StateSnapshotChunkBackup::Full(_) => {
+                    bail!("Can't resume an incremental state snapshot from full chunks.")
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let start_key = chunks.last().map(|chunk| chunk.last_key);
 
-        assert!(!chunk_bytes.is_empty());
-        let chunk = self
-            .write_chunk(
+        // Chunks are cut at each chunk boundary of the base snapshot, so that restoring a chunk
+        // only needs the chunks of the base snapshot in the same key range.
+        let mut boundaries: VecDeque<_> = base
+            .chunk_last_keys()
+            .into_iter()
+            .filter(|key| Some(*key) > start_key)
+            .collect();
+
+        let mut delta_file = self
+            .client
+            .get_state_snapshot_delta(
+                base_version,
+                self.version,
+                start_key.unwrap_or_else(HashValue::zero),
+            )
+            .await?;
+        let mut chunk_bytes = vec![];
+        let mut prev_key = HashValue::zero();
+
+        while let Some(record_bytes) = delta_file.read_record_bytes().await? {
+            let key = Self::parse_key(&record_bytes)?;
+            if Some(key) == start_key {
+                // Already in the last completed chunk.
+                continue;
+            }
+            while let Some(boundary) = boundaries.front().copied() {
+                if key <= boundary {
+                    break;
+                }
+                self.add_delta_chunk(
+                    &backup_handle,
+                    base_version,
+                    &mut chunks,
+                    &mut chunk_bytes,
+                    boundary,
+                )
+                .await?;
+                boundaries.pop_front();
+            }
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                self.add_delta_chunk(
+                    &backup_handle,
+                    base_version,
+                    &mut chunks,
+                    &mut chunk_bytes,
+                    prev_key,
+                )
+                .await?;
+            }
+
+            chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            prev_key = key;
+        }
+
+        for boundary in boundaries {
+            self.add_delta_chunk(
                 &backup_handle,
-                &chunk_bytes,
-                chunk_first_idx,
-                current_idx,
-                chunk_first_key,
-                Self::parse_key(&prev_record_bytes)?,
+                base_version,
+                &mut chunks,
+                &mut chunk_bytes,
+                boundary,
             )
             .await?;
-        chunks.push(chunk);
+        }
+        if !chunk_bytes.is_empty() {
+            // Accounts created after the last account in the base snapshot.
+            self.add_delta_chunk(
+                &backup_handle,
+                base_version,
+                &mut chunks,
+                &mut chunk_bytes,
+                prev_key,
+            )
+            .await?;
+        }
+        ensure!(!chunks.is_empty(), "State is empty.");
 
-        self.write_manifest(&backup_handle, chunks).await
+        let (root_hash, proof) = self.write_state_root_proof(&backup_handle).await?;
+        self.write_manifest(
+            &backup_handle,
+            StateSnapshotManifest::Delta(StateSnapshotDeltaBackup {
+                version: self.version,
+                root_hash,
+                base_version,
+                base_manifest: base_manifest.clone(),
+                chunks,
+                proof,
+            }),
+        )
+        .await
     }
 }
 
@@ -132,6 +329,10 @@ impl StateSnapshotBackupController {
         format!("state_ver_{}", self.version)
     }
 
+    fn delta_backup_name(&self, base_version: Version) -> String {
+        format!("state_ver_{}_base_{}", self.version, base_version)
+    }
+
     fn manifest_name() -> &'static ShellSafeName {
         static NAME: Lazy<ShellSafeName> =
             Lazy::new(|| ShellSafeName::from_str("state.manifest").unwrap());
@@ -154,6 +355,14 @@ impl StateSnapshotBackupController {
             .unwrap()
     }
 
+    fn delta_chunk_name(chunk_idx: usize) -> ShellSafeName {
+        format!("delta_{}.chunk", chunk_idx).try_into().unwrap()
+    }
+
+    fn delta_chunk_proof_name(chunk_idx: usize) -> ShellSafeName {
+        format!("delta_{}.proof", chunk_idx).try_into().unwrap()
+    }
+
     fn parse_key(record: &Bytes) -> Result<HashValue> {
         let (key, _): (HashValue, AccountStateBlob) = bcs::from_bytes(record)?;
         Ok(key)
@@ -174,19 +383,13 @@ impl StateSnapshotBackupController {
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
         chunk_file.shutdown().await?;
-        let (proof_handle, mut proof_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
+        let proof_handle = self
+            .write_chunk_proof(
+                backup_handle,
+                &Self::chunk_proof_name(first_idx, last_idx),
+                last_key,
+            )
             .await?;
-        tokio::io::copy(
-            &mut self
-                .client
-                .get_account_range_proof(last_key, self.version)
-                .await?,
-            &mut proof_file,
-        )
-        .await?;
-        proof_file.shutdown().await?;
 
         Ok(StateSnapshotChunk {
             first_idx,
@@ -198,11 +401,102 @@ impl StateSnapshotBackupController {
         })
     }
 
-    async fn write_manifest(
+    /// Writes the accounts in `chunk_bytes`, which can be empty, as a chunk ending at `last_key`,
+    /// and starts the next chunk.
+    async fn add_delta_chunk(
         &self,
         backup_handle: &BackupHandleRef,
-        chunks: Vec<StateSnapshotChunk>,
+        base_version: Version,
+        chunks: &mut Vec<StateSnapshotDeltaChunk>,
+        chunk_bytes: &mut Vec<u8>,
+        last_key: HashValue,
+    ) -> Result<()> {
+        let chunk_idx = chunks.len();
+        let blobs = if chunk_bytes.is_empty() {
+            None
+        } else {
+            let (chunk_handle, mut chunk_file) = self
+                .storage
+                .create_for_write(backup_handle, &Self::delta_chunk_name(chunk_idx))
+                .await?;
+            chunk_file.write_all(&chunk_bytes).await?;
+            chunk_file.shutdown().await?;
+            Some(chunk_handle)
+        };
+        let proof = self
+            .write_chunk_proof(
+                backup_handle,
+                &Self::delta_chunk_proof_name(chunk_idx),
+                last_key,
+            )
+            .await?;
+
+        let chunk = StateSnapshotDeltaChunk {
+            last_key,
+            blobs,
+            proof,
+        };
+        self.record_chunk(
+            Some(base_version),
+            chunk_idx,
+            StateSnapshotChunkBackup::Delta(chunk.clone()),
+        )
+        .await;
+        chunks.push(chunk);
+        chunk_bytes.clear();
+        Ok(())
+    }
+
+    async fn write_chunk_proof(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+        last_key: HashValue,
     ) -> Result<FileHandle> {
+        let (proof_handle, mut proof_file) =
+            self.storage.create_for_write(backup_handle, name).await?;
+        tokio::io::copy(
+            &mut self
+                .client
+                .get_account_range_proof(last_key, self.version)
+                .await?,
+            &mut proof_file,
+        )
+        .await?;
+        proof_file.shutdown().await?;
+        Ok(proof_handle)
+    }
+
+    /// Records a written chunk in the metadata, so that the backup can resume from it if
+    /// interrupted. Failing to do so only loses that, so the error is not propagated.
+    async fn record_chunk(
+        &self,
+        base_version: Option<Version>,
+        chunk_idx: usize,
+        chunk: StateSnapshotChunkBackup,
+    ) {
+        let metadata =
+            Metadata::new_state_snapshot_chunk(self.version, base_version, chunk_idx, chunk);
+        let res = match metadata.to_text_line() {
+            Ok(line) => {
+                self.storage
+                    .save_metadata_line(&metadata.name(), &line)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = res {
+            warn!(
+                "Failed to record chunk {} of state snapshot at version {}: {}",
+                chunk_idx, self.version, e
+            );
+        }
+    }
+
+    async fn write_state_root_proof(
+        &self,
+        backup_handle: &BackupHandleRef,
+    ) -> Result<(HashValue, FileHandle)> {
         let proof_bytes = self.client.get_state_root_proof(self.version).await?;
         let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
             bcs::from_bytes(&proof_bytes)?;
@@ -214,13 +508,14 @@ impl StateSnapshotBackupController {
         proof_file.write_all(&proof_bytes).await?;
         proof_file.shutdown().await?;
 
-        let manifest = StateSnapshotBackup {
-            version: self.version,
-            root_hash: txn_info.transaction_info().state_root_hash(),
-            chunks,
-            proof: proof_handle,
-        };
+        Ok((txn_info.transaction_info().state_root_hash(), proof_handle))
+    }
 
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        manifest: StateSnapshotManifest,
+    ) -> Result<FileHandle> {
         let (manifest_handle, mut manifest_file) = self
             .storage
             .create_for_write(&backup_handle, Self::manifest_name())
@@ -230,7 +525,16 @@ impl StateSnapshotBackupController {
             .await?;
         manifest_file.shutdown().await?;
 
-        let metadata = Metadata::new_state_snapshot_backup(self.version, manifest_handle.clone());
+        let metadata = match &manifest {
+            StateSnapshotManifest::Full(_) => {
+                Metadata::new_state_snapshot_backup(self.version, manifest_handle.clone())
+            }
+            StateSnapshotManifest::Delta(m) => Metadata::new_state_snapshot_delta_backup(
+                self.version,
+                m.base_version,
+                manifest_handle.clone(),
+            ),
+        };
         self.storage
             .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
             .await?;
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 /// A chunk of a state snapshot manifest, representing accounts in the key range
 /// [`first_key`, `last_key`] (right side inclusive).
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct StateSnapshotChunk {
     /// index of the first account in this chunk over all accounts.
     pub first_idx: usize,
@@ -47,3 +47,89 @@ pub struct StateSnapshotBackup {
     /// limits the requirement on such `EpochStateBackup` to no older than the same epoch.
     pub proof: FileHandle,
 }
+
+/// A chunk of an incremental state snapshot manifest, representing the accounts changed since the
+/// base snapshot in the key range (`last_key` of the previous chunk, `last_key`] (right side
+/// inclusive).
+#[derive(Clone, Deserialize, Serialize)]
+pub struct StateSnapshotDeltaChunk {
+    /// key of the last account in the range, which is not necessarily changed.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, account_state_blob)`, `None` if no account in the range changed.
+    pub blobs: Option<FileHandle>,
+    /// BCS serialized `SparseMerkleRangeProof` that proves all accounts up to `last_key` add up
+    /// to the root hash indicated in the backup (`StateSnapshotDeltaBackup::root_hash`).
+    pub proof: FileHandle,
+}
+
+/// Incremental state snapshot backup manifest, representing a complete state view at specified
+/// version by the accounts changed since a base snapshot.
+///
+/// The chunks are cut at least at the `last_key` of each chunk in the base snapshot, so a chunk
+/// of the state at `version` can be materialized from a chunk of this and the records of the base
+/// snapshot in the same key range.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDeltaBackup {
+    /// Version at which this state snapshot is taken.
+    pub version: Version,
+    /// Hash of the state tree root.
+    pub root_hash: HashValue,
+    /// Version of the base snapshot.
+    pub base_version: Version,
+    /// Manifest of the base snapshot, which can itself be incremental.
+    pub base_manifest: FileHandle,
+    /// Changed account blobs in chunks.
+    pub chunks: Vec<StateSnapshotDeltaChunk>,
+    /// BCS serialized `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, see
+    /// `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
+
+/// A state snapshot backup manifest of either kind.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StateSnapshotManifest {
+    // N.B. `Delta` goes first, since a delta manifest deserializes as a `StateSnapshotBackup` as
+    // well, with the extra fields ignored.
+    Delta(StateSnapshotDeltaBackup),
+    Full(StateSnapshotBackup),
+}
+
+impl StateSnapshotManifest {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::Delta(m) => m.version,
+            Self::Full(m) => m.version,
+        }
+    }
+
+    pub fn root_hash(&self) -> HashValue {
+        match self {
+            Self::Delta(m) => m.root_hash,
+            Self::Full(m) => m.root_hash,
+        }
+    }
+
+    pub fn proof(&self) -> &FileHandle {
+        match self {
+            Self::Delta(m) => &m.proof,
+            Self::Full(m) => &m.proof,
+        }
+    }
+
+    /// The `last_key` of each chunk, in order.
+    pub fn chunk_last_keys(&self) -> Vec<HashValue> {
+        match self {
+            Self::Delta(m) => m.chunks.iter().map(|c| c.last_key).collect(),
+            Self::Full(m) => m.chunks.iter().map(|c| c.last_key).collect(),
+        }
+    }
+}
+
+/// A chunk of a state snapshot backup of either kind.
+#[derive(Clone, Deserialize, Serialize)]
+pub enum StateSnapshotChunkBackup {
+    Full(StateSnapshotChunk),
+    Delta(StateSnapshotDeltaChunk),
+}
@@ -3,7 +3,7 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotManifest,
     },
     metrics::{
         restore::{
@@ -27,7 +27,10 @@ use diem_types::{
     account_state_blob::AccountStateBlob, ledger_info::LedgerInfoWithSignatures,
     proof::TransactionInfoWithProof, transaction::Version,
 };
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -93,15 +96,15 @@ impl StateSnapshotRestoreController {
             return Ok(());
         }
 
-        let manifest: StateSnapshotBackup =
+        let manifest: StateSnapshotManifest =
             self.storage.load_json_file(&self.manifest_handle).await?;
         let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
-            self.storage.load_bcs_file(&manifest.proof).await?;
-        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+            self.storage.load_bcs_file(manifest.proof()).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version())?;
         ensure!(
-            txn_info_with_proof.transaction_info().state_root_hash() == manifest.root_hash,
+            txn_info_with_proof.transaction_info().state_root_hash() == manifest.root_hash(),
             "Root hash mismatch with that in proof. root hash: {}, expected: {}",
-            manifest.root_hash,
+            manifest.root_hash(),
             txn_info_with_proof.transaction_info().state_root_hash(),
         );
         if let Some(epoch_history) = self.epoch_history.as_ref() {
@@ -110,7 +113,7 @@ impl StateSnapshotRestoreController {
 
         let mut receiver = self
             .run_mode
-            .get_state_restore_receiver(self.version, manifest.root_hash)?;
+            .get_state_restore_receiver(self.version, manifest.root_hash())?;
 
         let (ver_gauge, tgt_leaf_idx, leaf_idx) = if self.run_mode.is_verify() {
             (
@@ -128,19 +131,80 @@ impl StateSnapshotRestoreController {
 
         // FIXME update counters
         ver_gauge.set(self.version as i64);
-        tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
-        for chunk in manifest.chunks {
-            let blobs = self.read_account_state_chunk(chunk.blobs).await?;
-            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
-
-            receiver.add_chunk(blobs, proof)?;
-            leaf_idx.set(chunk.last_idx as i64);
+        match manifest {
+            StateSnapshotManifest::Full(manifest) => {
+                tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
+                for chunk in manifest.chunks {
+                    let blobs = self.read_account_state_chunk(chunk.blobs).await?;
+                    let proof = self.storage.load_bcs_file(&chunk.proof).await?;
+
+                    receiver.add_chunk(blobs, proof)?;
+                    leaf_idx.set(chunk.last_idx as i64);
+                }
+            }
+            StateSnapshotManifest::Delta(manifest) => {
+                // Layers of the state, from the full snapshot at the bottom to the incremental
+                // ones on top of it, the last being `manifest`.
+                let mut layers = VecDeque::new();
+                let mut base_manifest = manifest.base_manifest.clone();
+                loop {
+                    let base: StateSnapshotManifest =
+                        self.storage.load_json_file(&base_manifest).await?;
+                    layers.push_front(StateSnapshotLayer::new(&base));
+                    match base {
+                        StateSnapshotManifest::Delta(base) => base_manifest = base.base_manifest,
+                        StateSnapshotManifest::Full(_) => break,
+                    }
+                }
+                layers.push_back(StateSnapshotLayer::new(&StateSnapshotManifest::Delta(
+                    manifest,
+                )));
+                let top_chunks = layers.back().map_or(0, |l| l.chunks.len());
+
+                tgt_leaf_idx.set(top_chunks as i64);
+                for chunk_idx in 0..top_chunks {
+                    let (last_key, proof) = layers.back().unwrap().chunks[chunk_idx].clone();
+                    // Upper layers override lower ones.
+                    let mut blobs = BTreeMap::new();
+                    for layer in layers.iter_mut() {
+                        blobs.extend(self.read_layer_until(layer, last_key).await?);
+                    }
+                    let proof = self.storage.load_bcs_file(&proof).await?;
+
+                    receiver.add_chunk(blobs.into_iter().collect(), proof)?;
+                    leaf_idx.set(chunk_idx as i64);
+                }
+            }
         }
 
         receiver.finish()?;
         Ok(())
     }
 
+    /// Takes the records in `layer` up to `last_key` (inclusive), which are not taken yet.
+    async fn read_layer_until(
+        &self,
+        layer: &mut StateSnapshotLayer,
+        last_key: HashValue,
+    ) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+        let mut records = Vec::new();
+        loop {
+            while let Some((key, _)) = layer.buffered.front() {
+                if *key > last_key {
+                    return Ok(records);
+                }
+                records.push(layer.buffered.pop_front().unwrap());
+            }
+            match layer.blobs.pop_front() {
+                Some(Some(file_handle)) => {
+                    layer.buffered = self.read_account_state_chunk(file_handle).await?.into();
+                }
+                Some(None) => (),
+                None => return Ok(records),
+            }
+        }
+    }
+
     async fn read_account_state_chunk(
         &self,
         file_handle: FileHandle,
@@ -156,3 +220,35 @@ impl StateSnapshotRestoreController {
         Ok(chunk)
     }
 }
+
+/// A state snapshot read in key order, chunk by chunk.
+struct StateSnapshotLayer {
+    /// `last_key` and proof of each chunk.
+    chunks: Vec<(HashValue, FileHandle)>,
+    /// Blobs files of the chunks not read yet.
+    blobs: VecDeque<Option<FileHandle>>,
+    /// Records read but not taken yet.
+    buffered: VecDeque<(HashValue, AccountStateBlob)>,
+}
+
+impl StateSnapshotLayer {
+    fn new(manifest: &StateSnapshotManifest) -> Self {
+        let (chunks, blobs) = match manifest {
+            StateSnapshotManifest::Full(m) => m
+                .chunks
+                .iter()
+                .map(|c| ((c.last_key, c.proof.clone()), Some(c.blobs.clone())))
+                .unzip(),
+            StateSnapshotManifest::Delta(m) => m
+                .chunks
+                .iter()
+                .map(|c| ((c.last_key, c.proof.clone()), c.blobs.clone()))
+                .unzip(),
+        };
+        Self {
+            chunks,
+            blobs,
+            buffered: VecDeque::new(),
+        }
+    }
+}
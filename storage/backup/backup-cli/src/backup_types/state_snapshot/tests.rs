@@ -4,11 +4,14 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        manifest::{StateSnapshotChunkBackup, StateSnapshotManifest},
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
+    metadata::Metadata,
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -43,7 +46,10 @@ fn end_to_end() {
     let manifest_handle = rt
         .block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { version },
+                StateSnapshotBackupOpt {
+                    version,
+                    base_manifest: None,
+                },
                 GlobalBackupOpt {
                     max_chunk_size: 500,
                 },
@@ -81,6 +87,131 @@ fn end_to_end() {
         &tgt_db_dir,
         true, /* read_only */
         None, /* pruner */
+        None, /* ledger pruner */
+        RocksdbConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        tgt_db
+            .get_latest_tree_state()
+            .unwrap()
+            .account_state_root_hash,
+        state_root_hash,
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn incremental_end_to_end() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_tree_state = src_db.get_latest_tree_state().unwrap();
+    let version = latest_tree_state.num_transactions - 1;
+    let state_root_hash = latest_tree_state.account_state_root_hash;
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let backup = |version, base_manifest, completed_chunks| {
+        rt.block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    version,
+                    base_manifest,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .resume_from(completed_chunks)
+            .run(),
+        )
+        .unwrap()
+    };
+
+    // A full snapshot at version 0, with a chain of incremental ones on top of it.
+    let mut manifest_handle = backup(0, None, vec![]);
+    let mut base_version = 0;
+    for &delta_version in &[version / 2, version] {
+        if delta_version > base_version {
+            manifest_handle = backup(delta_version, Some(manifest_handle), vec![]);
+            base_version = delta_version;
+        }
+    }
+
+    // Resume the last snapshot from half of its chunks. An interrupted snapshot hasn't saved its
+    // metadata yet, so drop what the completed one saved.
+    let manifest: StateSnapshotManifest =
+        rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    std::fs::remove_file(
+        backup_dir.path().join("metadata").join(
+            Metadata::new_state_snapshot_backup(version, manifest_handle)
+                .name()
+                .as_ref(),
+        ),
+    )
+    .unwrap();
+    let (base_manifest, completed_chunks) = match manifest {
+        StateSnapshotManifest::Full(m) => (
+            None,
+            m.chunks
+                .into_iter()
+                .map(StateSnapshotChunkBackup::Full)
+                .collect::<Vec<_>>(),
+        ),
+        StateSnapshotManifest::Delta(m) => (
+            Some(m.base_manifest),
+            m.chunks
+                .into_iter()
+                .map(StateSnapshotChunkBackup::Delta)
+                .collect::<Vec<_>>(),
+        ),
+    };
+    let num_completed = completed_chunks.len() / 2;
+    let manifest_handle = backup(
+        version,
+        base_manifest,
+        completed_chunks.into_iter().take(num_completed).collect(),
+    );
+
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle,
+                version: PRE_GENESIS_VERSION,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: None, // max
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            store,
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = DiemDB::open(
+        &tgt_db_dir,
+        true, /* read_only */
+        None, /* pruner */
+        None, /* ledger pruner */
         RocksdbConfig::default(),
     )
     .unwrap();
@@ -82,7 +82,10 @@ fn test_end_to_end_impl(d: TestData) {
     let state_snapshot_manifest = d.state_snapshot_ver.map(|version| {
         rt.block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { version },
+                StateSnapshotBackupOpt {
+                    version,
+                    base_manifest: None,
+                },
                 global_backup_opt.clone(),
                 Arc::clone(&client),
                 Arc::clone(&store),
@@ -153,6 +156,7 @@ fn test_end_to_end_impl(d: TestData) {
         &tgt_db_dir,
         false, /* read_only */
         None,  /* pruner */
+        None,  /* ledger pruner */
         RocksdbConfig::default(),
     )
     .unwrap();
@@ -8,7 +8,11 @@ use crate::{
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
-    metadata::cache::MetadataCacheOpt,
+    metadata::{
+        cache::MetadataCacheOpt,
+        view::{MetadataView, UnfinishedStateSnapshot},
+        StateSnapshotBackupMeta,
+    },
     metrics::backup::{
         EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
     },
@@ -23,7 +27,10 @@ use diem_logger::prelude::*;
 use diem_types::transaction::Version;
 use diemdb::backup::backup_handler::DbState;
 use futures::{stream, Future, StreamExt};
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+};
 use structopt::StructOpt;
 use tokio::{
     sync::watch,
@@ -41,6 +48,11 @@ pub struct BackupCoordinatorOpt {
     // here to make it less than two, and easier for eyes.
     #[structopt(long, default_value = "10000000")]
     pub state_snapshot_interval: usize,
+    // An incremental state snapshot only carries the accounts changed since the previous snapshot,
+    // but restoring it reads all snapshots down to the last full one. Defaulting to 0, i.e. all
+    // snapshots are full.
+    #[structopt(long, default_value = "0")]
+    pub num_incremental_state_snapshots: usize,
     // Assuming the network runs at 100 tps, it's 100 * 3600 = 360k transactions per hour, we don't
     // want the backups to lag behind too much. Defaulting to 100k here in case the network is way
     // slower than expected.
@@ -73,6 +85,7 @@ pub struct BackupCoordinator {
     global_opt: GlobalBackupOpt,
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval: usize,
+    num_incremental_state_snapshots: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
 }
@@ -91,19 +104,21 @@ impl BackupCoordinator {
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval: opt.state_snapshot_interval,
+            num_incremental_state_snapshots: opt.num_incremental_state_snapshots,
             transaction_batch_size: opt.transaction_batch_size,
             concurrent_downloads: opt.concurernt_downloads.get(),
         }
     }
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local Diem node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state();
+        .await?;
+        let backup_state = metadata_view.get_storage_state();
+        let state_snapshot_progress = StateSnapshotProgress::new(&metadata_view)?;
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -128,11 +143,7 @@ impl BackupCoordinator {
             )
             .boxed_local();
         let backup_state_snapshots = self
-            .backup_work_stream(
-                backup_state.latest_state_snapshot_version,
-                &rx2,
-                Self::backup_state_snapshot,
-            )
+            .backup_work_stream(state_snapshot_progress, &rx2, Self::backup_state_snapshot)
             .boxed_local();
         let backup_transactions = self
             .backup_work_stream(
@@ -220,35 +231,89 @@ impl BackupCoordinator {
 
     async fn backup_state_snapshot(
         &self,
-        last_snapshot_version_in_backup: Option<Version>,
+        progress: StateSnapshotProgress,
         db_state: DbState,
-    ) -> Result<Option<Version>> {
+    ) -> Result<StateSnapshotProgress> {
+        let last_snapshot_version_in_backup = progress.last_snapshot.as_ref().map(|s| s.version);
         if let Some(version) = last_snapshot_version_in_backup {
             STATE_SNAPSHOT_VERSION.set(version as i64);
         }
-        let next_snapshot_version = get_next_snapshot(
-            last_snapshot_version_in_backup,
-            db_state,
-            self.state_snapshot_interval,
-        );
 
-        if db_state.committed_version < next_snapshot_version {
-            // wait for the next db_state update
-            return Ok(last_snapshot_version_in_backup);
+        let (version, mut base, base_num_deltas, mut completed_chunks) = match progress.unfinished {
+            Some(unfinished) => {
+                // Resume the interrupted snapshot first.
+                (
+                    unfinished.version,
+                    unfinished.base,
+                    unfinished.base_num_deltas,
+                    unfinished.chunks,
+                )
+            }
+            None => {
+                let next_snapshot_version = get_next_snapshot(
+                    last_snapshot_version_in_backup,
+                    db_state,
+                    self.state_snapshot_interval,
+                );
+
+                if db_state.committed_version < next_snapshot_version {
+                    // wait for the next db_state update
+                    return Ok(StateSnapshotProgress {
+                        unfinished: None,
+                        ..progress
+                    });
+                }
+
+                let base = if progress.num_deltas < self.num_incremental_state_snapshots {
+                    progress.last_snapshot
+                } else {
+                    None
+                };
+                (next_snapshot_version, base, progress.num_deltas, vec![])
+            }
+        };
+        if let Some(base_version) = base.as_ref().map(|b| b.version) {
+            // The delta is computed from the state tree at the base version, which the node
+            // might have pruned already. Take a full snapshot instead, from scratch since the
+            // chunks written so far only hold the delta.
+            if !self.client.is_state_readable(base_version).await? {
+                warn!(
+                    "State at version {} is pruned, taking a full snapshot at version {} instead \
+                     of an incremental one.",
+                    base_version, version,
+                );
+                base = None;
+                completed_chunks = vec![];
+            }
         }
 
-        StateSnapshotBackupController::new(
+        let manifest = StateSnapshotBackupController::new(
             StateSnapshotBackupOpt {
-                version: next_snapshot_version,
+                version,
+                base_manifest: base.as_ref().map(|b| b.manifest.clone()),
             },
             self.global_opt.clone(),
             Arc::clone(&self.client),
             Arc::clone(&self.storage),
         )
+        .resume_from(completed_chunks)
         .run()
         .await?;
 
-        Ok(Some(next_snapshot_version))
+        let num_deltas = if base.is_some() {
+            base_num_deltas + 1
+        } else {
+            0
+        };
+        Ok(StateSnapshotProgress {
+            last_snapshot: Some(StateSnapshotBackupMeta {
+                version,
+                manifest,
+                base_version: base.map(|b| b.version),
+            }),
+            num_deltas,
+            unfinished: None,
+        })
     }
 
     async fn backup_transactions(
@@ -293,7 +358,7 @@ impl BackupCoordinator {
         worker: W,
     ) -> impl StreamExt<Item = ()> + 'a
     where
-        S: Copy + Debug + 'a,
+        S: Clone + Debug + 'a,
         W: Worker<'a, S, Fut> + Copy + 'a,
         Fut: Future<Output = Result<S>> + 'a,
     {
@@ -303,7 +368,7 @@ impl BackupCoordinator {
                 rx.changed().await.unwrap();
                 let db_state = *rx.borrow();
                 if let Some(db_state) = db_state {
-                    let next_state = worker(self, s, db_state).await.unwrap_or_else(|e| {
+                    let next_state = worker(self, s.clone(), db_state).await.unwrap_or_else(|e| {
                         warn!("backup failed: {}. Keep trying with state {:?}.", e, s);
                         s
                     });
@@ -329,6 +394,45 @@ where
 {
 }
 
+/// State of the state snapshot work stream.
+#[derive(Clone)]
+struct StateSnapshotProgress {
+    /// The latest state snapshot in the backup storage.
+    last_snapshot: Option<StateSnapshotBackupMeta>,
+    /// Number of incremental snapshots from `last_snapshot` down to the latest full snapshot.
+    num_deltas: usize,
+    /// An interrupted snapshot newer than `last_snapshot`, to be resumed.
+    unfinished: Option<UnfinishedStateSnapshot>,
+}
+
+impl StateSnapshotProgress {
+    fn new(metadata_view: &MetadataView) -> Result<Self> {
+        let last_snapshot = metadata_view.select_latest_state_snapshot();
+        let num_deltas = last_snapshot
+            .as_ref()
+            .map_or(Ok(0), |s| metadata_view.get_state_snapshot_num_deltas(s))?;
+        let unfinished = metadata_view.select_unfinished_state_snapshot()?;
+
+        Ok(Self {
+            last_snapshot,
+            num_deltas,
+            unfinished,
+        })
+    }
+}
+
+impl Debug for StateSnapshotProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "last_snapshot_version: {:?}, num_deltas: {}, unfinished_version: {:?}",
+            self.last_snapshot.as_ref().map(|s| s.version),
+            self.num_deltas,
+            self.unfinished.as_ref().map(|u| u.version),
+        )
+    }
+}
+
 fn get_batch_range(last_in_backup: Option<u64>, batch_size: usize) -> (u64, u64) {
     // say, 7 is already in backup, and we target batches of size 10, we will return (8, 10) in this
     // case, so 8, 9, 10 will be in this batch, and next time the backup worker will pass in 10,
@@ -359,8 +463,26 @@ fn get_next_snapshot(last_in_backup: Option<u64>, db_state: DbState, interval: u
 
 #[cfg(test)]
 mod tests {
-    use crate::coordinators::backup::{get_batch_range, get_next_snapshot};
+    use crate::{
+        coordinators::backup::{
+            get_batch_range, get_next_snapshot, BackupCoordinator, BackupCoordinatorOpt,
+            StateSnapshotProgress,
+        },
+        metadata::StateSnapshotBackupMeta,
+        storage::{local_fs::LocalFs, BackupStorage},
+        utils::{
+            backup_service_client::BackupServiceClient,
+            test_utils::{start_local_backup_service, tmp_pruned_db_with_random_content},
+            GlobalBackupOpt,
+        },
+    };
+    use diem_temppath::TempPath;
     use diemdb::backup::backup_handler::DbState;
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+    use structopt::StructOpt;
 
     #[test]
     fn test_get_batch_range() {
@@ -386,4 +508,58 @@ mod tests {
         assert_eq!(get_next_snapshot(Some(0), _state(250), 100), 200);
         assert_eq!(get_next_snapshot(Some(200), _state(250), 100), 300);
     }
+
+    #[test]
+    fn test_backup_state_snapshot_with_pruned_base() {
+        let (_db_dir, db, _blocks) = tmp_pruned_db_with_random_content(0 /* prune_window */);
+        let backup_handler = db.get_backup_handler();
+        let db_state = backup_handler.get_db_state().unwrap().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while backup_handler.is_state_readable(0).unwrap() {
+            assert!(Instant::now() < deadline, "Timeout waiting for the pruner.");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+        let (rt, port) = start_local_backup_service(db);
+        let client = Arc::new(BackupServiceClient::new(format!(
+            "http://localhost:{}",
+            port
+        )));
+        let coordinator = BackupCoordinator::new(
+            BackupCoordinatorOpt::from_iter(&[
+                "backup-coordinator",
+                "--state-snapshot-interval=1",
+                "--num-incremental-state-snapshots=1",
+                "--transaction-batch-size=1",
+            ]),
+            GlobalBackupOpt {
+                max_chunk_size: 500,
+            },
+            client,
+            store,
+        );
+
+        // The base snapshot is never read: its state is gone from the node anyway.
+        let progress = StateSnapshotProgress {
+            last_snapshot: Some(StateSnapshotBackupMeta {
+                version: 0,
+                manifest: "missing_manifest".to_string(),
+                base_version: None,
+            }),
+            num_deltas: 0,
+            unfinished: None,
+        };
+        let progress = rt
+            .block_on(coordinator.backup_state_snapshot(progress, db_state))
+            .unwrap();
+        let snapshot = progress.last_snapshot.unwrap();
+        assert_eq!(snapshot.version, db_state.committed_version);
+        assert_eq!(snapshot.base_version, None);
+        assert_eq!(progress.num_deltas, 0);
+
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
 }
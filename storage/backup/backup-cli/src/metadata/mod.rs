@@ -4,7 +4,10 @@
 pub mod cache;
 pub mod view;
 
-use crate::storage::{FileHandle, ShellSafeName, TextLine};
+use crate::{
+    backup_types::state_snapshot::manifest::StateSnapshotChunkBackup,
+    storage::{FileHandle, ShellSafeName, TextLine},
+};
 use anyhow::Result;
 use diem_types::transaction::Version;
 use serde::{Deserialize, Serialize};
@@ -16,6 +19,7 @@ pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
+    StateSnapshotChunk(StateSnapshotChunkMeta),
 }
 
 impl Metadata {
@@ -36,7 +40,37 @@ impl Metadata {
     }
 
     pub fn new_state_snapshot_backup(version: Version, manifest: FileHandle) -> Self {
-        Self::StateSnapshotBackup(StateSnapshotBackupMeta { version, manifest })
+        Self::StateSnapshotBackup(StateSnapshotBackupMeta {
+            version,
+            manifest,
+            base_version: None,
+        })
+    }
+
+    pub fn new_state_snapshot_delta_backup(
+        version: Version,
+        base_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::StateSnapshotBackup(StateSnapshotBackupMeta {
+            version,
+            manifest,
+            base_version: Some(base_version),
+        })
+    }
+
+    pub fn new_state_snapshot_chunk(
+        version: Version,
+        base_version: Option<Version>,
+        chunk_idx: usize,
+        chunk: StateSnapshotChunkBackup,
+    ) -> Self {
+        Self::StateSnapshotChunk(StateSnapshotChunkMeta {
+            version,
+            base_version,
+            chunk_idx,
+            chunk,
+        })
     }
 
     pub fn new_transaction_backup(
@@ -60,6 +94,16 @@ impl Metadata {
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version,)
             }
+            Self::StateSnapshotChunk(c) => match c.base_version {
+                Some(base_version) => format!(
+                    "state_snapshot_ver_{}_base_{}_chunk_{}.meta",
+                    c.version, base_version, c.chunk_idx
+                ),
+                None => format!(
+                    "state_snapshot_ver_{}_chunk_{}.meta",
+                    c.version, c.chunk_idx
+                ),
+            },
         }
         .try_into()
         .unwrap()
@@ -79,10 +123,13 @@ pub struct EpochEndingBackupMeta {
     pub manifest: FileHandle,
 }
 
-#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct StateSnapshotBackupMeta {
     pub version: Version,
     pub manifest: FileHandle,
+    /// Version of the base snapshot if this is an incremental snapshot.
+    #[serde(default)]
+    pub base_version: Option<Version>,
 }
 
 #[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -91,3 +138,13 @@ pub struct TransactionBackupMeta {
     pub last_version: Version,
     pub manifest: FileHandle,
 }
+
+/// A chunk written by a state snapshot backup, recorded so that an interrupted backup can resume
+/// from it.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct StateSnapshotChunkMeta {
+    pub version: Version,
+    pub base_version: Option<Version>,
+    pub chunk_idx: usize,
+    pub chunk: StateSnapshotChunkBackup,
+}
@@ -1,8 +1,12 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metadata::{
-    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+use crate::{
+    backup_types::state_snapshot::manifest::StateSnapshotChunkBackup,
+    metadata::{
+        EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, StateSnapshotChunkMeta,
+        TransactionBackupMeta,
+    },
 };
 use anyhow::{anyhow, ensure, Result};
 use diem_types::transaction::Version;
@@ -13,6 +17,7 @@ pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
+    state_snapshot_chunks: Vec<StateSnapshotChunkMeta>,
 }
 
 impl MetadataView {
@@ -47,6 +52,84 @@ impl MetadataView {
             .map(Clone::clone))
     }
 
+    pub fn select_latest_state_snapshot(&self) -> Option<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups.iter().max().map(Clone::clone)
+    }
+
+    /// Number of incremental snapshots in the chain from `snapshot` down to the full snapshot it
+    /// is based on, 0 if `snapshot` is a full one.
+    pub fn get_state_snapshot_num_deltas(
+        &self,
+        snapshot: &StateSnapshotBackupMeta,
+    ) -> Result<usize> {
+        let mut num_deltas = 0;
+        let mut base_version = snapshot.base_version;
+        while let Some(version) = base_version {
+            let base = self
+                .state_snapshot_backups
+                .iter()
+                .find(|m| m.version == version)
+                .ok_or_else(|| anyhow!("Base state snapshot at version {} not found.", version))?;
+            num_deltas += 1;
+            base_version = base.base_version;
+        }
+        Ok(num_deltas)
+    }
+
+    /// Finds the state snapshot backup that was interrupted after the latest finished one, if any,
+    /// together with the chunks it had written.
+    pub fn select_unfinished_state_snapshot(&self) -> Result<Option<UnfinishedStateSnapshot>> {
+        let latest_version = self.state_snapshot_backups.iter().map(|m| m.version).max();
+        let (version, base_version) = match self
+            .state_snapshot_chunks
+            .iter()
+            .filter(|c| Some(c.version) > latest_version)
+            .map(|c| (c.version, c.base_version))
+            .max()
+        {
+            Some(job) => job,
+            None => return Ok(None),
+        };
+        let base = base_version
+            .map(|v| {
+                self.state_snapshot_backups
+                    .iter()
+                    .find(|m| m.version == v)
+                    .map(Clone::clone)
+                    .ok_or_else(|| anyhow!("Base state snapshot at version {} not found.", v))
+            })
+            .transpose()?;
+
+        // Only the chunks before the first missing one can be reused.
+        let mut chunks = Vec::new();
+        for chunk in self
+            .state_snapshot_chunks
+            .iter()
+            .filter(|c| c.version == version && c.base_version == base_version)
+            .sorted_by_key(|c| c.chunk_idx)
+        {
+            if chunk.chunk_idx < chunks.len() {
+                // duplicated
+                continue;
+            } else if chunk.chunk_idx > chunks.len() {
+                break;
+            }
+            chunks.push(chunk.chunk.clone());
+        }
+
+        let base_num_deltas = match &base {
+            Some(base) => self.get_state_snapshot_num_deltas(base)?,
+            None => 0,
+        };
+
+        Ok(Some(UnfinishedStateSnapshot {
+            version,
+            base,
+            base_num_deltas,
+            chunks,
+        }))
+    }
+
     pub fn select_transaction_backups(
         &self,
         target_version: Version,
@@ -107,12 +190,14 @@ impl From<Vec<Metadata>> for MetadataView {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
+        let mut state_snapshot_chunks = Vec::new();
 
         for meta in metadata_vec {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
+                Metadata::StateSnapshotChunk(c) => state_snapshot_chunks.push(c),
             }
         }
 
@@ -120,10 +205,23 @@ impl From<Vec<Metadata>> for MetadataView {
             epoch_ending_backups,
             state_snapshot_backups,
             transaction_backups,
+            state_snapshot_chunks,
         }
     }
 }
 
+/// A state snapshot backup that didn't finish.
+#[derive(Clone)]
+pub struct UnfinishedStateSnapshot {
+    pub version: Version,
+    /// The base snapshot, if it is an incremental snapshot.
+    pub base: Option<StateSnapshotBackupMeta>,
+    /// Number of incremental snapshots from `base` down to the latest full snapshot.
+    pub base_num_deltas: usize,
+    /// The chunks written, in order.
+    pub chunks: Vec<StateSnapshotChunkBackup>,
+}
+
 pub struct BackupStorageState {
     pub latest_epoch_ending_epoch: Option<u64>,
     pub latest_state_snapshot_version: Option<Version>,
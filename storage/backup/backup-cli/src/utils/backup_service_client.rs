@@ -76,6 +76,28 @@ impl BackupServiceClient {
         self.get(&format!("state_snapshot/{}", version)).await
     }
 
+    pub async fn get_state_snapshot_from(
+        &self,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!("state_snapshot_from/{}/{:x}", version, start_key))
+            .await
+    }
+
+    pub async fn get_state_snapshot_delta(
+        &self,
+        base_version: Version,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!(
+            "state_snapshot_delta/{}/{}/{:x}",
+            base_version, version, start_key
+        ))
+        .await
+    }
+
     pub async fn get_state_root_proof(&self, version: Version) -> Result<Vec<u8>> {
         let mut buf = Vec::new();
         self.get(&format!("state_root_proof/{}", version))
@@ -85,6 +107,15 @@ impl BackupServiceClient {
         Ok(buf)
     }
 
+    pub async fn is_state_readable(&self, version: Version) -> Result<bool> {
+        let mut buf = Vec::new();
+        self.get(&format!("state_readable/{}", version))
+            .await?
+            .read_to_end(&mut buf)
+            .await?;
+        Ok(bcs::from_bytes(&buf)?)
+    }
+
     pub async fn get_epoch_ending_ledger_infos(
         &self,
         start_epoch: u64,
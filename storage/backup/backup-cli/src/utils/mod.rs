@@ -159,6 +159,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
                 db_dir,
                 false, /* read_only */
                 None,  /* pruner */
+                None,  /* ledger pruner */
                 opt.rocksdb_opt.into(),
             )?)
             .get_restore_handler();
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use backup_service::start_backup_service;
-use diem_config::utils::get_available_port;
+use diem_config::{config::RocksdbConfig, utils::get_available_port};
 use diem_proptest_helpers::ValueGenerator;
 use diem_temppath::TempPath;
 use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
@@ -27,6 +27,37 @@ pub fn tmp_db_with_random_content() -> (
     Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
 ) {
     let (tmpdir, db) = tmp_db_empty();
+    let blocks = save_random_content(&db);
+
+    (tmpdir, db, blocks)
+}
+
+/// Like `tmp_db_with_random_content`, but the DB only keeps the state trees of the latest
+/// `prune_window` versions.
+pub fn tmp_pruned_db_with_random_content(
+    prune_window: u64,
+) -> (
+    TempPath,
+    Arc<DiemDB>,
+    Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmpdir = TempPath::new();
+    let db = Arc::new(
+        DiemDB::open(
+            &tmpdir,
+            false, /* readonly */
+            Some(prune_window),
+            None, /* ledger_prune_window */
+            RocksdbConfig::default(),
+        )
+        .unwrap(),
+    );
+    let blocks = save_random_content(&db);
+
+    (tmpdir, db, blocks)
+}
+
+fn save_random_content(db: &DiemDB) -> Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)> {
     let mut cur_ver = 0;
     let blocks = ValueGenerator::new().generate(arb_blocks_to_commit());
     for (txns_to_commit, ledger_info_with_sigs) in &blocks {
@@ -39,7 +70,7 @@ pub fn tmp_db_with_random_content() -> (
         cur_ver += txns_to_commit.len() as u64;
     }
 
-    (tmpdir, db, blocks)
+    blocks
 }
 
 pub fn start_local_backup_service(db: Arc<DiemDB>) -> (Runtime, u16) {
@@ -15,7 +15,10 @@ use warp::{filters::BoxedFilter, reply::Reply, Filter};
 static DB_STATE: &str = "db_state";
 static STATE_RANGE_PROOF: &str = "state_range_proof";
 static STATE_SNAPSHOT: &str = "state_snapshot";
+static STATE_SNAPSHOT_FROM: &str = "state_snapshot_from";
+static STATE_SNAPSHOT_DELTA: &str = "state_snapshot_delta";
 static STATE_ROOT_PROOF: &str = "state_root_proof";
+static STATE_READABLE: &str = "state_readable";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
@@ -50,6 +53,29 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         })
         .recover(handle_rejection);
 
+    // GET state_snapshot_from/<version>/<start_key>
+    let bh = backup_handler.clone();
+    let state_snapshot_from = warp::path!(Version / HashValue)
+        .map(move |version, start_key| {
+            reply_with_async_channel_writer(&bh, STATE_SNAPSHOT_FROM, |bh, sender| {
+                send_size_prefixed_bcs_bytes(bh.get_account_iter_from(version, start_key), sender)
+            })
+        })
+        .recover(handle_rejection);
+
+    // GET state_snapshot_delta/<base_version>/<version>/<start_key>
+    let bh = backup_handler.clone();
+    let state_snapshot_delta = warp::path!(Version / Version / HashValue)
+        .map(move |base_version, version, start_key| {
+            reply_with_async_channel_writer(&bh, STATE_SNAPSHOT_DELTA, |bh, sender| {
+                send_size_prefixed_bcs_bytes(
+                    bh.get_account_delta_iter(base_version, version, start_key),
+                    sender,
+                )
+            })
+        })
+        .recover(handle_rejection);
+
     // GET state_root_proof/<version>
     let bh = backup_handler.clone();
     let state_root_proof = warp::path!(Version)
@@ -59,6 +85,13 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // GET state_readable/<version>
+    let bh = backup_handler.clone();
+    let state_readable = warp::path!(Version)
+        .map(move |version| reply_with_bcs_bytes(STATE_READABLE, &bh.is_state_readable(version)?))
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
     // GET epoch_ending_ledger_infos/<start_epoch>/<end_epoch>/
     let bh = backup_handler.clone();
     let epoch_ending_ledger_infos = warp::path!(u64 / u64)
@@ -112,7 +145,10 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .and(warp::path(DB_STATE).and(db_state))
         .or(warp::path(STATE_RANGE_PROOF).and(state_range_proof))
         .or(warp::path(STATE_SNAPSHOT).and(state_snapshot))
+        .or(warp::path(STATE_SNAPSHOT_FROM).and(state_snapshot_from))
+        .or(warp::path(STATE_SNAPSHOT_DELTA).and(state_snapshot_delta))
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
+        .or(warp::path(STATE_READABLE).and(state_readable))
         .or(warp::path(EPOCH_ENDING_LEDGER_INFOS).and(epoch_ending_ledger_infos))
         .or(warp::path(TRANSACTIONS).and(transactions))
         .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
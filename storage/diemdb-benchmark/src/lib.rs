@@ -15,7 +15,7 @@ use diem_types::{
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{ChangeSet, Transaction, TransactionToCommit, WriteSetPayload},
     vm_status::KeptVMStatus,
-    write_set::WriteSetMut,
+    write_set::{WriteSet, WriteSetMut},
 };
 use diemdb::{
     metrics::DIEM_STORAGE_ROCKSDB_PROPERTIES, schema::JELLYFISH_MERKLE_NODE_CF_NAME, DiemDB,
@@ -66,8 +66,9 @@ fn gen_txn_to_commit<R: Rng>(
     TransactionToCommit::new(
         txn,
         states,
-        vec![], /* events */
-        0,      /* gas_used */
+        WriteSet::default(), /* write_set */
+        vec![],              /* events */
+        0,                   /* gas_used */
         KeptVMStatus::Executed,
     )
 }
@@ -89,6 +90,7 @@ pub fn run_benchmark(
         &db_dir,
         false,        /* readonly */
         prune_window, /* pruner */
+        None,         /* ledger pruner */
         RocksdbConfig::default(),
     )
     .expect("DB should open.");
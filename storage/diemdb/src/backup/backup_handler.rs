@@ -13,7 +13,7 @@ use crate::{
 };
 use anyhow::{anyhow, ensure, Result};
 use diem_crypto::hash::HashValue;
-use diem_jellyfish_merkle::iterator::JellyfishMerkleIterator;
+use diem_jellyfish_merkle::iterator::{JellyfishMerkleDeltaIterator, JellyfishMerkleIterator};
 use diem_types::{
     account_state_blob::AccountStateBlob,
     contract_event::ContractEvent,
@@ -104,10 +104,51 @@ impl BackupHandler {
         &self,
         version: Version,
     ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + Send + Sync>> {
-        let iterator = JellyfishMerkleIterator::new(
+        self.get_account_iter_from(version, HashValue::zero())
+    }
+
+    /// Gets an iterator which yields the accounts in the state tree, starting from the account
+    /// with key `start_key` (inclusive).
+    pub fn get_account_iter_from(
+        &self,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + Send + Sync>> {
+        let iterator =
+            JellyfishMerkleIterator::new(Arc::clone(&self.state_store), version, start_key)?
+                .enumerate()
+                .map(move |(idx, res)| {
+                    BACKUP_STATE_SNAPSHOT_VERSION.set(version as i64);
+                    BACKUP_STATE_SNAPSHOT_LEAF_IDX.set(idx as i64);
+                    res
+                });
+        Ok(Box::new(iterator))
+    }
+
+    /// Gets an iterator which yields, in key order and starting from `start_key` (inclusive), the
+    /// accounts whose states at `version` differ from their states at `base_version`.
+    ///
+    /// The accounts are found by walking the state trees of both versions side by side, rather
+    /// than from the write sets of the transactions in between, which are missing for transactions
+    /// restored from a backup or a state snapshot. Subtrees the two versions share are skipped.
+    /// Fails if the state at `base_version` has been pruned, see `is_state_readable`.
+    pub fn get_account_delta_iter(
+        &self,
+        base_version: Version,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + Send + Sync>> {
+        ensure!(
+            version >= base_version,
+            "Bad state delta range: ({}, {}]",
+            base_version,
+            version
+        );
+        let iterator = JellyfishMerkleDeltaIterator::new(
             Arc::clone(&self.state_store),
+            base_version,
             version,
-            HashValue::zero(),
+            start_key,
         )?
         .enumerate()
         .map(move |(idx, res)| {
@@ -128,6 +169,11 @@ impl BackupHandler {
             .get_account_state_range_proof(rightmost_key, version)
     }
 
+    /// Tells whether the state tree at `version` is still in the DB, i.e. not pruned.
+    pub fn is_state_readable(&self, version: Version) -> Result<bool> {
+        Ok(self.state_store.get_root_hash_option(version)?.is_some())
+    }
+
     /// Gets the epoch, commited version, and synced version of the DB.
     pub fn get_db_state(&self) -> Result<Option<DbState>> {
         self.ledger_store
@@ -119,6 +119,8 @@ impl RestoreHandler {
         self.db.write_schemas(cs.batch)
     }
 
+    /// Saves transactions restored from a backup. Backups don't carry write sets, so reading the
+    /// write sets of these transactions fails with `DiemDbError::WriteSetUnavailable`.
     pub fn save_transactions(
         &self,
         first_version: Version,
@@ -3,8 +3,11 @@
 
 use crate::{test_helper::arb_blocks_to_commit, DiemDB};
 use anyhow::Result;
+use diem_crypto::HashValue;
 use diem_temppath::TempPath;
-use proptest::prelude::*;
+use diem_types::account_address::HashAccountAddress;
+use proptest::{prelude::*, sample::Index};
+use std::collections::{BTreeMap, BTreeSet};
 use storage_interface::DbWriter;
 
 proptest! {
@@ -41,4 +44,71 @@ proptest! {
             .unwrap();
         prop_assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_get_account_delta_iter(
+        input in arb_blocks_to_commit(),
+        base_index in any::<Index>(),
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(&txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let version = cur_ver - 1;
+        let base_version = base_index.index(cur_ver as usize) as u64;
+
+        let backup_handler = db.get_backup_handler();
+        let base_accounts = backup_handler
+            .get_account_iter(base_version)
+            .unwrap()
+            .collect::<Result<BTreeMap<_, _>>>()
+            .unwrap();
+        let accounts = backup_handler
+            .get_account_iter(version)
+            .unwrap()
+            .collect::<Result<BTreeMap<_, _>>>()
+            .unwrap();
+        let expected_delta: Vec<_> = accounts
+            .iter()
+            .filter(|(key, blob)| base_accounts.get(key) != Some(blob))
+            .map(|(key, blob)| (*key, blob.clone()))
+            .collect();
+
+        let delta = backup_handler
+            .get_account_delta_iter(base_version, version, HashValue::zero())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(&delta, &expected_delta);
+
+        // Only accounts written since the base version are in the delta.
+        let written_keys: BTreeSet<_> = input
+            .iter()
+            .flat_map(|(txns_to_commit, _ledger_info_with_sigs)| txns_to_commit.iter())
+            .skip(base_version as usize + 1)
+            .flat_map(|txn_to_commit| txn_to_commit.account_states().keys())
+            .map(|address| address.hash())
+            .collect();
+        prop_assert!(delta.iter().all(|(key, _blob)| written_keys.contains(key)));
+
+        // The base snapshot with the delta applied is the snapshot at `version`.
+        let mut patched = base_accounts;
+        patched.extend(delta.clone());
+        prop_assert_eq!(patched, accounts);
+
+        // Resuming from a key yields the rest of the delta.
+        if let Some((start_key, _blob)) = delta.get(delta.len() / 2) {
+            let rest = backup_handler
+                .get_account_delta_iter(base_version, version, *start_key)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(&rest[..], &delta[delta.len() / 2..]);
+        }
+    }
 }
@@ -79,6 +79,7 @@ fn verify_epochs(db: &DiemDB, ledger_infos_with_sigs: &[LedgerInfoWithSignatures
 pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
     let tmp_dir = TempPath::new();
     let db = DiemDB::new_for_test(&tmp_dir);
+    let commits = db.subscribe_to_commits();
 
     let num_batches = input.len();
     let mut cur_ver = 0;
@@ -94,6 +95,10 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
             db.ledger_store.get_latest_ledger_info().unwrap(),
             *ledger_info_with_sigs
         );
+        assert_eq!(
+            *commits.borrow(),
+            ledger_info_with_sigs.ledger_info().version()
+        );
         verify_committed_transactions(
             &db,
             &txns_to_commit,
@@ -365,6 +370,12 @@ fn verify_committed_transactions(
             .verify(ledger_info, Some(cur_ver))
             .unwrap();
 
+        // Fetch and verify write set.
+        assert_eq!(
+            &db.get_write_set(cur_ver).unwrap(),
+            txn_to_commit.write_set()
+        );
+
         // Fetch and verify account states.
         for (addr, expected_blob) in txn_to_commit.account_states() {
             let account_state_with_proof = db
@@ -389,6 +400,70 @@ fn verify_committed_transactions(
     );
 }
 
+fn test_state_snapshot_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions(&txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let ledger_info_with_sigs = input.last().unwrap().1.clone();
+    let version = ledger_info_with_sigs.ledger_info().version();
+
+    // Restore the snapshot at the latest version into an empty db, two accounts at a time.
+    let tmp_dir2 = TempPath::new();
+    let db2 = DiemDB::new_for_test(&tmp_dir2);
+    let expected_root_hash = db.state_store.get_root_hash(version).unwrap();
+    let mut receiver = db2
+        .get_state_snapshot_receiver(version, expected_root_hash)
+        .unwrap();
+    let mut last_key = None;
+    loop {
+        let chunk = db
+            .get_account_chunk_with_proof(version, last_key, 2 /* chunk_size */)
+            .unwrap();
+        last_key = chunk.last_key();
+        let is_last_chunk = chunk.is_last_chunk;
+        receiver
+            .add_chunk(chunk.account_blobs, chunk.proof)
+            .unwrap();
+        if is_last_chunk {
+            break;
+        }
+    }
+    receiver.finish_box().unwrap();
+
+    let output_with_proof = db
+        .get_transactions(
+            version, 1, /* limit */
+            version, false, /* fetch_events */
+        )
+        .unwrap();
+    let frozen_subtrees = db.get_accumulator_frozen_subtrees(version).unwrap();
+    db2.finalize_state_snapshot(
+        version,
+        output_with_proof,
+        &frozen_subtrees,
+        &ledger_info_with_sigs,
+    )
+    .unwrap();
+
+    assert_eq!(db2.get_latest_ledger_info().unwrap(), ledger_info_with_sigs);
+    assert_eq!(
+        db2.get_latest_tree_state().unwrap(),
+        db.get_latest_tree_state().unwrap()
+    );
+    // The snapshot doesn't carry the write set of its transaction.
+    assert!(matches!(
+        db2.get_write_set(version)
+            .unwrap_err()
+            .downcast_ref::<DiemDbError>(),
+        Some(DiemDbError::WriteSetUnavailable(v)) if *v == version
+    ));
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -401,6 +476,11 @@ proptest! {
     fn test_sync_transactions(input in arb_blocks_to_commit()) {
         test_sync_transactions_impl(input);
     }
+
+    #[test]
+    fn test_state_snapshot(input in arb_blocks_to_commit()) {
+        test_state_snapshot_impl(input);
+    }
 }
 
 #[test]
@@ -11,6 +11,16 @@ pub enum DiemDbError {
     /// A requested item is not found.
     #[error("{0} not found.")]
     NotFound(String),
+    /// A requested item has been pruned.
+    #[error("{0} has been pruned.")]
+    Pruned(String),
+    /// The write set of a transaction restored from a backup or a state snapshot, neither of which
+    /// carries write sets.
+    #[error(
+        "WriteSet {0} is unavailable: transactions restored from a backup or a state snapshot don't \
+         have their write sets."
+    )]
+    WriteSetUnavailable(u64),
     /// Requested too many items.
     #[error("Too many items requested: at least {0} requested, max is {1}")]
     TooManyRequested(u64, u64),
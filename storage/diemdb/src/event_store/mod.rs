@@ -30,7 +30,7 @@ use diem_types::{
     proof::{position::Position, EventAccumulatorProof, EventProof},
     transaction::Version,
 };
-use schemadb::{schema::ValueCodec, ReadOptions, SchemaIterator, DB};
+use schemadb::{schema::ValueCodec, ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::{
     convert::{TryFrom, TryInto},
     iter::Peekable,
@@ -154,6 +154,18 @@ impl EventStore {
         ))
     }
 
+    /// Get the oldest sequence number on `event_key` that is still stored, which is greater than 0
+    /// once the older events of the stream have been pruned.
+    pub fn get_first_sequence_number(&self, event_key: &EventKey) -> Result<Option<u64>> {
+        let mut iter = self.db.iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, 0))?;
+
+        Ok(iter
+            .next()
+            .transpose()?
+            .and_then(|((key, seq), _)| if &key == event_key { Some(seq) } else { None }))
+    }
+
     /// Get the next sequence number for specified event key.
     /// Returns 0 if there's no events already in the event stream.
     pub fn get_next_sequence_number(
@@ -194,6 +206,15 @@ impl EventStore {
             if path != *event_key || ver > ledger_version {
                 break;
             }
+            // Sequence numbers of an event stream start from 0 and have no holes, unless the
+            // oldest ones have been pruned.
+            if result.is_empty() && seq > cur_seq {
+                return Err(DiemDbError::Pruned(format!(
+                    "Event {} of seq num {}",
+                    event_key, cur_seq
+                ))
+                .into());
+            }
             ensure!(
                 seq == cur_seq,
                 "DB corrupt: Sequence number not continuous, expected: {}, actual: {}.",
@@ -265,6 +286,45 @@ impl EventStore {
         Ok(root_hash)
     }
 
+    /// Deletes the events yielded by the transactions in `[begin, end)`, together with their
+    /// indices and the event accumulators of these transactions.
+    pub fn prune_events(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let ((version, index), event) = res?;
+            if version >= end {
+                break;
+            }
+            db_batch.delete::<EventSchema>(&(version, index))?;
+            db_batch.delete::<EventByKeySchema>(&(*event.key(), event.sequence_number()))?;
+            db_batch.delete::<EventByVersionSchema>(&(
+                *event.key(),
+                version,
+                event.sequence_number(),
+            ))?;
+        }
+
+        let mut iter = self
+            .db
+            .iter::<EventAccumulatorSchema>(ReadOptions::default())?;
+        iter.seek(&(begin, Position::from_inorder_index(0)))?;
+        for res in iter {
+            let ((version, position), _hash) = res?;
+            if version >= end {
+                break;
+            }
+            db_batch.delete::<EventAccumulatorSchema>(&(version, position))?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn put_events_multiple_versions(
         &self,
         first_version: u64,
@@ -294,7 +354,12 @@ impl EventStore {
     where
         C: FnMut(&ContractEvent) -> Result<bool>,
     {
-        let mut begin = 0u64;
+        // Only search the events that have not been pruned.
+        let first = match self.get_first_sequence_number(event_key)? {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+        let mut begin = first;
         let mut end = match self.get_latest_sequence_number(ledger_version, event_key)? {
             Some(s) => s
                 .checked_add(1)
@@ -319,6 +384,16 @@ impl EventStore {
             }
         }
 
+        // The pruned events may hold a lower bound too.
+        if begin == first && first > 0 {
+            return Err(DiemDbError::Pruned(format!(
+                "Event {} of seq num {}",
+                event_key,
+                first - 1
+            ))
+            .into());
+        }
+
         if begin == end {
             Ok(None)
         } else {
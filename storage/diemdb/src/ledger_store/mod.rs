@@ -24,14 +24,16 @@ use diem_types::{
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
-        definition::LeafCount, position::Position, AccumulatorConsistencyProof,
-        TransactionAccumulatorProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof,
+        definition::LeafCount,
+        position::{FrozenSubTreeIterator, Position},
+        AccumulatorConsistencyProof, TransactionAccumulatorProof, TransactionAccumulatorRangeProof,
+        TransactionInfoWithProof,
     },
     transaction::{TransactionInfo, Version},
 };
 use itertools::Itertools;
-use schemadb::{ReadOptions, SchemaIterator, DB};
-use std::{ops::Deref, sync::Arc};
+use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
+use std::{collections::HashMap, ops::Deref, sync::Arc};
 use storage_interface::{StartupInfo, TreeState};
 
 #[derive(Debug)]
@@ -344,6 +346,50 @@ impl LedgerStore {
         Ok(root_hash)
     }
 
+    /// Writes the `frozen_subtrees` of an accumulator with `num_leaves` leaves, which come with a
+    /// state snapshot instead of the transactions before it, and `txn_info` appended to them to
+    /// `cs`.
+    pub fn put_transaction_info_on_frozen_subtrees(
+        &self,
+        num_leaves: LeafCount,
+        frozen_subtrees: &[HashValue],
+        txn_info: &TransactionInfo,
+        cs: &mut ChangeSet,
+    ) -> Result<HashValue> {
+        let reader = FrozenSubtreeReader(
+            FrozenSubTreeIterator::new(num_leaves)
+                .zip_eq(frozen_subtrees.iter().cloned())
+                .collect(),
+        );
+        reader
+            .0
+            .iter()
+            .try_for_each(|(pos, hash)| cs.batch.put::<TransactionAccumulatorSchema>(pos, hash))?;
+
+        cs.batch
+            .put::<TransactionInfoSchema>(&num_leaves, txn_info)?;
+        let (root_hash, writes) = MerkleAccumulator::<_, TransactionAccumulatorHasher>::append(
+            &reader,
+            num_leaves, /* num_existing_leaves */
+            &[txn_info.hash()],
+        )?;
+        writes
+            .iter()
+            .try_for_each(|(pos, hash)| cs.batch.put::<TransactionAccumulatorSchema>(pos, hash))?;
+        Ok(root_hash)
+    }
+
+    /// Deletes the `TransactionInfo`s in `[begin, end)`. The transaction accumulator is left
+    /// untouched, so that proofs can still be generated for the versions that are kept.
+    pub fn prune_transaction_infos(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        (begin..end).try_for_each(|version| db_batch.delete::<TransactionInfoSchema>(&version))
+    }
+
     /// Write `ledger_info` to `cs`.
     pub fn put_ledger_info(
         &self,
@@ -376,6 +422,19 @@ impl HashReader for LedgerStore {
     }
 }
 
+/// Serves the frozen subtrees of an accumulator from memory, so that leaves can be appended to them
+/// in the same batch that persists them.
+struct FrozenSubtreeReader(HashMap<Position, HashValue>);
+
+impl HashReader for FrozenSubtreeReader {
+    fn get(&self, position: Position) -> Result<HashValue> {
+        self.0
+            .get(&position)
+            .cloned()
+            .ok_or_else(|| format_err!("{} does not exist.", position))
+    }
+}
+
 pub struct TransactionInfoIter<'a> {
     inner: SchemaIterator<'a, TransactionInfoSchema>,
     expected_next_version: Version,
@@ -49,7 +49,7 @@ use crate::{
         DIEM_STORAGE_NEXT_BLOCK_EPOCH, DIEM_STORAGE_OTHER_TIMERS_SECONDS,
         DIEM_STORAGE_ROCKSDB_PROPERTIES,
     },
-    pruner::Pruner,
+    pruner::{LedgerPruner, Pruner},
     schema::*,
     state_store::StateStore,
     system_store::SystemStore,
@@ -57,36 +57,46 @@ use crate::{
 };
 use anyhow::{ensure, Result};
 use diem_config::config::RocksdbConfig;
-use diem_crypto::hash::{CryptoHash, HashValue, SPARSE_MERKLE_PLACEHOLDER_HASH};
+use diem_crypto::hash::{
+    CryptoHash, HashValue, TransactionAccumulatorHasher, SPARSE_MERKLE_PLACEHOLDER_HASH,
+};
+use diem_jellyfish_merkle::{iterator::JellyfishMerkleIterator, restore::JellyfishMerkleRestore};
 use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress,
+    account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
-        AccountStateProof, AccumulatorConsistencyProof, EventProof, SparseMerkleProof,
-        TransactionListProof,
+        accumulator::InMemoryAccumulator, definition::LeafCount, AccountStateProof,
+        AccumulatorConsistencyProof, EventProof, SparseMerkleProof, TransactionListProof,
     },
     transaction::{
         TransactionInfo, TransactionListWithProof, TransactionToCommit, TransactionWithProof,
         Version, PRE_GENESIS_VERSION,
     },
+    write_set::WriteSet,
 };
 use itertools::{izip, zip_eq};
 use once_cell::sync::Lazy;
 use schemadb::{ColumnFamilyName, Options, DB, DEFAULT_CF_NAME};
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     iter::Iterator,
     path::Path,
     sync::{mpsc, Arc, Mutex},
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
-use storage_interface::{DbReader, DbWriter, Order, StartupInfo, TreeState};
+use storage_interface::{
+    AccountStateChunkWithProof, DbReader, DbWriter, Order, StartupInfo, StateSnapshotReceiver,
+    TreeState,
+};
+use tokio::sync::watch;
 
 const MAX_LIMIT: u64 = 1000;
 
@@ -209,6 +219,10 @@ pub struct DiemDB {
     system_store: SystemStore,
     rocksdb_property_reporter: RocksdbPropertyReporter,
     pruner: Option<Pruner>,
+    ledger_pruner: Option<LedgerPruner>,
+    commit_notifier: watch::Sender<Version>,
+    // Kept so that notifying never fails, even when nobody has subscribed.
+    commit_subscriber: watch::Receiver<Version>,
 }
 
 impl DiemDB {
@@ -227,34 +241,59 @@ impl DiemDB {
             TRANSACTION_ACCUMULATOR_CF_NAME,
             TRANSACTION_BY_ACCOUNT_CF_NAME,
             TRANSACTION_INFO_CF_NAME,
+            WRITE_SET_CF_NAME,
         ]
     }
 
-    fn new_with_db(db: DB, prune_window: Option<u64>) -> Self {
+    fn new_with_db(db: DB, prune_window: Option<u64>, ledger_prune_window: Option<u64>) -> Self {
         let db = Arc::new(db);
+        let ledger_store = Arc::new(LedgerStore::new(Arc::clone(&db)));
+        let latest_version = ledger_store
+            .get_latest_ledger_info_option()
+            .map_or(0, |li| li.ledger_info().version());
+        let (commit_notifier, commit_subscriber) = watch::channel(latest_version);
 
         DiemDB {
             db: Arc::clone(&db),
             event_store: Arc::new(EventStore::new(Arc::clone(&db))),
-            ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
+            ledger_store,
             state_store: Arc::new(StateStore::new(Arc::clone(&db))),
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
             system_store: SystemStore::new(Arc::clone(&db)),
             rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
             pruner: prune_window.map(|n| Pruner::new(Arc::clone(&db), n)),
+            ledger_pruner: ledger_prune_window.map(|n| LedgerPruner::new(Arc::clone(&db), n)),
+            commit_notifier,
+            commit_subscriber,
         }
     }
 
+    /// Returns a receiver holding the version of the latest committed ledger info, which is
+    /// updated every time a new ledger info is committed.
+    pub fn subscribe_to_commits(&self) -> watch::Receiver<Version> {
+        self.commit_subscriber.clone()
+    }
+
+    fn notify_commit(&self, version: Version) {
+        // Can't fail since `self.commit_subscriber` is never dropped.
+        let _ = self.commit_notifier.send(version);
+    }
+
     pub fn open<P: AsRef<Path> + Clone>(
         db_root_path: P,
         readonly: bool,
         prune_window: Option<u64>,
+        ledger_prune_window: Option<u64>,
         rocksdb_config: RocksdbConfig,
     ) -> Result<Self> {
         ensure!(
             prune_window.is_none() || !readonly,
             "Do not set prune_window when opening readonly.",
         );
+        ensure!(
+            ledger_prune_window.is_none() || !readonly,
+            "Do not set ledger_prune_window when opening readonly.",
+        );
 
         let path = db_root_path.as_ref().join("diemdb");
         let instant = Instant::now();
@@ -279,7 +318,7 @@ impl DiemDB {
             )?
         };
 
-        let ret = Self::new_with_db(db, prune_window);
+        let ret = Self::new_with_db(db, prune_window, ledger_prune_window);
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -308,6 +347,7 @@ impl DiemDB {
                 &rocksdb_opts,
             )?,
             None, // prune_window
+            None, // ledger_prune_window
         ))
     }
 
@@ -318,6 +358,7 @@ impl DiemDB {
             db_root_path,
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger pruner */
             RocksdbConfig::default(),
         )
         .expect("Unable to open DiemDB")
@@ -390,12 +431,68 @@ impl DiemDB {
         Ok((lis, more))
     }
 
+    /// Returns an error if the ledger history at `version` has been pruned.
+    fn error_if_ledger_pruned(&self, data_type: &str, version: Version) -> Result<()> {
+        let min_readable_version = self
+            .ledger_pruner
+            .as_ref()
+            .map_or(0, |pruner| pruner.least_readable_version());
+        if version < min_readable_version {
+            Err(DiemDbError::Pruned(format!(
+                "{} at version {} (min readable version: {})",
+                data_type, version, min_readable_version
+            ))
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns an error if the `seq_num`-th transaction of `address`, which is not in the DB, was
+    /// committed by `ledger_version` and has been pruned since.
+    fn error_if_account_transaction_pruned(
+        &self,
+        address: AccountAddress,
+        seq_num: u64,
+        ledger_version: Version,
+    ) -> Result<()> {
+        if self.ledger_pruner.is_none() {
+            return Ok(());
+        }
+        // The account's transactions are indexed by contiguous sequence numbers, so either a later
+        // one is left, or the account's sequence number tells how many it committed.
+        let committed = match self.transaction_store.get_first_sequence_number(address)? {
+            Some(first_seq_num) => first_seq_num > seq_num,
+            None => {
+                let (blob, _proof) = self
+                    .state_store
+                    .get_account_state_with_proof_by_version(address, ledger_version)?;
+                let sequence_number = match blob {
+                    Some(blob) => AccountState::try_from(&blob)?
+                        .get_account_resource()?
+                        .map_or(0, |resource| resource.sequence_number()),
+                    None => 0,
+                };
+                sequence_number > seq_num
+            }
+        };
+        if committed {
+            Err(
+                DiemDbError::Pruned(format!("Transaction {} of account {}", seq_num, address))
+                    .into(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
     fn get_transaction_with_proof(
         &self,
         version: Version,
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
+        self.error_if_ledger_pruned("Transaction", version)?;
         let proof = self
             .ledger_store
             .get_transaction_info_with_proof(version, ledger_version)?;
@@ -478,6 +575,7 @@ impl DiemDB {
         let mut events_with_proof = event_indices
             .into_iter()
             .map(|(seq, ver, idx)| {
+                self.error_if_ledger_pruned("Event", ver)?;
                 let (event, event_proof) = self
                     .event_store
                     .get_event_with_proof_by_version_and_index(ver, idx)?;
@@ -553,8 +651,13 @@ impl DiemDB {
         // Transaction updates. Gather transaction hashes.
         zip_eq(first_version..=last_version, txns_to_commit).try_for_each(
             |(ver, txn_to_commit)| {
+                self.transaction_store.put_transaction(
+                    ver,
+                    txn_to_commit.transaction(),
+                    &mut cs,
+                )?;
                 self.transaction_store
-                    .put_transaction(ver, txn_to_commit.transaction(), &mut cs)
+                    .put_write_set(ver, txn_to_commit.write_set(), &mut cs)
             },
         )?;
 
@@ -592,6 +695,9 @@ impl DiemDB {
         if let Some(pruner) = self.pruner.as_ref() {
             pruner.wake(latest_version)
         }
+        if let Some(ledger_pruner) = self.ledger_pruner.as_ref() {
+            ledger_pruner.wake(latest_version)
+        }
     }
 }
 
@@ -638,12 +744,19 @@ impl DbReader for DiemDB {
         fetch_events: bool,
     ) -> Result<Option<TransactionWithProof>> {
         gauged_api("get_txn_by_account", || {
-            self.transaction_store
-                .lookup_transaction_by_account(address, seq_num, ledger_version)?
-                .map(|version| {
-                    self.get_transaction_with_proof(version, ledger_version, fetch_events)
-                })
-                .transpose()
+            match self.transaction_store.lookup_transaction_by_account(
+                address,
+                seq_num,
+                ledger_version,
+            )? {
+                Some(version) => self
+                    .get_transaction_with_proof(version, ledger_version, fetch_events)
+                    .map(Some),
+                None => {
+                    self.error_if_account_transaction_pruned(address, seq_num, ledger_version)?;
+                    Ok(None)
+                }
+            }
         })
     }
 
@@ -666,6 +779,7 @@ impl DbReader for DiemDB {
             }
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
+            self.error_if_ledger_pruned("Transaction", start_version)?;
 
             let txns = (start_version..start_version + limit)
                 .map(|version| self.transaction_store.get_transaction(version))
@@ -812,6 +926,7 @@ impl DbReader for DiemDB {
                 version,
                 ledger_version
             );
+            self.error_if_ledger_pruned("TransactionInfo", version)?;
             {
                 let latest_version = self.get_latest_version()?;
                 ensure!(
@@ -889,6 +1004,7 @@ impl DbReader for DiemDB {
 
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         gauged_api("get_block_timestamp", || {
+            self.error_if_ledger_pruned("BlockMetadata", version)?;
             let ts = match self.transaction_store.get_block_metadata(version)? {
                 Some((_v, block_meta)) => block_meta.into_inner().1,
                 // genesis timestamp is 0
@@ -920,6 +1036,65 @@ impl DbReader for DiemDB {
             self.ledger_store.get_root_hash(version)
         })
     }
+
+    fn get_accumulator_frozen_subtrees(
+        &self,
+        num_transactions: LeafCount,
+    ) -> Result<Vec<HashValue>> {
+        gauged_api("get_accumulator_frozen_subtrees", || {
+            self.ledger_store
+                .get_frozen_subtree_hashes(num_transactions)
+        })
+    }
+
+    fn get_write_set(&self, version: Version) -> Result<WriteSet> {
+        gauged_api("get_write_set", || {
+            self.error_if_ledger_pruned("WriteSet", version)?;
+            self.transaction_store.get_write_set(version)
+        })
+    }
+
+    fn get_account_chunk_with_proof(
+        &self,
+        version: Version,
+        last_key: Option<HashValue>,
+        chunk_size: usize,
+    ) -> Result<AccountStateChunkWithProof> {
+        gauged_api("get_account_chunk_with_proof", || {
+            ensure!(chunk_size > 0, "chunk_size should > 0.");
+            let mut iter = JellyfishMerkleIterator::new(
+                Arc::clone(&self.state_store),
+                version,
+                last_key.unwrap_or_else(HashValue::zero),
+            )?
+            .skip_while(|res| match (res, last_key) {
+                (Ok((key, _blob)), Some(last_key)) => *key <= last_key,
+                _ => false,
+            })
+            .peekable();
+
+            let account_blobs = iter.by_ref().take(chunk_size).collect::<Result<Vec<_>>>()?;
+            let rightmost_key = account_blobs
+                .last()
+                .map(|(key, _blob)| *key)
+                .ok_or_else(|| {
+                    DiemDbError::NotFound(format!(
+                        "Accounts after {:?} at version {}",
+                        last_key, version
+                    ))
+                })?;
+            let is_last_chunk = iter.peek().is_none();
+            let proof = self
+                .state_store
+                .get_account_state_range_proof(rightmost_key, version)?;
+
+            Ok(AccountStateChunkWithProof {
+                account_blobs,
+                proof,
+                is_last_chunk,
+            })
+        })
+    }
 }
 
 impl DbWriter for DiemDB {
@@ -988,6 +1163,7 @@ impl DbWriter for DiemDB {
 
                 DIEM_STORAGE_LEDGER_VERSION.set(x.ledger_info().version() as i64);
                 DIEM_STORAGE_NEXT_BLOCK_EPOCH.set(x.ledger_info().next_block_epoch() as i64);
+                self.notify_commit(x.ledger_info().version());
             }
 
             // Only increment counter if commit succeeds and there are at least one transaction written
@@ -1006,6 +1182,96 @@ impl DbWriter for DiemDB {
             Ok(())
         })
     }
+
+    fn get_state_snapshot_receiver(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>> {
+        gauged_api("get_state_snapshot_receiver", || {
+            // Overwrite, as the tree already contains the nodes of older versions, e.g. genesis.
+            let receiver: Box<dyn StateSnapshotReceiver<AccountStateBlob>> =
+                Box::new(JellyfishMerkleRestore::new_overwrite(
+                    Arc::clone(&self.state_store),
+                    version,
+                    expected_root_hash,
+                )?);
+            Ok(receiver)
+        })
+    }
+
+    fn finalize_state_snapshot(
+        &self,
+        version: Version,
+        output_with_proof: TransactionListWithProof,
+        frozen_subtrees: &[HashValue],
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        gauged_api("finalize_state_snapshot", || {
+            let ledger_info = ledger_info_with_sigs.ledger_info();
+            ensure!(
+                ledger_info.version() == version,
+                "Ledger info version {} doesn't match the snapshot version {}.",
+                ledger_info.version(),
+                version,
+            );
+            ensure!(
+                output_with_proof.len() == 1,
+                "Expected the single transaction at the snapshot version, got {}.",
+                output_with_proof.len(),
+            );
+            output_with_proof.verify(ledger_info, Some(version))?;
+            let txn_info = &output_with_proof.proof.transaction_infos()[0];
+
+            // The state tree must have been fully restored.
+            let state_root_hash = self.state_store.get_root_hash(version)?;
+            ensure!(
+                state_root_hash == txn_info.state_root_hash(),
+                "State root hash doesn't match the snapshot version. {:?} vs {:?}",
+                state_root_hash,
+                txn_info.state_root_hash(),
+            );
+
+            // The frozen subtrees plus the snapshot transaction must make up the accumulator the
+            // ledger info commits to.
+            let accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::new(
+                frozen_subtrees.to_vec(),
+                version, /* num_leaves */
+            )?
+            .append(&[txn_info.hash()]);
+            ensure!(
+                accumulator.root_hash() == ledger_info.transaction_accumulator_hash(),
+                "Root hash calculated doesn't match expected. {:?} vs {:?}",
+                accumulator.root_hash(),
+                ledger_info.transaction_accumulator_hash(),
+            );
+
+            let mut cs = ChangeSet::new();
+            self.transaction_store.put_transaction(
+                version,
+                &output_with_proof.transactions[0],
+                &mut cs,
+            )?;
+            self.ledger_store.put_transaction_info_on_frozen_subtrees(
+                version, /* num_leaves */
+                frozen_subtrees,
+                txn_info,
+                &mut cs,
+            )?;
+            self.ledger_store
+                .put_ledger_info(ledger_info_with_sigs, &mut cs)?;
+            self.db.write_schemas(cs.batch)?;
+
+            self.ledger_store
+                .set_latest_ledger_info(ledger_info_with_sigs.clone());
+            DIEM_STORAGE_LEDGER_VERSION.set(version as i64);
+            DIEM_STORAGE_NEXT_BLOCK_EPOCH.set(ledger_info.next_block_epoch() as i64);
+            DIEM_STORAGE_LATEST_TXN_VERSION.set(version as i64);
+            self.notify_commit(version);
+
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
@@ -0,0 +1,294 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `LedgerPruner` which prunes the ledger history in the background, i.e.
+//! transactions, `TransactionInfo`s, events and their indices older than a configured window.
+//!
+//! The transaction accumulator is never pruned: its frozen subtrees are needed to prove the
+//! versions that are kept against any later ledger info. Epoch ending ledger infos are kept as
+//! well so that clients can still sync from genesis.
+
+use super::Command;
+use crate::{
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    metrics::{
+        DIEM_STORAGE_LEDGER_PRUNE_WINDOW, DIEM_STORAGE_OTHER_TIMERS_SECONDS,
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION,
+    },
+    schema::transaction::TransactionSchema,
+    transaction_store::TransactionStore,
+};
+use anyhow::Result;
+use diem_infallible::Mutex;
+use diem_logger::prelude::*;
+use diem_types::transaction::Version;
+use schemadb::{ReadOptions, SchemaBatch, DB};
+#[cfg(test)]
+use std::time::Instant;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread::{sleep, JoinHandle},
+    time::Duration,
+};
+
+/// The `LedgerPruner` is meant to be part of a `DiemDB` instance and runs in the background to
+/// prune old ledger history.
+///
+/// Like the state `Pruner`, it creates a worker thread on construction and joins it on
+/// destruction.
+#[derive(Debug)]
+pub(crate) struct LedgerPruner {
+    /// Other than the latest version, how many historical versions of the ledger history to keep.
+    historical_versions_to_keep: u64,
+    /// The worker thread handle, created upon construction and joined upon destruction. It only
+    /// becomes `None` after joined in `drop()`.
+    worker_thread: Option<JoinHandle<()>>,
+    /// The sender side of the channel talking to the worker thread.
+    command_sender: Mutex<Sender<Command>>,
+    /// Set by the worker thread as it makes progress. If this is `V`, the ledger history before
+    /// `V` is no longer readable.
+    least_readable_version: Arc<AtomicU64>,
+}
+
+impl LedgerPruner {
+    /// Creates a worker thread that waits on a channel for pruning commands.
+    pub fn new(db: Arc<DB>, historical_versions_to_keep: u64) -> Self {
+        let (command_sender, command_receiver) = channel();
+
+        let least_readable_version = Arc::new(AtomicU64::new(0));
+        let least_readable_version_clone = Arc::clone(&least_readable_version);
+
+        DIEM_STORAGE_LEDGER_PRUNE_WINDOW.set(historical_versions_to_keep as i64);
+        let worker_thread = std::thread::Builder::new()
+            .name("diemdb_ledger_pruner".into())
+            .spawn(move || Worker::new(db, command_receiver, least_readable_version_clone).work())
+            .expect("Creating ledger pruner thread should succeed.");
+
+        Self {
+            historical_versions_to_keep,
+            worker_thread: Some(worker_thread),
+            command_sender: Mutex::new(command_sender),
+            least_readable_version,
+        }
+    }
+
+    /// Returns the least version of which the ledger history is still readable.
+    pub fn least_readable_version(&self) -> Version {
+        self.least_readable_version.load(Ordering::Relaxed)
+    }
+
+    /// Sends pruning command to the worker thread when necessary.
+    pub fn wake(&self, latest_version: Version) {
+        if latest_version > self.historical_versions_to_keep {
+            let least_readable_version = latest_version - self.historical_versions_to_keep;
+            self.command_sender
+                .lock()
+                .send(Command::Prune {
+                    least_readable_version,
+                })
+                .expect("Receiver should not destruct prematurely.");
+        }
+    }
+
+    /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
+    /// the pruning progress.
+    #[cfg(test)]
+    pub fn wake_and_wait(&self, latest_version: Version) -> Result<()> {
+        self.wake(latest_version);
+
+        if latest_version > self.historical_versions_to_keep {
+            let least_readable_version = latest_version - self.historical_versions_to_keep;
+            // Assuming no big pruning chunks will be issued by a test.
+            const TIMEOUT: Duration = Duration::from_secs(10);
+            let end = Instant::now() + TIMEOUT;
+
+            while Instant::now() < end {
+                if self.least_readable_version() >= least_readable_version {
+                    return Ok(());
+                }
+                sleep(Duration::from_millis(1));
+            }
+            anyhow::bail!("Timeout waiting for ledger pruner worker.");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LedgerPruner {
+    fn drop(&mut self) {
+        self.command_sender
+            .lock()
+            .send(Command::Quit)
+            .expect("Receiver should not destruct.");
+        self.worker_thread
+            .take()
+            .expect("Worker thread must exist.")
+            .join()
+            .expect("Worker thread should join peacefully.");
+    }
+}
+
+struct Worker {
+    db: Arc<DB>,
+    ledger_store: LedgerStore,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
+    command_receiver: Receiver<Command>,
+    target_least_readable_version: Version,
+    /// Keeps a record of the pruning progress, shared with the `LedgerPruner`.
+    least_readable_version: Arc<AtomicU64>,
+    /// Indicates if there's NOT any pending work to do currently, to hint
+    /// `Self::receive_commands()` to `recv()` blocking-ly.
+    blocking_recv: bool,
+}
+
+impl Worker {
+    const MAX_VERSIONS_TO_PRUNE_PER_BATCH: u64 = 100;
+
+    fn new(
+        db: Arc<DB>,
+        command_receiver: Receiver<Command>,
+        least_readable_version: Arc<AtomicU64>,
+    ) -> Self {
+        Self {
+            ledger_store: LedgerStore::new(Arc::clone(&db)),
+            transaction_store: TransactionStore::new(Arc::clone(&db)),
+            event_store: EventStore::new(Arc::clone(&db)),
+            db,
+            command_receiver,
+            target_least_readable_version: 0,
+            least_readable_version,
+            blocking_recv: true,
+        }
+    }
+
+    fn work(mut self) {
+        self.initialize();
+
+        while self.receive_commands() {
+            // Process a reasonably small batch of work before trying to receive commands again,
+            // in case `Command::Quit` is received (that's when we should quit.)
+            let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
+            let new_least_readable_version = std::cmp::min(
+                least_readable_version + Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
+                self.target_least_readable_version,
+            );
+            match self.prune_ledger(least_readable_version, new_least_readable_version) {
+                Ok(()) => {
+                    self.record_progress(new_least_readable_version);
+                    // Make next recv() blocking if nothing left to do.
+                    self.blocking_recv =
+                        new_least_readable_version == self.target_least_readable_version;
+                }
+                Err(e) => {
+                    error!(
+                        error = ?e,
+                        "Error pruning ledger history.",
+                    );
+                    // On error, stop retrying vigorously by making next recv() blocking.
+                    self.blocking_recv = true;
+                }
+            }
+        }
+    }
+
+    /// Find out the first transaction still in the DB, the history before which is already
+    /// pruned.
+    fn initialize(&mut self) {
+        loop {
+            match self.get_least_readable_version() {
+                Ok(least_readable_version) => {
+                    info!(
+                        least_readable_version = least_readable_version,
+                        "[ledger pruner worker] initialized."
+                    );
+                    self.target_least_readable_version = least_readable_version;
+                    self.record_progress(least_readable_version);
+                    return;
+                }
+                Err(e) => {
+                    error!(
+                        error = ?e,
+                        "[ledger pruner worker] Error on first seek. Retrying in 1 second.",
+                    );
+                    sleep(Duration::from_secs(1));
+                }
+            }
+        }
+    }
+
+    fn get_least_readable_version(&self) -> Result<Version> {
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map_or(0, |(version, _)| version))
+    }
+
+    /// Deletes the ledger history in `[begin, end)` in a single DB write.
+    fn prune_ledger(&self, begin: Version, end: Version) -> Result<()> {
+        if begin >= end {
+            return Ok(());
+        }
+
+        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+            .with_label_values(&["ledger_pruner_commit"])
+            .start_timer();
+        let mut batch = SchemaBatch::new();
+        self.transaction_store
+            .prune_transactions(begin, end, &mut batch)?;
+        self.ledger_store
+            .prune_transaction_infos(begin, end, &mut batch)?;
+        self.event_store.prune_events(begin, end, &mut batch)?;
+        self.db.write_schemas(batch)
+    }
+
+    /// Log the progress.
+    fn record_progress(&mut self, least_readable_version: Version) {
+        self.least_readable_version
+            .store(least_readable_version, Ordering::Relaxed);
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION.set(least_readable_version as i64);
+    }
+
+    /// Tries to receive all pending commands, blocking waits for the next command if no work needs
+    /// to be done, otherwise quits with `true` to allow the outer loop to do some work before
+    /// getting back here.
+    ///
+    /// Returns `false` if `Command::Quit` is received, to break the outer loop and let `work()`
+    /// return.
+    fn receive_commands(&mut self) -> bool {
+        loop {
+            let command = if self.blocking_recv {
+                // Worker has nothing to do, blocking wait for the next command.
+                self.command_receiver
+                    .recv()
+                    .expect("Sender should not destruct prematurely.")
+            } else {
+                // Worker has pending work to do, non-blocking recv.
+                match self.command_receiver.try_recv() {
+                    Ok(command) => command,
+                    // Channel has drained, yield control to the outer loop.
+                    Err(_) => return true,
+                }
+            };
+
+            match command {
+                // On `Command::Quit` inform the outer loop to quit by returning `false`.
+                Command::Quit => return false,
+                Command::Prune {
+                    least_readable_version,
+                } => {
+                    if least_readable_version > self.target_least_readable_version {
+                        self.target_least_readable_version = least_readable_version;
+                        // Switch to non-blocking to allow some work to be done after the
+                        // channel has drained.
+                        self.blocking_recv = false;
+                    }
+                }
+            }
+        }
+    }
+}
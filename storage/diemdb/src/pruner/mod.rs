@@ -3,6 +3,12 @@
 
 //! This module provides `Pruner` which manages a thread pruning old data in the background and is
 //! meant to be triggered by other threads as they commit new data to the DB.
+//!
+//! `Pruner` prunes stale Jellyfish Merkle nodes, while `LedgerPruner` prunes the ledger history.
+
+mod ledger_pruner;
+
+pub(crate) use ledger_pruner::LedgerPruner;
 
 use crate::{
     metrics::{
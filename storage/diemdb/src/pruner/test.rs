@@ -2,11 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::{change_set::ChangeSet, state_store::StateStore, DiemDB};
+use crate::{
+    change_set::ChangeSet, errors::DiemDbError, state_store::StateStore,
+    test_helper::arb_blocks_to_commit, DiemDB,
+};
+use diem_config::config::RocksdbConfig;
 use diem_crypto::HashValue;
 use diem_temppath::TempPath;
-use diem_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
-use std::collections::HashMap;
+use diem_types::{
+    account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    account_state::AccountState,
+    account_state_blob::AccountStateBlob,
+    block_metadata::new_block_event_key,
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+};
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+use proptest::prelude::*;
+use std::{collections::HashMap, convert::TryFrom};
+use storage_interface::{DbReader, DbWriter, Order};
 
 fn put_account_state_set(
     db: &DB,
@@ -155,3 +171,255 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
+
+fn test_ledger_pruner_impl(blocks: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::open(
+        &tmp_dir,
+        false,   /* readonly */
+        None,    /* pruner */
+        Some(0), /* ledger pruner */
+        RocksdbConfig::default(),
+    )
+    .unwrap();
+
+    let mut cur_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let latest_version = cur_ver - 1;
+    let ledger_pruner = db.ledger_pruner.as_ref().unwrap();
+    ledger_pruner.wake_and_wait(latest_version).unwrap();
+    let least_readable_version = ledger_pruner.least_readable_version();
+    assert_eq!(least_readable_version, latest_version);
+
+    let latest_ledger_info = blocks.last().unwrap().1.ledger_info();
+    let txns_to_commit = blocks.iter().flat_map(|(txns, _)| txns);
+    for (version, txn_to_commit) in txns_to_commit.enumerate() {
+        let version = version as Version;
+        if version < least_readable_version {
+            assert!(db.transaction_store.get_transaction(version).is_err());
+            assert!(db.transaction_store.get_write_set(version).is_err());
+            assert!(db.ledger_store.get_transaction_info(version).is_err());
+            assert!(db
+                .event_store
+                .get_events_by_version(version)
+                .unwrap()
+                .is_empty());
+            let err = db
+                .get_transactions(version, 1, latest_version, false)
+                .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<DiemDbError>(),
+                Some(DiemDbError::Pruned(_))
+            ));
+        } else {
+            assert_eq!(
+                &db.transaction_store.get_transaction(version).unwrap(),
+                txn_to_commit.transaction()
+            );
+            assert_eq!(
+                &db.event_store.get_events_by_version(version).unwrap(),
+                txn_to_commit.events()
+            );
+            assert_eq!(
+                &db.get_write_set(version).unwrap(),
+                txn_to_commit.write_set()
+            );
+            // The transaction accumulator is kept, so what's left can still be proven.
+            db.ledger_store
+                .get_transaction_info_with_proof(version, latest_version)
+                .unwrap()
+                .verify(latest_ledger_info, version)
+                .unwrap();
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_ledger_pruner(blocks in arb_blocks_to_commit()) {
+        test_ledger_pruner_impl(blocks);
+    }
+}
+
+fn is_pruned<T>(result: Result<T>) -> bool {
+    matches!(
+        result
+            .err()
+            .as_ref()
+            .and_then(|err| err.downcast_ref::<DiemDbError>()),
+        Some(DiemDbError::Pruned(_))
+    )
+}
+
+fn test_ledger_pruner_readers_impl(
+    blocks: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    // Keep the second half of the history, so that the first half is pruned.
+    let num_txns = blocks
+        .iter()
+        .map(|(txns, _)| txns.len() as u64)
+        .sum::<u64>();
+    let latest_version = num_txns - 1;
+    let least_readable_version = num_txns / 2;
+    let versions_to_keep = latest_version - least_readable_version;
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::open(
+        &tmp_dir,
+        false,                  /* readonly */
+        None,                   /* pruner */
+        Some(versions_to_keep), /* ledger pruner */
+        RocksdbConfig::default(),
+    )
+    .unwrap();
+
+    let mut cur_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let ledger_pruner = db.ledger_pruner.as_ref().unwrap();
+    ledger_pruner.wake_and_wait(latest_version).unwrap();
+    assert_eq!(
+        ledger_pruner.least_readable_version(),
+        least_readable_version
+    );
+
+    let txns_to_commit: Vec<_> = blocks.iter().flat_map(|(txns, _)| txns).collect();
+    let kept = &txns_to_commit[least_readable_version as usize..];
+    for (version, txn_to_commit) in txns_to_commit.iter().enumerate() {
+        let version = version as Version;
+        let pruned = version < least_readable_version;
+        let txn = txn_to_commit.transaction().as_signed_user_txn().unwrap();
+
+        assert_eq!(
+            is_pruned(db.get_transactions(version, 1, latest_version, true)),
+            pruned
+        );
+        assert_eq!(is_pruned(db.get_write_set(version)), pruned);
+        assert_eq!(
+            is_pruned(db.get_account_state_with_proof(txn.sender(), version, latest_version)),
+            pruned
+        );
+        if pruned {
+            assert!(is_pruned(db.get_block_timestamp(version)));
+        }
+
+        let result =
+            db.get_txn_by_account(txn.sender(), txn.sequence_number(), latest_version, false);
+        if pruned {
+            // The DB knows the transaction was committed if a later one of the sender is left, or
+            // if the sender's sequence number has passed it.
+            let later_txn_kept = kept
+                .iter()
+                .any(|t| t.transaction().as_signed_user_txn().unwrap().sender() == txn.sender());
+            let sequence_number =
+                db.get_latest_account_state(txn.sender())
+                    .unwrap()
+                    .map_or(0, |blob| {
+                        AccountState::try_from(&blob)
+                            .unwrap()
+                            .get_account_resource()
+                            .unwrap()
+                            .map_or(0, |resource| resource.sequence_number())
+                    });
+            if later_txn_kept || sequence_number > txn.sequence_number() {
+                assert!(is_pruned(result));
+            } else {
+                assert!(result.unwrap().is_none());
+            }
+        } else {
+            assert_eq!(
+                &result.unwrap().unwrap().transaction,
+                txn_to_commit.transaction()
+            );
+        }
+
+        for event in txn_to_commit.events() {
+            let result = db.get_events(
+                event.key(),
+                event.sequence_number(),
+                Order::Ascending,
+                1, /* limit */
+            );
+            if pruned {
+                let later_event_kept = kept
+                    .iter()
+                    .flat_map(|t| t.events())
+                    .any(|e| e.key() == event.key());
+                if later_event_kept {
+                    assert!(is_pruned(result));
+                } else {
+                    // A stream whose events have all been pruned reads as empty.
+                    assert!(result.unwrap().is_empty());
+                }
+            } else {
+                assert_eq!(result.unwrap(), vec![(version, event.clone())]);
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_ledger_pruner_readers(blocks in arb_blocks_to_commit()) {
+        test_ledger_pruner_readers_impl(blocks);
+    }
+}
+
+#[test]
+fn test_ledger_pruner_block_search() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = &db.event_store;
+
+    // A block every 10 versions, a second apart.
+    let mut cs = ChangeSet::new();
+    for seq in 0..10u64 {
+        let new_block_event = NewBlockEvent::new(
+            seq,                   // round
+            AccountAddress::ZERO,  // proposer
+            Vec::new(),            // prev block voters
+            (seq + 1) * 1_000_000, // timestamp
+        );
+        let event = ContractEvent::new(
+            new_block_event_key(),
+            seq,
+            TypeTag::Struct(NewBlockEvent::struct_tag()),
+            bcs::to_bytes(&new_block_event).unwrap(),
+        );
+        store.put_events(seq * 10, &[event], &mut cs).unwrap();
+    }
+    db.db.write_schemas(cs.batch).unwrap();
+
+    // Prune the first 4 blocks.
+    let mut batch = SchemaBatch::new();
+    store.prune_events(0, 40, &mut batch).unwrap();
+    db.db.write_schemas(batch).unwrap();
+
+    // The blocks left bound the answer.
+    assert_eq!(
+        db.get_last_version_before_timestamp(5_500_000, 90).unwrap(),
+        49
+    );
+    assert_eq!(
+        db.get_last_version_before_timestamp(10_000_000, 90)
+            .unwrap(),
+        89
+    );
+    // The first block left may not be the first one at or after the timestamp.
+    assert!(is_pruned(
+        db.get_last_version_before_timestamp(5_000_000, 90)
+    ));
+    assert!(is_pruned(
+        db.get_last_version_before_timestamp(1_500_000, 90)
+    ));
+}
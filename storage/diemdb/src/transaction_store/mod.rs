@@ -6,15 +6,19 @@
 use crate::{
     change_set::ChangeSet,
     errors::DiemDbError,
-    schema::{transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema},
+    schema::{
+        transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        write_set::WriteSetSchema,
+    },
 };
 use anyhow::{ensure, format_err, Result};
 use diem_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
     transaction::{Transaction, Version},
+    write_set::WriteSet,
 };
-use schemadb::{SchemaIterator, DB};
+use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -46,6 +50,25 @@ impl TransactionStore {
         Ok(None)
     }
 
+    /// Gets the sequence number of the oldest transaction of `address` that is still stored, which
+    /// is greater than 0 once the older transactions of the account have been pruned.
+    pub fn get_first_sequence_number(&self, address: AccountAddress) -> Result<Option<u64>> {
+        let mut iter = self
+            .db
+            .iter::<TransactionByAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, 0))?;
+
+        Ok(iter.next().transpose()?.and_then(
+            |((sender, seq), _)| {
+                if sender == address {
+                    Some(seq)
+                } else {
+                    None
+                }
+            },
+        ))
+    }
+
     /// Get signed transaction given `version`
     pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
         self.db
@@ -83,6 +106,7 @@ impl TransactionStore {
         // each block.
         let mut iter = self.db.rev_iter::<TransactionSchema>(Default::default())?;
         iter.seek(&version)?;
+        let mut num_searched = 0;
         for res in iter.take(MAX_VERSIONS_TO_SEARCH) {
             let (v, txn) = res?;
             if let Transaction::BlockMetadata(block_meta) = txn {
@@ -90,6 +114,15 @@ impl TransactionStore {
             } else if v == 0 {
                 return Ok(None);
             }
+            num_searched += 1;
+        }
+        if num_searched < MAX_VERSIONS_TO_SEARCH {
+            // Ran out of transactions before the genesis: the block started in pruned history.
+            return Err(DiemDbError::Pruned(format!(
+                "BlockMetadata preceding version {}",
+                version
+            ))
+            .into());
         }
 
         Err(DiemDbError::NotFound(format!("BlockMetadata preceding version {}", version)).into())
@@ -112,6 +145,53 @@ impl TransactionStore {
 
         Ok(())
     }
+
+    /// Get the write set of the transaction at `version`.
+    pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
+        match self.db.get::<WriteSetSchema>(&version)? {
+            Some(write_set) => Ok(write_set),
+            None if self.db.get::<TransactionSchema>(&version)?.is_some() => {
+                Err(DiemDbError::WriteSetUnavailable(version).into())
+            }
+            None => Err(DiemDbError::NotFound(format!("WriteSet {}", version)).into()),
+        }
+    }
+
+    /// Save the write set of the transaction at `version`.
+    pub fn put_write_set(
+        &self,
+        version: Version,
+        write_set: &WriteSet,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        cs.batch.put::<WriteSetSchema>(&version, write_set)
+    }
+
+    /// Deletes the transactions in `[begin, end)` together with their write sets and the account
+    /// index entries of the user transactions among them.
+    pub fn prune_transactions(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let (version, transaction) = res?;
+            if version >= end {
+                break;
+            }
+            if let Transaction::UserTransaction(txn) = transaction {
+                db_batch
+                    .delete::<TransactionByAccountSchema>(&(txn.sender(), txn.sequence_number()))?;
+            }
+            db_batch.delete::<TransactionSchema>(&version)?;
+            db_batch.delete::<WriteSetSchema>(&version)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub struct TransactionIter<'a> {
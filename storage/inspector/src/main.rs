@@ -171,6 +171,7 @@ fn main() {
         p,
         true, /* readonly */
         None, /* pruner */
+        None, /* ledger pruner */
         RocksdbConfig::default(),
     )
     .expect("Unable to open DiemDB");
@@ -2,16 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    iterator::JellyfishMerkleIterator,
+    iterator::{JellyfishMerkleDeltaIterator, JellyfishMerkleIterator},
     mock_tree_store::MockTreeStore,
+    node_type::{LeafNode, Node, NodeKey},
     test_helper::{plus_one, ValueBlob},
-    JellyfishMerkleTree,
+    JellyfishMerkleTree, MissingRootError, TreeReader,
 };
 use anyhow::Result;
 use diem_crypto::HashValue;
 use diem_types::transaction::Version;
 use rand::{rngs::StdRng, SeedableRng};
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 #[test]
 fn test_iterator_same_version() {
@@ -110,3 +117,131 @@ where
         assert_eq!(iter.collect::<Result<Vec<_>>>().unwrap(), vec![]);
     }
 }
+
+/// A `MockTreeStore` which counts the nodes read from it.
+#[derive(Default)]
+struct CountingTreeStore {
+    inner: MockTreeStore<ValueBlob>,
+    num_reads: AtomicUsize,
+}
+
+impl TreeReader<ValueBlob> for CountingTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node<ValueBlob>>> {
+        self.num_reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode<ValueBlob>)>> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+/// Writes `n` random keys at version 0, then at version 1 updates one in every `step` of them and
+/// adds as many new keys.
+fn init_two_versions(
+    n: usize,
+    step: usize,
+) -> (
+    Arc<CountingTreeStore>,
+    BTreeMap<HashValue, ValueBlob>,
+    BTreeMap<HashValue, ValueBlob>,
+) {
+    let db = Arc::new(CountingTreeStore::default());
+    let tree = JellyfishMerkleTree::new(&db.inner);
+    let mut rng = StdRng::from_seed([1; 32]);
+
+    let base: BTreeMap<_, _> = (0..n)
+        .map(|i| {
+            let key = HashValue::random_with_rng(&mut rng);
+            (key, ValueBlob::from(i.to_be_bytes().to_vec()))
+        })
+        .collect();
+    let (_root_hash, batch) = tree
+        .put_value_set(base.clone().into_iter().collect(), 0 /* version */)
+        .unwrap();
+    db.inner.write_tree_update_batch(batch).unwrap();
+
+    let mut delta: BTreeMap<_, _> = base
+        .keys()
+        .step_by(step)
+        .map(|key| (*key, ValueBlob::from(b"updated".to_vec())))
+        .collect();
+    for i in 0..delta.len() {
+        let key = HashValue::random_with_rng(&mut rng);
+        delta.insert(key, ValueBlob::from((n + i).to_be_bytes().to_vec()));
+    }
+    let (_root_hash, batch) = tree
+        .put_value_set(delta.clone().into_iter().collect(), 1 /* version */)
+        .unwrap();
+    db.inner.write_tree_update_batch(batch).unwrap();
+
+    (db, base, delta)
+}
+
+#[test]
+fn test_delta_iterator() {
+    let (db, _base, delta) = init_two_versions(100, 10);
+
+    let iter = JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 0, 1, HashValue::zero()).unwrap();
+    assert_eq!(
+        iter.collect::<Result<Vec<_>>>().unwrap(),
+        delta.clone().into_iter().collect::<Vec<_>>(),
+    );
+
+    for (i, key) in delta.keys().enumerate() {
+        let iter = JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 0, 1, *key).unwrap();
+        assert_eq!(
+            iter.collect::<Result<Vec<_>>>().unwrap(),
+            delta.clone().into_iter().skip(i).collect::<Vec<_>>(),
+        );
+
+        let iter =
+            JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 0, 1, plus_one(*key)).unwrap();
+        assert_eq!(
+            iter.collect::<Result<Vec<_>>>().unwrap(),
+            delta.clone().into_iter().skip(i + 1).collect::<Vec<_>>(),
+        );
+    }
+
+    // Nothing changed from a version to itself.
+    let iter = JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 1, 1, HashValue::zero()).unwrap();
+    assert_eq!(iter.collect::<Result<Vec<_>>>().unwrap(), vec![]);
+}
+
+#[test]
+fn test_delta_iterator_skips_unchanged_subtrees() {
+    let (db, base, delta) = init_two_versions(1000, 100);
+
+    db.num_reads.store(0, Ordering::Relaxed);
+    let num_changed = JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 0, 1, HashValue::zero())
+        .unwrap()
+        .count();
+    assert_eq!(num_changed, delta.len());
+    let num_delta_reads = db.num_reads.load(Ordering::Relaxed);
+
+    db.num_reads.store(0, Ordering::Relaxed);
+    let num_accounts = JellyfishMerkleIterator::new(Arc::clone(&db), 1, HashValue::zero())
+        .unwrap()
+        .count();
+    assert_eq!(num_accounts, base.len() + delta.len() / 2);
+    let num_full_reads = db.num_reads.load(Ordering::Relaxed);
+
+    // Only the paths to the changed keys are read, in the base version as well.
+    assert!(
+        num_delta_reads * 2 < num_full_reads,
+        "read {} nodes for the delta, {} for the whole tree",
+        num_delta_reads,
+        num_full_reads,
+    );
+}
+
+#[test]
+fn test_delta_iterator_pruned_base() {
+    let (db, _base, _delta) = init_two_versions(100, 10);
+    db.inner.purge_stale_nodes(1).unwrap();
+
+    let err = JellyfishMerkleDeltaIterator::new(Arc::clone(&db), 0, 1, HashValue::zero())
+        .err()
+        .unwrap();
+    assert!(err.downcast_ref::<MissingRootError>().is_some());
+}
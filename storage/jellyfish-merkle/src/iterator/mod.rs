@@ -5,14 +5,18 @@
 //! iterator generates all the key-value pairs in this version of the tree, starting from the
 //! smallest key that is greater or equal to the given key, by performing a depth first traversal
 //! on the tree.
+//!
+//! It also implements `JellyfishMerkleDeltaIterator`, which generates the key-value pairs of a
+//! version of the tree that differ from those of an earlier version, without visiting the
+//! subtrees the two versions share.
 
 #[cfg(test)]
 mod iterator_test;
 
 use crate::{
     nibble_path::NibblePath,
-    node_type::{InternalNode, Node, NodeKey},
-    TreeReader,
+    node_type::{InternalNode, LeafNode, Node, NodeKey},
+    MissingRootError, NibbleExt, TreeReader,
 };
 use anyhow::{format_err, Result};
 use diem_crypto::HashValue;
@@ -262,3 +266,206 @@ where
         }
     }
 }
+
+/// The subtree of the base version of the tree at the position of a subtree of the target version
+/// being visited by a [`JellyfishMerkleDeltaIterator`].
+#[derive(Debug)]
+enum BaseSubtree<V> {
+    /// There is no key under this position in the base version.
+    Empty,
+    /// The node at this position in the base version, which hasn't been read yet.
+    Node { node_key: NodeKey, hash: HashValue },
+    /// A leaf of the base version higher up in the tree, whose key falls under this position. It
+    /// moved down in the target version because keys sharing a prefix with it were added.
+    Leaf(LeafNode<V>),
+}
+
+impl<V> BaseSubtree<V>
+where
+    V: crate::Value,
+{
+    fn hash(&self) -> Option<HashValue> {
+        match self {
+            BaseSubtree::Empty => None,
+            BaseSubtree::Node { hash, .. } => Some(*hash),
+            BaseSubtree::Leaf(leaf_node) => Some(leaf_node.hash()),
+        }
+    }
+}
+
+/// A subtree of the target version left to visit.
+#[derive(Debug)]
+struct DeltaVisitInfo<V> {
+    node_key: NodeKey,
+    hash: HashValue,
+    base: BaseSubtree<V>,
+}
+
+/// Generates, in key order and starting from the smallest key that is greater or equal to a given
+/// key, the key-value pairs of the target version of the tree that are not in the base version.
+///
+/// Subtrees whose hashes are the same in both versions are skipped without being read, so the
+/// nodes read are only those on the paths to the changed keys.
+pub struct JellyfishMerkleDeltaIterator<R, V> {
+    /// The storage engine from which we can read nodes using node keys.
+    reader: Arc<R>,
+
+    /// The smallest key to generate.
+    starting_key: HashValue,
+
+    /// The stack used for depth first traversal, with the next subtree to visit on top.
+    stack: Vec<DeltaVisitInfo<V>>,
+}
+
+impl<R, V> JellyfishMerkleDeltaIterator<R, V>
+where
+    R: TreeReader<V>,
+    V: crate::Value,
+{
+    /// Constructs a new iterator over the changes from `base_version` to `version`. Fails with a
+    /// [`MissingRootError`] if the base version of the tree has been pruned.
+    pub fn new(
+        reader: Arc<R>,
+        base_version: Version,
+        version: Version,
+        starting_key: HashValue,
+    ) -> Result<Self> {
+        let base_root_key = NodeKey::new_empty_path(base_version);
+        let base_root = reader
+            .get_node_option(&base_root_key)?
+            .ok_or(MissingRootError {
+                version: base_version,
+            })?;
+        let base = match base_root {
+            Node::Null => BaseSubtree::Empty,
+            base_root => BaseSubtree::Node {
+                node_key: base_root_key,
+                hash: base_root.hash(),
+            },
+        };
+
+        let root_key = NodeKey::new_empty_path(version);
+        let stack = match reader.get_node(&root_key)? {
+            Node::Null => vec![],
+            root => vec![DeltaVisitInfo {
+                node_key: root_key,
+                hash: root.hash(),
+                base,
+            }],
+        };
+
+        Ok(Self {
+            reader,
+            starting_key,
+            stack,
+        })
+    }
+
+    /// Whether all the keys under `nibble_path` are smaller than `self.starting_key`.
+    fn is_before_starting_key(&self, nibble_path: &NibblePath) -> bool {
+        for (i, nibble) in nibble_path.nibbles().enumerate() {
+            let starting_nibble = self.starting_key.get_nibble(i);
+            if nibble != starting_nibble {
+                return nibble < starting_nibble;
+            }
+        }
+        false
+    }
+
+    /// Pushes the children of the internal node at `node_key` in the target version onto the
+    /// stack, together with the subtrees of the base version at their positions.
+    fn push_children(
+        &mut self,
+        node_key: &NodeKey,
+        node: &InternalNode,
+        base: BaseSubtree<V>,
+    ) -> Result<()> {
+        let base = match base {
+            BaseSubtree::Node { node_key, .. } => match self.reader.get_node(&node_key)? {
+                Node::Internal(internal_node) => BaseChildren::Internal(node_key, internal_node),
+                Node::Leaf(leaf_node) => BaseChildren::Leaf(leaf_node),
+                Node::Null => BaseChildren::Empty,
+            },
+            BaseSubtree::Leaf(leaf_node) => BaseChildren::Leaf(leaf_node),
+            BaseSubtree::Empty => BaseChildren::Empty,
+        };
+        let depth = node_key.nibble_path().num_nibbles();
+
+        // Push the children from right to left, so they are visited from left to right.
+        for i in (0..16u8).rev() {
+            let nibble = Nibble::from(i);
+            let child = match node.child(nibble) {
+                Some(child) => child,
+                None => continue,
+            };
+            let child_node_key = node_key.gen_child_node_key(child.version, nibble);
+            if self.is_before_starting_key(child_node_key.nibble_path()) {
+                // So are the children on its left.
+                break;
+            }
+            let child_base = match &base {
+                BaseChildren::Internal(base_node_key, base_node) => match base_node.child(nibble) {
+                    Some(base_child) => BaseSubtree::Node {
+                        node_key: base_node_key.gen_child_node_key(base_child.version, nibble),
+                        hash: base_child.hash,
+                    },
+                    None => BaseSubtree::Empty,
+                },
+                BaseChildren::Leaf(leaf_node)
+                    if leaf_node.account_key().get_nibble(depth) == nibble =>
+                {
+                    BaseSubtree::Leaf(leaf_node.clone())
+                }
+                BaseChildren::Leaf(_) | BaseChildren::Empty => BaseSubtree::Empty,
+            };
+            self.stack.push(DeltaVisitInfo {
+                node_key: child_node_key,
+                hash: child.hash,
+                base: child_base,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The children of the base version of the tree at the position of an internal node of the
+/// target version.
+enum BaseChildren<V> {
+    Empty,
+    Internal(NodeKey, InternalNode),
+    Leaf(LeafNode<V>),
+}
+
+impl<R, V> Iterator for JellyfishMerkleDeltaIterator<R, V>
+where
+    R: TreeReader<V>,
+    V: crate::Value,
+{
+    type Item = Result<(HashValue, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(visit_info) = self.stack.pop() {
+            if visit_info.base.hash() == Some(visit_info.hash) {
+                // The subtree is the same in both versions.
+                continue;
+            }
+            match self.reader.get_node(&visit_info.node_key) {
+                Ok(Node::Internal(internal_node)) => {
+                    if let Err(err) =
+                        self.push_children(&visit_info.node_key, &internal_node, visit_info.base)
+                    {
+                        return Some(Err(err));
+                    }
+                }
+                Ok(Node::Leaf(leaf_node)) => {
+                    if leaf_node.account_key() >= self.starting_key {
+                        return Some(Ok((leaf_node.account_key(), leaf_node.value().clone())));
+                    }
+                }
+                Ok(Node::Null) => return Some(Err(format_err!("Should not reach a null node."))),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
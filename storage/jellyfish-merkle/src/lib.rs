@@ -141,7 +141,7 @@ pub trait Value: Clone + CryptoHash + Serialize + DeserializeOwned {}
 /// tests.
 #[cfg(any(test, feature = "fuzzing"))]
 pub trait TestValue:
-    Value + Arbitrary + Clone + std::fmt::Debug + Eq + PartialEq + 'static
+    Value + Arbitrary + Clone + std::fmt::Debug + Eq + PartialEq + Send + Sync + 'static
 {
 }
 
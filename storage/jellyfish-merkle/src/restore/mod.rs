@@ -105,7 +105,7 @@ where
 
 pub struct JellyfishMerkleRestore<V> {
     /// The underlying storage.
-    store: Arc<dyn TreeWriter<V>>,
+    store: Arc<dyn TreeWriter<V> + Send + Sync>,
 
     /// The version of the tree we are restoring.
     version: Version,
@@ -160,7 +160,7 @@ impl<V> JellyfishMerkleRestore<V>
 where
     V: crate::Value,
 {
-    pub fn new<D: 'static + TreeReader<V> + TreeWriter<V>>(
+    pub fn new<D: 'static + TreeReader<V> + TreeWriter<V> + Send + Sync>(
         store: Arc<D>,
         version: Version,
         expected_root_hash: HashValue,
@@ -193,7 +193,7 @@ where
         })
     }
 
-    pub fn new_overwrite<D: 'static + TreeWriter<V>>(
+    pub fn new_overwrite<D: 'static + TreeWriter<V> + Send + Sync>(
         store: Arc<D>,
         version: Version,
         expected_root_hash: HashValue,
@@ -675,3 +675,34 @@ where
         self.store.write_node_batch(&self.frozen_nodes)
     }
 }
+
+/// A receiver of an account state snapshot, fed with chunks of accounts in increasing key order.
+/// The snapshot is only complete once `finish_box` succeeds.
+pub trait StateSnapshotReceiver<V>: Send {
+    /// Verifies and persists the given chunk, see `JellyfishMerkleRestore::add_chunk`.
+    fn add_chunk(
+        &mut self,
+        chunk: Vec<(HashValue, V)>,
+        proof: SparseMerkleRangeProof,
+    ) -> Result<()>;
+
+    /// Signals that there are no more accounts, see `JellyfishMerkleRestore::finish`.
+    fn finish_box(self: Box<Self>) -> Result<()>;
+}
+
+impl<V> StateSnapshotReceiver<V> for JellyfishMerkleRestore<V>
+where
+    V: crate::Value + Send,
+{
+    fn add_chunk(
+        &mut self,
+        chunk: Vec<(HashValue, V)>,
+        proof: SparseMerkleRangeProof,
+    ) -> Result<()> {
+        JellyfishMerkleRestore::add_chunk(self, chunk, proof)
+    }
+
+    fn finish_box(self: Box<Self>) -> Result<()> {
+        JellyfishMerkleRestore::finish(*self)
+    }
+}
@@ -3,6 +3,7 @@
 
 use anyhow::{format_err, Result};
 use diem_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
+pub use diem_jellyfish_merkle::restore::StateSnapshotReceiver;
 use diem_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
@@ -14,11 +15,15 @@ use diem_types::{
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
-    proof::{definition::LeafCount, AccumulatorConsistencyProof, SparseMerkleProof},
+    proof::{
+        definition::LeafCount, AccumulatorConsistencyProof, SparseMerkleProof,
+        SparseMerkleRangeProof,
+    },
     transaction::{
         TransactionInfo, TransactionListWithProof, TransactionToCommit, TransactionWithProof,
         Version,
     },
+    write_set::WriteSet,
 };
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -161,6 +166,23 @@ pub enum Order {
     Descending,
 }
 
+/// A chunk of the account states in the state tree at a given version, in increasing order of the
+/// hashed account keys, with the proof of the range.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AccountStateChunkWithProof {
+    pub account_blobs: Vec<(HashValue, AccountStateBlob)>,
+    pub proof: SparseMerkleRangeProof,
+    /// Whether the chunk ends with the rightmost account of the tree.
+    pub is_last_chunk: bool,
+}
+
+impl AccountStateChunkWithProof {
+    /// Returns the hashed key of the last account in the chunk, if any.
+    pub fn last_key(&self) -> Option<HashValue> {
+        self.account_blobs.last().map(|(key, _blob)| *key)
+    }
+}
+
 /// Trait that is implemented by a DB that supports certain public (to client) read APIs
 /// expected of a Diem DB
 pub trait DbReader: Send + Sync {
@@ -327,6 +349,32 @@ pub trait DbReader: Send + Sync {
     fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
         unimplemented!()
     }
+
+    /// Gets the frozen subtree roots of the transaction accumulator with `num_transactions`
+    /// leaves, from left to right.
+    fn get_accumulator_frozen_subtrees(
+        &self,
+        _num_transactions: LeafCount,
+    ) -> Result<Vec<HashValue>> {
+        unimplemented!()
+    }
+
+    /// Gets the write set of the transaction at `version`.
+    fn get_write_set(&self, _version: Version) -> Result<WriteSet> {
+        unimplemented!()
+    }
+
+    /// Gets at most `chunk_size` account states in the state tree at `version`, starting from the
+    /// first account whose key is greater than `last_key` (from the leftmost account if `None`),
+    /// with the proof of the range.
+    fn get_account_chunk_with_proof(
+        &self,
+        _version: Version,
+        _last_key: Option<HashValue>,
+        _chunk_size: usize,
+    ) -> Result<AccountStateChunkWithProof> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -404,6 +452,32 @@ pub trait DbWriter: Send + Sync {
         first_version: Version,
         ledger_info_with_sigs: Option<&LedgerInfoWithSignatures>,
     ) -> Result<()>;
+
+    /// Gets a receiver that restores the state tree at `version`, which is expected to have
+    /// `expected_root_hash` once complete.
+    fn get_state_snapshot_receiver(
+        &self,
+        _version: Version,
+        _expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>> {
+        unimplemented!()
+    }
+
+    /// Finalizes a state snapshot restored through `get_state_snapshot_receiver`, making
+    /// `ledger_info_with_sigs` the latest ledger info. `output_with_proof` carries the transaction
+    /// at the snapshot version and `frozen_subtrees` the frozen subtree roots of the transaction
+    /// accumulator right before it, so that later transactions can be committed on top of the
+    /// snapshot. The ledger history before the snapshot version is not restored, nor is the write
+    /// set of the transaction at the snapshot version, which `output_with_proof` doesn't carry.
+    fn finalize_state_snapshot(
+        &self,
+        _version: Version,
+        _output_with_proof: TransactionListWithProof,
+        _frozen_subtrees: &[HashValue],
+        _ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
@@ -7,14 +7,17 @@ use crate::{
 };
 use cli::client_proxy::ClientProxy;
 use diem_client::{
-    Client, InMemoryStorage, MethodRequest, MethodResponse, Response, Result, VerifyingClient,
+    Client, FileStorage, InMemoryStorage, MethodRequest, MethodResponse, Response, Result,
+    VerifyingClient,
 };
+use diem_temppath::TempPath;
 use diem_types::{
     account_address::AccountAddress,
     account_config::constants::addresses::{
         diem_root_address, testnet_dd_account_address, treasury_compliance_account_address,
         validator_set_address,
     },
+    chain_id::ChainId,
     event::{EventHandle, EventKey},
     transaction::Version,
     trusted_state::TrustedState,
@@ -50,7 +53,7 @@ impl Environment {
         let trusted_state = TrustedState::from(genesis_waypoint);
         let storage = InMemoryStorage::new();
         let verifying_client =
-            VerifyingClient::new_with_state(client.clone(), trusted_state, storage);
+            VerifyingClient::new_with_state(client.clone(), trusted_state, storage).unwrap();
 
         Self {
             _env: env,
@@ -255,9 +258,13 @@ fn arb_request(
     });
 
     prop_oneof![
-        (arb_account, arb_version).prop_map(|(a, v)| MethodRequest::GetAccount(a, Some(v))),
-        (arb_version_and_limit, arb_include_events)
+        (arb_account.clone(), arb_version).prop_map(|(a, v)| MethodRequest::GetAccount(a, Some(v))),
+        (arb_version_and_limit, arb_include_events.clone())
             .prop_map(|((v, l), i)| MethodRequest::GetTransactions(v, l, i)),
+        (arb_account.clone(), 0u64..10, arb_include_events.clone())
+            .prop_map(|(a, s, i)| MethodRequest::GetAccountTransaction(a, s, i)),
+        (arb_account, 0u64..10, 1u64..10, arb_include_events)
+            .prop_map(|(a, s, l, i)| MethodRequest::GetAccountTransactions(a, s, l, i)),
         arb_events.prop_map(|(k, s, l)| MethodRequest::GetEvents(k, s, l)),
         Just(MethodRequest::get_currencies()),
     ]
@@ -327,3 +334,33 @@ fn test_client_equivalence() {
         assert_batches_equal(recv_nv, recv_v);
     });
 }
+
+#[test]
+fn test_client_trust_persistence() {
+    let rt = Builder::new_current_thread().enable_all().build().unwrap();
+    let env = Environment::new();
+    let path = TempPath::new();
+
+    // Trust the chain of the remote node on first use.
+    let storage = FileStorage::new(path.path()).unwrap();
+    let verifying_client = rt
+        .block_on(VerifyingClient::new_trust_on_first_use(
+            env.client.clone(),
+            storage,
+        ))
+        .unwrap();
+    let trusted_state = verifying_client.trusted_state();
+    let chain_id = verifying_client.chain_id().unwrap().unwrap();
+
+    // The trusted state and chain id are restored from the file.
+    let storage = FileStorage::new(path.path()).unwrap();
+    let verifying_client = VerifyingClient::new(env.client.clone(), storage).unwrap();
+    assert_eq!(verifying_client.trusted_state(), trusted_state);
+    assert_eq!(verifying_client.chain_id().unwrap(), Some(chain_id));
+    rt.block_on(verifying_client.sync()).unwrap();
+    assert!(verifying_client.version() >= trusted_state.version());
+
+    // We keep trusting the same chain.
+    let other_chain_id = ChainId::new(chain_id.id().wrapping_add(1));
+    verifying_client.with_chain_id(other_chain_id).unwrap_err();
+}
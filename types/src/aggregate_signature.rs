@@ -0,0 +1,32 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_bitvec::BitVec;
+use diem_crypto::bls12381::BLS12381Signature;
+use serde::{Deserialize, Serialize};
+
+/// This struct represents a BLS12-381 signature aggregated from the signatures of a set of
+/// validators on the same message. The i-th bit of `validator_bitmask` is set if the i-th
+/// validator, in the order of `ValidatorVerifier::get_ordered_account_addresses_iter`, signed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AggregateSignature {
+    validator_bitmask: BitVec,
+    signature: BLS12381Signature,
+}
+
+impl AggregateSignature {
+    pub fn new(validator_bitmask: BitVec, signature: BLS12381Signature) -> Self {
+        Self {
+            validator_bitmask,
+            signature,
+        }
+    }
+
+    pub fn validator_bitmask(&self) -> &BitVec {
+        &self.validator_bitmask
+    }
+
+    pub fn signature(&self) -> &BLS12381Signature {
+        &self.signature
+    }
+}
@@ -157,6 +157,16 @@ impl BlockInfo {
     pub fn version(&self) -> Version {
         self.version
     }
+
+    /// Used with decoupled execution, where a block is first certified for its position in the
+    /// chain and only later for its execution result. Returns true if `executed_block_info`
+    /// describes the same block as this (ordered only) block info.
+    pub fn match_ordered_only(&self, executed_block_info: &BlockInfo) -> bool {
+        self.epoch == executed_block_info.epoch
+            && self.round == executed_block_info.round
+            && self.id == executed_block_info.id
+            && self.timestamp_usecs == executed_block_info.timestamp_usecs
+    }
 }
 
 impl Display for BlockInfo {
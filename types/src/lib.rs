@@ -8,6 +8,7 @@ pub mod account_address;
 pub mod account_config;
 pub mod account_state;
 pub mod account_state_blob;
+pub mod aggregate_signature;
 pub mod block_info;
 pub mod block_metadata;
 pub mod chain_id;
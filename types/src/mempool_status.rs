@@ -53,6 +53,10 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // Replacement of a pending transaction doesn't bump the gas price enough
+    GasPriceBumpTooLow = 7,
+    // Mempool is full and the transaction isn't ranked above any ready transaction it could evict
+    GasPriceTooLowToEvict = 8,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -67,6 +71,8 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::GasPriceBumpTooLow),
+            8 => Ok(MempoolStatusCode::GasPriceTooLowToEvict),
             _ => Err("invalid StatusCode"),
         }
     }
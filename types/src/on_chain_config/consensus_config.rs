@@ -0,0 +1,77 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use anyhow::{format_err, Result};
+use serde::{Deserialize, Serialize};
+
+/// The consensus config every validator of an epoch agrees on. It's stored on-chain by the
+/// `DiemConsensusConfig` module as BCS bytes, which are empty until Diem root sets a config.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum OnChainConsensusConfig {
+    V1(ConsensusConfigV1),
+}
+
+impl OnChainConsensusConfig {
+    /// Whether DiemBFT commits a block with the two-chain rule instead of the three-chain rule.
+    pub fn two_chain(&self) -> bool {
+        match &self {
+            OnChainConsensusConfig::V1(config) => config.two_chain,
+        }
+    }
+}
+
+/// The config used while none has been set on-chain.
+impl Default for OnChainConsensusConfig {
+    fn default() -> Self {
+        OnChainConsensusConfig::V1(ConsensusConfigV1::default())
+    }
+}
+
+impl OnChainConfig for OnChainConsensusConfig {
+    const IDENTIFIER: &'static str = "DiemConsensusConfig";
+
+    fn deserialize_into_config(bytes: &[u8]) -> Result<Self> {
+        let raw_bytes: Vec<u8> = bcs::from_bytes(bytes).map_err(|e| {
+            format_err!(
+                "Failed first round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })?;
+        if raw_bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        bcs::from_bytes(&raw_bytes).map_err(|e| {
+            format_err!(
+                "Failed second round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ConsensusConfigV1 {
+    pub two_chain: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_into_config() {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 { two_chain: true });
+        let bytes = bcs::to_bytes(&bcs::to_bytes(&config).unwrap()).unwrap();
+        assert_eq!(
+            OnChainConsensusConfig::deserialize_into_config(&bytes).unwrap(),
+            config
+        );
+
+        let empty = bcs::to_bytes(&Vec::<u8>::new()).unwrap();
+        assert_eq!(
+            OnChainConsensusConfig::deserialize_into_config(&empty).unwrap(),
+            OnChainConsensusConfig::default()
+        );
+    }
+}
@@ -17,6 +17,7 @@ use move_core_types::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc};
 
+mod consensus_config;
 mod diem_version;
 mod registered_currencies;
 mod validator_set;
@@ -24,6 +25,7 @@ mod vm_config;
 mod vm_publishing_option;
 
 pub use self::{
+    consensus_config::{ConsensusConfigV1, OnChainConsensusConfig},
     diem_version::{DiemVersion, DIEM_MAX_KNOWN_VERSION, DIEM_VERSION_2, DIEM_VERSION_3},
     registered_currencies::RegisteredCurrencies,
     validator_set::ValidatorSet,
@@ -72,6 +74,10 @@ pub const ON_CHAIN_CONFIG_REGISTRY: &[ConfigID] = &[
     RegisteredCurrencies::CONFIG_ID,
 ];
 
+/// On-chain configs that may not be published yet, state sync only includes them in the payload
+/// once they are
+pub const OPTIONAL_ON_CHAIN_CONFIG_REGISTRY: &[ConfigID] = &[OnChainConsensusConfig::CONFIG_ID];
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct OnChainConfigPayload {
     epoch: u64,
@@ -33,7 +33,7 @@ use diem_crypto::{
     traits::*,
     HashValue,
 };
-use move_core_types::language_storage::TypeTag;
+use move_core_types::{language_storage::TypeTag, move_resource::MoveResource};
 use proptest::{
     collection::{vec, SizeRange},
     option,
@@ -43,7 +43,7 @@ use proptest::{
 use proptest_derive::Arbitrary;
 use serde_json::Value;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryFrom,
     iter::Iterator,
 };
@@ -811,7 +811,7 @@ impl TransactionToCommitGen {
             .collect();
         // Account states must be materialized last, to reflect the latest account and event
         // sequence numbers.
-        let account_states = self
+        let account_states: HashMap<_, _> = self
             .account_state_gens
             .into_iter()
             .map(|(index, blob_gen)| {
@@ -821,10 +821,25 @@ impl TransactionToCommitGen {
                 )
             })
             .collect();
+        // The write set touches exactly the accounts whose states are updated.
+        let write_set = WriteSetMut::new(
+            account_states
+                .iter()
+                .map(|(address, blob)| {
+                    (
+                        AccessPath::new(*address, AccountResource::resource_path()),
+                        WriteOp::Value(blob.into()),
+                    )
+                })
+                .collect(),
+        )
+        .freeze()
+        .expect("generated write set should be valid");
 
         TransactionToCommit::new(
             Transaction::UserTransaction(transaction),
             account_states,
+            write_set,
             events,
             self.gas_used,
             self.status,
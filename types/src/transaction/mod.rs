@@ -991,6 +991,7 @@ impl Display for TransactionInfo {
 pub struct TransactionToCommit {
     transaction: Transaction,
     account_states: HashMap<AccountAddress, AccountStateBlob>,
+    write_set: WriteSet,
     events: Vec<ContractEvent>,
     gas_used: u64,
     status: KeptVMStatus,
@@ -1000,6 +1001,7 @@ impl TransactionToCommit {
     pub fn new(
         transaction: Transaction,
         account_states: HashMap<AccountAddress, AccountStateBlob>,
+        write_set: WriteSet,
         events: Vec<ContractEvent>,
         gas_used: u64,
         status: KeptVMStatus,
@@ -1007,6 +1009,7 @@ impl TransactionToCommit {
         TransactionToCommit {
             transaction,
             account_states,
+            write_set,
             events,
             gas_used,
             status,
@@ -1021,6 +1024,10 @@ impl TransactionToCommit {
         &self.account_states
     }
 
+    pub fn write_set(&self) -> &WriteSet {
+        &self.write_set
+    }
+
     pub fn events(&self) -> &[ContractEvent] {
         &self.events
     }
@@ -1,14 +1,22 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{account_address::AccountAddress, on_chain_config::ValidatorSet};
+use crate::{
+    account_address::AccountAddress, aggregate_signature::AggregateSignature,
+    on_chain_config::ValidatorSet,
+};
+use diem_bitvec::BitVec;
 use diem_crypto::{
+    bls12381::{BLS12381PublicKey, BLS12381Signature},
     ed25519::{Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
     Signature, VerifyingKey,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 use thiserror::Error;
 
 #[cfg(any(test, feature = "fuzzing"))]
@@ -43,6 +51,9 @@ pub enum VerifyError {
     #[error("Signature is invalid")]
     /// The signature does not match the hash.
     InvalidSignature,
+    #[error("Validator bitmask is invalid")]
+    /// The bitmask of an aggregate signature refers to validators that don't exist.
+    InvalidBitVec,
 }
 
 /// Helper struct to manage validator information for validation
@@ -51,6 +62,11 @@ pub enum VerifyError {
 pub struct ValidatorConsensusInfo {
     public_key: Ed25519PublicKey,
     voting_power: u64,
+    /// The BLS12-381 key used for aggregate signatures. It is not part of the on-chain validator
+    /// set yet, so it is provided locally and skipped by serialization.
+    #[serde(skip)]
+    #[cfg_attr(any(test, feature = "fuzzing"), proptest(value = "None"))]
+    bls_public_key: Option<BLS12381PublicKey>,
 }
 
 impl ValidatorConsensusInfo {
@@ -58,6 +74,21 @@ impl ValidatorConsensusInfo {
         ValidatorConsensusInfo {
             public_key,
             voting_power,
+            bls_public_key: None,
+        }
+    }
+
+    /// Creates the validator info of a validator that also signs with a BLS12-381 key, whose
+    /// proof of possession must have been verified beforehand.
+    pub fn new_with_bls_public_key(
+        public_key: Ed25519PublicKey,
+        bls_public_key: BLS12381PublicKey,
+        voting_power: u64,
+    ) -> Self {
+        ValidatorConsensusInfo {
+            public_key,
+            voting_power,
+            bls_public_key: Some(bls_public_key),
         }
     }
 }
@@ -202,6 +233,72 @@ impl ValidatorVerifier {
         Ok(())
     }
 
+    /// Aggregates the BLS12-381 signatures of known authors on the same message into a single
+    /// signature, along with the bitmask of the signers' positions in the ordered validator set.
+    pub fn aggregate_signatures(
+        &self,
+        signatures: &BTreeMap<AccountAddress, BLS12381Signature>,
+    ) -> std::result::Result<AggregateSignature, VerifyError> {
+        let address_to_index: HashMap<&AccountAddress, usize> = self
+            .address_to_validator_info
+            .keys()
+            .enumerate()
+            .map(|(index, address)| (address, index))
+            .collect();
+        let mut validator_bitmask = BitVec::default();
+        let mut sigs = vec![];
+        for (author, signature) in signatures {
+            let index = *address_to_index
+                .get(author)
+                .ok_or(VerifyError::UnknownAuthor)?;
+            if index > u8::MAX as usize {
+                return Err(VerifyError::InvalidBitVec);
+            }
+            validator_bitmask.set(index as u8);
+            sigs.push(signature.clone());
+        }
+        let signature =
+            BLS12381Signature::aggregate(sigs).map_err(|_| VerifyError::InvalidSignature)?;
+        Ok(AggregateSignature::new(validator_bitmask, signature))
+    }
+
+    /// This function will successfully return when the signers designated by the bitmask of the
+    /// aggregate signature have at least quorum voting power, and the aggregate signature is a
+    /// valid signature of the message under their BLS12-381 public keys. A signer without a
+    /// BLS12-381 public key is treated as an unknown author.
+    pub fn verify_aggregate_signature<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        aggregate_signature: &AggregateSignature,
+    ) -> std::result::Result<(), VerifyError> {
+        let validator_bitmask = aggregate_signature.validator_bitmask();
+        if let Some(last_bit) = validator_bitmask.last_set_bit() {
+            if last_bit as usize >= self.len() {
+                return Err(VerifyError::InvalidBitVec);
+            }
+        }
+        let authors: Vec<AccountAddress> = self
+            .get_ordered_account_addresses_iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                *index <= u8::MAX as usize && validator_bitmask.is_set(*index as u8)
+            })
+            .map(|(_, address)| address)
+            .collect();
+        self.check_voting_power(authors.iter())?;
+        let keys = authors
+            .iter()
+            .map(|author| {
+                self.get_bls_public_key(author)
+                    .ok_or(VerifyError::UnknownAuthor)
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        aggregate_signature
+            .signature()
+            .verify_aggregate(message, &keys)
+            .map_err(|_| VerifyError::InvalidSignature)
+    }
+
     /// Ensure there are not more than the maximum expected signatures (all possible signatures).
     fn check_num_of_signatures(
         &self,
@@ -248,6 +345,13 @@ impl ValidatorVerifier {
             .map(|validator_info| validator_info.public_key.clone())
     }
 
+    /// Returns the BLS12-381 public key for this address, if the validator has one.
+    pub fn get_bls_public_key(&self, author: &AccountAddress) -> Option<&BLS12381PublicKey> {
+        self.address_to_validator_info
+            .get(author)
+            .and_then(|validator_info| validator_info.bls_public_key.as_ref())
+    }
+
     /// Returns the voting power for this address.
     pub fn get_voting_power(&self, author: &AccountAddress) -> Option<u64> {
         self.address_to_validator_info
@@ -377,7 +481,12 @@ pub fn random_validator_verifier(
 mod tests {
     use super::*;
     use crate::validator_signer::ValidatorSigner;
-    use diem_crypto::test_utils::{TestDiemCrypto, TEST_SEED};
+    use diem_crypto::{
+        bls12381::BLS12381PrivateKey,
+        test_utils::{TestDiemCrypto, TEST_SEED},
+        SigningKey, Uniform,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
     use std::collections::BTreeMap;
 
     #[test]
@@ -654,4 +763,115 @@ mod tests {
             Err(VerifyError::UnknownAuthor)
         );
     }
+
+    #[test]
+    fn test_aggregate_signature() {
+        const NUM_SIGNERS: u8 = 4;
+        let validator_signers: Vec<ValidatorSigner> = (0..NUM_SIGNERS)
+            .map(|i| ValidatorSigner::random([i; 32]))
+            .collect();
+        let dummy_struct = TestDiemCrypto("Hello, World".to_string());
+
+        let mut rng = StdRng::from_seed(TEST_SEED);
+        let bls_private_keys: BTreeMap<AccountAddress, BLS12381PrivateKey> = validator_signers
+            .iter()
+            .map(|signer| (signer.author(), BLS12381PrivateKey::generate(&mut rng)))
+            .collect();
+        let author_to_validator_info: BTreeMap<AccountAddress, ValidatorConsensusInfo> =
+            validator_signers
+                .iter()
+                .map(|signer| {
+                    let bls_public_key = (&bls_private_keys[&signer.author()]).into();
+                    (
+                        signer.author(),
+                        ValidatorConsensusInfo::new_with_bls_public_key(
+                            signer.public_key(),
+                            bls_public_key,
+                            1,
+                        ),
+                    )
+                })
+                .collect();
+        let validator_verifier = ValidatorVerifier::new(author_to_validator_info);
+
+        // Aggregate the signatures of a quorum (3 out of 4) and verify them.
+        let signatures: BTreeMap<AccountAddress, BLS12381Signature> = bls_private_keys
+            .iter()
+            .take(3)
+            .map(|(author, key)| (*author, key.sign(&dummy_struct)))
+            .collect();
+        let aggregate_signature = validator_verifier
+            .aggregate_signatures(&signatures)
+            .unwrap();
+        assert_eq!(aggregate_signature.validator_bitmask().count_ones(), 3);
+        assert_eq!(
+            validator_verifier.verify_aggregate_signature(&dummy_struct, &aggregate_signature),
+            Ok(())
+        );
+
+        // A different message fails.
+        let other_struct = TestDiemCrypto("Hello, Diem".to_string());
+        assert_eq!(
+            validator_verifier.verify_aggregate_signature(&other_struct, &aggregate_signature),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // Claiming an extra signer fails.
+        let mut validator_bitmask = aggregate_signature.validator_bitmask().clone();
+        validator_bitmask.set(3);
+        let forged_signature =
+            AggregateSignature::new(validator_bitmask, aggregate_signature.signature().clone());
+        assert_eq!(
+            validator_verifier.verify_aggregate_signature(&dummy_struct, &forged_signature),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // Pointing past the validator set fails.
+        let mut validator_bitmask = aggregate_signature.validator_bitmask().clone();
+        validator_bitmask.set(NUM_SIGNERS);
+        let forged_signature =
+            AggregateSignature::new(validator_bitmask, aggregate_signature.signature().clone());
+        assert_eq!(
+            validator_verifier.verify_aggregate_signature(&dummy_struct, &forged_signature),
+            Err(VerifyError::InvalidBitVec)
+        );
+
+        // Two signers are not a quorum.
+        let signatures: BTreeMap<AccountAddress, BLS12381Signature> =
+            signatures.into_iter().take(2).collect();
+        let aggregate_signature = validator_verifier
+            .aggregate_signatures(&signatures)
+            .unwrap();
+        assert_eq!(
+            validator_verifier.verify_aggregate_signature(&dummy_struct, &aggregate_signature),
+            Err(VerifyError::TooLittleVotingPower {
+                voting_power: 2,
+                quorum_voting_power: 3
+            })
+        );
+
+        // Validators without BLS12-381 public keys can't be verified.
+        let author_to_validator_info = validator_signers
+            .iter()
+            .map(|signer| {
+                (
+                    signer.author(),
+                    ValidatorConsensusInfo::new(signer.public_key(), 1),
+                )
+            })
+            .collect();
+        let ed25519_only_verifier = ValidatorVerifier::new(author_to_validator_info);
+        let signatures: BTreeMap<AccountAddress, BLS12381Signature> = bls_private_keys
+            .iter()
+            .take(3)
+            .map(|(author, key)| (*author, key.sign(&dummy_struct)))
+            .collect();
+        let aggregate_signature = ed25519_only_verifier
+            .aggregate_signatures(&signatures)
+            .unwrap();
+        assert_eq!(
+            ed25519_only_verifier.verify_aggregate_signature(&dummy_struct, &aggregate_signature),
+            Err(VerifyError::UnknownAuthor)
+        );
+    }
 }